@@ -0,0 +1,4 @@
+//! The example contracts are compiled as libraries and exercised by the tests of this crate on
+//! the in-process mocked blockchain, so that changes to the SDK that break them are caught by
+//! `cargo test` in the workspace. End-to-end tests against a sandbox node still live next to each
+//! example and use `workspaces`.
@@ -0,0 +1,21 @@
+use adder::{Adder, DoublePair, Pair};
+
+#[test]
+fn add() {
+    let Pair(a, b) = Adder::default().add(Pair(1, 2), Pair(3, 4));
+    assert_eq!((a, b), (4, 6));
+}
+
+#[test]
+fn add_borsh() {
+    let Pair(a, b) = Adder::default().add_borsh(Pair(u32::MAX - 1, 0), Pair(1, 0));
+    assert_eq!((a, b), (u32::MAX, 0));
+}
+
+#[test]
+fn add_callback() {
+    let double = |x| DoublePair { first: Pair(x, x), second: Pair(x, 2 * x) };
+    let DoublePair { first, second } =
+        Adder::default().add_callback(double(1), double(2), vec![double(3), double(4)]);
+    assert_eq!((first.0, first.1, second.0, second.1), (10, 10, 10, 20));
+}
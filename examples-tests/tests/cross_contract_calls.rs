@@ -0,0 +1,34 @@
+use near_sdk::mock::VmAction;
+use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+use near_sdk::{testing_env, PromiseOrValue};
+
+fn scheduled_calls() -> Vec<String> {
+    get_created_receipts()
+        .into_iter()
+        .flat_map(|receipt| receipt.actions)
+        .filter_map(|action| match action {
+            VmAction::FunctionCall { function_name, .. } => Some(function_name),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn high_level_factorial() {
+    testing_env!(VMContextBuilder::new().current_account_id(accounts(0)).build());
+    let contract = cross_contract_high_level::CrossContract::default();
+
+    assert!(matches!(contract.factorial(1), PromiseOrValue::Value(1)));
+    assert!(matches!(contract.factorial(3), PromiseOrValue::Promise(_)));
+    assert_eq!(scheduled_calls(), vec!["factorial", "factorial_mult"]);
+    assert_eq!(contract.factorial_mult(3, 2), 6);
+}
+
+#[test]
+fn low_level_factorial() {
+    testing_env!(VMContextBuilder::new().current_account_id(accounts(0)).build());
+    let contract = cross_contract_low_level::CrossContract::default();
+
+    contract.factorial(3);
+    assert_eq!(scheduled_calls(), vec!["factorial", "factorial_mult"]);
+}
@@ -0,0 +1,33 @@
+use fungible_token::Contract;
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, AccountId, Balance};
+
+const TOTAL_SUPPLY: Balance = 1_000_000_000_000_000;
+
+fn context(predecessor_account_id: AccountId) -> VMContextBuilder {
+    let mut builder = VMContextBuilder::new();
+    builder.current_account_id(accounts(0)).predecessor_account_id(predecessor_account_id);
+    builder
+}
+
+#[test]
+fn register_and_transfer() {
+    let mut context = context(accounts(1));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into());
+
+    let min_balance = contract.storage_balance_bounds().min.0;
+    testing_env!(context.attached_deposit(min_balance).build());
+    contract.storage_deposit(Some(accounts(2)), None);
+    assert!(contract.storage_balance_of(accounts(2)).is_some());
+
+    testing_env!(context.attached_deposit(1).build());
+    contract.ft_transfer(accounts(2), 1_000.into(), None);
+
+    testing_env!(context.is_view(true).attached_deposit(0).build());
+    assert_eq!(contract.ft_balance_of(accounts(1)).0, TOTAL_SUPPLY - 1_000);
+    assert_eq!(contract.ft_balance_of(accounts(2)).0, 1_000);
+    assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+}
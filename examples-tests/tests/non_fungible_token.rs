@@ -0,0 +1,47 @@
+use near_contract_standards::non_fungible_token::core::NonFungibleTokenCore;
+use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{env, testing_env};
+use non_fungible_token::Contract;
+
+const MINT_STORAGE_COST: u128 = 5870000000000000000000;
+
+fn token_metadata() -> TokenMetadata {
+    TokenMetadata {
+        title: Some("Olympus Mons".into()),
+        description: None,
+        media: None,
+        media_hash: None,
+        copies: Some(1u64),
+        issued_at: None,
+        expires_at: None,
+        starts_at: None,
+        updated_at: None,
+        extra: None,
+        reference: None,
+        reference_hash: None,
+    }
+}
+
+#[test]
+fn mint_and_transfer() {
+    let mut context = VMContextBuilder::new();
+    context.current_account_id(accounts(0)).predecessor_account_id(accounts(0));
+    testing_env!(context.build());
+    let mut contract = Contract::new_default_meta(accounts(0));
+
+    testing_env!(context
+        .storage_usage(env::storage_usage())
+        .attached_deposit(MINT_STORAGE_COST)
+        .build());
+    let token = contract.nft_mint("0".to_string(), accounts(0), token_metadata());
+    assert_eq!(token.owner_id, accounts(0));
+
+    testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+    contract.nft_transfer(accounts(1), "0".to_string(), None, None);
+
+    testing_env!(context.is_view(true).attached_deposit(0).build());
+    let token = contract.nft_token("0".to_string()).unwrap();
+    assert_eq!(token.owner_id, accounts(1));
+    assert_eq!(token.metadata, Some(token_metadata()));
+}
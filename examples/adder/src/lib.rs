@@ -4,12 +4,12 @@ use near_sdk::near_bindgen;
 use near_sdk::serde::{Deserialize, Serialize};
 
 #[derive(JsonSchema, Serialize, Deserialize, BorshDeserialize, BorshSerialize, BorshSchema)]
-pub struct Pair(u32, u32);
+pub struct Pair(pub u32, pub u32);
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct DoublePair {
-    first: Pair,
-    second: Pair,
+    pub first: Pair,
+    pub second: Pair,
 }
 
 #[near_bindgen]
@@ -0,0 +1,100 @@
+use super::{refund, settle, AuctionAsset, Bid};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, require, AccountId, Balance, Promise, Timestamp};
+
+/// Descending-price auction where the price falls linearly from `start_price` at `start_time`
+/// to `floor_price` at `end_time`, and the first bidder to pay the current price wins.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DutchAuction {
+    pub seller_id: AccountId,
+    pub asset: AuctionAsset,
+    pub start_price: Balance,
+    pub floor_price: Balance,
+    /// Block timestamp in nanoseconds at which the price starts to fall.
+    pub start_time: Timestamp,
+    /// Block timestamp in nanoseconds at which the price reaches the floor.
+    pub end_time: Timestamp,
+    pub winning_bid: Option<Bid>,
+}
+
+impl DutchAuction {
+    pub fn new(
+        seller_id: AccountId,
+        asset: AuctionAsset,
+        start_price: Balance,
+        floor_price: Balance,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    ) -> Self {
+        require!(start_price >= floor_price, "The start price is lower than the floor price");
+        require!(start_time < end_time, "The auction ends before it starts");
+        Self { seller_id, asset, start_price, floor_price, start_time, end_time, winning_bid: None }
+    }
+
+    /// The price of the asset at the given block timestamp.
+    pub fn price_at(&self, timestamp: Timestamp) -> Balance {
+        if timestamp <= self.start_time {
+            return self.start_price;
+        }
+        if timestamp >= self.end_time {
+            return self.floor_price;
+        }
+        let elapsed = Balance::from(timestamp - self.start_time);
+        let duration = Balance::from(self.end_time - self.start_time);
+        let drop = self.start_price - self.floor_price;
+        // Split the multiplication so that it cannot overflow for any realistic price.
+        let decrease = drop / duration * elapsed + drop % duration * elapsed / duration;
+        self.start_price - decrease
+    }
+
+    /// The current price of the asset.
+    pub fn current_price(&self) -> Balance {
+        self.price_at(env::block_timestamp())
+    }
+
+    /// Buys the asset at the current price on behalf of the predecessor account, refunding any
+    /// deposit above the price, and settles the auction. The price is refunded if the asset
+    /// transfer fails, see [`AuctionResolver`](super::AuctionResolver).
+    pub fn buy(&mut self) -> Promise {
+        require!(self.winning_bid.is_none(), "The asset is already sold");
+        require!(env::block_timestamp() >= self.start_time, "The auction has not started yet");
+        let price = self.current_price();
+        let deposit = env::attached_deposit();
+        require!(deposit >= price, "The attached deposit is less than the current price");
+
+        let bidder_id = env::predecessor_account_id();
+        refund(bidder_id.clone(), deposit - price);
+        let bid = self.winning_bid.insert(Bid { bidder_id, amount: price.into() });
+        settle(&self.asset, &self.seller_id, Some(bid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auction::test_utils::{call, nft};
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn price_falls_linearly() {
+        call(accounts(0), 0, 0);
+        let auction = DutchAuction::new(accounts(0), nft(), 1_000, 200, 100, 500);
+        assert_eq!(auction.price_at(0), 1_000);
+        assert_eq!(auction.price_at(300), 600);
+        assert_eq!(auction.price_at(499), 202);
+        assert_eq!(auction.price_at(10_000), 200);
+
+        let auction = DutchAuction::new(accounts(0), nft(), u128::MAX, 0, 0, 3);
+        assert_eq!(auction.price_at(1), u128::MAX - u128::MAX / 3);
+    }
+
+    #[test]
+    fn first_buyer_wins() {
+        call(accounts(0), 0, 0);
+        let mut auction = DutchAuction::new(accounts(0), nft(), 1_000, 200, 100, 500);
+
+        call(accounts(1), 700, 300);
+        auction.buy();
+        assert_eq!(auction.winning_bid, Some(Bid { bidder_id: accounts(1), amount: 600.into() }));
+    }
+}
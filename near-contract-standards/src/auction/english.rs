@@ -0,0 +1,116 @@
+use super::{refund, settle, AuctionAsset, Bid};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, require, AccountId, Balance, Duration, Promise, Timestamp};
+
+/// Ascending-price auction where every bid must beat the current highest bid.
+///
+/// A bid placed within `extension` of the end pushes the end back so that it is at least
+/// `extension` away, which gives other bidders a chance to respond to last-second bids.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct EnglishAuction {
+    pub seller_id: AccountId,
+    pub asset: AuctionAsset,
+    /// The lowest bid that is accepted.
+    pub reserve_price: Balance,
+    /// The amount by which a bid must exceed the current highest bid.
+    pub min_increment: Balance,
+    /// Block timestamp in nanoseconds after which no more bids are accepted.
+    pub end_time: Timestamp,
+    /// Anti-sniping window in nanoseconds.
+    pub extension: Duration,
+    pub highest_bid: Option<Bid>,
+    pub settled: bool,
+}
+
+impl EnglishAuction {
+    pub fn new(
+        seller_id: AccountId,
+        asset: AuctionAsset,
+        reserve_price: Balance,
+        min_increment: Balance,
+        end_time: Timestamp,
+        extension: Duration,
+    ) -> Self {
+        Self {
+            seller_id,
+            asset,
+            reserve_price,
+            min_increment,
+            end_time,
+            extension,
+            highest_bid: None,
+            settled: false,
+        }
+    }
+
+    pub fn is_ended(&self) -> bool {
+        env::block_timestamp() >= self.end_time
+    }
+
+    /// The lowest amount the next bid has to attach.
+    pub fn min_bid(&self) -> Balance {
+        match &self.highest_bid {
+            Some(bid) => bid.amount.0.saturating_add(self.min_increment.max(1)),
+            None => self.reserve_price,
+        }
+    }
+
+    /// Places a bid of the attached deposit on behalf of the predecessor account and refunds the
+    /// previous highest bidder.
+    pub fn bid(&mut self) -> &Bid {
+        require!(!self.is_ended(), "The auction has ended");
+        let amount = env::attached_deposit();
+        require!(amount >= self.min_bid(), "The bid is lower than the minimum bid");
+
+        let bid = Bid { bidder_id: env::predecessor_account_id(), amount: amount.into() };
+        if let Some(previous) = self.highest_bid.replace(bid) {
+            refund(previous.bidder_id, previous.amount.0);
+        }
+
+        let now = env::block_timestamp();
+        if self.end_time - now < self.extension {
+            self.end_time = now + self.extension;
+        }
+        self.highest_bid.as_ref().unwrap()
+    }
+
+    /// Transfers the asset to the highest bidder and then the highest bid to the seller, or
+    /// returns the asset to the seller if nobody bid. The highest bidder is refunded instead if
+    /// the asset transfer fails, see [`AuctionResolver`](super::AuctionResolver).
+    pub fn settle(&mut self) -> Promise {
+        require!(self.is_ended(), "The auction has not ended yet");
+        require!(!self.settled, "The auction is already settled");
+        self.settled = true;
+        settle(&self.asset, &self.seller_id, self.highest_bid.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auction::test_utils::{call, nft};
+    use near_sdk::test_utils::{accounts, get_created_receipts};
+
+    #[test]
+    fn bids_extend_end_within_window() {
+        call(accounts(0), 0, 0);
+        let mut auction = EnglishAuction::new(accounts(0), nft(), 10, 5, 1_000, 100);
+
+        call(accounts(1), 10, 500);
+        auction.bid();
+        assert_eq!(auction.end_time, 1_000);
+        assert_eq!(auction.min_bid(), 15);
+
+        call(accounts(2), 15, 950);
+        auction.bid();
+        assert_eq!(auction.end_time, 1_050);
+        assert_eq!(auction.highest_bid, Some(Bid { bidder_id: accounts(2), amount: 15.into() }));
+        // The outbid bidder is refunded.
+        assert_eq!(get_created_receipts()[0].receiver_id, accounts(1));
+
+        call(accounts(3), 0, 1_050);
+        assert!(auction.is_ended());
+        auction.settle();
+        assert!(auction.settled);
+    }
+}
@@ -0,0 +1,214 @@
+//! Building blocks for contracts that sell an asset by auction.
+//!
+//! Each auction kind holds the bids in escrow on the contract account: outbid and losing bidders
+//! are refunded as soon as they can no longer win, and [`settle`](EnglishAuction::settle) hands
+//! the asset to the winner and the proceeds to the seller. The contract is expected to own the
+//! auctioned asset (e.g. through `nft_transfer_call` or `ft_transfer_call`) before the auction
+//! starts, and to expose the auction methods through `#[near_bindgen]` itself, together with
+//! [`AuctionResolver`] which pays the seller once the asset reached the winner.
+//!
+//! * [`EnglishAuction`] -- ascending open bids with an anti-sniping extension window.
+//! * [`DutchAuction`] -- descending price, the first bidder to accept the current price wins.
+//! * [`SealedBidAuction`] -- bids are committed as hashes and revealed after the bidding closes.
+mod dutch;
+mod english;
+mod sealed_bid;
+
+pub use self::dutch::DutchAuction;
+pub use self::english::EnglishAuction;
+pub use self::sealed_bid::{bid_commitment, SealedBidAuction};
+
+use crate::fungible_token::core::ext_ft_core;
+use crate::non_fungible_token::core::ext_nft_core;
+use crate::non_fungible_token::TokenId;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, is_promise_success, require, AccountId, Balance, Gas, Promise};
+
+const GAS_FOR_ASSET_TRANSFER: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_SETTLEMENT: Gas = Gas(5_000_000_000_000);
+
+/// The asset sold by an auction.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub enum AuctionAsset {
+    /// A NEP-171 token held by the contract.
+    NonFungibleToken { contract_id: AccountId, token_id: TokenId },
+    /// An amount of a NEP-141 token held by the contract.
+    FungibleToken { contract_id: AccountId, amount: U128 },
+}
+
+impl AuctionAsset {
+    /// Transfers the asset from the current contract to `receiver_id`.
+    pub fn transfer(&self, receiver_id: AccountId) -> Promise {
+        match self {
+            AuctionAsset::NonFungibleToken { contract_id, token_id } => {
+                ext_nft_core::ext(contract_id.clone())
+                    .with_attached_deposit(1)
+                    .with_static_gas(GAS_FOR_ASSET_TRANSFER)
+                    .nft_transfer(receiver_id, token_id.clone(), None, None)
+            }
+            AuctionAsset::FungibleToken { contract_id, amount } => {
+                ext_ft_core::ext(contract_id.clone())
+                    .with_attached_deposit(1)
+                    .with_static_gas(GAS_FOR_ASSET_TRANSFER)
+                    .ft_transfer(receiver_id, *amount, None)
+            }
+        }
+    }
+}
+
+/// A bid held in escrow by an auction.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub struct Bid {
+    pub bidder_id: AccountId,
+    pub amount: U128,
+}
+
+/// Callback of the asset transfer to the winner of an auction. This trait is implemented on the
+/// contract exposing the auction, with a `#[private]` method which calls [`resolve_settlement`].
+///
+/// # Examples
+///
+/// ```
+/// use near_contract_standards::auction::{resolve_settlement, AuctionResolver, Bid};
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::{near_bindgen, AccountId};
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {}
+///
+/// #[near_bindgen]
+/// impl AuctionResolver for Contract {
+///     #[private]
+///     fn auction_resolve_settlement(&mut self, seller_id: AccountId, bid: Bid) -> bool {
+///         resolve_settlement(seller_id, bid)
+///     }
+/// }
+/// ```
+#[ext_contract(ext_auction_resolver)]
+pub trait AuctionResolver {
+    /// Pays `bid` to `seller_id` if the asset was transferred to the bidder, and refunds the
+    /// bidder otherwise. Returns whether the asset was transferred.
+    fn auction_resolve_settlement(&mut self, seller_id: AccountId, bid: Bid) -> bool;
+}
+
+/// Resolves the asset transfer of a settlement, see [`AuctionResolver`]. If the transfer failed,
+/// for example because the contract does not own the asset, the asset stays with the contract
+/// and the bidder gets the bid back instead of the seller.
+///
+/// Panics if not called by the contract itself, or outside of a callback of 1 promise.
+pub fn resolve_settlement(seller_id: AccountId, bid: Bid) -> bool {
+    require!(
+        env::predecessor_account_id() == env::current_account_id(),
+        "Method auction_resolve_settlement is private"
+    );
+    let transferred = is_promise_success();
+    if transferred {
+        refund(seller_id, bid.amount.0);
+    } else {
+        refund(bid.bidder_id, bid.amount.0);
+    }
+    transferred
+}
+
+/// Hands the asset to the winner and, once it was transferred, the proceeds to the seller, or
+/// returns the asset to the seller if there was no winning bid.
+fn settle(asset: &AuctionAsset, seller_id: &AccountId, winning_bid: Option<&Bid>) -> Promise {
+    match winning_bid {
+        Some(bid) => asset.transfer(bid.bidder_id.clone()).then(
+            ext_auction_resolver::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_RESOLVE_SETTLEMENT)
+                .auction_resolve_settlement(seller_id.clone(), bid.clone()),
+        ),
+        None => asset.transfer(seller_id.clone()),
+    }
+}
+
+/// Pays out an escrowed deposit if it's not empty.
+fn refund(account_id: AccountId, amount: Balance) {
+    if amount > 0 {
+        Promise::new(account_id).transfer(amount);
+    }
+}
+
+#[cfg(test)]
+mod test_utils {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, AccountId, Balance, Timestamp};
+
+    use super::AuctionAsset;
+
+    pub fn nft() -> AuctionAsset {
+        AuctionAsset::NonFungibleToken { contract_id: accounts(5), token_id: "1".to_string() }
+    }
+
+    pub fn call(predecessor: AccountId, deposit: Balance, timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .attached_deposit(deposit)
+            .block_timestamp(timestamp)
+            .build());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::nft;
+    use super::*;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    fn resolve(result: PromiseResult) -> bool {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(accounts(0))
+                .predecessor_account_id(accounts(0))
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+        resolve_settlement(accounts(1), Bid { bidder_id: accounts(2), amount: 10.into() })
+    }
+
+    #[test]
+    fn settle_resolves_asset_transfer() {
+        testing_env!(VMContextBuilder::new().current_account_id(accounts(0)).build());
+        settle(&nft(), &accounts(1), Some(&Bid { bidder_id: accounts(2), amount: 10.into() }));
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id, accounts(5));
+        assert_eq!(receipts[1].receiver_id, accounts(0));
+        assert!(matches!(
+            &receipts[1].actions[0],
+            VmAction::FunctionCall { function_name, .. }
+                if function_name == "auction_resolve_settlement"
+        ));
+    }
+
+    #[test]
+    fn seller_is_paid_after_asset_transfer() {
+        assert!(resolve(PromiseResult::Successful(vec![])));
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, accounts(1));
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 10 }]);
+    }
+
+    #[test]
+    fn bidder_is_refunded_if_asset_transfer_fails() {
+        assert!(!resolve(PromiseResult::Failed));
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, accounts(2));
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 10 }]);
+    }
+}
@@ -0,0 +1,158 @@
+use super::{refund, settle, AuctionAsset, Bid};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::{env, require, AccountId, Balance, CryptoHash, IntoStorageKey, Promise, Timestamp};
+
+/// Computes the commitment a bidder submits to [`SealedBidAuction::commit`]: the SHA-256 hash of
+/// the Borsh serialization of `(bidder_id, amount, salt)`.
+///
+/// The salt should be a random secret of at least 32 bytes, otherwise the bid can be recovered
+/// from the commitment by trying every likely amount.
+pub fn bid_commitment(bidder_id: &AccountId, amount: Balance, salt: &[u8]) -> CryptoHash {
+    let preimage = (bidder_id, amount, salt)
+        .try_to_vec()
+        .unwrap_or_else(|_| env::panic_str("Failed to serialize the bid"));
+    env::sha256_array(&preimage)
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct Commitment {
+    hash: CryptoHash,
+    deposit: Balance,
+}
+
+/// First-price sealed-bid auction using a commit-reveal scheme.
+///
+/// Until `commit_end` bidders submit a [`bid_commitment`] together with a deposit of at least
+/// their bid; the deposit may be larger to hide the bid amount. Until `reveal_end` bidders reveal
+/// the amount and salt, and the highest revealed bid wins. Deposits of bidders who never reveal
+/// are forfeited to the seller.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SealedBidAuction {
+    pub seller_id: AccountId,
+    pub asset: AuctionAsset,
+    /// The lowest bid that can win.
+    pub reserve_price: Balance,
+    /// Block timestamp in nanoseconds at which bidding closes and revealing opens.
+    pub commit_end: Timestamp,
+    /// Block timestamp in nanoseconds at which revealing closes.
+    pub reveal_end: Timestamp,
+    pub highest_bid: Option<Bid>,
+    /// Sum of the deposits of bidders who have not revealed their bid yet.
+    pub unrevealed_deposits: Balance,
+    pub settled: bool,
+    commitments: LookupMap<AccountId, Commitment>,
+}
+
+impl SealedBidAuction {
+    pub fn new<S>(
+        prefix: S,
+        seller_id: AccountId,
+        asset: AuctionAsset,
+        reserve_price: Balance,
+        commit_end: Timestamp,
+        reveal_end: Timestamp,
+    ) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        require!(commit_end < reveal_end, "The reveal phase ends before the commit phase");
+        Self {
+            seller_id,
+            asset,
+            reserve_price,
+            commit_end,
+            reveal_end,
+            highest_bid: None,
+            unrevealed_deposits: 0,
+            settled: false,
+            commitments: LookupMap::new(prefix),
+        }
+    }
+
+    /// Submits the commitment of the predecessor account, escrowing the attached deposit.
+    pub fn commit(&mut self, commitment: CryptoHash) {
+        require!(env::block_timestamp() < self.commit_end, "The commit phase has ended");
+        let deposit = env::attached_deposit();
+        require!(deposit > 0, "Requires a deposit covering the bid");
+        let bidder_id = env::predecessor_account_id();
+        require!(!self.commitments.contains_key(&bidder_id), "The bid is already committed");
+        self.commitments.insert(&bidder_id, &Commitment { hash: commitment, deposit });
+        self.unrevealed_deposits += deposit;
+    }
+
+    /// Reveals the bid of the predecessor account. Returns `true` if it is the highest bid so
+    /// far, in which case the deposit above the bid is refunded, otherwise the whole deposit is
+    /// refunded.
+    pub fn reveal(&mut self, amount: Balance, salt: Vec<u8>) -> bool {
+        let now = env::block_timestamp();
+        require!(now >= self.commit_end, "The commit phase has not ended yet");
+        require!(now < self.reveal_end, "The reveal phase has ended");
+        let bidder_id = env::predecessor_account_id();
+        let commitment = self
+            .commitments
+            .remove(&bidder_id)
+            .unwrap_or_else(|| env::panic_str("No bid is committed"));
+        require!(
+            bid_commitment(&bidder_id, amount, &salt) == commitment.hash,
+            "The bid does not match the commitment"
+        );
+        self.unrevealed_deposits -= commitment.deposit;
+
+        let is_highest = amount <= commitment.deposit
+            && amount >= self.reserve_price
+            && !matches!(&self.highest_bid, Some(bid) if amount <= bid.amount.0);
+        if !is_highest {
+            refund(bidder_id, commitment.deposit);
+            return false;
+        }
+        refund(bidder_id.clone(), commitment.deposit - amount);
+        if let Some(previous) = self.highest_bid.replace(Bid { bidder_id, amount: amount.into() }) {
+            refund(previous.bidder_id, previous.amount.0);
+        }
+        true
+    }
+
+    /// Transfers the deposits that were never revealed to the seller, and the asset to the
+    /// highest bidder and then the highest bid to the seller. The highest bidder is refunded
+    /// instead if the asset transfer fails, see [`AuctionResolver`](super::AuctionResolver).
+    pub fn settle(&mut self) -> Promise {
+        require!(env::block_timestamp() >= self.reveal_end, "The reveal phase has not ended yet");
+        require!(!self.settled, "The auction is already settled");
+        self.settled = true;
+        refund(self.seller_id.clone(), std::mem::take(&mut self.unrevealed_deposits));
+        settle(&self.asset, &self.seller_id, self.highest_bid.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auction::test_utils::{call, nft};
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn highest_revealed_bid_wins() {
+        call(accounts(0), 0, 0);
+        let mut auction = SealedBidAuction::new(b"a".to_vec(), accounts(0), nft(), 10, 100, 200);
+
+        call(accounts(1), 50, 10);
+        auction.commit(bid_commitment(&accounts(1), 30, b"salt1"));
+        call(accounts(2), 50, 20);
+        auction.commit(bid_commitment(&accounts(2), 40, b"salt2"));
+        call(accounts(3), 70, 30);
+        auction.commit(bid_commitment(&accounts(3), 60, b"salt3"));
+        assert_eq!(auction.unrevealed_deposits, 170);
+
+        call(accounts(1), 0, 150);
+        assert!(auction.reveal(30, b"salt1".to_vec()));
+        call(accounts(2), 0, 160);
+        assert!(auction.reveal(40, b"salt2".to_vec()));
+        assert_eq!(auction.highest_bid, Some(Bid { bidder_id: accounts(2), amount: 40.into() }));
+        assert_eq!(auction.unrevealed_deposits, 70);
+
+        call(accounts(0), 0, 200);
+        auction.settle();
+        assert_eq!(auction.unrevealed_deposits, 0);
+    }
+}
@@ -0,0 +1,267 @@
+use super::{assert_panics, call, callback, events, returned, ConformanceReport};
+use crate::fungible_token::core::FungibleTokenCore;
+use crate::fungible_token::resolver::FungibleTokenResolver;
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::accounts;
+use near_sdk::{AccountId, Balance, PromiseOrValue, PromiseResult};
+
+const TOTAL_SUPPLY: Balance = 1_000_000;
+const AMOUNT: Balance = 1_000;
+
+/// A fungible token contract that can be checked with [`check_fungible_token`].
+pub trait FungibleTokenHarness: FungibleTokenCore + FungibleTokenResolver + Sized {
+    /// Initializes the contract with the whole `total_supply` owned by the registered
+    /// `owner_id`.
+    fn new(owner_id: AccountId, total_supply: U128) -> Self;
+
+    /// Registers the account, so that it can receive tokens.
+    fn register(&mut self, account_id: AccountId);
+}
+
+/// Runs the NEP-141 conformance tests against the contract.
+pub fn check_fungible_token<C: FungibleTokenHarness>() -> ConformanceReport {
+    ConformanceReport::run(&[
+        ("ft_total_supply", total_supply::<C>),
+        ("ft_transfer", transfer::<C>),
+        ("ft_transfer_requires_one_yocto", transfer_requires_one_yocto::<C>),
+        ("ft_transfer_invalid", transfer_invalid::<C>),
+        ("ft_transfer_call_keeps_used_amount", transfer_call_used::<C>),
+        ("ft_transfer_call_refunds_on_failure", transfer_call_failed::<C>),
+        ("ft_transfer_call_caps_unused_amount", transfer_call_unused_capped::<C>),
+        ("ft_transfer_call_invalid_result", transfer_call_invalid_result::<C>),
+        ("ft_transfer_call_receiver_spent", transfer_call_receiver_spent::<C>),
+    ])
+}
+
+/// Alice owns the total supply and Bob and Charlie are registered.
+fn setup<C: FungibleTokenHarness>() -> C {
+    call(&accounts(0), 0);
+    let mut contract = C::new(accounts(0), U128(TOTAL_SUPPLY));
+    contract.register(accounts(1));
+    contract.register(accounts(2));
+    contract
+}
+
+fn balance<C: FungibleTokenHarness>(contract: &C, account_id: AccountId) -> Balance {
+    contract.ft_balance_of(account_id).0
+}
+
+fn total_supply<C: FungibleTokenHarness>() {
+    let contract = setup::<C>();
+    assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+    assert_eq!(balance(&contract, accounts(0)), TOTAL_SUPPLY);
+    assert_eq!(balance(&contract, accounts(1)), 0);
+}
+
+fn transfer<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    call(&accounts(0), 1);
+    contract.ft_transfer(accounts(1), U128(AMOUNT), Some("memo".to_string()));
+    assert_eq!(balance(&contract, accounts(0)), TOTAL_SUPPLY - AMOUNT);
+    assert_eq!(balance(&contract, accounts(1)), AMOUNT);
+    assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+    assert_eq!(
+        events("nep141", "ft_transfer"),
+        [json!({
+            "old_owner_id": accounts(0),
+            "new_owner_id": accounts(1),
+            "amount": AMOUNT.to_string(),
+            "memo": "memo",
+        })]
+    );
+}
+
+fn transfer_requires_one_yocto<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    for deposit in [0, 2] {
+        call(&accounts(0), deposit);
+        assert_panics(&format!("ft_transfer with a deposit of {}", deposit), || {
+            contract.ft_transfer(accounts(1), U128(AMOUNT), None)
+        });
+        call(&accounts(0), deposit);
+        assert_panics(&format!("ft_transfer_call with a deposit of {}", deposit), || {
+            contract.ft_transfer_call(accounts(1), U128(AMOUNT), None, String::new());
+        });
+    }
+}
+
+fn transfer_invalid<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    call(&accounts(0), 1);
+    assert_panics("ft_transfer of zero tokens", || {
+        contract.ft_transfer(accounts(1), U128(0), None)
+    });
+    call(&accounts(0), 1);
+    assert_panics("ft_transfer to the sender", || {
+        contract.ft_transfer(accounts(0), U128(AMOUNT), None)
+    });
+    call(&accounts(1), 1);
+    assert_panics("ft_transfer of more than the balance", || {
+        contract.ft_transfer(accounts(0), U128(AMOUNT), None)
+    });
+    call(&accounts(0), 1);
+    assert_panics("ft_transfer to an unregistered account", || {
+        contract.ft_transfer(accounts(3), U128(AMOUNT), None)
+    });
+}
+
+/// Transfers `AMOUNT` from Alice to Bob with `ft_transfer_call`.
+fn transfer_call<C: FungibleTokenHarness>(contract: &mut C) {
+    let before = balance(contract, accounts(1));
+    call(&accounts(0), 1);
+    let result = contract.ft_transfer_call(accounts(1), U128(AMOUNT), None, "msg".to_string());
+    assert!(matches!(result, PromiseOrValue::Promise(_)), "ft_transfer_call must return a promise");
+    assert_eq!(balance(contract, accounts(1)), before + AMOUNT);
+}
+
+/// Resolves the transfer of `transfer_call` with the result of `ft_on_transfer`.
+fn resolve<C: FungibleTokenHarness>(contract: &mut C, result: PromiseResult) -> Balance {
+    callback(result);
+    contract.ft_resolve_transfer(accounts(0), accounts(1), U128(AMOUNT)).0
+}
+
+fn refund_events(amount: Balance) -> Vec<near_sdk::serde_json::Value> {
+    vec![json!({
+        "old_owner_id": accounts(1),
+        "new_owner_id": accounts(0),
+        "amount": amount.to_string(),
+        "memo": "refund",
+    })]
+}
+
+fn transfer_call_used<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    assert_eq!(resolve(&mut contract, returned("\"400\"")), AMOUNT - 400);
+    assert_eq!(balance(&contract, accounts(0)), TOTAL_SUPPLY - AMOUNT + 400);
+    assert_eq!(balance(&contract, accounts(1)), AMOUNT - 400);
+    assert_eq!(events("nep141", "ft_transfer"), refund_events(400));
+
+    transfer_call(&mut contract);
+    assert_eq!(resolve(&mut contract, returned("\"0\"")), AMOUNT);
+    assert!(events("nep141", "ft_transfer").is_empty());
+}
+
+fn transfer_call_failed<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    assert_eq!(resolve(&mut contract, PromiseResult::Failed), 0);
+    assert_eq!(balance(&contract, accounts(0)), TOTAL_SUPPLY);
+    assert_eq!(balance(&contract, accounts(1)), 0);
+    assert_eq!(events("nep141", "ft_transfer"), refund_events(AMOUNT));
+}
+
+fn transfer_call_unused_capped<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    // The receiver cannot return more than it was sent.
+    assert_eq!(resolve(&mut contract, returned(&format!("\"{}\"", 10 * AMOUNT))), 0);
+    assert_eq!(balance(&contract, accounts(0)), TOTAL_SUPPLY);
+    assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+}
+
+fn transfer_call_invalid_result<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    assert_eq!(resolve(&mut contract, returned("not json")), 0);
+    assert_eq!(balance(&contract, accounts(0)), TOTAL_SUPPLY);
+}
+
+fn transfer_call_receiver_spent<C: FungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    // Bob spends most of the tokens before the transfer is resolved.
+    call(&accounts(1), 1);
+    contract.ft_transfer(accounts(2), U128(AMOUNT - 100), None);
+
+    assert_eq!(resolve(&mut contract, returned(&format!("\"{}\"", AMOUNT))), AMOUNT - 100);
+    assert_eq!(balance(&contract, accounts(0)), TOTAL_SUPPLY - AMOUNT + 100);
+    assert_eq!(balance(&contract, accounts(1)), 0);
+    assert_eq!(balance(&contract, accounts(2)), AMOUNT - 100);
+    assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fungible_token::FungibleToken;
+
+    impl FungibleTokenHarness for FungibleToken {
+        fn new(owner_id: AccountId, total_supply: U128) -> Self {
+            let mut token = FungibleToken::new(b"t".to_vec());
+            token.internal_register_account(&owner_id);
+            token.internal_deposit(&owner_id, total_supply.0);
+            token
+        }
+
+        fn register(&mut self, account_id: AccountId) {
+            self.internal_register_account(&account_id);
+        }
+    }
+
+    /// Keeps the whole amount of `ft_transfer_call` even if the receiver returns it.
+    struct NoRefund(FungibleToken);
+
+    impl FungibleTokenCore for NoRefund {
+        fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+            self.0.ft_transfer(receiver_id, amount, memo)
+        }
+
+        fn ft_transfer_call(
+            &mut self,
+            receiver_id: AccountId,
+            amount: U128,
+            memo: Option<String>,
+            msg: String,
+        ) -> PromiseOrValue<U128> {
+            self.0.ft_transfer_call(receiver_id, amount, memo, msg)
+        }
+
+        fn ft_total_supply(&self) -> U128 {
+            self.0.ft_total_supply()
+        }
+
+        fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+            self.0.ft_balance_of(account_id)
+        }
+    }
+
+    impl FungibleTokenResolver for NoRefund {
+        fn ft_resolve_transfer(&mut self, _: AccountId, _: AccountId, amount: U128) -> U128 {
+            amount
+        }
+    }
+
+    impl FungibleTokenHarness for NoRefund {
+        fn new(owner_id: AccountId, total_supply: U128) -> Self {
+            NoRefund(<FungibleToken as FungibleTokenHarness>::new(owner_id, total_supply))
+        }
+
+        fn register(&mut self, account_id: AccountId) {
+            self.0.register(account_id)
+        }
+    }
+
+    #[test]
+    fn fungible_token_conforms() {
+        check_fungible_token::<FungibleToken>().assert_success();
+    }
+
+    #[test]
+    fn missing_refunds_are_reported() {
+        let report = check_fungible_token::<NoRefund>();
+        let failed: Vec<_> = report.failed.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            failed,
+            [
+                "ft_transfer_call_keeps_used_amount",
+                "ft_transfer_call_refunds_on_failure",
+                "ft_transfer_call_caps_unused_amount",
+                "ft_transfer_call_invalid_result",
+                "ft_transfer_call_receiver_spent",
+            ]
+        );
+        assert_eq!(report.passed.len(), 4);
+    }
+}
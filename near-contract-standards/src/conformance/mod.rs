@@ -0,0 +1,151 @@
+//! Behavioral tests derived from the NEP specifications, which can be run against any contract
+//! implementing the standards in this crate to check that customizations have not broken them.
+//!
+//! The tests run in the mocked blockchain of `near-sdk` unit tests. Cross-contract calls are
+//! simulated by calling the resolver of the contract with the promise result the receiver would
+//! have produced, so no receiver contract is needed.
+//!
+//! ```ignore
+//! use near_contract_standards::conformance::{self, FungibleTokenHarness};
+//!
+//! impl FungibleTokenHarness for Contract {
+//!     fn new(owner_id: AccountId, total_supply: U128) -> Self {
+//!         Contract::new_default_meta(owner_id, total_supply)
+//!     }
+//!
+//!     fn register(&mut self, account_id: AccountId) {
+//!         self.token.internal_register_account(&account_id);
+//!     }
+//! }
+//!
+//! #[test]
+//! fn nep141_conformance() {
+//!     conformance::check_fungible_token::<Contract>().assert_success();
+//! }
+//! ```
+
+mod fungible_token;
+mod non_fungible_token;
+
+pub use fungible_token::{check_fungible_token, FungibleTokenHarness};
+pub use non_fungible_token::{check_non_fungible_token, NonFungibleTokenHarness};
+
+use near_sdk::serde_json::Value;
+use near_sdk::test_utils::{get_logs, VMContextBuilder};
+use near_sdk::{env, testing_env, AccountId, Balance, Gas, MockedBlockchain, PromiseResult};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Outcome of running a conformance suite.
+#[derive(Debug, Default)]
+pub struct ConformanceReport {
+    /// Names of the tests that passed.
+    pub passed: Vec<&'static str>,
+    /// Names of the tests that failed, with the reason of the failure.
+    pub failed: Vec<(&'static str, String)>,
+}
+
+impl ConformanceReport {
+    /// Returns `true` if all tests passed.
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Panics with the names and reasons of the tests that failed, if any.
+    pub fn assert_success(&self) {
+        if !self.is_success() {
+            let failures: Vec<String> = self
+                .failed
+                .iter()
+                .map(|(name, reason)| format!("  {}: {}", name, reason))
+                .collect();
+            panic!(
+                "{} of {} conformance tests failed:\n{}",
+                self.failed.len(),
+                self.failed.len() + self.passed.len(),
+                failures.join("\n")
+            );
+        }
+    }
+
+    fn run(cases: &[(&'static str, fn())]) -> Self {
+        let mut report = Self::default();
+        for (name, case) in cases {
+            env::set_blockchain_interface(MockedBlockchain::default());
+            match catch_panic(*case) {
+                None => report.passed.push(name),
+                Some(reason) => report.failed.push((name, reason)),
+            }
+        }
+        report
+    }
+}
+
+/// Account of the contract under test.
+pub(crate) fn contract_id() -> AccountId {
+    "contract.near".parse().unwrap()
+}
+
+/// Sets up the context of a call by `predecessor` to the contract, keeping its storage.
+pub(crate) fn call(predecessor: &AccountId, attached_deposit: Balance) {
+    testing_env!(VMContextBuilder::new()
+        .current_account_id(contract_id())
+        .predecessor_account_id(predecessor.clone())
+        .signer_account_id(predecessor.clone())
+        .attached_deposit(attached_deposit)
+        .prepaid_gas(Gas(300 * 10u64.pow(12)))
+        .build());
+}
+
+/// Sets up the context of a callback of the contract with the result of the receiver call.
+pub(crate) fn callback(result: PromiseResult) {
+    testing_env!(
+        VMContextBuilder::new()
+            .current_account_id(contract_id())
+            .predecessor_account_id(contract_id())
+            .prepaid_gas(Gas(300 * 10u64.pow(12)))
+            .build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![result],
+    );
+}
+
+/// Returns the JSON result of a receiver call.
+pub(crate) fn returned(value: &str) -> PromiseResult {
+    PromiseResult::Successful(value.as_bytes().to_vec())
+}
+
+/// Returns the data of the events of the given standard and kind logged in the current call.
+pub(crate) fn events(standard: &str, event: &str) -> Vec<Value> {
+    get_logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .filter_map(|json| near_sdk::serde_json::from_str::<Value>(json).ok())
+        .filter(|value| value["standard"] == standard && value["event"] == event)
+        .flat_map(|value| value["data"].as_array().cloned().unwrap_or_default())
+        .collect()
+}
+
+/// Checks that `f` panics, which the specification requires for invalid calls.
+pub(crate) fn assert_panics(what: &str, f: impl FnOnce()) {
+    if catch_panic(f).is_none() {
+        panic!("{} did not panic", what);
+    }
+}
+
+/// Runs `f` without printing panics, returning the panic message if it panicked.
+fn catch_panic(f: impl FnOnce()) -> Option<String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    panic::set_hook(hook);
+    let payload = result.err()?;
+    Some(
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|message| message.to_string()))
+            .unwrap_or_else(|| "panicked".to_string()),
+    )
+}
@@ -0,0 +1,184 @@
+use super::{assert_panics, call, callback, events, returned, ConformanceReport};
+use crate::non_fungible_token::core::{NonFungibleTokenCore, NonFungibleTokenResolver};
+use crate::non_fungible_token::TokenId;
+use near_sdk::serde_json::{json, Value};
+use near_sdk::test_utils::accounts;
+use near_sdk::{AccountId, Balance, PromiseOrValue, PromiseResult};
+
+/// Deposit attached to mint calls, to cover the storage of the token.
+const MINT_DEPOSIT: Balance = 10u128.pow(24);
+
+/// A non-fungible token contract that can be checked with [`check_non_fungible_token`].
+pub trait NonFungibleTokenHarness: NonFungibleTokenCore + NonFungibleTokenResolver + Sized {
+    /// Initializes the contract owned by `owner_id`.
+    fn new(owner_id: AccountId) -> Self;
+
+    /// Mints the token to `receiver_id`. Called by the owner of the contract with a deposit of
+    /// 1 NEAR attached to cover the storage of the token.
+    fn mint(&mut self, token_id: TokenId, receiver_id: AccountId);
+}
+
+/// Runs the NEP-171 conformance tests against the contract.
+pub fn check_non_fungible_token<C: NonFungibleTokenHarness>() -> ConformanceReport {
+    ConformanceReport::run(&[
+        ("nft_token", token::<C>),
+        ("nft_transfer", transfer::<C>),
+        ("nft_transfer_requires_one_yocto", transfer_requires_one_yocto::<C>),
+        ("nft_transfer_invalid", transfer_invalid::<C>),
+        ("nft_transfer_call_kept", transfer_call_kept::<C>),
+        ("nft_transfer_call_returned", transfer_call_returned::<C>),
+        ("nft_transfer_call_reverts_on_failure", transfer_call_failed::<C>),
+        ("nft_transfer_call_invalid_result", transfer_call_invalid_result::<C>),
+        ("nft_transfer_call_receiver_transferred", transfer_call_receiver_transferred::<C>),
+    ])
+}
+
+/// Alice owns the contract and the token `"1"`.
+fn setup<C: NonFungibleTokenHarness>() -> C {
+    call(&accounts(0), MINT_DEPOSIT);
+    let mut contract = C::new(accounts(0));
+    contract.mint("1".to_string(), accounts(0));
+    contract
+}
+
+fn owner<C: NonFungibleTokenHarness>(contract: &C) -> AccountId {
+    contract.nft_token("1".to_string()).expect("token 1 must exist").owner_id
+}
+
+fn transfer_event(old_owner_id: AccountId, new_owner_id: AccountId) -> Value {
+    json!({ "old_owner_id": old_owner_id, "new_owner_id": new_owner_id, "token_ids": ["1"] })
+}
+
+fn token<C: NonFungibleTokenHarness>() {
+    let contract = setup::<C>();
+    let token = contract.nft_token("1".to_string()).expect("token 1 must exist");
+    assert_eq!(token.token_id, "1");
+    assert_eq!(token.owner_id, accounts(0));
+    assert!(contract.nft_token("2".to_string()).is_none());
+}
+
+fn transfer<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    call(&accounts(0), 1);
+    contract.nft_transfer(accounts(1), "1".to_string(), None, Some("memo".to_string()));
+    assert_eq!(owner(&contract), accounts(1));
+    let mut expected = transfer_event(accounts(0), accounts(1));
+    expected["memo"] = json!("memo");
+    assert_eq!(events("nep171", "nft_transfer"), [expected]);
+}
+
+fn transfer_requires_one_yocto<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    for deposit in [0, 2] {
+        call(&accounts(0), deposit);
+        assert_panics(&format!("nft_transfer with a deposit of {}", deposit), || {
+            contract.nft_transfer(accounts(1), "1".to_string(), None, None)
+        });
+        call(&accounts(0), deposit);
+        assert_panics(&format!("nft_transfer_call with a deposit of {}", deposit), || {
+            contract.nft_transfer_call(accounts(1), "1".to_string(), None, None, String::new());
+        });
+    }
+}
+
+fn transfer_invalid<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    call(&accounts(1), 1);
+    assert_panics("nft_transfer by an account that does not own the token", || {
+        contract.nft_transfer(accounts(1), "1".to_string(), None, None)
+    });
+    call(&accounts(0), 1);
+    assert_panics("nft_transfer to the owner", || {
+        contract.nft_transfer(accounts(0), "1".to_string(), None, None)
+    });
+    call(&accounts(0), 1);
+    assert_panics("nft_transfer of a token that does not exist", || {
+        contract.nft_transfer(accounts(1), "2".to_string(), None, None)
+    });
+}
+
+/// Transfers the token from Alice to Bob with `nft_transfer_call`.
+fn transfer_call<C: NonFungibleTokenHarness>(contract: &mut C) {
+    call(&accounts(0), 1);
+    let result =
+        contract.nft_transfer_call(accounts(1), "1".to_string(), None, None, "msg".to_string());
+    assert!(
+        matches!(result, PromiseOrValue::Promise(_)),
+        "nft_transfer_call must return a promise"
+    );
+    assert_eq!(owner(contract), accounts(1));
+}
+
+/// Resolves the transfer of `transfer_call` with the result of `nft_on_transfer`.
+fn resolve<C: NonFungibleTokenHarness>(contract: &mut C, result: PromiseResult) -> bool {
+    callback(result);
+    contract.nft_resolve_transfer(accounts(0), accounts(1), "1".to_string(), None)
+}
+
+fn transfer_call_kept<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    assert!(resolve(&mut contract, returned("false")));
+    assert_eq!(owner(&contract), accounts(1));
+    assert!(events("nep171", "nft_transfer").is_empty());
+}
+
+fn transfer_call_returned<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    assert!(!resolve(&mut contract, returned("true")));
+    assert_eq!(owner(&contract), accounts(0));
+    assert_eq!(events("nep171", "nft_transfer"), [transfer_event(accounts(1), accounts(0))]);
+}
+
+fn transfer_call_failed<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    assert!(!resolve(&mut contract, PromiseResult::Failed));
+    assert_eq!(owner(&contract), accounts(0));
+}
+
+fn transfer_call_invalid_result<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    assert!(!resolve(&mut contract, returned("not json")));
+    assert_eq!(owner(&contract), accounts(0));
+}
+
+fn transfer_call_receiver_transferred<C: NonFungibleTokenHarness>() {
+    let mut contract = setup::<C>();
+    transfer_call(&mut contract);
+    // Bob transfers the token away before the transfer is resolved, so it cannot be returned.
+    call(&accounts(1), 1);
+    contract.nft_transfer(accounts(2), "1".to_string(), None, None);
+
+    assert!(resolve(&mut contract, returned("true")));
+    assert_eq!(owner(&contract), accounts(2));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::non_fungible_token::NonFungibleToken;
+
+    impl NonFungibleTokenHarness for NonFungibleToken {
+        fn new(owner_id: AccountId) -> Self {
+            NonFungibleToken::new(
+                b"o".to_vec(),
+                owner_id,
+                None::<Vec<u8>>,
+                Some(b"e".to_vec()),
+                None::<Vec<u8>>,
+            )
+        }
+
+        fn mint(&mut self, token_id: TokenId, receiver_id: AccountId) {
+            self.internal_mint(token_id, receiver_id, None);
+        }
+    }
+
+    #[test]
+    fn non_fungible_token_conforms() {
+        check_non_fungible_token::<NonFungibleToken>().assert_success();
+    }
+}
@@ -107,3 +107,73 @@ macro_rules! impl_fungible_token_storage {
         }
     };
 }
+
+/// Same as [`impl_fungible_token_storage`], but additionally lets accounts pay for the
+/// registration of others through `storage_deposit_many` and tracks who paid for whom, so that
+/// the storage deposit is returned to the sponsor once a sponsored account unregisters.
+/// Takes name of the Contract struct, the inner field for the token, the inner
+/// [`StorageSponsors`](crate::storage_management::StorageSponsors) field and optional method name
+/// to call when the account was closed.
+#[macro_export]
+macro_rules! impl_fungible_token_storage_sponsorship {
+    ($contract: ident, $token: ident, $sponsors: ident $(, $on_account_closed_fn:ident)?) => {
+        use $crate::storage_management::{
+            StorageManagement, StorageBalance, StorageBalanceBounds, StorageSponsorship,
+            Sponsorship,
+        };
+
+        #[near_bindgen]
+        impl StorageManagement for $contract {
+            #[payable]
+            fn storage_deposit(
+                &mut self,
+                account_id: Option<AccountId>,
+                registration_only: Option<bool>,
+            ) -> StorageBalance {
+                self.$token.internal_storage_deposit_sponsored(
+                    &mut self.$sponsors,
+                    account_id,
+                    registration_only,
+                )
+            }
+
+            #[payable]
+            fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+                self.$token.storage_withdraw(amount)
+            }
+
+            #[payable]
+            fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+                #[allow(unused_variables)]
+                if let Some((account_id, balance)) =
+                    self.$token.internal_storage_unregister_sponsored(&mut self.$sponsors, force)
+                {
+                    $(self.$on_account_closed_fn(account_id, balance);)?
+                    true
+                } else {
+                    false
+                }
+            }
+
+            fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+                self.$token.storage_balance_bounds()
+            }
+
+            fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+                self.$token.storage_balance_of(account_id)
+            }
+        }
+
+        #[near_bindgen]
+        impl StorageSponsorship for $contract {
+            #[payable]
+            fn storage_deposit_many(&mut self, account_ids: Vec<AccountId>) -> Vec<StorageBalance> {
+                self.$token.internal_storage_deposit_many(&mut self.$sponsors, account_ids)
+            }
+
+            fn storage_sponsor_of(&self, account_id: AccountId) -> Option<Sponsorship> {
+                self.$sponsors.get(&account_id)
+            }
+        }
+    };
+}
@@ -1,5 +1,7 @@
 use crate::fungible_token::FungibleToken;
-use crate::storage_management::{StorageBalance, StorageBalanceBounds, StorageManagement};
+use crate::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement, StorageSponsors,
+};
 use near_sdk::json_types::U128;
 use near_sdk::{assert_one_yocto, env, log, AccountId, Balance, Promise};
 
@@ -10,6 +12,81 @@ impl FungibleToken {
         &mut self,
         force: Option<bool>,
     ) -> Option<(AccountId, Balance)> {
+        let (account_id, balance) = self.internal_storage_close(force)?;
+        Promise::new(account_id.clone()).transfer(self.storage_balance_bounds().min.0 + 1);
+        Some((account_id, balance))
+    }
+
+    /// Same as [`internal_storage_unregister`](Self::internal_storage_unregister), but returns
+    /// the storage deposit to the sponsor if the account was registered by another account.
+    pub fn internal_storage_unregister_sponsored(
+        &mut self,
+        sponsors: &mut StorageSponsors,
+        force: Option<bool>,
+    ) -> Option<(AccountId, Balance)> {
+        let (account_id, balance) = self.internal_storage_close(force)?;
+        let refund_to = match sponsors.remove(&account_id) {
+            Some(sponsorship) => sponsorship.sponsor_id,
+            None => account_id.clone(),
+        };
+        Promise::new(refund_to).transfer(self.storage_balance_bounds().min.0 + 1);
+        Some((account_id, balance))
+    }
+
+    /// Same as [`storage_deposit`](StorageManagement::storage_deposit), but records the
+    /// predecessor as the sponsor when it registers an account other than itself.
+    pub fn internal_storage_deposit_sponsored(
+        &mut self,
+        sponsors: &mut StorageSponsors,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let sponsor_id = env::predecessor_account_id();
+        let account_id = account_id.unwrap_or_else(|| sponsor_id.clone());
+        let newly_registered = !self.accounts.contains_key(&account_id);
+        let storage_balance = self.storage_deposit(Some(account_id.clone()), registration_only);
+        if newly_registered && account_id != sponsor_id {
+            sponsors.insert(&account_id, sponsor_id, storage_balance.total.0);
+        }
+        storage_balance
+    }
+
+    /// Registers every account in `account_ids` on behalf of the predecessor account. See
+    /// [`storage_deposit_many`](crate::storage_management::StorageSponsorship::storage_deposit_many) for the details.
+    pub fn internal_storage_deposit_many(
+        &mut self,
+        sponsors: &mut StorageSponsors,
+        account_ids: Vec<AccountId>,
+    ) -> Vec<StorageBalance> {
+        let sponsor_id = env::predecessor_account_id();
+        let min_balance = self.storage_balance_bounds().min.0;
+        let mut remaining: Balance = env::attached_deposit();
+        let storage_balances = account_ids
+            .iter()
+            .map(|account_id| {
+                if !self.accounts.contains_key(account_id) {
+                    remaining = remaining.checked_sub(min_balance).unwrap_or_else(|| {
+                        env::panic_str(
+                            "The attached deposit is less than the minimum storage balance for all accounts",
+                        )
+                    });
+                    self.internal_register_account(account_id);
+                    if account_id != &sponsor_id {
+                        sponsors.insert(account_id, sponsor_id.clone(), min_balance);
+                    }
+                }
+                self.internal_storage_balance_of(account_id).unwrap()
+            })
+            .collect();
+        if remaining > 0 {
+            Promise::new(sponsor_id).transfer(remaining);
+        }
+        storage_balances
+    }
+
+    /// Removes the predecessor account from the token and returns it with the balance that was
+    /// burned. The caller is responsible for returning the storage deposit.
+    fn internal_storage_close(&mut self, force: Option<bool>) -> Option<(AccountId, Balance)> {
         assert_one_yocto();
         let account_id = env::predecessor_account_id();
         let force = force.unwrap_or(false);
@@ -17,7 +94,6 @@ impl FungibleToken {
             if balance == 0 || force {
                 self.accounts.remove(&account_id);
                 self.total_supply -= balance;
-                Promise::new(account_id.clone()).transfer(self.storage_balance_bounds().min.0 + 1);
                 Some((account_id, balance))
             } else {
                 env::panic_str(
@@ -109,3 +185,75 @@ impl StorageManagement for FungibleToken {
         self.internal_storage_balance_of(&account_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage_management::Sponsorship;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn setup() -> (FungibleToken, StorageSponsors, Balance) {
+        testing_env!(VMContextBuilder::new().build());
+        let token = FungibleToken::new(b"t".to_vec());
+        let min_balance = token.storage_balance_bounds().min.0;
+        (token, StorageSponsors::new(b"s".to_vec()), min_balance)
+    }
+
+    fn transfers() -> Vec<(AccountId, Balance)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver_id = receipt.receiver_id;
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    VmAction::Transfer { deposit } => Some((receiver_id.clone(), deposit)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deposit_many_records_sponsor_and_refunds_excess() {
+        let (mut token, mut sponsors, min_balance) = setup();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(3 * min_balance + 5)
+            .build());
+        let balances = token.internal_storage_deposit_many(
+            &mut sponsors,
+            vec![accounts(0), accounts(1), accounts(2), accounts(1)],
+        );
+
+        assert_eq!(balances.len(), 4);
+        assert!(token.accounts.contains_key(&accounts(2)));
+        assert_eq!(sponsors.get(&accounts(0)), None);
+        assert_eq!(
+            sponsors.get(&accounts(1)),
+            Some(Sponsorship { sponsor_id: accounts(0), amount: min_balance.into() })
+        );
+        assert_eq!(transfers(), vec![(accounts(0), 5)]);
+    }
+
+    #[test]
+    fn unregister_returns_deposit_to_sponsor() {
+        let (mut token, mut sponsors, min_balance) = setup();
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(min_balance)
+            .build());
+        token.internal_storage_deposit_sponsored(&mut sponsors, Some(accounts(1)), None);
+
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        assert_eq!(
+            token.internal_storage_unregister_sponsored(&mut sponsors, None),
+            Some((accounts(1), 0))
+        );
+        assert_eq!(sponsors.get(&accounts(1)), None);
+        assert_eq!(transfers(), vec![(accounts(0), min_balance + 1)]);
+    }
+}
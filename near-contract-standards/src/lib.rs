@@ -1,7 +1,14 @@
+/// Composable auctions (English, Dutch and sealed-bid) with bid escrow and settlement through NFT and FT transfers.
+pub mod auction;
+/// Conformance tests derived from the NEP specifications, to check customized implementations of the standards.
+#[cfg(feature = "conformance")]
+pub mod conformance;
 /// Fungible tokens as described in [by the spec](https://nomicon.io/Standards/FungibleToken/README.html).
 pub mod fungible_token;
 /// Non-fungible tokens as described in [by the spec](https://nomicon.io/Standards/NonFungibleToken/README.html).
 pub mod non_fungible_token;
+/// Commit-reveal randomness across blocks, as a safer alternative to using the `random_seed` of a single block.
+pub mod randomness;
 /// Storage management deals with handling [state storage](https://docs.near.org/docs/concepts/storage-staking) on NEAR. This follows the [storage management standard](https://nomicon.io/Standards/StorageManagement.html).
 pub mod storage_management;
 /// This upgrade standard is a use case where a staging area exists for a WASM
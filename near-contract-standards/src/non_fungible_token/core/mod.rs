@@ -9,6 +9,7 @@ pub use self::receiver::NonFungibleTokenReceiver;
 pub use self::resolver::NonFungibleTokenResolver;
 
 use crate::non_fungible_token::token::{Token, TokenId};
+use near_sdk::ext_contract;
 use near_sdk::AccountId;
 use near_sdk::PromiseOrValue;
 
@@ -19,6 +20,7 @@ use near_sdk::PromiseOrValue;
 /// understand how the cross-contract call work.
 ///
 /// [core non-fungible token standard]: <https://nomicon.io/Standards/NonFungibleToken/Core.html>
+#[ext_contract(ext_nft_core)]
 pub trait NonFungibleTokenCore {
     /// Simple transfer. Transfer a given `token_id` from current owner to
     /// `receiver_id`.
@@ -0,0 +1,228 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::{
+    env, require, AccountId, Balance, BlockHeight, CryptoHash, IntoStorageKey, Promise,
+};
+
+/// Computes the commitment an account submits to [`CommitReveal::commit`]: the SHA-256 hash of
+/// the Borsh serialization of `(account_id, value, salt)`.
+///
+/// The salt should be a random secret of at least 32 bytes, otherwise the value can be
+/// recovered from the commitment by trying every likely value.
+pub fn randomness_commitment(account_id: &AccountId, value: &[u8], salt: &[u8]) -> CryptoHash {
+    let preimage = (account_id, value, salt)
+        .try_to_vec()
+        .unwrap_or_else(|_| env::panic_str("Failed to serialize the commitment"));
+    env::sha256_array(&preimage)
+}
+
+/// A commitment that has not been revealed yet.
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, PartialEq, Eq)]
+pub struct Commitment {
+    pub hash: CryptoHash,
+    /// Height of the block in which the commitment was made.
+    pub block_height: BlockHeight,
+    /// Deposit attached to the commitment, which can be slashed if it is never revealed.
+    pub stake: Balance,
+}
+
+/// Hooks called by [`CommitReveal`] when a commitment is resolved.
+pub trait CommitRevealHooks {
+    /// Called after a commitment is revealed. Returns the stake to the account by default.
+    fn on_reveal(&mut self, account_id: &AccountId, commitment: &Commitment) {
+        if commitment.stake > 0 {
+            Promise::new(account_id.clone()).transfer(commitment.stake);
+        }
+    }
+
+    /// Called after a commitment expired without being revealed. Keeps the stake on the
+    /// contract by default, which is how the account is slashed.
+    fn on_expire(&mut self, _account_id: &AccountId, _commitment: &Commitment) {}
+}
+
+/// Default hooks, which refund the stake of revealed commitments and keep the stake of expired
+/// ones.
+impl CommitRevealHooks for () {}
+
+/// Randomness generated with a commit-reveal scheme across blocks.
+///
+/// The `random_seed` of a single block is known to the block producer before the block is
+/// produced, so it can be used to bias the outcome of a game. Instead, an account first commits
+/// to a secret value, and at least `reveal_delay` blocks later reveals it. The value is then
+/// combined with the `random_seed` of the revealing block, which was unknown at the time of the
+/// commitment, while the revealed value was fixed before that block was produced.
+///
+/// Commitments that are not revealed within `reveal_window` blocks expire, which can be used to
+/// slash accounts that withhold an unfavourable outcome.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CommitReveal {
+    /// Number of blocks after the commitment before it can be revealed.
+    pub reveal_delay: BlockHeight,
+    /// Number of blocks during which a commitment can be revealed.
+    pub reveal_window: BlockHeight,
+    /// The smallest deposit that has to be attached to a commitment.
+    pub min_stake: Balance,
+    commitments: LookupMap<AccountId, Commitment>,
+}
+
+impl CommitReveal {
+    pub fn new<S>(
+        prefix: S,
+        reveal_delay: BlockHeight,
+        reveal_window: BlockHeight,
+        min_stake: Balance,
+    ) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        require!(reveal_delay > 0, "Commitments must be revealed in a later block");
+        require!(reveal_window > 0, "The reveal window must not be empty");
+        Self { reveal_delay, reveal_window, min_stake, commitments: LookupMap::new(prefix) }
+    }
+
+    /// Returns the pending commitment of the account, if any.
+    pub fn commitment_of(&self, account_id: &AccountId) -> Option<Commitment> {
+        self.commitments.get(account_id)
+    }
+
+    /// Returns the height of the first block in which the commitment can no longer be revealed.
+    pub fn expires_at(&self, commitment: &Commitment) -> BlockHeight {
+        commitment.block_height + self.reveal_delay + self.reveal_window
+    }
+
+    /// Submits the commitment of the predecessor account, staking the attached deposit.
+    pub fn commit(&mut self, hash: CryptoHash) {
+        let stake = env::attached_deposit();
+        require!(stake >= self.min_stake, "The attached deposit is less than the minimum stake");
+        let account_id = env::predecessor_account_id();
+        require!(!self.commitments.contains_key(&account_id), "A commitment is already pending");
+        self.commitments
+            .insert(&account_id, &Commitment { hash, block_height: env::block_height(), stake });
+    }
+
+    /// Reveals the commitment of the predecessor account and returns the random value derived
+    /// from it and the `random_seed` of the current block.
+    pub fn reveal<H: CommitRevealHooks>(
+        &mut self,
+        hooks: &mut H,
+        value: &[u8],
+        salt: &[u8],
+    ) -> CryptoHash {
+        let account_id = env::predecessor_account_id();
+        let commitment = self
+            .commitments
+            .get(&account_id)
+            .unwrap_or_else(|| env::panic_str("No commitment is pending"));
+        let height = env::block_height();
+        require!(
+            height >= commitment.block_height + self.reveal_delay,
+            "The commitment cannot be revealed yet"
+        );
+        require!(height < self.expires_at(&commitment), "The commitment has expired");
+        require!(
+            randomness_commitment(&account_id, value, salt) == commitment.hash,
+            "The value does not match the commitment"
+        );
+        self.commitments.remove(&account_id);
+        hooks.on_reveal(&account_id, &commitment);
+
+        let preimage = (value, salt, env::random_seed_array())
+            .try_to_vec()
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize the randomness"));
+        env::sha256_array(&preimage)
+    }
+
+    /// Removes the expired commitment of the account. Can be called by anyone, so that accounts
+    /// cannot avoid being slashed.
+    pub fn expire<H: CommitRevealHooks>(&mut self, hooks: &mut H, account_id: &AccountId) {
+        let commitment = self
+            .commitments
+            .get(account_id)
+            .unwrap_or_else(|| env::panic_str("No commitment is pending"));
+        require!(
+            env::block_height() >= self.expires_at(&commitment),
+            "The commitment has not expired yet"
+        );
+        self.commitments.remove(account_id);
+        hooks.on_expire(account_id, &commitment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const STAKE: Balance = 1_000;
+
+    fn call(predecessor: AccountId, deposit: Balance, height: BlockHeight, seed: u8) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .attached_deposit(deposit)
+            .block_index(height)
+            .random_seed([seed; 32])
+            .build());
+    }
+
+    fn committed(value: &[u8], salt: &[u8]) -> CommitReveal {
+        let mut randomness = CommitReveal::new(b"r", 2, 10, STAKE);
+        call(accounts(0), STAKE, 100, 0);
+        randomness.commit(randomness_commitment(&accounts(0), value, salt));
+        randomness
+    }
+
+    #[test]
+    fn reveal_combines_value_with_seed() {
+        let mut randomness = committed(b"value", b"salt");
+        call(accounts(0), 0, 102, 1);
+        let first = randomness.reveal(&mut (), b"value", b"salt");
+        assert!(randomness.commitment_of(&accounts(0)).is_none());
+        assert_eq!(get_created_receipts().len(), 1);
+
+        let mut randomness = committed(b"value", b"salt");
+        call(accounts(0), 0, 102, 2);
+        assert_ne!(randomness.reveal(&mut (), b"value", b"salt"), first);
+    }
+
+    #[test]
+    fn expire_calls_slashing_hook() {
+        struct Treasury(Balance);
+        impl CommitRevealHooks for Treasury {
+            fn on_expire(&mut self, _account_id: &AccountId, commitment: &Commitment) {
+                self.0 += commitment.stake;
+            }
+        }
+
+        let mut randomness = committed(b"value", b"salt");
+        let mut treasury = Treasury(0);
+        call(accounts(1), 0, 112, 0);
+        randomness.expire(&mut treasury, &accounts(0));
+        assert_eq!(treasury.0, STAKE);
+        assert!(randomness.commitment_of(&accounts(0)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "The commitment cannot be revealed yet")]
+    fn reveal_in_same_block() {
+        let mut randomness = committed(b"value", b"salt");
+        call(accounts(0), 0, 101, 0);
+        randomness.reveal(&mut (), b"value", b"salt");
+    }
+
+    #[test]
+    #[should_panic(expected = "The value does not match the commitment")]
+    fn reveal_wrong_value() {
+        let mut randomness = committed(b"value", b"salt");
+        call(accounts(0), 0, 102, 0);
+        randomness.reveal(&mut (), b"other", b"salt");
+    }
+
+    #[test]
+    #[should_panic(expected = "The commitment has not expired yet")]
+    fn expire_too_early() {
+        let mut randomness = committed(b"value", b"salt");
+        call(accounts(1), 0, 111, 0);
+        randomness.expire(&mut (), &accounts(0));
+    }
+}
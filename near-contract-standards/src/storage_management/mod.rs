@@ -1,9 +1,17 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::AccountId;
+use near_sdk::{ext_contract, AccountId};
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+mod resolvers;
+mod sponsorship;
+pub use resolvers::{
+    resolve_storage_deposit, resolve_storage_withdraw, storage_balance_result,
+    StorageDepositResult, StorageWithdrawResult,
+};
+pub use sponsorship::{Sponsorship, StorageSponsors, StorageSponsorship};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 #[serde(crate = "near_sdk::serde")]
 #[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
 pub struct StorageBalance {
@@ -19,6 +27,7 @@ pub struct StorageBalanceBounds {
     pub max: Option<U128>,
 }
 
+#[ext_contract(ext_storage_management)]
 pub trait StorageManagement {
     // if `registration_only=true` MUST refund above the minimum balance if the account didn't exist and
     //     refund full deposit if the account exists.
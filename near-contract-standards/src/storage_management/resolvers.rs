@@ -0,0 +1,149 @@
+use super::StorageBalance;
+use near_sdk::{env, Balance, PromiseResult};
+
+/// Outcome of a `storage_deposit` call on another contract, as seen from its callback.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageDepositResult {
+    /// Storage balance of the account after the call, or `None` if the call failed.
+    pub balance: Option<StorageBalance>,
+    /// Part of the attached deposit that was kept by the other contract.
+    pub used: Balance,
+    /// Part of the attached deposit that was refunded to the current contract, either by the
+    /// other contract or, if the call failed, by the runtime.
+    pub refund: Balance,
+}
+
+/// Outcome of a `storage_withdraw` call on another contract, as seen from its callback.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageWithdrawResult {
+    /// Storage balance of the account after the call, or `None` if the call failed.
+    pub balance: Option<StorageBalance>,
+    /// Amount transferred to the current contract.
+    pub withdrawn: Balance,
+}
+
+/// Parses the result of the promise at `result_idx` of a `storage_deposit`, `storage_withdraw`
+/// or `storage_balance_of` call.
+///
+/// Returns `None` if the call failed or the account is not registered. Panics if the call
+/// succeeded but did not return a storage balance, since the other contract does not implement
+/// the storage management standard.
+pub fn storage_balance_result(result_idx: u64) -> Option<StorageBalance> {
+    match env::promise_result(result_idx) {
+        PromiseResult::NotReady => env::abort(),
+        PromiseResult::Successful(value) => near_sdk::serde_json::from_slice(&value)
+            .unwrap_or_else(|_| env::panic_str("Promise result is not a storage balance")),
+        PromiseResult::Failed => None,
+    }
+}
+
+/// Reconciles the `attached` deposit of the `storage_deposit` call at `result_idx` with the
+/// storage balance it returned. `previous` is the balance of the account before the call, or
+/// `None` if it was not registered.
+///
+/// The used amount is the growth of the total balance, capped by the attached deposit, so that
+/// concurrent deposits from other accounts are never credited to the caller.
+pub fn resolve_storage_deposit(
+    result_idx: u64,
+    attached: Balance,
+    previous: Option<&StorageBalance>,
+) -> StorageDepositResult {
+    let balance = storage_balance_result(result_idx);
+    let used = balance.as_ref().map_or(0, |balance| {
+        let previous_total = previous.map_or(0, |previous| previous.total.0);
+        std::cmp::min(balance.total.0.saturating_sub(previous_total), attached)
+    });
+    StorageDepositResult { balance, used, refund: attached - used }
+}
+
+/// Reconciles the `storage_withdraw` call at `result_idx` with the storage balance it returned.
+/// `previous` is the balance of the account before the call.
+///
+/// The withdrawn amount is the decrease of the available balance. Unlike the requested amount,
+/// this also covers calls which withdraw the full available balance.
+pub fn resolve_storage_withdraw(
+    result_idx: u64,
+    previous: &StorageBalance,
+) -> StorageWithdrawResult {
+    let balance = storage_balance_result(result_idx);
+    let withdrawn = balance
+        .as_ref()
+        .map_or(0, |balance| previous.available.0.saturating_sub(balance.available.0));
+    StorageWithdrawResult { balance, withdrawn }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::json_types::U128;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    const ONE_NEAR: Balance = 10u128.pow(24);
+
+    fn balance(total: Balance, available: Balance) -> StorageBalance {
+        StorageBalance { total: U128(total), available: U128(available) }
+    }
+
+    fn set_promise_result(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new().build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    fn successful(balance: Option<StorageBalance>) -> PromiseResult {
+        PromiseResult::Successful(near_sdk::serde_json::to_vec(&balance).unwrap())
+    }
+
+    #[test]
+    fn storage_deposit_refunds() {
+        // New registration that refunds everything above the minimum balance.
+        set_promise_result(successful(Some(balance(ONE_NEAR / 100, 0))));
+        let result = resolve_storage_deposit(0, ONE_NEAR, None);
+        assert_eq!(result.used, ONE_NEAR / 100);
+        assert_eq!(result.refund, ONE_NEAR - ONE_NEAR / 100);
+        assert_eq!(result.balance, Some(balance(ONE_NEAR / 100, 0)));
+
+        // Already registered with `registration_only`, which refunds the full deposit.
+        let previous = balance(ONE_NEAR / 100, 0);
+        set_promise_result(successful(Some(previous.clone())));
+        let result = resolve_storage_deposit(0, ONE_NEAR, Some(&previous));
+        assert_eq!((result.used, result.refund), (0, ONE_NEAR));
+
+        // A concurrent deposit is not counted as used.
+        set_promise_result(successful(Some(balance(3 * ONE_NEAR, 2 * ONE_NEAR))));
+        let result = resolve_storage_deposit(0, ONE_NEAR, None);
+        assert_eq!((result.used, result.refund), (ONE_NEAR, 0));
+
+        set_promise_result(PromiseResult::Failed);
+        let result = resolve_storage_deposit(0, ONE_NEAR, Some(&previous));
+        assert_eq!(result, StorageDepositResult { balance: None, used: 0, refund: ONE_NEAR });
+    }
+
+    #[test]
+    fn storage_withdraw_amounts() {
+        let previous = balance(2 * ONE_NEAR, ONE_NEAR);
+        set_promise_result(successful(Some(balance(ONE_NEAR, 0))));
+        let result = resolve_storage_withdraw(0, &previous);
+        assert_eq!(result.withdrawn, ONE_NEAR);
+        assert_eq!(result.balance, Some(balance(ONE_NEAR, 0)));
+
+        set_promise_result(PromiseResult::Failed);
+        assert_eq!(
+            resolve_storage_withdraw(0, &previous),
+            StorageWithdrawResult { balance: None, withdrawn: 0 }
+        );
+    }
+
+    #[test]
+    fn storage_balance_of_result() {
+        set_promise_result(successful(None));
+        assert_eq!(storage_balance_result(0), None);
+        set_promise_result(successful(Some(balance(ONE_NEAR, 0))));
+        assert_eq!(storage_balance_result(0), Some(balance(ONE_NEAR, 0)));
+    }
+}
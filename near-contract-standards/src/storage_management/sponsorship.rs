@@ -0,0 +1,70 @@
+use super::StorageBalance;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LookupMap;
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, Balance, IntoStorageKey};
+
+/// Record of an account whose storage deposit was paid by another account.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub struct Sponsorship {
+    /// The account that paid for the registration.
+    pub sponsor_id: AccountId,
+    /// The amount of Ⓝ the sponsor paid for the registration.
+    pub amount: U128,
+}
+
+/// Tracks who paid for whom when accounts are registered on behalf of others, so that the storage
+/// deposit can be returned to the sponsor once the account unregisters.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StorageSponsors {
+    /// Sponsored AccountID -> Sponsorship.
+    sponsorships: LookupMap<AccountId, Sponsorship>,
+}
+
+impl StorageSponsors {
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { sponsorships: LookupMap::new(prefix) }
+    }
+
+    /// Records that `sponsor_id` paid `amount` for the registration of `account_id`, replacing
+    /// any previous sponsorship for that account.
+    pub fn insert(&mut self, account_id: &AccountId, sponsor_id: AccountId, amount: Balance) {
+        self.sponsorships.insert(account_id, &Sponsorship { sponsor_id, amount: amount.into() });
+    }
+
+    /// Returns the sponsorship of the given account, if it was registered by another account.
+    pub fn get(&self, account_id: &AccountId) -> Option<Sponsorship> {
+        self.sponsorships.get(account_id)
+    }
+
+    /// Removes and returns the sponsorship of the given account.
+    pub fn remove(&mut self, account_id: &AccountId) -> Option<Sponsorship> {
+        self.sponsorships.remove(account_id)
+    }
+}
+
+/// Extension of [`StorageManagement`](super::StorageManagement) for contracts that let one
+/// account pay for the registration of others.
+pub trait StorageSponsorship {
+    /// Registers every account in `account_ids` on behalf of the predecessor account.
+    ///
+    /// The attached deposit MUST cover the minimum storage balance for every account that is not
+    /// registered yet, otherwise the contract MUST panic. Accounts that are already registered
+    /// are skipped, and any deposit left over is refunded to the predecessor.
+    ///
+    /// The predecessor is recorded as the sponsor of each newly registered account, so that the
+    /// storage deposit is returned to the sponsor when that account unregisters.
+    ///
+    /// Returns the storage balance of each account, in the order of `account_ids`.
+    fn storage_deposit_many(&mut self, account_ids: Vec<AccountId>) -> Vec<StorageBalance>;
+
+    /// Returns the sponsorship of the given account, or `None` if the account registered itself
+    /// or is not registered.
+    fn storage_sponsor_of(&self, account_id: AccountId) -> Option<Sponsorship>;
+}
@@ -16,16 +16,28 @@ pub fn generate(i: &ItemImplInfo) -> TokenStream2 {
         return TokenStream2::new();
     }
 
-    let functions: Vec<TokenStream2> = public_functions.iter().map(|m| m.abi_struct()).collect();
+    let functions: Vec<TokenStream2> = public_functions
+        .iter()
+        .map(|m| {
+            let removal_cfg = m.attr_signature_info.deprecation.as_ref().map(|d| d.removal_cfg());
+            let function = m.abi_struct();
+            quote! {
+                #removal_cfg
+                functions.push(#function);
+            }
+        })
+        .collect();
     let first_function_name = &public_functions[0].attr_signature_info.ident;
     let near_abi_symbol = format_ident!("__near_abi_{}", first_function_name);
     quote! {
         #[cfg(not(target_arch = "wasm32"))]
         const _: () = {
             #[no_mangle]
+            #[allow(clippy::vec_init_then_push)]
             pub fn #near_abi_symbol() -> near_sdk::__private::ChunkedAbiEntry {
                 let mut gen = near_sdk::__private::schemars::gen::SchemaGenerator::default();
-                let functions = vec![#(#functions),*];
+                let mut functions = Vec::new();
+                #(#functions)*
                 near_sdk::__private::ChunkedAbiEntry::new(
                     functions,
                     gen.into_root_schema_for::<String>()
@@ -77,7 +89,16 @@ impl ImplItemMethodInfo {
     /// If args are serialized with Borsh it will not include `#[derive(borsh::BorshSchema)]`.
     pub fn abi_struct(&self) -> TokenStream2 {
         let function_name_str = self.attr_signature_info.ident.to_string();
-        let function_doc = match parse_rustdoc(&self.attr_signature_info.non_bindgen_attrs) {
+        let mut function_doc = parse_rustdoc(&self.attr_signature_info.non_bindgen_attrs);
+        if let Some(deprecation) = &self.attr_signature_info.deprecation {
+            // The ABI has no dedicated field, so the notice leads the documentation.
+            let notice = format!(" {}", deprecation.notice());
+            function_doc = Some(match function_doc {
+                Some(doc) => format!("{}\n\n{}", notice, doc),
+                None => notice,
+            });
+        }
+        let function_doc = match function_doc {
             Some(doc) => quote! { Some(#doc.to_string()) },
             None => quote! { None },
         };
@@ -109,7 +130,9 @@ impl ImplItemMethodInfo {
                     callbacks.push(generate_abi_type(typ, &arg.serializer_ty));
                 }
                 BindgenArgType::CallbackResultArg => {
-                    let typ = if let Some(ok_type) = utils::extract_ok_type(typ) {
+                    let typ = if arg.on_error.is_some() {
+                        typ
+                    } else if let Some(ok_type) = utils::extract_ok_type(typ) {
                         ok_type
                     } else {
                         return syn::Error::new_spanned(
@@ -171,8 +194,10 @@ impl ImplItemMethodInfo {
                         )
                         .into_compile_error();
                     };
-                    let abi_type =
-                        generate_abi_type(ty, &self.attr_signature_info.result_serializer);
+                    let abi_type = generate_abi_type(
+                        &utils::owned_type(ty),
+                        &self.attr_signature_info.result_serializer,
+                    );
                     quote! { Some(#abi_type) }
                 }
                 ReturnType::Type(_, ty) if is_handles_result => {
@@ -183,13 +208,27 @@ impl ImplItemMethodInfo {
                     .to_compile_error();
                 }
                 ReturnType::Type(_, ty) => {
-                    let abi_type =
-                        generate_abi_type(ty, &self.attr_signature_info.result_serializer);
+                    let abi_type = generate_abi_type(
+                        &utils::owned_type(ty),
+                        &self.attr_signature_info.result_serializer,
+                    );
                     quote! { Some(#abi_type) }
                 }
             },
         };
 
+        // The errors of `#[handle_result]` methods are listed in the documentation, as the ABI
+        // has no dedicated field either.
+        let function_doc = match &self.attr_signature_info.returns {
+            ReturnType::Type(_, ty) if is_handles_result => match utils::extract_err_type(ty) {
+                Some(err_ty) => quote! {
+                    <#err_ty as near_sdk::FunctionError>::__abi_errors(&mut gen).document(#function_doc)
+                },
+                None => function_doc,
+            },
+            _ => function_doc,
+        };
+
         quote! {
              near_sdk::__private::AbiFunction {
                  name: #function_name_str.to_string(),
@@ -0,0 +1,367 @@
+use crate::core_impl::utils;
+use quote::{format_ident, quote, ToTokens};
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{
+    parse_quote, Error, Expr, ExprAwait, FnArg, ImplItem, ImplItemMethod, ItemImpl, Local, Pat,
+    PatIdent, PatType, Stmt, Token, Type, Visibility,
+};
+
+/// Desugars the public `async fn` methods of the impl section into promise chains. The part of the
+/// body before each `.await` on a promise becomes a method that ends by calling a private
+/// callback, given the locals used after the `.await` as arguments and the result of the promise
+/// as a callback argument, which runs the rest of the body.
+pub(crate) fn expand_async_methods(input: &mut ItemImpl) -> syn::Result<()> {
+    let mut callbacks = vec![];
+    for item in &mut input.items {
+        match item {
+            ImplItem::Method(method)
+                if method.sig.asyncness.is_some()
+                    && matches!(method.vis, Visibility::Public(_)) =>
+            {
+                callbacks.extend(expand_async_method(method)?);
+            }
+            _ => {}
+        }
+    }
+    input.items.extend(callbacks.into_iter().map(ImplItem::Method));
+    Ok(())
+}
+
+/// Local variable of the method, of which the type is only known if it was written.
+struct Variable {
+    ident: syn::Ident,
+    mutability: Option<Token![mut]>,
+    ty: Option<Type>,
+}
+
+/// Statement awaiting a promise, as in `let balance: U128 = promise.await;` or `promise.await;`.
+struct Await {
+    promise: Expr,
+    /// Variable the result of the promise is bound to, if any.
+    result: Option<Variable>,
+}
+
+fn expand_async_method(method: &mut ImplItemMethod) -> syn::Result<Vec<ImplItemMethod>> {
+    if let Some(attr) = method.attrs.iter().find(|attr| attr.path.is_ident("init")) {
+        return Err(Error::new_spanned(attr, "Init methods can not be async"));
+    }
+    method.sig.asyncness = None;
+
+    // The body is split in segments, ended by the statements awaiting promises.
+    let mut segments = vec![vec![]];
+    let mut awaits = vec![];
+    for stmt in std::mem::take(&mut method.block.stmts) {
+        match as_await(&stmt)? {
+            Some(awaited) => {
+                awaits.push(awaited);
+                segments.push(vec![]);
+            }
+            None => segments.last_mut().unwrap().push(stmt),
+        }
+    }
+    let last = segments.len() - 1;
+    for (i, segment) in segments.iter().enumerate() {
+        let mut check = AwaitCheck { error: None, allow_return: i == last };
+        segment.iter().for_each(|stmt| check.visit_stmt(stmt));
+        if let Some(awaited) = awaits.get(i) {
+            check.visit_expr(&awaited.promise);
+        }
+        if let Some(error) = check.error {
+            return Err(error);
+        }
+    }
+    if awaits.is_empty() {
+        method.block.stmts = segments.remove(0);
+        return Ok(vec![]);
+    }
+
+    let receiver = method.sig.receiver().cloned();
+    let mut variables: Vec<Variable> = method
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(PatType { pat, ty, .. }) => match &**pat {
+                Pat::Ident(PatIdent { ident, mutability, .. }) => Some(Variable {
+                    ident: ident.clone(),
+                    mutability: *mutability,
+                    ty: Some((**ty).clone()),
+                }),
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    // The attributes of the result belong to the callback returning it.
+    let (result_attrs, attrs) = std::mem::take(&mut method.attrs).into_iter().partition(|attr| {
+        attr.path.is_ident("result_serializer") || attr.path.is_ident("handle_result")
+    });
+    method.attrs = attrs;
+    let output = std::mem::replace(&mut method.sig.output, parse_quote!(-> near_sdk::Promise));
+
+    // Identifiers used after each `.await`, in the following segments and awaited promises.
+    let mut used_after = vec![HashSet::new(); awaits.len()];
+    let mut used = HashSet::new();
+    for i in (0..awaits.len()).rev() {
+        let rest = segments[i + 1].iter().map(ToTokens::to_token_stream);
+        utils::collect_idents(rest.collect(), &mut used);
+        if let Some(next) = awaits.get(i + 1) {
+            utils::collect_idents(next.promise.to_token_stream(), &mut used);
+        }
+        used_after[i] = used.clone();
+    }
+
+    let name = method.sig.ident.clone();
+    let mut callbacks = vec![];
+    let mut segments = segments.into_iter();
+    let mut body = segments.next().unwrap();
+    let mut signature = None;
+    for (i, ((segment, awaited), used)) in (1usize..).zip(segments.zip(awaits).zip(used_after)) {
+        variables.extend(declared_variables(&body));
+        // The locals used in the rest of the body are passed on to the callback.
+        let mut captured: Vec<Variable> = vec![];
+        for variable in variables.into_iter().rev() {
+            if used.contains(&variable.ident.to_string())
+                && !captured.iter().any(|v| v.ident == variable.ident)
+            {
+                if variable.ty.is_none() {
+                    return Err(Error::new(
+                        variable.ident.span(),
+                        "Variables used after an `.await` must have a type, as they are passed to \
+                        the callback",
+                    ));
+                }
+                captured.push(variable);
+            }
+        }
+        captured.reverse();
+        let callback = format_ident!("__{}_resume_{}", name, i);
+        let args = captured.iter().map(|v| &v.ident);
+        let Await { promise, result } = awaited;
+        body.push(Stmt::Expr(parse_quote! {
+            (#promise).then(Self::ext(near_sdk::env::current_account_id()).#callback(#(#args),*))
+        }));
+        match signature.take() {
+            None => method.block.stmts = body,
+            Some(signature) => callbacks.push(parse_quote! {
+                #signature -> near_sdk::Promise {
+                    #(#body)*
+                }
+            }),
+        }
+
+        let params = captured.iter().map(|Variable { ident, mutability, ty }| {
+            quote! { #mutability #ident: #ty }
+        });
+        let result_param = result.as_ref().map(|Variable { ident, mutability, ty }| {
+            let ty = ty.as_ref().unwrap();
+            let kind = if utils::type_is_result(ty) {
+                quote! { #[callback_result] }
+            } else {
+                quote! { #[callback_unwrap] }
+            };
+            quote! { #kind #mutability #ident: #ty }
+        });
+        let receiver = receiver.iter();
+        signature = Some(quote! {
+            #[doc(hidden)]
+            #[private]
+            pub fn #callback(#(#receiver,)* #(#params,)* #result_param)
+        });
+        variables = captured;
+        variables.extend(result);
+        body = segment;
+    }
+    let signature = signature.unwrap();
+    let callback: ImplItemMethod = parse_quote! {
+        #(#result_attrs)*
+        #signature #output {
+            #(#body)*
+        }
+    };
+    callbacks.push(callback);
+    Ok(callbacks)
+}
+
+/// Returns the promise awaited by the statement, if it is `let x: T = promise.await;` or
+/// `promise.await;`.
+fn as_await(stmt: &Stmt) -> syn::Result<Option<Await>> {
+    match stmt {
+        Stmt::Semi(Expr::Await(ExprAwait { base, .. }), _) => {
+            Ok(Some(Await { promise: (**base).clone(), result: None }))
+        }
+        Stmt::Local(Local { pat, init: Some((_, init)), .. })
+            if matches!(&**init, Expr::Await(_)) =>
+        {
+            let promise = match &**init {
+                Expr::Await(ExprAwait { base, .. }) => (**base).clone(),
+                _ => unreachable!(),
+            };
+            let result = match pat {
+                Pat::Type(PatType { pat, ty, .. }) => match &**pat {
+                    Pat::Ident(PatIdent {
+                        ident, mutability, by_ref: None, subpat: None, ..
+                    }) => Some(Variable {
+                        ident: ident.clone(),
+                        mutability: *mutability,
+                        ty: Some((**ty).clone()),
+                    }),
+                    Pat::Wild(_) => None,
+                    _ => return Err(result_error(pat)),
+                },
+                _ => return Err(result_error(pat)),
+            };
+            Ok(Some(Await { promise, result }))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn result_error(pat: &Pat) -> Error {
+    Error::new(
+        pat.span(),
+        "The result of `.await` must be bound to a variable with a type, as in \
+        `let balance: U128 = promise.await;`",
+    )
+}
+
+/// Variables declared by the `let` statements, of which the type is known if it was written.
+fn declared_variables(stmts: &[Stmt]) -> Vec<Variable> {
+    let mut variables = vec![];
+    for stmt in stmts {
+        if let Stmt::Local(Local { pat, .. }) = stmt {
+            let (pat, ty) = match pat {
+                Pat::Type(PatType { pat, ty, .. }) => (&**pat, Some((**ty).clone())),
+                pat => (pat, None),
+            };
+            match pat {
+                Pat::Ident(PatIdent { ident, mutability, subpat: None, .. }) => {
+                    variables.push(Variable { ident: ident.clone(), mutability: *mutability, ty })
+                }
+                pat => {
+                    let mut bindings = PatBindings(vec![]);
+                    bindings.visit_pat(pat);
+                    variables.extend(bindings.0.into_iter().map(|(ident, mutability)| Variable {
+                        ident,
+                        mutability,
+                        ty: None,
+                    }));
+                }
+            }
+        }
+    }
+    variables
+}
+
+/// Variables bound by a pattern.
+struct PatBindings(Vec<(syn::Ident, Option<Token![mut]>)>);
+
+impl<'ast> Visit<'ast> for PatBindings {
+    fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+        self.0.push((pat.ident.clone(), pat.mutability));
+        visit::visit_pat_ident(self, pat);
+    }
+}
+
+/// Finds the `.await`s that are not statements of the method body, and the `return`s before the
+/// last `.await`, which would return from the method with the wrong type.
+struct AwaitCheck {
+    error: Option<Error>,
+    allow_return: bool,
+}
+
+impl<'ast> Visit<'ast> for AwaitCheck {
+    fn visit_expr_await(&mut self, expr: &'ast ExprAwait) {
+        self.error.get_or_insert_with(|| {
+            Error::new(
+                expr.span(),
+                "`.await` is only supported as a statement of the method body, as in \
+                `let balance: U128 = promise.await;`",
+            )
+        });
+    }
+
+    fn visit_expr_return(&mut self, expr: &'ast syn::ExprReturn) {
+        if !self.allow_return {
+            self.error.get_or_insert_with(|| {
+                Error::new(expr.span(), "`return` is not supported before an `.await`")
+            });
+        }
+        visit::visit_expr_return(self, expr);
+    }
+
+    // Closures and nested items return on their own.
+    fn visit_expr_closure(&mut self, _: &'ast syn::ExprClosure) {}
+
+    fn visit_item(&mut self, _: &'ast syn::Item) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand_async_methods;
+    use quote::{quote, ToTokens};
+    use syn::{parse_quote, ItemImpl};
+
+    #[test]
+    fn promise_chain() {
+        let mut item: ItemImpl = parse_quote! {
+            impl Exchange {
+                #[payable]
+                #[result_serializer(borsh)]
+                pub async fn swap(&mut self, receiver_id: AccountId, amount: U128) -> u128 {
+                    let fee: u128 = self.fee;
+                    let available: U128 = ext_token::ext(self.token.clone()).balance().await;
+                    ext_token::ext(self.token.clone()).transfer(receiver_id, amount).await;
+                    available.0 - fee
+                }
+            }
+        };
+        expand_async_methods(&mut item).unwrap();
+        let expected = quote! {
+            impl Exchange {
+                #[payable]
+                pub fn swap(&mut self, receiver_id: AccountId, amount: U128) -> near_sdk::Promise {
+                    let fee: u128 = self.fee;
+                    (ext_token::ext(self.token.clone()).balance()).then(
+                        Self::ext(near_sdk::env::current_account_id())
+                            .__swap_resume_1(receiver_id, amount, fee)
+                    )
+                }
+                #[doc(hidden)]
+                #[private]
+                pub fn __swap_resume_1(
+                    &mut self,
+                    receiver_id: AccountId,
+                    amount: U128,
+                    fee: u128,
+                    #[callback_unwrap] available: U128
+                ) -> near_sdk::Promise {
+                    (ext_token::ext(self.token.clone()).transfer(receiver_id, amount)).then(
+                        Self::ext(near_sdk::env::current_account_id())
+                            .__swap_resume_2(fee, available)
+                    )
+                }
+                #[result_serializer(borsh)]
+                #[doc(hidden)]
+                #[private]
+                pub fn __swap_resume_2(&mut self, fee: u128, available: U128, ) -> u128 {
+                    available.0 - fee
+                }
+            }
+        };
+        assert_eq!(item.to_token_stream().to_string(), expected.to_string());
+
+        let mut item: ItemImpl = parse_quote! {
+            impl Exchange {
+                pub async fn swap(&mut self) {
+                    let fee = self.fee;
+                    let _: U128 = ext_token::ext(self.token.clone()).balance().await;
+                    self.fee = fee;
+                }
+            }
+        };
+        assert!(expand_async_methods(&mut item).is_err());
+    }
+}
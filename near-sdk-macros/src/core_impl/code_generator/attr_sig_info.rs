@@ -1,42 +1,87 @@
 use proc_macro2::TokenStream as TokenStream2;
 
-use crate::core_impl::info_extractor::{ArgInfo, AttrSigInfo, BindgenArgType, SerializerType};
+use crate::core_impl::info_extractor::{
+    ArgInfo, AttrSigInfo, BindgenArgType, Hook, HookAttr, MethodType, SerializerType, Validation,
+};
 use crate::core_impl::utils;
-use quote::quote;
+use quote::{format_ident, quote, ToTokens};
+use std::collections::HashSet;
+use syn::{Expr, Ident, Type};
 
 impl AttrSigInfo {
-    pub fn input_struct_ser(&self) -> TokenStream2 {
+    /// Input arguments of each input struct, with the serializer of the struct: `Input` for all
+    /// of them, or `Input` for the JSON arguments followed by `BorshInput` for the Borsh
+    /// arguments if the serializers are mixed.
+    fn input_structs(&self) -> Vec<(Ident, &SerializerType, Vec<&ArgInfo>)> {
         let args: Vec<_> = self.input_args().collect();
         assert!(
             !args.is_empty(),
             "Can only generate input struct for when input args are specified"
         );
-        let attribute = match &self.input_serializer {
-            SerializerType::JSON => quote! {
-                #[derive(near_sdk::serde::Serialize)]
-                #[serde(crate = "near_sdk::serde")]
-            },
-            SerializerType::Borsh => quote! {
-                #[derive(near_sdk::borsh::BorshSerialize)]
-            },
-        };
-        let mut fields = TokenStream2::new();
-        for arg in args {
-            let ArgInfo { ty, ident, .. } = &arg;
-            fields.extend(quote! {
-                #ident: &'nearinput #ty,
-            });
+        if !self.is_mixed_input() {
+            return vec![(format_ident!("Input"), &self.input_serializer, args)];
         }
-        quote! {
-            #attribute
-            struct Input<'nearinput> {
-                #fields
+        let (borsh, json) =
+            args.into_iter().partition(|arg| arg.serializer_ty == SerializerType::Borsh);
+        vec![
+            (format_ident!("Input"), &SerializerType::JSON, json),
+            (format_ident!("BorshInput"), &SerializerType::Borsh, borsh),
+        ]
+    }
+
+    pub fn input_struct_ser(&self) -> TokenStream2 {
+        let mut structs = TokenStream2::new();
+        for (name, serializer, args) in self.input_structs() {
+            let attribute = match serializer {
+                SerializerType::JSON => quote! {
+                    #[derive(near_sdk::serde::Serialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                },
+                SerializerType::Borsh => quote! {
+                    #[derive(near_sdk::borsh::BorshSerialize)]
+                },
+            };
+            let mut fields = TokenStream2::new();
+            for arg in &args {
+                let ArgInfo { ty, ident, .. } = arg;
+                fields.extend(quote! {
+                    #ident: &'nearinput #ty,
+                });
+            }
+            // Generic parameters are only in scope of the struct if it declares them as well, and
+            // it can only declare those used by its fields.
+            let mut used = HashSet::new();
+            for arg in &args {
+                utils::collect_idents(arg.ty.to_token_stream(), &mut used);
             }
+            let generics = &self.original_sig.generics;
+            let (params, unused): (Vec<_>, Vec<_>) =
+                generics.params.iter().partition(|param| used.contains(&utils::param_ident(param)));
+            let unused: HashSet<_> = unused.into_iter().map(utils::param_ident).collect();
+            let where_clause = generics.where_clause.as_ref().map(|where_clause| {
+                let predicates = where_clause.predicates.iter().filter(|predicate| {
+                    let mut idents = HashSet::new();
+                    utils::collect_idents(predicate.to_token_stream(), &mut idents);
+                    idents.is_disjoint(&unused)
+                });
+                quote! { where #(#predicates,)* }
+            });
+            let params = quote! { #(, #params)* };
+            structs.extend(quote! {
+                #attribute
+                struct #name<'nearinput #params> #where_clause {
+                    #fields
+                }
+            });
         }
+        structs
     }
+
     /// Create struct representing input arguments to deserialize.
     ///
-    /// Code generated is based on the serialization type of `Self::input_serializer`.
+    /// Code generated is based on the serialization type of `Self::input_serializer`. If the
+    /// serializers of the arguments are mixed, the Borsh arguments are in a `BorshInput` struct
+    /// instead.
     ///
     /// Each argument is getting converted to a field in a struct. Specifically argument:
     /// `ATTRIBUTES ref mut binding @ SUBPATTERN : TYPE` is getting converted to:
@@ -52,36 +97,35 @@ impl AttrSigInfo {
     /// }
     /// ```
     pub fn input_struct_deser(&self) -> TokenStream2 {
-        let args: Vec<_> = self.input_args().collect();
-        assert!(
-            !args.is_empty(),
-            "Can only generate input struct for when input args are specified"
-        );
-        let attribute = match &self.input_serializer {
-            SerializerType::JSON => quote! {
-                #[derive(near_sdk::serde::Deserialize)]
-                #[serde(crate = "near_sdk::serde")]
-            },
-            SerializerType::Borsh => quote! {
-                #[derive(near_sdk::borsh::BorshDeserialize)]
-            },
-        };
-        let mut fields = TokenStream2::new();
-        for arg in args {
-            let ArgInfo { ty, ident, .. } = &arg;
-            fields.extend(quote! {
-                #ident: #ty,
-            });
-        }
-        quote! {
-            #attribute
-            struct Input {
-                #fields
+        let mut structs = TokenStream2::new();
+        for (name, serializer, args) in self.input_structs() {
+            let attribute = match serializer {
+                SerializerType::JSON => quote! {
+                    #[derive(near_sdk::serde::Deserialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                },
+                SerializerType::Borsh => quote! {
+                    #[derive(near_sdk::borsh::BorshDeserialize)]
+                },
+            };
+            let mut fields = TokenStream2::new();
+            for arg in args {
+                let ArgInfo { ty, ident, .. } = &arg;
+                fields.extend(quote! {
+                    #ident: #ty,
+                });
             }
+            structs.extend(quote! {
+                #attribute
+                struct #name {
+                    #fields
+                }
+            });
         }
+        structs
     }
 
-    /// Create pattern that decomposes input struct using correct mutability modifiers.
+    /// Create patterns that decompose the input structs using correct mutability modifiers.
     /// # Example:
     /// ```ignore
     /// Input {
@@ -90,27 +134,53 @@ impl AttrSigInfo {
     ///     arg2
     /// }
     /// ```
-    pub fn decomposition_pattern(&self) -> TokenStream2 {
-        let args: Vec<_> = self.input_args().collect();
-        assert!(
-            !args.is_empty(),
-            "Can only generate decomposition pattern for when input args are specified."
-        );
-        let mut fields = TokenStream2::new();
-        for arg in args {
-            let ArgInfo { mutability, ident, .. } = &arg;
-            fields.extend(quote! {
-            #mutability #ident,
+    pub fn decomposition_patterns(&self) -> Vec<TokenStream2> {
+        let mut patterns = Vec::new();
+        for (name, _, args) in self.input_structs() {
+            let mut fields = TokenStream2::new();
+            for arg in args {
+                let ArgInfo { mutability, ident, .. } = &arg;
+                fields.extend(quote! {
+                #mutability #ident,
+                });
+            }
+            patterns.push(quote! {
+                #name {
+                    #fields
+                }
             });
         }
+        patterns
+    }
+
+    /// Create statement that deserializes the given bytes into the input structs and decomposes
+    /// them into the arguments.
+    pub fn input_deserialization(&self, bytes: TokenStream2) -> TokenStream2 {
+        let patterns = self.decomposition_patterns();
+        if let [json, borsh] = patterns.as_slice() {
+            // The Borsh arguments start where the JSON object ends.
+            return quote! {
+                let __near_input: &[u8] = #bytes;
+                let mut __near_json = near_sdk::serde_json::Deserializer::from_slice(__near_input).into_iter::<Input>();
+                let #json : Input = __near_json.next().and_then(Result::ok).expect("Failed to deserialize input from JSON.");
+                let #borsh : BorshInput = near_sdk::borsh::BorshDeserialize::try_from_slice(&__near_input[__near_json.byte_offset()..]).expect("Failed to deserialize input from Borsh.");
+            };
+        }
+        let decomposition = &patterns[0];
+        let serializer_invocation = match self.input_serializer {
+            SerializerType::JSON => quote! {
+                near_sdk::serde_json::from_slice(#bytes).expect("Failed to deserialize input from JSON.")
+            },
+            SerializerType::Borsh => quote! {
+                near_sdk::borsh::BorshDeserialize::try_from_slice(#bytes).expect("Failed to deserialize input from Borsh.")
+            },
+        };
         quote! {
-            Input {
-                #fields
-            }
+            let #decomposition : Input = #serializer_invocation ;
         }
     }
 
-    /// Create expression that constructs the struct with references to each variable.
+    /// Create expressions that construct the input structs with references to each variable.
     /// # Example:
     /// ```ignore
     /// Input {
@@ -119,24 +189,23 @@ impl AttrSigInfo {
     ///     arg2: &arg2,
     /// }
     /// ```
-    pub fn constructor_expr_ref(&self) -> TokenStream2 {
-        let args: Vec<_> = self.input_args().collect();
-        assert!(
-            !args.is_empty(),
-            "Can only generate constructor expression for when input args are specified."
-        );
-        let mut fields = TokenStream2::new();
-        for arg in args {
-            let ArgInfo { ident, .. } = &arg;
-            fields.extend(quote! {
-                #ident: &#ident,
-            });
-        }
-        quote! {
-            Input {
-                #fields
+    pub fn constructor_exprs_ref(&self) -> Vec<TokenStream2> {
+        let mut exprs = Vec::new();
+        for (name, _, args) in self.input_structs() {
+            let mut fields = TokenStream2::new();
+            for arg in args {
+                let ArgInfo { ident, .. } = &arg;
+                fields.extend(quote! {
+                    #ident: &#ident,
+                });
             }
+            exprs.push(quote! {
+                #name {
+                    #fields
+                }
+            });
         }
+        exprs
     }
 
     /// Create a sequence of arguments that can be used to call the method or the function
@@ -187,7 +256,7 @@ impl AttrSigInfo {
             .enumerate()
             .fold(TokenStream2::new(), |acc, (idx, arg)| {
                 let idx = idx as u64;
-                let ArgInfo { mutability, ident, ty, bindgen_ty, serializer_ty, .. } = arg;
+                let ArgInfo { mutability, ident, ty, bindgen_ty, serializer_ty, on_error, .. } = arg;
                 match &bindgen_ty {
                     BindgenArgType::CallbackArg => {
                         let error_msg = format!("Callback computation {} was not successful", idx);
@@ -205,7 +274,10 @@ impl AttrSigInfo {
                         }
                     }
                     BindgenArgType::CallbackResultArg => {
-                        let ok_type = if let Some(ok_type) = utils::extract_ok_type(ty) {
+                        let ok_type = if on_error.is_some() {
+                            // The error is given to the handler, so the argument is the value.
+                            ty
+                        } else if let Some(ok_type) = utils::extract_ok_type(ty) {
                             ok_type
                         } else {
                             return syn::Error::new_spanned(ty, "Function parameters marked with \
@@ -241,9 +313,14 @@ impl AttrSigInfo {
                                 near_sdk::PromiseResult::Failed => Err(near_sdk::PromiseError::Failed),
                             }
                         };
+                        let result_ty = if on_error.is_some() {
+                            quote! { Result<#ty, near_sdk::PromiseError> }
+                        } else {
+                            quote! { #ty }
+                        };
                         quote! {
                             #acc
-                            let #mutability #ident: #ty = #result;
+                            let #mutability #ident: #result_ty = #result;
                         }
                     }
                     _ => unreachable!()
@@ -251,6 +328,132 @@ impl AttrSigInfo {
             })
     }
 
+    /// Create code that checks the arguments decorated with `#[validate(..)]`, once they have been
+    /// read from the input.
+    pub fn arg_validation(&self, struct_type: &Type) -> TokenStream2 {
+        let checks = self.args.iter().flat_map(|arg| {
+            let ident = &arg.ident;
+            arg.validations.iter().map(move |validation| match validation {
+                Validation::Range { min, max } => {
+                    bounds_check(ident, quote! { #ident }, "be", min.as_ref(), max.as_ref())
+                }
+                Validation::Len { min, max } => bounds_check(
+                    ident,
+                    quote! { #ident.len() },
+                    "have a length",
+                    min.as_ref(),
+                    max.as_ref(),
+                ),
+                Validation::Predicate(predicate) => {
+                    let error = format!(
+                        "Argument `{}` is not valid according to `{}`",
+                        ident,
+                        predicate.to_token_stream().to_string().replace(' ', "")
+                    );
+                    let predicate = utils::resolve_self_path(predicate, struct_type);
+                    quote! {
+                        if !#predicate(&#ident) {
+                            near_sdk::env::panic_str(#error);
+                        }
+                    }
+                }
+            })
+        });
+        quote! { #(#checks)* }
+    }
+
+    /// Create code that checks the attached deposit against the `#[payable]` attribute of the
+    /// method, or that there is none if the method is neither payable nor a view method.
+    pub fn deposit_check(&self) -> TokenStream2 {
+        let ident = &self.ident;
+        if self.deposit.exactly_one_yocto {
+            quote! {
+                near_sdk::assert_one_yocto();
+            }
+        } else if let Some((min, min_str)) = &self.deposit.min {
+            let error = format!("Method {} requires a deposit of at least {}", ident, min_str);
+            quote! {
+                if near_sdk::env::attached_deposit() < #min {
+                    near_sdk::env::panic_str(#error);
+                }
+            }
+        } else if self.is_payable || matches!(self.method_type, MethodType::View) {
+            // No check if the method is payable or a view method
+            quote! {}
+        } else {
+            // If method is not payable, do a check to make sure that it doesn't consume deposit
+            let error = format!("Method {} doesn't accept deposit", ident);
+            quote! {
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str(#error);
+                }
+            }
+        }
+    }
+
+    /// Create code that checks that a `#[private]` method is called by the contract itself.
+    pub fn private_check(&self) -> TokenStream2 {
+        if !self.is_private {
+            return quote! {};
+        }
+        let error = format!("Method {} is private", self.ident);
+        quote! {
+            if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
+                near_sdk::env::panic_str(#error);
+            }
+        }
+    }
+
+    /// Wraps the invocation of the method so that the handlers of the failed
+    /// `#[callback_result(on_error = "..")]` arguments are called instead, with the contract
+    /// passed the same way as to the method, if any.
+    pub fn dispatch_callback_errors(
+        &self,
+        struct_type: &Type,
+        invocation: TokenStream2,
+        contract: Option<TokenStream2>,
+    ) -> TokenStream2 {
+        self.args.iter().rev().fold(invocation, |invocation, arg| {
+            let handler = match &arg.on_error {
+                Some(handler) => handler,
+                None => return invocation,
+            };
+            let handler = utils::resolve_self_path(handler, struct_type);
+            let ArgInfo { mutability, ident, .. } = arg;
+            let contract = contract.iter();
+            quote! {
+                match #ident {
+                    Ok(#mutability #ident) => #invocation,
+                    Err(__error) => #handler(#(#contract,)* __error),
+                }
+            }
+        })
+    }
+
+    /// Wraps the invocation of the method with the calls of its `before` and `after` hooks.
+    pub fn with_hooks(&self, struct_type: &Type, invocation: TokenStream2) -> TokenStream2 {
+        let call = |hook: &Option<HookAttr>| match hook {
+            Some(HookAttr::Call(Hook::Method(method))) => quote! { contract.#method(); },
+            Some(HookAttr::Call(Hook::Function(function))) => {
+                let function = utils::resolve_self_path(function, struct_type);
+                quote! { #function(); }
+            }
+            Some(HookAttr::Skip) | None => TokenStream2::new(),
+        };
+        let (before, after) = (call(&self.before), call(&self.after));
+        if before.is_empty() && after.is_empty() {
+            return invocation;
+        }
+        quote! {
+            {
+                #before
+                let __near_result = #invocation;
+                #after
+                __near_result
+            }
+        }
+    }
+
     /// Create code that deserializes arguments that were decorated with `#[callback_vec]`.
     pub fn callback_vec_deserialization(&self) -> TokenStream2 {
         self
@@ -275,6 +478,32 @@ impl AttrSigInfo {
     }
 }
 
+/// Checks that the value is within the bounds, failing with `Argument `x` must <what> <bounds>`.
+fn bounds_check(
+    ident: &Ident,
+    value: TokenStream2,
+    what: &str,
+    min: Option<&Expr>,
+    max: Option<&Expr>,
+) -> TokenStream2 {
+    let bound = |expr: &Expr| expr.to_token_stream().to_string();
+    let requirement = match (min, max) {
+        (Some(min), Some(max)) => format!("between {} and {}", bound(min), bound(max)),
+        (Some(min), None) => format!("at least {}", bound(min)),
+        (None, Some(max)) => format!("at most {}", bound(max)),
+        (None, None) => return TokenStream2::new(),
+    };
+    let error = format!("Argument `{}` must {} {}", ident, what, requirement);
+    let min = min.map(|min| quote! { #value < #min });
+    let max = max.map(|max| quote! { #value > #max });
+    let failed = min.into_iter().chain(max);
+    quote! {
+        if #(#failed)||* {
+            near_sdk::env::panic_str(#error);
+        }
+    }
+}
+
 pub fn deserialize_data(ty: &SerializerType) -> TokenStream2 {
     match ty {
         SerializerType::JSON => quote! {
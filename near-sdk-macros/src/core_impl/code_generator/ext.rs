@@ -24,8 +24,9 @@ pub(crate) fn generate_ext_structs(
     };
     if let Some(generics) = generic_details {
         // If ext generation is on struct, make ext function associated with struct not module
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
         ext_code = quote! {
-            impl #generics #ident #generics {
+            impl #impl_generics #ident #ty_generics #where_clause {
                 #ext_code
             }
         };
@@ -88,9 +89,10 @@ fn generate_ext_function(attr_signature_info: &AttrSigInfo) -> TokenStream2 {
         attribute.to_tokens(&mut new_non_bindgen_attrs);
     }
     let Signature { generics, .. } = original_sig;
+    let where_clause = &generics.where_clause;
     quote! {
         #new_non_bindgen_attrs
-        pub fn #ident #generics(self, #pat_type_list) -> near_sdk::Promise {
+        pub fn #ident #generics(self, #pat_type_list) -> near_sdk::Promise #where_clause {
             let __args = #serialize;
             near_sdk::Promise::new(self.account_id)
             .function_call_weight(
@@ -21,27 +21,15 @@ impl ImplItemMethodInfo {
         let arg_parsing;
         if has_input_args {
             arg_struct = attr_signature_info.input_struct_deser();
-            let decomposition = attr_signature_info.decomposition_pattern();
-            let serializer_invocation = match attr_signature_info.input_serializer {
-                SerializerType::JSON => quote! {
-                    near_sdk::serde_json::from_slice(
-                        &near_sdk::env::input().expect("Expected input since method has arguments.")
-                    ).expect("Failed to deserialize input from JSON.")
-                },
-                SerializerType::Borsh => quote! {
-                    near_sdk::borsh::BorshDeserialize::try_from_slice(
-                        &near_sdk::env::input().expect("Expected input since method has arguments.")
-                    ).expect("Failed to deserialize input from Borsh.")
-                },
-            };
-            arg_parsing = quote! {
-                let #decomposition : Input = #serializer_invocation ;
-            };
+            arg_parsing = attr_signature_info.input_deserialization(quote! {
+                &near_sdk::env::input().expect("Expected input since method has arguments.")
+            });
         } else {
             arg_struct = TokenStream2::new();
             arg_parsing = TokenStream2::new();
         };
 
+        let arg_validation = attr_signature_info.arg_validation(struct_type);
         let callback_deser = attr_signature_info.callback_deserialization();
         let callback_vec_deser = attr_signature_info.callback_vec_deserialization();
 
@@ -53,32 +41,40 @@ impl ImplItemMethodInfo {
             returns,
             result_serializer,
             method_type,
-            is_payable,
-            is_private,
+            deposit,
             is_handles_result,
+            is_read_mostly,
+            deprecation,
             ..
         } = attr_signature_info;
-        let deposit_check = if *is_payable || matches!(method_type, &MethodType::View) {
-            // No check if the method is payable or a view method
-            quote! {}
-        } else {
-            // If method is not payable, do a check to make sure that it doesn't consume deposit
-            let error = format!("Method {} doesn't accept deposit", ident);
-            quote! {
-                if near_sdk::env::attached_deposit() != 0 {
-                    near_sdk::env::panic_str(#error);
-                }
+        let deposit_check = attr_signature_info.deposit_check();
+        let is_private_check = attr_signature_info.private_check();
+        let (deprecation_log, removal_cfg) = match deprecation {
+            Some(deprecation) => {
+                let log = deprecation.event_log(ident);
+                (quote! { near_sdk::env::log_str(#log); }, deprecation.removal_cfg())
             }
+            None => (quote! {}, quote! {}),
         };
-        let is_private_check = if *is_private {
-            let error = format!("Method {} is private", ident);
-            quote! {
-                if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
-                    near_sdk::env::panic_str(#error);
+        // The deposit above the minimum goes back to the caller once the method has run.
+        let deposit_refund = match &deposit.min {
+            Some((min, _)) if deposit.refund_excess => quote! {
+                let __excess_deposit = near_sdk::env::attached_deposit() - #min;
+                if __excess_deposit > 0 {
+                    near_sdk::Promise::new(near_sdk::env::predecessor_account_id())
+                        .transfer(__excess_deposit);
                 }
-            }
-        } else {
+            },
+            _ => quote! {},
+        };
+        // Writes buffered by `env::buffered` are flushed once the method has run, unless it is a
+        // view method, which cannot write to storage.
+        let storage_flush = if matches!(method_type, &MethodType::View) {
             quote! {}
+        } else {
+            quote! {
+                near_sdk::env::buffered::flush();
+            }
         };
         let body = if matches!(method_type, &MethodType::Init) {
             match init_method_wrapper(self, true) {
@@ -96,26 +92,47 @@ impl ImplItemMethodInfo {
             let contract_ser;
             if let Some(receiver) = receiver {
                 let mutability = &receiver.mutability;
-                contract_deser = quote! {
-                    let #mutability contract: #struct_type = near_sdk::env::state_read().unwrap_or_default();
-                };
-                method_invocation = quote! {
-                    contract.#ident(#arg_list)
+                let contract_arg = match &receiver.reference {
+                    Some(_) => quote! { &#mutability contract },
+                    None => quote! { contract },
                 };
-                if matches!(method_type, &MethodType::Regular) {
+                method_invocation = attr_signature_info.dispatch_callback_errors(
+                    struct_type,
+                    quote! { contract.#ident(#arg_list) },
+                    Some(contract_arg),
+                );
+                if *is_read_mostly {
+                    // Keep the bytes the state was loaded from, so that the write can be skipped
+                    // if the method did not end up modifying anything.
+                    contract_deser = quote! {
+                        let (mut contract, __near_state_bytes): (#struct_type, _) =
+                            near_sdk::__private::state_read_with_bytes();
+                    };
                     contract_ser = quote! {
-                        near_sdk::env::state_write(&contract);
+                        near_sdk::__private::state_write_if_changed(&contract, __near_state_bytes.as_deref());
                     };
                 } else {
-                    contract_ser = TokenStream2::new();
+                    contract_deser = quote! {
+                        let #mutability contract: #struct_type = near_sdk::env::state_read().unwrap_or_default();
+                    };
+                    if matches!(method_type, &MethodType::Regular) {
+                        contract_ser = quote! {
+                            near_sdk::env::state_write(&contract);
+                        };
+                    } else {
+                        contract_ser = TokenStream2::new();
+                    }
                 }
             } else {
                 contract_deser = TokenStream2::new();
-                method_invocation = quote! {
-                    #struct_type::#ident(#arg_list)
-                };
+                method_invocation = attr_signature_info.dispatch_callback_errors(
+                    struct_type,
+                    quote! { <#struct_type>::#ident(#arg_list) },
+                    None,
+                );
                 contract_ser = TokenStream2::new();
             }
+            let method_invocation = attr_signature_info.with_hooks(struct_type, method_invocation);
             match returns {
                 ReturnType::Default => quote! {
                     #contract_deser
@@ -191,17 +208,22 @@ impl ImplItemMethodInfo {
         });
         quote! {
             #non_bindgen_attrs
+            #removal_cfg
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn #ident() {
                 #panic_hook
+                #deprecation_log
                 #is_private_check
                 #deposit_check
                 #arg_struct
                 #arg_parsing
+                #arg_validation
                 #callback_deser
                 #callback_vec_deser
                 #body
+                #deposit_refund
+                #storage_flush
             }
         }
     }
@@ -214,6 +236,12 @@ fn init_method_wrapper(
     let ImplItemMethodInfo { attr_signature_info, struct_type, .. } = method_info;
     let arg_list = attr_signature_info.arg_list();
     let AttrSigInfo { ident, returns, is_handles_result, .. } = attr_signature_info;
+    let invocation = attr_signature_info.dispatch_callback_errors(
+        struct_type,
+        quote! { <#struct_type>::#ident(#arg_list) },
+        None,
+    );
+    let invocation = attr_signature_info.with_hooks(struct_type, invocation);
     let state_check = if check_state {
         quote! {
             if near_sdk::env::state_exists() {
@@ -232,7 +260,7 @@ fn init_method_wrapper(
         {
             Ok(quote! {
                 #state_check
-                let result = #struct_type::#ident(#arg_list);
+                let result = #invocation;
                 match result {
                     Ok(contract) => near_sdk::env::state_write(&contract),
                     Err(err) => near_sdk::FunctionError::panic(&err)
@@ -245,7 +273,7 @@ fn init_method_wrapper(
         )),
         ReturnType::Type(_, _) => Ok(quote! {
             #state_check
-            let contract = #struct_type::#ident(#arg_list);
+            let contract = #invocation;
             near_sdk::env::state_write(&contract);
         }),
     }
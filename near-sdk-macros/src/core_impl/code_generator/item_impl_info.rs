@@ -1,8 +1,7 @@
 use crate::core_impl::ext::generate_ext_function_wrappers;
 use crate::ItemImplInfo;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::ToTokens;
-use syn::{spanned::Spanned, Ident};
+use syn::{spanned::Spanned, Type, TypePath};
 
 impl ItemImplInfo {
     /// Generate the code that wraps
@@ -17,15 +16,18 @@ impl ItemImplInfo {
     }
 
     pub fn generate_ext_wrapper_code(&self) -> TokenStream2 {
-        match syn::parse::<Ident>(self.ty.to_token_stream().into()) {
-            Ok(n) => generate_ext_function_wrappers(
-                &n,
-                self.methods
-                    .iter()
-                    .filter(|m| m.is_public || self.is_trait_impl)
-                    .map(|m| &m.attr_signature_info),
-            ),
-            Err(e) => syn::Error::new(self.ty.span(), e).to_compile_error(),
+        // The ext struct of a generic contract is named after the type without its arguments.
+        match &self.ty {
+            Type::Path(TypePath { qself: None, path }) if path.segments.len() == 1 => {
+                generate_ext_function_wrappers(
+                    &path.segments[0].ident,
+                    self.methods
+                        .iter()
+                        .filter(|m| m.is_public || self.is_trait_impl)
+                        .map(|m| &m.attr_signature_info),
+                )
+            }
+            _ => syn::Error::new(self.ty.span(), "expected identifier").to_compile_error(),
         }
     }
 }
@@ -33,9 +35,10 @@ impl ItemImplInfo {
 #[rustfmt::skip]
 #[cfg(test)]
 mod tests {
-    use syn::{Type, ImplItemMethod, parse_quote};
-    use quote::quote;
+    use syn::{Type, ImplItemMethod, ItemImpl, parse_quote};
+    use quote::{quote, ToTokens};
     use crate::core_impl::info_extractor::ImplItemMethodInfo;
+    use crate::ItemImplInfo;
 
 
     #[test]
@@ -128,6 +131,7 @@ mod tests {
                 let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
                 contract.method();
                 near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -188,6 +192,7 @@ mod tests {
                     let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
                     contract.method(k, m, );
                     near_sdk::env::state_write(&contract);
+                    near_sdk::env::buffered::flush();
                 }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -224,6 +229,7 @@ mod tests {
                         near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
                     near_sdk::env::value_return(&result);
                     near_sdk::env::state_write(&contract);
+                    near_sdk::env::buffered::flush();
                 }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -420,6 +426,121 @@ mod tests {
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn callback_result_on_error() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[private] pub fn method(&mut self, #[callback_result(on_error = "Self::rollback")] x: u64) -> bool { true }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
+                    near_sdk::env::panic_str("Method method is private");
+                }
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                let x: Result<u64, near_sdk::PromiseError> = match near_sdk::env::promise_result(0u64) {
+                    near_sdk::PromiseResult::Successful(data) => Ok(near_sdk::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON")),
+                    near_sdk::PromiseResult::NotReady => Err(near_sdk::PromiseError::NotReady),
+                    near_sdk::PromiseResult::Failed => Err(near_sdk::PromiseError::Failed),
+                };
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                let result = match x {
+                    Ok(x) => contract.method(x, ),
+                    Err(__error) => <Hello>::rollback(&mut contract, __error),
+                };
+                let result = near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+                near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
+            }
+        );
+
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn validated_args() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            pub fn method(
+                &self,
+                #[validate(range(min = 1, max = 100))] k: u64,
+                #[validate(len(max = 8), predicate = "Self::is_symbol")] s: String,
+            ) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    k: u64,
+                    s: String,
+                }
+                let Input { k, s, }: Input = near_sdk::serde_json::from_slice(
+                    &near_sdk::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                if k < 1 || k > 100 {
+                    near_sdk::env::panic_str("Argument `k` must be between 1 and 100");
+                }
+                if s.len() > 8 {
+                    near_sdk::env::panic_str("Argument `s` must have a length at most 8");
+                }
+                if !<Hello>::is_symbol(&s) {
+                    near_sdk::env::panic_str("Argument `s` is not valid according to `Self::is_symbol`");
+                }
+                let contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method(k, s, );
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn mixed_serializers() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            pub fn method(&self, k: u64, #[serializer(borsh)] proof: Vec<u8>) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                #[derive(near_sdk :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk::serde")]
+                struct Input {
+                    k: u64,
+                }
+                #[derive(near_sdk :: borsh :: BorshDeserialize)]
+                struct BorshInput {
+                    proof: Vec<u8>,
+                }
+                let __near_input: &[u8] = &near_sdk::env::input().expect("Expected input since method has arguments.");
+                let mut __near_json = near_sdk::serde_json::Deserializer::from_slice(__near_input).into_iter::<Input>();
+                let Input { k, }: Input = __near_json.next().and_then(Result::ok).expect("Failed to deserialize input from JSON.");
+                let BorshInput { proof, }: BorshInput = near_sdk::borsh::BorshDeserialize::try_from_slice(&__near_input[__near_json.byte_offset()..]).expect("Failed to deserialize input from Borsh.");
+                let contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method(k, proof, );
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
     #[test]
     fn callback_args_vec() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -490,8 +611,9 @@ mod tests {
                 if near_sdk::env::state_exists() {
                     near_sdk::env::panic_str("The contract has already been initialized");
                 }
-                let contract = Hello::method(&mut k,);
+                let contract = <Hello>::method(&mut k,);
                 near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -538,8 +660,9 @@ mod tests {
                     &near_sdk::env::input().expect("Expected input since method has arguments.")
                 )
                 .expect("Failed to deserialize input from JSON.");
-                let contract = Hello::method(&mut k,);
+                let contract = <Hello>::method(&mut k,);
                 near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -572,8 +695,9 @@ mod tests {
                 if near_sdk::env::state_exists() {
                     near_sdk::env::panic_str("The contract has already been initialized");
                 }
-                let contract = Hello::method(&mut k,);
+                let contract = <Hello>::method(&mut k,);
                 near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -611,6 +735,7 @@ mod tests {
                     .expect("Failed to serialize the return value using Borsh.");
                 near_sdk::env::value_return(&result);
                 near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -673,6 +798,7 @@ mod tests {
                 let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
                 contract.method();
                 near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -698,11 +824,147 @@ mod tests {
                 let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
                 contract.private_method();
                 near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn read_mostly_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("#[read_mostly] pub fn method(&mut self) -> bool { }").unwrap();
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                let (mut contract, __near_state_bytes): (Hello, _) =
+                    near_sdk::__private::state_read_with_bytes();
+                let result = contract.method();
+                let result =
+                    near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+                near_sdk::__private::state_write_if_changed(&contract, __near_state_bytes.as_deref());
+                near_sdk::env::buffered::flush();
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn read_mostly_view_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = syn::parse_str("#[read_mostly] pub fn method(&self) { }").unwrap();
+        let err = ImplItemMethodInfo::new(&mut method, impl_type).err().unwrap();
+        assert_eq!(err.to_string(), "Read-mostly method must take `&mut self`");
+    }
+
+    #[test]
+    fn deprecated_method() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[deprecated_method(since = "1.2", use = "new_method", removed_with_feature = "v2")]
+            pub fn method(&self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(not(feature = "v2"))]
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::env::log_str("EVENT_JSON:{\"standard\":\"near-sdk\",\"version\":\"1.0.0\",\"event\":\"method_deprecated\",\"data\":{\"method\":\"method\",\"since\":\"1.2\",\"use\":\"new_method\"}}");
+                let contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn deprecated_method_unknown_argument() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[deprecated_method(until = "2.0")]
+            pub fn method(&self) { }
+        };
+        let err = ImplItemMethodInfo::new(&mut method, impl_type).err().unwrap();
+        assert_eq!(err.to_string(), "Unsupported deprecated_method attribute.");
+    }
+
+    #[test]
+    fn payable_exactly_one_yocto() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[payable(exactly_one_yocto)]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                near_sdk::assert_one_yocto();
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn payable_min_refund_excess() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[payable(min = "0.1 NEAR", refund_excess)]
+            pub fn method(&mut self) { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() < 100000000000000000000000u128 {
+                    near_sdk::env::panic_str("Method method requires a deposit of at least 0.1 NEAR");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk::env::state_write(&contract);
+                let __excess_deposit = near_sdk::env::attached_deposit() - 100000000000000000000000u128;
+                if __excess_deposit > 0 {
+                    near_sdk::Promise::new(near_sdk::env::predecessor_account_id())
+                        .transfer(__excess_deposit);
+                }
+                near_sdk::env::buffered::flush();
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn payable_refund_excess_without_min() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[payable(refund_excess)]
+            pub fn method(&mut self) { }
+        };
+        let err = ImplItemMethodInfo::new(&mut method, impl_type).err().unwrap();
+        assert_eq!(err.to_string(), "`refund_excess` requires a `min` deposit.");
+    }
+
     #[test]
     fn handle_result_json() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -783,11 +1045,12 @@ mod tests {
                 if near_sdk::env::state_exists() {
                     near_sdk::env::panic_str("The contract has already been initialized");
                 }
-                let result = Hello::new();
+                let result = <Hello>::new();
                 match result {
                     Ok(contract) => near_sdk::env::state_write(&contract),
                     Err(err) => near_sdk::FunctionError::panic(&err)
                 }
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -811,11 +1074,12 @@ mod tests {
                 if near_sdk::env::attached_deposit() != 0 {
                     near_sdk::env::panic_str("Method new doesn't accept deposit");
                 }
-                let result = Hello::new();
+                let result = <Hello>::new();
                 match result {
                     Ok(contract) => near_sdk::env::state_write(&contract),
                     Err(err) => near_sdk::FunctionError::panic(&err)
                 }
+                near_sdk::env::buffered::flush();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -853,4 +1117,150 @@ mod tests {
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
+
+    #[test]
+    fn hooks() {
+        let mut item: ItemImpl = parse_quote! {
+            impl Hello {
+                pub fn method(&mut self) -> u64 { 1 }
+                #[after(call = "Self::log_call")]
+                pub fn overridden(&mut self) { }
+                #[before(skip)]
+                pub fn unpause(&mut self) { }
+                pub fn status() { }
+            }
+        };
+        let mut info = ItemImplInfo::new(&mut item, None).unwrap();
+        let before = parse_quote!(call = "self.assert_not_paused");
+        let after = parse_quote!(call = "self.record_call");
+        info.set_default_hooks(Some(&before), Some(&after));
+        let actual = info.wrapper_code();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method method doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                let result = {
+                    contract.assert_not_paused();
+                    let __near_result = contract.method();
+                    contract.record_call();
+                    __near_result
+                };
+                let result = near_sdk::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk::env::value_return(&result);
+                near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
+            }
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn overridden() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method overridden doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                {
+                    contract.assert_not_paused();
+                    let __near_result = contract.overridden();
+                    <Hello>::log_call();
+                    __near_result
+                };
+                near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
+            }
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn unpause() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method unpause doesn't accept deposit");
+                }
+                let mut contract: Hello = near_sdk::env::state_read().unwrap_or_default();
+                {
+                    let __near_result = contract.unpause();
+                    contract.record_call();
+                    __near_result
+                };
+                near_sdk::env::state_write(&contract);
+                near_sdk::env::buffered::flush();
+            }
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn status() {
+                near_sdk::env::setup_panic_hook();
+                if near_sdk::env::attached_deposit() != 0 {
+                    near_sdk::env::panic_str("Method status doesn't accept deposit");
+                }
+                <Hello>::status();
+                near_sdk::env::buffered::flush();
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let mut item: ItemImpl = parse_quote! {
+            impl Hello {
+                #[before(call = "self.assert_not_paused")]
+                pub fn status() { }
+            }
+        };
+        assert!(ItemImplInfo::new(&mut item, None).is_err());
+    }
+
+    #[test]
+    fn generic_impl() {
+        let mut item: ItemImpl = parse_quote! {
+            impl<S: Strategy> Skeleton<S> {
+                pub fn method(&mut self, k: u64, strategy: Option<S>) { }
+            }
+        };
+        let info = ItemImplInfo::new(&mut item, Some(parse_quote!(Skeleton<Fixed>))).unwrap();
+        let actual = info.wrapper_code();
+        let expected = quote!(
+                #[cfg(target_arch = "wasm32")]
+                #[no_mangle]
+                pub extern "C" fn method() {
+                    near_sdk::env::setup_panic_hook();
+                    if near_sdk::env::attached_deposit() != 0 {
+                        near_sdk::env::panic_str("Method method doesn't accept deposit");
+                    }
+                    #[derive(near_sdk :: serde :: Deserialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                    struct Input {
+                        k: u64,
+                        strategy: Option<Fixed>,
+                    }
+                    let Input { k, strategy, }: Input = near_sdk::serde_json::from_slice(
+                        &near_sdk::env::input().expect("Expected input since method has arguments.")
+                    )
+                    .expect("Failed to deserialize input from JSON.");
+                    let mut contract: Skeleton<Fixed> = near_sdk::env::state_read().unwrap_or_default();
+                    contract.method(k, strategy, );
+                    near_sdk::env::state_write(&contract);
+                    near_sdk::env::buffered::flush();
+                }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+        let method = match &item.items[0] {
+            syn::ImplItem::Method(method) => method,
+            _ => unreachable!(),
+        };
+        assert_eq!(method.sig.inputs.last().unwrap().to_token_stream().to_string(), "strategy : Option < S >");
+    }
+
+    #[test]
+    fn generic_impl_requires_contract() {
+        let mut item: ItemImpl = parse_quote! {
+            impl<S: Strategy> Skeleton<S> {
+                pub fn method(&self) { }
+            }
+        };
+        assert!(ItemImplInfo::new(&mut item.clone(), None).is_err());
+        assert!(ItemImplInfo::new(&mut item.clone(), Some(parse_quote!(Skeleton<Fixed>))).is_ok());
+        assert!(ItemImplInfo::new(&mut item.clone(), Some(parse_quote!(Other<Fixed>))).is_err());
+        assert!(ItemImplInfo::new(&mut item, Some(parse_quote!(Skeleton))).is_err());
+    }
 }
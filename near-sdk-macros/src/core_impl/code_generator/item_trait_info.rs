@@ -201,4 +201,38 @@ mod tests {
         };
         assert_eq!(actual.to_string(), expected.to_string());
     }
+
+    #[test]
+    fn generic_trait() {
+        let mut t: ItemTrait = syn::parse2(
+            quote!{
+                trait Token<B> where B: Serialize {
+                    fn transfer<M: Serialize>(&mut self, amount: B, msg: M);
+                    fn total_supply(&self) -> B;
+                }
+            }
+        ).unwrap();
+        let info = ItemTraitInfo::new(&mut t, None).unwrap();
+
+        let transfer = &info.methods[0].attr_sig_info;
+        let generics = &transfer.original_sig.generics;
+        let where_clause = &generics.where_clause;
+        assert_eq!(
+            quote!(#generics #where_clause).to_string(),
+            quote!(<B, M: Serialize> where B: Serialize).to_string()
+        );
+        let expected = quote! {
+            #[derive(near_sdk :: serde :: Serialize)]
+            #[serde(crate = "near_sdk::serde")]
+            struct Input<'nearinput, B, M: Serialize> where B: Serialize, {
+                amount: &'nearinput B,
+                msg: &'nearinput M,
+            }
+        };
+        assert_eq!(transfer.input_struct_ser().to_string(), expected.to_string());
+
+        // The result is not part of the ext method, so it does not need to be generic.
+        let total_supply = &info.methods[1].attr_sig_info;
+        assert!(total_supply.original_sig.generics.params.is_empty());
+    }
 }
@@ -0,0 +1,24 @@
+use syn::{parse_quote, ItemStruct};
+
+/// Replaces the type `T` of every field marked with `#[lazy]` by `near_sdk::store::Lazy<T>`, and
+/// removes the attribute, which is not known to the compiler.
+pub(crate) fn expand_lazy_fields(input: &mut ItemStruct) -> syn::Result<()> {
+    for field in input.fields.iter_mut() {
+        let mut lazy = false;
+        for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("lazy")) {
+            if !attr.tokens.is_empty() {
+                return Err(syn::Error::new_spanned(attr, "`#[lazy]` does not take arguments"));
+            }
+            if lazy {
+                return Err(syn::Error::new_spanned(attr, "duplicate `#[lazy]` attribute"));
+            }
+            lazy = true;
+        }
+        if lazy {
+            field.attrs.retain(|attr| !attr.path.is_ident("lazy"));
+            let ty = &field.ty;
+            field.ty = parse_quote!(near_sdk::store::Lazy<#ty>);
+        }
+    }
+    Ok(())
+}
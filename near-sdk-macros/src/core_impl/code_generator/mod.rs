@@ -10,6 +10,14 @@ pub use item_trait_info::*;
 mod item_impl_info;
 pub use item_impl_info::*;
 
+pub(crate) mod async_methods;
+
 pub(crate) mod ext;
 
+pub(crate) mod lazy_fields;
+
+pub(crate) mod multicall;
+
 pub(crate) mod serializer;
+
+pub(crate) mod serializer_derives;
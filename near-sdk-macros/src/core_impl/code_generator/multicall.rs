@@ -0,0 +1,218 @@
+use crate::core_impl::info_extractor::{BindgenArgType, ItemImplInfo, MethodType};
+use crate::core_impl::utils;
+use quote::quote;
+use syn::{parse_quote, ImplItemMethod, ReturnType};
+
+/// Generates the `execute_batch` method of `#[near_bindgen(multicall)]`, which calls the public
+/// change methods of the impl section in order, with the arguments of each call deserialized and
+/// validated, and the deposit and the predecessor checked, as by their exported functions. Methods
+/// with callback arguments or refunding the excess deposit can not be called.
+pub(crate) fn generate_multicall(info: &ItemImplInfo) -> ImplItemMethod {
+    let struct_type = &info.ty;
+    let methods: Vec<_> = info
+        .methods
+        .iter()
+        .filter(|method| {
+            let sig = &method.attr_signature_info;
+            method.is_public
+                && matches!(sig.method_type, MethodType::Regular)
+                && sig.receiver.is_some()
+                && !sig.deposit.refund_excess
+                && sig.args.iter().all(|arg| matches!(arg.bindgen_ty, BindgenArgType::Regular))
+        })
+        .collect();
+    let is_payable = methods.iter().any(|method| method.attr_signature_info.is_payable);
+    let arms = methods.iter().map(|method| {
+        let sig = &method.attr_signature_info;
+        let ident = &sig.ident;
+        let name = ident.to_string();
+        let private_check = sig.private_check();
+        let deposit_check = sig.deposit_check();
+        // The deposit attached to the batch is seen by each of its calls, so that it could
+        // otherwise be counted several times.
+        let deposit_use = if sig.is_payable {
+            quote! {
+                if near_sdk::env::attached_deposit() != 0 {
+                    if deposit_used {
+                        near_sdk::env::panic_str("The attached deposit can only be used by one call of a batch");
+                    }
+                    deposit_used = true;
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let arg_list = sig.arg_list();
+        let invocation = sig.with_hooks(struct_type, quote! { contract.#ident(#arg_list) });
+        let invocation = match &sig.returns {
+            ReturnType::Type(_, return_type)
+                if sig.is_handles_result && utils::type_is_result(return_type) =>
+            {
+                quote! {
+                    if let Err(err) = #invocation {
+                        near_sdk::FunctionError::panic(&err)
+                    }
+                }
+            }
+            _ => quote! { #invocation; },
+        };
+        let arg_parsing = if sig.input_args().next().is_some() {
+            let arg_struct = sig.input_struct_deser();
+            let deserialization = sig.input_deserialization(quote! { &call.args.0 });
+            let validation = sig.arg_validation(struct_type);
+            quote! {
+                #arg_struct
+                #deserialization
+                #validation
+            }
+        } else {
+            quote! {}
+        };
+        quote! {
+            #name => {
+                #private_check
+                #deposit_check
+                #deposit_use
+                #arg_parsing
+                let contract = &mut *self;
+                #invocation
+            }
+        }
+    });
+    let (payable, deposit_used) = if is_payable {
+        (quote! { #[payable] }, quote! { let mut deposit_used = false; })
+    } else {
+        (quote! {}, quote! {})
+    };
+    parse_quote! {
+        /// Calls the change methods of the contract in order, with the arguments of each call. The
+        /// batch is atomic, as a failing call reverts all of them.
+        #[private]
+        #payable
+        pub fn execute_batch(&mut self, calls: Vec<near_sdk::MethodCall>) {
+            #deposit_used
+            for call in calls {
+                match call.method_name.as_str() {
+                    #(#arms)*
+                    _ => near_sdk::env::panic_str(&format!(
+                        "Method {} can not be called in a batch",
+                        call.method_name
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_multicall;
+    use crate::ItemImplInfo;
+    use quote::{quote, ToTokens};
+    use syn::{parse_quote, ItemImpl};
+
+    #[test]
+    fn batch() {
+        let mut item: ItemImpl = parse_quote! {
+            impl Hello {
+                pub fn set_fee(&mut self, #[validate(range(max = 100))] fee: u32) { }
+                #[private]
+                #[handle_result]
+                pub fn pause(&mut self) -> Result<(), Error> { Ok(()) }
+                pub fn fee(&self) -> u32 { 0 }
+                #[private]
+                pub fn on_transfer(&mut self, #[callback_unwrap] amount: u64) { }
+                fn internal(&mut self) { }
+            }
+        };
+        let info = ItemImplInfo::new(&mut item, None).unwrap();
+        let actual = generate_multicall(&info);
+        let expected = quote! {
+            /// Calls the change methods of the contract in order, with the arguments of each call. The
+            /// batch is atomic, as a failing call reverts all of them.
+            #[private]
+            pub fn execute_batch(&mut self, calls: Vec<near_sdk::MethodCall>) {
+                for call in calls {
+                    match call.method_name.as_str() {
+                        "set_fee" => {
+                            if near_sdk::env::attached_deposit() != 0 {
+                                near_sdk::env::panic_str("Method set_fee doesn't accept deposit");
+                            }
+                            #[derive(near_sdk :: serde :: Deserialize)]
+                            #[serde(crate = "near_sdk::serde")]
+                            struct Input {
+                                fee: u32,
+                            }
+                            let Input { fee, }: Input = near_sdk::serde_json::from_slice(&call.args.0)
+                                .expect("Failed to deserialize input from JSON.");
+                            if fee > 100 {
+                                near_sdk::env::panic_str("Argument `fee` must be at most 100");
+                            }
+                            let contract = &mut *self;
+                            contract.set_fee(fee, );
+                        }
+                        "pause" => {
+                            if near_sdk::env::current_account_id() != near_sdk::env::predecessor_account_id() {
+                                near_sdk::env::panic_str("Method pause is private");
+                            }
+                            if near_sdk::env::attached_deposit() != 0 {
+                                near_sdk::env::panic_str("Method pause doesn't accept deposit");
+                            }
+                            let contract = &mut *self;
+                            if let Err(err) = contract.pause() {
+                                near_sdk::FunctionError::panic(&err)
+                            }
+                        }
+                        _ => near_sdk::env::panic_str(&format!(
+                            "Method {} can not be called in a batch",
+                            call.method_name
+                        )),
+                    }
+                }
+            }
+        };
+        assert_eq!(actual.to_token_stream().to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn batch_payable() {
+        let mut item: ItemImpl = parse_quote! {
+            impl Hello {
+                #[payable]
+                pub fn deposit(&mut self) { }
+                #[payable(min = "1 NEAR", refund_excess)]
+                pub fn register(&mut self) { }
+            }
+        };
+        let info = ItemImplInfo::new(&mut item, None).unwrap();
+        let actual = generate_multicall(&info);
+        let expected = quote! {
+            /// Calls the change methods of the contract in order, with the arguments of each call. The
+            /// batch is atomic, as a failing call reverts all of them.
+            #[private]
+            #[payable]
+            pub fn execute_batch(&mut self, calls: Vec<near_sdk::MethodCall>) {
+                let mut deposit_used = false;
+                for call in calls {
+                    match call.method_name.as_str() {
+                        "deposit" => {
+                            if near_sdk::env::attached_deposit() != 0 {
+                                if deposit_used {
+                                    near_sdk::env::panic_str("The attached deposit can only be used by one call of a batch");
+                                }
+                                deposit_used = true;
+                            }
+                            let contract = &mut *self;
+                            contract.deposit();
+                        }
+                        _ => near_sdk::env::panic_str(&format!(
+                            "Method {} can not be called in a batch",
+                            call.method_name
+                        )),
+                    }
+                }
+            }
+        };
+        assert_eq!(actual.to_token_stream().to_string(), expected.to_string());
+    }
+}
@@ -11,7 +11,19 @@ pub fn generate_serializer(
         return quote! { vec![] };
     }
     let struct_decl = attr_sig_info.input_struct_ser();
-    let constructor_call = attr_sig_info.constructor_expr_ref();
+    let constructor_calls = attr_sig_info.constructor_exprs_ref();
+    if let [json_call, borsh_call] = constructor_calls.as_slice() {
+        // The Borsh arguments follow the JSON object of the JSON arguments.
+        return quote! {
+            {
+                #struct_decl
+                let mut __args = near_sdk::serde_json::to_vec(&#json_call).expect("Failed to serialize the cross contract args using JSON.");
+                __args.extend(near_sdk::borsh::BorshSerialize::try_to_vec(&#borsh_call).expect("Failed to serialize the cross contract args using Borsh."));
+                __args
+            }
+        };
+    }
+    let constructor_call = &constructor_calls[0];
     let constructor = quote! { let __args = #constructor_call; };
     let value_ser = match serializer {
         SerializerType::JSON => quote! {
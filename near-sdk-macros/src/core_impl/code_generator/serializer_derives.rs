@@ -0,0 +1,84 @@
+use crate::core_impl::info_extractor::SerializerType;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_quote, Attribute, Fields, Ident, Item};
+
+/// Implements the given serializers for a struct or an enum, going through the crates re-exported
+/// by `near_sdk`, so that the contract does not need to depend on them itself.
+///
+/// Borsh implementations are returned, and the `borsh` attributes they use are removed from the
+/// item, while JSON ones are added to the item as derives.
+pub(crate) fn generate_serializers(
+    item: &mut Item,
+    serializers: &[SerializerType],
+) -> syn::Result<TokenStream2> {
+    let mut impls = TokenStream2::new();
+    if serializers.contains(&SerializerType::Borsh) {
+        // The derives of `borsh` refer to the crate by name, which is brought in scope of the
+        // implementations only.
+        let borsh = Ident::new("borsh", Span::call_site());
+        let borsh_impls = match &*item {
+            Item::Struct(input) => {
+                let ser = borsh_derive_internal::struct_ser(input, borsh.clone())?;
+                let de = borsh_derive_internal::struct_de(input, borsh)?;
+                quote! { #ser #de }
+            }
+            Item::Enum(input) => {
+                let ser = borsh_derive_internal::enum_ser(input, borsh.clone())?;
+                let de = borsh_derive_internal::enum_de(input, borsh)?;
+                quote! { #ser #de }
+            }
+            _ => unreachable!("serializers are only generated for structs and enums"),
+        };
+        impls.extend(quote! {
+            const _: () = {
+                use near_sdk::borsh;
+                #borsh_impls
+            };
+        });
+        remove_borsh_attrs(item);
+    }
+    if serializers.contains(&SerializerType::JSON) {
+        let mut derives: Vec<Attribute> = vec![
+            parse_quote! { #[derive(near_sdk::serde::Serialize, near_sdk::serde::Deserialize)] },
+            parse_quote! { #[serde(crate = "near_sdk::serde")] },
+        ];
+        if cfg!(feature = "__abi-generate") {
+            derives.extend([
+                parse_quote! { #[derive(near_sdk::__private::schemars::JsonSchema)] },
+                parse_quote! { #[schemars(crate = "near_sdk::__private::schemars")] },
+            ]);
+        }
+        let attrs = match item {
+            Item::Struct(input) => &mut input.attrs,
+            Item::Enum(input) => &mut input.attrs,
+            _ => unreachable!("serializers are only generated for structs and enums"),
+        };
+        // Derives go first, as their helper attributes can not be used before them.
+        attrs.splice(0..0, derives);
+    }
+    Ok(impls)
+}
+
+/// Removes `#[borsh_init]` and `#[borsh_skip]`, which are only known to the compiler when the
+/// `borsh` derives are used.
+fn remove_borsh_attrs(item: &mut Item) {
+    fn remove_from_fields(fields: &mut Fields) {
+        for field in fields.iter_mut() {
+            field.attrs.retain(|attr| !attr.path.is_ident("borsh_skip"));
+        }
+    }
+    match item {
+        Item::Struct(input) => {
+            input.attrs.retain(|attr| !attr.path.is_ident("borsh_init"));
+            remove_from_fields(&mut input.fields);
+        }
+        Item::Enum(input) => {
+            input.attrs.retain(|attr| !attr.path.is_ident("borsh_init"));
+            for variant in input.variants.iter_mut() {
+                remove_from_fields(&mut variant.fields);
+            }
+        }
+        _ => {}
+    }
+}
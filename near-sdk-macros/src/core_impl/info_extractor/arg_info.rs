@@ -1,7 +1,9 @@
 use crate::core_impl::info_extractor::serializer_attr::SerializerAttr;
-use crate::core_impl::info_extractor::SerializerType;
+use crate::core_impl::info_extractor::{
+    CallbackResultAttr, SerializerType, ValidateAttr, Validation,
+};
 use quote::ToTokens;
-use syn::{spanned::Spanned, Attribute, Error, Ident, Pat, PatType, Token, Type};
+use syn::{spanned::Spanned, Attribute, Error, Ident, Pat, PatType, Path, Token, Type};
 
 pub enum BindgenArgType {
     /// Argument that we read from `env::input()`.
@@ -34,6 +36,11 @@ pub struct ArgInfo {
     pub bindgen_ty: BindgenArgType,
     /// Type of serializer that we use for this argument.
     pub serializer_ty: SerializerType,
+    /// Function handling the error of a `#[callback_result(on_error = "..")]` argument, whose type
+    /// is then the type of the result it is given if the promise succeeded.
+    pub on_error: Option<Path>,
+    /// Checks on the value of the argument from `#[validate(..)]`.
+    pub validations: Vec<Validation>,
     /// The original `PatType` of the argument.
     pub original: PatType,
 }
@@ -69,6 +76,8 @@ impl ArgInfo {
         let mut bindgen_ty = BindgenArgType::Regular;
         // In the absence of serialization attributes this is a JSON serialization.
         let mut serializer_ty = SerializerType::JSON;
+        let mut on_error = None;
+        let mut validations = Vec::new();
         for attr in &mut original.attrs {
             let attr_str = attr.path.to_token_stream().to_string();
             match attr_str.as_str() {
@@ -77,10 +86,16 @@ impl ArgInfo {
                 }
                 "callback_result" => {
                     bindgen_ty = BindgenArgType::CallbackResultArg;
+                    let args: CallbackResultAttr = syn::parse2(attr.tokens.clone())?;
+                    on_error = args.on_error;
                 }
                 "callback_vec" => {
                     bindgen_ty = BindgenArgType::CallbackArgVec;
                 }
+                "validate" => {
+                    let validate: ValidateAttr = syn::parse2(attr.tokens.clone())?;
+                    validations.extend(validate.validations);
+                }
                 "serializer" => {
                     let serializer: SerializerAttr = syn::parse2(attr.tokens.clone())?;
                     serializer_ty = serializer.serializer_type;
@@ -98,8 +113,16 @@ impl ArgInfo {
                 && attr_str != "serializer"
                 && attr_str != "callback_result"
                 && attr_str != "callback_unwrap"
+                && attr_str != "validate"
         });
 
+        if !validations.is_empty() && !matches!(bindgen_ty, BindgenArgType::Regular) {
+            return Err(Error::new(
+                original.span(),
+                "#[validate] can only be used on arguments read from the input.",
+            ));
+        }
+
         Ok(Self {
             non_bindgen_attrs,
             ident,
@@ -110,6 +133,8 @@ impl ArgInfo {
             ty,
             bindgen_ty,
             serializer_ty,
+            on_error,
+            validations,
             original: original.clone(),
         })
     }
@@ -1,5 +1,7 @@
-use super::{ArgInfo, BindgenArgType, InitAttr, MethodType, SerializerAttr, SerializerType};
-use proc_macro2::Span;
+use super::{
+    ArgInfo, BindgenArgType, DeprecatedMethodAttr, Hook, HookAttr, InitAttr, MethodType, OnlyAttr,
+    PayableAttr, SerializerAttr, SerializerType,
+};
 use quote::ToTokens;
 use syn::spanned::Spanned;
 use syn::{Attribute, Error, FnArg, Ident, Receiver, ReturnType, Signature};
@@ -16,10 +18,22 @@ pub struct AttrSigInfo {
     pub method_type: MethodType,
     /// Whether method accepting $NEAR.
     pub is_payable: bool,
+    /// Constraints on the attached deposit of a payable method.
+    pub deposit: PayableAttr,
     /// Whether method can accept calls from self (current account)
     pub is_private: bool,
     /// Whether method returns Result type where only Ok type is serialized
     pub is_handles_result: bool,
+    /// Whether the state should only be written back if the method modified it.
+    pub is_read_mostly: bool,
+    /// Deprecation of the method, if it is marked with `#[deprecated_method]`.
+    pub deprecation: Option<DeprecatedMethodAttr>,
+    /// Roles any of which the predecessor must have, from `#[only(role = "..")]`.
+    pub only_roles: Vec<String>,
+    /// Function called before the method, from `#[before(..)]` or the impl section.
+    pub before: Option<HookAttr>,
+    /// Function called after the method, from `#[after(..)]` or the impl section.
+    pub after: Option<HookAttr>,
     /// The serializer that we use for `env::input()`.
     pub input_serializer: SerializerType,
     /// The serializer that we use for the return type.
@@ -62,8 +76,14 @@ impl AttrSigInfo {
         let mut args = vec![];
         let mut method_type = MethodType::Regular;
         let mut is_payable = false;
+        let mut deposit = PayableAttr::default();
         let mut is_private = false;
         let mut is_handles_result = false;
+        let mut read_mostly_attr = None;
+        let mut deprecation = None;
+        let mut only_attr = None;
+        let mut before = None;
+        let mut after = None;
         // By the default we serialize the result with JSON.
         let mut result_serializer = SerializerType::JSON;
 
@@ -80,6 +100,7 @@ impl AttrSigInfo {
                     }
                 }
                 "payable" => {
+                    deposit = syn::parse2(attr.tokens.clone())?;
                     payable_attr = Some(attr);
                     is_payable = true;
                 }
@@ -93,6 +114,22 @@ impl AttrSigInfo {
                 "handle_result" => {
                     is_handles_result = true;
                 }
+                "read_mostly" => {
+                    read_mostly_attr = Some(attr);
+                }
+                "deprecated_method" => {
+                    deprecation = Some(syn::parse2(attr.tokens.clone())?);
+                }
+                "only" => {
+                    let parsed: OnlyAttr = syn::parse2(attr.tokens.clone())?;
+                    only_attr = Some((attr, parsed.roles));
+                }
+                "before" => {
+                    before = Some((attr, syn::parse2(attr.tokens.clone())?));
+                }
+                "after" => {
+                    after = Some((attr, syn::parse2(attr.tokens.clone())?));
+                }
                 _ => {
                     non_bindgen_attrs.push((*attr).clone());
                 }
@@ -131,6 +168,41 @@ impl AttrSigInfo {
             }
         }
 
+        let is_read_mostly = read_mostly_attr.is_some();
+        if let Some(read_mostly_attr) = read_mostly_attr {
+            if !matches!(&receiver, Some(Receiver { reference: Some(_), mutability: Some(_), .. }))
+            {
+                return Err(Error::new(
+                    read_mostly_attr.span(),
+                    "Read-mostly method must take `&mut self`",
+                ));
+            }
+        }
+
+        let only_roles = match only_attr {
+            Some((attr, _)) if receiver.is_none() => {
+                return Err(Error::new(
+                    attr.span(),
+                    "Methods restricted to roles must take `self`",
+                ));
+            }
+            Some((_, roles)) => roles,
+            None => Vec::new(),
+        };
+
+        let [before, after] = [before, after].map(|hook| match hook {
+            Some((attr, HookAttr::Call(Hook::Method(_))))
+                if !matches!(&receiver, Some(Receiver { reference: Some(_), .. })) =>
+            {
+                Err(Error::new(
+                    attr.span(),
+                    "Hooks calling a method of the contract require the method to take `&self` or `&mut self`",
+                ))
+            }
+            hook => Ok(hook.map(|(_, hook)| hook)),
+        });
+        let (before, after) = (before?, after?);
+
         *original_attrs = non_bindgen_attrs.clone();
         let returns = original_sig.output.clone();
 
@@ -141,26 +213,26 @@ impl AttrSigInfo {
             input_serializer: SerializerType::JSON,
             method_type,
             is_payable,
+            deposit,
             is_private,
             is_handles_result,
+            is_read_mostly,
+            deprecation,
+            only_roles,
+            before,
+            after,
             result_serializer,
             receiver,
             returns,
             original_sig: original_sig.clone(),
         };
 
-        let input_serializer =
-            if result.input_args().all(|arg: &ArgInfo| arg.serializer_ty == SerializerType::JSON) {
-                SerializerType::JSON
-            } else if result.input_args().all(|arg| arg.serializer_ty == SerializerType::Borsh) {
-                SerializerType::Borsh
-            } else {
-                return Err(Error::new(
-                    Span::call_site(),
-                    "Input arguments should be all of the same serialization type.",
-                ));
-            };
-        result.input_serializer = input_serializer;
+        // Mixed input starts with the JSON arguments, so it is treated as JSON input.
+        if result.input_args().next().is_some()
+            && result.input_args().all(|arg| arg.serializer_ty == SerializerType::Borsh)
+        {
+            result.input_serializer = SerializerType::Borsh;
+        }
         Ok(result)
     }
 
@@ -168,4 +240,11 @@ impl AttrSigInfo {
     pub fn input_args(&self) -> impl Iterator<Item = &ArgInfo> {
         self.args.iter().filter(|arg| matches!(arg.bindgen_ty, BindgenArgType::Regular))
     }
+
+    /// Whether the input arguments use both serializers, in which case the input is the JSON
+    /// object of the JSON arguments followed by the Borsh encoding of the Borsh arguments.
+    pub fn is_mixed_input(&self) -> bool {
+        self.input_args().any(|arg| arg.serializer_ty == SerializerType::JSON)
+            && self.input_args().any(|arg| arg.serializer_ty == SerializerType::Borsh)
+    }
 }
@@ -0,0 +1,128 @@
+use super::{ContractMetadataAttr, Hook};
+use proc_macro2::TokenStream;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Ident, Path, Token, Type};
+
+/// Arguments of the `#[near_bindgen]` attribute.
+#[derive(Default)]
+pub struct BindgenAttr {
+    /// Concrete type of the contract, for generic impl sections.
+    pub contract: Option<Type>,
+    /// Whether the impl section is a component, exported by the contracts that use it.
+    pub component: bool,
+    /// Components exported by the contract, on the contract struct.
+    pub components: Vec<Path>,
+    /// Function called before each method of the impl section, from `before(call = "..")`.
+    pub before: Option<Hook>,
+    /// Function called after each method of the impl section, from `after(call = "..")`.
+    pub after: Option<Hook>,
+    /// Whether the impl section generates the `execute_batch` method calling its methods.
+    pub multicall: bool,
+    /// Source metadata of the contract, on the contract struct, from `contract_metadata(..)`.
+    pub contract_metadata: Option<ContractMetadataAttr>,
+}
+
+impl Parse for BindgenAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+        let mut first = true;
+        while !input.is_empty() {
+            let key = if input.peek(Ident) { Some(input.fork().parse::<Ident>()?) } else { None };
+            match key {
+                Some(key) if key == "contract" && input.peek2(Token![=]) => {
+                    input.parse::<Ident>()?;
+                    input.parse::<Token![=]>()?;
+                    args.contract = Some(input.parse()?);
+                }
+                Some(key) if key == "component" && (input.peek2(Token![,]) || is_last(input)) => {
+                    input.parse::<Ident>()?;
+                    args.component = true;
+                }
+                Some(key) if key == "multicall" && (input.peek2(Token![,]) || is_last(input)) => {
+                    input.parse::<Ident>()?;
+                    args.multicall = true;
+                }
+                Some(key) if key == "components" && input.peek2(syn::token::Paren) => {
+                    input.parse::<Ident>()?;
+                    let content;
+                    parenthesized!(content in input);
+                    args.components = Punctuated::<Path, Token![,]>::parse_terminated(&content)?
+                        .into_iter()
+                        .collect();
+                }
+                Some(key)
+                    if (key == "before" || key == "after") && input.peek2(syn::token::Paren) =>
+                {
+                    input.parse::<Ident>()?;
+                    let content;
+                    parenthesized!(content in input);
+                    let hook = Some(content.parse()?);
+                    if key == "before" {
+                        args.before = hook;
+                    } else {
+                        args.after = hook;
+                    }
+                }
+                Some(key) if key == "contract_metadata" && input.peek2(syn::token::Paren) => {
+                    input.parse::<Ident>()?;
+                    let content;
+                    parenthesized!(content in input);
+                    args.contract_metadata = Some(content.parse()?);
+                }
+                _ if first => {
+                    // Other arguments have always been accepted and ignored, so they are skipped.
+                    input.parse::<TokenStream>()?;
+                    return Ok(args);
+                }
+                _ => return Err(input.error("Unexpected near_bindgen attribute.")),
+            }
+            first = false;
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        if args.component
+            && (args.contract.is_some()
+                || !args.components.is_empty()
+                || args.before.is_some()
+                || args.after.is_some()
+                || args.multicall
+                || args.contract_metadata.is_some())
+        {
+            return Err(input.error("`component` can not be combined with other arguments."));
+        }
+        Ok(args)
+    }
+}
+
+fn is_last(input: ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Ident>().is_ok() && fork.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BindgenAttr;
+    use quote::ToTokens;
+
+    #[test]
+    fn arguments() {
+        let args: BindgenAttr = syn::parse_str("contract = Skeleton<Fixed>").unwrap();
+        assert_eq!(args.contract.to_token_stream().to_string(), "Skeleton < Fixed >");
+        assert!(syn::parse_str::<BindgenAttr>("component").unwrap().component);
+        let args: BindgenAttr = syn::parse_str("components(pausable::Pausable, Ownable)").unwrap();
+        assert_eq!(args.components.len(), 2);
+        // Unknown arguments are ignored.
+        let args: BindgenAttr = syn::parse_str("init => new").unwrap();
+        assert!(args.contract.is_none() && !args.component && args.components.is_empty());
+        assert!(syn::parse_str::<BindgenAttr>("contract = Skeleton<Fixed>, component").is_err());
+        let args: BindgenAttr = syn::parse_str(
+            "contract = Skeleton<Fixed>, before(call = \"self.assert_not_paused\"), after(call = \"log\")",
+        )
+        .unwrap();
+        assert!(args.contract.is_some() && args.before.is_some() && args.after.is_some());
+        assert!(syn::parse_str::<BindgenAttr>("multicall").unwrap().multicall);
+        assert!(syn::parse_str::<BindgenAttr>("before(call = \"check\"), init => new").is_err());
+    }
+}
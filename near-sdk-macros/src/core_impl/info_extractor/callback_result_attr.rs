@@ -0,0 +1,36 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Error, LitStr, Path, Token};
+
+/// Arguments of `#[callback_result]`, e.g. `#[callback_result(on_error = "Self::rollback")]`.
+#[derive(Default)]
+pub struct CallbackResultAttr {
+    /// Function called in place of the method if the promise failed.
+    pub on_error: Option<Path>,
+}
+
+impl Parse for CallbackResultAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(Self::default());
+        }
+        let content;
+        parenthesized!(content in input);
+        let key: Ident = content.parse()?;
+        if key != "on_error" {
+            return Err(Error::new(key.span(), "Unsupported callback_result attribute."));
+        }
+        content.parse::<Token![=]>()?;
+        let handler: LitStr = content.parse()?;
+        let on_error = handler.parse::<Path>().map_err(|_| {
+            Error::new(
+                handler.span(),
+                "Expected the path of the error handler, as in `Self::rollback`.",
+            )
+        })?;
+        if !content.is_empty() {
+            return Err(content.error("Unexpected callback_result attribute."));
+        }
+        Ok(Self { on_error: Some(on_error) })
+    }
+}
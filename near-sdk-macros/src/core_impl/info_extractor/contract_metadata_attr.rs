@@ -0,0 +1,97 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Error, LitStr, Token};
+
+/// Source metadata of the contract as specified by NEP-330, from
+/// `contract_metadata(version = "..", link = "..", standard(standard = "..", version = ".."))`,
+/// none of which are required.
+#[derive(Default)]
+pub struct ContractMetadataAttr {
+    /// Version of the source code, instead of the version of the package.
+    pub version: Option<String>,
+    /// Link to the source code, instead of the repository of the package.
+    pub link: Option<String>,
+    /// Names and versions of the standards implemented by the contract.
+    pub standards: Vec<(String, String)>,
+}
+
+impl Parse for ContractMetadataAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = Self::default();
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            let duplicate = match key.to_string().as_str() {
+                "version" => attr.version.replace(parse_value(input)?).is_some(),
+                "link" => attr.link.replace(parse_value(input)?).is_some(),
+                "standard" => {
+                    let content;
+                    parenthesized!(content in input);
+                    let (mut standard, mut version) = (None, None);
+                    while !content.is_empty() {
+                        let key: Ident = content.parse()?;
+                        let duplicate = match key.to_string().as_str() {
+                            "standard" => standard.replace(parse_value(&content)?).is_some(),
+                            "version" => version.replace(parse_value(&content)?).is_some(),
+                            _ => {
+                                return Err(Error::new(
+                                    key.span(),
+                                    "Expected `standard` or `version`.",
+                                ))
+                            }
+                        };
+                        if duplicate {
+                            return Err(Error::new(key.span(), "Duplicate standard attribute."));
+                        }
+                        if !content.is_empty() {
+                            content.parse::<Token![,]>()?;
+                        }
+                    }
+                    match (standard, version) {
+                        (Some(standard), Some(version)) => attr.standards.push((standard, version)),
+                        _ => {
+                            return Err(Error::new(
+                                key.span(),
+                                "Standards need a name and a version, as in `standard(standard = \"nep171\", version = \"1.0.0\")`.",
+                            ))
+                        }
+                    }
+                    false
+                }
+                _ => {
+                    return Err(Error::new(key.span(), "Unsupported contract_metadata attribute."))
+                }
+            };
+            if duplicate {
+                return Err(Error::new(key.span(), "Duplicate contract_metadata attribute."));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attr)
+    }
+}
+
+fn parse_value(input: ParseStream) -> syn::Result<String> {
+    input.parse::<Token![=]>()?;
+    Ok(input.parse::<LitStr>()?.value())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContractMetadataAttr;
+
+    #[test]
+    fn contract_metadata() {
+        let attr: ContractMetadataAttr = syn::parse_str(
+            r#"link = "https://example.com", standard(standard = "nep171", version = "1.0.0")"#,
+        )
+        .unwrap();
+        assert_eq!(attr.version, None);
+        assert_eq!(attr.link.as_deref(), Some("https://example.com"));
+        assert_eq!(attr.standards, [("nep171".to_string(), "1.0.0".to_string())]);
+        assert!(syn::parse_str::<ContractMetadataAttr>(r#"standard(standard = "nep171")"#).is_err());
+        assert!(syn::parse_str::<ContractMetadataAttr>(r#"version = "1", version = "2""#).is_err());
+        assert!(syn::parse_str::<ContractMetadataAttr>(r#"license = "MIT""#).is_err());
+    }
+}
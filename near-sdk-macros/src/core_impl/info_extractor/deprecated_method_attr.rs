@@ -0,0 +1,103 @@
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, LitStr, Token};
+
+/// Arguments of `#[deprecated_method(since = "..", use = "..", removed_with_feature = "..")]`,
+/// all of which are optional.
+#[derive(Default)]
+pub struct DeprecatedMethodAttr {
+    /// Version of the contract since which the method is deprecated.
+    pub since: Option<String>,
+    /// Method that should be called instead.
+    pub use_instead: Option<String>,
+    /// Cargo feature of the contract which removes the method from the contract API.
+    pub removed_with_feature: Option<String>,
+}
+
+impl Parse for DeprecatedMethodAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = Self::default();
+        if input.is_empty() {
+            return Ok(attr);
+        }
+        let content;
+        syn::parenthesized!(content in input);
+        while !content.is_empty() {
+            // `use` is a keyword, so it is parsed with `parse_any`.
+            let key = content.call(Ident::parse_any)?;
+            content.parse::<Token![=]>()?;
+            let value: LitStr = content.parse()?;
+            let field = match key.to_string().as_str() {
+                "since" => &mut attr.since,
+                "use" => &mut attr.use_instead,
+                "removed_with_feature" => &mut attr.removed_with_feature,
+                _ => {
+                    return Err(Error::new(key.span(), "Unsupported deprecated_method attribute."))
+                }
+            };
+            if field.replace(value.value()).is_some() {
+                return Err(Error::new(key.span(), "Duplicate deprecated_method attribute."));
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attr)
+    }
+}
+
+impl DeprecatedMethodAttr {
+    /// Human-readable deprecation notice of the method.
+    #[cfg(feature = "__abi-generate")]
+    pub fn notice(&self) -> String {
+        let mut notice = "Deprecated".to_string();
+        if let Some(since) = &self.since {
+            notice.push_str(&format!(" since {}", since));
+        }
+        if let Some(use_instead) = &self.use_instead {
+            notice.push_str(&format!(", use `{}` instead", use_instead));
+        }
+        notice.push('.');
+        notice
+    }
+
+    /// Attribute that removes the exported method when the `removed_with_feature` is enabled.
+    pub fn removal_cfg(&self) -> TokenStream2 {
+        match &self.removed_with_feature {
+            Some(feature) => quote! { #[cfg(not(feature = #feature))] },
+            None => quote! {},
+        }
+    }
+
+    /// The NEP-297 event logged when the method is called, which is known at compile time.
+    pub fn event_log(&self, method: &Ident) -> String {
+        let mut data = format!("\"method\":{}", json_string(&method.to_string()));
+        if let Some(since) = &self.since {
+            data.push_str(&format!(",\"since\":{}", json_string(since)));
+        }
+        if let Some(use_instead) = &self.use_instead {
+            data.push_str(&format!(",\"use\":{}", json_string(use_instead)));
+        }
+        format!(
+            "EVENT_JSON:{{\"standard\":\"near-sdk\",\"version\":\"1.0.0\",\"event\":\"method_deprecated\",\"data\":{{{}}}}}",
+            data
+        )
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
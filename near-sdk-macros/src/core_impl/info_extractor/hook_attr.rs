@@ -0,0 +1,82 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Error, Expr, ExprField, ExprPath, LitStr, Member, Path, Token};
+
+/// Function called before or after a method, from `call = ".."`.
+#[derive(Clone)]
+pub enum Hook {
+    /// Method of the contract, called on the contract the method is called on, from `self.name`.
+    Method(Ident),
+    /// Function without arguments, from its path, where `Self` is the contract type.
+    Function(Path),
+}
+
+/// `#[before(..)]` or `#[after(..)]` on a method.
+pub enum HookAttr {
+    /// Hook of the method, from `(call = "..")`.
+    Call(Hook),
+    /// Opts the method out of the hook of its impl section, from `(skip)`.
+    Skip,
+}
+
+impl Parse for HookAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        if content.peek(syn::Ident) && content.peek2(Token![=]) {
+            return Ok(Self::Call(content.parse()?));
+        }
+        let key: Ident = content.parse()?;
+        if key != "skip" || !content.is_empty() {
+            return Err(Error::new(key.span(), "Expected `call = \"..\"` or `skip`."));
+        }
+        Ok(Self::Skip)
+    }
+}
+
+impl Parse for Hook {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "call" {
+            return Err(Error::new(key.span(), "Expected `call = \"..\"`."));
+        }
+        input.parse::<Token![=]>()?;
+        let call: LitStr = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("Unexpected hook attribute."));
+        }
+        let error = || {
+            Error::new(
+                call.span(),
+                "Expected a method of the contract, as in `self.assert_not_paused`, or the path \
+                of a function.",
+            )
+        };
+        match call.parse::<Expr>().map_err(|_| error())? {
+            Expr::Field(ExprField { base, member: Member::Named(method), .. }) if matches!(&*base, Expr::Path(ExprPath { path, .. }) if path.is_ident("self")) => {
+                Ok(Self::Method(method))
+            }
+            Expr::Path(ExprPath { qself: None, path, .. }) if !path.is_ident("self") => {
+                Ok(Self::Function(path))
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hook, HookAttr};
+
+    #[test]
+    fn hooks() {
+        let attr: HookAttr = syn::parse_str("(call = \"self.assert_not_paused\")").unwrap();
+        assert!(matches!(attr, HookAttr::Call(Hook::Method(m)) if m == "assert_not_paused"));
+        let attr: HookAttr = syn::parse_str("(call = \"Self::log_call\")").unwrap();
+        assert!(matches!(attr, HookAttr::Call(Hook::Function(_))));
+        assert!(matches!(syn::parse_str("(skip)").unwrap(), HookAttr::Skip));
+        assert!(syn::parse_str::<HookAttr>("(call = \"self\")").is_err());
+        assert!(syn::parse_str::<HookAttr>("(call = \"self.check()\")").is_err());
+        assert!(syn::parse_str::<HookAttr>("(run = \"check\")").is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::core_impl::info_extractor::AttrSigInfo;
-use syn::{ImplItemMethod, Type, Visibility};
+use quote::quote;
+use syn::{parse_quote, ImplItemMethod, Type, Visibility};
 
 /// Information extracted from `ImplItemMethod`.
 pub struct ImplItemMethodInfo {
@@ -16,6 +17,17 @@ impl ImplItemMethodInfo {
     pub fn new(original: &mut ImplItemMethod, struct_type: Type) -> syn::Result<Self> {
         let ImplItemMethod { attrs, sig, .. } = original;
         let attr_signature_info = AttrSigInfo::new(attrs, sig)?;
+        if !attr_signature_info.only_roles.is_empty() {
+            // The check is part of the method itself, so that it also applies to calls from Rust.
+            let roles = &attr_signature_info.only_roles;
+            let check = quote! {
+                {
+                    use near_sdk::acl::AccessControl as _;
+                    self.acl().assert_any_role(&[#(#roles),*]);
+                }
+            };
+            original.block.stmts.insert(0, parse_quote!(#check));
+        }
         let is_public = matches!(original.vis, Visibility::Public(_));
         Ok(Self { attr_signature_info, is_public, struct_type })
     }
@@ -1,6 +1,10 @@
+use crate::core_impl::info_extractor::{Hook, HookAttr};
 use crate::ImplItemMethodInfo;
+use syn::fold::{self, Fold};
 use syn::spanned::Spanned;
-use syn::{Error, ImplItem, ItemImpl, Type};
+use syn::{
+    Error, GenericArgument, GenericParam, Ident, ImplItem, ItemImpl, PathArguments, Type, TypePath,
+};
 
 /// Information extracted from `impl` section.
 pub struct ItemImplInfo {
@@ -13,23 +17,159 @@ pub struct ItemImplInfo {
 }
 
 impl ItemImplInfo {
-    pub fn new(original: &mut ItemImpl) -> syn::Result<Self> {
-        if !original.generics.params.is_empty() {
-            return Err(Error::new(
-                original.generics.params.span(),
-                "Impl type parameters are not supported for smart contracts.",
-            ));
-        }
+    pub fn new(original: &mut ItemImpl, contract: Option<Type>) -> syn::Result<Self> {
         let is_trait_impl = original.trait_.is_some();
-        let ty = (*original.self_ty.as_ref()).clone();
+        let ty = (*original.self_ty).clone();
+        // Type the contract state is read as by the exported methods.
+        let (contract, type_params) = match contract {
+            None => {
+                if !original.generics.params.is_empty() {
+                    return Err(Error::new(
+                        original.generics.params.span(),
+                        "Generic impl sections must name the concrete type of the contract, as in `#[near_bindgen(contract = Contract<Strategy>)]`.",
+                    ));
+                }
+                (ty.clone(), TypeParams(vec![]))
+            }
+            Some(contract) => {
+                if original.generics.params.is_empty() {
+                    return Err(Error::new(
+                        contract.span(),
+                        "The contract type is only needed on generic impl sections.",
+                    ));
+                }
+                let type_params = TypeParams::resolve(original, &contract)?;
+                (contract, type_params)
+            }
+        };
 
         let mut methods = vec![];
         for subitem in &mut original.items {
             if let ImplItem::Method(m) = subitem {
-                let method_info = ImplItemMethodInfo::new(m, ty.clone())?;
+                let method_info = if type_params.0.is_empty() {
+                    ImplItemMethodInfo::new(m, contract.clone())?
+                } else {
+                    // The exported method is generated from the signature with the type parameters
+                    // replaced by the concrete types, while the generic method is kept as is.
+                    let mut concrete = m.clone();
+                    concrete.sig = type_params.clone().fold_signature(concrete.sig);
+                    ImplItemMethodInfo::new(m, contract.clone())?;
+                    ImplItemMethodInfo::new(&mut concrete, contract.clone())?
+                };
                 methods.push(method_info);
             }
         }
         Ok(Self { is_trait_impl, ty, methods })
     }
+
+    /// Sets the hooks of the impl section on the methods that do not have their own. Hooks calling
+    /// a method of the contract are only set on the methods taking `&self` or `&mut self`.
+    pub fn set_default_hooks(&mut self, before: Option<&Hook>, after: Option<&Hook>) {
+        for method in &mut self.methods {
+            let info = &mut method.attr_signature_info;
+            let has_receiver = matches!(&info.receiver, Some(r) if r.reference.is_some());
+            for (hook, default) in [(&mut info.before, before), (&mut info.after, after)] {
+                match default {
+                    Some(Hook::Method(_)) if !has_receiver => {}
+                    Some(default) if hook.is_none() => {
+                        *hook = Some(HookAttr::Call(default.clone()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Concrete types of the type parameters of a generic `impl` section.
+#[derive(Clone)]
+struct TypeParams(Vec<(Ident, Type)>);
+
+impl TypeParams {
+    /// Matches the generic arguments of the type of the `impl` section with those of the contract
+    /// type, e.g. `S` with `MyStrategy` for `impl<S: Strategy> Skeleton<S>` and
+    /// `Skeleton<MyStrategy>`, or the type of a blanket `impl` section with the contract type.
+    fn resolve(original: &ItemImpl, contract: &Type) -> syn::Result<Self> {
+        // Blanket impls, as written for components, are exported for the contract type itself.
+        if let Type::Path(TypePath { qself: None, path }) = &*original.self_ty {
+            if let Some(ident) = path.get_ident() {
+                if original.generics.type_params().any(|param| &param.ident == ident) {
+                    if original.generics.params.len() > 1 {
+                        return Err(Error::new(
+                            original.generics.params.span(),
+                            "Blanket impl sections can only have the type parameter of the contract.",
+                        ));
+                    }
+                    return Ok(Self(vec![(ident.clone(), contract.clone())]));
+                }
+            }
+        }
+        let generic_args = |ty: &Type| match ty {
+            Type::Path(TypePath { qself: None, path }) => {
+                path.segments.last().map(|segment| match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => {
+                        (segment.ident.clone(), args.args.iter().cloned().collect::<Vec<_>>())
+                    }
+                    _ => (segment.ident.clone(), vec![]),
+                })
+            }
+            _ => None,
+        };
+        let (self_args, contract_args) =
+            match (generic_args(&original.self_ty), generic_args(contract)) {
+                (Some((self_ident, self_args)), Some((contract_ident, contract_args)))
+                    if self_ident == contract_ident && self_args.len() == contract_args.len() =>
+                {
+                    (self_args, contract_args)
+                }
+                _ => {
+                    return Err(Error::new(
+                        contract.span(),
+                        "The contract type must be the type of the impl section with concrete generic arguments.",
+                    ))
+                }
+            };
+
+        let mut params = vec![];
+        for param in &original.generics.params {
+            let ident = match param {
+                GenericParam::Type(param) => &param.ident,
+                GenericParam::Lifetime(_) | GenericParam::Const(_) => {
+                    return Err(Error::new(
+                        param.span(),
+                        "Only type parameters are supported on generic impl sections.",
+                    ))
+                }
+            };
+            let concrete = self_args.iter().zip(&contract_args).find_map(|args| match args {
+                (GenericArgument::Type(Type::Path(path)), GenericArgument::Type(concrete))
+                    if path.qself.is_none() && path.path.is_ident(ident) =>
+                {
+                    Some(concrete.clone())
+                }
+                _ => None,
+            });
+            match concrete {
+                Some(concrete) => params.push((ident.clone(), concrete)),
+                None => {
+                    return Err(Error::new(
+                        ident.span(),
+                        "The type parameter must be a generic argument of the type of the impl section.",
+                    ))
+                }
+            }
+        }
+        Ok(Self(params))
+    }
+}
+
+impl Fold for TypeParams {
+    fn fold_type(&mut self, ty: Type) -> Type {
+        if let Type::Path(TypePath { qself: None, path }) = &ty {
+            if let Some((_, concrete)) = self.0.iter().find(|(ident, _)| path.is_ident(ident)) {
+                return concrete.clone();
+            }
+        }
+        fold::fold_type(self, ty)
+    }
 }
@@ -1,7 +1,10 @@
 use super::TraitItemMethodInfo;
+use crate::core_impl::utils;
 use inflector::Inflector;
+use quote::ToTokens;
+use std::collections::HashSet;
 use syn::spanned::Spanned;
-use syn::{Error, Ident, ItemTrait, TraitItem};
+use syn::{Error, GenericParam, Generics, Ident, ItemTrait, Signature, TraitItem};
 
 /// Information extracted from `ItemTrait`.
 pub struct ItemTraitInfo {
@@ -30,7 +33,11 @@ impl ItemTraitInfo {
                     ))
                 }
                 TraitItem::Method(method) => {
-                    methods.push(TraitItemMethodInfo::new(method)?);
+                    let mut method_info = TraitItemMethodInfo::new(method)?;
+                    // The ext method is generic over the type parameters of the trait it uses.
+                    let sig = &mut method_info.attr_sig_info.original_sig;
+                    sig.generics = merge_generics(&original.generics, sig);
+                    methods.push(method_info);
                     if method.default.is_some() {
                         return Err(Error::new(
                             method.span(),
@@ -46,3 +53,42 @@ impl ItemTraitInfo {
         Ok(Self { original: original.clone(), mod_name, methods })
     }
 }
+
+/// Adds the generic parameters of the trait used by the arguments of the method, along with the
+/// where-clause predicates on them, to the generics of the method. The result of the method is not
+/// part of the ext method, which returns a promise.
+fn merge_generics(trait_generics: &Generics, sig: &Signature) -> Generics {
+    let mut mentioned = HashSet::new();
+    utils::collect_idents(sig.inputs.to_token_stream(), &mut mentioned);
+    utils::collect_idents(sig.generics.to_token_stream(), &mut mentioned);
+    if let Some(where_clause) = &sig.generics.where_clause {
+        utils::collect_idents(where_clause.to_token_stream(), &mut mentioned);
+    }
+    let (used, unused): (Vec<_>, Vec<_>) = trait_generics
+        .params
+        .iter()
+        .partition(|param| mentioned.contains(&utils::param_ident(param)));
+    if used.is_empty() {
+        return sig.generics.clone();
+    }
+    let unused: HashSet<_> = unused.into_iter().map(utils::param_ident).collect();
+
+    let mut generics = sig.generics.clone();
+    let params = std::mem::take(&mut generics.params);
+    let (lifetimes, others): (Vec<_>, Vec<_>) = used
+        .into_iter()
+        .cloned()
+        .chain(params)
+        .partition(|param| matches!(param, GenericParam::Lifetime(_)));
+    generics.params = lifetimes.into_iter().chain(others).collect();
+    if let Some(trait_where_clause) = &trait_generics.where_clause {
+        let predicates = trait_where_clause.predicates.iter().filter(|predicate| {
+            let mut idents = HashSet::new();
+            utils::collect_idents(predicate.to_token_stream(), &mut idents);
+            idents.iter().any(|ident| mentioned.contains(ident))
+                && !idents.iter().any(|ident| unused.contains(ident))
+        });
+        generics.make_where_clause().predicates.extend(predicates.cloned());
+    }
+    generics
+}
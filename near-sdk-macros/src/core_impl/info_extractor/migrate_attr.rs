@@ -0,0 +1,24 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, Token, Type};
+
+/// Arguments of `#[migrate(from = OldState)]`.
+pub struct MigrateAttr {
+    /// Type of the state to migrate from.
+    pub from: Type,
+}
+
+impl Parse for MigrateAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "from" {
+            return Err(Error::new(key.span(), "Unsupported migrate attribute."));
+        }
+        input.parse::<Token![=]>()?;
+        let from = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("Unexpected migrate attribute."));
+        }
+        Ok(Self { from })
+    }
+}
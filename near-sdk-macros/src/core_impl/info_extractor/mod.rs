@@ -21,6 +21,36 @@ mod item_impl_info;
 mod init_attr;
 pub use init_attr::InitAttr;
 
+mod deprecated_method_attr;
+pub use deprecated_method_attr::DeprecatedMethodAttr;
+
+mod near_attr;
+pub use near_attr::NearAttr;
+
+mod only_attr;
+pub use only_attr::OnlyAttr;
+
+mod payable_attr;
+pub use payable_attr::PayableAttr;
+
+mod migrate_attr;
+pub use migrate_attr::MigrateAttr;
+
+mod bindgen_attr;
+pub use bindgen_attr::BindgenAttr;
+
+mod callback_result_attr;
+pub use callback_result_attr::CallbackResultAttr;
+
+mod validate_attr;
+pub use validate_attr::{ValidateAttr, Validation};
+
+mod hook_attr;
+pub use hook_attr::{Hook, HookAttr};
+
+mod contract_metadata_attr;
+pub use contract_metadata_attr::ContractMetadataAttr;
+
 pub use item_impl_info::ItemImplInfo;
 
 /// Type of serialization we use.
@@ -0,0 +1,57 @@
+use super::SerializerType;
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, Error, Token};
+
+/// Arguments of the `#[near]` attribute on a struct or an enum.
+pub struct NearAttr {
+    /// Whether the type is the state of the contract.
+    pub contract_state: bool,
+    /// Serializers to implement for the type, `None` if not given.
+    pub serializers: Option<Vec<SerializerType>>,
+}
+
+impl Parse for NearAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut contract_state = false;
+        let mut serializers = None;
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            match ident.to_string().as_str() {
+                "contract_state" if !contract_state => contract_state = true,
+                "serializers" if serializers.is_none() => {
+                    input.parse::<Token![=]>()?;
+                    let content;
+                    let _bracket_token = bracketed!(content in input);
+                    let mut types = Vec::new();
+                    for ident in Punctuated::<Ident, Token![,]>::parse_terminated(&content)? {
+                        let serializer_type = match ident.to_string().as_str() {
+                            "borsh" => SerializerType::Borsh,
+                            "json" => SerializerType::JSON,
+                            _ => {
+                                return Err(Error::new(
+                                    ident.span(),
+                                    "Unsupported serializer type.",
+                                ))
+                            }
+                        };
+                        if types.contains(&serializer_type) {
+                            return Err(Error::new(ident.span(), "Duplicate serializer type."));
+                        }
+                        types.push(serializer_type);
+                    }
+                    serializers = Some(types);
+                }
+                "contract_state" | "serializers" => {
+                    return Err(Error::new(ident.span(), "Duplicate near attribute argument."))
+                }
+                _ => return Err(Error::new(ident.span(), "Unsupported near attribute argument.")),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self { contract_state, serializers })
+    }
+}
@@ -0,0 +1,33 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, LitStr, Token};
+
+/// Arguments of `#[only(role = "..", role = "..")]`, the roles any of which the predecessor must
+/// have to call the method.
+pub struct OnlyAttr {
+    pub roles: Vec<String>,
+}
+
+impl Parse for OnlyAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        syn::parenthesized!(content in input);
+        let mut roles = Vec::new();
+        while !content.is_empty() {
+            let key: Ident = content.parse()?;
+            if key != "role" {
+                return Err(Error::new(key.span(), "Unsupported only attribute."));
+            }
+            content.parse::<Token![=]>()?;
+            let value: LitStr = content.parse()?;
+            roles.push(value.value());
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        if roles.is_empty() {
+            return Err(Error::new(input.span(), "Expected at least one `role = \"..\"`."));
+        }
+        Ok(Self { roles })
+    }
+}
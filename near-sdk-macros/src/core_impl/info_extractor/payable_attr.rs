@@ -0,0 +1,103 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::token::Paren;
+use syn::{Error, LitStr, Token};
+
+/// Constraints on the attached deposit from `#[payable(..)]`, none of which are required.
+#[derive(Default)]
+pub struct PayableAttr {
+    /// Whether the deposit must be exactly one yoctoNEAR, from `exactly_one_yocto`.
+    pub exactly_one_yocto: bool,
+    /// Minimum deposit in yoctoNEAR, with the amount as written, from `min = ".."`.
+    pub min: Option<(u128, String)>,
+    /// Whether the deposit above the minimum is refunded to the predecessor, from `refund_excess`.
+    pub refund_excess: bool,
+}
+
+impl Parse for PayableAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = Self::default();
+        if !input.peek(Paren) {
+            return Ok(attr);
+        }
+        let content;
+        syn::parenthesized!(content in input);
+        while !content.is_empty() {
+            let key: Ident = content.parse()?;
+            let duplicate = match key.to_string().as_str() {
+                "exactly_one_yocto" => std::mem::replace(&mut attr.exactly_one_yocto, true),
+                "refund_excess" => std::mem::replace(&mut attr.refund_excess, true),
+                "min" => {
+                    content.parse::<Token![=]>()?;
+                    let value: LitStr = content.parse()?;
+                    let amount = parse_amount(&value.value()).ok_or_else(|| {
+                        Error::new(
+                            value.span(),
+                            "Expected an amount like \"0.1 NEAR\" or \"1000 yoctoNEAR\".",
+                        )
+                    })?;
+                    attr.min.replace((amount, value.value())).is_some()
+                }
+                _ => return Err(Error::new(key.span(), "Unsupported payable attribute.")),
+            };
+            if duplicate {
+                return Err(Error::new(key.span(), "Duplicate payable attribute."));
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        if attr.exactly_one_yocto && (attr.min.is_some() || attr.refund_excess) {
+            return Err(Error::new(
+                input.span(),
+                "`exactly_one_yocto` can not be combined with other payable attributes.",
+            ));
+        }
+        if attr.refund_excess && attr.min.is_none() {
+            return Err(Error::new(input.span(), "`refund_excess` requires a `min` deposit."));
+        }
+        Ok(attr)
+    }
+}
+
+/// Parses an amount of NEAR like `0.1 NEAR` or `1000 yoctoNEAR` to yoctoNEAR.
+fn parse_amount(amount: &str) -> Option<u128> {
+    const NEAR_DECIMALS: usize = 24;
+    let (number, unit) = amount.trim().split_once(' ')?;
+    match unit.trim() {
+        "yoctoNEAR" => number.parse().ok(),
+        "NEAR" => {
+            let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+            let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+            if whole.is_empty()
+                || !digits(whole)
+                || !digits(fraction)
+                || fraction.len() > NEAR_DECIMALS
+            {
+                return None;
+            }
+            let whole: u128 = whole.parse().ok()?;
+            let fraction: u128 =
+                format!("{:0<width$}", fraction, width = NEAR_DECIMALS).parse().ok()?;
+            whole.checked_mul(10u128.pow(NEAR_DECIMALS as u32))?.checked_add(fraction)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_amount;
+
+    #[test]
+    fn amounts() {
+        assert_eq!(parse_amount("1 NEAR"), Some(10u128.pow(24)));
+        assert_eq!(parse_amount("0.1 NEAR"), Some(10u128.pow(23)));
+        assert_eq!(parse_amount("2.000000000000000000000001 NEAR"), Some(2 * 10u128.pow(24) + 1));
+        assert_eq!(parse_amount("1000 yoctoNEAR"), Some(1000));
+        assert_eq!(parse_amount("0.0000000000000000000000001 NEAR"), None);
+        assert_eq!(parse_amount(".5 NEAR"), None);
+        assert_eq!(parse_amount("1.5 yoctoNEAR"), None);
+        assert_eq!(parse_amount("1"), None);
+    }
+}
@@ -0,0 +1,109 @@
+use proc_macro2::Ident;
+use syn::parse::{Parse, ParseStream};
+use syn::{parenthesized, Error, Expr, LitStr, Path, Token};
+
+/// Check on an argument from `#[validate(..)]`.
+pub enum Validation {
+    /// Bounds of the value, from `range(min = .., max = ..)`.
+    Range { min: Option<Expr>, max: Option<Expr> },
+    /// Bounds of the `len()` of the value, from `len(min = .., max = ..)`.
+    Len { min: Option<Expr>, max: Option<Expr> },
+    /// Function the value is given to by reference, from `predicate = ".."`.
+    Predicate(Path),
+}
+
+/// Checks on an argument from `#[validate(..)]`, e.g. `#[validate(range(min = 1, max = 100))]`.
+pub struct ValidateAttr {
+    pub validations: Vec<Validation>,
+}
+
+impl Parse for ValidateAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        let mut validations = Vec::new();
+        while !content.is_empty() {
+            let key: Ident = content.parse()?;
+            let validation = match key.to_string().as_str() {
+                "range" => {
+                    let (min, max) = parse_bounds(&content, &key)?;
+                    Validation::Range { min, max }
+                }
+                "len" => {
+                    let (min, max) = parse_bounds(&content, &key)?;
+                    Validation::Len { min, max }
+                }
+                "predicate" => {
+                    content.parse::<Token![=]>()?;
+                    let predicate: LitStr = content.parse()?;
+                    Validation::Predicate(predicate.parse().map_err(|_| {
+                        Error::new(
+                            predicate.span(),
+                            "Expected the path of the predicate, as in `is_valid_symbol`.",
+                        )
+                    })?)
+                }
+                _ => return Err(Error::new(key.span(), "Unsupported validate attribute.")),
+            };
+            validations.push(validation);
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        if validations.is_empty() {
+            return Err(Error::new(input.span(), "Expected validations, as in `len(max = 64)`."));
+        }
+        Ok(Self { validations })
+    }
+}
+
+/// Parses `(min = .., max = ..)`, where at least one of the bounds is given.
+fn parse_bounds(input: ParseStream, key: &Ident) -> syn::Result<(Option<Expr>, Option<Expr>)> {
+    let content;
+    parenthesized!(content in input);
+    let (mut min, mut max) = (None, None);
+    while !content.is_empty() {
+        let bound: Ident = content.parse()?;
+        content.parse::<Token![=]>()?;
+        let value: Expr = content.parse()?;
+        let duplicate = match bound.to_string().as_str() {
+            "min" => min.replace(value).is_some(),
+            "max" => max.replace(value).is_some(),
+            _ => return Err(Error::new(bound.span(), "Expected `min` or `max`.")),
+        };
+        if duplicate {
+            return Err(Error::new(bound.span(), "Duplicate bound."));
+        }
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    if min.is_none() && max.is_none() {
+        return Err(Error::new(key.span(), "Expected a `min` or `max` bound."));
+    }
+    Ok((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ValidateAttr, Validation};
+
+    #[test]
+    fn validations() {
+        let attr: ValidateAttr =
+            syn::parse_str("(len(max = 8), range(min = 1), predicate = \"Self::is_symbol\")")
+                .unwrap();
+        assert!(matches!(
+            attr.validations.as_slice(),
+            [
+                Validation::Len { min: None, max: Some(_) },
+                Validation::Range { min: Some(_), max: None },
+                Validation::Predicate(_)
+            ]
+        ));
+        assert!(syn::parse_str::<ValidateAttr>("()").is_err());
+        assert!(syn::parse_str::<ValidateAttr>("(range())").is_err());
+        assert!(syn::parse_str::<ValidateAttr>("(len(max = 1, max = 2))").is_err());
+        assert!(syn::parse_str::<ValidateAttr>("(email)").is_err());
+    }
+}
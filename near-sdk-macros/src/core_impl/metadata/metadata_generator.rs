@@ -1,3 +1,4 @@
+use crate::core_impl::utils;
 use crate::{BindgenArgType, ImplItemMethodInfo, MethodType, SerializerType};
 
 use proc_macro2::TokenStream as TokenStream2;
@@ -100,8 +101,9 @@ impl ImplItemMethodInfo {
                 }
             }
             ReturnType::Type(_, ty) => {
+                let ty = utils::owned_type(ty);
                 quote! {
-                    Some(#ty::schema_container())
+                    Some(<#ty>::schema_container())
                 }
             }
         };
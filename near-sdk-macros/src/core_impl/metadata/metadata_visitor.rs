@@ -2,7 +2,8 @@
 //! it decorates. Note, that this in an inner attribute. For it to work we should be
 //! able to visit every method in the module intended to be a contract method.
 //! For this we implement the visitor.
-use crate::ItemImplInfo;
+use crate::core_impl::async_methods::expand_async_methods;
+use crate::{BindgenAttr, ItemImplInfo};
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
@@ -19,12 +20,21 @@ pub struct MetadataVisitor {
 
 impl<'ast> Visit<'ast> for MetadataVisitor {
     fn visit_item_impl(&mut self, i: &'ast ItemImpl) {
-        let has_near_sdk_attr = i
+        let near_sdk_attr = i
             .attrs
             .iter()
-            .any(|attr| attr.path.to_token_stream().to_string().as_str() == "near_bindgen");
-        if has_near_sdk_attr {
-            match ItemImplInfo::new(&mut i.clone()) {
+            .find(|attr| attr.path.to_token_stream().to_string().as_str() == "near_bindgen");
+        if let Some(attr) = near_sdk_attr {
+            let args = if attr.tokens.is_empty() {
+                Ok(BindgenAttr::default())
+            } else {
+                attr.parse_args::<BindgenAttr>()
+            };
+            let mut i = i.clone();
+            let info = expand_async_methods(&mut i)
+                .and(args)
+                .and_then(|args| ItemImplInfo::new(&mut i, args.contract));
+            match info {
                 Ok(info) => self.impl_item_infos.push(info),
                 Err(err) => self.errors.push(err),
             }
@@ -146,7 +156,7 @@ mod tests {
                         },
                         callbacks: vec![],
                         callbacks_vec: None,
-                        result: Some(Result < IsOk, Error > ::schema_container())
+                        result: Some(<Result<IsOk, Error> >::schema_container())
                     }
                 ]);
                 let data = near_sdk::borsh::BorshSerialize::try_to_vec(&metadata)
@@ -1,4 +1,7 @@
-use syn::{GenericArgument, Path, PathArguments, Type};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use std::collections::HashSet;
+use syn::{parse_quote, GenericArgument, GenericParam, Path, PathArguments, Type};
 
 /// Checks whether the given path is literally "Result".
 /// Note that it won't match a fully qualified name `core::result::Result` or a type alias like
@@ -21,13 +24,26 @@ pub(crate) fn type_is_result(ty: &Type) -> bool {
 ///
 /// For example, given `Result<String, u8>` type it will return `String` type.
 pub(crate) fn extract_ok_type(ty: &Type) -> Option<&Type> {
+    extract_result_type_arg(ty, 0)
+}
+
+/// Extracts the Err type from a `Result` type.
+///
+/// For example, given `Result<String, u8>` type it will return `u8` type.
+#[cfg(feature = "__abi-generate")]
+pub(crate) fn extract_err_type(ty: &Type) -> Option<&Type> {
+    extract_result_type_arg(ty, 1)
+}
+
+fn extract_result_type_arg(ty: &Type, index: usize) -> Option<&Type> {
     match ty {
         Type::Path(type_path) if type_path.qself.is_none() && path_is_result(&type_path.path) => {
             // Get the first segment of the path (there should be only one, in fact: "Result"):
             let type_params = &type_path.path.segments.first()?.arguments;
-            // We are interested in the first angle-bracketed param responsible for Ok type ("<String, _>"):
+            // We are interested in the angle-bracketed param at `index`, 0 being the Ok type
+            // and 1 the Err type ("<String, u8>"):
             let generic_arg = match type_params {
-                PathArguments::AngleBracketed(params) => Some(params.args.first()?),
+                PathArguments::AngleBracketed(params) => Some(params.args.iter().nth(index)?),
                 _ => None,
             }?;
             // This argument must be a type:
@@ -73,3 +89,75 @@ pub(crate) fn extract_vec_type(ty: &Type) -> Option<&Type> {
         _ => None,
     }
 }
+
+/// Owned type that a returned value of the given type is serialized like, as the schemas are
+/// implemented for owned types: `&T` and `Cow<'_, T>` like `T`, slices like `Vec`s and `str` like
+/// `String`.
+pub(crate) fn owned_type(ty: &Type) -> Type {
+    let borrowed = match ty {
+        Type::Reference(reference) => &*reference.elem,
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            match type_path.path.segments.last() {
+                Some(segment) if segment.ident == "Cow" => match &segment.arguments {
+                    PathArguments::AngleBracketed(params) => {
+                        match params.args.iter().find_map(|arg| match arg {
+                            GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        }) {
+                            Some(ty) => ty,
+                            None => return ty.clone(),
+                        }
+                    }
+                    _ => return ty.clone(),
+                },
+                _ => return ty.clone(),
+            }
+        }
+        _ => return ty.clone(),
+    };
+    match borrowed {
+        Type::Slice(slice) => {
+            let elem = &slice.elem;
+            parse_quote! { ::std::vec::Vec<#elem> }
+        }
+        Type::Path(type_path) if type_path.path.is_ident("str") => {
+            parse_quote! { ::std::string::String }
+        }
+        _ => borrowed.clone(),
+    }
+}
+
+/// Name of a generic parameter, without the `'` of lifetimes.
+pub(crate) fn param_ident(param: &GenericParam) -> String {
+    match param {
+        GenericParam::Type(param) => param.ident.to_string(),
+        GenericParam::Lifetime(param) => param.lifetime.ident.to_string(),
+        GenericParam::Const(param) => param.ident.to_string(),
+    }
+}
+
+/// Collects the identifiers in the tokens, including those of lifetimes, which is enough to tell
+/// which generic parameters they use.
+pub(crate) fn collect_idents(tokens: TokenStream, idents: &mut HashSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident.to_string());
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+    }
+}
+
+/// Path of a function named in an attribute of a method, with a leading `Self` replaced by the
+/// type of the contract, as `Self` is not in scope of the exported function.
+pub(crate) fn resolve_self_path(path: &Path, struct_type: &Type) -> TokenStream {
+    match path.segments.first() {
+        Some(first) if first.ident == "Self" && path.leading_colon.is_none() => {
+            let rest = path.segments.iter().skip(1);
+            quote! { <#struct_type>#(::#rest)* }
+        }
+        _ => quote! { #path },
+    }
+}
@@ -3,7 +3,11 @@ extern crate proc_macro;
 
 mod core_impl;
 
+use core_impl::async_methods::expand_async_methods;
 use core_impl::ext::generate_ext_structs;
+use core_impl::lazy_fields::expand_lazy_fields;
+use core_impl::multicall::generate_multicall;
+use core_impl::serializer_derives::generate_serializers;
 use proc_macro::TokenStream;
 
 use self::core_impl::*;
@@ -21,12 +25,100 @@ use syn::{File, ItemEnum, ItemImpl, ItemStruct, ItemTrait};
 ///
 /// For parameter serialization, this macro will generate a struct with all of the parameters as
 /// fields and derive deserialization for it. By default this will be JSON deserialized with `serde`
-/// but can be overwritten by using `#[serializer(borsh)]`.
+/// but can be overwritten by using `#[serializer(borsh)]`. Only some of the parameters can use
+/// `#[serializer(borsh)]`, e.g. for a large proof next to JSON parameters, in which case the input
+/// is the JSON object of the JSON parameters followed by the Borsh encoding of the others, in
+/// order. Calls made through `ext_contract` and `Contract::ext` encode the arguments this way.
 ///
 /// `#[near_bindgen]` will also handle serializing and setting the return value of the
 /// function execution based on what type is returned by the function. By default, this will be
 /// done through `serde` serialized as JSON, but this can be overwritten using
-/// `#[result_serializer(borsh)]`.
+/// `#[result_serializer(borsh)]`. Methods can also return borrows, such as `&T`, `&[T]`, `&str` or
+/// `Cow<'_, T>`, which are serialized like the owned values without cloning the state they borrow.
+///
+/// Methods taking `&mut self` can be marked with `#[read_mostly]` to only write the state back
+/// if the method actually modified it, which saves gas for calls that turn out to be no-ops.
+///
+/// Fields of the contract struct can be marked with `#[lazy]` to keep them out of the contract
+/// state, which is read on every call. A `#[lazy]` field of type `T` becomes a
+/// `near_sdk::store::Lazy<T>` (`unstable`), which is read from its own storage key on the first
+/// access and only written back if it was modified. The field is initialized with
+/// `Lazy::new(key, value)`, and otherwise used through `Deref` as a `T`. The struct must be marked
+/// with `#[near_bindgen]` before its derives, so that they see the replaced type.
+///
+/// Methods can be marked with `#[deprecated_method(since = "1.2", use = "new_method")]`, which logs
+/// a `method_deprecated` event on every call and adds a deprecation notice to the ABI. With
+/// `removed_with_feature = "<feature>"` the method is no longer exported once the given cargo
+/// feature of the contract is enabled. All arguments are optional.
+///
+/// `#[payable]` methods can constrain the attached deposit with `#[payable(exactly_one_yocto)]` or
+/// `#[payable(min = "0.1 NEAR")]`, where the amount is given in `NEAR` or `yoctoNEAR`. With
+/// `#[payable(min = "..", refund_excess)]`, the deposit above the minimum is transferred back to
+/// the predecessor once the method has run.
+///
+/// Methods taking `self` can be marked with `#[only(role = "admin")]` to only be callable by
+/// accounts with the given role, or with any of the roles if several are given. The roles are
+/// read from the `near_sdk::acl::Acl` the contract returns by implementing
+/// `near_sdk::acl::AccessControl`, and checked before the body of the method runs.
+///
+/// Arguments read from the input can be checked before the method runs with
+/// `#[validate(range(min = 1, max = 100))]`, `#[validate(len(max = 64))]` on types with a `len()`,
+/// or `#[validate(predicate = "is_valid_symbol")]`, given the argument by reference. Several checks
+/// can be combined in one attribute, and a failed check panics with an error naming the argument.
+///
+/// Callback arguments marked with `#[callback_result(on_error = "Self::rollback")]` have the type
+/// of the promise result instead of `Result<T, PromiseError>`. If the promise failed, the given
+/// function is called in place of the method, with the contract passed as the method receives it,
+/// if at all, and the `PromiseError`. It returns the same type as the method.
+///
+/// Methods can be marked with `#[before(call = "self.assert_not_paused")]` and
+/// `#[after(call = "Self::log_call")]` to call a method of the contract, or a function without
+/// arguments, before or after the method runs, once the state is read and before it is written
+/// back. The impl section can set them for all of its methods with
+/// `#[near_bindgen(before(call = ".."), after(call = ".."))]`, which methods replace with their
+/// own, or opt out of with `#[before(skip)]`. Hooks calling a method of the contract only apply to
+/// methods taking `&self` or `&mut self`.
+///
+/// Public methods can be `async`, awaiting the promises of cross-contract calls as statements of
+/// the method body, like `let balance: U128 = ext_token::ext(token).ft_balance_of(id).await;` or
+/// `promise.await;`. The method is split at each `.await` into private callbacks named
+/// `__<method>_resume_<n>`, chained with `then`. The locals used after an `.await` are passed to
+/// the callback as JSON arguments, so they must have a type and be serializable. The result of a
+/// failed promise panics, unless it is awaited as a `Result<T, PromiseError>`. `return` can only be
+/// used after the last `.await`.
+///
+/// `#[near_bindgen(multicall)]` on an impl section generates a private
+/// `execute_batch(calls: Vec<near_sdk::MethodCall>)` method, which calls the public `&mut self`
+/// methods of the impl section in order, with the arguments of each call serialized as the method
+/// expects them. The arguments are validated, the deposit and the predecessor checked and the
+/// hooks of the methods called as when they are called on their own, but their results are
+/// dropped. An error returned by a `#[handle_result]` method panics, which reverts the whole
+/// batch. The attached deposit can only be used by one call of a batch. Methods with callback
+/// arguments or with `#[payable(refund_excess)]` can not be called in a batch.
+///
+/// The contract struct can be generic, for example over a strategy type, so that it can be reused
+/// by several contracts. Generic impl sections name the concrete type of the contract, with
+/// `#[near_bindgen(contract = Skeleton<MyStrategy>)]` on `impl<S: Strategy> Skeleton<S>`, which
+/// the methods are exported for. The type parameters in the signatures of the exported methods
+/// are replaced with their concrete types, and the bounds are checked on the concrete type.
+///
+/// Library crates can provide components, whose methods are exported by the contracts using them,
+/// with `#[near_bindgen(component)]` on a blanket implementation of a trait, such as
+/// `impl<C: PausableState> Pausable for C`, where `PausableState` gives access to the state of
+/// the component within the contract state. The contract exports them with
+/// `#[near_bindgen(components(pausable::Pausable))]` on its struct, and implements the traits the
+/// component requires. The component impl must be in the module of the trait, which it is
+/// exported from.
+///
+/// `#[near_bindgen(contract_metadata(..))]` on the contract struct generates the
+/// `contract_source_metadata` view method of [NEP-330](https://github.com/near/NEPs/blob/master/neps/nep-0330.md),
+/// which returns a `near_sdk::ContractSourceMetadata`. Its `version` and `link` default to the
+/// version and the repository of the package, and the implemented standards are listed with
+/// `standard(standard = "nep171", version = "1.0.0")`, along with NEP-330 itself.
+///
+/// With the `abi` feature, the signatures of the exported methods are collected to generate the
+/// ABI of the contract through [cargo-near](https://github.com/near/cargo-near), which can also
+/// embed it in the contract, as the compressed result of a `__contract_abi` method.
 ///
 /// # Examples
 ///
@@ -44,79 +136,440 @@ use syn::{File, ItemEnum, ItemImpl, ItemStruct, ItemTrait};
 /// }
 /// ```
 #[proc_macro_attribute]
-pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
-        let ext_gen = generate_ext_structs(&input.ident, Some(&input.generics));
-        #[cfg(feature = "__abi-embed")]
-        let abi_embedded = abi::embed();
-        #[cfg(not(feature = "__abi-embed"))]
-        let abi_embedded = quote! {};
+pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = match syn::parse::<BindgenAttr>(attr) {
+        Ok(args) => args,
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+    if let Ok(mut input) = syn::parse::<ItemStruct>(item.clone()) {
+        if let Err(err) = expand_lazy_fields(&mut input) {
+            return err.to_compile_error().into();
+        }
+        let state_code = generate_state_code(&input.ident, &input.generics);
+        let components = generate_component_exports(&input.ident, &input.generics, &args);
+        let metadata = generate_contract_metadata(&input.ident, &input.generics, &args);
         TokenStream::from(quote! {
             #input
-            #ext_gen
-            #abi_embedded
+            #state_code
+            #components
+            #metadata
         })
     } else if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
-        let ext_gen = generate_ext_structs(&input.ident, Some(&input.generics));
-        #[cfg(feature = "__abi-embed")]
-        let abi_embedded = abi::embed();
-        #[cfg(not(feature = "__abi-embed"))]
-        let abi_embedded = quote! {};
+        let state_code = generate_state_code(&input.ident, &input.generics);
+        let components = generate_component_exports(&input.ident, &input.generics, &args);
+        let metadata = generate_contract_metadata(&input.ident, &input.generics, &args);
         TokenStream::from(quote! {
             #input
-            #ext_gen
-            #abi_embedded
+            #state_code
+            #components
+            #metadata
         })
-    } else if let Ok(mut input) = syn::parse::<ItemImpl>(item) {
-        let item_impl_info = match ItemImplInfo::new(&mut input) {
-            Ok(x) => x,
-            Err(err) => {
-                return err.to_compile_error().into();
+    } else if let Ok(input) = syn::parse::<ItemImpl>(item) {
+        if args.component {
+            return TokenStream::from(process_component(input));
+        }
+        TokenStream::from(process_impl_block(input, args))
+    } else {
+        TokenStream::from(
+            syn::Error::new(
+                Span::call_site(),
+                "near_bindgen can only be used on struct or enum definition and impl sections.",
+            )
+            .to_compile_error(),
+        )
+    }
+}
+
+/// Generates the exported methods of an impl section marked with `#[near_bindgen]`.
+fn process_impl_block(mut input: ItemImpl, args: BindgenAttr) -> proc_macro2::TokenStream {
+    if let Err(err) = expand_async_methods(&mut input) {
+        return err.to_compile_error();
+    }
+    let mut item_impl_info = match ItemImplInfo::new(&mut input, args.contract) {
+        Ok(x) => x,
+        Err(err) => {
+            return err.to_compile_error();
+        }
+    };
+    item_impl_info.set_default_hooks(args.before.as_ref(), args.after.as_ref());
+    if args.multicall {
+        if input.trait_.is_some() || !input.generics.params.is_empty() {
+            return syn::Error::new_spanned(
+                &input.self_ty,
+                "multicall can only be used on impl sections of the contract type, which are not generic or trait implementations.",
+            )
+            .to_compile_error();
+        }
+        let mut batch = generate_multicall(&item_impl_info);
+        match ImplItemMethodInfo::new(&mut batch, item_impl_info.ty.clone()) {
+            Ok(mut batch_info) => {
+                batch_info.attr_signature_info.before = None;
+                batch_info.attr_signature_info.after = None;
+                item_impl_info.methods.push(batch_info);
             }
+            Err(err) => return err.to_compile_error(),
+        }
+        input.items.push(syn::ImplItem::Method(batch));
+    }
+
+    #[cfg(not(feature = "__abi-generate"))]
+    let abi_generated = quote! {};
+    #[cfg(feature = "__abi-generate")]
+    let abi_generated = abi::generate(&item_impl_info);
+
+    for method in &item_impl_info.methods {
+        if method.attr_signature_info.ident == "__contract_abi" {
+            return syn::Error::new_spanned(
+                method.attr_signature_info.original_sig.ident.to_token_stream(),
+                "use of reserved contract method",
+            )
+            .to_compile_error();
+        }
+    }
+
+    let generated_code = item_impl_info.wrapper_code();
+
+    // Add wrapper methods for ext call API
+    let ext_generated_code = item_impl_info.generate_ext_wrapper_code();
+    quote! {
+        #ext_generated_code
+        #input
+        #generated_code
+        #abi_generated
+    }
+}
+
+/// Generates the code of a `#[near_bindgen(component)]` impl section, whose methods are exported
+/// by the contracts using the component. They can only be generated once the contract type is
+/// known, so they are generated by a macro, invoked with the contract type and the path of the
+/// component trait by `#[near_bindgen(components(..))]`. The macro is exported with the name of
+/// the trait, so that it is imported along with it.
+fn process_component(mut input: ItemImpl) -> proc_macro2::TokenStream {
+    let trait_ident =
+        match &input.trait_ {
+            Some((_, path, _)) => path.segments.last().unwrap().ident.clone(),
+            None => return syn::Error::new_spanned(
+                &input.self_ty,
+                "Components must be implementations of a trait for the contract type parameter.",
+            )
+            .to_compile_error(),
         };
+    let placeholder = quote::format_ident!("__NearComponentContract");
+    let item_impl_info = match ItemImplInfo::new(&mut input, Some(syn::parse_quote!(#placeholder)))
+    {
+        Ok(x) => x,
+        Err(err) => {
+            return err.to_compile_error();
+        }
+    };
 
-        #[cfg(not(feature = "__abi-generate"))]
-        let abi_generated = quote! {};
-        #[cfg(feature = "__abi-generate")]
-        let abi_generated = abi::generate(&item_impl_info);
-
-        for method in &item_impl_info.methods {
-            if method.attr_signature_info.ident == "__contract_abi" {
-                return TokenStream::from(
-                    syn::Error::new_spanned(
-                        method.attr_signature_info.original_sig.ident.to_token_stream(),
-                        "use of reserved contract method",
-                    )
-                    .to_compile_error(),
-                );
-            }
+    #[cfg(not(feature = "__abi-generate"))]
+    let abi_generated = quote! {};
+    #[cfg(feature = "__abi-generate")]
+    let abi_generated = abi::generate(&item_impl_info);
+    let generated_code = item_impl_info.wrapper_code();
+    let contract = quote!($contract);
+    let exports = replace_ident(quote! { #generated_code #abi_generated }, &placeholder, &contract);
+
+    let macro_ident = quote::format_ident!("__near_component_{}", trait_ident);
+    quote! {
+        #input
+
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #macro_ident {
+            ($contract:ty, $($component:tt)+) => {
+                const _: () = {
+                    use $($component)+ as _;
+                    #exports
+                };
+            };
         }
 
-        let generated_code = item_impl_info.wrapper_code();
+        #[doc(hidden)]
+        pub use #macro_ident as #trait_ident;
+    }
+}
 
-        // Add wrapper methods for ext call API
-        let ext_generated_code = item_impl_info.generate_ext_wrapper_code();
-        TokenStream::from(quote! {
-            #ext_generated_code
-            #input
-            #generated_code
-            #abi_generated
+/// Exports the methods of the components listed in `#[near_bindgen(components(..))]`.
+fn generate_component_exports(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    args: &BindgenAttr,
+) -> proc_macro2::TokenStream {
+    if args.components.is_empty() {
+        return quote! {};
+    }
+    if !generics.params.is_empty() {
+        return syn::Error::new_spanned(generics, "Generic contracts can not export components.")
+            .to_compile_error();
+    }
+    let components = &args.components;
+    quote! {
+        #(#components!(#ident, #components);)*
+    }
+}
+
+/// Generates the `contract_source_metadata` view method of NEP-330 from
+/// `#[near_bindgen(contract_metadata(..))]`. The version and the link default to those of the
+/// package being compiled, which Cargo gives to the compiler, and so to the macro.
+fn generate_contract_metadata(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    args: &BindgenAttr,
+) -> proc_macro2::TokenStream {
+    let metadata = match &args.contract_metadata {
+        Some(metadata) => metadata,
+        None => return quote! {},
+    };
+    if !generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            generics,
+            "Generic contracts can not generate their source metadata.",
+        )
+        .to_compile_error();
+    }
+    let package = |var: &str| std::env::var(var).ok().filter(|value| !value.is_empty());
+    let optional = |value: Option<String>| match value {
+        Some(value) => quote! { Some(#value.to_string()) },
+        None => quote! { None },
+    };
+    let version = optional(metadata.version.clone().or_else(|| package("CARGO_PKG_VERSION")));
+    let link = optional(metadata.link.clone().or_else(|| package("CARGO_PKG_REPOSITORY")));
+    let mut standards = vec![("nep330".to_string(), "1.1.0".to_string())];
+    standards.retain(|(nep, _)| metadata.standards.iter().all(|(standard, _)| standard != nep));
+    standards.extend(metadata.standards.iter().cloned());
+    let standards = standards.iter().map(|(standard, version)| {
+        quote! {
+            near_sdk::Standard { standard: #standard.to_string(), version: #version.to_string() }
+        }
+    });
+    let method: ItemImpl = syn::parse_quote! {
+        impl #ident {
+            /// Source metadata of the contract, as specified by NEP-330.
+            pub fn contract_source_metadata() -> near_sdk::ContractSourceMetadata {
+                near_sdk::ContractSourceMetadata {
+                    version: #version,
+                    link: #link,
+                    standards: vec![#(#standards),*],
+                }
+            }
+        }
+    };
+    process_impl_block(method, BindgenAttr::default())
+}
+
+/// Replaces the given identifier with the tokens in the token stream, including in its groups.
+fn replace_ident(
+    tokens: proc_macro2::TokenStream,
+    ident: &syn::Ident,
+    replacement: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            proc_macro2::TokenTree::Ident(i) if &i == ident => replacement.clone(),
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced = proc_macro2::Group::new(
+                    group.delimiter(),
+                    replace_ident(group.stream(), ident, replacement),
+                );
+                replaced.set_span(group.span());
+                proc_macro2::TokenTree::Group(replaced).into()
+            }
+            token => token.into(),
         })
+        .collect()
+}
+
+/// Generates the code needed alongside the type of the contract state.
+fn generate_state_code(ident: &syn::Ident, generics: &syn::Generics) -> proc_macro2::TokenStream {
+    let ext_gen = generate_ext_structs(ident, Some(generics));
+    #[cfg(feature = "__abi-embed")]
+    let abi_embedded = abi::embed();
+    #[cfg(not(feature = "__abi-embed"))]
+    let abi_embedded = quote! {};
+    quote! {
+        #ext_gen
+        #abi_embedded
+    }
+}
+
+/// `#[near]` replaces the `#[near_bindgen]` attribute and the serialization derives usually
+/// written next to it, going through the crates re-exported by `near_sdk`, so that the contract
+/// does not need to depend on `borsh`, `serde` or `schemars` itself.
+///
+/// On a struct or an enum, it takes the following arguments:
+/// - `contract_state` marks the type as the state of the contract, as `#[near_bindgen]` does, and
+///   implements Borsh serialization for it, which the state is stored with.
+/// - `serializers = [borsh, json]` implements the given serializations for the type. Borsh is
+///   implemented as with `#[derive(BorshSerialize, BorshDeserialize)]`, and supports the
+///   `#[borsh_skip]` and `#[borsh_init]` attributes. JSON derives `Serialize` and `Deserialize`
+///   from `serde`, along with `JsonSchema` for the ABI, and supports the `#[serde]` attributes.
+///   Without this argument, only Borsh is implemented.
+///
+/// On an impl section, it is the same as `#[near_bindgen]`, and takes the same arguments.
+///
+/// # Examples
+///
+/// ```ignore
+/// use near_sdk::near;
+///
+/// #[near(serializers = [borsh, json])]
+/// pub struct Config {
+///     owner: String,
+/// }
+///
+/// #[near(contract_state)]
+/// pub struct Contract {
+///     config: Config,
+/// }
+///
+/// #[near]
+/// impl Contract {
+///     pub fn config(&self) -> &Config {
+///         &self.config
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn near(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if syn::parse::<ItemImpl>(item.clone()).is_ok() {
+        return near_bindgen(attr, item);
+    }
+    let args = match syn::parse::<NearAttr>(attr) {
+        Ok(args) => args,
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+    let mut serializers = args.serializers.unwrap_or_else(|| vec![SerializerType::Borsh]);
+    if args.contract_state && !serializers.contains(&SerializerType::Borsh) {
+        serializers.push(SerializerType::Borsh);
+    }
+    let mut input = match syn::parse::<syn::Item>(item) {
+        Ok(input @ (syn::Item::Struct(_) | syn::Item::Enum(_))) => input,
+        _ => {
+            return TokenStream::from(
+                syn::Error::new(
+                    Span::call_site(),
+                    "near can only be used on struct or enum definition and impl sections.",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+    let mut state_code = quote! {};
+    if args.contract_state {
+        // Lazy fields are expanded first, so that the serialization sees the replaced types.
+        match &mut input {
+            syn::Item::Struct(input) => {
+                if let Err(err) = expand_lazy_fields(input) {
+                    return err.to_compile_error().into();
+                }
+                state_code = generate_state_code(&input.ident, &input.generics);
+            }
+            syn::Item::Enum(input) => {
+                state_code = generate_state_code(&input.ident, &input.generics);
+            }
+            _ => unreachable!(),
+        }
+    }
+    let serializer_impls = match generate_serializers(&mut input, &serializers) {
+        Ok(impls) => impls,
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+    TokenStream::from(quote! {
+        #input
+        #serializer_impls
+        #state_code
+    })
+}
+
+/// `migrate` generates the `migrate` method of a contract, which replaces the state of the
+/// contract, of the type given with `from`, by the state of the type it is used on. The new state
+/// is converted from the old one with its `From` implementation.
+///
+/// The method is `#[private]`, so that it can only be called by the contract itself, usually in a
+/// batch with the deployment of the new code, and `#[init(ignore_state)]`, so that it can be
+/// called while the contract has a state.
+///
+/// # Examples
+///
+/// ```ignore
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::{migrate, near_bindgen, PanicOnDefault};
+///
+/// #[derive(BorshDeserialize, BorshSerialize)]
+/// pub struct OldContract {
+///     counter: u32,
+/// }
+///
+/// #[near_bindgen]
+/// #[migrate(from = OldContract)]
+/// #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+/// pub struct Contract {
+///     counter: u64,
+/// }
+///
+/// impl From<OldContract> for Contract {
+///     fn from(old: OldContract) -> Self {
+///         Self { counter: old.counter.into() }
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn migrate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let MigrateAttr { from } = match syn::parse::<MigrateAttr>(attr) {
+        Ok(attr) => attr,
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+    let (name, generics) = if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
+        (input.ident, input.generics)
+    } else if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
+        (input.ident, input.generics)
     } else {
-        TokenStream::from(
+        return TokenStream::from(
             syn::Error::new(
                 Span::call_site(),
-                "near_bindgen can only be used on struct or enum definition and impl sections.",
+                "migrate can only be used on struct or enum definitions.",
             )
             .to_compile_error(),
-        )
+        );
+    };
+    if !generics.params.is_empty() {
+        return TokenStream::from(
+            syn::Error::new_spanned(&generics, "migrate can not be used on generic types.")
+                .to_compile_error(),
+        );
     }
+    let error = format!("Cannot read the state to migrate as `{}`.", from.to_token_stream());
+    let migration: ItemImpl = syn::parse_quote! {
+        impl #name {
+            /// Migrates the state of the contract.
+            #[private]
+            #[init(ignore_state)]
+            pub fn migrate() -> Self {
+                let old_state: #from = near_sdk::env::state_read()
+                    .unwrap_or_else(|| near_sdk::env::panic_str(#error));
+                <Self as ::core::convert::From<#from>>::from(old_state)
+            }
+        }
+    };
+    let migration = process_impl_block(migration, BindgenAttr::default());
+    let item = proc_macro2::TokenStream::from(item);
+    TokenStream::from(quote! {
+        #item
+        #migration
+    })
 }
 
 /// `ext_contract` takes a Rust Trait and converts it to a module with static methods.
 /// Each of these static methods takes positional arguments defined by the Trait,
 /// then the receiver_id, the attached deposit and the amount of gas and returns a new Promise.
 ///
+/// The trait and its methods can be generic, with where-clauses. The methods calling the contract
+/// are generic over the parameters of the trait used by their arguments, so that they are
+/// monomorphized for the types of the arguments they are called with.
+///
 /// # Examples
 ///
 /// ```ignore
@@ -258,10 +711,34 @@ pub fn derive_no_default(item: TokenStream) -> TokenStream {
 /// `BorshStorageKey` generates implementation for `BorshIntoStorageKey` trait.
 /// It allows the type to be passed as a unique prefix for persistent collections.
 /// The type should also implement or derive `BorshSerialize` trait.
+///
+/// For enums, it also records the prefix of the keys of every variant, so that the enum can be
+/// registered with [`storage_keys!`] to check that its keys do not overlap with other ones.
 #[proc_macro_derive(BorshStorageKey)]
 pub fn borsh_storage_key(item: TokenStream) -> TokenStream {
     let name = if let Ok(input) = syn::parse::<ItemEnum>(item.clone()) {
-        input.ident
+        if input.variants.len() > usize::from(u8::MAX) + 1 {
+            return TokenStream::from(
+                syn::Error::new_spanned(
+                    &input.ident,
+                    "BorshStorageKey enums can have at most 256 variants.",
+                )
+                .to_compile_error(),
+            );
+        }
+        // Borsh serializes the index of the variant as a single byte, followed by its fields.
+        let prefixes = (0..input.variants.len()).map(|i| {
+            let index = i as u8;
+            quote! { &[#index] }
+        });
+        let name = &input.ident;
+        return TokenStream::from(quote! {
+            impl near_sdk::__private::BorshIntoStorageKey for #name {}
+
+            impl near_sdk::__private::StorageKeyPrefixes for #name {
+                const PREFIXES: &'static [&'static [u8]] = &[#(#prefixes),*];
+            }
+        });
     } else if let Ok(input) = syn::parse::<ItemStruct>(item) {
         input.ident
     } else {
@@ -278,6 +755,134 @@ pub fn borsh_storage_key(item: TokenStream) -> TokenStream {
     })
 }
 
+/// `storage_keys!` checks at compile time that the storage prefixes of a contract do not overlap,
+/// which would make different collections read and overwrite each other's entries.
+///
+/// It takes a comma-separated list of the prefixes given to collections, as byte string or string
+/// literals, and of the enums deriving [`BorshStorageKey`] they are created with. Each variant of
+/// an enum counts as a prefix of one byte, its index, and two prefixes overlap if one of them
+/// starts with the other. Overlaps are reported as compile errors, which requires Rust 1.57 or
+/// newer for enums.
+///
+/// # Examples
+///
+/// ```ignore
+/// use near_sdk::{storage_keys, BorshStorageKey};
+/// use near_sdk::borsh::{self, BorshSerialize};
+///
+/// #[derive(BorshSerialize, BorshStorageKey)]
+/// enum StorageKey {
+///     Accounts,
+///     Tokens { owner_id: String },
+/// }
+///
+/// storage_keys!(StorageKey, b"config", "metadata");
+/// ```
+#[proc_macro]
+pub fn storage_keys(item: TokenStream) -> TokenStream {
+    let entries = match syn::parse::Parser::parse(
+        syn::punctuated::Punctuated::<StorageKeyEntry, syn::Token![,]>::parse_terminated,
+        item,
+    ) {
+        Ok(entries) => entries.into_iter().collect::<Vec<_>>(),
+        Err(err) => return TokenStream::from(err.to_compile_error()),
+    };
+
+    let mut checks = Vec::new();
+    for (i, a) in entries.iter().enumerate() {
+        for b in &entries[i + 1..] {
+            match (a, b) {
+                (StorageKeyEntry::Literal(a_bytes, _), StorageKeyEntry::Literal(b_bytes, span)) => {
+                    if a_bytes.starts_with(b_bytes) || b_bytes.starts_with(a_bytes) {
+                        return TokenStream::from(
+                            syn::Error::new(
+                                *span,
+                                format!(
+                                    "Storage key {} overlaps with {}.",
+                                    b.description(),
+                                    a.description()
+                                ),
+                            )
+                            .to_compile_error(),
+                        );
+                    }
+                }
+                _ => {
+                    let (a_prefixes, b_prefixes) = (a.prefixes(), b.prefixes());
+                    let message = format!(
+                        "Storage keys {} and {} overlap.",
+                        a.description(),
+                        b.description()
+                    );
+                    // The message is a format string, so braces of the prefixes must be escaped.
+                    let message = message.replace('{', "{{").replace('}', "}}");
+                    checks.push(quote! {
+                        if near_sdk::__private::storage_keys_overlap(#a_prefixes, #b_prefixes) {
+                            panic!(#message);
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    TokenStream::from(quote! {
+        const _: () = {
+            #(#checks)*
+        };
+    })
+}
+
+/// Prefix registered with [`storage_keys!`].
+enum StorageKeyEntry {
+    /// Literal prefix, with the span of the literal.
+    Literal(Vec<u8>, Span),
+    /// Type implementing `StorageKeyPrefixes`.
+    Type(Box<syn::Type>),
+}
+
+impl syn::parse::Parse for StorageKeyEntry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::LitByteStr) {
+            let lit: syn::LitByteStr = input.parse()?;
+            Ok(Self::Literal(lit.value(), lit.span()))
+        } else if input.peek(syn::LitStr) {
+            let lit: syn::LitStr = input.parse()?;
+            Ok(Self::Literal(lit.value().into_bytes(), lit.span()))
+        } else {
+            input.parse().map(|ty| Self::Type(Box::new(ty)))
+        }
+    }
+}
+
+impl StorageKeyEntry {
+    fn description(&self) -> String {
+        match self {
+            Self::Literal(bytes, _) => {
+                let escaped: String = bytes
+                    .iter()
+                    .flat_map(|b| std::ascii::escape_default(*b))
+                    .map(char::from)
+                    .collect();
+                format!("`b\"{}\"`", escaped)
+            }
+            Self::Type(ty) => format!("of `{}`", ty.to_token_stream()),
+        }
+    }
+
+    fn prefixes(&self) -> proc_macro2::TokenStream {
+        match self {
+            Self::Literal(bytes, _) => {
+                let bytes = syn::LitByteStr::new(bytes, Span::call_site());
+                quote! { &[#bytes as &[u8]] }
+            }
+            Self::Type(ty) => quote! {
+                <#ty as near_sdk::__private::StorageKeyPrefixes>::PREFIXES
+            },
+        }
+    }
+}
+
 /// `FunctionError` generates implementation for `near_sdk::FunctionError` trait.
 /// It allows contract runtime to panic with the type using its `ToString` implementation
 /// as the message.
@@ -304,3 +909,363 @@ pub fn function_error(item: TokenStream) -> TokenStream {
         }
     })
 }
+
+/// `ContractError` generates implementations for `near_sdk::ContractError` and
+/// `near_sdk::FunctionError` traits. The error code is given with `#[error_code(..)]` on the
+/// struct or on every variant of the enum, and the message is the `Display` implementation of
+/// the type. The name of the error is the name of the struct or of the variant, and with
+/// `#[error_data]` on the type, the error is serialized to JSON as its data.
+#[proc_macro_derive(ContractError, attributes(error_code, error_data))]
+pub fn contract_error(item: TokenStream) -> TokenStream {
+    let (name, generics, attrs, variants) = if let Ok(input) = syn::parse::<ItemEnum>(item.clone())
+    {
+        let variants: syn::Result<Vec<_>> = input
+            .variants
+            .iter()
+            .map(|variant| {
+                let code = parse_error_code(&variant.attrs, variant.ident.span())?;
+                let ident = &variant.ident;
+                Ok((quote! { Self::#ident { .. } }, code, ident.to_string()))
+            })
+            .collect();
+        let variants = match variants {
+            Ok(variants) => variants,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
+        (input.ident, input.generics, input.attrs, variants)
+    } else if let Ok(input) = syn::parse::<ItemStruct>(item) {
+        let code = match parse_error_code(&input.attrs, input.ident.span()) {
+            Ok(code) => code,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
+        let variants = vec![(quote! { _ }, code, input.ident.to_string())];
+        (input.ident, input.generics, input.attrs, variants)
+    } else {
+        return TokenStream::from(
+            syn::Error::new(
+                Span::call_site(),
+                "ContractError can only be used as a derive on enums or structs.",
+            )
+            .to_compile_error(),
+        );
+    };
+    let has_data = attrs.iter().any(|attr| attr.path.is_ident("error_data"));
+    let code_arms = variants.iter().map(|(pat, code, _)| quote! { #pat => #code, });
+    let name_arms = variants.iter().map(|(pat, _, name)| quote! { #pat => #name, });
+    let error_data = if has_data {
+        quote! {
+            fn error_data(&self) -> ::core::option::Option<::std::string::String> {
+                near_sdk::serde_json::to_string(self).ok()
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let abi_errors = if cfg!(feature = "__abi-generate") {
+        let errors = variants.iter().map(|(_, code, name)| quote! { (#code, #name) });
+        let data = if has_data {
+            quote! { ::core::option::Option::Some(gen.subschema_for::<Self>()) }
+        } else {
+            quote! { ::core::option::Option::None }
+        };
+        quote! {
+            fn __abi_errors(
+                gen: &mut near_sdk::__private::schemars::gen::SchemaGenerator,
+            ) -> near_sdk::__private::AbiErrors {
+                near_sdk::__private::AbiErrors { errors: vec![#(#errors),*], data: #data }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    TokenStream::from(quote! {
+        impl #impl_generics near_sdk::ContractError for #name #ty_generics #where_clause {
+            fn error_code(&self) -> u32 {
+                match self { #(#code_arms)* }
+            }
+
+            fn error_name(&self) -> &'static str {
+                match self { #(#name_arms)* }
+            }
+
+            #error_data
+        }
+
+        impl #impl_generics near_sdk::FunctionError for #name #ty_generics #where_clause {
+            fn panic(&self) -> ! {
+                near_sdk::ContractError::panic_err(self)
+            }
+
+            #abi_errors
+        }
+    })
+}
+
+/// Parses the `u32` code of `#[error_code(..)]` from the attributes of an item.
+fn parse_error_code(attrs: &[syn::Attribute], span: Span) -> syn::Result<u32> {
+    let attr = attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("error_code"))
+        .ok_or_else(|| syn::Error::new(span, "Missing `#[error_code(..)]` attribute."))?;
+    attr.parse_args::<syn::LitInt>()?.base10_parse()
+}
+
+/// `NearEvent` generates implementation for `near_sdk::NearEvent` trait, which logs the variants
+/// of an enum as events in the NEP-297 format. The standard and the version are given with
+/// `#[event(standard = "..", version = "..")]` on the enum, and the name of an event defaults to
+/// its variant in snake case, which can be changed with `#[event(name = "..")]` on the variant.
+#[proc_macro_derive(NearEvent, attributes(event))]
+pub fn near_event(item: TokenStream) -> TokenStream {
+    let input = match syn::parse::<ItemEnum>(item) {
+        Ok(input) => input,
+        Err(_) => {
+            return TokenStream::from(
+                syn::Error::new(
+                    Span::call_site(),
+                    "NearEvent can only be used as a derive on enums.",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+    match generate_near_event(&input) {
+        Ok(generated) => TokenStream::from(generated),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
+fn generate_near_event(input: &ItemEnum) -> syn::Result<proc_macro2::TokenStream> {
+    let args = parse_event_args(&input.attrs, &["standard", "version"])?;
+    let standard = args[0].as_ref().ok_or_else(|| {
+        syn::Error::new_spanned(&input.ident, "Missing `standard` event argument.")
+    })?;
+    let version = args[1].as_ref().ok_or_else(|| {
+        syn::Error::new_spanned(&input.ident, "Missing `version` event argument.")
+    })?;
+
+    let mut arms = Vec::new();
+    for variant in &input.variants {
+        let ident = &variant.ident;
+        let name = match &parse_event_args(&variant.attrs, &["name"])?[0] {
+            Some(name) => name.clone(),
+            None => {
+                use inflector::Inflector;
+                ident.to_string().to_snake_case()
+            }
+        };
+        let arm = match &variant.fields {
+            syn::Fields::Named(fields) => {
+                let idents: Vec<_> =
+                    fields.named.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+                let keys = idents
+                    .iter()
+                    .map(|ident| format!("\"{}\":", ident.to_string().trim_start_matches("r#")));
+                quote! {
+                    Self::#ident { #(#idents),* } => {
+                        let mut __fields = ::std::vec::Vec::<::std::string::String>::new();
+                        #(
+                            let __value = near_sdk::serde_json::to_string(#idents)
+                                .unwrap_or_else(|_| near_sdk::env::abort());
+                            if __value != "null" {
+                                __fields.push(::std::format!("{}{}", #keys, __value));
+                            }
+                        )*
+                        (#name, ::core::option::Option::Some(::std::format!("{{{}}}", __fields.join(","))))
+                    }
+                }
+            }
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+                Self::#ident(__value) => (
+                    #name,
+                    ::core::option::Option::Some(
+                        near_sdk::serde_json::to_string(__value)
+                            .unwrap_or_else(|_| near_sdk::env::abort()),
+                    ),
+                )
+            },
+            syn::Fields::Unnamed(fields) => {
+                return Err(syn::Error::new_spanned(
+                    fields,
+                    "NearEvent variants can have named fields or a single unnamed field.",
+                ))
+            }
+            syn::Fields::Unit => quote! { Self::#ident => (#name, ::core::option::Option::None) },
+        };
+        arms.push(arm);
+    }
+
+    let prefix = format!(
+        "EVENT_JSON:{{\"standard\":\"{}\",\"version\":\"{}\",\"event\":\"",
+        standard, version
+    );
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics near_sdk::NearEvent for #name #ty_generics #where_clause {
+            fn to_event_log_many(events: &[Self]) -> ::std::string::String {
+                let mut __event = ::core::option::Option::None;
+                let mut __data = ::std::vec::Vec::<::std::string::String>::new();
+                for __item in events {
+                    let (__name, __item_data): (&str, ::core::option::Option<::std::string::String>) =
+                        match __item { #(#arms,)* };
+                    if *__event.get_or_insert(__name) != __name {
+                        near_sdk::env::panic_str("Events logged together must be of the same kind.");
+                    }
+                    __data.extend(__item_data);
+                }
+                let __event = __event.unwrap_or_else(|| near_sdk::env::panic_str("No events to log."));
+                let mut __log = ::std::format!("{}{}\"", #prefix, __event);
+                if !__data.is_empty() {
+                    __log.push_str(",\"data\":[");
+                    __log.push_str(&__data.join(","));
+                    __log.push(']');
+                }
+                __log.push('}');
+                __log
+            }
+        }
+    })
+}
+
+/// Parses the string arguments with the given names from the `#[event(..)]` attributes of an
+/// item, which must not need to be escaped in JSON.
+fn parse_event_args(attrs: &[syn::Attribute], names: &[&str]) -> syn::Result<Vec<Option<String>>> {
+    let mut values = vec![None; names.len()];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("event")) {
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "Expected `#[event(..)]`.")),
+        };
+        for nested in &list.nested {
+            let (path, value) = match nested {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(value),
+                    ..
+                })) => (path, value),
+                other => return Err(syn::Error::new_spanned(other, "Expected `name = \"..\"`.")),
+            };
+            let index = names
+                .iter()
+                .position(|name| path.is_ident(name))
+                .ok_or_else(|| syn::Error::new_spanned(path, "Unsupported event argument."))?;
+            if values[index].is_some() {
+                return Err(syn::Error::new_spanned(path, "Duplicate event argument."));
+            }
+            let value_str = value.value();
+            if value_str.chars().any(|c| c == '"' || c == '\\' || c.is_control()) {
+                return Err(syn::Error::new_spanned(
+                    value,
+                    "Event arguments can not contain quotes, backslashes or control characters.",
+                ));
+            }
+            values[index] = Some(value_str);
+        }
+    }
+    Ok(values)
+}
+
+/// `StorageNamespace` generates implementation for `near_sdk::StorageNamespace` trait, which
+/// creates every field in the child namespace of its name. The name can be changed with
+/// `#[namespace(name = "..")]`, and fields with `#[namespace(skip)]` are created with `Default`.
+/// Names that are prefixes of one another would give overlapping storage keys and are rejected.
+#[proc_macro_derive(StorageNamespace, attributes(namespace))]
+pub fn storage_namespace(item: TokenStream) -> TokenStream {
+    let input = match syn::parse::<ItemStruct>(item) {
+        Ok(input) => input,
+        Err(_) => {
+            return TokenStream::from(
+                syn::Error::new(
+                    Span::call_site(),
+                    "StorageNamespace can only be used as a derive on structs.",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+    let fields = match &input.fields {
+        syn::Fields::Named(fields) => &fields.named,
+        _ => {
+            return TokenStream::from(
+                syn::Error::new_spanned(
+                    &input.fields,
+                    "StorageNamespace can only be derived for structs with named fields.",
+                )
+                .to_compile_error(),
+            )
+        }
+    };
+
+    let mut names: Vec<(String, &syn::Ident)> = Vec::new();
+    let mut inits = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let name = match parse_namespace_name(&field.attrs, ident) {
+            Ok(Some(name)) => name,
+            Ok(None) => {
+                inits.push(quote! { #ident: ::core::default::Default::default(), });
+                continue;
+            }
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
+        if let Some((other, other_ident)) = names
+            .iter()
+            .find(|(other, _)| other.starts_with(name.as_str()) || name.starts_with(other.as_str()))
+        {
+            return TokenStream::from(
+                syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "Storage namespace `{}` of field `{}` overlaps with `{}` of field `{}`, \
+                         names must not be prefixes of each other.",
+                        name, ident, other, other_ident
+                    ),
+                )
+                .to_compile_error(),
+            );
+        }
+        let bytes = syn::LitByteStr::new(name.as_bytes(), ident.span());
+        inits.push(quote! {
+            #ident: near_sdk::StorageNamespace::new_in(&namespace.child(#bytes)),
+        });
+        names.push((name, ident));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    TokenStream::from(quote! {
+        impl #impl_generics near_sdk::StorageNamespace for #name #ty_generics #where_clause {
+            fn new_in(namespace: &near_sdk::Namespace) -> Self {
+                Self { #(#inits)* }
+            }
+        }
+    })
+}
+
+/// Parses the `#[namespace(..)]` attribute of a field, returning the name of its namespace or
+/// `None` if the field is skipped.
+fn parse_namespace_name(
+    attrs: &[syn::Attribute],
+    ident: &syn::Ident,
+) -> syn::Result<Option<String>> {
+    let attr = match attrs.iter().find(|attr| attr.path.is_ident("namespace")) {
+        Some(attr) => attr,
+        None => return Ok(Some(ident.to_string())),
+    };
+    match attr.parse_meta()? {
+        syn::Meta::List(list) if list.nested.len() == 1 => match &list.nested[0] {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("skip") => Ok(None),
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                lit: syn::Lit::Str(name),
+                ..
+            })) if path.is_ident("name") => Ok(Some(name.value())),
+            other => Err(syn::Error::new_spanned(other, "Expected `skip` or `name = \"..\"`.")),
+        },
+        meta => Err(syn::Error::new_spanned(
+            meta,
+            "Expected `#[namespace(skip)]` or `#[namespace(name = \"..\")]`.",
+        )),
+    }
+}
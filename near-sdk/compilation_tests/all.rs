@@ -20,5 +20,32 @@ fn compilation_tests() {
     t.compile_fail("compilation_tests/payable_view.rs");
     t.pass("compilation_tests/borsh_storage_key.rs");
     t.pass("compilation_tests/function_error.rs");
+    t.pass("compilation_tests/contract_error.rs");
     t.pass("compilation_tests/enum_near_bindgen.rs");
+    t.pass("compilation_tests/read_mostly.rs");
+    t.pass("compilation_tests/deprecated_method.rs");
+    t.pass("compilation_tests/storage_namespace.rs");
+    t.compile_fail("compilation_tests/storage_namespace_overlap.rs");
+    #[cfg(feature = "unstable")]
+    t.pass("compilation_tests/lazy_field.rs");
+    t.compile_fail("compilation_tests/lazy_field_args.rs");
+    t.pass("compilation_tests/storage_keys.rs");
+    t.pass("compilation_tests/near_attr.rs");
+    t.pass("compilation_tests/near_event.rs");
+    t.pass("compilation_tests/only_role.rs");
+    t.compile_fail("compilation_tests/only_role_static.rs");
+    t.pass("compilation_tests/payable_deposit.rs");
+    t.pass("compilation_tests/migrate.rs");
+    t.pass("compilation_tests/generic_contract.rs");
+    t.pass("compilation_tests/component.rs");
+    t.pass("compilation_tests/ext_contract_generic.rs");
+    t.pass("compilation_tests/callback_result_on_error.rs");
+    t.pass("compilation_tests/validate.rs");
+    t.pass("compilation_tests/hooks.rs");
+    t.pass("compilation_tests/async_method.rs");
+    t.pass("compilation_tests/contract_metadata.rs");
+    t.pass("compilation_tests/multicall.rs");
+    t.pass("compilation_tests/mixed_serializers.rs");
+    t.pass("compilation_tests/borrowed_returns.rs");
+    t.compile_fail("compilation_tests/storage_keys_overlap.rs");
 }
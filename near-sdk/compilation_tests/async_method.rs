@@ -0,0 +1,53 @@
+//! Async methods awaiting the results of cross-contract calls.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, PanicOnDefault, PromiseError};
+
+#[ext_contract(ext_token)]
+pub trait Token {
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128);
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Exchange {
+    token_in: AccountId,
+    token_out: AccountId,
+    swapped: u128,
+}
+
+#[near_bindgen]
+impl Exchange {
+    #[init]
+    pub fn new(token_in: AccountId, token_out: AccountId) -> Self {
+        Self { token_in, token_out, swapped: 0 }
+    }
+
+    pub async fn swap(&mut self, receiver_id: AccountId, amount: U128) -> u128 {
+        let pool: AccountId = env::current_account_id();
+        let available: U128 = ext_token::ext(self.token_out.clone()).ft_balance_of(pool).await;
+        if available.0 < amount.0 {
+            env::panic_str("Not enough liquidity");
+        }
+        ext_token::ext(self.token_in.clone())
+            .ft_transfer(env::current_account_id(), amount)
+            .await;
+        let transfer: Result<(), PromiseError> =
+            ext_token::ext(self.token_out.clone()).ft_transfer(receiver_id, amount).await;
+        if transfer.is_err() {
+            return 0;
+        }
+        self.swapped += amount.0;
+        amount.0
+    }
+
+    pub async fn balance(&self) -> U128 {
+        let balance: U128 =
+            ext_token::ext(self.token_out.clone()).ft_balance_of(env::current_account_id()).await;
+        balance
+    }
+}
+
+fn main() {}
@@ -0,0 +1,66 @@
+//! Returning borrows of the contract state from view methods.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+use near_sdk::serde::Serialize;
+use std::borrow::Cow;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    owner: String,
+    fee: u32,
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+pub struct Registry {
+    config: Config,
+    entries: Vec<u64>,
+    blob: Vec<u8>,
+}
+
+#[near_bindgen]
+impl Registry {
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn entries(&self) -> &[u64] {
+        &self.entries
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.config.owner
+    }
+
+    pub fn label(&self) -> Cow<'_, str> {
+        if self.config.owner.is_empty() {
+            Cow::Owned(format!("fee {}", self.config.fee))
+        } else {
+            Cow::Borrowed(&self.config.owner)
+        }
+    }
+
+    #[handle_result]
+    pub fn entry(&self, index: u64) -> Result<&u64, String> {
+        self.entries.get(index as usize).ok_or_else(|| format!("No entry at {}", index))
+    }
+
+    #[result_serializer(borsh)]
+    pub fn blob(&self) -> &Vec<u8> {
+        &self.blob
+    }
+
+    #[result_serializer(borsh)]
+    pub fn blob_slice(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.blob)
+    }
+
+    pub fn push(&mut self, entry: u64) -> &[u64] {
+        self.entries.push(entry);
+        &self.entries
+    }
+}
+
+fn main() {}
@@ -0,0 +1,54 @@
+//! Handling the failed promises of callbacks with a dedicated function.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, PanicOnDefault, PromiseError};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Escrow {
+    locked: u64,
+    released: u64,
+}
+
+#[near_bindgen]
+impl Escrow {
+    #[init]
+    pub fn new() -> Self {
+        Self { locked: 0, released: 0 }
+    }
+
+    #[private]
+    pub fn on_release(
+        &mut self,
+        #[callback_result(on_error = "Self::rollback_release")] amount: u64,
+    ) -> bool {
+        self.released += amount;
+        true
+    }
+
+    #[private]
+    pub fn on_lookup(&self, #[callback_result(on_error = "Self::missing")] released: u64) -> u64 {
+        released + self.released
+    }
+
+    #[private]
+    #[init]
+    pub fn restore(#[callback_result(on_error = "Self::fresh")] locked: u64) -> Self {
+        Self { locked, released: 0 }
+    }
+
+    fn rollback_release(&mut self, _error: PromiseError) -> bool {
+        self.locked = 0;
+        false
+    }
+
+    fn missing(&self, _error: PromiseError) -> u64 {
+        self.locked
+    }
+
+    fn fresh(_error: PromiseError) -> Self {
+        Self::new()
+    }
+}
+
+fn main() {}
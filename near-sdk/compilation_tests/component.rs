@@ -0,0 +1,71 @@
+//! Component implemented for any contract holding its state, and exported by a contract.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+mod pausable {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use near_sdk::near_bindgen;
+
+    #[derive(BorshDeserialize, BorshSerialize, Default)]
+    pub struct PauseState {
+        paused: bool,
+    }
+
+    /// Gives the component access to its state within the contract state.
+    pub trait PausableState {
+        fn pause_state(&self) -> &PauseState;
+        fn pause_state_mut(&mut self) -> &mut PauseState;
+    }
+
+    pub trait Pausable {
+        fn paused(&self) -> bool;
+        fn set_paused(&mut self, paused: bool);
+    }
+
+    #[near_bindgen(component)]
+    impl<C: PausableState> Pausable for C {
+        fn paused(&self) -> bool {
+            self.pause_state().paused
+        }
+
+        #[private]
+        fn set_paused(&mut self, paused: bool) {
+            self.pause_state_mut().paused = paused;
+        }
+    }
+}
+
+use pausable::{PauseState, PausableState};
+
+#[near_bindgen(components(pausable::Pausable))]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {
+    value: u32,
+    pause: PauseState,
+}
+
+impl PausableState for Contract {
+    fn pause_state(&self) -> &PauseState {
+        &self.pause
+    }
+
+    fn pause_state_mut(&mut self) -> &mut PauseState {
+        &mut self.pause
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+}
+
+fn main() {
+    use pausable::Pausable;
+
+    let mut contract = Contract::default();
+    contract.set_paused(true);
+    assert!(contract.paused());
+}
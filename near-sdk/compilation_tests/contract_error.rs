@@ -0,0 +1,85 @@
+//! Testing ContractError macro.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{near_bindgen, ContractError};
+use std::fmt;
+
+#[derive(ContractError)]
+#[error_code(100)]
+struct ErrorStruct {
+    message: String,
+}
+
+impl fmt::Display for ErrorStruct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error ocurred: {}", self.message)
+    }
+}
+
+#[derive(ContractError)]
+enum ErrorEnum<T: fmt::Display> {
+    #[error_code(1)]
+    NotFound,
+    #[error_code(2)]
+    Banned { account_id: String },
+    #[error_code(3)]
+    Invalid(T),
+}
+
+impl<T: fmt::Display> fmt::Display for ErrorEnum<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorEnum::NotFound => write!(f, "not found"),
+            ErrorEnum::Banned { account_id } => write!(f, "account {} is banned", account_id),
+            ErrorEnum::Invalid(value) => write!(f, "invalid value {}", value),
+        }
+    }
+}
+
+#[derive(ContractError, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[error_data]
+enum DataError {
+    #[error_code(10)]
+    InsufficientBalance { missing: u128 },
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::InsufficientBalance { missing } => write!(f, "missing {}", missing),
+        }
+    }
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    #[handle_result]
+    pub fn set(&self, value: String) -> Result<String, ErrorStruct> {
+        Err(ErrorStruct { message: format!("Could not set to {}", value) })
+    }
+
+    #[handle_result]
+    pub fn withdraw(&self) -> Result<(), DataError> {
+        Err(DataError::InsufficientBalance { missing: 1 })
+    }
+
+    #[handle_result]
+    pub fn get(&self) -> Result<String, ErrorEnum<u64>> {
+        Err(ErrorEnum::Invalid(1))
+    }
+}
+
+fn main() {
+    assert_eq!(near_sdk::ContractError::error_code(&ErrorEnum::<u64>::NotFound), 1);
+    assert_eq!(near_sdk::ContractError::error_name(&ErrorEnum::<u64>::NotFound), "NotFound");
+    assert_eq!(
+        near_sdk::ContractError::error_data(&DataError::InsufficientBalance { missing: 1 }),
+        Some(r#"{"InsufficientBalance":{"missing":1}}"#.to_string())
+    );
+}
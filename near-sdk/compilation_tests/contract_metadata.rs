@@ -0,0 +1,30 @@
+//! Generating the NEP-330 source metadata of the contract.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, Standard};
+
+#[near_bindgen(contract_metadata(
+    link = "https://github.com/near/near-sdk-rs",
+    standard(standard = "nep141", version = "1.0.0"),
+    standard(standard = "nep148", version = "1.0.0")
+))]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+pub struct FungibleToken {
+    total_supply: u128,
+}
+
+#[near_bindgen]
+impl FungibleToken {
+    pub fn ft_total_supply(&self) -> u128 {
+        self.total_supply
+    }
+}
+
+fn main() {
+    let metadata = FungibleToken::contract_source_metadata();
+    assert!(metadata.version.is_some());
+    assert_eq!(metadata.link.as_deref(), Some("https://github.com/near/near-sdk-rs"));
+    let standard = |standard: &str| Standard { standard: standard.into(), version: "1.0.0".into() };
+    assert_eq!(metadata.standards[1..], [standard("nep141"), standard("nep148")]);
+    assert_eq!(metadata.standards[0].standard, "nep330");
+}
@@ -0,0 +1,29 @@
+//! Deprecated methods, which log an event when called.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Counter {
+    value: u32,
+}
+
+#[near_bindgen]
+impl Counter {
+    #[deprecated_method(since = "1.2", use = "get_value")]
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    #[deprecated_method]
+    pub fn reset(&mut self) {
+        self.value = 0;
+    }
+
+    pub fn get_value(&self) -> u32 {
+        self.value
+    }
+}
+
+fn main() {}
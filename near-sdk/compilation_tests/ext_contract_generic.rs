@@ -0,0 +1,31 @@
+//! Generic traits and methods, with where-clauses, in `#[ext_contract]`.
+
+use near_sdk::serde::Serialize;
+use near_sdk::{ext_contract, AccountId};
+
+#[ext_contract(ext_token)]
+pub trait Token<B>
+where
+    B: Serialize,
+{
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: B);
+    fn ft_transfer_call<M: Serialize>(&mut self, receiver_id: AccountId, amount: B, msg: M);
+    fn ft_batch_transfer<I>(&mut self, receivers: I)
+    where
+        I: IntoIterator<Item = (AccountId, u64)> + Serialize;
+    fn ft_total_supply(&self) -> B;
+}
+
+fn main() {
+    let receiver_id: AccountId = "bob.near".parse().unwrap();
+    let _ = ext_token::ext("token.near".parse().unwrap())
+        .ft_transfer(receiver_id.clone(), near_sdk::json_types::U128(10));
+    let _ = ext_token::ext("token.near".parse().unwrap()).ft_transfer_call(
+        receiver_id.clone(),
+        10u64,
+        "message",
+    );
+    let _ = ext_token::ext("token.near".parse().unwrap())
+        .ft_batch_transfer(vec![(receiver_id, 10u64)]);
+    let _ = ext_token::ext("token.near".parse().unwrap()).ft_total_supply();
+}
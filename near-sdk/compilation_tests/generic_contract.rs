@@ -0,0 +1,49 @@
+//! Contract struct generic over a strategy type, with a generic impl section exported for a
+//! concrete strategy.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+pub trait Strategy: BorshSerialize + BorshDeserialize + Default {
+    fn next(&self, value: u64) -> u64;
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Double;
+
+impl Strategy for Double {
+    fn next(&self, value: u64) -> u64 {
+        value * 2
+    }
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Counter<S: Strategy> {
+    value: u64,
+    strategy: S,
+}
+
+#[near_bindgen(contract = Counter<Double>)]
+impl<S: Strategy> Counter<S> {
+    #[init]
+    pub fn new(value: u64) -> Self {
+        Self { value, strategy: S::default() }
+    }
+
+    pub fn step(&mut self) -> u64 {
+        self.value = self.strategy.next(self.value);
+        self.value
+    }
+
+    #[result_serializer(borsh)]
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+}
+
+fn main() {
+    let mut counter = Counter::<Double>::new(1);
+    assert_eq!(counter.step(), 2);
+    let _ = Counter::<Double>::ext("counter.near".parse().unwrap()).step();
+}
@@ -0,0 +1,58 @@
+//! Calling functions before and after the methods of the contract.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{env, near_bindgen, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Token {
+    paused: bool,
+    calls: u64,
+}
+
+impl Token {
+    fn assert_not_paused(&self) {
+        if self.paused {
+            env::panic_str("The contract is paused");
+        }
+    }
+
+    fn record_call(&mut self) {
+        self.calls += 1;
+    }
+
+    fn log_call() {
+        env::log_str(&format!("Called by {}", env::predecessor_account_id()));
+    }
+}
+
+#[near_bindgen(before(call = "self.assert_not_paused"), after(call = "self.record_call"))]
+impl Token {
+    #[init]
+    #[after(call = "Self::log_call")]
+    pub fn new() -> Self {
+        Self { paused: false, calls: 0 }
+    }
+
+    pub fn transfer(&mut self, amount: u64) -> u64 {
+        amount
+    }
+
+    #[after(skip)]
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    #[private]
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    #[private]
+    #[before(skip)]
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+}
+
+fn main() {}
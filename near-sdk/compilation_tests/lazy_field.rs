@@ -0,0 +1,50 @@
+//! Fields loaded from their own storage key only when they are used.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+use near_sdk::store::Lazy;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+struct Config {
+    owner: String,
+    fee: u32,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize)]
+struct Contract {
+    counter: u64,
+    #[lazy]
+    config: Config,
+    #[lazy]
+    history: Vec<u64>,
+}
+
+impl Default for Contract {
+    fn default() -> Self {
+        Self {
+            counter: 0,
+            config: Lazy::new(b"c", Config { owner: "alice".to_string(), fee: 1 }),
+            history: Lazy::new(b"h", Vec::new()),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn increment(&mut self) -> u64 {
+        self.counter += 1;
+        self.counter
+    }
+
+    pub fn set_fee(&mut self, fee: u32) {
+        self.config.fee = fee;
+        self.history.push(fee as u64);
+    }
+
+    pub fn owner(&self) -> String {
+        self.config.owner.clone()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+//! `#[lazy]` does not take arguments.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Contract {
+    #[lazy(key = "c")]
+    config: u64,
+}
+
+fn main() {}
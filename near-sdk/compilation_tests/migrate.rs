@@ -0,0 +1,32 @@
+//! Generating the migration of the contract state from a previous version.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{migrate, near_bindgen, PanicOnDefault};
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldIncrementer {
+    value: u32,
+}
+
+#[near_bindgen]
+#[migrate(from = OldIncrementer)]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Incrementer {
+    value: u64,
+    step: u64,
+}
+
+impl From<OldIncrementer> for Incrementer {
+    fn from(old: OldIncrementer) -> Self {
+        Self { value: old.value.into(), step: 1 }
+    }
+}
+
+#[near_bindgen]
+impl Incrementer {
+    pub fn inc(&mut self) {
+        self.value += self.step;
+    }
+}
+
+fn main() {}
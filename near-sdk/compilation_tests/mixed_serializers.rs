@@ -0,0 +1,39 @@
+//! Mixing JSON and Borsh arguments within one method.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Promise};
+
+#[ext_contract(ext_verifier)]
+pub trait Verifier {
+    fn verify(&self, statement: String, #[serializer(borsh)] proof: Vec<u8>) -> bool;
+}
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+pub struct Bridge {
+    verified: u64,
+}
+
+#[near_bindgen]
+impl Bridge {
+    pub fn submit(
+        &mut self,
+        statement: String,
+        #[serializer(borsh)] proof: Vec<u8>,
+        #[serializer(borsh)] height: u64,
+    ) {
+        assert!(!statement.is_empty() && !proof.is_empty() && height > 0);
+        self.verified += 1;
+    }
+
+    pub fn forward(&self, verifier: AccountId, statement: String, proof: Vec<u8>) -> Promise {
+        ext_verifier::ext(verifier).verify(statement, proof)
+    }
+
+    pub fn verified(&self) -> u64 {
+        env::log_str("verified");
+        self.verified
+    }
+}
+
+fn main() {}
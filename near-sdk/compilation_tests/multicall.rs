@@ -0,0 +1,39 @@
+//! Calling several change methods of the contract in a single batch.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Config {
+    owner_id: AccountId,
+    fee: u32,
+    paused: bool,
+}
+
+#[near_bindgen(multicall)]
+impl Config {
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        Self { owner_id, fee: 0, paused: false }
+    }
+
+    pub fn set_fee(&mut self, #[validate(range(max = 1000))] fee: u32) {
+        self.fee = fee;
+    }
+
+    pub fn set_owner(&mut self, owner_id: &AccountId) {
+        self.owner_id = owner_id.clone();
+    }
+
+    #[result_serializer(borsh)]
+    pub fn pause(&mut self) -> bool {
+        std::mem::replace(&mut self.paused, true)
+    }
+
+    pub fn fee(&self) -> u32 {
+        self.fee
+    }
+}
+
+fn main() {}
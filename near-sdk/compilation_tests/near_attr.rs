@@ -0,0 +1,70 @@
+//! Contract written with `#[near]` only, without depending on `borsh` or `serde` directly.
+
+use near_sdk::near;
+
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    owner_id: String,
+    fee_rate: u32,
+}
+
+#[near(serializers = [json])]
+pub enum Status {
+    Active,
+    Paused { reason: String },
+}
+
+#[near]
+#[borsh_init(init)]
+pub struct Stats {
+    calls: u64,
+    #[borsh_skip]
+    cached: u64,
+}
+
+impl Stats {
+    fn init(&mut self) {
+        self.cached = self.calls;
+    }
+}
+
+#[near(contract_state)]
+pub struct Contract {
+    config: Config,
+    stats: Stats,
+    paused: bool,
+}
+
+impl Default for Contract {
+    fn default() -> Self {
+        Self {
+            config: Config { owner_id: "alice".to_string(), fee_rate: 1 },
+            stats: Stats { calls: 0, cached: 0 },
+            paused: false,
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    pub fn config(&self) -> Config {
+        self.config.clone()
+    }
+
+    pub fn set_config(&mut self, config: Config) {
+        self.stats.calls += 1;
+        self.config = config;
+    }
+
+    pub fn status(&self) -> Status {
+        if self.paused {
+            Status::Paused { reason: "maintenance".to_string() }
+        } else {
+            Status::Active
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,36 @@
+//! Testing NearEvent macro.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{AccountId, NearEvent};
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct Transfer<'a> {
+    old_owner_id: &'a AccountId,
+    new_owner_id: &'a AccountId,
+    amount: U128,
+}
+
+#[derive(NearEvent)]
+#[event(standard = "nep141", version = "1.0.0")]
+enum TokenEvent<'a, T: Serialize> {
+    FtMint { owner_id: &'a AccountId, amount: U128, memo: Option<T> },
+    FtTransfer(Transfer<'a>),
+    #[event(name = "paused")]
+    Pause,
+    r#Burn { r#type: T },
+}
+
+fn main() {
+    let alice: AccountId = "alice.near".parse().unwrap();
+    let bob: AccountId = "bob.near".parse().unwrap();
+    let memo: Option<String> = None;
+    TokenEvent::FtMint { owner_id: &alice, amount: U128(1), memo }.emit();
+    TokenEvent::<'_, String>::emit_many(&[
+        TokenEvent::FtTransfer(Transfer { old_owner_id: &alice, new_owner_id: &bob, amount: U128(1) }),
+        TokenEvent::FtTransfer(Transfer { old_owner_id: &bob, new_owner_id: &alice, amount: U128(1) }),
+    ]);
+    let _ = TokenEvent::<'_, u8>::Pause.to_event_log();
+    let _ = TokenEvent::Burn { r#type: 1u8 }.to_event_log();
+}
@@ -0,0 +1,51 @@
+//! Methods restricted to accounts with roles.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::acl::{AccessControl, Acl};
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+struct Contract {
+    acl: Acl,
+    value: u64,
+}
+
+impl AccessControl for Contract {
+    fn acl(&self) -> &Acl {
+        &self.acl
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new(owner_id: AccountId) -> Self {
+        let mut acl = Acl::new(b"a");
+        acl.grant_role("admin", owner_id);
+        Self { acl, value: 0 }
+    }
+
+    #[only(role = "admin")]
+    pub fn grant(&mut self, role: String, account_id: AccountId) -> bool {
+        self.acl.grant_role(&role, account_id)
+    }
+
+    #[only(role = "admin", role = "operator")]
+    #[payable]
+    pub fn set_value(&mut self, value: u64) {
+        self.value = value;
+    }
+
+    #[only(role = "auditor")]
+    pub fn audit(&self) -> u64 {
+        self.value
+    }
+
+    #[only(role = "admin")]
+    pub fn into_value(self) -> u64 {
+        self.value
+    }
+}
+
+fn main() {}
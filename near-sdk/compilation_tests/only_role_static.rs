@@ -0,0 +1,16 @@
+//! Methods restricted to roles must have a contract state to read the roles from.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    #[only(role = "admin")]
+    pub fn method() {}
+}
+
+fn main() {}
@@ -0,0 +1,37 @@
+//! Payable methods with constraints on the attached deposit.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Contract {
+    value: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable(exactly_one_yocto)]
+    pub fn confirm(&mut self) {
+        self.value += 1;
+    }
+
+    #[payable(min = "0.1 NEAR")]
+    pub fn register(&mut self) {
+        self.value += 1;
+    }
+
+    #[payable(min = "1000 yoctoNEAR", refund_excess)]
+    pub fn store(&mut self, value: u64) -> u64 {
+        self.value = value;
+        value
+    }
+
+    #[init]
+    #[payable(min = "1 NEAR")]
+    pub fn new() -> Self {
+        Self { value: 0 }
+    }
+}
+
+fn main() {}
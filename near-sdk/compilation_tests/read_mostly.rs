@@ -0,0 +1,25 @@
+//! Method that only writes the state back if it was modified.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+#[near_bindgen]
+#[derive(Default, BorshDeserialize, BorshSerialize)]
+struct Incrementer {
+    value: u32,
+}
+
+#[near_bindgen]
+impl Incrementer {
+    #[read_mostly]
+    pub fn inc_if_below(&mut self, limit: u32) -> bool {
+        if self.value < limit {
+            self.value += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+//! Storage prefixes of a contract registered to check that they do not overlap.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, Vector};
+use near_sdk::{near_bindgen, storage_keys, BorshStorageKey};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Accounts,
+    History { account_id: String },
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum Other {
+    Unused,
+    Tokens,
+}
+
+storage_keys!(StorageKey, b"config", "metadata", b"\x05");
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize)]
+struct Contract {
+    accounts: LookupMap<String, u64>,
+    config: Vector<u8>,
+}
+
+impl Default for Contract {
+    fn default() -> Self {
+        Self { accounts: LookupMap::new(StorageKey::Accounts), config: Vector::new(b"config".to_vec()) }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get(&self, account_id: String) -> Option<u64> {
+        self.accounts.get(&account_id)
+    }
+}
+
+fn main() {
+    let _ = (StorageKey::History { account_id: String::new() }, Other::Unused, Other::Tokens);
+}
@@ -0,0 +1,18 @@
+//! Storage prefixes that overlap are rejected.
+
+use borsh::BorshSerialize;
+use near_sdk::{storage_keys, BorshStorageKey};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    Accounts,
+    Tokens,
+}
+
+storage_keys!(b"config", "conf");
+
+storage_keys!(StorageKey, b"a", b"\x01");
+
+fn main() {
+    let _ = (StorageKey::Accounts, StorageKey::Tokens);
+}
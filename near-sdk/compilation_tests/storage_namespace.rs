@@ -0,0 +1,36 @@
+//! Testing StorageNamespace macro.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::{LookupMap, UnorderedSet, Vector};
+use near_sdk::{near_bindgen, Namespace, StorageNamespace};
+
+#[derive(BorshDeserialize, BorshSerialize, StorageNamespace)]
+struct Account<T: BorshSerialize + BorshDeserialize> {
+    tokens: UnorderedSet<T>,
+    #[namespace(name = "h")]
+    history: Vector<u64>,
+    #[namespace(skip)]
+    nonce: u64,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize)]
+struct Contract {
+    accounts: LookupMap<String, Account<String>>,
+}
+
+impl Default for Contract {
+    fn default() -> Self {
+        Self { accounts: LookupMap::new(b"a") }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn register(&mut self, account_id: String) {
+        let account: Account<String> = Namespace::new(b"n").nested(&account_id).collection();
+        self.accounts.insert(&account_id, &account);
+    }
+}
+
+fn main() {}
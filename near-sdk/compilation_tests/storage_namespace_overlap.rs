@@ -0,0 +1,14 @@
+//! StorageNamespace field names must not be prefixes of each other.
+
+use near_sdk::collections::Vector;
+use near_sdk::StorageNamespace;
+
+#[derive(StorageNamespace)]
+struct Account {
+    #[namespace(name = "t")]
+    tokens: Vector<u64>,
+    #[namespace(name = "tx")]
+    transactions: Vector<u64>,
+}
+
+fn main() {}
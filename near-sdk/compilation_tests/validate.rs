@@ -0,0 +1,43 @@
+//! Validating the arguments of the methods with attributes.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::collections::UnorderedMap;
+use near_sdk::{near_bindgen, PanicOnDefault};
+
+const MAX_SUPPLY: u128 = 1_000_000;
+
+fn is_valid_symbol(symbol: &str) -> bool {
+    symbol.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Registry {
+    tokens: UnorderedMap<String, u128>,
+}
+
+#[near_bindgen]
+impl Registry {
+    #[init]
+    pub fn new(#[validate(len(min = 1))] prefix: Vec<u8>) -> Self {
+        Self { tokens: UnorderedMap::new(prefix) }
+    }
+
+    pub fn register(
+        &mut self,
+        #[validate(len(min = 3, max = 8), predicate = "is_valid_symbol")] symbol: String,
+        #[validate(range(min = 1, max = MAX_SUPPLY))] supply: u128,
+    ) {
+        self.tokens.insert(&symbol, &supply);
+    }
+
+    pub fn supply(&self, #[validate(predicate = "Self::is_registered")] symbol: String) -> u128 {
+        self.tokens.get(&symbol).unwrap()
+    }
+
+    fn is_registered(symbol: &str) -> bool {
+        !symbol.is_empty()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+//! Tools working with the ABI of a contract, as generated with the `__abi-generate` feature, for
+//! example by [`cargo near`](https://github.com/near/cargo-near).
+
+pub use near_abi::{
+    AbiBody, AbiFunction, AbiMetadata, AbiParameter, AbiRoot, AbiType, SCHEMA_VERSION,
+};
+
+mod typescript;
+pub use typescript::generate_typescript_client;
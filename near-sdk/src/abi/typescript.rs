@@ -0,0 +1,395 @@
+use near_abi::{AbiFunction, AbiRoot, AbiType};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use std::fmt::Write;
+
+/// Generates a typed TypeScript client of the contract with the given ABI.
+///
+/// The client declares a type for each definition of the ABI schema, an interface for the
+/// arguments of each method, and a class with one async function per method, calling it as a view
+/// or a change method. Private methods, which can only be called by the contract itself, are left
+/// out. The arguments and the results of methods using Borsh are passed as raw bytes.
+///
+/// The client does not depend on any library: the calls go through a `ContractConnection`, which
+/// can be implemented with `near-api-js` for example.
+///
+/// This is meant to be used from a build script or a tool reading the ABI of the contract:
+///
+/// ```ignore
+/// let abi: near_sdk::abi::AbiRoot = serde_json::from_str(&std::fs::read_to_string(abi_path)?)?;
+/// std::fs::write("client.ts", near_sdk::abi::generate_typescript_client(&abi))?;
+/// ```
+pub fn generate_typescript_client(abi: &AbiRoot) -> String {
+    let mut out = String::from(PRELUDE);
+
+    for (name, schema) in &abi.body.root_schema.definitions {
+        write_doc(&mut out, "", description(schema));
+        let _ = writeln!(out, "export type {} = {};\n", name, ts_type(schema));
+    }
+
+    let functions: Vec<&AbiFunction> =
+        abi.body.functions.iter().filter(|f| !f.is_private).collect();
+    for function in &functions {
+        if takes_json_args(function) && !function.params.is_empty() {
+            let _ = writeln!(out, "export interface {} {{", args_interface(function));
+            for param in &function.params {
+                if let AbiType::Json { type_schema } = &param.typ {
+                    let _ = writeln!(
+                        out,
+                        "  {}: {};",
+                        property_name(&param.name),
+                        ts_type(type_schema)
+                    );
+                }
+            }
+            out.push_str("}\n\n");
+        }
+    }
+
+    let class =
+        format!("{}Client", pascal_case(abi.metadata.name.as_deref().unwrap_or("contract")));
+    let methods = |view: bool| {
+        functions
+            .iter()
+            .filter(|f| f.is_view == view)
+            .map(|f| quote_str(&f.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let _ = writeln!(out, "export class {} {{", class);
+    let _ = writeln!(out, "  static readonly viewMethods = [{}] as const;", methods(true));
+    let _ = writeln!(out, "  static readonly changeMethods = [{}] as const;\n", methods(false));
+    out.push_str(
+        "  constructor(readonly connection: ContractConnection, readonly contractId: string) {}\n",
+    );
+    for function in &functions {
+        out.push('\n');
+        write_function(&mut out, function);
+    }
+    out.push_str("}\n");
+    out
+}
+
+const PRELUDE: &str = "\
+// This file was generated from the ABI of the contract. Do not edit it by hand.
+
+/** Options of a call to a change method. */
+export interface CallOptions {
+  /** Gas attached to the call, in gas units. */
+  gas?: string;
+  /** Deposit attached to the call, in yoctoNEAR. */
+  attachedDeposit?: string;
+}
+
+/** Calls the methods of a contract, for example through `near-api-js`. */
+export interface ContractConnection {
+  view(contractId: string, methodName: string, args: Uint8Array): Promise<Uint8Array>;
+  call(
+    contractId: string,
+    methodName: string,
+    args: Uint8Array,
+    options?: CallOptions,
+  ): Promise<Uint8Array>;
+}
+
+function encodeJson(value: unknown): Uint8Array {
+  return new TextEncoder().encode(JSON.stringify(value));
+}
+
+function decodeJson<T>(bytes: Uint8Array): T {
+  return JSON.parse(new TextDecoder().decode(bytes)) as T;
+}
+
+";
+
+fn write_function(out: &mut String, function: &AbiFunction) {
+    let mut doc = function.doc.clone().unwrap_or_default();
+    if function.is_init {
+        doc.push_str("\n\nInitializes the contract.");
+    }
+    if function.is_payable {
+        doc.push_str("\n\nAccepts an attached deposit.");
+    }
+    write_doc(out, "  ", Some(&doc));
+
+    let mut params = Vec::new();
+    let args = if !takes_json_args(function) {
+        params.push("args: Uint8Array".to_string());
+        "args"
+    } else if function.params.is_empty() {
+        "new Uint8Array()"
+    } else {
+        params.push(format!("args: {}", args_interface(function)));
+        "encodeJson(args)"
+    };
+    let call = if function.is_view {
+        format!("this.connection.view(this.contractId, {}, {})", quote_str(&function.name), args)
+    } else {
+        params.push("options?: CallOptions".to_string());
+        format!(
+            "this.connection.call(this.contractId, {}, {}, options)",
+            quote_str(&function.name),
+            args
+        )
+    };
+    let (result, ret) = match &function.result {
+        None => ("void".to_string(), format!("    await {};", call)),
+        Some(AbiType::Json { type_schema }) => {
+            let typ = ts_type(type_schema);
+            let ret = format!("    return decodeJson<{}>(await {});", typ, call);
+            (typ, ret)
+        }
+        Some(AbiType::Borsh { .. }) => ("Uint8Array".to_string(), format!("    return {};", call)),
+    };
+    let _ = writeln!(
+        out,
+        "  async {}({}): Promise<{}> {{\n{}\n  }}",
+        camel_case(&function.name),
+        params.join(", "),
+        result,
+        ret
+    );
+}
+
+/// Converts a JSON schema, as found in the ABI, to the TypeScript type of the values it matches.
+fn ts_type(schema: &Schema) -> String {
+    match schema {
+        Schema::Bool(true) => "unknown".to_string(),
+        Schema::Bool(false) => "never".to_string(),
+        Schema::Object(object) => object_type(object),
+    }
+}
+
+fn object_type(schema: &SchemaObject) -> String {
+    if let Some(reference) = &schema.reference {
+        return reference.rsplit('/').next().unwrap_or(reference).to_string();
+    }
+    if let Some(values) = &schema.enum_values {
+        return union(values.iter().map(|value| value.to_string()).collect());
+    }
+    if let Some(subschemas) = &schema.subschemas {
+        if let Some(schemas) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            return union(schemas.iter().map(ts_type).collect());
+        }
+        if let Some(schemas) = &subschemas.all_of {
+            return schemas.iter().map(ts_type).collect::<Vec<_>>().join(" & ");
+        }
+    }
+    match &schema.instance_type {
+        None => "unknown".to_string(),
+        Some(SingleOrVec::Single(typ)) => instance_type(schema, typ),
+        Some(SingleOrVec::Vec(types)) => {
+            union(types.iter().map(|typ| instance_type(schema, typ)).collect())
+        }
+    }
+}
+
+fn instance_type(schema: &SchemaObject, typ: &InstanceType) -> String {
+    match typ {
+        InstanceType::Null => "null".to_string(),
+        InstanceType::Boolean => "boolean".to_string(),
+        InstanceType::Integer | InstanceType::Number => "number".to_string(),
+        InstanceType::String => "string".to_string(),
+        InstanceType::Array => match schema.array.as_ref().and_then(|array| array.items.as_ref()) {
+            Some(SingleOrVec::Single(item)) => format!("{}[]", wrap(ts_type(item))),
+            Some(SingleOrVec::Vec(items)) => {
+                format!("[{}]", items.iter().map(ts_type).collect::<Vec<_>>().join(", "))
+            }
+            None => "unknown[]".to_string(),
+        },
+        InstanceType::Object => {
+            let object = match &schema.object {
+                Some(object) => object,
+                None => return "Record<string, unknown>".to_string(),
+            };
+            if object.properties.is_empty() {
+                let values = match &object.additional_properties {
+                    Some(values) => ts_type(values),
+                    None => "unknown".to_string(),
+                };
+                return format!("Record<string, {}>", values);
+            }
+            let properties: Vec<String> = object
+                .properties
+                .iter()
+                .map(|(name, schema)| {
+                    let optional = if object.required.contains(name) { "" } else { "?" };
+                    format!("{}{}: {}", property_name(name), optional, ts_type(schema))
+                })
+                .collect();
+            format!("{{ {} }}", properties.join("; "))
+        }
+    }
+}
+
+fn union(mut types: Vec<String>) -> String {
+    types.dedup();
+    match types.len() {
+        0 => "never".to_string(),
+        1 => types.remove(0),
+        _ => types.into_iter().map(wrap).collect::<Vec<_>>().join(" | "),
+    }
+}
+
+/// Parenthesizes unions and intersections, so that they can be used in arrays and other unions.
+fn wrap(typ: String) -> String {
+    if typ.contains(" | ") || typ.contains(" & ") {
+        format!("({})", typ)
+    } else {
+        typ
+    }
+}
+
+fn description(schema: &Schema) -> Option<&str> {
+    match schema {
+        Schema::Object(object) => object.metadata.as_ref()?.description.as_deref(),
+        Schema::Bool(_) => None,
+    }
+}
+
+fn write_doc(out: &mut String, indent: &str, doc: Option<&str>) {
+    let doc = match doc.map(str::trim) {
+        Some(doc) if !doc.is_empty() => doc,
+        _ => return,
+    };
+    let _ = writeln!(out, "{}/**", indent);
+    for line in doc.lines() {
+        let line = line.trim().replace("*/", "*\\/");
+        if line.is_empty() {
+            let _ = writeln!(out, "{} *", indent);
+        } else {
+            let _ = writeln!(out, "{} * {}", indent, line);
+        }
+    }
+    let _ = writeln!(out, "{} */", indent);
+}
+
+fn takes_json_args(function: &AbiFunction) -> bool {
+    function.params.iter().all(|param| matches!(param.typ, AbiType::Json { .. }))
+}
+
+fn args_interface(function: &AbiFunction) -> String {
+    format!("{}Args", pascal_case(&function.name))
+}
+
+fn property_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_identifier = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        name.to_string()
+    } else {
+        quote_str(name)
+    }
+}
+
+fn quote_str(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map_or_else(String::new, |c| c.to_ascii_uppercase().to_string() + chars.as_str())
+        })
+        .collect()
+}
+
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    chars.next().map_or_else(String::new, |c| c.to_ascii_lowercase().to_string() + chars.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_abi::{AbiBody, AbiMetadata, AbiParameter};
+    use schemars::gen::SchemaGenerator;
+    use std::collections::HashMap;
+
+    #[derive(schemars::JsonSchema)]
+    #[allow(dead_code)]
+    /// A point on the plane.
+    struct Point {
+        x: i32,
+        y: Option<i32>,
+    }
+
+    fn function(name: &str, is_view: bool) -> AbiFunction {
+        AbiFunction {
+            name: name.to_string(),
+            doc: None,
+            is_view,
+            is_init: false,
+            is_payable: false,
+            is_private: false,
+            params: vec![],
+            callbacks: vec![],
+            callbacks_vec: None,
+            result: None,
+        }
+    }
+
+    #[test]
+    fn typescript_client() {
+        let mut gen = SchemaGenerator::default();
+        let mut get_point = function("get_point", true);
+        get_point.doc = Some(" Gets a point.".to_string());
+        get_point.params = vec![AbiParameter {
+            name: "index".to_string(),
+            typ: AbiType::Json { type_schema: gen.subschema_for::<u64>() },
+        }];
+        get_point.result =
+            Some(AbiType::Json { type_schema: gen.subschema_for::<Option<Point>>() });
+        let mut add_points = function("add_points", false);
+        add_points.is_payable = true;
+        add_points.params = vec![AbiParameter {
+            name: "points".to_string(),
+            typ: AbiType::Json { type_schema: gen.subschema_for::<Vec<Point>>() },
+        }];
+        let mut on_added = function("on_added", false);
+        on_added.is_private = true;
+        let abi = AbiRoot {
+            schema_version: near_abi::SCHEMA_VERSION.to_string(),
+            metadata: AbiMetadata {
+                name: Some("point-registry".to_string()),
+                version: None,
+                authors: vec![],
+                other: HashMap::new(),
+            },
+            body: AbiBody {
+                functions: vec![get_point, add_points, on_added],
+                root_schema: gen.into_root_schema_for::<String>(),
+            },
+        };
+
+        let client = generate_typescript_client(&abi);
+        assert!(client.contains(
+            "/**\n * A point on the plane.\n */\nexport type Point = { x: number; y?: number | null };"
+        ));
+        assert!(client.contains("export interface GetPointArgs {\n  index: number;\n}"));
+        assert!(client.contains("export interface AddPointsArgs {\n  points: Point[];\n}"));
+        assert!(client.contains("export class PointRegistryClient {"));
+        assert!(client.contains("static readonly viewMethods = [\"get_point\"] as const;"));
+        assert!(client.contains("static readonly changeMethods = [\"add_points\"] as const;"));
+        assert!(client.contains(
+            "  /**\n   * Gets a point.\n   */\n  async getPoint(args: GetPointArgs): Promise<Point | null> {\n    return decodeJson<Point | null>(await this.connection.view(this.contractId, \"get_point\", encodeJson(args)));\n  }"
+        ));
+        assert!(client.contains(
+            "  async addPoints(args: AddPointsArgs, options?: CallOptions): Promise<void> {\n    await this.connection.call(this.contractId, \"add_points\", encodeJson(args), options);\n  }"
+        ));
+        assert!(!client.contains("on_added"));
+    }
+
+    #[test]
+    fn case_conversions() {
+        assert_eq!(pascal_case("get_point"), "GetPoint");
+        assert_eq!(camel_case("ft_transfer_call"), "ftTransferCall");
+        assert_eq!(property_name("receiver_id"), "receiver_id");
+        assert_eq!(property_name("token-id"), "\"token-id\"");
+    }
+}
@@ -0,0 +1,142 @@
+//! Access control of contract methods by roles of accounts.
+//!
+//! The roles of the accounts are kept in an [`Acl`], which is usually a field of the contract
+//! state, and the contract gives access to it by implementing [`AccessControl`]. Methods marked
+//! with `#[only(role = "..")]` in a `#[near_bindgen]` implementation can then only be called by
+//! accounts with one of the given roles, which is checked at the start of the method, before its
+//! body runs.
+//!
+//! ```
+//! use near_sdk::acl::{AccessControl, Acl};
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault};
+//!
+//! #[near_bindgen]
+//! #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+//! pub struct Contract {
+//!     acl: Acl,
+//!     paused: bool,
+//! }
+//!
+//! impl AccessControl for Contract {
+//!     fn acl(&self) -> &Acl {
+//!         &self.acl
+//!     }
+//! }
+//!
+//! #[near_bindgen]
+//! impl Contract {
+//!     #[init]
+//!     pub fn new(owner_id: AccountId) -> Self {
+//!         let mut acl = Acl::new(b"a");
+//!         acl.grant_role("admin", owner_id);
+//!         Self { acl, paused: false }
+//!     }
+//!
+//!     #[only(role = "admin")]
+//!     pub fn add_pauser(&mut self, account_id: AccountId) {
+//!         self.acl.grant_role("pauser", account_id);
+//!     }
+//!
+//!     // Callable by admins and pausers.
+//!     #[only(role = "admin", role = "pauser")]
+//!     pub fn pause(&mut self) {
+//!         self.paused = true;
+//!     }
+//! }
+//! ```
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::collections::LookupSet;
+use crate::{env, AccountId, IntoStorageKey};
+
+/// Roles granted to accounts, stored with a key for each pair of a role and an account.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Acl {
+    members: LookupSet<(String, AccountId)>,
+}
+
+impl Acl {
+    /// Creates an empty access control list. Use `prefix` as a unique prefix for its keys.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { members: LookupSet::new(prefix) }
+    }
+
+    /// Returns `true` if the account has the role.
+    pub fn has_role(&self, role: &str, account_id: &AccountId) -> bool {
+        self.members.contains(&(role.to_string(), account_id.clone()))
+    }
+
+    /// Returns `true` if the account has any of the roles.
+    pub fn has_any_role(&self, roles: &[&str], account_id: &AccountId) -> bool {
+        roles.iter().any(|role| self.has_role(role, account_id))
+    }
+
+    /// Grants the role to the account. Returns `false` if the account already had it.
+    pub fn grant_role(&mut self, role: &str, account_id: AccountId) -> bool {
+        self.members.insert(&(role.to_string(), account_id))
+    }
+
+    /// Revokes the role from the account. Returns `false` if the account did not have it.
+    pub fn revoke_role(&mut self, role: &str, account_id: &AccountId) -> bool {
+        self.members.remove(&(role.to_string(), account_id.clone()))
+    }
+
+    /// Panics unless the predecessor account has any of the roles. This is the check generated
+    /// for methods marked with `#[only(role = "..")]`.
+    pub fn assert_any_role(&self, roles: &[&str]) {
+        if !self.has_any_role(roles, &env::predecessor_account_id()) {
+            let roles: Vec<String> = roles.iter().map(|role| format!("`{}`", role)).collect();
+            env::panic_str(&format!("Requires role {}", roles.join(" or ")))
+        }
+    }
+}
+
+impl std::fmt::Debug for Acl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Acl").field("members", &self.members).finish()
+    }
+}
+
+/// Contract state holding the [`Acl`] checked by methods marked with `#[only(role = "..")]`.
+pub trait AccessControl {
+    /// Access control list of the contract.
+    fn acl(&self) -> &Acl;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::Acl;
+    use crate::test_utils::{accounts, VMContextBuilder};
+    use crate::testing_env;
+
+    #[test]
+    fn grant_and_revoke() {
+        let mut acl = Acl::new(b"a");
+        assert!(acl.grant_role("admin", accounts(0)));
+        assert!(!acl.grant_role("admin", accounts(0)));
+        assert!(acl.grant_role("pauser", accounts(1)));
+
+        assert!(acl.has_role("admin", &accounts(0)));
+        assert!(!acl.has_role("pauser", &accounts(0)));
+        assert!(acl.has_any_role(&["admin", "pauser"], &accounts(1)));
+        assert!(!acl.has_any_role(&["admin"], &accounts(1)));
+
+        assert!(acl.revoke_role("admin", &accounts(0)));
+        assert!(!acl.revoke_role("admin", &accounts(0)));
+        assert!(!acl.has_role("admin", &accounts(0)));
+    }
+
+    #[test]
+    fn assert_predecessor_role() {
+        let mut acl = Acl::new(b"a");
+        acl.grant_role("admin", accounts(0));
+        testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(0)).build());
+        acl.assert_any_role(&["pauser", "admin"]);
+    }
+}
@@ -95,6 +95,24 @@ impl<K, V> UnorderedMap<K, V> {
         append_slice(&self.key_index_prefix, raw_key)
     }
 
+    /// Removes the serialized key and value at `index` from storage, without changing the length
+    /// of the map. Used to move entries out in order, after which [`Self::forget_entries`] resets
+    /// the length.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn take_entry_raw(&mut self, index: u64) -> (Vec<u8>, Vec<u8>) {
+        let key_raw = self.keys.take_raw(index);
+        env::storage_remove(&self.raw_key_to_index_lookup(&key_raw));
+        (key_raw, self.values.take_raw(index))
+    }
+
+    /// Resets the length of the map, after all entries were removed with
+    /// [`Self::take_entry_raw`].
+    #[cfg(feature = "unstable")]
+    pub(crate) fn forget_entries(&mut self) {
+        self.keys.forget_elements();
+        self.values.forget_elements();
+    }
+
     /// Returns an index of the given raw key.
     fn get_index_raw(&self, key_raw: &[u8]) -> Option<u64> {
         let index_lookup = self.raw_key_to_index_lookup(key_raw);
@@ -72,6 +72,28 @@ impl<T> Vector<T> {
         }
     }
 
+    /// Removes the serialized element at `index` from storage, without changing the length of
+    /// the vector. Used to move elements out in order, after which [`Self::forget_elements`]
+    /// resets the length.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn take_raw(&mut self, index: u64) -> Vec<u8> {
+        if index >= self.len {
+            env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)
+        }
+        let lookup_key = self.index_to_lookup_key(index);
+        if env::storage_remove(&lookup_key) {
+            expect_consistent_state(env::storage_get_evicted())
+        } else {
+            env::panic_str(ERR_INCONSISTENT_STATE)
+        }
+    }
+
+    /// Resets the length of the vector, after all elements were removed with [`Self::take_raw`].
+    #[cfg(feature = "unstable")]
+    pub(crate) fn forget_elements(&mut self) {
+        self.len = 0;
+    }
+
     fn index_to_lookup_key(&self, index: u64) -> Vec<u8> {
         append_slice(&self.prefix, &index.to_le_bytes()[..])
     }
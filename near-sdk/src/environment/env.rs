@@ -3,17 +3,20 @@
 //! whenever possible. In case of cross-contract calls prefer using even higher-level API available
 //! through `callback_args`, `callback_args_vec`, `ext_contract`, `Promise`, and `PromiseOrValue`.
 
+use std::cell::RefCell;
 use std::convert::TryInto;
 use std::mem::size_of;
 use std::panic as std_panic;
 use std::{convert::TryFrom, mem::MaybeUninit};
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "unit-testing"))]
-use crate::mock::MockedBlockchain;
+use crate::mock::BlockchainInterface;
+use crate::types::alt_bn128::{G1Point, G2Point};
+use crate::types::bls12_381;
 use crate::types::{
     AccountId, Balance, BlockHeight, Gas, PromiseIndex, PromiseResult, PublicKey, StorageUsage,
 };
-use crate::{GasWeight, PromiseError};
+use crate::{EnvError, GasWeight, ParseInputError, PromiseError};
 use near_sys as sys;
 
 const REGISTER_EXPECTED_ERR: &str =
@@ -27,13 +30,42 @@ const ATOMIC_OP_REGISTER: u64 = std::u64::MAX - 2;
 const EVICTED_REGISTER: u64 = std::u64::MAX - 1;
 
 /// Key used to store the state of the contract.
-const STATE_KEY: &[u8] = b"STATE";
+pub(crate) const STATE_KEY: &[u8] = b"STATE";
 
 /// The minimum length of a valid account ID.
 const MIN_ACCOUNT_ID_LEN: u64 = 2;
 /// The maximum length of a valid account ID.
 const MAX_ACCOUNT_ID_LEN: u64 = 64;
 
+/// The maximum length of a log message with the default protocol limits.
+const MAX_LOG_LEN: usize = 16 * 1024;
+
+/// Context values that do not change during a contract call, which are cached after they are
+/// read for the first time, to not cross the host boundary and validate them again.
+#[derive(Default)]
+struct ContextCache {
+    current_account_id: Option<AccountId>,
+    signer_account_id: Option<AccountId>,
+    predecessor_account_id: Option<AccountId>,
+    attached_deposit: Option<Balance>,
+}
+
+thread_local! {
+    /// Cache of the context of the current call. It is reset when the blockchain interface is
+    /// replaced, which `testing_env!` does for every new context in unit tests.
+    static CONTEXT_CACHE: RefCell<ContextCache> = RefCell::new(ContextCache::default());
+}
+
+/// Returns the cached context value, reading it with `read` if it is not cached yet.
+fn cached<T: Clone>(field: fn(&mut ContextCache) -> &mut Option<T>, read: fn() -> T) -> T {
+    if let Some(value) = CONTEXT_CACHE.with(|cache| field(&mut cache.borrow_mut()).clone()) {
+        return value;
+    }
+    let value = read();
+    CONTEXT_CACHE.with(|cache| *field(&mut cache.borrow_mut()) = Some(value.clone()));
+    value
+}
+
 fn expect_register<T>(option: Option<T>) -> T {
     option.unwrap_or_else(|| panic_str(REGISTER_EXPECTED_ERR))
 }
@@ -75,8 +107,8 @@ pub(crate) unsafe fn read_register_fixed_64(register_id: u64) -> [u8; 64] {
 }
 
 /// Replaces the current low-level blockchain interface accessible through `env::*` with another
-/// low-level blockchain interfacr that implements `BlockchainInterface` trait. In most cases you
-/// want to use `testing_env!` macro to set it.
+/// low-level blockchain interface that implements [`BlockchainInterface`] trait. In most cases you
+/// want to use `testing_env!` macro to set a [`MockedBlockchain`](crate::MockedBlockchain).
 ///
 /// ```no_run
 /// # let context = near_sdk::test_utils::VMContextBuilder::new().build();
@@ -96,10 +128,18 @@ pub(crate) unsafe fn read_register_fixed_64(register_id: u64) -> [u8; 64] {
 /// near_sdk::env::set_blockchain_interface(mocked_blockchain);
 /// ```
 #[cfg(all(not(target_arch = "wasm32"), feature = "unit-testing"))]
-pub fn set_blockchain_interface(blockchain_interface: MockedBlockchain) {
-    crate::mock::with_mocked_blockchain(|b| {
-        *b = blockchain_interface;
-    })
+pub fn set_blockchain_interface<B: BlockchainInterface + 'static>(blockchain_interface: B) {
+    replace_blockchain_interface(Box::new(blockchain_interface));
+}
+
+/// Replaces the current low-level blockchain interface with `blockchain_interface` and returns
+/// the previous one, for example to restore it after running code against a custom interface.
+#[cfg(all(not(target_arch = "wasm32"), feature = "unit-testing"))]
+pub fn replace_blockchain_interface(
+    blockchain_interface: Box<dyn BlockchainInterface>,
+) -> Box<dyn BlockchainInterface> {
+    CONTEXT_CACHE.with(|cache| cache.take());
+    crate::mock::replace_blockchain_interface(blockchain_interface)
 }
 
 /// Implements panic hook that converts `PanicInfo` into a string and provides it through the
@@ -145,18 +185,91 @@ pub fn register_len(register_id: u64) -> Option<u64> {
     }
 }
 
+/// Handle to a register of the host, which allows passing data between host functions without
+/// copying it into the memory of the contract.
+///
+/// Host functions accept a register in place of a value in memory, which saves the gas and the
+/// memory of copying large payloads, for example to hash the input or to store it as is. Passing
+/// a register that has not been written to aborts the execution.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env::{self, Register};
+///
+/// let register = Register::new(0);
+/// register.write(b"payload");
+/// env::storage_write_from_register(b"key", &register);
+/// assert_eq!(env::sha256_register(&register), env::sha256_array(b"payload"));
+///
+/// assert!(env::storage_read_into_register(b"key", &register));
+/// assert_eq!(register.read().unwrap(), b"payload");
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Register(u64);
+
+impl Register {
+    /// Creates a handle to the register with the given id.
+    ///
+    /// Panics if the id is one of the registers used internally by the SDK, which are the ones
+    /// from `u64::MAX - 2` upwards.
+    pub fn new(id: u64) -> Self {
+        if id >= ATOMIC_OP_REGISTER {
+            panic_str("Register id is reserved by the SDK");
+        }
+        Self(id)
+    }
+
+    /// The id of the register.
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the length of the data in the register, or `None` if it has not been written to.
+    pub fn len(&self) -> Option<u64> {
+        register_len(self.0)
+    }
+
+    /// Returns `true` if the register has not been written to or contains no data.
+    pub fn is_empty(&self) -> bool {
+        matches!(self.len(), None | Some(0))
+    }
+
+    /// Copies the data of the register into memory, or returns `None` if it has not been
+    /// written to.
+    pub fn read(&self) -> Option<Vec<u8>> {
+        read_register(self.0)
+    }
+
+    /// Replaces the data of the register.
+    pub fn write(&self, data: &[u8]) {
+        unsafe { sys::write_register(self.0, data.len() as _, data.as_ptr() as _) }
+    }
+
+    /// The length argument which makes host functions read the value from this register.
+    fn as_input(&self) -> (u64, u64) {
+        (std::u64::MAX, self.0)
+    }
+}
+
 // ###############
 // # Context API #
 // ###############
 /// The id of the account that owns the current contract.
 pub fn current_account_id() -> AccountId {
-    assert_valid_account_id(method_into_register!(current_account_id))
+    cached(
+        |cache| &mut cache.current_account_id,
+        || assert_valid_account_id(method_into_register!(current_account_id)),
+    )
 }
 
 /// The id of the account that either signed the original transaction or issued the initial
 /// cross-contract call.
 pub fn signer_account_id() -> AccountId {
-    assert_valid_account_id(method_into_register!(signer_account_id))
+    cached(
+        |cache| &mut cache.signer_account_id,
+        || assert_valid_account_id(method_into_register!(signer_account_id)),
+    )
 }
 
 /// The public key of the account that did the signing.
@@ -167,7 +280,10 @@ pub fn signer_account_pk() -> PublicKey {
 /// The id of the account that was the previous contract in the chain of cross-contract calls.
 /// If this is the first contract, it is equal to `signer_account_id`.
 pub fn predecessor_account_id() -> AccountId {
-    assert_valid_account_id(method_into_register!(predecessor_account_id))
+    cached(
+        |cache| &mut cache.predecessor_account_id,
+        || assert_valid_account_id(method_into_register!(predecessor_account_id)),
+    )
 }
 
 /// Helper function to convert and check the account ID from bytes from the runtime.
@@ -183,6 +299,136 @@ pub fn input() -> Option<Vec<u8>> {
     try_method_into_register!(input)
 }
 
+/// Writes the input to the contract call into the register without copying it into memory.
+pub fn input_into_register(register: &Register) {
+    unsafe { sys::input(register.id()) }
+}
+
+/// Deserializes the input to the contract call from JSON, the same way as the arguments of
+/// `#[near_bindgen]` methods. Returns an error if the input is empty or cannot be deserialized,
+/// whose message matches the panic message of the generated code.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::serde::Deserialize;
+/// use near_sdk::test_utils::VMContextBuilder;
+/// use near_sdk::{env, testing_env};
+///
+/// #[derive(Deserialize)]
+/// #[serde(crate = "near_sdk::serde")]
+/// struct Args {
+///     amount: u64,
+/// }
+///
+/// testing_env!(VMContextBuilder::new().input(br#"{"amount":5}"#.to_vec()).build());
+/// let args: Args = env::input_json().unwrap_or_else(|err| env::panic_str(&err.to_string()));
+/// assert_eq!(args.amount, 5);
+/// ```
+pub fn input_json<T: serde::de::DeserializeOwned>() -> Result<T, ParseInputError> {
+    let input = non_empty_input()?;
+    serde_json::from_slice(&input).map_err(|err| ParseInputError::Json(format!("{:?}", err)))
+}
+
+/// Deserializes the input to the contract call from Borsh, the same way as the arguments of
+/// `#[near_bindgen]` methods with `#[serializer(borsh)]`. Returns an error if the input is empty
+/// or cannot be deserialized, whose message matches the panic message of the generated code.
+pub fn input_borsh<T: borsh::BorshDeserialize>() -> Result<T, ParseInputError> {
+    let input = non_empty_input()?;
+    T::try_from_slice(&input).map_err(|err| ParseInputError::Borsh(format!("{:?}", err)))
+}
+
+/// Deserializes a single field of the JSON object input to the contract call, skipping over the
+/// other fields without deserializing them. This is cheaper than [`input_json`] when only one
+/// argument of a large input is needed, like the receiver of a call that also carries a large
+/// payload.
+///
+/// A missing field deserializes to `None` if `T` is an `Option`, the same as with
+/// [`input_json`], and is an error otherwise.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::test_utils::VMContextBuilder;
+/// use near_sdk::{env, testing_env, AccountId};
+///
+/// let input = br#"{"code":"AGFzbQE=","receiver_id":"bob.near"}"#.to_vec();
+/// testing_env!(VMContextBuilder::new().input(input).build());
+/// let receiver_id: AccountId = env::input_field("receiver_id").unwrap();
+/// assert_eq!(receiver_id.as_str(), "bob.near");
+/// ```
+pub fn input_field<T: serde::de::DeserializeOwned>(field: &str) -> Result<T, ParseInputError> {
+    use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+
+    /// Deserializes a key to whether it is the field.
+    struct IsField<'a>(&'a str);
+
+    impl<'de> DeserializeSeed<'de> for IsField<'_> {
+        type Value = bool;
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+            deserializer.deserialize_str(self)
+        }
+    }
+
+    impl<'de> Visitor<'de> for IsField<'_> {
+        type Value = bool;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a field name")
+        }
+
+        fn visit_str<E>(self, key: &str) -> Result<bool, E> {
+            Ok(key == self.0)
+        }
+    }
+
+    struct Field<'a, T>(&'a str, std::marker::PhantomData<T>);
+
+    impl<'de, T: serde::Deserialize<'de>> Visitor<'de> for Field<'_, T> {
+        type Value = T;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "an object with the field `{}`", self.0)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+            use serde::de::{Error, IntoDeserializer};
+
+            let mut value = None;
+            while let Some(is_field) = map.next_key_seed(IsField(self.0))? {
+                if !is_field {
+                    map.next_value::<IgnoredAny>()?;
+                } else if value.is_some() {
+                    return Err(A::Error::custom(format_args!("duplicate field `{}`", self.0)));
+                } else {
+                    value = Some(map.next_value()?);
+                }
+            }
+            match value {
+                Some(value) => Ok(value),
+                None => T::deserialize(().into_deserializer()).map_err(|_: A::Error| {
+                    A::Error::custom(format_args!("missing field `{}`", self.0))
+                }),
+            }
+        }
+    }
+
+    let input = non_empty_input()?;
+    let mut deserializer = serde_json::Deserializer::from_slice(&input);
+    deserializer
+        .deserialize_map(Field(field, std::marker::PhantomData))
+        .and_then(|value| deserializer.end().map(|()| value))
+        .map_err(|err| ParseInputError::Json(format!("{:?}", err)))
+}
+
+fn non_empty_input() -> Result<Vec<u8>, ParseInputError> {
+    match input() {
+        Some(input) if !input.is_empty() => Ok(input),
+        _ => Err(ParseInputError::Missing),
+    }
+}
+
 /// Current block index.
 #[deprecated(since = "4.0.0", note = "Use block_height instead")]
 pub fn block_index() -> BlockHeight {
@@ -204,6 +450,11 @@ pub fn block_timestamp_ms() -> u64 {
     block_timestamp() / 1_000_000
 }
 
+/// Current block date and time in UTC.
+pub fn block_datetime() -> crate::time::DateTime {
+    crate::time::DateTime::from_timestamp(block_timestamp())
+}
+
 /// Current epoch height.
 pub fn epoch_height() -> u64 {
     unsafe { sys::epoch_height() }
@@ -235,9 +486,14 @@ pub fn account_locked_balance() -> Balance {
 /// The balance that was attached to the call that will be immediately deposited before the
 /// contract execution starts
 pub fn attached_deposit() -> Balance {
-    let data = [0u8; size_of::<Balance>()];
-    unsafe { sys::attached_deposit(data.as_ptr() as u64) };
-    Balance::from_le_bytes(data)
+    cached(
+        |cache| &mut cache.attached_deposit,
+        || {
+            let data = [0u8; size_of::<Balance>()];
+            unsafe { sys::attached_deposit(data.as_ptr() as u64) };
+            Balance::from_le_bytes(data)
+        },
+    )
 }
 
 /// The amount of gas attached to the call that can be used to pay for the gas fees.
@@ -250,6 +506,22 @@ pub fn used_gas() -> Gas {
     Gas(unsafe { sys::used_gas() })
 }
 
+/// The gas used at a point of the contract execution, returned by [`gas_checkpoint`] to measure
+/// the gas used since then with [`gas_since`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasCheckpoint(Gas);
+
+/// Records the gas used so far, to measure the gas used by the following code with [`gas_since`].
+/// See also [`profile_gas!`](crate::profile_gas) to log the gas used by a block of code.
+pub fn gas_checkpoint() -> GasCheckpoint {
+    GasCheckpoint(used_gas())
+}
+
+/// The gas used since the checkpoint was recorded with [`gas_checkpoint`].
+pub fn gas_since(checkpoint: GasCheckpoint) -> Gas {
+    used_gas() - checkpoint.0
+}
+
 // ############
 // # Math API #
 // ############
@@ -326,6 +598,17 @@ pub fn sha256_array(value: &[u8]) -> [u8; 32] {
     }
 }
 
+/// Hashes the data of the register using the SHA-256 hash function, without copying it into
+/// memory first.
+pub fn sha256_register(register: &Register) -> [u8; 32] {
+    let (len, ptr) = register.as_input();
+    //* SAFETY: sha256 syscall will always generate 32 bytes inside of the atomic op register.
+    unsafe {
+        sys::sha256(len, ptr, ATOMIC_OP_REGISTER);
+        read_register_fixed_32(ATOMIC_OP_REGISTER)
+    }
+}
+
 /// Hashes the bytes using the Keccak-256 hash function. This returns a 32 byte hash.
 pub fn keccak256_array(value: &[u8]) -> [u8; 32] {
     //* SAFETY: keccak256 syscall will always generate 32 bytes inside of the atomic op register
@@ -366,7 +649,6 @@ pub fn ripemd160_array(value: &[u8]) -> [u8; 20] {
 /// which is generally only ideal for transactions.
 ///
 /// Returns 64 bytes representing the public key if the recovery was successful.
-#[cfg(feature = "unstable")]
 pub fn ecrecover(
     hash: &[u8],
     signature: &[u8],
@@ -391,6 +673,205 @@ pub fn ecrecover(
     }
 }
 
+/// Verifies that `signature` is a valid ed25519 signature of `message` by `public_key`.
+pub fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    unsafe {
+        sys::ed25519_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            public_key.len() as _,
+            public_key.as_ptr() as _,
+        ) == 1
+    }
+}
+
+/// Verifies a batch of ed25519 signatures, each given as `(signature, message, public_key)`.
+/// Returns the index of the first invalid signature, without verifying the ones after it.
+///
+/// To verify typed payloads, see [`SignedPayload::verify_batch`](crate::SignedPayload::verify_batch).
+pub fn ed25519_verify_batch(items: &[(&[u8; 64], &[u8], &[u8; 32])]) -> Result<(), usize> {
+    match items.iter().position(|(signature, message, public_key)| {
+        !ed25519_verify(signature, message, public_key)
+    }) {
+        Some(index) => Err(index),
+        None => Ok(()),
+    }
+}
+
+/// Computes the multi-scalar multiplication `sum(point * scalar)` over the given terms of alt_bn128
+/// [`G1Point`]s and little-endian scalars of the `Fr` field.
+///
+/// Panics if any point is not on the curve or any scalar is not in the field.
+pub fn alt_bn128_g1_multiexp(terms: &[(G1Point, [u8; 32])]) -> G1Point {
+    let mut input = Vec::with_capacity(terms.len() * 96);
+    for (point, scalar) in terms {
+        point.write_to(&mut input);
+        input.extend_from_slice(scalar);
+    }
+    //* SAFETY: alt_bn128_g1_multiexp syscall always writes a 64 byte point into the register.
+    unsafe {
+        sys::alt_bn128_g1_multiexp(input.len() as _, input.as_ptr() as _, ATOMIC_OP_REGISTER);
+        G1Point::from_bytes(read_register_fixed_64(ATOMIC_OP_REGISTER))
+    }
+}
+
+/// Computes the sum of the given alt_bn128 [`G1Point`]s, where each point is negated if it is
+/// paired with `true`.
+///
+/// Panics if any point is not on the curve.
+pub fn alt_bn128_g1_sum(terms: &[(bool, G1Point)]) -> G1Point {
+    let mut input = Vec::with_capacity(terms.len() * 65);
+    for (negate, point) in terms {
+        input.push(*negate as u8);
+        point.write_to(&mut input);
+    }
+    //* SAFETY: alt_bn128_g1_sum syscall always writes a 64 byte point into the register.
+    unsafe {
+        sys::alt_bn128_g1_sum(input.len() as _, input.as_ptr() as _, ATOMIC_OP_REGISTER);
+        G1Point::from_bytes(read_register_fixed_64(ATOMIC_OP_REGISTER))
+    }
+}
+
+/// Checks whether the product of the pairings `e(g1, g2)` of the given points is the identity
+/// of the target group, which is the final step of verifying most zk-SNARK proofs.
+///
+/// Panics if any point is not on its curve or not in the `Fr`-ordered subgroup.
+pub fn alt_bn128_pairing_check(pairs: &[(G1Point, G2Point)]) -> bool {
+    let mut input = Vec::with_capacity(pairs.len() * 192);
+    for (g1, g2) in pairs {
+        g1.write_to(&mut input);
+        g2.write_to(&mut input);
+    }
+    unsafe { sys::alt_bn128_pairing_check(input.len() as _, input.as_ptr() as _) == 1 }
+}
+
+/// Calls a BLS12-381 host function, which writes its output into the register unless the input
+/// contains an invalid element.
+fn bls12381_op(input: &[u8], op: unsafe extern "C" fn(u64, u64, u64) -> u64) -> Option<Vec<u8>> {
+    match unsafe { op(input.len() as _, input.as_ptr() as _, ATOMIC_OP_REGISTER) } {
+        0 => Some(expect_register(read_register(ATOMIC_OP_REGISTER))),
+        _ => None,
+    }
+}
+
+/// Computes the sum of the given BLS12-381 [`G1Point`](bls12_381::G1Point)s, where each point is
+/// negated if it is paired with `true`.
+///
+/// Returns `None` if any point is not on the curve.
+pub fn bls12381_p1_sum(terms: &[(bool, bls12_381::G1Point)]) -> Option<bls12_381::G1Point> {
+    let mut input = Vec::with_capacity(terms.len() * 97);
+    for (negate, point) in terms {
+        input.push(*negate as u8);
+        input.extend_from_slice(&point.0);
+    }
+    bls12381_op(&input, sys::bls12381_p1_sum).map(|output| bls12_381::G1Point::from_slice(&output))
+}
+
+/// Computes the sum of the given BLS12-381 [`G2Point`](bls12_381::G2Point)s, where each point is
+/// negated if it is paired with `true`.
+///
+/// Returns `None` if any point is not on the curve.
+pub fn bls12381_p2_sum(terms: &[(bool, bls12_381::G2Point)]) -> Option<bls12_381::G2Point> {
+    let mut input = Vec::with_capacity(terms.len() * 193);
+    for (negate, point) in terms {
+        input.push(*negate as u8);
+        input.extend_from_slice(&point.0);
+    }
+    bls12381_op(&input, sys::bls12381_p2_sum).map(|output| bls12_381::G2Point::from_slice(&output))
+}
+
+/// Computes the multi-scalar multiplication `sum(point * scalar)` over the given terms of
+/// BLS12-381 [`G1Point`](bls12_381::G1Point)s and little-endian scalars.
+///
+/// Returns `None` if any point is not in the prime-order subgroup `G1`.
+pub fn bls12381_g1_multiexp(
+    terms: &[(bls12_381::G1Point, [u8; 32])],
+) -> Option<bls12_381::G1Point> {
+    let mut input = Vec::with_capacity(terms.len() * 128);
+    for (point, scalar) in terms {
+        input.extend_from_slice(&point.0);
+        input.extend_from_slice(scalar);
+    }
+    bls12381_op(&input, sys::bls12381_g1_multiexp)
+        .map(|output| bls12_381::G1Point::from_slice(&output))
+}
+
+/// Computes the multi-scalar multiplication `sum(point * scalar)` over the given terms of
+/// BLS12-381 [`G2Point`](bls12_381::G2Point)s and little-endian scalars.
+///
+/// Returns `None` if any point is not in the prime-order subgroup `G2`.
+pub fn bls12381_g2_multiexp(
+    terms: &[(bls12_381::G2Point, [u8; 32])],
+) -> Option<bls12_381::G2Point> {
+    let mut input = Vec::with_capacity(terms.len() * 224);
+    for (point, scalar) in terms {
+        input.extend_from_slice(&point.0);
+        input.extend_from_slice(scalar);
+    }
+    bls12381_op(&input, sys::bls12381_g2_multiexp)
+        .map(|output| bls12_381::G2Point::from_slice(&output))
+}
+
+/// Maps each of the given field elements to a point of `G1`, as used when hashing messages to
+/// the curve (the `map_to_curve` and `clear_cofactor` steps of RFC 9380).
+///
+/// Returns `None` if any element is not reduced modulo the field characteristic.
+pub fn bls12381_map_fp_to_g1(values: &[bls12_381::Fp]) -> Option<Vec<bls12_381::G1Point>> {
+    let input: Vec<u8> = values.iter().flat_map(|value| value.0).collect();
+    bls12381_op(&input, sys::bls12381_map_fp_to_g1)
+        .map(|output| output.chunks_exact(96).map(bls12_381::G1Point::from_slice).collect())
+}
+
+/// Maps each of the given `Fp2` elements to a point of `G2`, as used when hashing messages to
+/// the curve (the `map_to_curve` and `clear_cofactor` steps of RFC 9380).
+///
+/// Returns `None` if any element is not reduced modulo the field characteristic.
+pub fn bls12381_map_fp2_to_g2(values: &[bls12_381::Fp2]) -> Option<Vec<bls12_381::G2Point>> {
+    let mut input = Vec::with_capacity(values.len() * 96);
+    for value in values {
+        value.write_to(&mut input);
+    }
+    bls12381_op(&input, sys::bls12381_map_fp2_to_g2)
+        .map(|output| output.chunks_exact(192).map(bls12_381::G2Point::from_slice).collect())
+}
+
+/// Checks whether the product of the pairings `e(g1, g2)` of the given BLS12-381 points is the
+/// identity of the target group, which is how BLS signatures are verified.
+///
+/// Returns `false` if any point is not in its prime-order subgroup.
+pub fn bls12381_pairing_check(pairs: &[(bls12_381::G1Point, bls12_381::G2Point)]) -> bool {
+    let mut input = Vec::with_capacity(pairs.len() * 288);
+    for (g1, g2) in pairs {
+        input.extend_from_slice(&g1.0);
+        input.extend_from_slice(&g2.0);
+    }
+    unsafe { sys::bls12381_pairing_check(input.len() as _, input.as_ptr() as _) == 0 }
+}
+
+/// Decompresses the given BLS12-381 [`CompressedG1Point`](bls12_381::CompressedG1Point)s.
+///
+/// Returns `None` if any point is not on the curve.
+pub fn bls12381_p1_decompress(
+    points: &[bls12_381::CompressedG1Point],
+) -> Option<Vec<bls12_381::G1Point>> {
+    let input: Vec<u8> = points.iter().flat_map(|point| point.0).collect();
+    bls12381_op(&input, sys::bls12381_p1_decompress)
+        .map(|output| output.chunks_exact(96).map(bls12_381::G1Point::from_slice).collect())
+}
+
+/// Decompresses the given BLS12-381 [`CompressedG2Point`](bls12_381::CompressedG2Point)s.
+///
+/// Returns `None` if any point is not on the curve.
+pub fn bls12381_p2_decompress(
+    points: &[bls12_381::CompressedG2Point],
+) -> Option<Vec<bls12_381::G2Point>> {
+    let input: Vec<u8> = points.iter().flat_map(|point| point.0).collect();
+    bls12381_op(&input, sys::bls12381_p2_decompress)
+        .map(|output| output.chunks_exact(192).map(bls12_381::G2Point::from_slice).collect())
+}
+
 // ################
 // # Promises API #
 // ################
@@ -619,6 +1100,69 @@ pub fn promise_result(result_idx: u64) -> PromiseResult {
     }
 }
 
+/// Same as [`promise_result`], but returns an error instead of aborting if there is no promise
+/// result with the given index, for example because the function was not called as a callback.
+pub fn try_promise_result(result_idx: u64) -> Result<PromiseResult, EnvError> {
+    let count = promise_results_count();
+    if result_idx >= count {
+        return Err(EnvError::PromiseIndexOutOfBounds { index: result_idx, count });
+    }
+    Ok(promise_result(result_idx))
+}
+
+/// Iterator over the results of the promises that caused the callback, in order, returned by
+/// [`promise_results`].
+#[derive(Debug, Clone)]
+pub struct PromiseResults {
+    next: u64,
+    count: u64,
+}
+
+impl Iterator for PromiseResults {
+    type Item = PromiseResult;
+
+    fn next(&mut self) -> Option<PromiseResult> {
+        if self.next == self.count {
+            return None;
+        }
+        let result = promise_result(self.next);
+        self.next += 1;
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.count - self.next) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for PromiseResults {}
+
+impl std::iter::FusedIterator for PromiseResults {}
+
+/// Returns an iterator over the results of all the promises that caused the callback, for
+/// callbacks of a dynamic number of promises, like the ones joined with [`Promise::and`].
+///
+/// [`Promise::and`]: crate::Promise::and
+pub fn promise_results() -> PromiseResults {
+    PromiseResults { next: 0, count: promise_results_count() }
+}
+
+/// Deserializes the successful results of the promises that caused the callback from JSON, in
+/// order, skipping the ones that failed. Panics if a successful result cannot be deserialized,
+/// the same as `#[callback_result]` arguments.
+pub fn collect_successes<T: serde::de::DeserializeOwned>() -> Vec<T> {
+    promise_results()
+        .filter_map(|result| match result {
+            PromiseResult::Successful(data) => Some(
+                serde_json::from_slice(&data)
+                    .unwrap_or_else(|_| panic_str("Failed to deserialize callback using JSON")),
+            ),
+            _ => None,
+        })
+        .collect()
+}
+
 pub(crate) fn promise_result_internal(result_idx: u64) -> Result<(), PromiseError> {
     match unsafe { sys::promise_result(result_idx, ATOMIC_OP_REGISTER) } {
         0 => Err(PromiseError::NotReady),
@@ -661,6 +1205,36 @@ pub fn validator_total_stake() -> Balance {
 pub fn value_return(value: &[u8]) {
     unsafe { sys::value_return(value.len() as _, value.as_ptr() as _) }
 }
+/// Sets the JSON serialization of the value as the return value of the contract, the same way as
+/// the code generated by `#[near_bindgen]` returns values.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env;
+///
+/// env::value_return_json(&vec!["alice.near", "bob.near"]);
+/// ```
+pub fn value_return_json<T: serde::Serialize + ?Sized>(value: &T) {
+    let data = serde_json::to_vec(value)
+        .unwrap_or_else(|_| panic_str("Failed to serialize the return value using JSON."));
+    value_return(&data)
+}
+
+/// Sets the Borsh serialization of the value as the return value of the contract, the same way
+/// as the code generated by `#[near_bindgen]` returns values with `#[result_serializer(borsh)]`.
+pub fn value_return_borsh<T: borsh::BorshSerialize + ?Sized>(value: &T) {
+    let data = value
+        .try_to_vec()
+        .unwrap_or_else(|_| panic_str("Failed to serialize the return value using Borsh."));
+    value_return(&data)
+}
+/// Sets the data of the register as the return value of the contract, without copying it into
+/// memory first.
+pub fn value_return_register(register: &Register) {
+    let (len, ptr) = register.as_input();
+    unsafe { sys::value_return(len, ptr) }
+}
 /// Terminates the execution of the program with the UTF-8 encoded message.
 /// [`panic_str`] should be used as the bytes are required to be UTF-8
 #[deprecated(since = "4.0.0", note = "Use env::panic_str to panic with a message.")]
@@ -673,6 +1247,16 @@ pub fn panic_str(message: &str) -> ! {
     unsafe { sys::panic_utf8(message.len() as _, message.as_ptr() as _) }
 }
 
+/// Terminates the execution of the program with an error `code` and message, formatted as
+/// `E<code>: <message>`, so that the failure can be classified by its code.
+///
+/// ```no_run
+/// near_sdk::env::panic_err(42, "not enough balance"); // Panics with `E42: not enough balance`.
+/// ```
+pub fn panic_err(code: u32, message: &str) -> ! {
+    panic_str(&format!("E{}: {}", code, message))
+}
+
 /// Aborts the current contract execution without a custom message.
 /// To include a message, use [`panic_str`].
 pub fn abort() -> ! {
@@ -697,6 +1281,49 @@ pub fn log_str(message: &str) {
     unsafe { sys::log_utf8(message.len() as _, message.as_ptr() as _) }
 }
 
+/// Logs a [NEP-297] event, which is formatted as `EVENT_JSON:` followed by a JSON object with the
+/// `standard`, `version`, `event` and `data` fields.
+///
+/// Panics if the log message would exceed the log length limit of the protocol.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env;
+/// use near_sdk::serde_json::json;
+///
+/// env::emit_event("nep171", "1.0.0", "nft_mint", &[json!({ "owner_id": "alice.near", "token_ids": ["1"] })]);
+/// ```
+///
+/// [NEP-297]: https://nomicon.io/Standards/EventsFormat
+pub fn emit_event<T: serde::Serialize + ?Sized>(
+    standard: &str,
+    version: &str,
+    event: &str,
+    data: &T,
+) {
+    #[derive(serde::Serialize)]
+    #[serde(crate = "crate::serde")]
+    struct Event<'a, T: ?Sized> {
+        standard: &'a str,
+        version: &'a str,
+        event: &'a str,
+        data: &'a T,
+    }
+
+    let json = serde_json::to_string(&Event { standard, version, event, data })
+        .unwrap_or_else(|_| panic_str("Failed to serialize the event"));
+    let message = format!("EVENT_JSON:{}", json);
+    if message.len() > MAX_LOG_LEN {
+        panic_str(&format!(
+            "The event log is {} bytes long, which exceeds the limit of {} bytes",
+            message.len(),
+            MAX_LOG_LEN
+        ));
+    }
+    log_str(&message);
+}
+
 /// Log the UTF-8 encodable message.
 #[deprecated(since = "4.0.0", note = "Use env::log_str for logging messages.")]
 pub fn log(message: &[u8]) {
@@ -754,6 +1381,28 @@ pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
         _ => abort(),
     }
 }
+/// Writes the data of the register into storage under the given key, without copying it into
+/// memory first. If another key-value existed in the storage with the same key it returns `true`,
+/// otherwise `false`.
+pub fn storage_write_from_register(key: &[u8], register: &Register) -> bool {
+    let (len, ptr) = register.as_input();
+    match unsafe {
+        sys::storage_write(key.len() as _, key.as_ptr() as _, len, ptr, EVICTED_REGISTER)
+    } {
+        0 => false,
+        1 => true,
+        _ => abort(),
+    }
+}
+/// Reads the value stored under the given key into the register, without copying it into memory.
+/// Returns `false` and leaves the register unchanged if there is no value under the key.
+pub fn storage_read_into_register(key: &[u8], register: &Register) -> bool {
+    match unsafe { sys::storage_read(key.len() as _, key.as_ptr() as _, register.id()) } {
+        0 => false,
+        1 => true,
+        _ => abort(),
+    }
+}
 /// Removes the value stored under the given key.
 /// If key-value existed returns `true`, otherwise `false`.
 pub fn storage_remove(key: &[u8]) -> bool {
@@ -775,6 +1424,88 @@ pub fn storage_has_key(key: &[u8]) -> bool {
         _ => abort(),
     }
 }
+/// Writes the Borsh serialization of the value into storage under the given key.
+/// If another key-value existed in the storage with the same key it returns `true`, otherwise `false`.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env::{storage_read_typed, storage_write_typed};
+///
+/// storage_write_typed(b"config", &(1u8, "owner.near".to_string()));
+/// assert_eq!(storage_read_typed(b"config"), Some((1u8, "owner.near".to_string())));
+/// ```
+pub fn storage_write_typed<T: borsh::BorshSerialize + ?Sized>(key: &[u8], value: &T) -> bool {
+    try_storage_write_typed(key, value).unwrap_or_else(|_| panic_str("Cannot serialize the value."))
+}
+/// Same as [`storage_write_typed`], but returns an error instead of panicking if the value cannot
+/// be serialized.
+pub fn try_storage_write_typed<T: borsh::BorshSerialize + ?Sized>(
+    key: &[u8],
+    value: &T,
+) -> Result<bool, EnvError> {
+    let data = value.try_to_vec().map_err(|err| EnvError::Serialization(err.to_string()))?;
+    Ok(storage_write(key, &data))
+}
+/// Reads the value stored under the given key and deserializes it with Borsh.
+///
+/// Panics if the stored value cannot be deserialized as `T`.
+pub fn storage_read_typed<T: borsh::BorshDeserialize>(key: &[u8]) -> Option<T> {
+    try_storage_read_typed(key)
+        .unwrap_or_else(|_| panic_str("Cannot deserialize the value stored under the key."))
+}
+/// Same as [`storage_read_typed`], but returns an error instead of panicking if the stored value
+/// cannot be deserialized as `T`.
+pub fn try_storage_read_typed<T: borsh::BorshDeserialize>(
+    key: &[u8],
+) -> Result<Option<T>, EnvError> {
+    storage_read(key)
+        .map(|data| {
+            T::try_from_slice(&data).map_err(|err| EnvError::Deserialization(err.to_string()))
+        })
+        .transpose()
+}
+/// Writes the JSON serialization of the value into storage under the given key.
+/// If another key-value existed in the storage with the same key it returns `true`, otherwise `false`.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::env::{storage_read_json, storage_write_json};
+///
+/// storage_write_json(b"config", &vec!["alice.near", "bob.near"]);
+/// assert_eq!(storage_read_json::<Vec<String>>(b"config").unwrap(), ["alice.near", "bob.near"]);
+/// ```
+pub fn storage_write_json<T: serde::Serialize + ?Sized>(key: &[u8], value: &T) -> bool {
+    try_storage_write_json(key, value).unwrap_or_else(|_| panic_str("Cannot serialize the value."))
+}
+/// Same as [`storage_write_json`], but returns an error instead of panicking if the value cannot
+/// be serialized.
+pub fn try_storage_write_json<T: serde::Serialize + ?Sized>(
+    key: &[u8],
+    value: &T,
+) -> Result<bool, EnvError> {
+    let data = serde_json::to_vec(value).map_err(|err| EnvError::Serialization(err.to_string()))?;
+    Ok(storage_write(key, &data))
+}
+/// Reads the value stored under the given key and deserializes it from JSON.
+///
+/// Panics if the stored value cannot be deserialized as `T`.
+pub fn storage_read_json<T: serde::de::DeserializeOwned>(key: &[u8]) -> Option<T> {
+    try_storage_read_json(key)
+        .unwrap_or_else(|_| panic_str("Cannot deserialize the value stored under the key."))
+}
+/// Same as [`storage_read_json`], but returns an error instead of panicking if the stored value
+/// cannot be deserialized as `T`.
+pub fn try_storage_read_json<T: serde::de::DeserializeOwned>(
+    key: &[u8],
+) -> Result<Option<T>, EnvError> {
+    storage_read(key)
+        .map(|data| {
+            serde_json::from_slice(&data).map_err(|err| EnvError::Deserialization(err.to_string()))
+        })
+        .transpose()
+}
 
 // ############################################
 // # Saving and loading of the contract state #
@@ -785,11 +1516,91 @@ pub fn state_read<T: borsh::BorshDeserialize>() -> Option<T> {
         .map(|data| T::try_from_slice(&data).expect("Cannot deserialize the contract state."))
 }
 
+/// Same as [`state_read`], but returns an error instead of panicking if the state cannot be
+/// deserialized as `T`, for example to detect that the state needs to be migrated.
+pub fn try_state_read<T: borsh::BorshDeserialize>() -> Result<Option<T>, EnvError> {
+    try_storage_read_typed(STATE_KEY)
+}
+
 pub fn state_write<T: borsh::BorshSerialize>(state: &T) {
     let data = state.try_to_vec().expect("Cannot serialize the contract state.");
     storage_write(STATE_KEY, &data);
 }
 
+/// Contract state that is written with a leading version tag by [`state_write_versioned`], so
+/// that [`state_read_versioned`] can deserialize the state written by any previous version of
+/// the contract, and migrate it to the current one.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::env::{self, VersionedState};
+/// use std::io;
+///
+/// #[derive(BorshDeserialize)]
+/// struct StateV0 {
+///     owner: String,
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+/// struct State {
+///     owner: String,
+///     paused: bool,
+/// }
+///
+/// impl VersionedState for State {
+///     const VERSION: u8 = 1;
+///
+///     fn deserialize_version(version: u8, data: &[u8]) -> io::Result<Self> {
+///         match version {
+///             0 => StateV0::try_from_slice(data).map(|v0| State { owner: v0.owner, paused: false }),
+///             1 => State::try_from_slice(data),
+///             _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unknown state version")),
+///         }
+///     }
+/// }
+///
+/// env::storage_write(b"STATE", &[0, 5, 0, 0, 0, b'a', b'l', b'i', b'c', b'e']);
+/// let state: State = env::state_read_versioned().unwrap();
+/// assert_eq!(state, State { owner: "alice".to_string(), paused: false });
+/// env::state_write_versioned(&state);
+/// ```
+pub trait VersionedState: Sized {
+    /// Version tag written before the state by [`state_write_versioned`].
+    const VERSION: u8;
+
+    /// Deserializes the state written with the version tag `version`, which is either
+    /// [`Self::VERSION`] or the version of a previous state.
+    fn deserialize_version(version: u8, data: &[u8]) -> std::io::Result<Self>;
+}
+
+/// Load the state written with [`state_write_versioned`], by any version of the state.
+pub fn state_read_versioned<T: VersionedState>() -> Option<T> {
+    try_state_read_versioned().expect("Cannot deserialize the contract state.")
+}
+
+/// Same as [`state_read_versioned`], but returns an error instead of panicking if the state
+/// cannot be deserialized.
+pub fn try_state_read_versioned<T: VersionedState>() -> Result<Option<T>, EnvError> {
+    match storage_read(STATE_KEY) {
+        None => Ok(None),
+        Some(data) => match data.split_first() {
+            Some((&version, data)) => T::deserialize_version(version, data)
+                .map(Some)
+                .map_err(|err| EnvError::Deserialization(err.to_string())),
+            None => Err(EnvError::Deserialization("missing state version".to_string())),
+        },
+    }
+}
+
+/// Save the state with its version tag, to be loaded with [`state_read_versioned`].
+pub fn state_write_versioned<T: VersionedState + borsh::BorshSerialize>(state: &T) {
+    let mut data = vec![T::VERSION];
+    state.serialize(&mut data).expect("Cannot serialize the contract state.");
+    storage_write(STATE_KEY, &data);
+}
+
 /// Returns `true` if the contract state exists and `false` otherwise.
 pub fn state_exists() -> bool {
     storage_has_key(STATE_KEY)
@@ -807,6 +1618,46 @@ pub fn storage_byte_cost() -> Balance {
     STORAGE_PRICE_PER_BYTE
 }
 
+/// Minimum gas price of mainnet, in yoctoNEAR per unit of gas. This is `min_gas_price` from the
+/// protocol genesis config, and the gas price of a block can only be higher, so costs computed
+/// with it are lower bounds.
+pub const MIN_GAS_PRICE: Balance = 100_000_000;
+
+/// Gas to create an action receipt, to send and to execute it, from mainnet genesis config.
+const ACTION_RECEIPT_CREATION_GAS: u64 = 2 * 108_059_500_000;
+/// Base gas of a function call action, to send and to execute it, from mainnet genesis config.
+const FUNCTION_CALL_BASE_GAS: u64 = 2 * 2_319_861_500_000;
+/// Gas per byte of the method name and arguments of a function call action, to send and to
+/// execute it, from mainnet genesis config.
+const FUNCTION_CALL_BYTE_GAS: u64 = 2 * 2_235_934;
+
+/// Estimates the cost of storing `bytes` bytes in the state of an account, which has to be
+/// covered by its balance.
+pub fn estimate_storage_cost(bytes: StorageUsage) -> Balance {
+    Balance::from(bytes) * storage_byte_cost()
+}
+
+/// Estimates the cost in tokens of a function call with `gas` attached, where `args_len` is the
+/// length of the method name and the arguments in bytes. This includes the fees of creating the
+/// receipt and the action, and assumes the minimum gas price, so the price of the gas that is
+/// actually burnt may be higher when the network is congested.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{env, Gas};
+///
+/// // The deposit to forward so that the receiver can pay for a call with 5 TGas.
+/// let cost = env::estimate_function_call_cost(100, Gas::ONE_TERA * 5);
+/// assert!(cost > env::MIN_GAS_PRICE * 5 * 10u128.pow(12));
+/// ```
+pub fn estimate_function_call_cost(args_len: u64, gas: Gas) -> Balance {
+    let fees = ACTION_RECEIPT_CREATION_GAS
+        .saturating_add(FUNCTION_CALL_BASE_GAS)
+        .saturating_add(FUNCTION_CALL_BYTE_GAS.saturating_mul(args_len));
+    Balance::from(fees.saturating_add(gas.0)) * MIN_GAS_PRICE
+}
+
 // ##################
 // # Helper methods #
 // ##################
@@ -841,6 +1692,13 @@ pub fn is_valid_account_id(account_id: &[u8]) -> bool {
     !last_char_is_separator
 }
 
+pub mod buffered;
+pub mod ext;
+mod hasher;
+pub mod typed;
+
+pub use self::hasher::{Keccak256Hasher, Sha256Hasher};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -969,7 +1827,6 @@ mod tests {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    #[cfg(feature = "unstable")]
     #[test]
     fn test_ecrecover() {
         use crate::test_utils::test_env;
@@ -1010,6 +1867,347 @@ mod tests {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_ed25519_verify() {
+        use near_crypto::{KeyType, SecretKey, Signature};
+
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "alice");
+        let public_key: [u8; 32] = secret_key.public_key().key_data().try_into().unwrap();
+        let signature: [u8; 64] = match secret_key.sign(b"hello") {
+            Signature::ED25519(signature) => signature.to_bytes(),
+            _ => unreachable!(),
+        };
+
+        assert!(super::ed25519_verify(&signature, b"hello", &public_key));
+        assert!(!super::ed25519_verify(&signature, b"hellp", &public_key));
+        assert!(!super::ed25519_verify(&[0; 64], b"hello", &public_key));
+
+        assert_eq!(super::ed25519_verify_batch(&[]), Ok(()));
+        assert_eq!(
+            super::ed25519_verify_batch(&[
+                (&signature, b"hello", &public_key),
+                (&signature, b"hellp", &public_key),
+                (&[0; 64], b"hello", &public_key),
+            ]),
+            Err(1)
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_alt_bn128() {
+        use crate::alt_bn128::G1Point;
+
+        let scalar = |n: u8| {
+            let mut scalar = [0; 32];
+            scalar[0] = n;
+            scalar
+        };
+        let generator = G1Point { x: scalar(1), y: scalar(2) };
+
+        let doubled = super::alt_bn128_g1_multiexp(&[(generator, scalar(2))]);
+        assert_ne!(doubled, generator);
+        assert_eq!(super::alt_bn128_g1_sum(&[(false, generator), (false, generator)]), doubled);
+        assert_eq!(
+            super::alt_bn128_g1_sum(&[(false, doubled), (true, generator), (true, generator)]),
+            G1Point::default()
+        );
+        assert!(super::alt_bn128_pairing_check(&[]));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_bls12381() {
+        use crate::bls12_381::*;
+
+        let (mut g1, mut g1_compressed) = (G1Point([0; 96]), CompressedG1Point([0; 48]));
+        let (mut g2, mut g2_compressed) = (G2Point([0; 192]), CompressedG2Point([0; 96]));
+        unsafe {
+            blst::blst_p1_affine_serialize(g1.0.as_mut_ptr(), blst::blst_p1_affine_generator());
+            blst::blst_p1_affine_compress(
+                g1_compressed.0.as_mut_ptr(),
+                blst::blst_p1_affine_generator(),
+            );
+            blst::blst_p2_affine_serialize(g2.0.as_mut_ptr(), blst::blst_p2_affine_generator());
+            blst::blst_p2_affine_compress(
+                g2_compressed.0.as_mut_ptr(),
+                blst::blst_p2_affine_generator(),
+            );
+        }
+        let scalar = |n: u8| {
+            let mut scalar = [0; 32];
+            scalar[0] = n;
+            scalar
+        };
+
+        let g1_doubled = super::bls12381_g1_multiexp(&[(g1, scalar(2))]).unwrap();
+        assert_ne!(g1_doubled, g1);
+        assert_eq!(super::bls12381_p1_sum(&[(false, g1), (false, g1)]), Some(g1_doubled));
+        assert_eq!(
+            super::bls12381_p1_sum(&[(false, g1_doubled), (true, g1), (true, g1)]),
+            Some(G1Point::INFINITY)
+        );
+        assert_eq!(super::bls12381_p1_sum(&[(false, G1Point([0; 96]))]), None);
+
+        let g2_doubled = super::bls12381_g2_multiexp(&[(g2, scalar(2))]).unwrap();
+        assert_eq!(super::bls12381_p2_sum(&[(false, g2), (false, g2)]), Some(g2_doubled));
+        assert_eq!(super::bls12381_p2_sum(&[(true, g2), (false, g2)]), Some(G2Point::INFINITY));
+
+        assert_eq!(super::bls12381_p1_decompress(&[g1_compressed]), Some(vec![g1]));
+        assert_eq!(super::bls12381_p2_decompress(&[g2_compressed]), Some(vec![g2]));
+
+        // e(2 * g1, g2) * e(-g1, 2 * g2) == 1
+        let g1_negated = super::bls12381_p1_sum(&[(true, g1)]).unwrap();
+        assert!(super::bls12381_pairing_check(&[(g1_doubled, g2), (g1_negated, g2_doubled)]));
+        assert!(!super::bls12381_pairing_check(&[(g1, g2)]));
+        assert!(super::bls12381_pairing_check(&[]));
+
+        let mut one = Fp([0; 48]);
+        one.0[47] = 1;
+        let mapped = super::bls12381_map_fp_to_g1(&[one]).unwrap();
+        assert!(super::bls12381_g1_multiexp(&[(mapped[0], scalar(1))]).is_some());
+        assert_eq!(super::bls12381_map_fp_to_g1(&[Fp([0xff; 48])]), None);
+        let mapped = super::bls12381_map_fp2_to_g2(&[Fp2 { c0: one, c1: one }]).unwrap();
+        assert!(super::bls12381_g2_multiexp(&[(mapped[0], scalar(1))]).is_some());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_storage_typed() {
+        assert_eq!(super::storage_read_typed::<u64>(b"borsh"), None);
+        assert!(!super::storage_write_typed(b"borsh", &42u64));
+        assert_eq!(super::storage_read(b"borsh").unwrap(), 42u64.to_le_bytes());
+        assert_eq!(super::storage_read_typed::<u64>(b"borsh"), Some(42));
+
+        assert!(!super::storage_write_json(b"json", &[1, 2]));
+        assert_eq!(super::storage_read(b"json").unwrap(), b"[1,2]");
+        assert_eq!(super::storage_read_json::<Vec<u8>>(b"json"), Some(vec![1, 2]));
+        assert_eq!(super::storage_read_json::<u8>(b"missing"), None);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_register_api() {
+        use super::Register;
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .input(b"input".to_vec())
+            .build());
+        let register = Register::new(7);
+        assert_eq!(register.len(), None);
+        assert!(register.is_empty());
+
+        super::input_into_register(&register);
+        assert_eq!(register.read().unwrap(), b"input");
+        assert_eq!(super::sha256_register(&register), super::sha256_array(b"input"));
+
+        assert!(!super::storage_write_from_register(b"key", &register));
+        assert_eq!(super::storage_read(b"key").unwrap(), b"input");
+
+        let other = Register::new(8);
+        assert!(!super::storage_read_into_register(b"missing", &other));
+        assert_eq!(other.read(), None);
+        assert!(super::storage_read_into_register(b"key", &other));
+        assert_eq!(other.len(), Some(5));
+
+        other.write(b"output");
+        assert_eq!(other.read().unwrap(), b"output");
+        super::value_return_register(&other);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_input_typed() {
+        use crate::ParseInputError;
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        assert_eq!(super::input_json::<u64>(), Err(ParseInputError::Missing));
+        assert_eq!(
+            super::input_borsh::<u64>().unwrap_err().to_string(),
+            "Expected input since method has arguments."
+        );
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .input(b"[1,2]".to_vec())
+            .build());
+        assert_eq!(super::input_json::<Vec<u8>>(), Ok(vec![1, 2]));
+        let err = super::input_json::<String>().unwrap_err();
+        let expected = serde_json::from_slice::<String>(b"[1,2]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Failed to deserialize input from JSON.: {:?}", expected)
+        );
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .input(7u32.to_le_bytes().to_vec())
+            .build());
+        assert_eq!(super::input_borsh::<u32>(), Ok(7));
+        assert!(matches!(super::input_borsh::<u64>(), Err(ParseInputError::Borsh(_))));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_promise_results() {
+        use crate::PromiseResult;
+
+        crate::testing_env!(
+            crate::test_utils::VMContextBuilder::new().build(),
+            crate::VMConfig::test(),
+            crate::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![
+                PromiseResult::Successful(b"1".to_vec()),
+                PromiseResult::Failed,
+                PromiseResult::Successful(b"3".to_vec()),
+            ],
+        );
+        let mut results = super::promise_results();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.next(), Some(PromiseResult::Successful(b"1".to_vec())));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results.nth(1), Some(PromiseResult::Successful(b"3".to_vec())));
+        assert_eq!(results.next(), None);
+        assert_eq!(super::collect_successes::<u8>(), vec![1, 3]);
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        assert_eq!(super::promise_results().len(), 0);
+        assert!(super::collect_successes::<u8>().is_empty());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_profile_gas() {
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        let checkpoint = super::gas_checkpoint();
+        let value = crate::profile_gas!("hashing", {
+            super::sha256(b"data");
+            1
+        });
+        assert_eq!(value, 1);
+        let used = super::gas_since(checkpoint);
+        let logs = crate::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        let logged: u64 = logs[0]
+            .strip_prefix("hashing: ")
+            .and_then(|log| log.strip_suffix(" gas"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(logged > 0 && logged <= used.0, "{} > {}", logged, used.0);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_input_field() {
+        use crate::ParseInputError;
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .input(br#"{"code":[1,{"a":"}"}],"id":"alice.near","n":null,"x\"":3}"#.to_vec())
+            .build());
+        assert_eq!(super::input_field::<String>("id"), Ok("alice.near".to_string()));
+        assert_eq!(super::input_field::<u8>("x\""), Ok(3));
+        assert_eq!(super::input_field::<Option<u8>>("n"), Ok(None));
+        assert_eq!(super::input_field::<Option<u8>>("missing"), Ok(None));
+        let err = super::input_field::<u8>("missing").unwrap_err().to_string();
+        assert!(err.contains("missing field `missing`"), "{}", err);
+        assert!(matches!(super::input_field::<u8>("id"), Err(ParseInputError::Json(_))));
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new()
+            .input(br#"{"id":1,"id":2}"#.to_vec())
+            .build());
+        assert!(matches!(super::input_field::<u8>("id"), Err(ParseInputError::Json(_))));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_try_variants() {
+        use crate::EnvError;
+
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().build());
+        assert_eq!(
+            super::try_promise_result(0),
+            Err(EnvError::PromiseIndexOutOfBounds { index: 0, count: 0 })
+        );
+
+        super::storage_write(b"short", &[1]);
+        assert_eq!(super::try_storage_read_typed::<u8>(b"short"), Ok(Some(1)));
+        assert_eq!(super::try_storage_read_typed::<u8>(b"missing"), Ok(None));
+        assert!(matches!(
+            super::try_storage_read_typed::<u64>(b"short"),
+            Err(EnvError::Deserialization(_))
+        ));
+        assert!(matches!(
+            super::try_storage_read_json::<String>(b"short"),
+            Err(EnvError::Deserialization(_))
+        ));
+        assert!(matches!(super::try_state_read::<u64>(), Ok(None)));
+
+        let map: std::collections::HashMap<(u8, u8), u8> = [((1, 2), 3)].into_iter().collect();
+        assert!(matches!(
+            super::try_storage_write_json(b"map", &map),
+            Err(EnvError::Serialization(_))
+        ));
+        assert_eq!(super::try_storage_write_json(b"map", &[1, 2]), Ok(false));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_state_versioned() {
+        use crate::EnvError;
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+        struct State(u16);
+
+        impl super::VersionedState for State {
+            const VERSION: u8 = 2;
+
+            fn deserialize_version(version: u8, data: &[u8]) -> std::io::Result<Self> {
+                match version {
+                    1 => u8::try_from_slice(data).map(|v1| State(v1.into())),
+                    2 => State::try_from_slice(data),
+                    _ => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "version")),
+                }
+            }
+        }
+
+        assert_eq!(super::try_state_read_versioned::<State>(), Ok(None));
+        super::storage_write(b"STATE", &[1, 7]);
+        assert_eq!(super::state_read_versioned(), Some(State(7)));
+
+        super::state_write_versioned(&State(300));
+        assert_eq!(super::storage_read(b"STATE").unwrap(), [2, 44, 1]);
+        assert_eq!(super::state_read_versioned(), Some(State(300)));
+
+        for data in [&[3, 0, 0][..], &[]] {
+            super::storage_write(b"STATE", data);
+            assert!(matches!(
+                super::try_state_read_versioned::<State>(),
+                Err(EnvError::Deserialization(_))
+            ));
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_emit_event() {
+        super::emit_event("nep171", "1.0.0", "nft_burn", &[("1", 2)]);
+        assert_eq!(
+            crate::test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_burn","data":[["1",2]]}"#
+            ]
+        );
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic(expected = "exceeds the limit of 16384 bytes")]
+    fn test_emit_event_too_long() {
+        super::emit_event("nep171", "1.0.0", "nft_mint", &"a".repeat(16 * 1024));
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     #[test]
     fn signer_public_key() {
@@ -1021,4 +2219,137 @@ mod tests {
             .build());
         assert_eq!(super::signer_account_pk(), key);
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_custom_blockchain_interface() {
+        use crate::mock::BlockchainInterface;
+
+        struct FixedTime(u64);
+
+        impl BlockchainInterface for FixedTime {
+            unsafe fn block_timestamp(&mut self) -> u64 {
+                self.0
+            }
+        }
+
+        super::storage_write(b"key", b"value");
+        super::set_blockchain_interface(FixedTime(42));
+        assert_eq!(super::block_timestamp(), 42);
+
+        // The custom interface has no storage to carry over, so the mocked blockchain starts empty.
+        crate::testing_env!(crate::test_utils::VMContextBuilder::new().block_timestamp(7).build());
+        assert_eq!(super::block_timestamp(), 7);
+        assert!(!super::storage_has_key(b"key"));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_value_return_typed() {
+        use crate::mock::BlockchainInterface;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct ReturnValue(Rc<RefCell<Vec<u8>>>);
+
+        impl BlockchainInterface for ReturnValue {
+            unsafe fn value_return(&mut self, value_len: u64, value_ptr: u64) {
+                let value = std::slice::from_raw_parts(value_ptr as *const u8, value_len as _);
+                *self.0.borrow_mut() = value.to_vec();
+            }
+        }
+
+        let returned = Rc::new(RefCell::new(Vec::new()));
+        super::set_blockchain_interface(ReturnValue(Rc::clone(&returned)));
+
+        super::value_return_json(&("a", 1u8));
+        assert_eq!(*returned.borrow(), br#"["a",1]"#);
+        super::value_return_borsh(&7u32);
+        assert_eq!(*returned.borrow(), 7u32.to_le_bytes());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_estimate_costs() {
+        use crate::RuntimeFeesConfig;
+
+        let fees = RuntimeFeesConfig::test();
+        let receipt = &fees.action_receipt_creation_config;
+        let call = &fees.action_creation_config.function_call_cost;
+        let call_byte = &fees.action_creation_config.function_call_cost_per_byte;
+        let gas = receipt.send_not_sir
+            + receipt.execution
+            + call.send_not_sir
+            + call.execution
+            + 10 * (call_byte.send_not_sir + call_byte.execution)
+            + 1_000;
+        assert_eq!(
+            super::estimate_function_call_cost(10, Gas(1_000)),
+            Balance::from(gas) * super::MIN_GAS_PRICE
+        );
+        assert_eq!(super::estimate_storage_cost(100), 100 * super::STORAGE_PRICE_PER_BYTE);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_context_cache() {
+        use crate::mock::BlockchainInterface;
+        use crate::test_utils::{accounts, VMContextBuilder};
+        use crate::{MockedBlockchain, RuntimeFeesConfig, VMConfig};
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        struct CountingContextReads {
+            inner: MockedBlockchain,
+            reads: Rc<Cell<u32>>,
+        }
+
+        impl BlockchainInterface for CountingContextReads {
+            unsafe fn read_register(&mut self, register_id: u64, ptr: u64) {
+                self.inner.read_register(register_id, ptr)
+            }
+            unsafe fn register_len(&mut self, register_id: u64) -> u64 {
+                self.inner.register_len(register_id)
+            }
+            unsafe fn predecessor_account_id(&mut self, register_id: u64) {
+                self.reads.set(self.reads.get() + 1);
+                self.inner.predecessor_account_id(register_id)
+            }
+            unsafe fn attached_deposit(&mut self, balance_ptr: u64) {
+                self.reads.set(self.reads.get() + 1);
+                self.inner.attached_deposit(balance_ptr)
+            }
+        }
+
+        let reads = Rc::new(Cell::new(0));
+        let context =
+            VMContextBuilder::new().predecessor_account_id(accounts(1)).attached_deposit(5).build();
+        super::set_blockchain_interface(CountingContextReads {
+            inner: MockedBlockchain::new(
+                context,
+                VMConfig::test(),
+                RuntimeFeesConfig::test(),
+                vec![],
+                Default::default(),
+                Default::default(),
+                None,
+            ),
+            reads: Rc::clone(&reads),
+        });
+        for _ in 0..3 {
+            assert_eq!(super::predecessor_account_id(), accounts(1));
+            assert_eq!(super::attached_deposit(), 5);
+        }
+        assert_eq!(reads.get(), 2);
+
+        // A new context resets the cache.
+        super::set_blockchain_interface(MockedBlockchain::default());
+        assert_eq!(
+            super::predecessor_account_id(),
+            VMContextBuilder::new().build().predecessor_account_id
+        );
+        crate::testing_env!(VMContextBuilder::new().predecessor_account_id(accounts(2)).build());
+        assert_eq!(super::predecessor_account_id(), accounts(2));
+        assert_eq!(super::attached_deposit(), 0);
+    }
 }
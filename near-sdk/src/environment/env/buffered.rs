@@ -0,0 +1,121 @@
+//! Storage writes that are buffered in memory and written to storage once, when the buffer is
+//! flushed, instead of on every call.
+//!
+//! Repeated writes and removes of the same key only cost a single `storage_write` or
+//! `storage_remove` when flushed, similarly to how `store` collections cache their own keys.
+//! Reads go through the buffer, so they observe the buffered writes.
+//!
+//! The code generated by `#[near_bindgen]` flushes the buffer at the end of every method that is
+//! not a view method. Contracts written without it have to call [`flush`] before returning.
+//!
+//! ```
+//! use near_sdk::env::{self, buffered};
+//!
+//! for i in 0..10u8 {
+//!     // Only the last write is written to storage.
+//!     buffered::storage_write(b"counter", &[i]);
+//! }
+//! assert_eq!(buffered::storage_read(b"counter"), Some(vec![9]));
+//! assert!(!env::storage_has_key(b"counter"));
+//!
+//! buffered::flush();
+//! assert_eq!(env::storage_read(b"counter"), Some(vec![9]));
+//! ```
+
+// Const initializers of thread locals need a newer compiler than the minimum supported one.
+#![allow(clippy::missing_const_for_thread_local)]
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+
+thread_local! {
+    /// Buffered values by key, where `None` is a buffered remove.
+    static BUFFER: RefCell<BTreeMap<Vec<u8>, Option<Vec<u8>>>> = RefCell::new(BTreeMap::new());
+    /// Writes the buffer to storage. Only set once something is buffered, so that contracts that
+    /// do not use the buffer do not include the code to write it.
+    static PENDING_FLUSH: Cell<Option<fn()>> = Cell::new(None);
+}
+
+fn buffer(key: &[u8], value: Option<Vec<u8>>) {
+    PENDING_FLUSH.with(|pending| pending.set(Some(write_buffer)));
+    BUFFER.with(|buffer| buffer.borrow_mut().insert(key.to_vec(), value));
+}
+
+fn write_buffer() {
+    let buffer = BUFFER.with(|buffer| std::mem::take(&mut *buffer.borrow_mut()));
+    for (key, value) in buffer {
+        match value {
+            Some(value) => super::storage_write(&key, &value),
+            None => super::storage_remove(&key),
+        };
+    }
+}
+
+/// Buffers a write of the value under the key, replacing any buffered write or remove of it.
+pub fn storage_write(key: &[u8], value: &[u8]) {
+    buffer(key, Some(value.to_vec()))
+}
+
+/// Buffers a remove of the key, replacing any buffered write of it.
+pub fn storage_remove(key: &[u8]) {
+    buffer(key, None)
+}
+
+/// Reads the value under the key, from the buffer if it has a buffered write or remove of the
+/// key, or from storage otherwise.
+pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
+    match BUFFER.with(|buffer| buffer.borrow().get(key).cloned()) {
+        Some(value) => value,
+        None => super::storage_read(key),
+    }
+}
+
+/// Returns `true` if there is a value under the key, considering the buffered writes and removes.
+pub fn storage_has_key(key: &[u8]) -> bool {
+    match BUFFER.with(|buffer| buffer.borrow().get(key).map(Option::is_some)) {
+        Some(has_key) => has_key,
+        None => super::storage_has_key(key),
+    }
+}
+
+/// Writes the buffered writes and removes to storage, in the order of their keys, and empties the
+/// buffer.
+pub fn flush() {
+    if let Some(write) = PENDING_FLUSH.with(Cell::take) {
+        write()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::env;
+
+    #[test]
+    fn coalesces_writes() {
+        env::storage_write(b"a", b"old");
+        storage_write(b"a", b"1");
+        storage_write(b"b", b"2");
+        storage_remove(b"b");
+        storage_remove(b"a");
+        storage_write(b"a", b"3");
+        storage_write(b"c", b"4");
+
+        assert_eq!(env::storage_read(b"a"), Some(b"old".to_vec()));
+        assert_eq!(storage_read(b"a"), Some(b"3".to_vec()));
+        assert!(!storage_has_key(b"b"));
+        assert!(storage_has_key(b"c"));
+        assert!(!env::storage_has_key(b"c"));
+
+        flush();
+        assert_eq!(env::storage_read(b"a"), Some(b"3".to_vec()));
+        assert!(!env::storage_has_key(b"b"));
+        assert_eq!(env::storage_read(b"c"), Some(b"4".to_vec()));
+
+        // The buffer is empty after flushing.
+        env::storage_remove(b"c");
+        assert!(!storage_has_key(b"c"));
+        flush();
+        assert!(!env::storage_has_key(b"c"));
+    }
+}
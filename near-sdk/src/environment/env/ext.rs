@@ -0,0 +1,48 @@
+//! Raw bindings to the host functions of the NEAR runtime, for host functions that
+//! [`env`](crate::env) does not wrap yet, or when its wrappers do not fit. These are the bindings
+//! of `near-sys`, which are also available as `near_sdk::sys` with the `unstable` feature.
+//!
+//! The functions are `unsafe` and take pointers into the memory of the contract as `u64`. Most
+//! of them write their results into a register, which can be read with [`read_into_register`].
+//!
+//! ```
+//! use near_sdk::env::ext;
+//!
+//! let seed = ext::read_into_register(|register_id| unsafe { ext::random_seed(register_id) });
+//! assert_eq!(seed, near_sdk::env::random_seed());
+//! ```
+
+pub use near_sys::*;
+
+/// Calls `f` with the id of a register to write a result into, and returns the content of the
+/// register. The register is cleared before, so the result is empty if `f` did not write it.
+pub fn read_into_register(f: impl FnOnce(u64)) -> Vec<u8> {
+    // Clear the register, so that a value left by a previous call is not returned.
+    let empty: &[u8] = &[];
+    unsafe { near_sys::write_register(super::ATOMIC_OP_REGISTER, 0, empty.as_ptr() as _) };
+    f(super::ATOMIC_OP_REGISTER);
+    super::expect_register(super::read_register(super::ATOMIC_OP_REGISTER))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    #[test]
+    fn raw_host_functions() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("alice.near".parse().unwrap())
+            .build());
+        let account_id =
+            read_into_register(|register_id| unsafe { current_account_id(register_id) });
+        assert_eq!(account_id, b"alice.near");
+
+        let found = std::cell::Cell::new(1);
+        let value = read_into_register(|register_id| unsafe {
+            found.set(storage_read(3, b"key".as_ptr() as _, register_id));
+        });
+        assert_eq!((found.get(), value), (0, Vec::new()));
+    }
+}
@@ -0,0 +1,99 @@
+use std::io;
+
+macro_rules! impl_hasher {
+    ($(#[$doc:meta])* $name:ident, $hash:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Default, Clone)]
+        pub struct $name {
+            data: Vec<u8>,
+        }
+
+        impl $name {
+            /// Creates a hasher without any data.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Creates a hasher that can be updated with `capacity` bytes before reallocating.
+            pub fn with_capacity(capacity: usize) -> Self {
+                Self { data: Vec::with_capacity(capacity) }
+            }
+
+            /// Appends the bytes to the data to hash.
+            pub fn update(&mut self, data: &[u8]) -> &mut Self {
+                self.data.extend_from_slice(data);
+                self
+            }
+
+            /// Hashes all the data the hasher was updated with, in a single host function call.
+            pub fn finalize(&self) -> [u8; 32] {
+                super::$hash(&self.data)
+            }
+        }
+
+        /// Writing to the hasher updates it, so values can be serialized directly into it.
+        impl io::Write for $name {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_hasher!(
+    /// Incremental SHA-256 hasher, which hashes data that is built from multiple parts without
+    /// having to concatenate them first.
+    ///
+    /// ```
+    /// use near_sdk::env::{self, Sha256Hasher};
+    ///
+    /// let hash = Sha256Hasher::new().update(b"prefix").update(b"data").finalize();
+    /// assert_eq!(hash, env::sha256_array(b"prefixdata"));
+    /// ```
+    Sha256Hasher,
+    sha256_array
+);
+
+impl_hasher!(
+    /// Incremental Keccak-256 hasher, which hashes data that is built from multiple parts without
+    /// having to concatenate them first.
+    ///
+    /// ```
+    /// use near_sdk::env::{self, Keccak256Hasher};
+    ///
+    /// let hash = Keccak256Hasher::new().update(b"prefix").update(b"data").finalize();
+    /// assert_eq!(hash, env::keccak256_array(b"prefixdata"));
+    /// ```
+    Keccak256Hasher,
+    keccak256_array
+);
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::env;
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn hashes_concatenation() {
+        let mut sha256 = Sha256Hasher::with_capacity(8);
+        let mut keccak256 = Keccak256Hasher::new();
+        for part in [&b"one"[..], b"", b"two"] {
+            sha256.update(part);
+            keccak256.update(part);
+        }
+        assert_eq!(sha256.finalize(), env::sha256_array(b"onetwo"));
+        assert_eq!(keccak256.finalize(), env::keccak256_array(b"onetwo"));
+        assert_eq!(Sha256Hasher::new().finalize(), env::sha256_array(b""));
+
+        let value = (7u32, "seven".to_string());
+        let mut hasher = Sha256Hasher::new();
+        value.serialize(&mut hasher).unwrap();
+        assert_eq!(hasher.finalize(), env::sha256_array(&value.try_to_vec().unwrap()));
+    }
+}
@@ -0,0 +1,67 @@
+//! Variants of the economics functions of [`env`](crate::env) that return amounts of tokens as
+//! [`NearToken`] and amounts of gas as [`Gas`], instead of plain integers, so that mixing up units
+//! is a compile error.
+//!
+//! ```no_run
+//! use near_sdk::env::typed;
+//! use near_sdk::NearToken;
+//!
+//! if typed::attached_deposit() < NearToken::ONE_NEAR {
+//!     near_sdk::env::panic_str("Attach at least 1 NEAR");
+//! }
+//! ```
+
+use crate::{Gas, NearToken};
+
+/// The balance attached to the given account. This includes the attached deposit that was
+/// attached to the transaction.
+pub fn account_balance() -> NearToken {
+    NearToken(super::account_balance())
+}
+
+/// The balance locked for potential validator staking.
+pub fn account_locked_balance() -> NearToken {
+    NearToken(super::account_locked_balance())
+}
+
+/// The balance that was attached to the call that will be immediately deposited before the
+/// contract execution starts
+pub fn attached_deposit() -> NearToken {
+    NearToken(super::attached_deposit())
+}
+
+/// The amount of gas attached to the call that can be used to pay for the gas fees.
+pub fn prepaid_gas() -> Gas {
+    super::prepaid_gas()
+}
+
+/// The gas that was already burnt during the contract execution (cannot exceed `prepaid_gas`)
+pub fn used_gas() -> Gas {
+    super::used_gas()
+}
+
+/// Price for storing one byte of data in the contract state.
+pub fn storage_byte_cost() -> NearToken {
+    NearToken(super::storage_byte_cost())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    #[test]
+    fn typed_economics() {
+        testing_env!(VMContextBuilder::new()
+            .attached_deposit(5)
+            .account_balance(NearToken::ONE_NEAR.as_yoctonear())
+            .prepaid_gas(Gas::ONE_TERA * 10)
+            .build());
+        assert_eq!(attached_deposit(), NearToken::from_yoctonear(5));
+        assert_eq!(account_balance(), NearToken::ONE_NEAR + NearToken::from_yoctonear(5));
+        assert_eq!(account_locked_balance(), NearToken::ZERO);
+        assert_eq!(prepaid_gas(), Gas::ONE_TERA * 10);
+        assert_eq!(storage_byte_cost(), NearToken(crate::env::storage_byte_cost()));
+    }
+}
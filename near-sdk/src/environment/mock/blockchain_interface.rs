@@ -0,0 +1,423 @@
+use super::MockedBlockchain;
+
+/// Panics for host functions that are not implemented by the blockchain interface.
+macro_rules! unsupported {
+    ($name:literal) => {
+        panic!(concat!("`", $name, "` is not supported by the blockchain interface"))
+    };
+}
+
+/// Low-level interface to the host functions of the NEAR runtime, which `env::*` calls when the
+/// contract is not compiled to Wasm.
+///
+/// [`MockedBlockchain`] is the implementation used by `testing_env!`. Test frameworks, coverage
+/// tools and alternative runtimes can implement this trait to run contract code against their own
+/// blockchain, and set it with [`env::set_blockchain_interface`](crate::env::set_blockchain_interface).
+///
+/// The methods have the same arguments and semantics as the host functions of `near-sys`, and panic
+/// by default, so that an implementation only needs to provide the functions it supports. Pointers
+/// are addresses in the memory of the current process, and errors that would abort the execution of
+/// the contract should be raised as panics. Implementations must not call `env::*` functions,
+/// since the interface is borrowed while its methods run.
+///
+/// # Safety
+///
+/// Callers must pass pointers to memory that is valid for the lengths given, as with the host
+/// functions of `near-sys`.
+#[allow(clippy::too_many_arguments, clippy::missing_safety_doc)]
+pub trait BlockchainInterface {
+    // #############
+    // # Registers #
+    // #############
+    unsafe fn read_register(&mut self, _register_id: u64, _ptr: u64) {
+        unsupported!("read_register")
+    }
+    unsafe fn register_len(&mut self, _register_id: u64) -> u64 {
+        unsupported!("register_len")
+    }
+    unsafe fn write_register(&mut self, _register_id: u64, _data_len: u64, _data_ptr: u64) {
+        unsupported!("write_register")
+    }
+    // ###############
+    // # Context API #
+    // ###############
+    unsafe fn current_account_id(&mut self, _register_id: u64) {
+        unsupported!("current_account_id")
+    }
+    unsafe fn signer_account_id(&mut self, _register_id: u64) {
+        unsupported!("signer_account_id")
+    }
+    unsafe fn signer_account_pk(&mut self, _register_id: u64) {
+        unsupported!("signer_account_pk")
+    }
+    unsafe fn predecessor_account_id(&mut self, _register_id: u64) {
+        unsupported!("predecessor_account_id")
+    }
+    unsafe fn input(&mut self, _register_id: u64) {
+        unsupported!("input")
+    }
+    unsafe fn block_index(&mut self) -> u64 {
+        unsupported!("block_index")
+    }
+    unsafe fn block_timestamp(&mut self) -> u64 {
+        unsupported!("block_timestamp")
+    }
+    unsafe fn epoch_height(&mut self) -> u64 {
+        unsupported!("epoch_height")
+    }
+    unsafe fn storage_usage(&mut self) -> u64 {
+        unsupported!("storage_usage")
+    }
+    // #################
+    // # Economics API #
+    // #################
+    unsafe fn account_balance(&mut self, _balance_ptr: u64) {
+        unsupported!("account_balance")
+    }
+    unsafe fn account_locked_balance(&mut self, _balance_ptr: u64) {
+        unsupported!("account_locked_balance")
+    }
+    unsafe fn attached_deposit(&mut self, _balance_ptr: u64) {
+        unsupported!("attached_deposit")
+    }
+    unsafe fn prepaid_gas(&mut self) -> u64 {
+        unsupported!("prepaid_gas")
+    }
+    unsafe fn used_gas(&mut self) -> u64 {
+        unsupported!("used_gas")
+    }
+    // ############
+    // # Math API #
+    // ############
+    unsafe fn random_seed(&mut self, _register_id: u64) {
+        unsupported!("random_seed")
+    }
+    unsafe fn sha256(&mut self, _value_len: u64, _value_ptr: u64, _register_id: u64) {
+        unsupported!("sha256")
+    }
+    unsafe fn keccak256(&mut self, _value_len: u64, _value_ptr: u64, _register_id: u64) {
+        unsupported!("keccak256")
+    }
+    unsafe fn keccak512(&mut self, _value_len: u64, _value_ptr: u64, _register_id: u64) {
+        unsupported!("keccak512")
+    }
+    unsafe fn ripemd160(&mut self, _value_len: u64, _value_ptr: u64, _register_id: u64) {
+        unsupported!("ripemd160")
+    }
+    unsafe fn ecrecover(
+        &mut self,
+        _hash_len: u64,
+        _hash_ptr: u64,
+        _sig_len: u64,
+        _sig_ptr: u64,
+        _v: u64,
+        _malleability_flag: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("ecrecover")
+    }
+    unsafe fn ed25519_verify(
+        &mut self,
+        _sig_len: u64,
+        _sig_ptr: u64,
+        _msg_len: u64,
+        _msg_ptr: u64,
+        _pub_key_len: u64,
+        _pub_key_ptr: u64,
+    ) -> u64 {
+        unsupported!("ed25519_verify")
+    }
+    // #####################
+    // # Miscellaneous API #
+    // #####################
+    unsafe fn value_return(&mut self, _value_len: u64, _value_ptr: u64) {
+        unsupported!("value_return")
+    }
+    unsafe fn panic(&mut self) -> ! {
+        unsupported!("panic")
+    }
+    unsafe fn panic_utf8(&mut self, _len: u64, _ptr: u64) -> ! {
+        unsupported!("panic_utf8")
+    }
+    unsafe fn log_utf8(&mut self, _len: u64, _ptr: u64) {
+        unsupported!("log_utf8")
+    }
+    unsafe fn log_utf16(&mut self, _len: u64, _ptr: u64) {
+        unsupported!("log_utf16")
+    }
+    // ################
+    // # Promises API #
+    // ################
+    unsafe fn promise_create(
+        &mut self,
+        _account_id_len: u64,
+        _account_id_ptr: u64,
+        _function_name_len: u64,
+        _function_name_ptr: u64,
+        _arguments_len: u64,
+        _arguments_ptr: u64,
+        _amount_ptr: u64,
+        _gas: u64,
+    ) -> u64 {
+        unsupported!("promise_create")
+    }
+    unsafe fn promise_then(
+        &mut self,
+        _promise_index: u64,
+        _account_id_len: u64,
+        _account_id_ptr: u64,
+        _function_name_len: u64,
+        _function_name_ptr: u64,
+        _arguments_len: u64,
+        _arguments_ptr: u64,
+        _amount_ptr: u64,
+        _gas: u64,
+    ) -> u64 {
+        unsupported!("promise_then")
+    }
+    unsafe fn promise_and(&mut self, _promise_idx_ptr: u64, _promise_idx_count: u64) -> u64 {
+        unsupported!("promise_and")
+    }
+    unsafe fn promise_batch_create(&mut self, _account_id_len: u64, _account_id_ptr: u64) -> u64 {
+        unsupported!("promise_batch_create")
+    }
+    unsafe fn promise_batch_then(
+        &mut self,
+        _promise_index: u64,
+        _account_id_len: u64,
+        _account_id_ptr: u64,
+    ) -> u64 {
+        unsupported!("promise_batch_then")
+    }
+    // #######################
+    // # Promise API actions #
+    // #######################
+    unsafe fn promise_batch_action_create_account(&mut self, _promise_index: u64) {
+        unsupported!("promise_batch_action_create_account")
+    }
+    unsafe fn promise_batch_action_deploy_contract(
+        &mut self,
+        _promise_index: u64,
+        _code_len: u64,
+        _code_ptr: u64,
+    ) {
+        unsupported!("promise_batch_action_deploy_contract")
+    }
+    unsafe fn promise_batch_action_function_call(
+        &mut self,
+        _promise_index: u64,
+        _function_name_len: u64,
+        _function_name_ptr: u64,
+        _arguments_len: u64,
+        _arguments_ptr: u64,
+        _amount_ptr: u64,
+        _gas: u64,
+    ) {
+        unsupported!("promise_batch_action_function_call")
+    }
+    unsafe fn promise_batch_action_function_call_weight(
+        &mut self,
+        _promise_index: u64,
+        _function_name_len: u64,
+        _function_name_ptr: u64,
+        _arguments_len: u64,
+        _arguments_ptr: u64,
+        _amount_ptr: u64,
+        _gas: u64,
+        _weight: u64,
+    ) {
+        unsupported!("promise_batch_action_function_call_weight")
+    }
+    unsafe fn promise_batch_action_transfer(&mut self, _promise_index: u64, _amount_ptr: u64) {
+        unsupported!("promise_batch_action_transfer")
+    }
+    unsafe fn promise_batch_action_stake(
+        &mut self,
+        _promise_index: u64,
+        _amount_ptr: u64,
+        _public_key_len: u64,
+        _public_key_ptr: u64,
+    ) {
+        unsupported!("promise_batch_action_stake")
+    }
+    unsafe fn promise_batch_action_add_key_with_full_access(
+        &mut self,
+        _promise_index: u64,
+        _public_key_len: u64,
+        _public_key_ptr: u64,
+        _nonce: u64,
+    ) {
+        unsupported!("promise_batch_action_add_key_with_full_access")
+    }
+    unsafe fn promise_batch_action_add_key_with_function_call(
+        &mut self,
+        _promise_index: u64,
+        _public_key_len: u64,
+        _public_key_ptr: u64,
+        _nonce: u64,
+        _allowance_ptr: u64,
+        _receiver_id_len: u64,
+        _receiver_id_ptr: u64,
+        _function_names_len: u64,
+        _function_names_ptr: u64,
+    ) {
+        unsupported!("promise_batch_action_add_key_with_function_call")
+    }
+    unsafe fn promise_batch_action_delete_key(
+        &mut self,
+        _promise_index: u64,
+        _public_key_len: u64,
+        _public_key_ptr: u64,
+    ) {
+        unsupported!("promise_batch_action_delete_key")
+    }
+    unsafe fn promise_batch_action_delete_account(
+        &mut self,
+        _promise_index: u64,
+        _beneficiary_id_len: u64,
+        _beneficiary_id_ptr: u64,
+    ) {
+        unsupported!("promise_batch_action_delete_account")
+    }
+    // #######################
+    // # Promise API results #
+    // #######################
+    unsafe fn promise_results_count(&mut self) -> u64 {
+        unsupported!("promise_results_count")
+    }
+    unsafe fn promise_result(&mut self, _result_idx: u64, _register_id: u64) -> u64 {
+        unsupported!("promise_result")
+    }
+    unsafe fn promise_return(&mut self, _promise_id: u64) {
+        unsupported!("promise_return")
+    }
+    // ###############
+    // # Storage API #
+    // ###############
+    unsafe fn storage_write(
+        &mut self,
+        _key_len: u64,
+        _key_ptr: u64,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("storage_write")
+    }
+    unsafe fn storage_read(&mut self, _key_len: u64, _key_ptr: u64, _register_id: u64) -> u64 {
+        unsupported!("storage_read")
+    }
+    unsafe fn storage_remove(&mut self, _key_len: u64, _key_ptr: u64, _register_id: u64) -> u64 {
+        unsupported!("storage_remove")
+    }
+    unsafe fn storage_has_key(&mut self, _key_len: u64, _key_ptr: u64) -> u64 {
+        unsupported!("storage_has_key")
+    }
+    // #################
+    // # Validator API #
+    // #################
+    unsafe fn validator_stake(
+        &mut self,
+        _account_id_len: u64,
+        _account_id_ptr: u64,
+        _stake_ptr: u64,
+    ) {
+        unsupported!("validator_stake")
+    }
+    unsafe fn validator_total_stake(&mut self, _stake_ptr: u64) {
+        unsupported!("validator_total_stake")
+    }
+    // #############
+    // # Alt BN128 #
+    // #############
+    unsafe fn alt_bn128_g1_multiexp(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) {
+        unsupported!("alt_bn128_g1_multiexp")
+    }
+    unsafe fn alt_bn128_g1_sum(&mut self, _value_len: u64, _value_ptr: u64, _register_id: u64) {
+        unsupported!("alt_bn128_g1_sum")
+    }
+    unsafe fn alt_bn128_pairing_check(&mut self, _value_len: u64, _value_ptr: u64) -> u64 {
+        unsupported!("alt_bn128_pairing_check")
+    }
+    // #############
+    // # BLS12-381 #
+    // #############
+    unsafe fn bls12381_p1_sum(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_p1_sum")
+    }
+    unsafe fn bls12381_p2_sum(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_p2_sum")
+    }
+    unsafe fn bls12381_g1_multiexp(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_g1_multiexp")
+    }
+    unsafe fn bls12381_g2_multiexp(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_g2_multiexp")
+    }
+    unsafe fn bls12381_map_fp_to_g1(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_map_fp_to_g1")
+    }
+    unsafe fn bls12381_map_fp2_to_g2(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_map_fp2_to_g2")
+    }
+    unsafe fn bls12381_pairing_check(&mut self, _value_len: u64, _value_ptr: u64) -> u64 {
+        unsupported!("bls12381_pairing_check")
+    }
+    unsafe fn bls12381_p1_decompress(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_p1_decompress")
+    }
+    unsafe fn bls12381_p2_decompress(
+        &mut self,
+        _value_len: u64,
+        _value_ptr: u64,
+        _register_id: u64,
+    ) -> u64 {
+        unsupported!("bls12381_p2_decompress")
+    }
+
+    /// Returns the interface as a [`MockedBlockchain`] if it is one, which is needed by the test
+    /// utilities that inspect the mocked state, like `testing_env!` and `test_utils::get_logs`.
+    fn as_mocked_blockchain(&mut self) -> Option<&mut MockedBlockchain> {
+        None
+    }
+}
@@ -0,0 +1,225 @@
+//! BLS12-381 host functions, which `VMLogic` does not implement yet, computed with `blst`.
+//!
+//! Each function takes the raw input of the host function and returns `None` if any of the
+//! encoded elements is invalid, which the host function reports with the return value `1`.
+//! Inputs of an invalid length abort execution, the same as with the real host functions.
+
+use blst::*;
+
+const FP_LEN: usize = 48;
+const P1_LEN: usize = 96;
+const P2_LEN: usize = 192;
+const SCALAR_LEN: usize = 32;
+
+fn elements<'a>(input: &'a [u8], len: usize, name: &str) -> std::slice::ChunksExact<'a, u8> {
+    let elements = input.chunks_exact(len);
+    if !elements.remainder().is_empty() {
+        panic!("invalid {} input length: {}", name, input.len());
+    }
+    elements
+}
+
+fn read_p1_affine(bytes: &[u8]) -> Option<blst_p1_affine> {
+    let mut point = blst_p1_affine::default();
+    // Compressed points are only accepted by the `decompress` functions.
+    if bytes[0] & 0x80 != 0
+        || unsafe { blst_p1_deserialize(&mut point, bytes.as_ptr()) } != BLST_ERROR::BLST_SUCCESS
+    {
+        return None;
+    }
+    Some(point)
+}
+
+fn read_p2_affine(bytes: &[u8]) -> Option<blst_p2_affine> {
+    let mut point = blst_p2_affine::default();
+    if bytes[0] & 0x80 != 0
+        || unsafe { blst_p2_deserialize(&mut point, bytes.as_ptr()) } != BLST_ERROR::BLST_SUCCESS
+    {
+        return None;
+    }
+    Some(point)
+}
+
+fn read_p1(bytes: &[u8]) -> Option<blst_p1> {
+    let affine = read_p1_affine(bytes)?;
+    let mut point = blst_p1::default();
+    unsafe { blst_p1_from_affine(&mut point, &affine) };
+    Some(point)
+}
+
+fn read_p2(bytes: &[u8]) -> Option<blst_p2> {
+    let affine = read_p2_affine(bytes)?;
+    let mut point = blst_p2::default();
+    unsafe { blst_p2_from_affine(&mut point, &affine) };
+    Some(point)
+}
+
+/// Reads a big-endian field element, rejecting values that are not reduced modulo `p`.
+fn read_fp(bytes: &[u8]) -> Option<blst_fp> {
+    let mut fp = blst_fp::default();
+    let mut reduced = [0u8; FP_LEN];
+    unsafe {
+        blst_fp_from_bendian(&mut fp, bytes.as_ptr());
+        blst_bendian_from_fp(reduced.as_mut_ptr(), &fp);
+    }
+    if reduced[..] == bytes[..] {
+        Some(fp)
+    } else {
+        None
+    }
+}
+
+pub(super) fn p1_sum(input: &[u8]) -> Option<Vec<u8>> {
+    let mut sum = blst_p1::default();
+    for item in elements(input, 1 + P1_LEN, "bls12381_p1_sum") {
+        let mut point = read_p1(&item[1..])?;
+        match item[0] {
+            0 => {}
+            1 => unsafe { blst_p1_cneg(&mut point, true) },
+            sign => panic!("invalid bls12381_p1_sum sign: {}", sign),
+        }
+        let sum_ptr: *mut blst_p1 = &mut sum;
+        unsafe { blst_p1_add_or_double(sum_ptr, sum_ptr, &point) };
+    }
+    let mut output = vec![0; P1_LEN];
+    unsafe { blst_p1_serialize(output.as_mut_ptr(), &sum) };
+    Some(output)
+}
+
+pub(super) fn p2_sum(input: &[u8]) -> Option<Vec<u8>> {
+    let mut sum = blst_p2::default();
+    for item in elements(input, 1 + P2_LEN, "bls12381_p2_sum") {
+        let mut point = read_p2(&item[1..])?;
+        match item[0] {
+            0 => {}
+            1 => unsafe { blst_p2_cneg(&mut point, true) },
+            sign => panic!("invalid bls12381_p2_sum sign: {}", sign),
+        }
+        let sum_ptr: *mut blst_p2 = &mut sum;
+        unsafe { blst_p2_add_or_double(sum_ptr, sum_ptr, &point) };
+    }
+    let mut output = vec![0; P2_LEN];
+    unsafe { blst_p2_serialize(output.as_mut_ptr(), &sum) };
+    Some(output)
+}
+
+pub(super) fn g1_multiexp(input: &[u8]) -> Option<Vec<u8>> {
+    let mut sum = blst_p1::default();
+    for item in elements(input, P1_LEN + SCALAR_LEN, "bls12381_g1_multiexp") {
+        let point = read_p1(&item[..P1_LEN])?;
+        if !unsafe { blst_p1_in_g1(&point) } {
+            return None;
+        }
+        let mut product = blst_p1::default();
+        let sum_ptr: *mut blst_p1 = &mut sum;
+        unsafe {
+            blst_p1_mult(&mut product, &point, item[P1_LEN..].as_ptr(), SCALAR_LEN * 8);
+            blst_p1_add_or_double(sum_ptr, sum_ptr, &product);
+        }
+    }
+    let mut output = vec![0; P1_LEN];
+    unsafe { blst_p1_serialize(output.as_mut_ptr(), &sum) };
+    Some(output)
+}
+
+pub(super) fn g2_multiexp(input: &[u8]) -> Option<Vec<u8>> {
+    let mut sum = blst_p2::default();
+    for item in elements(input, P2_LEN + SCALAR_LEN, "bls12381_g2_multiexp") {
+        let point = read_p2(&item[..P2_LEN])?;
+        if !unsafe { blst_p2_in_g2(&point) } {
+            return None;
+        }
+        let mut product = blst_p2::default();
+        let sum_ptr: *mut blst_p2 = &mut sum;
+        unsafe {
+            blst_p2_mult(&mut product, &point, item[P2_LEN..].as_ptr(), SCALAR_LEN * 8);
+            blst_p2_add_or_double(sum_ptr, sum_ptr, &product);
+        }
+    }
+    let mut output = vec![0; P2_LEN];
+    unsafe { blst_p2_serialize(output.as_mut_ptr(), &sum) };
+    Some(output)
+}
+
+pub(super) fn map_fp_to_g1(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / FP_LEN * P1_LEN);
+    for item in elements(input, FP_LEN, "bls12381_map_fp_to_g1") {
+        let fp = read_fp(item)?;
+        let mut point = blst_p1::default();
+        let mut bytes = [0u8; P1_LEN];
+        unsafe {
+            blst_map_to_g1(&mut point, &fp, std::ptr::null());
+            blst_p1_serialize(bytes.as_mut_ptr(), &point);
+        }
+        output.extend_from_slice(&bytes);
+    }
+    Some(output)
+}
+
+pub(super) fn map_fp2_to_g2(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / (2 * FP_LEN) * P2_LEN);
+    for item in elements(input, 2 * FP_LEN, "bls12381_map_fp2_to_g2") {
+        // The imaginary part comes first, as in the coordinates of serialized `G2` points.
+        let fp2 = blst_fp2 { fp: [read_fp(&item[FP_LEN..])?, read_fp(&item[..FP_LEN])?] };
+        let mut point = blst_p2::default();
+        let mut bytes = [0u8; P2_LEN];
+        unsafe {
+            blst_map_to_g2(&mut point, &fp2, std::ptr::null());
+            blst_p2_serialize(bytes.as_mut_ptr(), &point);
+        }
+        output.extend_from_slice(&bytes);
+    }
+    Some(output)
+}
+
+pub(super) fn pairing_check(input: &[u8]) -> Option<bool> {
+    let mut product = unsafe { *blst_fp12_one() };
+    for item in elements(input, P1_LEN + P2_LEN, "bls12381_pairing_check") {
+        let p1 = read_p1_affine(&item[..P1_LEN])?;
+        let p2 = read_p2_affine(&item[P1_LEN..])?;
+        if !unsafe { blst_p1_affine_in_g1(&p1) && blst_p2_affine_in_g2(&p2) } {
+            return None;
+        }
+        // Pairings with the point at infinity are the identity.
+        if unsafe { blst_p1_affine_is_inf(&p1) || blst_p2_affine_is_inf(&p2) } {
+            continue;
+        }
+        let mut pairing = blst_fp12::default();
+        let product_ptr: *mut blst_fp12 = &mut product;
+        unsafe {
+            blst_miller_loop(&mut pairing, &p2, &p1);
+            blst_fp12_mul(product_ptr, product_ptr, &pairing);
+        }
+    }
+    let mut result = blst_fp12::default();
+    unsafe { blst_final_exp(&mut result, &product) };
+    Some(unsafe { blst_fp12_is_one(&result) })
+}
+
+pub(super) fn p1_decompress(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / (P1_LEN / 2) * P1_LEN);
+    for item in elements(input, P1_LEN / 2, "bls12381_p1_decompress") {
+        let mut point = blst_p1_affine::default();
+        if unsafe { blst_p1_uncompress(&mut point, item.as_ptr()) } != BLST_ERROR::BLST_SUCCESS {
+            return None;
+        }
+        let mut bytes = [0u8; P1_LEN];
+        unsafe { blst_p1_affine_serialize(bytes.as_mut_ptr(), &point) };
+        output.extend_from_slice(&bytes);
+    }
+    Some(output)
+}
+
+pub(super) fn p2_decompress(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() / (P2_LEN / 2) * P2_LEN);
+    for item in elements(input, P2_LEN / 2, "bls12381_p2_decompress") {
+        let mut point = blst_p2_affine::default();
+        if unsafe { blst_p2_uncompress(&mut point, item.as_ptr()) } != BLST_ERROR::BLST_SUCCESS {
+            return None;
+        }
+        let mut bytes = [0u8; P2_LEN];
+        unsafe { blst_p2_affine_serialize(bytes.as_mut_ptr(), &point) };
+        output.extend_from_slice(&bytes);
+    }
+    Some(output)
+}
@@ -1,4 +1,4 @@
-use super::{Receipt, SdkExternal};
+use super::{bls12381, BlockchainInterface, Receipt, SdkExternal};
 use crate::mock::VmAction;
 use crate::test_utils::VMContextBuilder;
 use crate::types::{Balance, PromiseResult};
@@ -181,100 +181,574 @@ fn pub_key_conversion(key: &VmPublicKey) -> PublicKey {
     String::from(key).parse().unwrap()
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-mod mock_chain {
-    use near_vm_logic::{VMLogic, VMLogicError};
+impl MockedBlockchain {
+    /// Runs a BLS12-381 operation, which `VMLogic` does not implement yet, on the input in guest
+    /// memory (the host memory in unit tests) and writes its output into the register.
+    unsafe fn bls12381_op(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+        op: fn(&[u8]) -> Option<Vec<u8>>,
+    ) -> u64 {
+        let input = std::slice::from_raw_parts(value_ptr as *const u8, value_len as _);
+        match op(input) {
+            Some(output) => {
+                self.write_register(register_id, output.len() as _, output.as_ptr() as _);
+                0
+            }
+            None => 1,
+        }
+    }
+}
 
-    fn with_mock_interface<F, R>(f: F) -> R
-    where
-        F: FnOnce(&mut VMLogic) -> Result<R, VMLogicError>,
-    {
-        crate::mock::with_mocked_blockchain(|b| f(&mut b.logic.borrow_mut()).unwrap())
+impl BlockchainInterface for MockedBlockchain {
+    unsafe fn read_register(&mut self, register_id: u64, ptr: u64) {
+        self.logic.get_mut().read_register(register_id, ptr).unwrap()
+    }
+    unsafe fn register_len(&mut self, register_id: u64) -> u64 {
+        self.logic.get_mut().register_len(register_id).unwrap()
+    }
+    unsafe fn write_register(&mut self, register_id: u64, data_len: u64, data_ptr: u64) {
+        self.logic.get_mut().write_register(register_id, data_len, data_ptr).unwrap()
+    }
+    unsafe fn current_account_id(&mut self, register_id: u64) {
+        self.logic.get_mut().current_account_id(register_id).unwrap()
+    }
+    unsafe fn signer_account_id(&mut self, register_id: u64) {
+        self.logic.get_mut().signer_account_id(register_id).unwrap()
+    }
+    unsafe fn signer_account_pk(&mut self, register_id: u64) {
+        self.logic.get_mut().signer_account_pk(register_id).unwrap()
+    }
+    unsafe fn predecessor_account_id(&mut self, register_id: u64) {
+        self.logic.get_mut().predecessor_account_id(register_id).unwrap()
+    }
+    unsafe fn input(&mut self, register_id: u64) {
+        self.logic.get_mut().input(register_id).unwrap()
+    }
+    unsafe fn block_index(&mut self) -> u64 {
+        self.logic.get_mut().block_index().unwrap()
+    }
+    unsafe fn block_timestamp(&mut self) -> u64 {
+        self.logic.get_mut().block_timestamp().unwrap()
     }
+    unsafe fn epoch_height(&mut self) -> u64 {
+        self.logic.get_mut().epoch_height().unwrap()
+    }
+    unsafe fn storage_usage(&mut self) -> u64 {
+        self.logic.get_mut().storage_usage().unwrap()
+    }
+    unsafe fn account_balance(&mut self, balance_ptr: u64) {
+        self.logic.get_mut().account_balance(balance_ptr).unwrap()
+    }
+    unsafe fn account_locked_balance(&mut self, balance_ptr: u64) {
+        self.logic.get_mut().account_locked_balance(balance_ptr).unwrap()
+    }
+    unsafe fn attached_deposit(&mut self, balance_ptr: u64) {
+        self.logic.get_mut().attached_deposit(balance_ptr).unwrap()
+    }
+    unsafe fn prepaid_gas(&mut self) -> u64 {
+        self.logic.get_mut().prepaid_gas().unwrap()
+    }
+    unsafe fn used_gas(&mut self) -> u64 {
+        self.logic.get_mut().used_gas().unwrap()
+    }
+    unsafe fn random_seed(&mut self, register_id: u64) {
+        self.logic.get_mut().random_seed(register_id).unwrap()
+    }
+    unsafe fn sha256(&mut self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.logic.get_mut().sha256(value_len, value_ptr, register_id).unwrap()
+    }
+    unsafe fn keccak256(&mut self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.logic.get_mut().keccak256(value_len, value_ptr, register_id).unwrap()
+    }
+    unsafe fn keccak512(&mut self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.logic.get_mut().keccak512(value_len, value_ptr, register_id).unwrap()
+    }
+    unsafe fn ripemd160(&mut self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.logic.get_mut().ripemd160(value_len, value_ptr, register_id).unwrap()
+    }
+    unsafe fn ecrecover(
+        &mut self,
+        hash_len: u64,
+        hash_ptr: u64,
+        sig_len: u64,
+        sig_ptr: u64,
+        v: u64,
+        malleability_flag: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.logic
+            .get_mut()
+            .ecrecover(hash_len, hash_ptr, sig_len, sig_ptr, v, malleability_flag, register_id)
+            .unwrap()
+    }
+    unsafe fn ed25519_verify(
+        &mut self,
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64 {
+        use near_crypto::{KeyType, PublicKey, Signature};
+
+        // `VMLogic` does not implement `ed25519_verify` yet, so the check is done here. Guest
+        // memory is the host memory in unit tests, the same as with `MockedMemory`.
+        let read = |ptr: u64, len: u64| std::slice::from_raw_parts(ptr as *const u8, len as usize);
+        let public_key: [u8; 32] = read(pub_key_ptr, pub_key_len)
+            .try_into()
+            .unwrap_or_else(|_| panic!("invalid ed25519 public key length: {}", pub_key_len));
+        if sig_len != 64 {
+            panic!("invalid ed25519 signature length: {}", sig_len);
+        }
+        match Signature::from_parts(KeyType::ED25519, read(sig_ptr, sig_len)) {
+            Ok(signature) => signature
+                .verify(read(msg_ptr, msg_len), &PublicKey::ED25519(public_key.into()))
+                as u64,
+            Err(_) => 0,
+        }
+    }
+    unsafe fn value_return(&mut self, value_len: u64, value_ptr: u64) {
+        self.logic.get_mut().value_return(value_len, value_ptr).unwrap()
+    }
+    unsafe fn panic(&mut self) -> ! {
+        self.logic.get_mut().panic().unwrap();
+        unreachable!()
+    }
+    unsafe fn panic_utf8(&mut self, len: u64, ptr: u64) -> ! {
+        self.logic.get_mut().panic_utf8(len, ptr).unwrap();
+        unreachable!()
+    }
+    unsafe fn log_utf8(&mut self, len: u64, ptr: u64) {
+        self.logic.get_mut().log_utf8(len, ptr).unwrap()
+    }
+    unsafe fn log_utf16(&mut self, len: u64, ptr: u64) {
+        self.logic.get_mut().log_utf16(len, ptr).unwrap()
+    }
+    unsafe fn promise_create(
+        &mut self,
+        account_id_len: u64,
+        account_id_ptr: u64,
+        function_name_len: u64,
+        function_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+    ) -> u64 {
+        self.logic
+            .get_mut()
+            .promise_create(
+                account_id_len,
+                account_id_ptr,
+                function_name_len,
+                function_name_ptr,
+                arguments_len,
+                arguments_ptr,
+                amount_ptr,
+                gas,
+            )
+            .unwrap()
+    }
+    unsafe fn promise_then(
+        &mut self,
+        promise_index: u64,
+        account_id_len: u64,
+        account_id_ptr: u64,
+        function_name_len: u64,
+        function_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+    ) -> u64 {
+        self.logic
+            .get_mut()
+            .promise_then(
+                promise_index,
+                account_id_len,
+                account_id_ptr,
+                function_name_len,
+                function_name_ptr,
+                arguments_len,
+                arguments_ptr,
+                amount_ptr,
+                gas,
+            )
+            .unwrap()
+    }
+    unsafe fn promise_and(&mut self, promise_idx_ptr: u64, promise_idx_count: u64) -> u64 {
+        self.logic.get_mut().promise_and(promise_idx_ptr, promise_idx_count).unwrap()
+    }
+    unsafe fn promise_batch_create(&mut self, account_id_len: u64, account_id_ptr: u64) -> u64 {
+        self.logic.get_mut().promise_batch_create(account_id_len, account_id_ptr).unwrap()
+    }
+    unsafe fn promise_batch_then(
+        &mut self,
+        promise_index: u64,
+        account_id_len: u64,
+        account_id_ptr: u64,
+    ) -> u64 {
+        self.logic
+            .get_mut()
+            .promise_batch_then(promise_index, account_id_len, account_id_ptr)
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_create_account(&mut self, promise_index: u64) {
+        self.logic.get_mut().promise_batch_action_create_account(promise_index).unwrap()
+    }
+    unsafe fn promise_batch_action_deploy_contract(
+        &mut self,
+        promise_index: u64,
+        code_len: u64,
+        code_ptr: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_deploy_contract(promise_index, code_len, code_ptr)
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_function_call(
+        &mut self,
+        promise_index: u64,
+        function_name_len: u64,
+        function_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_function_call(
+                promise_index,
+                function_name_len,
+                function_name_ptr,
+                arguments_len,
+                arguments_ptr,
+                amount_ptr,
+                gas,
+            )
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_function_call_weight(
+        &mut self,
+        promise_index: u64,
+        function_name_len: u64,
+        function_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+        weight: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_function_call_weight(
+                promise_index,
+                function_name_len,
+                function_name_ptr,
+                arguments_len,
+                arguments_ptr,
+                amount_ptr,
+                gas,
+                weight,
+            )
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_transfer(&mut self, promise_index: u64, amount_ptr: u64) {
+        self.logic.get_mut().promise_batch_action_transfer(promise_index, amount_ptr).unwrap()
+    }
+    unsafe fn promise_batch_action_stake(
+        &mut self,
+        promise_index: u64,
+        amount_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_stake(promise_index, amount_ptr, public_key_len, public_key_ptr)
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_add_key_with_full_access(
+        &mut self,
+        promise_index: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+        nonce: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_add_key_with_full_access(
+                promise_index,
+                public_key_len,
+                public_key_ptr,
+                nonce,
+            )
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_add_key_with_function_call(
+        &mut self,
+        promise_index: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+        nonce: u64,
+        allowance_ptr: u64,
+        receiver_id_len: u64,
+        receiver_id_ptr: u64,
+        function_names_len: u64,
+        function_names_ptr: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_add_key_with_function_call(
+                promise_index,
+                public_key_len,
+                public_key_ptr,
+                nonce,
+                allowance_ptr,
+                receiver_id_len,
+                receiver_id_ptr,
+                function_names_len,
+                function_names_ptr,
+            )
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_delete_key(
+        &mut self,
+        promise_index: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_delete_key(promise_index, public_key_len, public_key_ptr)
+            .unwrap()
+    }
+    unsafe fn promise_batch_action_delete_account(
+        &mut self,
+        promise_index: u64,
+        beneficiary_id_len: u64,
+        beneficiary_id_ptr: u64,
+    ) {
+        self.logic
+            .get_mut()
+            .promise_batch_action_delete_account(
+                promise_index,
+                beneficiary_id_len,
+                beneficiary_id_ptr,
+            )
+            .unwrap()
+    }
+    unsafe fn promise_results_count(&mut self) -> u64 {
+        self.logic.get_mut().promise_results_count().unwrap()
+    }
+    unsafe fn promise_result(&mut self, result_idx: u64, register_id: u64) -> u64 {
+        self.logic.get_mut().promise_result(result_idx, register_id).unwrap()
+    }
+    unsafe fn promise_return(&mut self, promise_id: u64) {
+        self.logic.get_mut().promise_return(promise_id).unwrap()
+    }
+    unsafe fn storage_write(
+        &mut self,
+        key_len: u64,
+        key_ptr: u64,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.logic
+            .get_mut()
+            .storage_write(key_len, key_ptr, value_len, value_ptr, register_id)
+            .unwrap()
+    }
+    unsafe fn storage_read(&mut self, key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
+        self.logic.get_mut().storage_read(key_len, key_ptr, register_id).unwrap()
+    }
+    unsafe fn storage_remove(&mut self, key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
+        self.logic.get_mut().storage_remove(key_len, key_ptr, register_id).unwrap()
+    }
+    unsafe fn storage_has_key(&mut self, key_len: u64, key_ptr: u64) -> u64 {
+        self.logic.get_mut().storage_has_key(key_len, key_ptr).unwrap()
+    }
+    unsafe fn validator_stake(&mut self, account_id_len: u64, account_id_ptr: u64, stake_ptr: u64) {
+        self.logic.get_mut().validator_stake(account_id_len, account_id_ptr, stake_ptr).unwrap()
+    }
+    unsafe fn validator_total_stake(&mut self, stake_ptr: u64) {
+        self.logic.get_mut().validator_total_stake(stake_ptr).unwrap()
+    }
+    unsafe fn alt_bn128_g1_multiexp(&mut self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.logic.get_mut().alt_bn128_g1_multiexp(value_len, value_ptr, register_id).unwrap()
+    }
+    unsafe fn alt_bn128_g1_sum(&mut self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.logic.get_mut().alt_bn128_g1_sum(value_len, value_ptr, register_id).unwrap()
+    }
+    unsafe fn alt_bn128_pairing_check(&mut self, value_len: u64, value_ptr: u64) -> u64 {
+        self.logic.get_mut().alt_bn128_pairing_check(value_len, value_ptr).unwrap()
+    }
+    unsafe fn bls12381_p1_sum(&mut self, value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::p1_sum)
+    }
+    unsafe fn bls12381_p2_sum(&mut self, value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::p2_sum)
+    }
+    unsafe fn bls12381_g1_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::g1_multiexp)
+    }
+    unsafe fn bls12381_g2_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::g2_multiexp)
+    }
+    unsafe fn bls12381_map_fp_to_g1(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::map_fp_to_g1)
+    }
+    unsafe fn bls12381_map_fp2_to_g2(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::map_fp2_to_g2)
+    }
+    unsafe fn bls12381_pairing_check(&mut self, value_len: u64, value_ptr: u64) -> u64 {
+        let input = std::slice::from_raw_parts(value_ptr as *const u8, value_len as _);
+        match bls12381::pairing_check(input) {
+            Some(true) => 0,
+            Some(false) => 2,
+            None => 1,
+        }
+    }
+    unsafe fn bls12381_p1_decompress(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::p1_decompress)
+    }
+    unsafe fn bls12381_p2_decompress(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.bls12381_op(value_len, value_ptr, register_id, bls12381::p2_decompress)
+    }
+
+    fn as_mocked_blockchain(&mut self) -> Option<&mut MockedBlockchain> {
+        Some(self)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod mock_chain {
+    use crate::mock::with_blockchain_interface;
 
     #[no_mangle]
     extern "C" fn read_register(register_id: u64, ptr: u64) {
-        with_mock_interface(|b| b.read_register(register_id, ptr))
+        with_blockchain_interface(|b| unsafe { b.read_register(register_id, ptr) })
     }
     #[no_mangle]
     extern "C" fn register_len(register_id: u64) -> u64 {
-        with_mock_interface(|b| b.register_len(register_id))
+        with_blockchain_interface(|b| unsafe { b.register_len(register_id) })
+    }
+    #[no_mangle]
+    extern "C" fn write_register(register_id: u64, data_len: u64, data_ptr: u64) {
+        with_blockchain_interface(|b| unsafe { b.write_register(register_id, data_len, data_ptr) })
     }
     #[no_mangle]
     extern "C" fn current_account_id(register_id: u64) {
-        with_mock_interface(|b| b.current_account_id(register_id))
+        with_blockchain_interface(|b| unsafe { b.current_account_id(register_id) })
     }
     #[no_mangle]
     extern "C" fn signer_account_id(register_id: u64) {
-        with_mock_interface(|b| b.signer_account_id(register_id))
+        with_blockchain_interface(|b| unsafe { b.signer_account_id(register_id) })
     }
     #[no_mangle]
     extern "C" fn signer_account_pk(register_id: u64) {
-        with_mock_interface(|b| b.signer_account_pk(register_id))
+        with_blockchain_interface(|b| unsafe { b.signer_account_pk(register_id) })
     }
     #[no_mangle]
     extern "C" fn predecessor_account_id(register_id: u64) {
-        with_mock_interface(|b| b.predecessor_account_id(register_id))
+        with_blockchain_interface(|b| unsafe { b.predecessor_account_id(register_id) })
     }
     #[no_mangle]
     extern "C" fn input(register_id: u64) {
-        with_mock_interface(|b| b.input(register_id))
+        with_blockchain_interface(|b| unsafe { b.input(register_id) })
     }
     #[no_mangle]
     extern "C" fn block_index() -> u64 {
-        with_mock_interface(|b| b.block_index())
+        with_blockchain_interface(|b| unsafe { b.block_index() })
     }
     #[no_mangle]
     extern "C" fn block_timestamp() -> u64 {
-        with_mock_interface(|b| b.block_timestamp())
+        with_blockchain_interface(|b| unsafe { b.block_timestamp() })
     }
     #[no_mangle]
     extern "C" fn epoch_height() -> u64 {
-        with_mock_interface(|b| b.epoch_height())
+        with_blockchain_interface(|b| unsafe { b.epoch_height() })
     }
     #[no_mangle]
     extern "C" fn storage_usage() -> u64 {
-        with_mock_interface(|b| b.storage_usage())
+        with_blockchain_interface(|b| unsafe { b.storage_usage() })
     }
     #[no_mangle]
     extern "C" fn account_balance(balance_ptr: u64) {
-        with_mock_interface(|b| b.account_balance(balance_ptr))
+        with_blockchain_interface(|b| unsafe { b.account_balance(balance_ptr) })
     }
     #[no_mangle]
     extern "C" fn account_locked_balance(balance_ptr: u64) {
-        with_mock_interface(|b| b.account_locked_balance(balance_ptr))
+        with_blockchain_interface(|b| unsafe { b.account_locked_balance(balance_ptr) })
     }
     #[no_mangle]
     extern "C" fn attached_deposit(balance_ptr: u64) {
-        with_mock_interface(|b| b.attached_deposit(balance_ptr))
+        with_blockchain_interface(|b| unsafe { b.attached_deposit(balance_ptr) })
     }
     #[no_mangle]
     extern "C" fn prepaid_gas() -> u64 {
-        with_mock_interface(|b| b.prepaid_gas())
+        with_blockchain_interface(|b| unsafe { b.prepaid_gas() })
     }
     #[no_mangle]
     extern "C" fn used_gas() -> u64 {
-        with_mock_interface(|b| b.used_gas())
+        with_blockchain_interface(|b| unsafe { b.used_gas() })
     }
     #[no_mangle]
     extern "C" fn random_seed(register_id: u64) {
-        with_mock_interface(|b| b.random_seed(register_id))
+        with_blockchain_interface(|b| unsafe { b.random_seed(register_id) })
     }
     #[no_mangle]
     extern "C" fn sha256(value_len: u64, value_ptr: u64, register_id: u64) {
-        with_mock_interface(|b| b.sha256(value_len, value_ptr, register_id))
+        with_blockchain_interface(|b| unsafe { b.sha256(value_len, value_ptr, register_id) })
     }
     #[no_mangle]
     extern "C" fn keccak256(value_len: u64, value_ptr: u64, register_id: u64) {
-        with_mock_interface(|b| b.keccak256(value_len, value_ptr, register_id))
+        with_blockchain_interface(|b| unsafe { b.keccak256(value_len, value_ptr, register_id) })
     }
     #[no_mangle]
     extern "C" fn keccak512(value_len: u64, value_ptr: u64, register_id: u64) {
-        with_mock_interface(|b| b.keccak512(value_len, value_ptr, register_id))
+        with_blockchain_interface(|b| unsafe { b.keccak512(value_len, value_ptr, register_id) })
     }
     #[no_mangle]
     extern "C" fn ripemd160(value_len: u64, value_ptr: u64, register_id: u64) {
-        with_mock_interface(|b| b.ripemd160(value_len, value_ptr, register_id))
+        with_blockchain_interface(|b| unsafe { b.ripemd160(value_len, value_ptr, register_id) })
     }
     #[no_mangle]
     extern "C" fn ecrecover(
@@ -286,31 +760,42 @@ mod mock_chain {
         malleability_flag: u64,
         register_id: u64,
     ) -> u64 {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.ecrecover(hash_len, hash_ptr, sig_len, sig_ptr, v, malleability_flag, register_id)
         })
     }
     #[no_mangle]
+    extern "C" fn ed25519_verify(
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.ed25519_verify(sig_len, sig_ptr, msg_len, msg_ptr, pub_key_len, pub_key_ptr)
+        })
+    }
+    #[no_mangle]
     extern "C" fn value_return(value_len: u64, value_ptr: u64) {
-        with_mock_interface(|b| b.value_return(value_len, value_ptr))
+        with_blockchain_interface(|b| unsafe { b.value_return(value_len, value_ptr) })
     }
     #[no_mangle]
     extern "C" fn panic() -> ! {
-        with_mock_interface(|b| b.panic());
-        unreachable!()
+        with_blockchain_interface(|b| unsafe { b.panic() })
     }
     #[no_mangle]
     extern "C" fn panic_utf8(len: u64, ptr: u64) -> ! {
-        with_mock_interface(|b| b.panic_utf8(len, ptr));
-        unreachable!()
+        with_blockchain_interface(|b| unsafe { b.panic_utf8(len, ptr) })
     }
     #[no_mangle]
     extern "C" fn log_utf8(len: u64, ptr: u64) {
-        with_mock_interface(|b| b.log_utf8(len, ptr))
+        with_blockchain_interface(|b| unsafe { b.log_utf8(len, ptr) })
     }
     #[no_mangle]
     extern "C" fn log_utf16(len: u64, ptr: u64) {
-        with_mock_interface(|b| b.log_utf16(len, ptr))
+        with_blockchain_interface(|b| unsafe { b.log_utf16(len, ptr) })
     }
     #[no_mangle]
     extern "C" fn promise_create(
@@ -323,7 +808,7 @@ mod mock_chain {
         amount_ptr: u64,
         gas: u64,
     ) -> u64 {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_create(
                 account_id_len,
                 account_id_ptr,
@@ -348,7 +833,7 @@ mod mock_chain {
         amount_ptr: u64,
         gas: u64,
     ) -> u64 {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_then(
                 promise_index,
                 account_id_len,
@@ -364,11 +849,13 @@ mod mock_chain {
     }
     #[no_mangle]
     extern "C" fn promise_and(promise_idx_ptr: u64, promise_idx_count: u64) -> u64 {
-        with_mock_interface(|b| b.promise_and(promise_idx_ptr, promise_idx_count))
+        with_blockchain_interface(|b| unsafe { b.promise_and(promise_idx_ptr, promise_idx_count) })
     }
     #[no_mangle]
     extern "C" fn promise_batch_create(account_id_len: u64, account_id_ptr: u64) -> u64 {
-        with_mock_interface(|b| b.promise_batch_create(account_id_len, account_id_ptr))
+        with_blockchain_interface(|b| unsafe {
+            b.promise_batch_create(account_id_len, account_id_ptr)
+        })
     }
     #[no_mangle]
     extern "C" fn promise_batch_then(
@@ -376,11 +863,15 @@ mod mock_chain {
         account_id_len: u64,
         account_id_ptr: u64,
     ) -> u64 {
-        with_mock_interface(|b| b.promise_batch_then(promise_index, account_id_len, account_id_ptr))
+        with_blockchain_interface(|b| unsafe {
+            b.promise_batch_then(promise_index, account_id_len, account_id_ptr)
+        })
     }
     #[no_mangle]
     extern "C" fn promise_batch_action_create_account(promise_index: u64) {
-        with_mock_interface(|b| b.promise_batch_action_create_account(promise_index))
+        with_blockchain_interface(|b| unsafe {
+            b.promise_batch_action_create_account(promise_index)
+        })
     }
     #[no_mangle]
     extern "C" fn promise_batch_action_deploy_contract(
@@ -388,7 +879,7 @@ mod mock_chain {
         code_len: u64,
         code_ptr: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_deploy_contract(promise_index, code_len, code_ptr)
         })
     }
@@ -402,7 +893,7 @@ mod mock_chain {
         amount_ptr: u64,
         gas: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_function_call(
                 promise_index,
                 function_name_len,
@@ -414,7 +905,6 @@ mod mock_chain {
             )
         })
     }
-
     #[no_mangle]
     extern "C" fn promise_batch_action_function_call_weight(
         promise_index: u64,
@@ -426,7 +916,7 @@ mod mock_chain {
         gas: u64,
         weight: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_function_call_weight(
                 promise_index,
                 function_name_len,
@@ -439,10 +929,11 @@ mod mock_chain {
             )
         })
     }
-
     #[no_mangle]
     extern "C" fn promise_batch_action_transfer(promise_index: u64, amount_ptr: u64) {
-        with_mock_interface(|b| b.promise_batch_action_transfer(promise_index, amount_ptr))
+        with_blockchain_interface(|b| unsafe {
+            b.promise_batch_action_transfer(promise_index, amount_ptr)
+        })
     }
     #[no_mangle]
     extern "C" fn promise_batch_action_stake(
@@ -451,7 +942,7 @@ mod mock_chain {
         public_key_len: u64,
         public_key_ptr: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_stake(promise_index, amount_ptr, public_key_len, public_key_ptr)
         })
     }
@@ -462,7 +953,7 @@ mod mock_chain {
         public_key_ptr: u64,
         nonce: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_add_key_with_full_access(
                 promise_index,
                 public_key_len,
@@ -483,7 +974,7 @@ mod mock_chain {
         function_names_len: u64,
         function_names_ptr: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_add_key_with_function_call(
                 promise_index,
                 public_key_len,
@@ -503,7 +994,7 @@ mod mock_chain {
         public_key_len: u64,
         public_key_ptr: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_delete_key(promise_index, public_key_len, public_key_ptr)
         })
     }
@@ -513,7 +1004,7 @@ mod mock_chain {
         beneficiary_id_len: u64,
         beneficiary_id_ptr: u64,
     ) {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.promise_batch_action_delete_account(
                 promise_index,
                 beneficiary_id_len,
@@ -523,15 +1014,15 @@ mod mock_chain {
     }
     #[no_mangle]
     extern "C" fn promise_results_count() -> u64 {
-        with_mock_interface(|b| b.promise_results_count())
+        with_blockchain_interface(|b| unsafe { b.promise_results_count() })
     }
     #[no_mangle]
     extern "C" fn promise_result(result_idx: u64, register_id: u64) -> u64 {
-        with_mock_interface(|b| b.promise_result(result_idx, register_id))
+        with_blockchain_interface(|b| unsafe { b.promise_result(result_idx, register_id) })
     }
     #[no_mangle]
     extern "C" fn promise_return(promise_id: u64) {
-        with_mock_interface(|b| b.promise_return(promise_id))
+        with_blockchain_interface(|b| unsafe { b.promise_return(promise_id) })
     }
     #[no_mangle]
     extern "C" fn storage_write(
@@ -541,40 +1032,98 @@ mod mock_chain {
         value_ptr: u64,
         register_id: u64,
     ) -> u64 {
-        with_mock_interface(|b| {
+        with_blockchain_interface(|b| unsafe {
             b.storage_write(key_len, key_ptr, value_len, value_ptr, register_id)
         })
     }
     #[no_mangle]
     extern "C" fn storage_read(key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
-        with_mock_interface(|b| b.storage_read(key_len, key_ptr, register_id))
+        with_blockchain_interface(|b| unsafe { b.storage_read(key_len, key_ptr, register_id) })
     }
     #[no_mangle]
     extern "C" fn storage_remove(key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
-        with_mock_interface(|b| b.storage_remove(key_len, key_ptr, register_id))
+        with_blockchain_interface(|b| unsafe { b.storage_remove(key_len, key_ptr, register_id) })
     }
     #[no_mangle]
     extern "C" fn storage_has_key(key_len: u64, key_ptr: u64) -> u64 {
-        with_mock_interface(|b| b.storage_has_key(key_len, key_ptr))
+        with_blockchain_interface(|b| unsafe { b.storage_has_key(key_len, key_ptr) })
     }
     #[no_mangle]
     extern "C" fn validator_stake(account_id_len: u64, account_id_ptr: u64, stake_ptr: u64) {
-        with_mock_interface(|b| b.validator_stake(account_id_len, account_id_ptr, stake_ptr))
+        with_blockchain_interface(|b| unsafe {
+            b.validator_stake(account_id_len, account_id_ptr, stake_ptr)
+        })
     }
     #[no_mangle]
     extern "C" fn validator_total_stake(stake_ptr: u64) {
-        with_mock_interface(|b| b.validator_total_stake(stake_ptr))
+        with_blockchain_interface(|b| unsafe { b.validator_total_stake(stake_ptr) })
     }
     #[no_mangle]
     extern "C" fn alt_bn128_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64) {
-        with_mock_interface(|b| b.alt_bn128_g1_multiexp(value_len, value_ptr, register_id))
+        with_blockchain_interface(|b| unsafe {
+            b.alt_bn128_g1_multiexp(value_len, value_ptr, register_id)
+        })
     }
     #[no_mangle]
     extern "C" fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64) {
-        with_mock_interface(|b| b.alt_bn128_g1_sum(value_len, value_ptr, register_id))
+        with_blockchain_interface(|b| unsafe {
+            b.alt_bn128_g1_sum(value_len, value_ptr, register_id)
+        })
     }
     #[no_mangle]
     extern "C" fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64 {
-        with_mock_interface(|b| b.alt_bn128_pairing_check(value_len, value_ptr))
+        with_blockchain_interface(|b| unsafe { b.alt_bn128_pairing_check(value_len, value_ptr) })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_p1_sum(value_len, value_ptr, register_id)
+        })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_p2_sum(value_len, value_ptr, register_id)
+        })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_g1_multiexp(value_len, value_ptr, register_id)
+        })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_g2_multiexp(value_len, value_ptr, register_id)
+        })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_map_fp_to_g1(value_len, value_ptr, register_id)
+        })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_map_fp2_to_g2(value_len, value_ptr, register_id)
+        })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe { b.bls12381_pairing_check(value_len, value_ptr) })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_p1_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_p1_decompress(value_len, value_ptr, register_id)
+        })
+    }
+    #[no_mangle]
+    extern "C" fn bls12381_p2_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64 {
+        with_blockchain_interface(|b| unsafe {
+            b.bls12381_p2_decompress(value_len, value_ptr, register_id)
+        })
     }
 }
@@ -1,28 +1,61 @@
+mod blockchain_interface;
+mod bls12381;
 mod external;
 mod mocked_blockchain;
 mod receipt;
 
+pub use self::blockchain_interface::BlockchainInterface;
 pub(crate) use self::external::SdkExternal;
 pub use self::mocked_blockchain::MockedBlockchain;
 pub use self::receipt::{Receipt, VmAction};
 use crate::AccountId;
 use core::cell::RefCell;
 use near_primitives_core::account::id::ParseAccountError;
+use std::collections::HashMap;
 
 thread_local! {
     /// Low-level blockchain interface wrapped by the environment. Prefer using `env::*` and
     /// `testing_env` for interacting with the real and fake blockchains.
-    static BLOCKCHAIN_INTERFACE: RefCell<MockedBlockchain>
-         = RefCell::new(MockedBlockchain::default());
+    static BLOCKCHAIN_INTERFACE: RefCell<Box<dyn BlockchainInterface>>
+         = RefCell::new(Box::new(MockedBlockchain::default()));
+}
+
+/// Perform function on a mutable reference to the current [`BlockchainInterface`]. This can only
+/// be used inside tests.
+pub fn with_blockchain_interface<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut dyn BlockchainInterface) -> R,
+{
+    BLOCKCHAIN_INTERFACE.with(|b| f(b.borrow_mut().as_mut()))
+}
+
+/// Replaces the current [`BlockchainInterface`], returning the previous one.
+pub(crate) fn replace_blockchain_interface(
+    blockchain_interface: Box<dyn BlockchainInterface>,
+) -> Box<dyn BlockchainInterface> {
+    BLOCKCHAIN_INTERFACE.with(|b| b.replace(blockchain_interface))
 }
 
 /// Perform function on a mutable reference to the [`MockedBlockchain`]. This can only be used
 /// inside tests.
+///
+/// # Panics
+///
+/// Panics if the current blockchain interface is not a [`MockedBlockchain`].
 pub fn with_mocked_blockchain<F, R>(f: F) -> R
 where
     F: FnOnce(&mut MockedBlockchain) -> R,
 {
-    BLOCKCHAIN_INTERFACE.with(|b| f(&mut b.borrow_mut()))
+    with_blockchain_interface(|b| {
+        f(b.as_mocked_blockchain().expect("The blockchain interface is not a `MockedBlockchain`"))
+    })
+}
+
+/// Takes the storage of the current [`MockedBlockchain`], which `testing_env!` moves to the
+/// blockchain it sets up, or returns `None` if the current blockchain interface is not a
+/// [`MockedBlockchain`].
+pub fn take_storage() -> Option<HashMap<Vec<u8>, Vec<u8>>> {
+    with_blockchain_interface(|b| b.as_mocked_blockchain().map(MockedBlockchain::take_storage))
 }
 
 impl From<near_vm_logic::types::AccountId> for AccountId {
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use super::U64;
+
+/// Page of items returned from a view method, limited to a byte budget.
+///
+/// Nodes limit the size of view call results, and responses above the limit are cut off, which
+/// leaves clients with invalid JSON. [`BoundedResponse::new`] instead includes only as many items
+/// as fit into the JSON budget, and sets `truncated` and the `next_cursor` to continue from when
+/// not all of them fit.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::json_types::{BoundedResponse, U64};
+///
+/// fn list_names(names: &[String], from_index: Option<U64>) -> BoundedResponse<&String> {
+///     let start = from_index.map_or(0, u64::from);
+///     BoundedResponse::new(names.iter().skip(start as usize), start, 100)
+/// }
+///
+/// let names: Vec<String> = (0..20).map(|i| format!("name-{}", i)).collect();
+/// let page = list_names(&names, None);
+/// assert!(page.truncated);
+/// assert!(near_sdk::serde_json::to_vec(&page).unwrap().len() <= 100);
+///
+/// let rest = list_names(&names, page.next_cursor);
+/// assert_eq!(rest.items[0], &names[page.items.len()]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+#[serde(crate = "crate::serde")]
+pub struct BoundedResponse<T> {
+    pub items: Vec<T>,
+    /// Whether items were left out because they did not fit into the budget.
+    pub truncated: bool,
+    /// Index of the first item that was left out, to request the next page from.
+    pub next_cursor: Option<U64>,
+}
+
+impl<T: Serialize> BoundedResponse<T> {
+    /// Collects items until the JSON serialization of the response would exceed `max_bytes`.
+    /// `start` is the index of the first item, which is used to compute the `next_cursor`.
+    ///
+    /// Items are consumed lazily, so at most one item more than the ones returned is read.
+    /// If the first item alone does not fit into the budget, the response is empty and the cursor
+    /// points at that item.
+    pub fn new<I>(items: I, start: u64, max_bytes: usize) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        // The largest the response can be without items, with the longest possible cursor.
+        let envelope = serde_json::to_vec(&BoundedResponse::<()> {
+            items: Vec::new(),
+            truncated: true,
+            next_cursor: Some(U64(u64::MAX)),
+        })
+        .map_or(0, |json| json.len());
+        let mut used = envelope;
+        let mut page = Vec::new();
+        for item in items {
+            let len = serde_json::to_vec(&item)
+                .unwrap_or_else(|_| crate::env::panic_str("Failed to serialize the item"))
+                .len();
+            // Items after the first one are preceded by a comma.
+            let len = if page.is_empty() { len } else { len + 1 };
+            if used + len > max_bytes {
+                return Self {
+                    next_cursor: Some(U64(start + page.len() as u64)),
+                    items: page,
+                    truncated: true,
+                };
+            }
+            used += len;
+            page.push(item);
+        }
+        Self { items: page, truncated: false, next_cursor: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_budget() {
+        let items: Vec<u32> = (0..1000).collect();
+        for max_bytes in [60, 100, 1000] {
+            let page = BoundedResponse::new(items.iter(), 0, max_bytes);
+            assert!(page.truncated);
+            assert!(serde_json::to_vec(&page).unwrap().len() <= max_bytes);
+            assert_eq!(page.next_cursor, Some(U64(page.items.len() as u64)));
+        }
+
+        let page = BoundedResponse::new(items[990..].iter(), 990, 1000);
+        assert_eq!(page.items.len(), 10);
+        assert!(!page.truncated);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(
+            serde_json::to_string(&BoundedResponse::new([1, 2], 0, 100)).unwrap(),
+            r#"{"items":[1,2],"truncated":false,"next_cursor":null}"#
+        );
+    }
+
+    #[test]
+    fn pages_cover_all_items() {
+        let items: Vec<String> = (0..100).map(|i| "x".repeat(i % 7)).collect();
+        let mut collected = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let page = BoundedResponse::new(items.iter().skip(cursor as usize), cursor, 120);
+            collected.extend(page.items.iter().map(|item| item.to_string()));
+            match page.next_cursor {
+                Some(next) => cursor = next.0,
+                None => break,
+            }
+        }
+        assert_eq!(collected, items);
+    }
+
+    #[test]
+    fn item_over_budget() {
+        let page = BoundedResponse::new(["x".repeat(100)], 5, 80);
+        assert!(page.items.is_empty());
+        assert!(page.truncated);
+        assert_eq!(page.next_cursor, Some(U64(5)));
+    }
+}
@@ -6,7 +6,8 @@
 extern crate quickcheck;
 
 pub use near_sdk_macros::{
-    ext_contract, metadata, near_bindgen, BorshStorageKey, FunctionError, PanicOnDefault,
+    ext_contract, metadata, migrate, near, near_bindgen, storage_keys, BorshStorageKey,
+    ContractError, FunctionError, NearEvent, PanicOnDefault, StorageNamespace,
 };
 
 pub mod store;
@@ -19,7 +20,7 @@ pub use environment::env;
 pub use near_sys as sys;
 
 mod promise;
-pub use promise::{Promise, PromiseOrValue};
+pub use promise::{AccessKeyBatch, AccessKeyBatchError, Promise, PromiseAction, PromiseOrValue};
 
 // Private types just used within macro generation, not stable to be used.
 #[doc(hidden)]
@@ -28,6 +29,15 @@ pub mod __private;
 
 pub mod json_types;
 
+pub mod acl;
+
+#[cfg(feature = "abi")]
+pub mod abi;
+
+pub mod rand;
+
+pub mod time;
+
 mod types;
 pub use crate::types::*;
 
@@ -42,6 +52,7 @@ pub use near_vm_logic::VMConfig;
 pub use test_utils::context::VMContext;
 
 pub mod utils;
+pub use crate::utils::namespace::{Namespace, Storage, StorageNamespace};
 pub use crate::utils::storage_key_impl::IntoStorageKey;
 pub use crate::utils::*;
 
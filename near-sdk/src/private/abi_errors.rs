@@ -0,0 +1,46 @@
+use schemars::schema::Schema;
+
+/// Errors a `#[handle_result]` method can fail with, as listed in its ABI.
+#[derive(Default)]
+pub struct AbiErrors {
+    /// Code and name of each error.
+    pub errors: Vec<(u32, &'static str)>,
+    /// Schema of the data of the errors, if they have any.
+    pub data: Option<Schema>,
+}
+
+impl AbiErrors {
+    /// Appends the errors to the documentation of the method.
+    pub fn document(self, doc: Option<String>) -> Option<String> {
+        if self.errors.is_empty() {
+            return doc;
+        }
+        let mut errors = String::from(" Errors:");
+        for (code, name) in &self.errors {
+            errors.push_str(&format!("\n - `E{}` `{}`", code, name));
+        }
+        if let Some(data) = &self.data {
+            let data = serde_json::to_string(data).unwrap_or_default();
+            errors.push_str(&format!("\n\n Error data: `{}`", data));
+        }
+        Some(match doc {
+            Some(doc) => format!("{}\n\n{}", doc, errors),
+            None => errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AbiErrors;
+
+    #[test]
+    fn document_errors() {
+        let errors = AbiErrors { errors: vec![(1, "NotFound"), (2, "Banned")], data: None };
+        assert_eq!(
+            errors.document(Some(" Gets the value.".to_string())).unwrap(),
+            " Gets the value.\n\n Errors:\n - `E1` `NotFound`\n - `E2` `Banned`"
+        );
+        assert_eq!(AbiErrors::default().document(None), None);
+    }
+}
@@ -9,8 +9,13 @@ pub use schemars;
 mod metadata;
 pub use metadata::{Metadata, MethodMetadata};
 
-use crate::IntoStorageKey;
-use borsh::BorshSerialize;
+#[cfg(feature = "abi")]
+mod abi_errors;
+#[cfg(feature = "abi")]
+pub use abi_errors::AbiErrors;
+
+use crate::{env, IntoStorageKey};
+use borsh::{BorshDeserialize, BorshSerialize};
 
 /// Converts a Borsh serializable object into a `Vec<u8>` that is used for a storage key.
 ///
@@ -41,3 +46,92 @@ where
         self.try_to_vec().unwrap()
     }
 }
+
+/// Constant prefixes of all storage keys of a type deriving
+/// [`BorshStorageKey`](crate::BorshStorageKey), used by [`storage_keys!`](crate::storage_keys) to
+/// check that the storage keys of a contract do not overlap.
+///
+/// Every variant of an enum is serialized starting with its index, so each variant has a prefix of
+/// one byte that all of its keys start with, regardless of its fields.
+pub trait StorageKeyPrefixes {
+    const PREFIXES: &'static [&'static [u8]];
+}
+
+/// Whether any of the prefixes `a` is a prefix of any of `b`, or the other way around, in which
+/// case the collections using them can share storage keys.
+pub const fn storage_keys_overlap(a: &[&[u8]], b: &[&[u8]]) -> bool {
+    let mut i = 0;
+    while i < a.len() {
+        let mut j = 0;
+        while j < b.len() {
+            if is_prefix_of_either(a[i], b[j]) {
+                return true;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    false
+}
+
+const fn is_prefix_of_either(a: &[u8], b: &[u8]) -> bool {
+    let len = if a.len() < b.len() { a.len() } else { b.len() };
+    let mut i = 0;
+    while i < len {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Loads the contract state, or the default value if no state exists, along with the raw bytes
+/// it was deserialized from. Used by `#[read_mostly]` methods.
+pub fn state_read_with_bytes<T>() -> (T, Option<Vec<u8>>)
+where
+    T: BorshDeserialize + Default,
+{
+    match env::storage_read(env::STATE_KEY) {
+        Some(data) => {
+            let state = T::try_from_slice(&data).expect("Cannot deserialize the contract state.");
+            (state, Some(data))
+        }
+        None => (T::default(), None),
+    }
+}
+
+/// Writes the contract state only if its serialized form differs from the bytes it was loaded
+/// from. Returns `true` if the state was written.
+pub fn state_write_if_changed<T>(state: &T, original: Option<&[u8]>) -> bool
+where
+    T: BorshSerialize,
+{
+    let data = state.try_to_vec().expect("Cannot serialize the contract state.");
+    if original == Some(data.as_slice()) {
+        return false;
+    }
+    env::storage_write(env::STATE_KEY, &data);
+    true
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_write_skipped_if_unchanged() {
+        let (state, original) = state_read_with_bytes::<u64>();
+        assert_eq!(state, 0);
+        assert!(original.is_none());
+        assert!(state_write_if_changed(&state, original.as_deref()));
+
+        let (mut state, original) = state_read_with_bytes::<u64>();
+        assert_eq!(original.as_deref(), Some(&0u64.to_le_bytes()[..]));
+        assert!(!state_write_if_changed(&state, original.as_deref()));
+
+        state += 1;
+        assert!(state_write_if_changed(&state, original.as_deref()));
+        assert_eq!(env::state_read::<u64>(), Some(1));
+    }
+}
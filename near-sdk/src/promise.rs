@@ -6,7 +6,33 @@ use std::rc::Rc;
 
 use crate::{AccountId, Balance, Gas, GasWeight, PromiseIndex, PublicKey};
 
-enum PromiseAction {
+type PromiseInterceptor = Rc<dyn Fn(&AccountId, &PromiseAction) -> Result<(), String>>;
+
+thread_local! {
+    static INTERCEPTOR: RefCell<Option<PromiseInterceptor>> = RefCell::new(None);
+}
+
+/// Runs the registered interceptor over the actions of a promise, panicking if any of them is
+/// vetoed.
+fn intercept(account_id: &AccountId, actions: &[PromiseAction]) {
+    // The interceptor is cloned out so that it is free to create promises itself.
+    let interceptor = match INTERCEPTOR.with(|i| i.borrow().clone()) {
+        Some(interceptor) => interceptor,
+        None => return,
+    };
+    for action in actions {
+        if let Err(reason) = interceptor(account_id, action) {
+            crate::env::panic_str(&reason);
+        }
+    }
+}
+
+/// An action scheduled on a [`Promise`], as observed by a [promise interceptor].
+///
+/// [promise interceptor]: Promise::set_interceptor
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PromiseAction {
     CreateAccount,
     DeployContract {
         code: Vec<u8>,
@@ -51,7 +77,7 @@ enum PromiseAction {
 }
 
 impl PromiseAction {
-    pub fn add(&self, promise_index: PromiseIndex) {
+    pub(crate) fn add(&self, promise_index: PromiseIndex) {
         use PromiseAction::*;
         match self {
             CreateAccount => crate::env::promise_batch_action_create_account(promise_index),
@@ -124,6 +150,7 @@ impl PromiseSingle {
         if let Some(res) = promise_lock.as_ref() {
             return *res;
         }
+        intercept(&self.account_id, &self.actions.borrow());
         let promise_index = if let Some(after) = self.after.borrow().as_ref() {
             crate::env::promise_batch_then(after.construct_recursively(), &self.account_id)
         } else {
@@ -225,6 +252,34 @@ enum PromiseSubtype {
 }
 
 impl Promise {
+    /// Registers a contract-wide hook which is called with the receiver and every action of each
+    /// promise right before the promise is scheduled, and replaces any previously registered one.
+    ///
+    /// Returning `Err` vetoes the action and panics with the given message, which makes it
+    /// possible to enforce policies such as transfer limits, or to observe scheduled actions for
+    /// audit logging and gas accounting.
+    ///
+    /// ```
+    /// # use near_sdk::{Promise, PromiseAction};
+    /// Promise::set_interceptor(|_receiver_id, action| match action {
+    ///     PromiseAction::Transfer { amount } if *amount > 10u128.pow(24) => {
+    ///         Err("Transfers above 1 NEAR are not allowed".to_string())
+    ///     }
+    ///     _ => Ok(()),
+    /// });
+    /// ```
+    pub fn set_interceptor<F>(interceptor: F)
+    where
+        F: Fn(&AccountId, &PromiseAction) -> Result<(), String> + 'static,
+    {
+        INTERCEPTOR.with(|i| *i.borrow_mut() = Some(Rc::new(interceptor)));
+    }
+
+    /// Removes the hook registered with [`Promise::set_interceptor`].
+    pub fn clear_interceptor() {
+        INTERCEPTOR.with(|i| *i.borrow_mut() = None);
+    }
+
     /// Create a promise that acts on the given account.
     pub fn new(account_id: AccountId) -> Self {
         Self {
@@ -340,6 +395,24 @@ impl Promise {
         })
     }
 
+    /// Add all function call access keys of the batch to the given account.
+    ///
+    /// Panics if the batch is not valid, see [`AccessKeyBatch::validate`].
+    pub fn add_access_keys(mut self, batch: AccessKeyBatch) -> Self {
+        if let Err(err) = batch.validate() {
+            crate::env::panic_str(&err.to_string());
+        }
+        for key in batch.keys {
+            self = self.add_access_key(
+                key.public_key,
+                key.allowance,
+                key.receiver_id,
+                key.method_names.join(","),
+            );
+        }
+        self
+    }
+
     /// Delete access key from the given account.
     pub fn delete_key(self, public_key: PublicKey) -> Self {
         self.add_action(PromiseAction::DeleteKey { public_key })
@@ -553,3 +626,245 @@ impl<T: schemars::JsonSchema> schemars::JsonSchema for PromiseOrValue<T> {
         T::json_schema(gen)
     }
 }
+
+/// The longest method name a function call access key can be restricted to.
+const MAX_METHOD_NAME_LEN: usize = 256;
+/// The largest total size of the comma separated method names of a function call access key.
+const MAX_METHOD_NAMES_LEN: usize = 2000;
+
+struct FunctionCallKey {
+    public_key: PublicKey,
+    allowance: Balance,
+    receiver_id: AccountId,
+    method_names: Vec<String>,
+}
+
+/// Builder of function call access keys that are added to an account in one batch, e.g. the
+/// session keys of an account that is being created. Use [`Promise::add_access_keys`] to add them.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::{AccessKeyBatch, Promise, PublicKey};
+///
+/// let game: PublicKey = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp".parse().unwrap();
+/// let market: PublicKey = "ed25519:H4sbE3Fvc5kjnHFqD5k6pPPeD2zsjFsbVGrhTDcwPfY9".parse().unwrap();
+/// let keys = AccessKeyBatch::new()
+///     .max_total_allowance(10u128.pow(24))
+///     .add_key(game, 10u128.pow(23), "game.near".parse().unwrap(), ["play", "claim"])
+///     .add_key(market, 10u128.pow(23), "market.near".parse().unwrap(), ["buy"]);
+///
+/// Promise::new("alice.near".parse().unwrap()).create_account().add_access_keys(keys);
+/// ```
+#[derive(Default)]
+pub struct AccessKeyBatch {
+    keys: Vec<FunctionCallKey>,
+    max_total_allowance: Option<Balance>,
+}
+
+impl AccessKeyBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the sum of the allowances of all keys in the batch. Keys with an allowance of `0`,
+    /// which is unlimited, are rejected when a limit is set.
+    pub fn max_total_allowance(mut self, limit: Balance) -> Self {
+        self.max_total_allowance = Some(limit);
+        self
+    }
+
+    /// Adds a key that can spend up to `allowance` on gas for calls of `method_names` on
+    /// `receiver_id`. An empty list of method names allows calling any method, and an allowance
+    /// of `0` is unlimited.
+    pub fn add_key<I, S>(
+        mut self,
+        public_key: PublicKey,
+        allowance: Balance,
+        receiver_id: AccountId,
+        method_names: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let method_names = method_names.into_iter().map(Into::into).collect();
+        self.keys.push(FunctionCallKey { public_key, allowance, receiver_id, method_names });
+        self
+    }
+
+    /// Returns the sum of the allowances of all keys in the batch.
+    pub fn total_allowance(&self) -> Balance {
+        self.keys.iter().fold(0, |total, key| total.saturating_add(key.allowance))
+    }
+
+    /// Checks that no public key is added twice, that the method names of every key are valid,
+    /// unique and within the protocol limits, and that the allowances are within the limit set by
+    /// [`max_total_allowance`](Self::max_total_allowance).
+    pub fn validate(&self) -> Result<(), AccessKeyBatchError> {
+        for (i, key) in self.keys.iter().enumerate() {
+            if self.keys[..i].iter().any(|other| other.public_key == key.public_key) {
+                return Err(AccessKeyBatchError::DuplicateKey(key.public_key.clone()));
+            }
+            for (j, name) in key.method_names.iter().enumerate() {
+                if name.is_empty() || name.len() > MAX_METHOD_NAME_LEN || name.contains(',') {
+                    return Err(AccessKeyBatchError::InvalidMethodName(name.clone()));
+                }
+                if key.method_names[..j].contains(name) {
+                    return Err(AccessKeyBatchError::DuplicateMethodName(name.clone()));
+                }
+            }
+            let names_len = key.method_names.iter().map(|name| name.len() + 1).sum::<usize>();
+            if names_len.saturating_sub(1) > MAX_METHOD_NAMES_LEN {
+                return Err(AccessKeyBatchError::MethodNamesTooLong(key.public_key.clone()));
+            }
+            if self.max_total_allowance.is_some() && key.allowance == 0 {
+                return Err(AccessKeyBatchError::UnlimitedAllowance(key.public_key.clone()));
+            }
+        }
+        match self.max_total_allowance {
+            Some(limit) if self.total_allowance() > limit => {
+                Err(AccessKeyBatchError::AllowanceLimitExceeded {
+                    total: self.total_allowance(),
+                    limit,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reason an [`AccessKeyBatch`] is not valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AccessKeyBatchError {
+    /// The public key is added more than once.
+    DuplicateKey(PublicKey),
+    /// The method name is empty, contains a comma or is too long.
+    InvalidMethodName(String),
+    /// The method name is listed more than once for the same key.
+    DuplicateMethodName(String),
+    /// The method names of the key exceed the protocol limit.
+    MethodNamesTooLong(PublicKey),
+    /// The key has an unlimited allowance while the total allowance is limited.
+    UnlimitedAllowance(PublicKey),
+    /// The sum of the allowances exceeds the limit.
+    AllowanceLimitExceeded { total: Balance, limit: Balance },
+}
+
+impl std::fmt::Display for AccessKeyBatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateKey(key) => write!(f, "access key {:?} is added more than once", key),
+            Self::InvalidMethodName(name) => write!(f, "invalid method name {:?}", name),
+            Self::DuplicateMethodName(name) => {
+                write!(f, "method name {:?} is listed more than once", name)
+            }
+            Self::MethodNamesTooLong(key) => {
+                write!(f, "method names of access key {:?} are too long", key)
+            }
+            Self::UnlimitedAllowance(key) => {
+                write!(f, "access key {:?} has an unlimited allowance", key)
+            }
+            Self::AllowanceLimitExceeded { total, limit } => {
+                write!(f, "total allowance {} exceeds the limit of {}", total, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AccessKeyBatchError {}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_utils::{accounts, get_created_receipts};
+
+    #[test]
+    fn interceptor_observes_actions() {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let seen_clone = Rc::clone(&seen);
+        Promise::set_interceptor(move |receiver_id, action| {
+            seen_clone.borrow_mut().push(format!("{} {:?}", receiver_id, action));
+            Ok(())
+        });
+        Promise::new(accounts(1)).create_account().transfer(10);
+        Promise::clear_interceptor();
+        Promise::new(accounts(2)).transfer(20);
+
+        assert_eq!(*seen.borrow(), vec!["bob CreateAccount", "bob Transfer { amount: 10 }"]);
+        assert_eq!(get_created_receipts().len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers are not allowed")]
+    fn interceptor_vetoes_actions() {
+        Promise::set_interceptor(|_, action| match action {
+            PromiseAction::Transfer { .. } => Err("Transfers are not allowed".to_string()),
+            _ => Ok(()),
+        });
+        Promise::new(accounts(1)).transfer(10);
+    }
+
+    fn key(seed: u8) -> PublicKey {
+        format!("ed25519:{}", bs58::encode([seed; 32]).into_string()).parse().unwrap()
+    }
+
+    #[test]
+    fn add_access_keys_in_batch() {
+        let batch = AccessKeyBatch::new()
+            .max_total_allowance(100)
+            .add_key(key(1), 60, accounts(2), ["play", "claim"])
+            .add_key(key(2), 40, accounts(3), Vec::<String>::new());
+        assert_eq!(batch.total_allowance(), 100);
+        Promise::new(accounts(1)).create_account().add_access_keys(batch);
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].actions.len(), 3);
+        match &receipts[0].actions[1] {
+            crate::mock::VmAction::AddKeyWithFunctionCall {
+                allowance,
+                receiver_id,
+                function_names,
+                ..
+            } => {
+                assert_eq!(*allowance, Some(60));
+                assert_eq!(*receiver_id, accounts(2));
+                assert_eq!(*function_names, vec!["play", "claim"]);
+            }
+            action => panic!("unexpected action {:?}", action),
+        }
+    }
+
+    #[test]
+    fn validate_access_key_batch() {
+        let valid = || AccessKeyBatch::new().add_key(key(1), 10, accounts(2), ["play"]);
+        assert_eq!(valid().validate(), Ok(()));
+        assert_eq!(
+            valid().add_key(key(1), 10, accounts(3), ["play"]).validate(),
+            Err(AccessKeyBatchError::DuplicateKey(key(1)))
+        );
+        assert_eq!(
+            valid().add_key(key(2), 10, accounts(2), ["a,b"]).validate(),
+            Err(AccessKeyBatchError::InvalidMethodName("a,b".to_string()))
+        );
+        assert_eq!(
+            valid().add_key(key(2), 10, accounts(2), ["a", "a"]).validate(),
+            Err(AccessKeyBatchError::DuplicateMethodName("a".to_string()))
+        );
+        let names: Vec<String> = (0..300).map(|i| format!("method_{}", i)).collect();
+        assert_eq!(
+            valid().add_key(key(2), 10, accounts(2), names).validate(),
+            Err(AccessKeyBatchError::MethodNamesTooLong(key(2)))
+        );
+        assert_eq!(
+            valid().max_total_allowance(100).add_key(key(2), 0, accounts(2), ["a"]).validate(),
+            Err(AccessKeyBatchError::UnlimitedAllowance(key(2)))
+        );
+        assert_eq!(
+            valid().max_total_allowance(15).add_key(key(2), 10, accounts(2), ["a"]).validate(),
+            Err(AccessKeyBatchError::AllowanceLimitExceeded { total: 20, limit: 15 })
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Random number generation seeded from [`env::random_seed`].
+//!
+//! The random seed is the same for every call within a block and is known to the block producer
+//! in advance, so [`ContractRng`] is only suitable where neither is a concern. Outcomes that
+//! someone has an incentive to bias should use a commit-reveal scheme across blocks instead.
+
+use crate::env;
+use core::ops::Range;
+use rand_core::{impls, Error};
+pub use rand_core::{RngCore, SeedableRng};
+
+/// Deterministic random number generator seeded from [`env::random_seed`].
+///
+/// Output is produced by hashing the seed together with a counter, so the whole stream is fixed
+/// by the seed. Every generator created in the same block with the same nonce produces the same
+/// numbers.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::rand::ContractRng;
+///
+/// let mut rng = ContractRng::with_nonce(b"lottery-42");
+/// let winner = rng.gen_range(0..10);
+/// assert!(winner < 10);
+///
+/// let mut tickets = vec![1, 2, 3, 4];
+/// rng.shuffle(&mut tickets);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ContractRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    index: usize,
+}
+
+impl ContractRng {
+    /// Creates a generator seeded with the random seed of the current block.
+    pub fn new() -> Self {
+        Self::from_seed(env::random_seed_array())
+    }
+
+    /// Creates a generator seeded with the random seed and height of the current block, mixed
+    /// with `nonce`. Use distinct nonces to get independent streams within the same block.
+    pub fn with_nonce(nonce: impl AsRef<[u8]>) -> Self {
+        let mut preimage = env::random_seed_array().to_vec();
+        preimage.extend_from_slice(&env::block_height().to_le_bytes());
+        preimage.extend_from_slice(nonce.as_ref());
+        Self::from_seed(env::sha256_array(&preimage))
+    }
+
+    /// Returns a uniformly distributed number in `range`.
+    ///
+    /// Panics if the range is empty.
+    pub fn gen_range(&mut self, range: Range<u64>) -> u64 {
+        if range.start >= range.end {
+            env::panic_str("Cannot sample from an empty range");
+        }
+        let span = range.end - range.start;
+        // Reject the last incomplete multiple of `span` to avoid modulo bias.
+        let limit = u64::MAX - u64::MAX % span;
+        loop {
+            let value = self.next_u64();
+            if value < limit {
+                return range.start + value % span;
+            }
+        }
+    }
+
+    /// Shuffles the slice in place with the Fisher-Yates algorithm.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.gen_range(0..i as u64 + 1) as usize;
+            slice.swap(i, j);
+        }
+    }
+
+    /// Returns a uniformly chosen element of the slice, or `None` if it is empty.
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        if slice.is_empty() {
+            return None;
+        }
+        slice.get(self.gen_range(0..slice.len() as u64) as usize)
+    }
+
+    fn refill(&mut self) {
+        let mut preimage = [0u8; 40];
+        preimage[..32].copy_from_slice(&self.seed);
+        preimage[32..].copy_from_slice(&self.counter.to_le_bytes());
+        self.block = env::sha256_array(&preimage);
+        self.counter += 1;
+        self.index = 0;
+    }
+}
+
+impl Default for ContractRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SeedableRng for ContractRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        // The first block is generated on the first use.
+        Self { seed, counter: 0, block: [0; 32], index: 32 }
+    }
+}
+
+impl RngCore for ContractRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut written = 0;
+        while written < dest.len() {
+            if self.index == self.block.len() {
+                self.refill();
+            }
+            let len = core::cmp::min(dest.len() - written, self.block.len() - self.index);
+            dest[written..written + len].copy_from_slice(&self.block[self.index..self.index + len]);
+            written += len;
+            self.index += len;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    fn set_seed(seed: u8) {
+        testing_env!(VMContextBuilder::new().random_seed([seed; 32]).build());
+    }
+
+    #[test]
+    fn deterministic_per_seed_and_nonce() {
+        set_seed(1);
+        let first = ContractRng::new().next_u64();
+        assert_eq!(ContractRng::new().next_u64(), first);
+        assert_ne!(
+            ContractRng::with_nonce(b"a").next_u64(),
+            ContractRng::with_nonce(b"b").next_u64()
+        );
+
+        set_seed(2);
+        assert_ne!(ContractRng::new().next_u64(), first);
+    }
+
+    #[test]
+    fn fill_bytes_spans_blocks() {
+        set_seed(1);
+        let mut rng = ContractRng::new();
+        let mut bytes = [0u8; 100];
+        rng.fill_bytes(&mut bytes);
+
+        let mut rng = ContractRng::new();
+        let mut chunks = [0u8; 100];
+        for chunk in chunks.chunks_mut(7) {
+            rng.fill_bytes(chunk);
+        }
+        assert_eq!(bytes, chunks);
+        assert_ne!(bytes[..32], bytes[32..64]);
+    }
+
+    #[test]
+    fn helpers() {
+        set_seed(3);
+        let mut rng = ContractRng::new();
+        for _ in 0..100 {
+            assert!((10..20).contains(&rng.gen_range(10..20)));
+        }
+        assert_eq!(rng.gen_range(5..6), 5);
+
+        let mut values: Vec<u32> = (0..20).collect();
+        rng.shuffle(&mut values);
+        assert_ne!(values, (0..20).collect::<Vec<_>>());
+        values.sort_unstable();
+        assert_eq!(values, (0..20).collect::<Vec<_>>());
+
+        assert!(rng.choose::<u32>(&[]).is_none());
+        assert!(values.contains(rng.choose(&values).unwrap()));
+
+        // Works with the `rand` ecosystem through `RngCore`.
+        use rand::Rng;
+        let _: u128 = rng.gen();
+    }
+}
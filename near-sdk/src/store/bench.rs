@@ -0,0 +1,295 @@
+//! Gas measurements of collections in the mocked runtime, to compare them on standard workloads.
+//!
+//! Each workload runs against a collection of `n` `u64` keys and values, in a mocked blockchain
+//! with the gas costs of [`VMConfig::test`]. Only the costs of host functions are counted, like
+//! storage reads and writes, hashing and registers, and not the cost of executing the contract's
+//! WebAssembly, so the numbers compare how collections use storage rather than predict the gas
+//! of a contract call.
+//!
+//! # Examples
+//! ```
+//! use near_sdk::store::bench;
+//!
+//! let report = bench::compare_maps(50);
+//! println!("{}", report);
+//! # assert_eq!(report.results.len(), 3);
+//! ```
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::store::{LookupMap, TreeMap, UnorderedMap};
+use crate::test_utils::VMContextBuilder;
+use crate::{env, Gas, MockedBlockchain, RuntimeFeesConfig, VMConfig};
+
+/// Collections that can be measured with [`bench`].
+pub trait BenchCollection: BorshSerialize + BorshDeserialize {
+    /// Name of the collection in reports.
+    const NAME: &'static str;
+
+    /// Creates an empty collection under `prefix`.
+    fn new(prefix: &[u8]) -> Self;
+
+    /// Inserts `value` under `key`.
+    fn insert(&mut self, key: u64, value: u64);
+
+    /// Returns the value under `key`.
+    fn get(&self, key: &u64) -> Option<u64>;
+
+    /// Removes the value under `key`.
+    fn remove(&mut self, key: &u64);
+
+    /// Visits every element and returns how many there are, or `None` if the collection can't
+    /// be iterated.
+    fn iterate(&self) -> Option<usize>;
+
+    /// Writes the changes to storage.
+    fn flush(&mut self);
+}
+
+/// Gas used by a collection on each workload, as measured by [`bench`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchResult {
+    /// [`BenchCollection::NAME`] of the collection.
+    pub collection: &'static str,
+    /// Number of elements of each workload.
+    pub n: u32,
+    /// Gas used to insert `n` elements into an empty collection and flush it.
+    pub insert: Gas,
+    /// Gas used to get each of the `n` elements, without any of them cached.
+    pub get: Gas,
+    /// Gas used to remove each of the `n` elements and flush the collection.
+    pub remove: Gas,
+    /// Gas used to iterate over the `n` elements, or `None` if the collection can't be iterated.
+    pub iterate: Option<Gas>,
+}
+
+/// Results of [`bench`] for several collections, which are displayed as a table of the average
+/// gas per element of each workload.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchReport {
+    pub results: Vec<BenchResult>,
+}
+
+impl FromIterator<BenchResult> for BenchReport {
+    fn from_iter<I: IntoIterator<Item = BenchResult>>(iter: I) -> Self {
+        Self { results: iter.into_iter().collect() }
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Average gas per element, in Ggas.
+        let per_element = |gas: Gas, n: u32| format!("{:.2}", gas.0 as f64 / n.max(1) as f64 / 1e9);
+
+        writeln!(
+            f,
+            "| {:<16} | {:>6} | {:>12} | {:>12} | {:>12} | {:>12} |",
+            "collection", "n", "insert", "get", "remove", "iterate"
+        )?;
+        writeln!(f, "|{:-<18}|{:->8}|{:->14}|{:->14}|{:->14}|{:->14}|", "", "", "", "", "", "")?;
+        for r in &self.results {
+            writeln!(
+                f,
+                "| {:<16} | {:>6} | {:>12} | {:>12} | {:>12} | {:>12} |",
+                r.collection,
+                r.n,
+                per_element(r.insert, r.n),
+                per_element(r.get, r.n),
+                per_element(r.remove, r.n),
+                r.iterate.map_or_else(|| "-".to_string(), |gas| per_element(gas, r.n)),
+            )?;
+        }
+        write!(f, "Ggas per element")
+    }
+}
+
+/// Measures the gas used by the collection `C` on each workload with `n` elements.
+///
+/// This replaces the mocked blockchain, so the storage and context set up by the test before are
+/// discarded.
+pub fn bench<C>(n: u32) -> BenchResult
+where
+    C: BenchCollection,
+{
+    let mut config = VMConfig::test();
+    config.limit_config.max_gas_burnt = u64::MAX;
+    env::set_blockchain_interface(MockedBlockchain::new(
+        VMContextBuilder::new().prepaid_gas(Gas(u64::MAX)).build(),
+        config,
+        RuntimeFeesConfig::test(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        None,
+    ));
+
+    // Spread the keys over the key space, so ordered collections don't get them in order.
+    let keys: Vec<u64> = (0..n as u64).map(|i| i.wrapping_mul(0x9E37_79B9_7F4A_7C15)).collect();
+
+    let mut collection = C::new(b"b");
+    let insert = measure(|| {
+        for &key in &keys {
+            collection.insert(key, key);
+        }
+        collection.flush();
+    });
+    // Later workloads load the collection from its state, so that none of the values are cached.
+    let state = collection.try_to_vec().unwrap_or_else(|_| env::abort());
+    drop(collection);
+    let load = || C::try_from_slice(&state).unwrap_or_else(|_| env::abort());
+
+    let collection = load();
+    let get = measure(|| {
+        for key in &keys {
+            collection.get(key);
+        }
+    });
+
+    let collection = load();
+    let mut iterate = None;
+    let iterate_gas = measure(|| iterate = collection.iterate());
+
+    let mut collection = load();
+    let remove = measure(|| {
+        for key in &keys {
+            collection.remove(key);
+        }
+        collection.flush();
+    });
+
+    BenchResult {
+        collection: C::NAME,
+        n,
+        insert,
+        get,
+        remove,
+        iterate: iterate.map(|_| iterate_gas),
+    }
+}
+
+/// Compares [`LookupMap`], [`UnorderedMap`] and [`TreeMap`] with [`bench`].
+pub fn compare_maps(n: u32) -> BenchReport {
+    [
+        bench::<LookupMap<u64, u64>>(n),
+        bench::<UnorderedMap<u64, u64>>(n),
+        bench::<TreeMap<u64, u64>>(n),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn measure(f: impl FnOnce()) -> Gas {
+    let checkpoint = env::gas_checkpoint();
+    f();
+    env::gas_since(checkpoint)
+}
+
+impl BenchCollection for LookupMap<u64, u64> {
+    const NAME: &'static str = "LookupMap";
+
+    fn new(prefix: &[u8]) -> Self {
+        LookupMap::new(prefix)
+    }
+
+    fn insert(&mut self, key: u64, value: u64) {
+        LookupMap::insert(self, key, value);
+    }
+
+    fn get(&self, key: &u64) -> Option<u64> {
+        LookupMap::get(self, key).copied()
+    }
+
+    fn remove(&mut self, key: &u64) {
+        LookupMap::remove(self, key);
+    }
+
+    fn iterate(&self) -> Option<usize> {
+        None
+    }
+
+    fn flush(&mut self) {
+        LookupMap::flush(self)
+    }
+}
+
+impl BenchCollection for UnorderedMap<u64, u64> {
+    const NAME: &'static str = "UnorderedMap";
+
+    fn new(prefix: &[u8]) -> Self {
+        UnorderedMap::new(prefix)
+    }
+
+    fn insert(&mut self, key: u64, value: u64) {
+        UnorderedMap::insert(self, key, value);
+    }
+
+    fn get(&self, key: &u64) -> Option<u64> {
+        UnorderedMap::get(self, key).copied()
+    }
+
+    fn remove(&mut self, key: &u64) {
+        UnorderedMap::remove(self, key);
+    }
+
+    fn iterate(&self) -> Option<usize> {
+        Some(UnorderedMap::iter(self).fold(0, |count, _| count + 1))
+    }
+
+    fn flush(&mut self) {
+        UnorderedMap::flush(self)
+    }
+}
+
+impl BenchCollection for TreeMap<u64, u64> {
+    const NAME: &'static str = "TreeMap";
+
+    fn new(prefix: &[u8]) -> Self {
+        TreeMap::new(prefix)
+    }
+
+    fn insert(&mut self, key: u64, value: u64) {
+        TreeMap::insert(self, key, value);
+    }
+
+    fn get(&self, key: &u64) -> Option<u64> {
+        TreeMap::get(self, key).copied()
+    }
+
+    fn remove(&mut self, key: &u64) {
+        TreeMap::remove(self, key);
+    }
+
+    fn iterate(&self) -> Option<usize> {
+        Some(TreeMap::iter(self).fold(0, |count, _| count + 1))
+    }
+
+    fn flush(&mut self) {
+        TreeMap::flush(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_maps_report() {
+        let report = compare_maps(20);
+        let names: Vec<_> = report.results.iter().map(|r| r.collection).collect();
+        assert_eq!(names, ["LookupMap", "UnorderedMap", "TreeMap"]);
+
+        for r in &report.results {
+            assert!(r.insert.0 > 0 && r.get.0 > 0 && r.remove.0 > 0);
+            assert_eq!(r.iterate.is_some(), r.collection != "LookupMap");
+            assert!(r.iterate.map_or(true, |gas| gas.0 > 0));
+        }
+        // Maps that can be iterated store the keys as well, which costs more to insert.
+        assert!(report.results[0].insert.0 < report.results[1].insert.0);
+
+        let table = report.to_string();
+        assert_eq!(table.lines().count(), 2 + 3 + 1);
+        assert!(table.lines().nth(2).unwrap().starts_with("| LookupMap"));
+    }
+}
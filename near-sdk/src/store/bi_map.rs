@@ -0,0 +1,405 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::ERR_INCONSISTENT_STATE;
+use crate::store::key::{Identity, ToKey};
+use crate::store::LookupMap;
+use crate::{env, IntoStorageKey};
+
+const ERR_KEY2_IN_USE: &str = "Secondary key is already used by another entry";
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// Value of an entry, stored under its primary key along with its secondary key.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Entry<K2, V> {
+    key2: K2,
+    value: V,
+}
+
+/// A map whose entries can be looked up by either of two keys, stored on the storage trie.
+///
+/// Each entry has a primary key of type `K1` and a secondary key of type `K2`, and no two entries
+/// share either key. The values are stored in a [`LookupMap`] by primary key, along with the
+/// secondary key of their entry, and a second [`LookupMap`] indexes the primary keys by secondary
+/// key. Inserting and removing entries updates both, so a lookup by secondary key costs one more
+/// storage read than a lookup by primary key.
+///
+/// Like [`LookupMap`], the entries cannot be iterated over.
+///
+/// # Examples
+/// ```
+/// use near_sdk::AccountId;
+/// use near_sdk::store::BiMap;
+///
+/// let mut members: BiMap<AccountId, u64, String> = BiMap::new(b"m");
+/// let alice: AccountId = "alice.near".parse().unwrap();
+/// members.insert(alice.clone(), 1, "admin".to_string());
+///
+/// assert_eq!(members.get(&alice), Some(&"admin".to_string()));
+/// assert_eq!(members.get_by_key2(&1), Some(&"admin".to_string()));
+/// assert_eq!(members.key1(&1), Some(&alice));
+///
+/// assert_eq!(members.remove_by_key2(&1), Some((alice.clone(), "admin".to_string())));
+/// assert!(!members.contains_key(&alice));
+/// ```
+pub struct BiMap<K1, K2, V, H = Identity>
+where
+    K1: BorshSerialize + Ord,
+    K2: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    values: LookupMap<K1, Entry<K2, V>, H>,
+    keys: LookupMap<K2, K1, H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K1, K2, V, H> BorshSerialize for BiMap<K1, K2, V, H>
+where
+    K1: BorshSerialize + Ord,
+    K2: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.values, writer)?;
+        BorshSerialize::serialize(&self.keys, writer)?;
+        Ok(())
+    }
+}
+
+impl<K1, K2, V, H> BorshDeserialize for BiMap<K1, K2, V, H>
+where
+    K1: BorshSerialize + Ord,
+    K2: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            values: BorshDeserialize::deserialize(buf)?,
+            keys: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<K1, K2, V, H> fmt::Debug for BiMap<K1, K2, V, H>
+where
+    K1: BorshSerialize + Ord,
+    K2: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BiMap").field("values", &self.values).field("keys", &self.keys).finish()
+    }
+}
+
+impl<K1, K2, V> BiMap<K1, K2, V, Identity>
+where
+    K1: BorshSerialize + Ord,
+    K2: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new map. Use `prefix` as a unique prefix for keys.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BiMap;
+    ///
+    /// let mut map: BiMap<String, u64, u128> = BiMap::new(b"m");
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<K1, K2, V, H> BiMap<K1, K2, V, H>
+where
+    K1: BorshSerialize + Ord,
+    K2: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize a [`BiMap`] with a custom hash function, used for both keys.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{BiMap, key::Keccak256};
+    ///
+    /// let map = BiMap::<String, String, u8, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            values: LookupMap::with_hasher([&prefix[..], b"v"].concat()),
+            keys: LookupMap::with_hasher([&prefix[..], b"k"].concat()),
+        }
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.values.flush();
+        self.keys.flush();
+    }
+}
+
+impl<K1, K2, V, H> BiMap<K1, K2, V, H>
+where
+    K1: BorshSerialize + BorshDeserialize + Ord,
+    K2: BorshSerialize + BorshDeserialize + Ord,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns a reference to the value of the entry with the primary key `k1`.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K1>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn get<Q: ?Sized>(&self, k1: &Q) -> Option<&V>
+    where
+        K1: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K1>,
+    {
+        self.values.get(k1).map(|entry| &entry.value)
+    }
+
+    /// Returns a reference to the value of the entry with the secondary key `k2`.
+    pub fn get_by_key2<Q: ?Sized>(&self, k2: &Q) -> Option<&V>
+    where
+        K2: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K2>,
+        K1: Clone,
+    {
+        self.key1(k2).map(|k1| &expect_consistent_state(self.values.get(k1)).value)
+    }
+
+    /// Returns a mutable reference to the value of the entry with the primary key `k1`.
+    pub fn get_mut<Q: ?Sized>(&mut self, k1: &Q) -> Option<&mut V>
+    where
+        K1: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K1>,
+    {
+        self.values.get_mut(k1).map(|entry| &mut entry.value)
+    }
+
+    /// Returns a mutable reference to the value of the entry with the secondary key `k2`.
+    pub fn get_mut_by_key2<Q: ?Sized>(&mut self, k2: &Q) -> Option<&mut V>
+    where
+        K2: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K2>,
+        K1: Clone,
+    {
+        let k1 = self.keys.get(k2)?;
+        Some(&mut expect_consistent_state(self.values.get_mut(k1)).value)
+    }
+
+    /// Returns the primary key of the entry with the secondary key `k2`.
+    pub fn key1<Q: ?Sized>(&self, k2: &Q) -> Option<&K1>
+    where
+        K2: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K2>,
+    {
+        self.keys.get(k2)
+    }
+
+    /// Returns the secondary key of the entry with the primary key `k1`.
+    pub fn key2<Q: ?Sized>(&self, k1: &Q) -> Option<&K2>
+    where
+        K1: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K1>,
+    {
+        self.values.get(k1).map(|entry| &entry.key2)
+    }
+
+    /// Returns `true` if the map contains an entry with the primary key `k1`.
+    pub fn contains_key<Q: ?Sized>(&self, k1: &Q) -> bool
+    where
+        K1: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K1> + Ord,
+    {
+        self.values.contains_key(k1)
+    }
+
+    /// Returns `true` if the map contains an entry with the secondary key `k2`.
+    pub fn contains_key2<Q: ?Sized>(&self, k2: &Q) -> bool
+    where
+        K2: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K2> + Ord,
+    {
+        self.keys.contains_key(k2)
+    }
+
+    /// Inserts an entry with the keys `k1` and `k2`, and returns the previous value of the entry
+    /// with the primary key `k1`. The secondary key of that entry is replaced by `k2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k2` is the secondary key of an entry with another primary key, since the entries
+    /// of the map can't share keys. Remove that entry first to move the secondary key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BiMap;
+    ///
+    /// let mut map: BiMap<String, u64, u8> = BiMap::new(b"m");
+    /// assert_eq!(map.insert("a".to_string(), 1, 10), None);
+    /// assert_eq!(map.insert("a".to_string(), 2, 20), Some(10));
+    ///
+    /// assert!(!map.contains_key2(&1));
+    /// assert_eq!(map.get_by_key2(&2), Some(&20));
+    /// ```
+    pub fn insert(&mut self, k1: K1, k2: K2, value: V) -> Option<V>
+    where
+        K1: Clone,
+        K2: Clone,
+    {
+        if matches!(self.keys.get(&k2), Some(other) if *other != k1) {
+            env::panic_str(ERR_KEY2_IN_USE);
+        }
+        let prev = self.values.insert(k1.clone(), Entry { key2: k2.clone(), value });
+        if let Some(prev) = &prev {
+            if prev.key2 != k2 {
+                self.keys.remove(&prev.key2);
+            }
+        }
+        self.keys.set(k2, Some(k1));
+        prev.map(|entry| entry.value)
+    }
+
+    /// Removes the entry with the primary key `k1`, and returns its secondary key and value.
+    pub fn remove<Q: ?Sized>(&mut self, k1: &Q) -> Option<(K2, V)>
+    where
+        K1: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K1>,
+        K2: Clone,
+    {
+        let entry = self.values.remove(k1)?;
+        expect_consistent_state(self.keys.remove(&entry.key2));
+        Some((entry.key2, entry.value))
+    }
+
+    /// Removes the entry with the secondary key `k2`, and returns its primary key and value.
+    pub fn remove_by_key2<Q: ?Sized>(&mut self, k2: &Q) -> Option<(K1, V)>
+    where
+        K2: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K2>,
+        K1: Clone,
+    {
+        let k1 = self.keys.remove(k2)?;
+        let entry = expect_consistent_state(self.values.remove(&k1));
+        Some((k1, entry.value))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::BTreeMap;
+
+    use super::BiMap;
+    use crate::test_utils::test_env::setup_free;
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Insert(u8, u8, u32),
+        Remove(u8),
+        RemoveByKey2(u8),
+        Get(u8),
+        GetByKey2(u8),
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sm = BiMap::new(b"m");
+            let mut mm = BTreeMap::<u8, (u8, u32)>::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Insert(k1, k2, v) => {
+                            // Inserting a secondary key of another entry panics.
+                            if mm.iter().any(|(k, (k2_, _))| *k2_ == k2 && *k != k1) {
+                                continue;
+                            }
+                            let prev = mm.insert(k1, (k2, v)).map(|(_, v)| v);
+                            assert_eq!(sm.insert(k1, k2, v), prev);
+                        }
+                        Op::Remove(k1) => {
+                            assert_eq!(sm.remove(&k1), mm.remove(&k1));
+                        }
+                        Op::RemoveByKey2(k2) => {
+                            let k1 = mm.iter().find(|(_, (k, _))| *k == k2).map(|(k1, _)| *k1);
+                            let removed = k1.map(|k1| (k1, mm.remove(&k1).unwrap().1));
+                            assert_eq!(sm.remove_by_key2(&k2), removed);
+                        }
+                        Op::Get(k1) => {
+                            assert_eq!(sm.get(&k1), mm.get(&k1).map(|(_, v)| v));
+                            assert_eq!(sm.key2(&k1), mm.get(&k1).map(|(k2, _)| k2));
+                        }
+                        Op::GetByKey2(k2) => {
+                            let entry = mm.iter().find(|(_, (k, _))| *k == k2);
+                            assert_eq!(sm.key1(&k2), entry.map(|(k1, _)| k1));
+                            assert_eq!(sm.get_by_key2(&k2), entry.map(|(_, (_, v))| v));
+                            let k1 = entry.map(|(k1, _)| *k1);
+                            if let Some(v) = sm.get_mut_by_key2(&k2) {
+                                *v += 1;
+                                mm.get_mut(&k1.unwrap()).unwrap().1 += 1;
+                            }
+                        }
+                        Op::Flush => sm.flush(),
+                        Op::Reset => {
+                            sm.flush();
+                            let serialized = sm.try_to_vec().unwrap();
+                            sm = BiMap::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                }
+            }
+
+            // Removing every entry leaves nothing in storage.
+            for k1 in mm.keys() {
+                assert!(sm.remove(k1).is_some());
+            }
+            sm.flush();
+            crate::mock::with_mocked_blockchain(|b| assert!(b.take_storage().is_empty()));
+        }
+    }
+}
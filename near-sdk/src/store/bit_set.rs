@@ -0,0 +1,414 @@
+//! A set of integers stored as a bitmap on the trie. See [`BitSet`].
+
+use core::iter::FusedIterator;
+use core::ops::Range;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::IndexMap;
+use crate::{env, IntoStorageKey};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+
+/// Number of bits stored in each storage slot.
+const WORD_BITS: u64 = u64::BITS as u64;
+
+/// A set of `u64` indices stored as a bitmap on the trie, 64 bits per storage slot. This
+/// implementation will load and store the words of the bitmap in the underlying storage lazily.
+///
+/// This is much more compact than a [`LookupSet<u64>`](crate::store::LookupSet) for dense ranges
+/// of indices, like tracking which airdrop claims or tickets have been redeemed: each storage
+/// slot holds 64 indices, and slots that have no bits set are removed from storage.
+///
+/// The number of set bits is kept in the metadata, so [`BitSet::count_ones`] does not load any
+/// words. The words can be iterated in order with [`BitSet::chunks`] to process the bitmap over
+/// several calls.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::BitSet;
+///
+/// let mut claimed = BitSet::new(b"c");
+/// assert!(!claimed.set(3, true));
+/// assert!(!claimed.set(100, true));
+///
+/// assert!(claimed.get(3));
+/// assert!(!claimed.get(4));
+/// assert_eq!(claimed.count_ones(), 2);
+/// assert!(Iterator::eq(claimed.iter(), [3, 100]));
+/// ```
+pub struct BitSet {
+    count: u64,
+    /// One past the index of the highest word that had bits set since the set was cleared.
+    word_count: u32,
+    words: IndexMap<u64>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl BorshSerialize for BitSet {
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.count, writer)?;
+        BorshSerialize::serialize(&self.word_count, writer)?;
+        BorshSerialize::serialize(&self.words, writer)?;
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for BitSet {
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            count: BorshDeserialize::deserialize(buf)?,
+            word_count: BorshDeserialize::deserialize(buf)?,
+            words: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl Drop for BitSet {
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            f.debug_set().entries(self.iter()).finish()
+        } else {
+            f.debug_struct("BitSet")
+                .field("count", &self.count)
+                .field("prefix", &self.words.prefix)
+                .finish()
+        }
+    }
+}
+
+impl BitSet {
+    /// Create a new bitmap with no bits set. Prefixes storage access with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BitSet;
+    ///
+    /// let mut bits = BitSet::new(b"b");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { count: 0, word_count: 0, words: IndexMap::new(prefix) }
+    }
+
+    /// Index of the word that stores the bit at `index`, and the mask of the bit in the word.
+    fn position(index: u64) -> Option<(u32, u64)> {
+        let word = u32::try_from(index / WORD_BITS).ok().filter(|&word| word < u32::MAX)?;
+        Some((word, 1 << (index % WORD_BITS)))
+    }
+
+    /// Returns the number of bits that are set.
+    pub fn count_ones(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns `true` if no bits are set.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns whether the bit at `index` is set.
+    pub fn get(&self, index: u64) -> bool {
+        match Self::position(index) {
+            Some((word, mask)) => matches!(self.words.get(word), Some(w) if w & mask != 0),
+            None => false,
+        }
+    }
+
+    /// Sets the bit at `index` to `value`, and returns its previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index / 64` is `u32::MAX` or greater, since words are indexed by `u32`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BitSet;
+    ///
+    /// let mut bits = BitSet::new(b"b");
+    /// assert_eq!(bits.set(7, true), false);
+    /// assert_eq!(bits.set(7, true), true);
+    /// assert_eq!(bits.set(7, false), true);
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn set(&mut self, index: u64, value: bool) -> bool {
+        let (word_index, mask) =
+            Self::position(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        let entry = self.words.get_mut_inner(word_index);
+        let word = entry.value().unwrap_or(0);
+        let prev = word & mask != 0;
+        if prev == value {
+            // Avoid writing the word back when nothing changed.
+            return prev;
+        }
+
+        let word = word ^ mask;
+        // Words without any bits set are removed from storage.
+        entry.replace(if word == 0 { None } else { Some(word) });
+        if value {
+            self.count += 1;
+            self.word_count = self.word_count.max(word_index + 1);
+        } else {
+            self.count -= 1;
+        }
+        prev
+    }
+
+    /// Unsets all bits. This will remove the storage of every word up to the highest one that
+    /// had a bit set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BitSet;
+    ///
+    /// let mut bits = BitSet::new(b"b");
+    /// bits.set(1, true);
+    ///
+    /// bits.clear();
+    ///
+    /// assert!(!bits.get(1));
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        for i in 0..self.word_count {
+            self.words.set(i, None);
+        }
+        self.count = 0;
+        self.word_count = 0;
+    }
+
+    /// Returns an iterator over the indices of the bits that are set, in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { chunks: self.chunks(), current: None }
+    }
+
+    /// Returns an iterator over the words of the bitmap, as the index of their first bit and the
+    /// word, where bit `i` of the word is the bit at `index + i`. Words without any bits set are
+    /// included, so the iterator can be skipped ahead with [`Iterator::nth`] without loading the
+    /// words in between, to process the bitmap over several calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BitSet;
+    ///
+    /// let mut bits = BitSet::new(b"b");
+    /// bits.set(1, true);
+    /// bits.set(130, true);
+    ///
+    /// // Process one word per call, starting from a cursor.
+    /// let cursor = 2;
+    /// assert_eq!(bits.chunks().nth(cursor), Some((128, 0b100)));
+    /// assert_eq!(bits.chunks().len(), 3);
+    /// ```
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks { bits: self, range: 0..self.word_count }
+    }
+
+    /// Flushes the cache and writes all modified words to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.words.flush();
+    }
+}
+
+/// An iterator over the words of a [`BitSet`], returned by [`BitSet::chunks`].
+#[derive(Debug)]
+pub struct Chunks<'a> {
+    bits: &'a BitSet,
+    range: Range<u32>,
+}
+
+impl<'a> Chunks<'a> {
+    fn word(&self, index: u32) -> (u64, u64) {
+        (u64::from(index) * WORD_BITS, self.bits.words.get(index).copied().unwrap_or(0))
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.range.len()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth(n)?;
+        Some(self.word(index))
+    }
+}
+
+impl<'a> ExactSizeIterator for Chunks<'a> {}
+impl<'a> FusedIterator for Chunks<'a> {}
+
+impl<'a> DoubleEndedIterator for Chunks<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth_back(n)?;
+        Some(self.word(index))
+    }
+}
+
+/// An iterator over the indices of the set bits of a [`BitSet`], returned by [`BitSet::iter`].
+#[derive(Debug)]
+pub struct Iter<'a> {
+    chunks: Chunks<'a>,
+    /// Index of the first bit and remaining bits of the word being iterated.
+    current: Option<(u64, u64)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match &mut self.current {
+                Some((start, word)) if *word != 0 => {
+                    let bit = word.trailing_zeros();
+                    // Clear the lowest set bit.
+                    *word &= *word - 1;
+                    return Some(*start + u64::from(bit));
+                }
+                _ => self.current = Some(self.chunks.next()?),
+            }
+        }
+    }
+}
+
+impl<'a> FusedIterator for Iter<'a> {}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = u64;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Extend<u64> for BitSet {
+    /// Sets the bits at all the indices of the iterator.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        for index in iter {
+            self.set(index, true);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::BTreeSet;
+
+    use super::BitSet;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn words_removed_when_empty() {
+        let mut bits = BitSet::new(b"b");
+        bits.extend([0, 63, 64, 1000]);
+        assert_eq!(bits.chunks().len(), 16);
+        assert_eq!(bits.chunks().next(), Some((0, 1 << 63 | 1)));
+        bits.flush();
+
+        bits.set(0, false);
+        bits.set(63, false);
+        bits.set(64, false);
+        bits.flush();
+        // Only the word of bit 1000 is left.
+        crate::mock::with_mocked_blockchain(|m| assert_eq!(m.take_storage().len(), 1));
+
+        bits.clear();
+        bits.flush();
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Set(u16, bool),
+        Get(u16),
+        Clear,
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sb = BitSet::new(b"b");
+            let mut mb = BTreeSet::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Set(i, v) => {
+                            let i = u64::from(i);
+                            let prev = if v { !mb.insert(i) } else { mb.remove(&i) };
+                            assert_eq!(sb.set(i, v), prev);
+                        }
+                        Op::Get(i) => assert_eq!(sb.get(u64::from(i)), mb.contains(&u64::from(i))),
+                        Op::Clear => {
+                            sb.clear();
+                            mb.clear();
+                        }
+                        Op::Flush => sb.flush(),
+                        Op::Reset => {
+                            let serialized = sb.try_to_vec().unwrap();
+                            sb = BitSet::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(sb.count_ones() as usize, mb.len());
+                }
+            }
+
+            // After all operations, compare both sets
+            assert!(Iterator::eq(sb.iter(), mb.iter().copied()));
+        }
+    }
+}
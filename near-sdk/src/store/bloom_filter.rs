@@ -0,0 +1,221 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::BitSet;
+use crate::{env, IntoStorageKey};
+
+/// A probabilistic set stored on the trie, which can tell that an element was *definitely not*
+/// inserted, or that it *may have been* inserted.
+///
+/// The filter is stored in a [`BitSet`] of a fixed number of bits, and each element sets a fixed
+/// number of bits derived from the `sha256` hash of its Borsh serialization. Checking an element
+/// only loads the storage words of its bits, so a filter can be used to skip a more expensive
+/// exact lookup, like checking a nonce against all the ones used before, when the element is
+/// definitely new. Elements can not be removed.
+///
+/// The chance of [`contains`](Self::contains) returning `true` for an element that was not
+/// inserted grows with the number of inserted elements. [`BloomFilter::new`] sizes the filter to
+/// keep it under a target rate for an expected number of elements.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::BloomFilter;
+///
+/// // Up to 1% false positives for 10,000 nonces.
+/// let mut seen: BloomFilter<u64> = BloomFilter::new(b"n", 10_000, 0.01);
+/// seen.insert(&42);
+///
+/// assert!(seen.contains(&42));
+/// if !seen.contains(&43) {
+///     // Definitely not seen before, no need to check the exact set of nonces.
+/// }
+/// ```
+pub struct BloomFilter<T: ?Sized> {
+    num_bits: u64,
+    num_hashes: u32,
+    bits: BitSet,
+    _marker: PhantomData<fn(&T)>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T: ?Sized> BorshSerialize for BloomFilter<T> {
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.num_bits, writer)?;
+        BorshSerialize::serialize(&self.num_hashes, writer)?;
+        BorshSerialize::serialize(&self.bits, writer)?;
+        Ok(())
+    }
+}
+
+impl<T: ?Sized> BorshDeserialize for BloomFilter<T> {
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            num_bits: BorshDeserialize::deserialize(buf)?,
+            num_hashes: BorshDeserialize::deserialize(buf)?,
+            bits: BorshDeserialize::deserialize(buf)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for BloomFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .field("bits", &self.bits)
+            .finish()
+    }
+}
+
+impl<T> BloomFilter<T>
+where
+    T: BorshSerialize + ?Sized,
+{
+    /// Create a filter sized to have a rate of false positives of at most `false_positive_rate`
+    /// once `expected_items` elements are inserted. Prefixes storage access with the prefix
+    /// provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `expected_items` is zero or `false_positive_rate` is not between 0 and 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::BloomFilter;
+    ///
+    /// let filter: BloomFilter<str> = BloomFilter::new(b"f", 1000, 0.001);
+    /// assert_eq!(filter.num_bits(), 14378);
+    /// assert_eq!(filter.num_hashes(), 10);
+    /// ```
+    pub fn new<S>(prefix: S, expected_items: u64, false_positive_rate: f64) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if expected_items == 0 || !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            env::panic_str(
+                "BloomFilter requires expected items and a false positive rate in (0, 1)",
+            );
+        }
+        // Optimal number of bits and hash functions for the expected number of elements.
+        let ln2 = core::f64::consts::LN_2;
+        let num_bits = (-(expected_items as f64) * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let num_hashes = (num_bits / expected_items as f64 * ln2).round();
+        Self::with_params(prefix, num_bits as u64, num_hashes.max(1.0) as u32)
+    }
+
+    /// Create a filter of `num_bits` bits, which sets `num_hashes` bits for every element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_bits` or `num_hashes` is zero.
+    pub fn with_params<S>(prefix: S, num_bits: u64, num_hashes: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if num_bits == 0 || num_hashes == 0 {
+            env::panic_str("BloomFilter requires at least one bit and one hash");
+        }
+        Self { num_bits, num_hashes, bits: BitSet::new(prefix), _marker: PhantomData }
+    }
+
+    /// Returns the number of bits of the filter.
+    pub fn num_bits(&self) -> u64 {
+        self.num_bits
+    }
+
+    /// Returns the number of bits set for every element.
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// Indices of the bits of `item`. They are derived from two halves of a single `sha256` hash,
+    /// which is as good as independent hash functions for a bloom filter.
+    fn bit_indices(&self, item: &T) -> impl Iterator<Item = u64> {
+        let data = item.try_to_vec().unwrap_or_else(|_| env::panic_str("Cannot serialize element"));
+        let hash = env::sha256_array(&data);
+        let mut h1 = [0; 8];
+        let mut h2 = [0; 8];
+        h1.copy_from_slice(&hash[..8]);
+        h2.copy_from_slice(&hash[8..16]);
+        let (h1, h2) = (u64::from_le_bytes(h1), u64::from_le_bytes(h2));
+        let num_bits = self.num_bits;
+        (0..u64::from(self.num_hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+
+    /// Adds an element to the filter. Returns `true` if the element may have been inserted
+    /// before, or `false` if it definitely was not.
+    pub fn insert(&mut self, item: &T) -> bool {
+        let mut present = true;
+        for index in self.bit_indices(item) {
+            present &= self.bits.set(index, true);
+        }
+        present
+    }
+
+    /// Returns `true` if the element may have been inserted, or `false` if it definitely was not.
+    pub fn contains(&self, item: &T) -> bool {
+        self.bit_indices(item).all(|index| self.bits.get(index))
+    }
+
+    /// Removes all elements from the filter.
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// Flushes the cache and writes all modified words to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.bits.flush();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::BloomFilter;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn false_positive_rate() {
+        setup_free();
+
+        let mut filter = BloomFilter::new(b"f", 1000, 0.01);
+        for i in 0..1000u32 {
+            filter.insert(&i);
+        }
+        filter.flush();
+
+        let filter = BloomFilter::<u32>::try_from_slice(&filter.try_to_vec().unwrap()).unwrap();
+        // No false negatives.
+        assert!((0..1000u32).all(|i| filter.contains(&i)));
+        let false_positives = (1000..11_000u32).filter(|i| filter.contains(i)).count();
+        // Expected around 100 of 10,000.
+        assert!(false_positives < 200, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn insert_reports_new_elements() {
+        let mut filter = BloomFilter::<str>::with_params(b"f", 1 << 16, 4);
+        assert!(!filter.insert("a"));
+        assert!(filter.insert("a"));
+        assert!(!filter.contains("b"));
+
+        filter.clear();
+        assert!(!filter.contains("a"));
+    }
+}
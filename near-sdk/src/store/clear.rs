@@ -0,0 +1,43 @@
+/// Progress of clearing a collection over multiple calls, returned by the `clear_chunk` methods
+/// of the collections, like [`UnorderedMap::clear_chunk`](super::UnorderedMap::clear_chunk).
+///
+/// The progress is kept in the collection itself, so a contract only has to persist the
+/// collection, as it does anyway, and call `clear_chunk` again in a later function call until it
+/// returns [`ClearProgress::Done`].
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::{ClearProgress, UnorderedMap};
+///
+/// let mut map: UnorderedMap<u32, u32> = UnorderedMap::new(b"m");
+/// map.extend((0..250).map(|i| (i, i)));
+///
+/// assert_eq!(map.clear_chunk(100), ClearProgress::Remaining(150));
+/// assert_eq!(map.clear_chunk(100), ClearProgress::Remaining(50));
+/// assert_eq!(map.clear_chunk(100), ClearProgress::Done);
+/// assert!(map.is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClearProgress {
+    /// The collection is cleared.
+    Done,
+    /// Entries are left to be removed by later calls. This is the number of entries the calls
+    /// have to remove, which can include entries that were already removed from the collection
+    /// but are still kept in storage.
+    Remaining(u32),
+}
+
+impl ClearProgress {
+    pub(crate) fn remaining(entries: u32) -> Self {
+        if entries == 0 {
+            Self::Done
+        } else {
+            Self::Remaining(entries)
+        }
+    }
+
+    /// Returns `true` if the collection is cleared.
+    pub fn is_done(&self) -> bool {
+        matches!(self, Self::Done)
+    }
+}
@@ -0,0 +1,195 @@
+//! Values that are compressed in storage. See [`Compressed`].
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use borsh::maybestd::io;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Tag of a value that is stored as it is serialized.
+const RAW: u8 = 0;
+/// Tag of a value that is stored compressed.
+const LZ4: u8 = 1;
+
+/// Value that is compressed with [LZ4](https://lz4.github.io/lz4/) when its serialized size is at
+/// least `THRESHOLD` bytes, and decompressed when it is deserialized.
+///
+/// This can be used as the value of any collection, or as a field of the contract state, to lower
+/// the storage staking cost of large values that compress well, like JSON metadata. Compression
+/// is configured per collection through its value type, and the threshold avoids spending gas on
+/// values too small to gain from it. A value is only stored compressed if that makes it smaller,
+/// and either way it takes one more byte to tag how it is stored.
+///
+/// The value is accessed through [`Deref`] and [`DerefMut`], and serialized with `serde` as the
+/// value itself, so it can be returned from view methods as is.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::{Compressed, LookupMap};
+///
+/// let mut metadata: LookupMap<u64, Compressed<String>> = LookupMap::new(b"m");
+/// metadata.insert(0, Compressed::new(r#"{"title":"..."}"#.repeat(100)));
+///
+/// assert!(metadata[&0].starts_with(r#"{"title""#));
+/// ```
+#[derive(
+    Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct Compressed<T, const THRESHOLD: usize = 256>(T);
+
+impl<T, const THRESHOLD: usize> Compressed<T, THRESHOLD> {
+    /// Wraps `value`, to be compressed when it is serialized.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns the value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T, const THRESHOLD: usize> From<T> for Compressed<T, THRESHOLD> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T, const THRESHOLD: usize> Deref for Compressed<T, THRESHOLD> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T, const THRESHOLD: usize> DerefMut for Compressed<T, THRESHOLD> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T, const THRESHOLD: usize> fmt::Debug for Compressed<T, THRESHOLD>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl<T, const THRESHOLD: usize> BorshSerialize for Compressed<T, THRESHOLD>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        let bytes = self.0.try_to_vec()?;
+        if bytes.len() >= THRESHOLD {
+            let compressed = lz4_flex::compress_prepend_size(&bytes);
+            if compressed.len() < bytes.len() {
+                LZ4.serialize(writer)?;
+                // Prefixed with its length, so that the value can be followed by other data.
+                return compressed.serialize(writer);
+            }
+        }
+        RAW.serialize(writer)?;
+        writer.write_all(&bytes)
+    }
+}
+
+impl<T, const THRESHOLD: usize> BorshDeserialize for Compressed<T, THRESHOLD>
+where
+    T: BorshDeserialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, io::Error> {
+        match u8::deserialize(buf)? {
+            RAW => Ok(Self(T::deserialize(buf)?)),
+            LZ4 => {
+                let compressed = Vec::<u8>::deserialize(buf)?;
+                let bytes = lz4_flex::decompress_size_prepended(&compressed)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+                Ok(Self(T::try_from_slice(&bytes)?))
+            }
+            tag => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid tag of compressed value: {}", tag),
+            )),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::LookupMap;
+
+    #[test]
+    fn compresses_above_threshold() {
+        let small = Compressed::<String>::new("a".repeat(100));
+        let serialized = small.try_to_vec().unwrap();
+        assert_eq!(serialized[0], RAW);
+        assert_eq!(&serialized[1..], "a".repeat(100).try_to_vec().unwrap());
+
+        let large = Compressed::<String>::new("a".repeat(1000));
+        let serialized = large.try_to_vec().unwrap();
+        assert_eq!(serialized[0], LZ4);
+        assert!(serialized.len() < 100);
+        assert_eq!(Compressed::<String>::try_from_slice(&serialized).unwrap(), large);
+
+        // The threshold is part of the type.
+        let serialized = Compressed::<String, 50>::new("a".repeat(100)).try_to_vec().unwrap();
+        assert_eq!(serialized[0], LZ4);
+    }
+
+    #[test]
+    fn incompressible_values_are_stored_raw() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let noise: Vec<u8> = (0..1000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect();
+        let value = Compressed::<Vec<u8>>::new(noise);
+        let serialized = value.try_to_vec().unwrap();
+        assert_eq!(serialized[0], RAW);
+        assert_eq!(Compressed::<Vec<u8>>::try_from_slice(&serialized).unwrap(), value);
+    }
+
+    #[test]
+    fn followed_by_other_fields() {
+        #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+        struct Token {
+            metadata: Compressed<String, 16>,
+            owner: String,
+        }
+
+        let token = Token { metadata: "{}".repeat(50).into(), owner: "alice.near".to_string() };
+        let serialized = token.try_to_vec().unwrap();
+        assert_eq!(Token::try_from_slice(&serialized).unwrap(), token);
+        assert!(Token::try_from_slice(&[LZ4, 1, 0, 0, 0, 0]).is_err());
+        assert!(Token::try_from_slice(&[2]).is_err());
+    }
+
+    #[test]
+    fn stored_in_collection() {
+        let value = r#"{"title":"Token","description":"..."}"#.repeat(20);
+        let mut map: LookupMap<u8, Compressed<String>> = LookupMap::new(b"m");
+        map.insert(0, value.clone().into());
+        map.flush();
+
+        let stored = crate::mock::with_mocked_blockchain(|m| m.take_storage());
+        let (_, bytes) = stored.into_iter().next().unwrap();
+        assert!(bytes.len() < value.len() / 4);
+
+        let mut map: LookupMap<u8, Compressed<String>> = LookupMap::new(b"m");
+        crate::env::storage_write(&[b"m".as_slice(), &[0]].concat(), &bytes);
+        assert_eq!(map.get(&0).map(|v| v.as_str()), Some(value.as_str()));
+        assert_eq!(map.remove(&0).unwrap().into_inner(), value);
+        assert_eq!(serde_json::to_string(&Compressed::<u8>::new(3)).unwrap(), "3");
+    }
+}
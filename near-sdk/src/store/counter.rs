@@ -0,0 +1,223 @@
+//! A persistent counter, stored under its own key. See [`Counter`].
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use once_cell::unsync::OnceCell;
+
+use crate::env;
+use crate::store::{Snapshot, Transactional};
+use crate::utils::{CacheEntry, EntryState};
+use crate::IntoStorageKey;
+
+const ERR_OVERFLOW: &str = "Counter overflowed";
+const ERR_VALUE_SERIALIZATION: &str = "Cannot serialize value with Borsh";
+const ERR_VALUE_DESERIALIZATION: &str = "Cannot deserialize value with Borsh";
+
+mod private {
+    /// Seal `CounterValue` implementations to limit usage to the builtin integer types
+    pub trait Sealed {}
+
+    impl Sealed for u64 {}
+    impl Sealed for u128 {}
+}
+
+/// Integer types that a [`Counter`] can count with.
+pub trait CounterValue:
+    self::private::Sealed + Copy + BorshSerialize + BorshDeserialize + fmt::Debug
+{
+    /// Value of a counter that has not been incremented yet.
+    const ZERO: Self;
+
+    /// Returns the value after `self`, or `None` if it does not fit the type.
+    fn checked_increment(self) -> Option<Self>;
+}
+
+macro_rules! impl_counter_value {
+    ($($ty:ty),*) => {
+        $(
+            impl CounterValue for $ty {
+                const ZERO: Self = 0;
+
+                fn checked_increment(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_counter_value!(u64, u128);
+
+/// A persistent monotonically increasing counter, stored under its own key rather than in the
+/// contract state.
+///
+/// This is meant for allocating identifiers, like token ids, nonces or proposal ids. The counter
+/// starts at zero, and each call to [`Counter::next`] returns the current value and increments it,
+/// so no value is returned twice. A counter that was never incremented does not use any storage.
+///
+/// The value is read from storage once, on first use, and written when the counter is flushed or
+/// dropped.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Counter;
+///
+/// let mut token_ids: Counter = Counter::new(b"t");
+/// assert_eq!(token_ids.next(), 0);
+/// assert_eq!(token_ids.next(), 1);
+/// assert_eq!(token_ids.current(), 2);
+/// ```
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Counter<T = u64>
+where
+    T: CounterValue,
+{
+    /// Key bytes to index the contract's storage.
+    storage_key: Box<[u8]>,
+    #[borsh_skip]
+    /// Cached value which is lazily loaded and deserialized from storage.
+    cache: OnceCell<CacheEntry<T>>,
+}
+
+impl<T> Counter<T>
+where
+    T: CounterValue,
+{
+    /// Creates a counter stored under `key`, which starts at zero if no value was stored under
+    /// the key before.
+    ///
+    /// The key can be anything that implements [`IntoStorageKey`]. It must not overlap with the
+    /// prefixes of collections, to ensure no collisions with their values.
+    pub fn new<S>(key: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { storage_key: key.into_storage_key().into_boxed_slice(), cache: OnceCell::new() }
+    }
+
+    fn entry(&self) -> &CacheEntry<T> {
+        self.cache.get_or_init(|| {
+            let value = env::storage_read(&self.storage_key).map_or(T::ZERO, |bytes| {
+                T::try_from_slice(&bytes)
+                    .unwrap_or_else(|_| env::panic_str(ERR_VALUE_DESERIALIZATION))
+            });
+            CacheEntry::new_cached(Some(value))
+        })
+    }
+
+    /// Returns the value that the next call to [`Counter::next`] returns, which is also the
+    /// number of values returned so far.
+    pub fn current(&self) -> T {
+        (*self.entry().value()).unwrap_or(T::ZERO)
+    }
+
+    /// Returns the current value and increments the counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the incremented value does not fit in `T`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> T {
+        self.checked_next().unwrap_or_else(|| env::panic_str(ERR_OVERFLOW))
+    }
+
+    /// Returns the current value and increments the counter, or returns `None` and leaves the
+    /// counter unchanged if the incremented value does not fit in `T`.
+    pub fn checked_next(&mut self) -> Option<T> {
+        let current = self.current();
+        let next = current.checked_increment()?;
+        let entry = self.cache.get_mut().unwrap_or_else(|| env::abort());
+        entry.replace(Some(next));
+        Some(current)
+    }
+
+    /// Writes the value of the counter to storage if it changed. This will automatically be done
+    /// when the counter is dropped through [`Drop`] so this should only be used when the changes
+    /// need to be reflected in the underlying storage before then.
+    pub fn flush(&mut self) {
+        if let Some(v) = self.cache.get_mut() {
+            if v.is_modified() {
+                let value = (*v.value()).unwrap_or(T::ZERO);
+                let serialized =
+                    value.try_to_vec().unwrap_or_else(|_| env::panic_str(ERR_VALUE_SERIALIZATION));
+                env::storage_write(&self.storage_key, &serialized);
+                v.replace_state(EntryState::Cached);
+            }
+        }
+    }
+}
+
+impl<T> Drop for Counter<T>
+where
+    T: CounterValue,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T> fmt::Debug for Counter<T>
+where
+    T: CounterValue,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Counter")
+            .field("storage_key", &self.storage_key)
+            .field("cache", &self.cache.get())
+            .finish()
+    }
+}
+
+impl<T> Transactional for Counter<T>
+where
+    T: CounterValue,
+{
+    type Snapshot = Snapshot<()>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        Snapshot(())
+    }
+
+    fn rollback(&mut self, _snapshot: Self::Snapshot) {
+        if matches!(self.cache.get(), Some(v) if v.is_modified()) {
+            // Clearing the cache makes the next access load the value from storage again.
+            self.cache = OnceCell::new();
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_and_reload() {
+        let mut counter = Counter::<u64>::new(b"c");
+        assert_eq!(counter.current(), 0);
+        counter.flush();
+        // Reading the counter does not write it.
+        assert!(!env::storage_has_key(b"c"));
+
+        assert_eq!((counter.next(), counter.next(), counter.next()), (0, 1, 2));
+        assert!(!env::storage_has_key(b"c"));
+        let serialized = counter.try_to_vec().unwrap();
+        drop(counter);
+        assert_eq!(env::storage_read(b"c"), Some(3u64.to_le_bytes().to_vec()));
+
+        let mut counter = Counter::<u64>::try_from_slice(&serialized).unwrap();
+        assert_eq!(counter.current(), 3);
+        assert_eq!(counter.next(), 3);
+    }
+
+    #[test]
+    fn overflow() {
+        env::storage_write(b"c", &(u128::MAX - 1).to_le_bytes());
+        let mut counter = Counter::<u128>::new(b"c");
+        assert_eq!(counter.checked_next(), Some(u128::MAX - 1));
+        assert_eq!(counter.checked_next(), None);
+        assert_eq!(counter.current(), u128::MAX);
+    }
+}
@@ -0,0 +1,489 @@
+mod impls;
+mod iter;
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+pub use self::iter::Iter;
+use super::{IndexMap, ERR_INCONSISTENT_STATE};
+use crate::{env, IntoStorageKey};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// A double-ended queue that stores its content on the trie, with `O(1)` pushes and pops at both
+/// ends. This implementation will load and store values in the underlying storage lazily.
+///
+/// Elements are stored by position in a window of indices that moves as elements are pushed and
+/// popped, wrapping around at [`u32::MAX`], so removing from the front does not move the other
+/// elements like [`Vector::swap_remove`](crate::store::Vector::swap_remove) or shifting would.
+///
+/// Like [`Vector`](crate::store::Vector), changes are cached and only written to storage when
+/// the queue is dropped, or when [`Deque::flush`] is called.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Deque;
+///
+/// let mut queue = Deque::new(b"q");
+/// queue.push_back(2);
+/// queue.push_back(3);
+/// queue.push_front(1);
+///
+/// assert_eq!(queue.len(), 3);
+/// assert_eq!(queue.front(), Some(&1));
+/// assert_eq!(queue.back(), Some(&3));
+///
+/// assert_eq!(queue.pop_front(), Some(1));
+/// assert_eq!(queue.pop_back(), Some(3));
+/// assert!(Iterator::eq(queue.iter(), [2].iter()));
+/// ```
+pub struct Deque<T>
+where
+    T: BorshSerialize,
+{
+    /// Storage index of the first element.
+    head: u32,
+    len: u32,
+    values: IndexMap<T>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.head, writer)?;
+        BorshSerialize::serialize(&self.len, writer)?;
+        BorshSerialize::serialize(&self.values, writer)?;
+        Ok(())
+    }
+}
+
+impl<T> BorshDeserialize for Deque<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            head: BorshDeserialize::deserialize(buf)?,
+            len: BorshDeserialize::deserialize(buf)?,
+            values: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new queue with zero elements. Prefixes storage access with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue: Deque<u8> = Deque::new(b"q");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { head: 0, len: 0, values: IndexMap::new(prefix) }
+    }
+
+    /// Returns the number of elements in the queue.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the queue contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Storage index of the element at `index` from the front.
+    fn storage_index(&self, index: u32) -> u32 {
+        self.head.wrapping_add(index)
+    }
+
+    fn increment_len(&mut self) {
+        self.len =
+            self.len.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+    }
+
+    /// Appends an element to the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_back(1);
+    /// queue.push_back(2);
+    /// assert_eq!(queue.back(), Some(&2));
+    /// ```
+    pub fn push_back(&mut self, element: T) {
+        let index = self.storage_index(self.len);
+        self.increment_len();
+        self.values.set(index, Some(element));
+    }
+
+    /// Prepends an element to the front of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_front(1);
+    /// queue.push_front(2);
+    /// assert_eq!(queue.front(), Some(&2));
+    /// ```
+    pub fn push_front(&mut self, element: T) {
+        self.increment_len();
+        self.head = self.head.wrapping_sub(1);
+        self.values.set(self.head, Some(element));
+    }
+
+    /// Removes all elements from the queue. This will remove all storage values for the
+    /// length of the [`Deque`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.push_back(1);
+    ///
+    /// queue.clear();
+    ///
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        for i in 0..self.len {
+            self.values.set(self.storage_index(i), None);
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.values.flush();
+    }
+}
+
+impl<T> Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Returns the element at `index` from the front, or `None` if it is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2, 3]);
+    ///
+    /// assert_eq!(queue.get(1), Some(&2));
+    /// assert_eq!(queue.get(3), None);
+    /// ```
+    pub fn get(&self, index: u32) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get(self.storage_index(index))
+    }
+
+    /// Returns a mutable reference to the element at `index` from the front, or `None` if it is
+    /// out of bounds.
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get_mut(self.storage_index(index))
+    }
+
+    /// Returns the first element, or `None` if the queue is empty.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a mutable reference to the first element, or `None` if the queue is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    /// Returns the last element, or `None` if the queue is empty.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns a mutable reference to the last element, or `None` if the queue is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.checked_sub(1)?)
+    }
+
+    /// Removes the first element and returns it, or `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2]);
+    ///
+    /// assert_eq!(queue.pop_front(), Some(1));
+    /// assert_eq!(queue.pop_front(), Some(2));
+    /// assert_eq!(queue.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        let prev = self.values.get_mut_inner(self.head).replace(None);
+        self.head = self.head.wrapping_add(1);
+        Some(expect_consistent_state(prev))
+    }
+
+    /// Removes the last element and returns it, or `None` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2]);
+    ///
+    /// assert_eq!(queue.pop_back(), Some(2));
+    /// assert_eq!(queue.pop_back(), Some(1));
+    /// assert_eq!(queue.pop_back(), None);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.len = self.len.checked_sub(1)?;
+        let prev = self.values.get_mut_inner(self.storage_index(self.len)).replace(None);
+        Some(expect_consistent_state(prev))
+    }
+
+    /// Removes up to `n` elements from the front of the queue and returns them in order, to
+    /// process a queue of work items a chunk at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2, 3]);
+    ///
+    /// assert_eq!(queue.pop_front_n(2), [1, 2]);
+    /// assert_eq!(queue.pop_front_n(2), [3]);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn pop_front_n(&mut self, n: u32) -> Vec<T> {
+        (0..n.min(self.len)).filter_map(|_| self.pop_front()).collect()
+    }
+
+    /// Removes up to `n` elements from the back of the queue and returns them in the order they
+    /// were removed, starting with the last element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([1, 2, 3]);
+    ///
+    /// assert_eq!(queue.pop_back_n(2), [3, 2]);
+    /// assert_eq!(queue.len(), 1);
+    /// ```
+    pub fn pop_back_n(&mut self, n: u32) -> Vec<T> {
+        (0..n.min(self.len)).filter_map(|_| self.pop_back()).collect()
+    }
+
+    /// Returns an iterator over the queue, from front to back. This iterator will lazily load
+    /// any values iterated over from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Deque;
+    ///
+    /// let mut queue = Deque::new(b"q");
+    /// queue.extend([2, 3]);
+    /// queue.push_front(1);
+    ///
+    /// assert!(Iterator::eq(queue.iter(), [1, 2, 3].iter()));
+    /// assert!(Iterator::eq(queue.iter().rev(), [3, 2, 1].iter()));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+}
+
+impl<T> fmt::Debug for Deque<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            fmt::Debug::fmt(&self.iter().collect::<Vec<_>>(), f)
+        } else {
+            f.debug_struct("Deque")
+                .field("head", &self.head)
+                .field("len", &self.len)
+                .field("prefix", &self.values.prefix)
+                .finish()
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::VecDeque;
+
+    use super::Deque;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn wraps_around() {
+        let mut queue = Deque::new(b"q");
+        queue.push_front(1u8);
+        queue.push_front(0);
+        queue.push_back(2);
+        assert_eq!(queue.head, u32::MAX - 1);
+        assert!(Iterator::eq(queue.iter(), [0, 1, 2].iter()));
+
+        queue.flush();
+        let mut queue = Deque::<u8>::try_from_slice(&queue.try_to_vec().unwrap()).unwrap();
+        assert_eq!(queue.pop_front(), Some(0));
+        assert_eq!(queue.pop_front(), Some(1));
+        assert_eq!(queue.head, 0);
+        assert_eq!(queue.get(0), Some(&2));
+
+        queue.clear();
+        queue.flush();
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        PushBack(u8),
+        PushFront(u8),
+        PopBack,
+        PopFront,
+        Set(u32, u8),
+        Get(u32),
+        Clear,
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn pop_n_wraps_around() {
+        let mut queue = Deque::new(b"q");
+        queue.extend(0..4u32);
+        assert_eq!(queue.pop_front_n(3), [0, 1, 2]);
+        // Pushed to the front, so that the queue wraps around the end of storage.
+        queue.push_front(10);
+        queue.push_front(11);
+        queue.push_back(4);
+
+        assert_eq!(queue.pop_back_n(2), [4, 3]);
+        assert_eq!(queue.pop_front_n(5), [11, 10]);
+        assert!(queue.is_empty());
+        assert!(queue.pop_back_n(1).is_empty());
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..1024 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sq = Deque::new(b"q");
+            let mut mq = VecDeque::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::PushBack(v) => {
+                            sq.push_back(v);
+                            mq.push_back(v);
+                        }
+                        Op::PushFront(v) => {
+                            sq.push_front(v);
+                            mq.push_front(v);
+                        }
+                        Op::PopBack => assert_eq!(sq.pop_back(), mq.pop_back()),
+                        Op::PopFront => assert_eq!(sq.pop_front(), mq.pop_front()),
+                        Op::Set(i, v) => {
+                            if sq.is_empty() {
+                                continue;
+                            }
+                            let i = i % sq.len();
+                            *sq.get_mut(i).unwrap() = v;
+                            mq[i as usize] = v;
+                        }
+                        Op::Get(i) => assert_eq!(sq.get(i), mq.get(i as usize)),
+                        Op::Clear => {
+                            sq.clear();
+                            mq.clear();
+                        }
+                        Op::Flush => sq.flush(),
+                        Op::Reset => {
+                            let serialized = sq.try_to_vec().unwrap();
+                            sq = Deque::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(sq.len() as usize, mq.len());
+                    assert_eq!(sq.front(), mq.front());
+                    assert_eq!(sq.back(), mq.back());
+                }
+            }
+
+            // After all operations, compare both queues
+            assert!(Iterator::eq(sq.iter(), mq.iter()));
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::ERR_INCONSISTENT_STATE;
+use crate::store::key::{Sha256, ToKey};
+use crate::store::{LookupMap, TreeMap};
+use crate::{env, IntoStorageKey, Timestamp};
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// Value of an entry with the time it expires at.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Expiring<V> {
+    expires_at: Timestamp,
+    value: V,
+}
+
+/// A map whose entries expire at a given block timestamp, like session keys, quotes or oracle
+/// values. Expired entries are treated as absent, and their storage is reclaimed with
+/// [`purge_expired`](Self::purge_expired).
+///
+/// Entries are stored in a [`LookupMap`] together with their expiry, and their keys are also kept
+/// in a [`TreeMap`] ordered by expiry, so that expired entries can be found without iterating over
+/// the ones that are still valid. An entry expires once [`env::block_timestamp`] reaches its
+/// expiry.
+///
+/// # Examples
+/// ```
+/// use near_sdk::{env, store::ExpiringMap};
+///
+/// const MINUTE: u64 = 60 * 1_000_000_000;
+///
+/// let mut sessions: ExpiringMap<String, u64> = ExpiringMap::new(b"s");
+/// sessions.insert("alice.near".to_string(), 1, env::block_timestamp() + 5 * MINUTE);
+/// assert_eq!(sessions.get("alice.near"), Some(&1));
+///
+/// // Expired entries are treated as absent, until they are purged.
+/// sessions.insert("bob.near".to_string(), 2, env::block_timestamp());
+/// assert_eq!(sessions.get("bob.near"), None);
+/// assert_eq!(sessions.purge_expired(100), 1);
+/// ```
+pub struct ExpiringMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    entries: LookupMap<K, Expiring<V>, H>,
+    expiries: TreeMap<(Timestamp, K), ()>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.entries, writer)?;
+        BorshSerialize::serialize(&self.expiries, writer)?;
+        Ok(())
+    }
+}
+
+impl<K, V, H> BorshDeserialize for ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            entries: BorshDeserialize::deserialize(buf)?,
+            expiries: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<K, V, H> fmt::Debug for ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone + fmt::Debug,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExpiringMap").field("expiries", &self.expiries).finish()
+    }
+}
+
+impl<K, V> ExpiringMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new map. Use `prefix` as a unique prefix for keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::ExpiringMap;
+    ///
+    /// let mut map: ExpiringMap<String, u8> = ExpiringMap::new(b"e");
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<K, V, H> ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize an [`ExpiringMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{ExpiringMap, key::Keccak256};
+    ///
+    /// let map = ExpiringMap::<String, String, Keccak256>::with_hasher(b"e");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            entries: LookupMap::with_hasher([prefix.as_slice(), b"e"].concat()),
+            expiries: TreeMap::new([prefix.as_slice(), b"x"].concat()),
+        }
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.entries.flush();
+        self.expiries.flush();
+    }
+}
+
+impl<K, V, H> ExpiringMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns the number of entries in the map, including expired entries that have not been
+    /// purged yet.
+    pub fn len(&self) -> u32 {
+        self.expiries.len()
+    }
+
+    /// Returns `true` if the map contains no entries, including expired entries that have not
+    /// been purged yet.
+    pub fn is_empty(&self) -> bool {
+        self.expiries.is_empty()
+    }
+
+    fn live<Q: ?Sized>(&self, k: &Q) -> Option<&Expiring<V>>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        let now = env::block_timestamp();
+        self.entries.get(k).filter(|entry| entry.expires_at > now)
+    }
+
+    /// Returns `true` if the map contains an entry for the key that has not expired.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.live(k).is_some()
+    }
+
+    /// Returns a reference to the value corresponding to the key, or `None` if there is no entry
+    /// for the key or it has expired.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.live(k).map(|entry| &entry.value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, or `None` if there is
+    /// no entry for the key or it has expired. The expiry of the entry is not changed.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        let now = env::block_timestamp();
+        self.entries.get_mut(k).filter(|entry| entry.expires_at > now).map(|entry| &mut entry.value)
+    }
+
+    /// Returns the timestamp the entry of the key expires at, or `None` if there is no entry for
+    /// the key or it has expired.
+    pub fn expires_at<Q: ?Sized>(&self, k: &Q) -> Option<Timestamp>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.live(k).map(|entry| entry.expires_at)
+    }
+
+    /// Inserts a key-value pair that expires once the block timestamp reaches `expires_at`, in
+    /// nanoseconds like [`env::block_timestamp`].
+    ///
+    /// If the map did have an entry for this key that has not expired, its value is returned.
+    /// The expiry of an existing entry is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::{env, store::ExpiringMap};
+    ///
+    /// let mut quotes: ExpiringMap<String, u128> = ExpiringMap::new(b"q");
+    /// let expires_at = env::block_timestamp() + 30_000_000_000;
+    /// assert_eq!(quotes.insert("near".to_string(), 5, expires_at), None);
+    /// assert_eq!(quotes.insert("near".to_string(), 6, expires_at), Some(5));
+    /// assert_eq!(quotes.expires_at("near"), Some(expires_at));
+    /// ```
+    pub fn insert(&mut self, k: K, value: V, expires_at: Timestamp) -> Option<V> {
+        self.expiries.insert((expires_at, k.clone()), ());
+        let now = env::block_timestamp();
+        let prev = self.entries.insert(k.clone(), Expiring { expires_at, value })?;
+        if prev.expires_at != expires_at {
+            expect_consistent_state(self.expiries.remove(&(prev.expires_at, k)));
+        }
+        if prev.expires_at > now {
+            Some(prev.value)
+        } else {
+            None
+        }
+    }
+
+    /// Removes a key from the map, returning its value if the entry had not expired. The storage
+    /// of the entry is removed either way.
+    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        let entry = self.entries.remove(k)?;
+        expect_consistent_state(self.expiries.remove(&(entry.expires_at, k.to_owned())));
+        if entry.expires_at > env::block_timestamp() {
+            Some(entry.value)
+        } else {
+            None
+        }
+    }
+
+    /// Removes up to `limit` expired entries from storage, earliest expiry first, and returns the
+    /// number of entries that were removed.
+    ///
+    /// Expired entries are already treated as absent, so this only needs to be called to reclaim
+    /// their storage. Only expired entries are loaded, so this can be called with a small limit
+    /// on every call of a contract method, or from a separate method.
+    pub fn purge_expired(&mut self, limit: u32) -> u32 {
+        let now = env::block_timestamp();
+        let expired: Vec<(Timestamp, K)> = self
+            .expiries
+            .keys()
+            .take_while(|(expires_at, _)| *expires_at <= now)
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        for key in &expired {
+            self.expiries.remove(key);
+            // Set instead of remove to avoid loading the value from storage.
+            self.entries.set(key.1.clone(), None);
+        }
+        expired.len() as u32
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::ExpiringMap;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    fn set_block_timestamp(timestamp: u64) {
+        testing_env!(VMContextBuilder::new().block_timestamp(timestamp).build());
+    }
+
+    #[test]
+    fn entries_expire() {
+        set_block_timestamp(100);
+
+        let mut map = ExpiringMap::new(b"e");
+        map.insert(1u8, 10u8, 200);
+        map.insert(2, 20, 300);
+        map.insert(3, 30, 150);
+        assert_eq!(map.insert(3, 31, 400), Some(30));
+        assert_eq!(map.len(), 3);
+
+        set_block_timestamp(200);
+        assert_eq!(map.get(&1), None);
+        assert!(!map.contains_key(&1));
+        assert_eq!(map.get(&2), Some(&20));
+        *map.get_mut(&2).unwrap() += 1;
+        assert_eq!(map.expires_at(&3), Some(400));
+
+        // Re-inserting an expired entry doesn't return the expired value.
+        assert_eq!(map.insert(1, 11, 250), None);
+        assert_eq!(map.len(), 3);
+
+        set_block_timestamp(300);
+        assert_eq!(map.purge_expired(1), 1);
+        assert_eq!(map.purge_expired(10), 1);
+        assert_eq!(map.purge_expired(10), 0);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.remove(&3), Some(31));
+        assert!(map.is_empty());
+    }
+}
@@ -1,10 +1,11 @@
 mod iter;
 pub use self::iter::{Drain, Iter, IterMut};
 
-use super::{Vector, ERR_INCONSISTENT_STATE};
+use super::{Vector, WritePolicy, ERR_INCONSISTENT_STATE};
 use crate::{env, IntoStorageKey};
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use rand_core::RngCore;
 
 use std::{fmt, mem};
 
@@ -116,17 +117,38 @@ where
         self.len() == 0
     }
 
-    /// Flushes cached changes to storage. This retains any cached values in memory.
+    /// Flushes cached changes to storage. This retains any cached values in memory, unless there
+    /// are more of them than the cache capacity.
     pub fn flush(&mut self) {
         self.elements.flush()
     }
 
+    /// Returns when the cells are written to storage.
+    pub fn write_policy(&self) -> WritePolicy {
+        self.elements.write_policy()
+    }
+
+    /// Sets when the cells are written to storage, see [`Vector::set_write_policy`].
+    pub fn set_write_policy(&mut self, policy: WritePolicy) {
+        self.elements.set_write_policy(policy)
+    }
+
+    /// Bounds the number of cells kept in memory, see [`Vector::set_cache_capacity`].
+    pub fn set_cache_capacity(&mut self, capacity: Option<u32>) {
+        self.elements.set_cache_capacity(capacity)
+    }
+
     /// Clears the bucket, removing all values (including removed entries).
     pub fn clear(&mut self) {
         self.elements.clear();
         self.first_free = None;
         self.occupied_count = 0;
     }
+
+    /// Returns the number of cells in the bucket, including removed entries.
+    pub fn slots(&self) -> u32 {
+        self.elements.len()
+    }
 }
 
 impl<T> FreeList<T>
@@ -200,6 +222,133 @@ where
         prev.into_value()
     }
 
+    /// Removes up to `max_slots` cells from the end of the bucket, including removed entries, and
+    /// returns the values that were removed.
+    ///
+    /// Removed entries are no longer reused for insertions after this is called, so that the
+    /// bucket stays consistent while it is cleared over multiple calls.
+    pub fn clear_chunk(&mut self, max_slots: u32) -> Vec<T> {
+        self.first_free = None;
+        let mut values = Vec::new();
+        for _ in 0..max_slots {
+            match self.elements.pop() {
+                Some(Slot::Occupied(value)) => {
+                    self.occupied_count -= 1;
+                    values.push(value);
+                }
+                Some(Slot::Empty { .. }) => {}
+                None => break,
+            }
+        }
+        values
+    }
+
+    /// Moves values from the end of the bucket into removed cells and removes the cells that are
+    /// left empty at the end, until the values are in the first [`len`](Self::len) cells. `moved`
+    /// is called with each value that is moved and its new index.
+    ///
+    /// At most `max_steps` cells are moved, removed or unlinked from the free list, so that the
+    /// bucket can be compacted over multiple calls, and it can be used as usual in-between them.
+    /// Returns `true` once the bucket has no removed cells left.
+    pub fn compact<F>(&mut self, max_steps: u32, mut moved: F) -> bool
+    where
+        F: FnMut(&T, FreeListIndex),
+    {
+        for _ in 0..max_steps {
+            let slots = self.slots();
+            if slots == self.occupied_count {
+                break;
+            }
+            let last = slots - 1;
+            if self.is_unlinked(last) {
+                self.elements.pop();
+                continue;
+            }
+
+            let free = match self.first_free {
+                Some(FreeListIndex(free)) => {
+                    self.first_free = match self.elements.get(free) {
+                        Some(Slot::Empty { next_free }) => *next_free,
+                        _ => env::panic_str(ERR_INCONSISTENT_STATE),
+                    };
+                    if free == last {
+                        self.elements.pop();
+                        continue;
+                    }
+                    if free >= self.occupied_count {
+                        // The cell ends up past the values, where it is removed once it is last.
+                        self.unlink(free);
+                        continue;
+                    }
+                    free
+                }
+                // Removed cells that are not linked are only found by looking for them, which
+                // happens when they were left below values inserted since the last call.
+                None => (0..last)
+                    .rev()
+                    .find(|&i| matches!(self.elements.get(i), Some(Slot::Empty { .. })))
+                    .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE)),
+            };
+
+            // There are as many values past `len` as removed cells before it, so one is found.
+            let from = (free + 1..slots)
+                .rev()
+                .find(|&i| matches!(self.elements.get(i), Some(Slot::Occupied(_))))
+                .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            let value = if from == last {
+                self.elements.pop()
+            } else {
+                Some(
+                    self.elements
+                        .replace(from, Slot::Empty { next_free: Some(FreeListIndex(from)) }),
+                )
+            };
+            let value = value.and_then(Slot::into_value).unwrap_or_else(|| env::abort());
+            moved(&value, FreeListIndex(free));
+            self.elements.set(free, Slot::Occupied(value));
+        }
+        self.slots() == self.occupied_count
+    }
+
+    /// Marks the removed cell at `index`, which is no longer linked from the free list, by
+    /// pointing it to itself, so that it can be told apart from the linked cells.
+    fn unlink(&mut self, index: u32) {
+        self.elements.set(index, Slot::Empty { next_free: Some(FreeListIndex(index)) });
+    }
+
+    /// Returns `true` if the cell at `index` is removed and not linked from the free list.
+    fn is_unlinked(&self, index: u32) -> bool {
+        match self.elements.get(index) {
+            Some(Slot::Empty { next_free }) => {
+                self.first_free.is_none() || *next_free == Some(FreeListIndex(index))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns a uniformly random filled cell and its index, or `None` if the bucket is empty.
+    ///
+    /// Cells are picked at random until a filled one is found, so this loads
+    /// [`slots`](Self::slots) / [`len`](Self::len) cells on average.
+    pub fn sample<R: RngCore>(&self, rng: &mut R) -> Option<(FreeListIndex, &T)> {
+        if self.is_empty() {
+            return None;
+        }
+        let slots = self.slots() as u64;
+        // Reject the last incomplete multiple of `slots` to avoid modulo bias.
+        let limit = u64::MAX - u64::MAX % slots;
+        loop {
+            let value = rng.next_u64();
+            if value >= limit {
+                continue;
+            }
+            let index = FreeListIndex((value % slots) as u32);
+            if let Some(value) = self.get(index) {
+                return Some((index, value));
+            }
+        }
+    }
+
     /// Generates iterator for shared references to each value in the bucket.
     pub fn iter(&self) -> Iter<T> {
         Iter::new(self)
@@ -222,6 +371,26 @@ where
     }
 }
 
+#[cfg(feature = "unstable")]
+impl<T> crate::store::Transactional for FreeList<T>
+where
+    T: BorshSerialize,
+{
+    type Snapshot =
+        crate::store::Snapshot<(Option<FreeListIndex>, u32, crate::store::Snapshot<u32>)>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        crate::store::Snapshot((self.first_free, self.occupied_count, self.elements.snapshot()))
+    }
+
+    fn rollback(&mut self, snapshot: Self::Snapshot) {
+        let (first_free, occupied_count, elements) = snapshot.0;
+        self.first_free = first_free;
+        self.occupied_count = occupied_count;
+        self.elements.rollback(elements);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -346,6 +515,43 @@ mod tests {
         crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
     }
 
+    #[test]
+    fn compact_across_calls() {
+        let mut bucket = FreeList::new(b"b");
+        bucket.extend(0..100u32);
+        // Remove the last values in reverse order, so the last cell is not the first free one.
+        for i in (50..100).chain((0..50).step_by(2)) {
+            bucket.remove(FreeListIndex(i));
+        }
+        let mut moves = HashMap::new();
+        assert!(!bucket.compact(20, |&v, i| assert!(moves.insert(v, i.0).is_none())));
+
+        // The bucket stays usable in-between the calls.
+        let inserted = bucket.insert(100);
+        assert_eq!(bucket.remove(FreeListIndex(1)), Some(1));
+        assert_eq!(bucket.len(), 25);
+
+        while !bucket.compact(20, |&v, i| {
+            moves.insert(v, i.0);
+        }) {}
+        assert_eq!(bucket.slots(), 25);
+        assert_eq!(bucket.first_free, None);
+        for (&v, &i) in &moves {
+            assert_eq!(bucket.get(FreeListIndex(i)), Some(&v));
+        }
+        if !moves.contains_key(&100) {
+            assert_eq!(bucket.get(inserted), Some(&100));
+        }
+        let mut values: Vec<_> = bucket.iter().copied().collect();
+        values.sort_unstable();
+        let expected: Vec<_> = (3..50).step_by(2).chain([100]).collect();
+        assert_eq!(values, expected);
+
+        // Removed cells at the end were removed from storage.
+        bucket.flush();
+        crate::mock::with_mocked_blockchain(|m| assert_eq!(m.take_storage().len(), 25));
+    }
+
     #[derive(Arbitrary, Debug)]
     enum Op {
         Insert(u8),
@@ -354,6 +560,7 @@ mod tests {
         Reset,
         Get(u32),
         Clear,
+        Compact(u8),
     }
 
     #[test]
@@ -402,6 +609,19 @@ mod tests {
                             sv.clear();
                             hm.clear();
                         }
+                        Op::Compact(max_steps) => {
+                            let done = sv.compact(max_steps.into(), |_, _| {});
+                            assert_eq!(done, sv.slots() == sv.len());
+
+                            let mut before: Vec<_> = hm.values().copied().collect();
+                            hm = (0..sv.slots())
+                                .filter_map(|i| sv.get(FreeListIndex(i)).map(|v| (i, *v)))
+                                .collect();
+                            let mut after: Vec<_> = hm.values().copied().collect();
+                            before.sort_unstable();
+                            after.sort_unstable();
+                            assert_eq!(before, after);
+                        }
                     }
                 }
             }
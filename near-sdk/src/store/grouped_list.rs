@@ -0,0 +1,450 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::store::key::{Identity, ToKey};
+use crate::store::{vec, ClearProgress, LookupMap, Page, Paginate, Vector};
+use crate::{env, IntoStorageKey};
+
+/// Lists of items grouped by a key, like all the items belonging to an account, stored on the
+/// storage trie.
+///
+/// The items of each group are stored in their own [`Vector`], under a prefix derived from the
+/// collection prefix and the hash of the group, so the items of one group can be appended,
+/// counted and read a page at a time without loading those of any other group. A group is present
+/// as long as it has at least one item, and is removed along with its list once it is emptied.
+///
+/// Like [`LookupMap`], the groups themselves cannot be iterated over.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::GroupedList;
+///
+/// let mut orders: GroupedList<String, u64> = GroupedList::new(b"o");
+/// orders.push("alice.near".to_string(), 10);
+/// orders.push("alice.near".to_string(), 20);
+/// orders.push("bob.near".to_string(), 30);
+///
+/// assert_eq!(orders.len("alice.near"), 2);
+/// assert_eq!(orders.get("alice.near", 1), Some(&20));
+/// assert_eq!(orders.iter("bob.near").collect::<Vec<_>>(), [&30]);
+///
+/// assert_eq!(orders.remove_group("alice.near"), 2);
+/// assert!(!orders.contains_group("alice.near"));
+/// ```
+pub struct GroupedList<G, T, H = Identity>
+where
+    G: BorshSerialize + Ord,
+    T: BorshSerialize,
+    H: ToKey,
+{
+    prefix: Box<[u8]>,
+    groups: LookupMap<G, Vector<T>, H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<G, T, H> BorshSerialize for GroupedList<G, T, H>
+where
+    G: BorshSerialize + Ord,
+    T: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.prefix, writer)?;
+        BorshSerialize::serialize(&self.groups, writer)?;
+        Ok(())
+    }
+}
+
+impl<G, T, H> BorshDeserialize for GroupedList<G, T, H>
+where
+    G: BorshSerialize + Ord,
+    T: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            prefix: BorshDeserialize::deserialize(buf)?,
+            groups: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<G, T, H> Drop for GroupedList<G, T, H>
+where
+    G: BorshSerialize + Ord,
+    T: BorshSerialize,
+    H: ToKey,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<G, T, H> fmt::Debug for GroupedList<G, T, H>
+where
+    G: BorshSerialize + Ord,
+    T: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupedList").field("prefix", &self.prefix).finish()
+    }
+}
+
+impl<G, T> GroupedList<G, T, Identity>
+where
+    G: BorshSerialize + Ord,
+    T: BorshSerialize,
+{
+    /// Create a new collection. Use `prefix` as a unique prefix for keys.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::GroupedList;
+    ///
+    /// let mut list: GroupedList<String, u64> = GroupedList::new(b"g");
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<G, T, H> GroupedList<G, T, H>
+where
+    G: BorshSerialize + Ord,
+    T: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize a [`GroupedList`] with a custom hash function for the groups.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{GroupedList, key::Keccak256};
+    ///
+    /// let list = GroupedList::<String, String, Keccak256>::with_hasher(b"g");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key().into_boxed_slice();
+        let groups = LookupMap::with_hasher([&prefix[..], b"g"].concat());
+        Self { prefix, groups }
+    }
+
+    /// Storage prefix of the list of items of `group`.
+    fn items_prefix(prefix: &[u8], group: &G) -> Vec<u8> {
+        let group = group.try_to_vec().unwrap_or_else(|_| env::panic_str("Cannot serialize key"));
+        [prefix, b"i", &env::sha256_array(&group)].concat()
+    }
+
+    /// Returns `true` if `g` has at least one item.
+    pub fn contains_group<Q: ?Sized>(&self, g: &Q) -> bool
+    where
+        G: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = G> + Ord,
+    {
+        self.groups.contains_key(g)
+    }
+
+    /// Returns the list of items of `g`, or `None` if the group has no items.
+    pub fn get_group<Q: ?Sized>(&self, g: &Q) -> Option<&Vector<T>>
+    where
+        G: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        self.groups.get(g)
+    }
+
+    /// Returns the number of items of `g`.
+    pub fn len<Q: ?Sized>(&self, g: &Q) -> u32
+    where
+        G: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        self.get_group(g).map_or(0, Vector::len)
+    }
+
+    /// Appends `item` to the items of `group`, and returns its index in the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::GroupedList;
+    ///
+    /// let mut list = GroupedList::new(b"g");
+    /// assert_eq!(list.push(1u8, "a".to_string()), 0);
+    /// assert_eq!(list.push(1, "b".to_string()), 1);
+    /// assert_eq!(list.push(2, "c".to_string()), 0);
+    /// ```
+    pub fn push(&mut self, group: G, item: T) -> u32
+    where
+        G: Clone + BorshDeserialize,
+    {
+        let prefix = &self.prefix;
+        let items = self
+            .groups
+            .entry(group)
+            .or_insert_with_key(|group| Vector::new(Self::items_prefix(prefix, group)));
+        items.push(item);
+        items.len() - 1
+    }
+
+    /// Removes all items of `g`, returning how many there were.
+    ///
+    /// The items are removed from storage without being loaded. For groups that are too large
+    /// to remove within one function call, use [`clear_group_chunk`](Self::clear_group_chunk).
+    pub fn remove_group<Q: ?Sized>(&mut self, g: &Q) -> u32
+    where
+        G: Borrow<Q> + Clone + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        match self.groups.remove(g) {
+            Some(mut items) => {
+                let count = items.len();
+                items.clear();
+                count
+            }
+            None => 0,
+        }
+    }
+
+    /// Removes up to `max_entries` items from the back of the items of `g`, and removes the group
+    /// once it has no items left, to remove a large group over multiple calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{ClearProgress, GroupedList};
+    ///
+    /// let mut list = GroupedList::new(b"g");
+    /// for i in 0..150u32 {
+    ///     list.push(1u8, i);
+    /// }
+    ///
+    /// assert_eq!(list.clear_group_chunk(&1, 100), ClearProgress::Remaining(50));
+    /// assert_eq!(list.clear_group_chunk(&1, 100), ClearProgress::Done);
+    /// assert!(!list.contains_group(&1));
+    /// ```
+    pub fn clear_group_chunk<Q: ?Sized>(&mut self, g: &Q, max_entries: u32) -> ClearProgress
+    where
+        G: Borrow<Q> + Clone + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        let progress = match self.groups.get_mut(g) {
+            Some(items) => items.clear_chunk(max_entries),
+            None => return ClearProgress::Done,
+        };
+        if progress.is_done() {
+            self.groups.remove(g);
+        }
+        progress
+    }
+
+    /// Flushes the intermediate values of the collection, and of the lists of items that were
+    /// loaded, before this is called when the structure is [`Drop`]ed. This will write all
+    /// modified values to storage but keep all cached values in memory.
+    pub fn flush(&mut self) {
+        for items in self.groups.cached_values_mut() {
+            items.flush();
+        }
+        self.groups.flush();
+    }
+}
+
+impl<G, T, H> GroupedList<G, T, H>
+where
+    G: BorshSerialize + BorshDeserialize + Ord,
+    T: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns the item at `index` of `g`, or `None` if it is out of bounds.
+    pub fn get<Q: ?Sized>(&self, g: &Q, index: u32) -> Option<&T>
+    where
+        G: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        self.get_group(g)?.get(index)
+    }
+
+    /// Returns a mutable reference to the item at `index` of `g`, or `None` if it is out of
+    /// bounds.
+    pub fn get_mut<Q: ?Sized>(&mut self, g: &Q, index: u32) -> Option<&mut T>
+    where
+        G: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        self.groups.get_mut(g)?.get_mut(index)
+    }
+
+    /// Removes the last item of `g` and returns it, and removes the group once it has no items
+    /// left.
+    pub fn pop<Q: ?Sized>(&mut self, g: &Q) -> Option<T>
+    where
+        G: Borrow<Q> + Clone,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        let items = self.groups.get_mut(g)?;
+        let item = items.pop();
+        if items.is_empty() {
+            self.groups.remove(g);
+        }
+        item
+    }
+
+    /// Returns an iterator over the items of `g`, in the order they were pushed. The iterator is
+    /// empty if the group has no items.
+    pub fn iter<Q: ?Sized>(&self, g: &Q) -> Items<'_, T>
+    where
+        G: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        Items { inner: self.get_group(g).map(Vector::iter) }
+    }
+
+    /// Returns up to `limit` items of `g`, starting from the item at `from_index`. Only the
+    /// items of the page are loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::GroupedList;
+    ///
+    /// let mut list = GroupedList::new(b"g");
+    /// for i in 0..5u32 {
+    ///     list.push("alice.near".to_string(), i);
+    /// }
+    ///
+    /// let page = list.page("alice.near", 3, 10);
+    /// assert_eq!(page.items, [&3, &4]);
+    /// assert_eq!(page.next_cursor, None);
+    /// ```
+    pub fn page<Q: ?Sized>(&self, g: &Q, from_index: u32, limit: u32) -> Page<&T>
+    where
+        G: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = G>,
+    {
+        match self.get_group(g) {
+            Some(items) => items.page(from_index, limit),
+            None => Page { items: Vec::new(), next_cursor: None },
+        }
+    }
+}
+
+/// An iterator over the items of a group of a [`GroupedList`], returned by
+/// [`GroupedList::iter`].
+pub struct Items<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    inner: Option<vec::Iter<'a, T>>,
+}
+
+impl<'a, T> Iterator for Items<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.as_ref().map_or((0, Some(0)), Iterator::size_hint)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.as_mut()?.nth(n)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Items<'a, T> where T: BorshSerialize + BorshDeserialize {}
+impl<'a, T> FusedIterator for Items<'a, T> where T: BorshSerialize + BorshDeserialize {}
+
+impl<'a, T> DoubleEndedIterator for Items<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next_back()
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.as_mut()?.nth_back(n)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use std::collections::BTreeMap;
+
+    use super::GroupedList;
+    use crate::store::ClearProgress;
+
+    #[test]
+    fn groups_are_independent() {
+        let mut list = GroupedList::new(b"g");
+        let mut baseline = BTreeMap::<u8, Vec<u32>>::new();
+        for i in 0..200u32 {
+            let group = (i % 7) as u8;
+            assert_eq!(list.push(group, i) as usize, baseline.entry(group).or_default().len());
+            baseline.get_mut(&group).unwrap().push(i);
+        }
+        assert_eq!(list.pop(&3), baseline.get_mut(&3).unwrap().pop());
+        *list.get_mut(&4, 2).unwrap() += 1000;
+        baseline.get_mut(&4).unwrap()[2] += 1000;
+        assert_eq!(list.remove_group(&5), baseline.remove(&5).unwrap().len() as u32);
+        assert_eq!(list.remove_group(&5), 0);
+
+        list.flush();
+        let serialized = list.try_to_vec().unwrap();
+        let restored = GroupedList::<u8, u32>::try_from_slice(&serialized).unwrap();
+        for group in 0..8u8 {
+            let expected = baseline.get(&group).cloned().unwrap_or_default();
+            assert_eq!(restored.iter(&group).copied().collect::<Vec<_>>(), expected);
+            assert_eq!(restored.len(&group) as usize, expected.len());
+            assert_eq!(restored.contains_group(&group), !expected.is_empty());
+        }
+        assert_eq!(restored.page(&6, 20, 100).items.len(), baseline[&6].len() - 20);
+    }
+
+    #[test]
+    fn emptied_groups_leave_no_storage() {
+        let mut list = GroupedList::new(b"g");
+        list.push("a".to_string(), 1u8);
+        list.push("a".to_string(), 2);
+        list.push("b".to_string(), 3);
+        for i in 0..10 {
+            list.push("c".to_string(), i);
+        }
+
+        assert_eq!(list.pop("b"), Some(3));
+        assert!(!list.contains_group("b"));
+        assert_eq!(list.remove_group("a"), 2);
+        assert_eq!(list.clear_group_chunk("c", 6), ClearProgress::Remaining(4));
+        assert_eq!(list.len("c"), 4);
+        assert_eq!(list.clear_group_chunk("c", 6), ClearProgress::Done);
+        assert_eq!(list.clear_group_chunk("c", 6), ClearProgress::Done);
+
+        drop(list);
+        crate::mock::with_mocked_blockchain(|b| assert!(b.take_storage().is_empty()));
+    }
+}
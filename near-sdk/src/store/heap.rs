@@ -0,0 +1,336 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::vec::Iter;
+use super::Vector;
+use crate::{env, IntoStorageKey};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+
+/// A priority queue implemented with a binary heap that stores its content on the trie. This
+/// implementation will load and store values in the underlying storage lazily.
+///
+/// This is a max-heap, like [`std::collections::BinaryHeap`]: [`Heap::pop`] returns the greatest
+/// element. The elements are stored in a [`Vector`], so pushing and popping only load and write
+/// the `O(log n)` elements on the path between the root and a leaf, and [`Heap::peek`] only
+/// loads the root.
+///
+/// For a min-heap, wrap the elements in a type whose [`Ord`] implementation is reversed.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::Heap;
+///
+/// let mut heap = Heap::new(b"h");
+/// heap.push(3);
+/// heap.push(7);
+/// heap.push(1);
+///
+/// assert_eq!(heap.peek(), Some(&7));
+/// assert_eq!(heap.pop(), Some(7));
+/// assert_eq!(heap.pop(), Some(3));
+/// assert_eq!(heap.len(), 1);
+/// ```
+pub struct Heap<T>
+where
+    T: BorshSerialize,
+{
+    data: Vector<T>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for Heap<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.data, writer)
+    }
+}
+
+impl<T> BorshDeserialize for Heap<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self { data: BorshDeserialize::deserialize(buf)? })
+    }
+}
+
+impl<T> Heap<T>
+where
+    T: BorshSerialize,
+{
+    /// Create new heap with zero elements. Prefixes storage access with the prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Heap;
+    ///
+    /// let mut heap: Heap<u64> = Heap::new(b"h");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { data: Vector::new(prefix) }
+    }
+
+    /// Returns the number of elements in the heap.
+    pub fn len(&self) -> u32 {
+        self.data.len()
+    }
+
+    /// Returns `true` if the heap contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Removes all elements from the heap. This will remove all storage values for the
+    /// length of the [`Heap`].
+    pub fn clear(&mut self) {
+        self.data.clear()
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.data.flush()
+    }
+}
+
+impl<T> Heap<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn element(&self, index: u32) -> &T {
+        self.data.get(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
+    }
+
+    /// Returns the greatest element, or `None` if the heap is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Heap;
+    ///
+    /// let mut heap = Heap::new(b"h");
+    /// assert_eq!(heap.peek(), None);
+    ///
+    /// heap.extend([1, 5, 2]);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.data.get(0)
+    }
+
+    /// Pushes an element onto the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Heap;
+    ///
+    /// let mut heap = Heap::new(b"h");
+    /// heap.push(3);
+    /// heap.push(5);
+    ///
+    /// assert_eq!(heap.len(), 2);
+    /// assert_eq!(heap.peek(), Some(&5));
+    /// ```
+    pub fn push(&mut self, element: T) {
+        self.data.push(element);
+        self.sift_up(self.data.len() - 1);
+    }
+
+    /// Removes the greatest element from the heap and returns it, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Heap;
+    ///
+    /// let mut heap = Heap::new(b"h");
+    /// heap.extend([1, 3]);
+    ///
+    /// assert_eq!(heap.pop(), Some(3));
+    /// assert_eq!(heap.pop(), Some(1));
+    /// assert_eq!(heap.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.data.len().checked_sub(1)?;
+        self.data.values.swap(0, last);
+        let greatest = self.data.pop();
+        self.sift_down(0);
+        greatest
+    }
+
+    /// Returns an iterator over the elements of the heap, in arbitrary order. This iterator will
+    /// lazily load any values iterated over from storage.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.data.iter()
+    }
+
+    /// Moves the element at `index` up until its parent is greater than or equal to it.
+    fn sift_up(&mut self, mut index: u32) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if self.element(index) <= self.element(parent) {
+                break;
+            }
+            self.data.values.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    /// Moves the element at `index` down until it is greater than or equal to its children.
+    fn sift_down(&mut self, mut index: u32) {
+        let len = u64::from(self.data.len());
+        loop {
+            let left = 2 * u64::from(index) + 1;
+            if left >= len {
+                break;
+            }
+            // Indices are less than the length, so they fit in `u32`.
+            let mut child = left as u32;
+            if left + 1 < len && self.element(child + 1) > self.element(child) {
+                child += 1;
+            }
+            if self.element(child) <= self.element(index) {
+                break;
+            }
+            self.data.values.swap(index, child);
+            index = child;
+        }
+    }
+}
+
+impl<T> Extend<T> for Heap<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item)
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Heap<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> fmt::Debug for Heap<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            fmt::Debug::fmt(&self.data.iter().collect::<Vec<_>>(), f)
+        } else {
+            f.debug_struct("Heap")
+                .field("len", &self.data.len())
+                .field("prefix", &self.data.values.prefix)
+                .finish()
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::BinaryHeap;
+
+    use super::Heap;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn pops_in_order() {
+        let mut heap = Heap::new(b"h");
+        heap.extend([5u32, 1, 8, 3, 9, 2, 8]);
+        heap.flush();
+
+        let mut heap = Heap::<u32>::try_from_slice(&heap.try_to_vec().unwrap()).unwrap();
+        let mut popped = Vec::new();
+        while let Some(element) = heap.pop() {
+            popped.push(element);
+        }
+        assert_eq!(popped, [9, 8, 8, 5, 3, 2, 1]);
+        assert!(heap.is_empty());
+
+        heap.flush();
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Push(u8),
+        Pop,
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..1024 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sh = Heap::new(b"h");
+            let mut mh = BinaryHeap::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Push(v) => {
+                            sh.push(v);
+                            mh.push(v);
+                        }
+                        Op::Pop => assert_eq!(sh.pop(), mh.pop()),
+                        Op::Flush => sh.flush(),
+                        Op::Reset => {
+                            let serialized = sh.try_to_vec().unwrap();
+                            sh = Heap::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(sh.len() as usize, mh.len());
+                    assert_eq!(sh.peek(), mh.peek());
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::BTreeSet;
 use std::fmt;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
 
+use super::WritePolicy;
 use crate::utils::StableMap;
 use crate::{env, CacheEntry, EntryState, IntoStorageKey};
 
@@ -23,6 +25,15 @@ where
     /// Some functionality would be different from tests to Wasm if exceeding 32-bit length.
     #[borsh_skip]
     pub(crate) cache: StableMap<u32, OnceCell<CacheEntry<T>>>,
+    #[borsh_skip]
+    pub(crate) write_policy: WritePolicy,
+    /// Number of entries the cache can hold before it is emptied by a flush.
+    #[borsh_skip]
+    pub(crate) cache_capacity: Option<u32>,
+    /// Indices of the entries given out for changes since the last flush under
+    /// [`WritePolicy::WriteThrough`], which are the only entries the next change has to write.
+    #[borsh_skip]
+    touched: BTreeSet<u32>,
 }
 
 impl<T> IndexMap<T>
@@ -37,7 +48,13 @@ where
     where
         S: IntoStorageKey,
     {
-        Self { prefix: prefix.into_storage_key().into_boxed_slice(), cache: Default::default() }
+        Self {
+            prefix: prefix.into_storage_key().into_boxed_slice(),
+            cache: Default::default(),
+            write_policy: WritePolicy::WriteBack,
+            cache_capacity: None,
+            touched: BTreeSet::new(),
+        }
     }
 
     fn index_to_lookup_key(prefix: &[u8], index: u32, buf: &mut Vec<u8>) {
@@ -45,40 +62,113 @@ where
         buf.extend_from_slice(&index.to_le_bytes());
     }
 
+    /// Writes the entry at `index` to storage if it was modified, and marks it as cached.
+    fn write_entry(
+        prefix: &[u8],
+        index: u32,
+        entry: &mut OnceCell<CacheEntry<T>>,
+        buf: &mut Vec<u8>,
+        key_buf: &mut Vec<u8>,
+    ) {
+        if let Some(v) = entry.get_mut() {
+            if v.is_modified() {
+                key_buf.clear();
+                Self::index_to_lookup_key(prefix, index, key_buf);
+                match v.value().as_ref() {
+                    Some(modified) => {
+                        buf.clear();
+                        BorshSerialize::serialize(modified, buf)
+                            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+                        env::storage_write(key_buf, buf);
+                    }
+                    None => {
+                        // Element was removed, clear the storage for the value
+                        env::storage_remove(key_buf);
+                    }
+                }
+
+                // Update state of flushed state as cached, to avoid duplicate writes/removes
+                // while also keeping the cached values in memory.
+                v.replace_state(EntryState::Cached);
+            }
+        }
+    }
+
     /// Flushes the cache and writes all modified values to storage.
     pub fn flush(&mut self) {
         let mut buf = Vec::new();
         // Capacity is prefix length plus bytes needed for u32 bytes (4*u8)
         let mut key_buf = Vec::with_capacity(self.prefix.len() + 4);
         for (k, v) in self.cache.inner().iter_mut() {
-            if let Some(v) = v.get_mut() {
-                if v.is_modified() {
-                    key_buf.clear();
-                    Self::index_to_lookup_key(&self.prefix, *k, &mut key_buf);
-                    match v.value().as_ref() {
-                        Some(modified) => {
-                            buf.clear();
-                            BorshSerialize::serialize(modified, &mut buf)
-                                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
-                            env::storage_write(&key_buf, &buf);
-                        }
-                        None => {
-                            // Element was removed, clear the storage for the value
-                            env::storage_remove(&key_buf);
-                        }
-                    }
+            Self::write_entry(&self.prefix, *k, v, &mut buf, &mut key_buf);
+        }
+        self.touched.clear();
+        if self.is_over_capacity() {
+            // Every entry was just written, so the cache can be emptied without losing changes.
+            self.cache.inner().clear();
+        }
+    }
 
-                    // Update state of flushed state as cached, to avoid duplicate writes/removes
-                    // while also keeping the cached values in memory.
-                    v.replace_state(EntryState::Cached);
-                }
+    /// Writes the entries given out for changes since the last flush, which under
+    /// [`WritePolicy::WriteThrough`] are the only ones that can be modified.
+    fn flush_touched(&mut self) {
+        let mut buf = Vec::new();
+        let mut key_buf = Vec::with_capacity(self.prefix.len() + 4);
+        for index in std::mem::take(&mut self.touched) {
+            // Entries discarded by a rollback are not in the cache anymore.
+            if let Some(v) = self.cache.inner().get_mut(&index) {
+                Self::write_entry(&self.prefix, index, v, &mut buf, &mut key_buf);
             }
         }
     }
 
+    /// Writes the changes if the map writes through, or all of them and empties the cache if it
+    /// is over capacity.
+    pub(crate) fn write_through(&mut self) {
+        if self.is_over_capacity() {
+            self.flush();
+        } else if self.write_policy == WritePolicy::WriteThrough {
+            self.flush_touched();
+        }
+    }
+
+    /// Sets when the map writes its changes. Changes made before switching to
+    /// [`WritePolicy::WriteThrough`] are not tracked, so they are all written.
+    pub(crate) fn set_write_policy(&mut self, policy: WritePolicy) {
+        self.write_policy = policy;
+        if policy == WritePolicy::WriteThrough {
+            self.flush();
+        }
+    }
+
+    /// Records that the entry at `index` is given out for a change, if the map writes through.
+    fn touch(&mut self, index: u32) {
+        if self.write_policy == WritePolicy::WriteThrough {
+            self.touched.insert(index);
+        }
+    }
+
+    /// Returns `true` if the cache holds more entries than its capacity.
+    pub(crate) fn is_over_capacity(&mut self) -> bool {
+        matches!(self.cache_capacity, Some(capacity) if self.cache.inner().len() > capacity as usize)
+    }
+
+    /// Empties the cache, after writing the changes, if it is full, so that loading another
+    /// entry does not go over its capacity. Only called with `&mut self`, as there can be no
+    /// references into the cache then.
+    fn make_room(&mut self) {
+        if matches!(self.cache_capacity, Some(capacity) if self.cache.inner().len() >= capacity as usize)
+        {
+            self.flush();
+            self.cache.inner().clear();
+        }
+    }
+
     /// Sets a value at a given index to the value provided. If none is provided, this index will
     /// be removed from storage.
     pub fn set(&mut self, index: u32, value: Option<T>) {
+        self.make_room();
+        self.touch(index);
         let entry = self.cache.get_mut(index);
         match entry.get_mut() {
             Some(entry) => *entry.value_mut() = value,
@@ -112,6 +202,15 @@ where
 
     /// Returns a mutable reference to the element at the `index` provided.
     pub(crate) fn get_mut_inner(&mut self, index: u32) -> &mut CacheEntry<T> {
+        self.make_room();
+        self.get_mut_without_eviction(index)
+    }
+
+    /// Like [`get_mut_inner`](Self::get_mut_inner), but never empties the cache, so that the
+    /// references returned by earlier calls stay valid. Used by mutable iterators, which give out
+    /// references that outlive the calls.
+    pub(crate) fn get_mut_without_eviction(&mut self, index: u32) -> &mut CacheEntry<T> {
+        self.touch(index);
         let prefix = &self.prefix;
         let entry = self.cache.get_mut(index);
         entry.get_or_init(|| {
@@ -9,6 +9,8 @@ mod private {
     impl Sealed for super::Sha256 {}
     impl Sealed for super::Keccak256 {}
     impl Sealed for super::Identity {}
+    impl Sealed for super::Sha256Truncated8 {}
+    impl Sealed for super::Sha256Truncated16 {}
 }
 
 /// Trait used to generate keys to store data based on a serializable structure.
@@ -60,6 +62,9 @@ impl ToKey for Keccak256 {
 }
 
 /// Identity hash which just prefixes all of the serializes bytes and uses it as the key.
+///
+/// Keys can never collide, but the storage key is as long as the prefix plus the serialized key,
+/// so this is only cheaper than a hash for small keys such as integers or short account IDs.
 pub enum Identity {}
 
 impl ToKey for Identity {
@@ -76,3 +81,61 @@ impl ToKey for Identity {
         buffer.clone()
     }
 }
+
+/// Hashes the prefixed key with `sha256` and keeps the first `N` bytes of the hash.
+fn truncated_sha256<Q: ?Sized, const N: usize>(
+    prefix: &[u8],
+    key: &Q,
+    buffer: &mut Vec<u8>,
+) -> [u8; N]
+where
+    Q: BorshSerialize,
+{
+    buffer.extend(prefix);
+    key.serialize(buffer).unwrap_or_else(|_| env::abort());
+
+    let mut truncated = [0; N];
+    truncated.copy_from_slice(&env::sha256_array(buffer)[..N]);
+    truncated
+}
+
+/// [`Sha256`] hash truncated to its first 8 bytes, to reduce the storage cost of each entry.
+/// This type satisfies the [`ToKey`] trait.
+///
+/// Keys of different entries, or of entries in other collections, collide with a probability of
+/// about `n² / 2^65` for `n` entries in storage, so around `3 * 10^-8` for a million entries. A
+/// collision makes two entries share a value, and can be caused on purpose by anyone able to
+/// search for one with about `2^32` hashes, so only use this hasher when keys are not chosen by
+/// untrusted users. Otherwise, prefer [`Sha256Truncated16`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Sha256Truncated8 {}
+
+impl ToKey for Sha256Truncated8 {
+    type KeyType = [u8; 8];
+
+    fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
+    where
+        Q: BorshSerialize,
+    {
+        truncated_sha256(prefix, key, buffer)
+    }
+}
+
+/// [`Sha256`] hash truncated to its first 16 bytes, to reduce the storage cost of each entry.
+/// This type satisfies the [`ToKey`] trait.
+///
+/// With 128 bits, keys are not expected to collide for any realistic number of entries, and
+/// finding a collision on purpose would take about `2^64` hashes.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Sha256Truncated16 {}
+
+impl ToKey for Sha256Truncated16 {
+    type KeyType = [u8; 16];
+
+    fn to_key<Q: ?Sized>(prefix: &[u8], key: &Q, buffer: &mut Vec<u8>) -> Self::KeyType
+    where
+        Q: BorshSerialize,
+    {
+        truncated_sha256(prefix, key, buffer)
+    }
+}
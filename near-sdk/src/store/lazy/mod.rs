@@ -3,6 +3,9 @@
 //! If the underlying value is large, e.g. the contract needs to store an image, but it doesn't need
 //! to have access to this image at regular calls, then the contract can wrap this image into
 //! [`Lazy`] and it will not be deserialized until requested.
+//!
+//! Fields of a `#[near_bindgen]` contract struct can also be marked with `#[lazy]`, which replaces
+//! their type `T` with [`Lazy<T>`](Lazy).
 
 mod impls;
 
@@ -11,6 +14,7 @@ use once_cell::unsync::OnceCell;
 
 use crate::collections::ERR_INCONSISTENT_STATE;
 use crate::env;
+use crate::store::{Snapshot, Transactional};
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
 
@@ -147,6 +151,25 @@ where
     }
 }
 
+impl<T> Transactional for Lazy<T>
+where
+    T: BorshSerialize,
+{
+    type Snapshot = Snapshot<()>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        Snapshot(())
+    }
+
+    fn rollback(&mut self, _snapshot: Self::Snapshot) {
+        if matches!(self.cache.get(), Some(v) if v.is_modified()) {
+            // Clearing the cache makes the next access load the value from storage again.
+            self.cache = OnceCell::new();
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
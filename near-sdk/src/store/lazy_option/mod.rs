@@ -5,6 +5,7 @@ use once_cell::unsync::OnceCell;
 
 use crate::env;
 use crate::store::lazy::{load_and_deserialize, serialize_and_store};
+use crate::store::{Snapshot, Transactional};
 use crate::utils::{CacheEntry, EntryState};
 use crate::IntoStorageKey;
 
@@ -121,6 +122,25 @@ where
     }
 }
 
+impl<T> Transactional for LazyOption<T>
+where
+    T: BorshSerialize,
+{
+    type Snapshot = Snapshot<()>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        Snapshot(())
+    }
+
+    fn rollback(&mut self, _snapshot: Self::Snapshot) {
+        if matches!(self.cache.get(), Some(v) if v.is_modified()) {
+            // Clearing the cache makes the next access load the value from storage again.
+            self.cache = OnceCell::new();
+        }
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -11,6 +11,9 @@ where
     V: BorshSerialize,
     H: ToKey,
 {
+    /// Inserts all key-value pairs of the iterator, replacing the values of existing keys. Like
+    /// [`LookupMap::set`], this does not read the previous values, and the entries are only
+    /// written to storage when the map is flushed.
     fn extend<I>(&mut self, iter: I)
     where
         I: IntoIterator<Item = (K, V)>,
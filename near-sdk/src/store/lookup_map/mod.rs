@@ -2,6 +2,7 @@ mod entry;
 mod impls;
 
 use std::borrow::Borrow;
+use std::collections::BTreeSet;
 use std::fmt;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -9,6 +10,7 @@ use once_cell::unsync::OnceCell;
 
 use super::ERR_NOT_EXIST;
 use crate::store::key::{Identity, ToKey};
+use crate::store::{Snapshot, Transactional, WritePolicy};
 use crate::utils::{EntryState, StableMap};
 use crate::{env, CacheEntry, IntoStorageKey};
 
@@ -26,7 +28,9 @@ const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
 /// key object and uses these bytes as the key. This is to be backwards-compatible with
 /// [`collections::LookupMap`](crate::collections::LookupMap) and be fast for small keys.
 /// To use a custom function, use [`with_hasher`]. Alternative builtin hash functions can be found
-/// at [`near_sdk::store::key`](crate::store::key).
+/// at [`near_sdk::store::key`](crate::store::key), including truncated hashes such as
+/// [`Sha256Truncated16`](crate::store::key::Sha256Truncated16) which use shorter storage keys
+/// than a full hash for large keys.
 ///
 /// # Examples
 /// ```
@@ -88,6 +92,14 @@ where
     /// invalidated.
     #[borsh_skip]
     cache: StableMap<K, EntryAndHash<V, H::KeyType>>,
+    #[borsh_skip]
+    write_policy: WritePolicy,
+    #[borsh_skip]
+    cache_capacity: Option<u32>,
+    /// Keys of the entries given out for changes since the last flush under
+    /// [`WritePolicy::WriteThrough`], which are the only entries the next change has to write.
+    #[borsh_skip]
+    touched: BTreeSet<K>,
 }
 
 struct EntryAndHash<V, T> {
@@ -167,7 +179,89 @@ where
     where
         S: IntoStorageKey,
     {
-        Self { prefix: prefix.into_storage_key().into_boxed_slice(), cache: Default::default() }
+        Self {
+            prefix: prefix.into_storage_key().into_boxed_slice(),
+            cache: Default::default(),
+            write_policy: WritePolicy::WriteBack,
+            cache_capacity: None,
+            touched: BTreeSet::new(),
+        }
+    }
+
+    /// Returns when the map writes its changes to storage.
+    pub fn write_policy(&self) -> WritePolicy {
+        self.write_policy
+    }
+
+    /// Sets when the map writes its changes to storage. Changes that are still cached are
+    /// written when switching to [`WritePolicy::WriteThrough`].
+    ///
+    /// The policy is not persisted with the map, see [`WritePolicy`].
+    pub fn set_write_policy(&mut self, policy: WritePolicy) {
+        if policy == WritePolicy::WriteThrough {
+            // Changes made before are not tracked, so they are all written.
+            self.flush();
+        }
+        self.write_policy = policy;
+    }
+
+    /// Returns the map with the given write policy, to choose it when the map is created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{LookupMap, WritePolicy};
+    ///
+    /// let map: LookupMap<u32, u32> =
+    ///     LookupMap::new(b"m").with_write_policy(WritePolicy::WriteThrough);
+    /// assert_eq!(map.write_policy(), WritePolicy::WriteThrough);
+    /// ```
+    pub fn with_write_policy(mut self, policy: WritePolicy) -> Self {
+        self.set_write_policy(policy);
+        self
+    }
+
+    /// Returns the number of entries the map keeps in memory before emptying its cache, or `None`
+    /// if the cache is not bounded.
+    pub fn cache_capacity(&self) -> Option<u32> {
+        self.cache_capacity
+    }
+
+    /// Bounds the number of entries the map keeps in memory when they are accessed through
+    /// methods that take `&mut self`.
+    ///
+    /// Before such a method loads an entry into a full cache, the changes are written to storage
+    /// and the cache is emptied, and entries accessed later are read from storage again. Methods
+    /// that take `&self`, like [`get`](Self::get) and [`contains_key`](Self::contains_key), return
+    /// references into the cache, so the entries they load stay in memory until the next
+    /// [`flush`](Self::flush) or change. A method that reads many entries should call `flush`
+    /// between chunks of reads. Changes written this way are kept if a
+    /// [`transaction`](Self::transaction) fails, like changes written under
+    /// [`WritePolicy::WriteThrough`].
+    ///
+    /// The capacity is not persisted with the map, so maps loaded from the contract state start
+    /// with an unbounded cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<u32, u32> = LookupMap::new(b"m");
+    /// map.set_cache_capacity(Some(10));
+    ///
+    /// let mut sum = 0;
+    /// for i in 0..100 {
+    ///     sum += map.get(&i).copied().unwrap_or_default();
+    ///     if i % 10 == 9 {
+    ///         map.flush();
+    ///     }
+    /// }
+    /// # assert_eq!(sum, 0);
+    /// ```
+    pub fn set_cache_capacity(&mut self, capacity: Option<u32>) {
+        self.cache_capacity = capacity;
+        self.write_through();
     }
 
     /// Overwrites the current value for the given key.
@@ -191,6 +285,10 @@ where
     /// assert!(!map.contains_key("test"));
     /// ```
     pub fn set(&mut self, key: K, value: Option<V>) {
+        self.make_room();
+        // The key is moved into the cache, so the entry is written here instead of being touched.
+        let storage_key = (self.write_policy == WritePolicy::WriteThrough)
+            .then(|| H::to_key(&self.prefix, &key, &mut Vec::new()));
         let entry = self.cache.get_mut(key);
         match entry.value.get_mut() {
             Some(entry) => *entry.value_mut() = value,
@@ -198,6 +296,11 @@ where
                 let _ = entry.value.set(CacheEntry::new_modified(value));
             }
         }
+        if let Some(storage_key) = storage_key {
+            let _ = entry.hash.set(storage_key);
+            Self::write_entry(&self.prefix, None, entry, &mut Vec::new());
+        }
+        self.write_through();
     }
 }
 
@@ -252,11 +355,75 @@ where
         entry.value().as_ref()
     }
 
+    /// Returns references to the values of `keys`, in the same order, reading every key that is
+    /// not cached yet from storage in one pass before any value is used.
+    ///
+    /// The values are kept in the cache like those read with [`get`](Self::get), so a method
+    /// that needs several values up front can load them together and then read or update them
+    /// without further storage reads.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut balances: LookupMap<String, u128> = LookupMap::new(b"b");
+    /// balances.insert("alice".to_string(), 10);
+    /// balances.insert("carol".to_string(), 30);
+    ///
+    /// let found = balances.get_many(["alice", "bob", "carol"]);
+    /// assert_eq!(found, [Some(&10), None, Some(&30)]);
+    /// ```
+    pub fn get_many<'k, Q, I>(&self, keys: I) -> Vec<Option<&V>>
+    where
+        I: IntoIterator<Item = &'k Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + BorshSerialize + ToOwned<Owned = K> + 'k,
+    {
+        keys.into_iter().map(|k| self.get(k)).collect()
+    }
+
+    /// Returns whether the map contains each of `keys`, in the same order. Like
+    /// [`contains_key`](Self::contains_key), this checks storage for keys that are not cached
+    /// without reading their values.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<u32, String> = LookupMap::new(b"m");
+    /// map.insert(1, "a".to_string());
+    ///
+    /// assert_eq!(map.contains_many(&[1, 2]), [true, false]);
+    /// ```
+    pub fn contains_many<'k, Q, I>(&self, keys: I) -> Vec<bool>
+    where
+        I: IntoIterator<Item = &'k Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + BorshSerialize + ToOwned<Owned = K> + Ord + 'k,
+    {
+        keys.into_iter().map(|k| self.contains_key(k)).collect()
+    }
+
     pub(crate) fn get_mut_inner<Q: ?Sized>(&mut self, k: &Q) -> &mut CacheEntry<V>
     where
         K: Borrow<Q>,
         Q: BorshSerialize + ToOwned<Owned = K>,
     {
+        self.make_room();
+        self.get_mut_without_eviction(k)
+    }
+
+    /// Like [`get_mut_inner`](Self::get_mut_inner), but never empties the cache, so that the
+    /// references returned by earlier calls stay valid. Used by mutable iterators, which give out
+    /// references that outlive the calls.
+    pub(crate) fn get_mut_without_eviction<Q>(&mut self, k: &Q) -> &mut CacheEntry<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + BorshSerialize + ToOwned<Owned = K>,
+    {
+        if self.write_policy == WritePolicy::WriteThrough {
+            self.touched.insert(k.to_owned());
+        }
         let prefix = &self.prefix;
         //* ToOwned bound, which forces a clone, is required to be able to keep the key in the cache
         let entry = self.cache.get_mut(k.to_owned());
@@ -318,7 +485,9 @@ where
     where
         K: Clone,
     {
-        self.get_mut_inner(&k).replace(Some(v))
+        let prev = self.get_mut_inner(&k).replace(Some(v));
+        self.write_through();
+        prev
     }
 
     /// Returns `true` if the map contains a value for the specified key.
@@ -383,10 +552,17 @@ where
         K: Borrow<Q>,
         Q: BorshSerialize + ToOwned<Owned = K>,
     {
-        self.get_mut_inner(k).replace(None)
+        let prev = self.get_mut_inner(k).replace(None);
+        self.write_through();
+        prev
     }
 
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    ///
+    /// The value is read from storage at most once, and changes made through the entry are kept
+    /// in the cache, so a read-modify-write through the entry writes the key once, when the map is
+    /// flushed or dropped, or by the next change under [`WritePolicy::WriteThrough`].
+    ///
     /// ```
     /// use near_sdk::store::LookupMap;
     ///
@@ -423,38 +599,158 @@ where
     V: BorshSerialize,
     H: ToKey,
 {
+    /// Returns the values currently loaded in the cache, without marking them as modified. Used
+    /// to flush collections that are stored as values of the map.
+    pub(crate) fn cached_values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.cache
+            .inner()
+            .values_mut()
+            .filter_map(|v| v.value.get_mut()?.value_mut_unmodified().as_mut())
+    }
+
+    /// Returns `true` if the cache holds more entries than its capacity.
+    fn is_over_capacity(&mut self) -> bool {
+        matches!(self.cache_capacity, Some(capacity) if self.cache.inner().len() > capacity as usize)
+    }
+
+    /// Empties the cache, after writing the changes, if it is full, so that loading another
+    /// entry does not go over its capacity. Only called with `&mut self`, as there can be no
+    /// references into the cache then.
+    fn make_room(&mut self) {
+        if matches!(self.cache_capacity, Some(capacity) if self.cache.inner().len() >= capacity as usize)
+        {
+            self.flush();
+            self.cache.inner().clear();
+        }
+    }
+
+    /// Writes the changes if the map writes through, or all of them and empties the cache if it
+    /// is over capacity.
+    pub(crate) fn write_through(&mut self) {
+        if self.is_over_capacity() {
+            self.flush();
+        } else if self.write_policy == WritePolicy::WriteThrough {
+            self.flush_touched();
+        }
+    }
+
+    /// Writes the entry to storage if it was modified, and marks it as cached. The storage key
+    /// is computed from `key` if it was not yet.
+    fn write_entry(
+        prefix: &[u8],
+        key: Option<&K>,
+        entry: &mut EntryAndHash<V, H::KeyType>,
+        buf: &mut Vec<u8>,
+    ) {
+        if let Some(val) = entry.value.get_mut() {
+            if val.is_modified() {
+                let storage_key = entry.hash.get_or_init(|| {
+                    buf.clear();
+                    H::to_key(prefix, key.unwrap_or_else(|| env::abort()), buf)
+                });
+                match val.value().as_ref() {
+                    Some(modified) => {
+                        buf.clear();
+                        BorshSerialize::serialize(modified, buf)
+                            .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+                        env::storage_write(storage_key.as_ref(), buf);
+                    }
+                    None => {
+                        // Element was removed, clear the storage for the value
+                        env::storage_remove(storage_key.as_ref());
+                    }
+                }
+
+                // Update state of flushed state as cached, to avoid duplicate writes/removes
+                // while also keeping the cached values in memory.
+                val.replace_state(EntryState::Cached);
+            }
+        }
+    }
+
+    /// Writes the entries given out for changes since the last flush, which under
+    /// [`WritePolicy::WriteThrough`] are the only ones that can be modified.
+    fn flush_touched(&mut self) {
+        let mut buf = Vec::new();
+        for k in std::mem::take(&mut self.touched) {
+            // Entries discarded by a rollback are not in the cache anymore.
+            if let Some(v) = self.cache.inner().get_mut(&k) {
+                Self::write_entry(&self.prefix, Some(&k), v, &mut buf);
+            }
+        }
+    }
+
     /// Flushes the intermediate values of the map before this is called when the structure is
     /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
-    /// in memory.
+    /// in memory, unless there are more of them than the
+    /// [cache capacity](Self::set_cache_capacity).
     pub fn flush(&mut self) {
         let mut buf = Vec::new();
         for (k, v) in self.cache.inner().iter_mut() {
-            if let Some(val) = v.value.get_mut() {
-                if val.is_modified() {
-                    let prefix = &self.prefix;
-                    let key = v.hash.get_or_init(|| {
-                        buf.clear();
-                        H::to_key(prefix, k, &mut buf)
-                    });
-                    match val.value().as_ref() {
-                        Some(modified) => {
-                            buf.clear();
-                            BorshSerialize::serialize(modified, &mut buf)
-                                .unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
-                            env::storage_write(key.as_ref(), &buf);
-                        }
-                        None => {
-                            // Element was removed, clear the storage for the value
-                            env::storage_remove(key.as_ref());
-                        }
-                    }
-
-                    // Update state of flushed state as cached, to avoid duplicate writes/removes
-                    // while also keeping the cached values in memory.
-                    val.replace_state(EntryState::Cached);
-                }
-            }
+            Self::write_entry(&self.prefix, Some(k), v, &mut buf);
         }
+        self.touched.clear();
+        if self.is_over_capacity() {
+            // Every entry was just written, so the cache can be emptied without losing changes.
+            self.cache.inner().clear();
+        }
+    }
+}
+
+impl<K, V, H> LookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Runs `f` over the map and discards every change it made if it returns an error. See
+    /// [`store::transaction`](crate::store::transaction) for updating several collections at
+    /// once.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::LookupMap;
+    ///
+    /// let mut map: LookupMap<u32, u32> = LookupMap::new(b"m");
+    /// map.insert(1, 1);
+    ///
+    /// let result = map.transaction(|map| {
+    ///     map.insert(1, 2);
+    ///     map.insert(2, 2);
+    ///     Err("abort")
+    /// });
+    ///
+    /// assert_eq!(result, Err::<(), _>("abort"));
+    /// assert_eq!(map[&1], 1);
+    /// assert!(!map.contains_key(&2));
+    /// ```
+    pub fn transaction<F, R, E>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Self) -> Result<R, E>,
+    {
+        crate::store::transaction(self, f)
+    }
+}
+
+impl<K, V, H> Transactional for LookupMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    type Snapshot = Snapshot<()>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        Snapshot(())
+    }
+
+    fn rollback(&mut self, _snapshot: Self::Snapshot) {
+        // Everything was flushed when the snapshot was taken, so the modified entries are exactly
+        // the changes to discard. Dropping them makes the next access load from storage again.
+        self.cache
+            .inner()
+            .retain(|_, v| !matches!(v.value.get(), Some(entry) if entry.is_modified()));
     }
 }
 
@@ -463,7 +759,8 @@ where
 mod tests {
     use super::LookupMap;
     use crate::env;
-    use crate::store::key::{Keccak256, ToKey};
+    use crate::store::key::{Keccak256, Sha256, Sha256Truncated16, Sha256Truncated8, ToKey};
+    use crate::store::WritePolicy;
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
     use rand::seq::SliceRandom;
@@ -588,7 +885,7 @@ mod tests {
 
     #[test]
     fn size_of_map() {
-        assert_eq!(core::mem::size_of::<LookupMap<u8, u8>>(), 48);
+        assert_eq!(core::mem::size_of::<LookupMap<u8, u8>>(), 88);
     }
 
     #[test]
@@ -637,6 +934,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn get_many_fills_cache() {
+        let mut map = LookupMap::new(b"m");
+        map.extend((0..5u8).map(|i| (i, u32::from(i) * 10)));
+        map.flush();
+        let mut map = LookupMap::<u8, u32>::new(b"m");
+
+        assert_eq!(map.get_many(&[4, 7, 1, 4]), [Some(&40), None, Some(&10), Some(&40)]);
+        assert_eq!(map.contains_many(&[0, 5]), [true, false]);
+
+        // The values are read from the cache after the storage entries are gone.
+        env::storage_remove(&[b'm', 4]);
+        env::storage_remove(&[b'm', 1]);
+        assert_eq!((map.get(&4), map.get(&1)), (Some(&40), Some(&10)));
+        *map.get_mut(&1).unwrap() += 1;
+        map.flush();
+        assert_eq!(LookupMap::<u8, u32>::new(b"m").get(&1), Some(&11));
+    }
+
+    #[test]
+    fn bounded_cache() {
+        let mut map = LookupMap::new(b"m");
+        map.set_cache_capacity(Some(10));
+        for i in 0..100u32 {
+            map.insert(i, i);
+            assert!(map.cache.inner().len() <= 10);
+        }
+        for i in 0..100 {
+            *map.get_mut(&i).unwrap() += 1;
+            assert!(map.cache.inner().len() <= 10);
+        }
+        // Reads through `&self` return references into the cache, so they are only evicted by the
+        // next flush.
+        let sum: u32 = (0..50).map(|i| map[&i]).sum();
+        assert_eq!(sum, 1275);
+        map.flush();
+        assert!(map.cache.inner().is_empty());
+
+        *map.get_mut(&3).unwrap() = 30;
+        map.flush();
+        assert_eq!(map.cache.inner().len(), 1);
+        map.set_cache_capacity(None);
+        (0..100).for_each(|i| assert!(map.get(&i).is_some()));
+        map.flush();
+        assert_eq!(map.cache.inner().len(), 100);
+        assert_eq!((map[&3], map[&4]), (30, 5));
+    }
+
+    #[test]
+    fn transaction_rollback() {
+        let mut a = LookupMap::new(b"a");
+        let mut b = LookupMap::new(b"b");
+        a.insert(1u8, 1u8);
+        b.insert(1u8, 1u8);
+
+        let result: Result<(), ()> = crate::store::transaction(&mut (&mut a, &mut b), |(a, b)| {
+            a.insert(1, 2);
+            a.remove(&1);
+            b.insert(2, 2);
+            *b.get_mut(&1).unwrap() = 3;
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(a.get(&1), Some(&1));
+        assert_eq!(b.get(&1), Some(&1));
+        assert!(!b.contains_key(&2));
+
+        let result = b.transaction(|b| b.insert(2, 2).ok_or(()));
+        assert!(result.is_err());
+        assert!(!b.contains_key(&2));
+        assert_eq!(b.transaction(|b| Ok::<_, ()>(b.insert(3, 3))), Ok(None));
+
+        drop(a);
+        drop(b);
+        let a = LookupMap::<u8, u8>::new(b"a");
+        let b = LookupMap::<u8, u8>::new(b"b");
+        assert_eq!(a.get(&1), Some(&1));
+        assert_eq!(b.get(&1), Some(&1));
+        assert_eq!(b.get(&2), None);
+        assert_eq!(b.get(&3), Some(&3));
+    }
+
     #[test]
     fn flush_on_drop() {
         let mut map = LookupMap::<_, _, Keccak256>::with_hasher(b"m");
@@ -658,6 +1037,64 @@ mod tests {
         assert_eq!(dup_map[&5], 8);
     }
 
+    #[test]
+    fn write_through() {
+        let mut map = LookupMap::<_, _, Keccak256>::with_hasher(b"m");
+        map.insert(1u8, 1u8);
+        let storage_key = |k: u8| Keccak256::to_key(b"m", &k, &mut Vec::new());
+        assert!(!env::storage_has_key(&storage_key(1)));
+
+        // Switching policy writes the cached changes.
+        map.set_write_policy(WritePolicy::WriteThrough);
+        assert_eq!(env::storage_read(&storage_key(1)), Some(vec![1]));
+
+        *map.get_mut(&1).unwrap() = 2;
+        map.insert(2, 2);
+        assert_eq!(env::storage_read(&storage_key(1)), Some(vec![2]));
+        // Only the entries given out for changes are written, not the whole cache.
+        assert!(map.touched.is_empty());
+        map.remove(&2);
+        assert!(!env::storage_has_key(&storage_key(2)));
+        map.set(3, Some(3));
+        assert_eq!(env::storage_read(&storage_key(3)), Some(vec![3]));
+    }
+
+    #[test]
+    fn entry_read_modify_write() {
+        let mut map = LookupMap::<_, _, Keccak256>::with_hasher(b"m");
+        let storage_key = |k: u8| Keccak256::to_key(b"m", &k, &mut Vec::new());
+        map.insert(1u8, 10u32);
+        map.flush();
+
+        map.entry(1).and_modify(|v| *v += 1).or_insert(0);
+        *map.entry(1).or_insert_with_key(|_| unreachable!()) *= 2;
+        map.entry(2).and_modify(|_| unreachable!()).or_insert_with_key(|k| *k as u32 * 100);
+        // Changes through entries are only written when flushed.
+        assert_eq!(env::storage_read(&storage_key(1)), Some(10u32.to_le_bytes().to_vec()));
+        assert!(!env::storage_has_key(&storage_key(2)));
+
+        map.flush();
+        assert_eq!(env::storage_read(&storage_key(1)), Some(22u32.to_le_bytes().to_vec()));
+        assert_eq!(env::storage_read(&storage_key(2)), Some(200u32.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn truncated_hash_keys() {
+        let mut map = LookupMap::<_, _, Sha256Truncated8>::with_hasher(b"m");
+        map.insert("alice.near".to_string(), 1u8);
+        map.flush();
+
+        let full = Sha256::to_key(b"m", "alice.near", &mut Vec::new());
+        let storage_key = Sha256Truncated8::to_key(b"m", "alice.near", &mut Vec::new());
+        assert_eq!(storage_key, full[..8]);
+        assert_eq!(env::storage_read(&storage_key), Some(vec![1]));
+        assert_eq!(Sha256Truncated16::to_key(b"m", "alice.near", &mut Vec::new()), full[..16]);
+
+        let dup_map = LookupMap::<String, u8, Sha256Truncated8>::with_hasher(b"m");
+        assert_eq!(dup_map["alice.near"], 1);
+        assert!(!dup_map.contains_key("bob.near"));
+    }
+
     #[derive(Arbitrary, Debug)]
     enum Op {
         Insert(u8, u8),
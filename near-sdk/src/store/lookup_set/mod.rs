@@ -1,6 +1,7 @@
 mod impls;
 
 use crate::store::key::{Identity, ToKey};
+use crate::store::{Snapshot, Transactional};
 use crate::{env, IntoStorageKey, StableMap};
 use borsh::{BorshDeserialize, BorshSerialize};
 use once_cell::unsync::OnceCell;
@@ -239,6 +240,25 @@ where
     }
 }
 
+impl<T, H> Transactional for LookupSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    type Snapshot = Snapshot<()>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        Snapshot(())
+    }
+
+    fn rollback(&mut self, _snapshot: Self::Snapshot) {
+        self.cache
+            .inner()
+            .retain(|_, v| !matches!(v.get(), Some(EntryState::Inserted | EntryState::Deleted)));
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
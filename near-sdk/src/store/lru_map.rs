@@ -0,0 +1,449 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::ERR_INCONSISTENT_STATE;
+use crate::store::key::{Identity, ToKey};
+use crate::store::LookupMap;
+use crate::{env, IntoStorageKey};
+
+const ERR_ZERO_CAPACITY: &str = "LruMap capacity must be greater than zero";
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// Value of an entry, linked to the entries used right before and after it.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Node<K, V> {
+    value: V,
+    /// Key of the entry that was used more recently.
+    prev: Option<K>,
+    /// Key of the entry that was used less recently.
+    next: Option<K>,
+}
+
+/// A map with a maximum number of entries, which evicts the least recently used entry to make
+/// room for new ones. Entries are stored on the storage trie, in a [`LookupMap`].
+///
+/// Entries are kept in a list ordered by when they were last used, linked through their keys, so
+/// updating the order and evicting the least recently used entry are `O(1)` and only load the
+/// neighbouring entries. Inserting an entry and reading it with [`get`](Self::get) or
+/// [`get_mut`](Self::get_mut) count as using it, while [`peek`](Self::peek) and
+/// [`contains_key`](Self::contains_key) do not change the order.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::LruMap;
+///
+/// let mut prices: LruMap<String, u128> = LruMap::new(b"p", 2);
+/// prices.push("near".to_string(), 5);
+/// prices.push("eth".to_string(), 1500);
+///
+/// // Reading "near" makes "eth" the least recently used entry.
+/// assert_eq!(prices.get("near"), Some(&5));
+///
+/// let evicted = prices.push("btc".to_string(), 20000);
+/// assert_eq!(evicted, Some(("eth".to_string(), 1500)));
+/// assert_eq!(prices.len(), 2);
+/// ```
+pub struct LruMap<K, V, H = Identity>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    capacity: u32,
+    len: u32,
+    /// Key of the most recently used entry.
+    head: Option<K>,
+    /// Key of the least recently used entry.
+    tail: Option<K>,
+    nodes: LookupMap<K, Node<K, V>, H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for LruMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.capacity, writer)?;
+        BorshSerialize::serialize(&self.len, writer)?;
+        BorshSerialize::serialize(&self.head, writer)?;
+        BorshSerialize::serialize(&self.tail, writer)?;
+        BorshSerialize::serialize(&self.nodes, writer)?;
+        Ok(())
+    }
+}
+
+impl<K, V, H> BorshDeserialize for LruMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            capacity: BorshDeserialize::deserialize(buf)?,
+            len: BorshDeserialize::deserialize(buf)?,
+            head: BorshDeserialize::deserialize(buf)?,
+            tail: BorshDeserialize::deserialize(buf)?,
+            nodes: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<K, V, H> fmt::Debug for LruMap<K, V, H>
+where
+    K: BorshSerialize + Ord + fmt::Debug,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LruMap")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len)
+            .field("head", &self.head)
+            .field("tail", &self.tail)
+            .finish()
+    }
+}
+
+impl<K, V> LruMap<K, V, Identity>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new map holding at most `capacity` entries. Use `prefix` as a unique prefix for
+    /// keys.
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LruMap;
+    ///
+    /// let mut map: LruMap<String, u8> = LruMap::new(b"l", 100);
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S, capacity: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix, capacity)
+    }
+}
+
+impl<K, V, H> LruMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize a [`LruMap`] with a custom hash function.
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{LruMap, key::Keccak256};
+    ///
+    /// let map = LruMap::<String, String, Keccak256>::with_hasher(b"l", 100);
+    /// ```
+    pub fn with_hasher<S>(prefix: S, capacity: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if capacity == 0 {
+            env::panic_str(ERR_ZERO_CAPACITY);
+        }
+        Self { capacity, len: 0, head: None, tail: None, nodes: LookupMap::with_hasher(prefix) }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of entries in the map.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.nodes.flush()
+    }
+}
+
+impl<K, V, H> LruMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn node_mut(&mut self, key: &K) -> &mut Node<K, V> {
+        expect_consistent_state(self.nodes.get_mut(key))
+    }
+
+    /// Removes the entry of `key` from the list of entries, leaving its node unlinked.
+    fn unlink(&mut self, key: &K) {
+        let node = self.node_mut(key);
+        let prev = node.prev.take();
+        let next = node.next.take();
+        match &prev {
+            Some(prev) => self.node_mut(prev).next = next.clone(),
+            None => self.head = next.clone(),
+        }
+        match &next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    /// Links the unlinked node of `key` as the most recently used entry.
+    fn link_front(&mut self, key: K) {
+        let old_head = self.head.replace(key.clone());
+        match &old_head {
+            Some(old_head) => self.node_mut(old_head).prev = Some(key.clone()),
+            None => self.tail = Some(key.clone()),
+        }
+        self.node_mut(&key).next = old_head;
+    }
+
+    fn touch(&mut self, key: K) {
+        if self.head.as_ref() != Some(&key) {
+            self.unlink(&key);
+            self.link_front(key);
+        }
+    }
+
+    /// Returns `true` if the map contains a value for the specified key, without marking it as
+    /// used.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.nodes.contains_key(k)
+    }
+
+    /// Returns a reference to the value corresponding to the key, without marking it as used.
+    pub fn peek<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.nodes.get(k).map(|node| &node.value)
+    }
+
+    /// Returns a reference to the value corresponding to the key, and marks it as the most
+    /// recently used entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LruMap;
+    ///
+    /// let mut map = LruMap::new(b"l", 2);
+    /// map.push(1, "a".to_string());
+    /// map.push(2, "b".to_string());
+    ///
+    /// assert_eq!(map.get(&1), Some(&"a".to_string()));
+    /// assert_eq!(map.peek_lru(), Some((&2, &"b".to_string())));
+    /// ```
+    pub fn get<Q: ?Sized>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.get_mut(k).map(|value| &*value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, and marks it as the
+    /// most recently used entry.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        if !self.nodes.contains_key(k) {
+            return None;
+        }
+        self.touch(k.to_owned());
+        self.nodes.get_mut(k).map(|node| &mut node.value)
+    }
+
+    /// Inserts a key-value pair into the map as the most recently used entry.
+    ///
+    /// If the map already had the key, its value is replaced and the key with the old value is
+    /// returned. Otherwise, if the map is at capacity, the least recently used entry is evicted
+    /// and returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::LruMap;
+    ///
+    /// let mut map = LruMap::new(b"l", 2);
+    /// assert_eq!(map.push(1, "a".to_string()), None);
+    /// assert_eq!(map.push(1, "b".to_string()), Some((1, "a".to_string())));
+    /// assert_eq!(map.push(2, "c".to_string()), None);
+    /// assert_eq!(map.push(3, "d".to_string()), Some((1, "b".to_string())));
+    /// ```
+    pub fn push(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(node) = self.nodes.get_mut(&key) {
+            let old = core::mem::replace(&mut node.value, value);
+            self.touch(key.clone());
+            return Some((key, old));
+        }
+
+        let evicted = if self.len >= self.capacity { self.pop_lru() } else { None };
+        self.nodes.insert(key.clone(), Node { value, prev: None, next: None });
+        self.len += 1;
+        self.link_front(key);
+        evicted
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in
+    /// the map.
+    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        if !self.nodes.contains_key(k) {
+            return None;
+        }
+        self.unlink(&k.to_owned());
+        self.len -= 1;
+        self.nodes.remove(k).map(|node| node.value)
+    }
+
+    /// Returns the least recently used entry, which is the next to be evicted, without marking
+    /// it as used.
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        let key = self.tail.as_ref()?;
+        Some((key, &expect_consistent_state(self.nodes.get(key)).value))
+    }
+
+    /// Removes the least recently used entry and returns it, or `None` if the map is empty.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let key = self.tail.clone()?;
+        self.unlink(&key);
+        self.len -= 1;
+        let node = expect_consistent_state(self.nodes.remove(&key));
+        Some((key, node.value))
+    }
+
+    /// Removes all entries from the map. This loads every entry, from the least recently used.
+    pub fn clear(&mut self) {
+        while self.pop_lru().is_some() {}
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+
+    use super::LruMap;
+    use crate::test_utils::test_env::setup_free;
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Push(u8, u8),
+        Get(u8),
+        Peek(u8),
+        Remove(u8),
+        PopLru,
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..1024 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut lm = LruMap::new(b"l", 8);
+            // Entries from the least to the most recently used.
+            let mut model: Vec<(u8, u8)> = Vec::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    let position = |model: &Vec<(u8, u8)>, k| model.iter().position(|e| e.0 == k);
+                    match op {
+                        Op::Push(k, v) => {
+                            let expected = match position(&model, k) {
+                                Some(i) => Some(model.remove(i)),
+                                None if model.len() == 8 => Some(model.remove(0)),
+                                None => None,
+                            };
+                            model.push((k, v));
+                            assert_eq!(lm.push(k, v), expected);
+                        }
+                        Op::Get(k) => {
+                            let expected = position(&model, k).map(|i| model.remove(i));
+                            if let Some(entry) = expected {
+                                model.push(entry);
+                            }
+                            assert_eq!(lm.get(&k), expected.map(|e| e.1).as_ref());
+                        }
+                        Op::Peek(k) => {
+                            let expected = position(&model, k).map(|i| model[i].1);
+                            assert_eq!(lm.peek(&k), expected.as_ref());
+                            assert_eq!(lm.contains_key(&k), expected.is_some());
+                        }
+                        Op::Remove(k) => {
+                            let expected = position(&model, k).map(|i| model.remove(i).1);
+                            assert_eq!(lm.remove(&k), expected);
+                        }
+                        Op::PopLru => {
+                            let expected =
+                                if model.is_empty() { None } else { Some(model.remove(0)) };
+                            assert_eq!(lm.pop_lru(), expected);
+                        }
+                        Op::Flush => lm.flush(),
+                        Op::Reset => {
+                            lm.flush();
+                            let serialized = lm.try_to_vec().unwrap();
+                            lm = LruMap::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(lm.len() as usize, model.len());
+                    assert_eq!(lm.peek_lru(), model.first().map(|(k, v)| (k, v)));
+                }
+            }
+
+            lm.clear();
+            assert!(lm.is_empty());
+            lm.flush();
+            crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+        }
+    }
+}
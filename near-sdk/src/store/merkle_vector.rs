@@ -0,0 +1,401 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::ERR_INCONSISTENT_STATE;
+use crate::json_types::Base58CryptoHash;
+use crate::store::key::Identity;
+use crate::store::{LookupMap, Vector};
+use crate::{env, CryptoHash, IntoStorageKey};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+const ERR_ELEMENT_SERIALIZATION: &str = "Cannot serialize element";
+
+/// Domain separation of leaf and inner node hashes, so that a leaf can't be passed off as an
+/// inner node in a proof.
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+fn leaf_hash<T: BorshSerialize + ?Sized>(value: &T) -> CryptoHash {
+    let mut data = vec![LEAF_PREFIX];
+    value.serialize(&mut data).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_SERIALIZATION));
+    env::sha256_array(&data)
+}
+
+fn node_hash(left: &CryptoHash, right: &CryptoHash) -> CryptoHash {
+    let mut data = [0; 65];
+    data[0] = NODE_PREFIX;
+    data[1..33].copy_from_slice(left);
+    data[33..].copy_from_slice(right);
+    env::sha256_array(&data)
+}
+
+/// Number of nodes on the level above a level of `len` nodes.
+// `u32::div_ceil` needs Rust 1.73, which is above the minimum supported Rust version.
+#[allow(clippy::manual_div_ceil)]
+fn parent_len(len: u32) -> u32 {
+    (len + 1) / 2
+}
+
+/// Proof that an element is included in a [`MerkleVector`] with a given root.
+///
+/// Proofs can be verified without access to the vector, like by a contract that only stores the
+/// committed root of a list built elsewhere. They are serialized with Borsh and, for JSON
+/// arguments, with the hashes encoded in base58.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "crate::serde")]
+pub struct MerkleProof {
+    /// Index of the element in the vector.
+    pub index: u32,
+    /// Length of the vector the proof was created for.
+    pub len: u32,
+    /// Hashes of the siblings on the path from the element to the root, from the bottom up.
+    pub siblings: Vec<Base58CryptoHash>,
+}
+
+impl MerkleProof {
+    /// Returns `true` if the proof shows that `element` is at [`index`](Self::index) of a vector
+    /// with the given `root`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::MerkleVector;
+    ///
+    /// let mut eligible = MerkleVector::new(b"m");
+    /// eligible.extend(["alice.near", "bob.near", "carol.near"].map(String::from));
+    ///
+    /// let root = eligible.root();
+    /// let proof = eligible.proof(1);
+    /// assert!(proof.verify(&root, &"bob.near".to_string()));
+    /// assert!(!proof.verify(&root, &"mallory.near".to_string()));
+    /// ```
+    pub fn verify<T>(&self, root: &CryptoHash, element: &T) -> bool
+    where
+        T: BorshSerialize + ?Sized,
+    {
+        if self.index >= self.len {
+            return false;
+        }
+        let mut siblings = self.siblings.iter().map(|hash| CryptoHash::from(*hash));
+        let mut hash = leaf_hash(element);
+        let (mut index, mut count) = (self.index, self.len);
+        while count > 1 {
+            if index % 2 == 1 {
+                match siblings.next() {
+                    Some(left) => hash = node_hash(&left, &hash),
+                    None => return false,
+                }
+            } else if index + 1 < count {
+                match siblings.next() {
+                    Some(right) => hash = node_hash(&hash, &right),
+                    None => return false,
+                }
+            }
+            index /= 2;
+            count = parent_len(count);
+        }
+        siblings.next().is_none() && &hash == root
+    }
+}
+
+/// A vector that keeps a Merkle root over its elements, and can produce [`MerkleProof`]s of their
+/// inclusion.
+///
+/// The hash of an element is the `sha256` hash of `0x00` followed by its Borsh serialization, and
+/// the hash of an inner node is the `sha256` hash of `0x01` followed by the hashes of its two
+/// children. A node without a right child, at the end of a level with an odd number of nodes, is
+/// moved up to the next level unchanged. The root of an empty vector is all zeroes.
+///
+/// Besides the elements, the hashes of all nodes of the tree are stored, so updating an element
+/// or creating a proof only loads the `O(log n)` hashes on its path to the root.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::MerkleVector;
+///
+/// let mut vec = MerkleVector::new(b"m");
+/// vec.push(1u64);
+/// vec.push(2);
+/// let root = vec.root();
+///
+/// vec.set(1, 3);
+/// assert_ne!(vec.root(), root);
+/// assert!(vec.proof(1).verify(&vec.root(), &3u64));
+/// ```
+pub struct MerkleVector<T>
+where
+    T: BorshSerialize,
+{
+    root: CryptoHash,
+    elements: Vector<T>,
+    /// Hashes of the nodes of the tree, by level and index in the level. Elements are at level 0.
+    nodes: LookupMap<(u8, u32), CryptoHash, Identity>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for MerkleVector<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.root, writer)?;
+        BorshSerialize::serialize(&self.elements, writer)?;
+        BorshSerialize::serialize(&self.nodes, writer)?;
+        Ok(())
+    }
+}
+
+impl<T> BorshDeserialize for MerkleVector<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            root: BorshDeserialize::deserialize(buf)?,
+            elements: BorshDeserialize::deserialize(buf)?,
+            nodes: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<T> fmt::Debug for MerkleVector<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MerkleVector")
+            .field("root", &self.root)
+            .field("elements", &self.elements)
+            .finish()
+    }
+}
+
+impl<T> MerkleVector<T>
+where
+    T: BorshSerialize,
+{
+    /// Create a new empty vector. Use `prefix` as a unique prefix for keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::MerkleVector;
+    ///
+    /// let mut vec: MerkleVector<String> = MerkleVector::new(b"m");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            root: [0; 32],
+            elements: Vector::new([prefix.as_slice(), b"v"].concat()),
+            nodes: LookupMap::with_hasher([prefix.as_slice(), b"n"].concat()),
+        }
+    }
+
+    /// Returns the number of elements in the vector.
+    pub fn len(&self) -> u32 {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the Merkle root of the elements.
+    pub fn root(&self) -> CryptoHash {
+        self.root
+    }
+
+    /// Flushes the cache and writes all modified elements and hashes to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.elements.flush();
+        self.nodes.flush();
+    }
+
+    fn node(&self, level: u8, index: u32) -> CryptoHash {
+        *self.nodes.get(&(level, index)).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+    }
+
+    /// Recomputes the hashes on the path from the element at `index` to the root.
+    fn update_path(&mut self, mut index: u32, mut hash: CryptoHash) {
+        let mut count = self.len();
+        let mut level = 0;
+        self.nodes.set((level, index), Some(hash));
+        while count > 1 {
+            if index % 2 == 1 {
+                hash = node_hash(&self.node(level, index - 1), &hash);
+            } else if index + 1 < count {
+                hash = node_hash(&hash, &self.node(level, index + 1));
+            }
+            level += 1;
+            index /= 2;
+            count = parent_len(count);
+            self.nodes.set((level, index), Some(hash));
+        }
+        self.root = hash;
+    }
+
+    /// Appends an element to the back of the vector and updates the root.
+    pub fn push(&mut self, element: T) {
+        let hash = leaf_hash(&element);
+        self.elements.push(element);
+        self.update_path(self.len() - 1, hash);
+    }
+
+    /// Replaces the element at `index` and updates the root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: u32, element: T) {
+        if index >= self.len() {
+            env::panic_str(ERR_INDEX_OUT_OF_BOUNDS);
+        }
+        let hash = leaf_hash(&element);
+        self.elements.set(index, element);
+        self.update_path(index, hash);
+    }
+
+    /// Returns a proof that the element at `index` is included in the vector with the current
+    /// root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn proof(&self, index: u32) -> MerkleProof {
+        let len = self.len();
+        if index >= len {
+            env::panic_str(ERR_INDEX_OUT_OF_BOUNDS);
+        }
+        let mut siblings = Vec::new();
+        let (mut at, mut count, mut level) = (index, len, 0);
+        while count > 1 {
+            if at % 2 == 1 {
+                siblings.push(self.node(level, at - 1).into());
+            } else if at + 1 < count {
+                siblings.push(self.node(level, at + 1).into());
+            }
+            level += 1;
+            at /= 2;
+            count = parent_len(count);
+        }
+        MerkleProof { index, len, siblings }
+    }
+}
+
+impl<T> MerkleVector<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Returns the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: u32) -> Option<&T> {
+        self.elements.get(index)
+    }
+
+    /// Returns an iterator over the elements of the vector.
+    pub fn iter(&self) -> super::vec::Iter<'_, T> {
+        self.elements.iter()
+    }
+}
+
+impl<T> Extend<T> for MerkleVector<T>
+where
+    T: BorshSerialize,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for element in iter {
+            self.push(element)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::{leaf_hash, node_hash, MerkleVector};
+    use crate::CryptoHash;
+
+    /// Root computed from all leaf hashes at once.
+    fn naive_root(mut level: Vec<CryptoHash>) -> CryptoHash {
+        if level.is_empty() {
+            return [0; 32];
+        }
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        level[0]
+    }
+
+    #[test]
+    fn roots_and_proofs() {
+        let mut vec = MerkleVector::new(b"m");
+        let mut leaves = Vec::new();
+        assert_eq!(vec.root(), naive_root(Vec::new()));
+        for i in 0..40u32 {
+            vec.push(i);
+            leaves.push(leaf_hash(&i));
+            assert_eq!(vec.root(), naive_root(leaves.clone()));
+            for j in 0..=i {
+                let proof = vec.proof(j);
+                assert!(proof.verify(&vec.root(), &j));
+                assert!(!proof.verify(&vec.root(), &(j + 1)));
+            }
+        }
+
+        vec.set(17, 100);
+        leaves[17] = leaf_hash(&100u32);
+        assert_eq!(vec.root(), naive_root(leaves));
+        assert!(vec.proof(17).verify(&vec.root(), &100u32));
+        assert!(!vec.proof(17).verify(&vec.root(), &17u32));
+
+        // Proofs don't verify for another position or length.
+        let mut proof = vec.proof(3);
+        proof.index = 2;
+        assert!(!proof.verify(&vec.root(), &3u32));
+        let mut proof = vec.proof(3);
+        proof.len = 4;
+        assert!(!proof.verify(&vec.root(), &3u32));
+    }
+
+    #[test]
+    fn persisted() {
+        let mut vec = MerkleVector::new(b"m");
+        vec.extend(0..10u8);
+        vec.flush();
+
+        let serialized = vec.try_to_vec().unwrap();
+        let mut restored = MerkleVector::<u8>::try_from_slice(&serialized).unwrap();
+        assert_eq!(restored.root(), vec.root());
+        assert_eq!(restored.get(9), Some(&9));
+
+        restored.push(10);
+        vec.push(10);
+        assert_eq!(restored.root(), vec.root());
+        assert!(restored.proof(4).verify(&vec.root(), &4u8));
+    }
+}
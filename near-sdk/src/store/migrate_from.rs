@@ -0,0 +1,249 @@
+//! Moves the entries of the deprecated [`collections`](crate::collections) into their `store`
+//! equivalents.
+//!
+//! The storage layouts of the two modules differ, so the entries have to be read from the old
+//! collection and inserted into the new one. Collections can be too large to move in a single
+//! function call, so [`vector`] and [`unordered_map`] accept a limit of entries to move and can be
+//! called again, in later function calls, until they return `true`. Both the old and the new
+//! collection have to be persisted in the contract state in-between these calls, and the old
+//! collection should not be used in any other way until the migration is done.
+//!
+//! The keys of a [`collections::LookupMap`] are not stored, so [`lookup_map`] moves the entries of
+//! the keys it's given.
+//!
+//! # Examples
+//! ```
+//! use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+//! use near_sdk::{collections, store};
+//!
+//! #[derive(BorshSerialize, BorshDeserialize)]
+//! struct Contract {
+//!     legacy: collections::UnorderedMap<String, u64>,
+//!     balances: store::UnorderedMap<String, u64>,
+//! }
+//!
+//! impl Contract {
+//!     /// Called until it returns `true`.
+//!     pub fn migrate_balances(&mut self) -> bool {
+//!         store::migrate_from::unordered_map(&mut self.legacy, &mut self.balances, Some(100))
+//!     }
+//! }
+//! # let mut contract = Contract {
+//! #     legacy: collections::UnorderedMap::new(b"l"),
+//! #     balances: store::UnorderedMap::new(b"b"),
+//! # };
+//! # contract.legacy.insert(&"alice.near".to_string(), &5);
+//! # assert!(contract.migrate_balances());
+//! # assert_eq!(contract.balances["alice.near"], 5);
+//! ```
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::key::ToKey;
+use super::{LookupMap, UnorderedMap, Vector};
+use crate::collections;
+use crate::env;
+
+const ERR_ELEMENT_DESERIALIZATION: &str = "Cannot deserialize element";
+const ERR_NOT_EMPTY: &str = "Migration target must be empty when the migration starts";
+
+fn deserialize<T: BorshDeserialize>(raw: &[u8]) -> T {
+    T::try_from_slice(raw).unwrap_or_else(|_| env::panic_str(ERR_ELEMENT_DESERIALIZATION))
+}
+
+/// Number of entries to move in this call, starting after the `moved` entries that were moved
+/// in previous calls.
+fn chunk_end(moved: u32, len: u64, limit: Option<u32>) -> u64 {
+    let moved = u64::from(moved);
+    if moved > len {
+        env::panic_str(ERR_NOT_EMPTY)
+    }
+    match limit {
+        Some(limit) => len.min(moved + u64::from(limit)),
+        None => len,
+    }
+}
+
+/// Moves the elements of `from` to the end of `to`, in order, removing them from `from`.
+///
+/// At most `limit` elements are moved, or all of them if it's `None`. Returns `true` when all
+/// elements have been moved, at which point `from` is empty. `to` must be empty when the first
+/// chunk is moved, since its length is how the elements moved so far are tracked.
+///
+/// # Panics
+///
+/// Panics if `to` has more elements than `from`.
+///
+/// # Examples
+/// ```
+/// use near_sdk::{collections, store};
+///
+/// let mut legacy = collections::Vector::new(b"l");
+/// legacy.extend([1u8, 2, 3]);
+/// let mut vec = store::Vector::new(b"v");
+///
+/// assert!(!store::migrate_from::vector(&mut legacy, &mut vec, Some(2)));
+/// assert!(store::migrate_from::vector(&mut legacy, &mut vec, Some(2)));
+/// assert!(legacy.is_empty());
+/// assert_eq!(vec.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+/// ```
+pub fn vector<T>(from: &mut collections::Vector<T>, to: &mut Vector<T>, limit: Option<u32>) -> bool
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    let end = chunk_end(to.len(), from.len(), limit);
+    for index in u64::from(to.len())..end {
+        to.push(deserialize(&from.take_raw(index)));
+    }
+    let done = end == from.len();
+    if done {
+        from.forget_elements();
+    }
+    done
+}
+
+/// Moves the entries of `from` into `to`, in the order of iteration of `from`, removing them from
+/// `from`.
+///
+/// At most `limit` entries are moved, or all of them if it's `None`. Returns `true` when all
+/// entries have been moved, at which point `from` is empty. `to` must be empty when the first
+/// chunk is moved, since its length is how the entries moved so far are tracked.
+///
+/// # Panics
+///
+/// Panics if `to` has more entries than `from`, or already contains a moved key.
+pub fn unordered_map<K, V, H>(
+    from: &mut collections::UnorderedMap<K, V>,
+    to: &mut UnorderedMap<K, V, H>,
+    limit: Option<u32>,
+) -> bool
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    let end = chunk_end(to.len(), from.len(), limit);
+    for index in u64::from(to.len())..end {
+        let (key_raw, value_raw) = from.take_entry_raw(index);
+        if to.insert(deserialize(&key_raw), deserialize(&value_raw)).is_some() {
+            env::panic_str(ERR_NOT_EMPTY)
+        }
+    }
+    let done = end == from.len();
+    if done {
+        from.forget_entries();
+    }
+    done
+}
+
+/// Moves the entries of `keys` from `from` into `to`, and returns how many of the keys were
+/// present in `from`.
+///
+/// Entries of `to` with the same keys are replaced. The keys of `from` are not stored, so they
+/// have to be known from elsewhere, like another collection or the event logs of the contract.
+///
+/// A [`LookupMap`] with the [`Identity`](super::key::Identity) hasher uses the same layout as a
+/// [`collections::LookupMap`], so it can also read the old entries in place when created with the
+/// same prefix.
+///
+/// # Examples
+/// ```
+/// use near_sdk::{collections, store};
+///
+/// let mut legacy = collections::LookupMap::new(b"l");
+/// legacy.insert(&"alice.near".to_string(), &1u64);
+/// let mut map = store::LookupMap::new(b"m");
+///
+/// let keys = ["alice.near".to_string(), "bob.near".to_string()];
+/// assert_eq!(store::migrate_from::lookup_map(&mut legacy, &mut map, keys), 1);
+/// assert_eq!(map["alice.near"], 1);
+/// assert!(!legacy.contains_key(&"alice.near".to_string()));
+/// ```
+pub fn lookup_map<K, V, H, I>(
+    from: &mut collections::LookupMap<K, V>,
+    to: &mut LookupMap<K, V, H>,
+    keys: I,
+) -> u32
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+    I: IntoIterator<Item = K>,
+{
+    let mut moved = 0;
+    for key in keys {
+        if let Some(value) = from.remove(&key) {
+            to.insert(key, value);
+            moved += 1;
+        }
+    }
+    moved
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::key::Identity;
+    use crate::test_utils::test_env::setup_free;
+
+    /// Asserts that none of the entries of the old collection are left in storage.
+    fn assert_storage_moved(old_prefix: &[u8]) {
+        crate::mock::with_mocked_blockchain(|b| {
+            assert!(b.take_storage().keys().all(|k| !k.starts_with(old_prefix)));
+        });
+    }
+
+    #[test]
+    fn vector_in_chunks() {
+        setup_free();
+
+        let mut legacy = collections::Vector::new(b"l");
+        legacy.extend(0..10u32);
+        let mut vec = Vector::new(b"v");
+
+        let mut calls = 0;
+        while !vector(&mut legacy, &mut vec, Some(3)) {
+            calls += 1;
+        }
+        assert_eq!(calls, 3);
+        assert!(legacy.is_empty());
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+
+        vec.flush();
+        assert_storage_moved(b"l");
+    }
+
+    #[test]
+    fn unordered_map_in_chunks() {
+        setup_free();
+
+        let mut legacy = collections::UnorderedMap::new(b"l");
+        for i in 0..10u32 {
+            legacy.insert(&i, &(i * 2));
+        }
+        legacy.remove(&3);
+        let mut map = UnorderedMap::new(b"m");
+
+        assert!(!unordered_map(&mut legacy, &mut map, Some(5)));
+        assert!(unordered_map(&mut legacy, &mut map, None));
+        assert!(legacy.is_empty());
+        assert_eq!(map.len(), 9);
+        assert_eq!(map.get(&3), None);
+        assert_eq!(map[&9], 18);
+
+        map.flush();
+        assert_storage_moved(b"l");
+    }
+
+    #[test]
+    fn lookup_map_identity_layout() {
+        setup_free();
+
+        let mut legacy = collections::LookupMap::new(b"l");
+        legacy.insert(&"a".to_string(), &1u8);
+
+        let map = LookupMap::<String, u8, Identity>::with_hasher(b"l");
+        assert_eq!(map["a"], 1);
+    }
+}
@@ -14,10 +14,13 @@
 //! For example, a store::Vector is stored as several key-value pairs, where indices are the keys.
 //! So, accessing a single element would only load this specific element.
 //!
-//! It can be expensive to load all values into memory, and because of this, `serde`
-//! [`Serialize`](serde::Serialize) and [`Deserialize`](serde::Deserialize) traits are
-//! intentionally not implemented. If you want to return all values from a storage collection from
-//! a function, consider using pagination with the collection iterators.
+//! [`Vector`], [`UnorderedMap`] (`unstable`) and [`UnorderedSet`] (`unstable`) implement `serde`
+//! [`Serialize`](serde::Serialize) as a sequence of their elements, or of `(key, value)` pairs for
+//! maps, so that view methods can return a reference to a reasonably sized collection. This loads
+//! every value from storage, so for large collections, return a [`Page`] (`unstable`) of values
+//! from [`Paginate::page`] instead, which takes a `from_index` and a `limit` and also implements
+//! `Serialize`. [`Deserialize`](serde::Deserialize) is not implemented, since collections are
+//! bound to their storage prefix.
 //!
 //! All of the collections implement [`BorshSerialize`](borsh::BorshSerialize) and
 //! [`BorshDeserialize`](borsh::BorshDeserialize) to be able to store the metadata of the
@@ -30,6 +33,20 @@
 //!
 //! - [`Vector`]: Analogous to [`Vec`] but not contiguous and persisted to storage.
 //!
+//! - [`Deque`] (`unstable`): Analogous to [`std::collections::VecDeque`], with `O(1)` pushes and
+//! pops at both ends.
+//!
+//! - [`RingBuffer`] (`unstable`): Sequence of the most recent elements up to a fixed capacity,
+//! where each push overwrites the oldest element once it is full.
+//!
+//! - [`Heap`] (`unstable`): Priority queue analogous to [`std::collections::BinaryHeap`].
+//!
+//! - [`MerkleVector`] (`unstable`): [`Vector`] that keeps a Merkle root over its elements and
+//! produces proofs of their inclusion.
+//!
+//! - [`PackedVector`] (`unstable`): [`Vector`] that stores several elements per storage entry,
+//! which lowers the storage cost of small elements.
+//!
 //! Maps:
 //!
 //! - [`LookupMap`] (`unstable`): Wrapper around key-value storage interactions, similar to
@@ -42,6 +59,27 @@
 //! - [`TreeMap`] (`unstable`): Storage version of [`std::collections::BTreeMap`]. Ordered by key,
 //! which comes at the cost of more expensive lookups and iteration.
 //!
+//! - [`ShardedMap`] (`unstable`): [`UnorderedMap`] split across a fixed number of shards by key
+//! hash, to keep the size of each shard small for very large maps.
+//!
+//! - [`LruMap`] (`unstable`): [`LookupMap`] with a maximum number of entries, which evicts the
+//! least recently used entry when full.
+//!
+//! - [`ExpiringMap`] (`unstable`): [`LookupMap`] whose entries expire at a block timestamp, after
+//! which they are treated as absent until their storage is reclaimed.
+//!
+//! - [`OrderedMap`] (`unstable`): Map which iterates in insertion order, also after entries are
+//! removed, with cursors for pages that stay valid when the map changes.
+//!
+//! - [`MultiMap`] (`unstable`): Map from each key to an [`UnorderedSet`] of values, which can be
+//! iterated and counted per key.
+//!
+//! - [`BiMap`] (`unstable`): Map whose entries can be looked up, updated and removed by either of
+//! two keys, which are kept consistent with each other.
+//!
+//! - [`GroupedList`] (`unstable`): Map from each key to a [`Vector`] of items, which can be
+//! appended to, paged through and removed per key.
+//!
 //! Sets:
 //!
 //! - [`LookupSet`] (`unstable`): Non-iterable storage version of [`std::collections::HashSet`].
@@ -49,6 +87,15 @@
 //! - [`UnorderedSet`] (`unstable`): Analogous to [`std::collections::HashSet`], and is an iterable
 //! version of [`LookupSet`] and persisted to storage.
 //!
+//! - [`TreeSet`] (`unstable`): Storage version of [`std::collections::BTreeSet`], the set
+//! counterpart of [`TreeMap`]. Ordered, with range iteration and the smallest and largest values.
+//!
+//! - [`BitSet`] (`unstable`): Set of `u64` indices stored as a bitmap, 64 indices per storage
+//! slot.
+//!
+//! - [`BloomFilter`] (`unstable`): Probabilistic set backed by a [`BitSet`], which can tell that
+//! an element was definitely not inserted without an exact lookup.
+//!
 //! Basic Types:
 //!
 //! - [`Lazy<T>`](Lazy): Lazily loaded type that can be used in place of a type `T`.
@@ -57,6 +104,48 @@
 //! - [`LazyOption<T>`](LazyOption): Lazily loaded, optional type that can be used in
 //! place of a type [`Option<T>`](Option). Will only be loaded when interacted with and will
 //! persist on [`Drop`].
+//!
+//! - [`Counter`] (`unstable`): Monotonically increasing `u64` or `u128` counter stored under its
+//! own key, to allocate identifiers.
+//!
+//! - [`Versioned<T>`](Versioned) (`unstable`): Value that is stored with the version of its type
+//! and upgraded from older versions when it is read, to change the type of the values of a
+//! collection without migrating all of them at once.
+//!
+//! - [`Compressed<T>`](Compressed) (`unstable`, `compression`): Value that is compressed in
+//! storage when it is large, to be used as the value of any collection.
+//!
+//! # Writing to storage
+//!
+//! Collections keep the values they load and the changes made to them in an in-memory cache. The
+//! changes are written to storage when the collection is dropped, which for the fields of the
+//! contract state happens after the method returns and the state is written, or earlier with the
+//! `flush` method that every collection has. [`Vector`], [`LookupMap`], [`UnorderedMap`] and
+//! [`UnorderedSet`] can instead write each change as it is made, with
+//! [`WritePolicy::WriteThrough`].
+//!
+//! The cache keeps every value a call accesses, so a method that iterates over a large collection
+//! can run out of memory. [`Vector`], [`LookupMap`], [`UnorderedMap`] and [`UnorderedSet`] can
+//! bound their cache with `set_cache_capacity`: methods that take `&mut self` write the changes
+//! and empty the cache before loading a value into a full cache. Values read through `&self`,
+//! including by iterators, are borrowed from the cache, so they are only evicted by the next
+//! `flush` or change, and a method that iterates should flush between chunks of the iteration.
+//!
+//! Whichever way the changes are written, all storage writes of a call are committed together at
+//! the end of the call, along with the promises it created. A promise is only executed in a later
+//! receipt, so it always observes every change made by the call that created it, regardless of
+//! the order in which the promise was built and the collections were flushed. If the call panics,
+//! both the storage writes and the promises are discarded.
+//!
+//! Changes to several collections can be grouped with [`transaction`] (`unstable`), which discards
+//! them all if one of the updates fails, or with a [`StateTransaction`] (`unstable`) over the
+//! contract state, which discards them unless it is committed.
+//!
+//! With the `unit-testing` feature, the gas that collections use on standard workloads can be
+//! compared with the [`bench`] (`unstable`) utilities.
+//!
+//! Collections of the deprecated [`collections`](crate::collections) module can be moved to their
+//! equivalents here with [`migrate_from`] (`unstable`).
 
 #[cfg(feature = "unstable")]
 mod lazy;
@@ -71,6 +160,31 @@ pub use lazy_option::LazyOption;
 pub mod vec;
 pub use vec::Vector;
 
+#[cfg(feature = "unstable")]
+pub mod deque;
+#[cfg(feature = "unstable")]
+pub use self::deque::Deque;
+
+#[cfg(feature = "unstable")]
+pub mod ring_buffer;
+#[cfg(feature = "unstable")]
+pub use self::ring_buffer::RingBuffer;
+
+#[cfg(feature = "unstable")]
+mod heap;
+#[cfg(feature = "unstable")]
+pub use self::heap::Heap;
+
+#[cfg(feature = "unstable")]
+pub mod packed_vector;
+#[cfg(feature = "unstable")]
+pub use self::packed_vector::PackedVector;
+
+#[cfg(feature = "unstable")]
+mod merkle_vector;
+#[cfg(feature = "unstable")]
+pub use self::merkle_vector::{MerkleProof, MerkleVector};
+
 #[cfg(feature = "unstable")]
 pub mod lookup_map;
 #[cfg(feature = "unstable")]
@@ -91,11 +205,97 @@ pub mod unordered_set;
 #[cfg(feature = "unstable")]
 pub use self::unordered_set::UnorderedSet;
 
+#[cfg(feature = "unstable")]
+pub mod tree_set;
+#[cfg(feature = "unstable")]
+pub use self::tree_set::TreeSet;
+
 #[cfg(feature = "unstable")]
 pub mod tree_map;
 #[cfg(feature = "unstable")]
 pub use self::tree_map::TreeMap;
 
+#[cfg(feature = "unstable")]
+pub mod sharded_map;
+#[cfg(feature = "unstable")]
+pub use self::sharded_map::ShardedMap;
+
+#[cfg(feature = "unstable")]
+mod lru_map;
+#[cfg(feature = "unstable")]
+pub use self::lru_map::LruMap;
+
+#[cfg(feature = "unstable")]
+mod expiring_map;
+#[cfg(feature = "unstable")]
+pub use self::expiring_map::ExpiringMap;
+
+#[cfg(feature = "unstable")]
+pub mod ordered_map;
+#[cfg(feature = "unstable")]
+pub use self::ordered_map::OrderedMap;
+
+#[cfg(feature = "unstable")]
+pub mod multi_map;
+#[cfg(feature = "unstable")]
+pub use self::multi_map::MultiMap;
+
+#[cfg(feature = "unstable")]
+mod bi_map;
+#[cfg(feature = "unstable")]
+pub use self::bi_map::BiMap;
+
+#[cfg(feature = "unstable")]
+pub mod grouped_list;
+#[cfg(feature = "unstable")]
+pub use self::grouped_list::GroupedList;
+
+#[cfg(feature = "unstable")]
+pub mod bit_set;
+#[cfg(feature = "unstable")]
+pub use self::bit_set::BitSet;
+
+#[cfg(feature = "unstable")]
+mod bloom_filter;
+#[cfg(feature = "unstable")]
+pub use self::bloom_filter::BloomFilter;
+
+#[cfg(feature = "unstable")]
+mod counter;
+#[cfg(feature = "unstable")]
+pub use self::counter::{Counter, CounterValue};
+
+#[cfg(feature = "unstable")]
+mod versioned;
+#[cfg(feature = "unstable")]
+pub use self::versioned::{FromVersion, NoPrevious, Versioned};
+
+#[cfg(all(feature = "unstable", feature = "compression"))]
+mod compressed;
+#[cfg(all(feature = "unstable", feature = "compression"))]
+pub use self::compressed::Compressed;
+
+#[cfg(feature = "unstable")]
+pub mod migrate_from;
+
+#[cfg(feature = "unstable")]
+mod clear;
+#[cfg(feature = "unstable")]
+pub use self::clear::ClearProgress;
+
+#[cfg(feature = "unstable")]
+mod paginate;
+#[cfg(feature = "unstable")]
+pub use self::paginate::{Page, Paginate};
+
+#[cfg(feature = "unstable")]
+mod transaction;
+#[cfg(feature = "unstable")]
+pub use self::transaction::{transaction, Snapshot, StateTransaction, Transactional};
+
+mod write_policy;
+pub use self::write_policy::WritePolicy;
+
 mod index_map;
 pub(crate) use self::index_map::IndexMap;
 
@@ -104,6 +304,9 @@ pub(crate) mod free_list;
 #[cfg(feature = "unstable")]
 pub(crate) use self::free_list::FreeList;
 
+#[cfg(all(feature = "unstable", feature = "unit-testing", not(target_arch = "wasm32")))]
+pub mod bench;
+
 /// Storage key hash function types and trait to override map hash functions.
 #[cfg(feature = "unstable")]
 pub mod key;
@@ -0,0 +1,436 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::store::key::{Identity, ToKey};
+use crate::store::{unordered_set, LookupMap, UnorderedSet};
+use crate::{env, IntoStorageKey};
+
+/// A map from each key to a set of values, stored on the storage trie.
+///
+/// The values of each key are stored in their own [`UnorderedSet`], under a prefix derived from
+/// the map prefix and the hash of the key, so the values of one key can be counted, iterated and
+/// updated without loading those of any other key. A key is present in the map as long as it has
+/// at least one value, and is removed along with its set once its last value is removed.
+///
+/// Like [`LookupMap`], the keys themselves cannot be iterated over.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::MultiMap;
+///
+/// let mut followers: MultiMap<String, String> = MultiMap::new(b"f");
+/// followers.insert("alice".to_string(), "bob".to_string());
+/// followers.insert("alice".to_string(), "carol".to_string());
+/// followers.insert("bob".to_string(), "alice".to_string());
+///
+/// assert_eq!(followers.count("alice"), 2);
+/// assert!(followers.contains("alice", "carol"));
+///
+/// assert!(followers.remove_value("bob", "alice"));
+/// assert!(!followers.contains_key("bob"));
+///
+/// let mut alice: Vec<_> = followers.get_all("alice").collect();
+/// alice.sort();
+/// assert_eq!(alice, [&"bob".to_string(), &"carol".to_string()]);
+/// ```
+pub struct MultiMap<K, V, H = Identity>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + Ord,
+    H: ToKey,
+{
+    prefix: Box<[u8]>,
+    sets: LookupMap<K, UnorderedSet<V>, H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for MultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.prefix, writer)?;
+        BorshSerialize::serialize(&self.sets, writer)?;
+        Ok(())
+    }
+}
+
+impl<K, V, H> BorshDeserialize for MultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            prefix: BorshDeserialize::deserialize(buf)?,
+            sets: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<K, V, H> Drop for MultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<K, V, H> fmt::Debug for MultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiMap").field("prefix", &self.prefix).finish()
+    }
+}
+
+impl<K, V> MultiMap<K, V, Identity>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + Ord,
+{
+    /// Create a new map. Use `prefix` as a unique prefix for keys.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::MultiMap;
+    ///
+    /// let mut map: MultiMap<String, u64> = MultiMap::new(b"m");
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<K, V, H> MultiMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + Ord,
+    H: ToKey,
+{
+    /// Initialize a [`MultiMap`] with a custom hash function for the keys of the map. The sets of
+    /// values always use the default hash function of [`UnorderedSet`].
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{MultiMap, key::Keccak256};
+    ///
+    /// let map = MultiMap::<String, String, Keccak256>::with_hasher(b"m");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key().into_boxed_slice();
+        let sets = LookupMap::with_hasher([&prefix[..], b"k"].concat());
+        Self { prefix, sets }
+    }
+
+    /// Storage prefix of the set of values of `key`.
+    fn values_prefix(prefix: &[u8], key: &K) -> Vec<u8> {
+        let key = key.try_to_vec().unwrap_or_else(|_| env::panic_str("Cannot serialize key"));
+        [prefix, b"v", &env::sha256_array(&key)].concat()
+    }
+
+    /// Returns `true` if the map contains at least one value for `k`.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`], [`ToOwned<Owned = K>`](ToOwned) and [`Ord`] on the borrowed form *must*
+    /// match those for the key type.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.sets.contains_key(k)
+    }
+
+    /// Returns the set of values of `k`, or `None` if the key has no values.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&UnorderedSet<V>>
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.sets.get(k)
+    }
+
+    /// Returns the number of values of `k`.
+    pub fn count<Q: ?Sized>(&self, k: &Q) -> u32
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.get(k).map_or(0, UnorderedSet::len)
+    }
+
+    /// Returns `true` if `value` is one of the values of `k`.
+    pub fn contains<Q: ?Sized, R: ?Sized>(&self, k: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+        V: Borrow<R>,
+        R: BorshSerialize + ToOwned<Owned = V> + Ord,
+    {
+        match self.get(k) {
+            Some(set) => set.contains(value),
+            None => false,
+        }
+    }
+
+    /// Returns an iterator over the values of `k`, in no particular order. The iterator is empty
+    /// if the key has no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::MultiMap;
+    ///
+    /// let mut map = MultiMap::new(b"m");
+    /// map.insert(1u8, "a".to_string());
+    ///
+    /// assert_eq!(map.get_all(&1).collect::<Vec<_>>(), [&"a".to_string()]);
+    /// assert_eq!(map.get_all(&2).count(), 0);
+    /// ```
+    pub fn get_all<Q: ?Sized>(&self, k: &Q) -> Values<'_, V>
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+        V: BorshDeserialize,
+    {
+        Values { inner: self.get(k).map(UnorderedSet::iter) }
+    }
+
+    /// Adds `value` to the values of `key`. Returns `false` if it was already one of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::MultiMap;
+    ///
+    /// let mut map = MultiMap::new(b"m");
+    /// assert!(map.insert(1u8, 10u64));
+    /// assert!(map.insert(1, 20));
+    /// assert!(!map.insert(1, 10));
+    /// assert_eq!(map.count(&1), 2);
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> bool
+    where
+        K: Clone + BorshDeserialize,
+        V: Clone + BorshDeserialize,
+    {
+        let prefix = &self.prefix;
+        self.sets
+            .entry(key)
+            .or_insert_with_key(|key| UnorderedSet::new(Self::values_prefix(prefix, key)))
+            .insert(value)
+    }
+
+    /// Removes `value` from the values of `k`, and removes the key once it has no values left.
+    /// Returns whether the value was present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::MultiMap;
+    ///
+    /// let mut map = MultiMap::new(b"m");
+    /// map.insert(1u8, 10u64);
+    ///
+    /// assert!(map.remove_value(&1, &10));
+    /// assert!(!map.remove_value(&1, &10));
+    /// assert!(!map.contains_key(&1));
+    /// ```
+    pub fn remove_value<Q: ?Sized, R: ?Sized>(&mut self, k: &Q, value: &R) -> bool
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+        V: Borrow<R> + Clone + BorshDeserialize,
+        R: BorshSerialize + ToOwned<Owned = V> + Ord,
+    {
+        let set = match self.sets.get_mut(k) {
+            Some(set) => set,
+            None => return false,
+        };
+        if !set.remove(value) {
+            return false;
+        }
+        if set.is_empty() {
+            // Clear the set to remove the storage of its removed elements.
+            set.clear();
+            self.sets.remove(k);
+        }
+        true
+    }
+
+    /// Removes all values of `k`, returning how many there were.
+    pub fn remove_all<Q: ?Sized>(&mut self, k: &Q) -> u32
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+        V: Clone + BorshDeserialize,
+    {
+        match self.sets.remove(k) {
+            Some(mut set) => {
+                let count = set.len();
+                set.clear();
+                count
+            }
+            None => 0,
+        }
+    }
+
+    /// Flushes the intermediate values of the map, and of the sets of values that were loaded,
+    /// before this is called when the structure is [`Drop`]ed. This will write all modified values
+    /// to storage but keep all cached values in memory.
+    pub fn flush(&mut self) {
+        for set in self.sets.cached_values_mut() {
+            set.flush();
+        }
+        self.sets.flush();
+    }
+}
+
+/// An iterator over the values of a key of a [`MultiMap`], returned by
+/// [`MultiMap::get_all`].
+pub struct Values<'a, V>
+where
+    V: BorshSerialize + Ord + BorshDeserialize,
+{
+    inner: Option<unordered_set::Iter<'a, V>>,
+}
+
+impl<'a, V> Iterator for Values<'a, V>
+where
+    V: BorshSerialize + Ord + BorshDeserialize,
+{
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.as_ref().map_or((0, Some(0)), Iterator::size_hint)
+    }
+}
+
+impl<'a, V> ExactSizeIterator for Values<'a, V> where V: BorshSerialize + Ord + BorshDeserialize {}
+impl<'a, V> FusedIterator for Values<'a, V> where V: BorshSerialize + Ord + BorshDeserialize {}
+
+impl<'a, V> DoubleEndedIterator for Values<'a, V>
+where
+    V: BorshSerialize + Ord + BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next_back()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::MultiMap;
+    use crate::test_utils::test_env::setup_free;
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Insert(u8, u8),
+        RemoveValue(u8, u8),
+        RemoveAll(u8),
+        Contains(u8, u8),
+        GetAll(u8),
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sm = MultiMap::new(b"m");
+            let mut mm = BTreeMap::<u8, BTreeSet<u8>>::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Insert(k, v) => {
+                            assert_eq!(sm.insert(k, v), mm.entry(k).or_default().insert(v));
+                        }
+                        Op::RemoveValue(k, v) => {
+                            let removed = mm.get_mut(&k).map_or(false, |set| set.remove(&v));
+                            if mm.get(&k).map_or(false, BTreeSet::is_empty) {
+                                mm.remove(&k);
+                            }
+                            assert_eq!(sm.remove_value(&k, &v), removed);
+                        }
+                        Op::RemoveAll(k) => {
+                            let count = mm.remove(&k).map_or(0, |set| set.len() as u32);
+                            assert_eq!(sm.remove_all(&k), count);
+                        }
+                        Op::Contains(k, v) => {
+                            let contains = mm.get(&k).map_or(false, |set| set.contains(&v));
+                            assert_eq!(sm.contains(&k, &v), contains);
+                        }
+                        Op::GetAll(k) => {
+                            let values: BTreeSet<u8> = sm.get_all(&k).copied().collect();
+                            assert_eq!(values, mm.get(&k).cloned().unwrap_or_default());
+                            assert_eq!(sm.count(&k) as usize, values.len());
+                            assert_eq!(sm.contains_key(&k), mm.contains_key(&k));
+                        }
+                        Op::Flush => sm.flush(),
+                        Op::Reset => {
+                            sm.flush();
+                            let serialized = sm.try_to_vec().unwrap();
+                            sm = MultiMap::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                }
+            }
+
+            // Removing every value leaves nothing in storage.
+            for (k, values) in mm {
+                for v in values {
+                    assert!(sm.remove_value(&k, &v));
+                }
+            }
+            drop(sm);
+            crate::mock::with_mocked_blockchain(|b| assert!(b.take_storage().is_empty()));
+        }
+    }
+}
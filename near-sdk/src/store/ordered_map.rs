@@ -0,0 +1,545 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::ERR_INCONSISTENT_STATE;
+use crate::store::key::{Identity, Sha256, ToKey};
+use crate::store::{LookupMap, Page};
+use crate::{env, IntoStorageKey};
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// Key of an entry with its neighbours in insertion order.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Node<K> {
+    key: K,
+    prev: Option<u32>,
+    next: Option<u32>,
+}
+
+/// Value of an entry with the id of its node.
+#[derive(BorshSerialize, BorshDeserialize)]
+struct Entry<V> {
+    id: u32,
+    value: V,
+}
+
+/// A map which iterates over its entries in the order they were inserted, also after entries
+/// are removed.
+///
+/// Every entry gets an id when it is inserted, which is larger than the ids of all entries
+/// inserted before it and is never reused. The entries are linked in insertion order by their
+/// ids, so inserting and removing an entry takes `O(1)` storage operations. Unlike
+/// [`UnorderedMap`](super::UnorderedMap), which moves its last entry into the place of a removed
+/// one, removing an entry doesn't change the order of the others.
+///
+/// The ids are used as cursors by [`page`](Self::page), which stay valid when entries are
+/// inserted or removed between pages, so they can be handed out to clients.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::OrderedMap;
+///
+/// let mut map: OrderedMap<String, u8> = OrderedMap::new(b"o");
+/// map.insert("c".to_string(), 1);
+/// map.insert("a".to_string(), 2);
+/// map.insert("b".to_string(), 3);
+/// map.remove("c");
+/// map.insert("c".to_string(), 4);
+///
+/// let keys: Vec<_> = map.keys().map(String::as_str).collect();
+/// assert_eq!(keys, ["a", "b", "c"]);
+/// ```
+pub struct OrderedMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    len: u32,
+    next_id: u32,
+    head: Option<u32>,
+    tail: Option<u32>,
+    nodes: LookupMap<u32, Node<K>, Identity>,
+    entries: LookupMap<K, Entry<V>, H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for OrderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.len, writer)?;
+        BorshSerialize::serialize(&self.next_id, writer)?;
+        BorshSerialize::serialize(&self.head, writer)?;
+        BorshSerialize::serialize(&self.tail, writer)?;
+        BorshSerialize::serialize(&self.nodes, writer)?;
+        BorshSerialize::serialize(&self.entries, writer)?;
+        Ok(())
+    }
+}
+
+impl<K, V, H> BorshDeserialize for OrderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            len: BorshDeserialize::deserialize(buf)?,
+            next_id: BorshDeserialize::deserialize(buf)?,
+            head: BorshDeserialize::deserialize(buf)?,
+            tail: BorshDeserialize::deserialize(buf)?,
+            nodes: BorshDeserialize::deserialize(buf)?,
+            entries: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<K, V, H> fmt::Debug for OrderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderedMap")
+            .field("len", &self.len)
+            .field("head", &self.head)
+            .field("tail", &self.tail)
+            .finish()
+    }
+}
+
+impl<K, V> OrderedMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new map. Use `prefix` as a unique prefix for keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::OrderedMap;
+    ///
+    /// let mut map: OrderedMap<String, u8> = OrderedMap::new(b"o");
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<K, V, H> OrderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize an [`OrderedMap`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{OrderedMap, key::Keccak256};
+    ///
+    /// let map = OrderedMap::<String, String, Keccak256>::with_hasher(b"o");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        let prefix = prefix.into_storage_key();
+        Self {
+            len: 0,
+            next_id: 0,
+            head: None,
+            tail: None,
+            nodes: LookupMap::with_hasher([prefix.as_slice(), b"n"].concat()),
+            entries: LookupMap::with_hasher([prefix.as_slice(), b"e"].concat()),
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Flushes the intermediate values of the map before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.nodes.flush();
+        self.entries.flush();
+    }
+}
+
+impl<K, V, H> OrderedMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.entries.contains_key(k)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get(k).map(|entry| &entry.value)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get_mut(k).map(|entry| &mut entry.value)
+    }
+
+    /// Returns the cursor of the entry of the key, which [`page`](Self::page) starts from.
+    pub fn cursor<Q: ?Sized>(&self, k: &Q) -> Option<u32>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.entries.get(k).map(|entry| entry.id)
+    }
+
+    fn node_mut(&mut self, id: u32) -> &mut Node<K> {
+        expect_consistent_state(self.nodes.get_mut(&id))
+    }
+
+    fn node_and_value(&self, id: u32) -> (&Node<K>, &V) {
+        let node = expect_consistent_state(self.nodes.get(&id));
+        let entry = expect_consistent_state(self.entries.get(&node.key));
+        (node, &entry.value)
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map did not have this key present, the entry is added after all other entries and
+    /// [`None`] is returned. Otherwise, the value is updated, the entry keeps its position and
+    /// the old value is returned.
+    pub fn insert(&mut self, k: K, value: V) -> Option<V> {
+        if let Some(entry) = self.entries.get_mut(&k) {
+            return Some(core::mem::replace(&mut entry.value, value));
+        }
+
+        let id = self.next_id;
+        self.next_id =
+            self.next_id.checked_add(1).unwrap_or_else(|| env::panic_str("Too many insertions"));
+        self.len += 1;
+        match self.tail {
+            Some(tail) => self.node_mut(tail).next = Some(id),
+            None => self.head = Some(id),
+        }
+        self.nodes.set(id, Some(Node { key: k.clone(), prev: self.tail, next: None }));
+        self.tail = Some(id);
+        self.entries.set(k, Some(Entry { id, value }));
+        None
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in
+    /// the map. The order of the other entries is not changed.
+    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        let Entry { id, value } = self.entries.remove(k)?;
+        let Node { prev, next, .. } = expect_consistent_state(self.nodes.remove(&id));
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// Removes up to `limit` entries that were inserted first and returns them in insertion
+    /// order, to process the entries of the map a chunk at a time over multiple calls, like a
+    /// queue of work items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::OrderedMap;
+    ///
+    /// let mut queue: OrderedMap<u32, u32> = OrderedMap::new(b"o");
+    /// queue.extend([(7, 70), (3, 30), (5, 50)]);
+    ///
+    /// assert_eq!(queue.drain_limit(2), [(7, 70), (3, 30)]);
+    /// assert_eq!(queue.drain_limit(2), [(5, 50)]);
+    /// assert!(queue.is_empty());
+    /// ```
+    pub fn drain_limit(&mut self, limit: u32) -> Vec<(K, V)> {
+        let keys: Vec<K> = self.keys().take(limit as usize).cloned().collect();
+        keys.into_iter()
+            .map(|k| {
+                let value = expect_consistent_state(self.remove(&k));
+                (k, value)
+            })
+            .collect()
+    }
+
+    /// An iterator visiting all key-value pairs in insertion order.
+    /// The iterator element type is `(&'a K, &'a V)`.
+    pub fn iter(&self) -> Iter<'_, K, V, H> {
+        Iter { map: self, front: self.head, back: self.tail, remaining: self.len }
+    }
+
+    /// An iterator visiting all keys in insertion order.
+    /// The iterator element type is `&'a K`.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// An iterator visiting all values in insertion order.
+    /// The iterator element type is `&'a V`.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Returns up to `limit` entries in insertion order, starting from the entry at `cursor`, or
+    /// from the first entry if `cursor` is `None`. The `next_cursor` of the page is the cursor of
+    /// the entry after the last one of the page.
+    ///
+    /// If the entry at `cursor` has been removed since the cursor was returned, the page starts
+    /// from the next entry that was inserted after it. It is found by checking the ids after the
+    /// cursor one by one, so this costs a storage read for every entry that was inserted after
+    /// and then removed before the next remaining one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::OrderedMap;
+    ///
+    /// let mut map: OrderedMap<u32, u32> = OrderedMap::new(b"o");
+    /// map.extend((0..5).map(|i| (i, i * 10)));
+    ///
+    /// let first = map.page(None, 2);
+    /// assert_eq!(first.items, [(&0, &0), (&1, &10)]);
+    /// let cursor = first.next_cursor;
+    ///
+    /// // Removing entries doesn't affect the next page.
+    /// map.remove(&0);
+    /// let second = map.page(cursor, 2);
+    /// assert_eq!(second.items, [(&2, &20), (&3, &30)]);
+    /// ```
+    pub fn page(&self, cursor: Option<u32>, limit: u32) -> Page<(&K, &V)> {
+        let start = match cursor {
+            None => self.head,
+            Some(cursor) => (cursor..self.next_id).find(|id| self.nodes.contains_key(id)),
+        };
+        let mut iter = Iter { map: self, front: start, back: self.tail, remaining: self.len };
+        let items = iter.by_ref().take(limit as usize).collect();
+        Page { items, next_cursor: iter.front }
+    }
+}
+
+impl<K, V, H> Extend<(K, V)> for OrderedMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+/// An iterator over elements of an [`OrderedMap`], in insertion order.
+///
+/// This `struct` is created by the [`iter`](OrderedMap::iter) method on [`OrderedMap`].
+pub struct Iter<'a, K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    map: &'a OrderedMap<K, V, H>,
+    /// Node of the next entry from the front.
+    front: Option<u32>,
+    /// Node of the next entry from the back.
+    back: Option<u32>,
+    /// Number of entries left, which is only an upper bound for the iterators of pages.
+    remaining: u32,
+}
+
+impl<'a, K, V, H> Iterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (node, value) = self.map.node_and_value(self.front?);
+        self.front = node.next;
+        self.remaining -= 1;
+        Some((&node.key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl<'a, K, V, H> ExactSizeIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+}
+
+impl<'a, K, V, H> FusedIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+}
+
+impl<'a, K, V, H> DoubleEndedIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let (node, value) = self.map.node_and_value(self.back?);
+        self.back = node.prev;
+        self.remaining -= 1;
+        Some((&node.key, value))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    use super::OrderedMap;
+
+    #[test]
+    fn insertion_order_across_removals() {
+        let mut map = OrderedMap::new(b"o");
+        let mut baseline = Vec::new();
+        for i in 0..50u32 {
+            map.insert(i, i);
+            baseline.push((i, i));
+        }
+        for i in (0..50).step_by(3).chain([1, 49, 47]) {
+            assert_eq!(map.remove(&i), Some(i));
+            baseline.retain(|&(k, _)| k != i);
+        }
+        assert_eq!(map.remove(&0), None);
+        // Updating a value keeps the position, re-inserting a key moves it to the end.
+        assert_eq!(map.insert(2, 20), Some(2));
+        baseline.iter_mut().find(|(k, _)| *k == 2).unwrap().1 = 20;
+        assert_eq!(map.insert(0, 0), None);
+        baseline.push((0, 0));
+
+        assert_eq!(map.len() as usize, baseline.len());
+        let actual: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(actual, baseline);
+        let reversed: Vec<_> = map.iter().rev().map(|(&k, &v)| (k, v)).collect();
+        assert!(reversed.iter().eq(baseline.iter().rev()));
+        let mut iter = map.iter();
+        assert_eq!(iter.next_back().map(|(&k, &v)| (k, v)), baseline.last().copied());
+        assert_eq!(iter.len(), baseline.len() - 1);
+        assert!(iter.map(|(&k, _)| k).eq(baseline[..baseline.len() - 1].iter().map(|&(k, _)| k)));
+
+        for k in baseline.iter().map(|&(k, _)| k).collect::<Vec<_>>() {
+            map.remove(&k);
+        }
+        assert!(map.is_empty());
+        assert_eq!(map.iter().next(), None);
+        map.insert(7, 7);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&7, &7)]);
+    }
+
+    #[test]
+    fn stable_cursors() {
+        let mut map = OrderedMap::new(b"o");
+        map.extend((0..10u8).map(|i| (i, ())));
+
+        let page = map.page(None, 4);
+        assert_eq!(page.items.len(), 4);
+        let cursor = page.next_cursor;
+        assert_eq!(cursor, map.cursor(&4));
+
+        // Entries before the cursor are removed, and the entry at the cursor too.
+        map.remove(&1);
+        map.remove(&4);
+        map.remove(&5);
+        map.insert(10, ());
+        let page = map.page(cursor, 4);
+        assert_eq!(page.items.iter().map(|(k, _)| **k).collect::<Vec<_>>(), [6, 7, 8, 9]);
+        let page = map.page(page.next_cursor, 4);
+        assert_eq!(page.items.iter().map(|(k, _)| **k).collect::<Vec<_>>(), [10]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn persisted() {
+        let mut map = OrderedMap::new(b"o");
+        map.extend([("b".to_string(), 1u8), ("a".to_string(), 2)]);
+        map.flush();
+
+        let serialized = map.try_to_vec().unwrap();
+        let mut restored = OrderedMap::<String, u8>::try_from_slice(&serialized).unwrap();
+        restored.remove("b");
+        restored.insert("c".to_string(), 3);
+        let actual: Vec<_> = restored.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(actual, [("a", 2), ("c", 3)]);
+    }
+}
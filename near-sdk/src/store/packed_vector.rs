@@ -0,0 +1,415 @@
+use core::iter::FusedIterator;
+use core::ops::Range;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{Vector, ERR_INCONSISTENT_STATE};
+use crate::{env, IntoStorageKey};
+
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+const ERR_ZERO_CHUNK_SIZE: &str = "Chunk size must be greater than zero";
+
+/// Number of elements stored together by [`PackedVector::new`].
+pub const DEFAULT_CHUNK_SIZE: u32 = 32;
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// A vector which stores its elements in chunks of a fixed number of elements, each chunk in a
+/// single storage entry.
+///
+/// Every storage entry has an overhead of about 40 bytes of staked storage on top of its key and
+/// value, which dominates the cost of storing small elements like integers one per entry, as
+/// [`Vector`] does. Packing them reduces the storage cost per element, at the cost of reading and
+/// writing the whole chunk of an element when accessing or modifying it. Chunks are cached like
+/// the elements of a [`Vector`], so accessing neighbouring elements only reads their chunk once.
+///
+/// The chunk size is fixed when the vector is created and persisted with it.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::PackedVector;
+///
+/// let mut vec: PackedVector<u64> = PackedVector::with_chunk_size(b"p", 4);
+/// vec.extend(0..10);
+/// assert_eq!(vec.len(), 10);
+/// assert_eq!(vec.get(5), Some(&5));
+///
+/// // 10 elements in chunks of 4 take 3 storage entries.
+/// assert_eq!(vec.chunk_count(), 3);
+///
+/// *vec.get_mut(5).unwrap() = 50;
+/// assert_eq!(vec.pop(), Some(9));
+/// assert_eq!(vec.iter().rev().take(2).copied().collect::<Vec<_>>(), [8, 7]);
+/// ```
+pub struct PackedVector<T>
+where
+    T: BorshSerialize,
+{
+    len: u32,
+    chunk_size: u32,
+    chunks: Vector<Vec<T>>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for PackedVector<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.len, writer)?;
+        BorshSerialize::serialize(&self.chunk_size, writer)?;
+        BorshSerialize::serialize(&self.chunks, writer)?;
+        Ok(())
+    }
+}
+
+impl<T> BorshDeserialize for PackedVector<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            len: BorshDeserialize::deserialize(buf)?,
+            chunk_size: BorshDeserialize::deserialize(buf)?,
+            chunks: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<T> PackedVector<T>
+where
+    T: BorshSerialize,
+{
+    /// Create a new vector storing [`DEFAULT_CHUNK_SIZE`] elements per storage entry.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::PackedVector;
+    ///
+    /// let mut vec: PackedVector<u8> = PackedVector::new(b"p");
+    /// ```
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_chunk_size(prefix, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Create a new vector storing `chunk_size` elements per storage entry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn with_chunk_size<S>(prefix: S, chunk_size: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if chunk_size == 0 {
+            env::panic_str(ERR_ZERO_CHUNK_SIZE);
+        }
+        Self { len: 0, chunk_size, chunks: Vector::new(prefix) }
+    }
+
+    /// Returns the number of elements in the vector, also referred to as its size.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements stored in each storage entry.
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    /// Returns the number of storage entries used by the elements.
+    pub fn chunk_count(&self) -> u32 {
+        self.chunks.len()
+    }
+
+    /// Removes all elements from the vector, and the chunks storing them from storage.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+        self.len = 0;
+    }
+
+    /// Flushes the cache and writes all modified chunks to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.chunks.flush();
+    }
+
+    /// Index of the chunk of the element at `index`, and of the element in the chunk.
+    fn position(&self, index: u32) -> (u32, usize) {
+        (index / self.chunk_size, (index % self.chunk_size) as usize)
+    }
+}
+
+impl<T> PackedVector<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Returns the element by index or `None` if it is out of bounds. This loads the whole chunk
+    /// of the element if it is not cached yet.
+    pub fn get(&self, index: u32) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let (chunk, offset) = self.position(index);
+        Some(expect_consistent_state(self.chunks.get(chunk).and_then(|chunk| chunk.get(offset))))
+    }
+
+    /// Returns a mutable reference to the element at the `index` if it exists. The whole chunk of
+    /// the element is written to storage when the vector is flushed.
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let (chunk, offset) = self.position(index);
+        Some(expect_consistent_state(
+            self.chunks.get_mut(chunk).and_then(|chunk| chunk.get_mut(offset)),
+        ))
+    }
+
+    /// Replaces the element at `index` with `value`, returning the previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn replace(&mut self, index: u32, value: T) -> T {
+        let element =
+            self.get_mut(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        core::mem::replace(element, value)
+    }
+
+    /// Appends an element to the back of the collection. A new chunk is only created when the
+    /// last one is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if new length exceeds `u32::MAX`
+    pub fn push(&mut self, element: T) {
+        let index = self.len;
+        let new_len =
+            index.checked_add(1).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS));
+        let (chunk, offset) = self.position(index);
+        if offset == 0 {
+            let mut elements = Vec::with_capacity(self.chunk_size.min(DEFAULT_CHUNK_SIZE) as usize);
+            elements.push(element);
+            self.chunks.push(elements);
+        } else {
+            expect_consistent_state(self.chunks.get_mut(chunk)).push(element);
+        }
+        self.len = new_len;
+    }
+
+    /// Removes the last element from a vector and returns it, or `None` if it is empty. The last
+    /// chunk is removed from storage when its last element is popped.
+    pub fn pop(&mut self) -> Option<T> {
+        let index = self.len.checked_sub(1)?;
+        let (chunk, offset) = self.position(index);
+        let element = if offset == 0 {
+            let mut elements = expect_consistent_state(self.chunks.pop());
+            expect_consistent_state(elements.pop())
+        } else {
+            expect_consistent_state(self.chunks.get_mut(chunk).and_then(|chunk| chunk.pop()))
+        };
+        self.len = index;
+        Some(element)
+    }
+
+    /// Removes an element from the vector and returns it. The removed element is replaced by the
+    /// last element of the vector.
+    ///
+    /// This does not preserve ordering, but is `O(1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: u32) -> T {
+        if index >= self.len {
+            env::panic_str(ERR_INDEX_OUT_OF_BOUNDS);
+        }
+        let last = expect_consistent_state(self.pop());
+        if index == self.len {
+            last
+        } else {
+            self.replace(index, last)
+        }
+    }
+
+    /// Returns an iterator over the vector. Elements of a chunk are only loaded once.
+    pub fn iter(&self) -> Iter<T> {
+        Iter { vec: self, range: 0..self.len }
+    }
+}
+
+impl<T> Drop for PackedVector<T>
+where
+    T: BorshSerialize,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T> Extend<T> for PackedVector<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for element in iter {
+            self.push(element)
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PackedVector<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> fmt::Debug for PackedVector<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            fmt::Debug::fmt(&self.iter().collect::<Vec<_>>(), f)
+        } else {
+            f.debug_struct("PackedVector")
+                .field("len", &self.len)
+                .field("chunk_size", &self.chunk_size)
+                .field("chunks", &self.chunks)
+                .finish()
+        }
+    }
+}
+
+/// An iterator over references to each element of a [`PackedVector`].
+pub struct Iter<'a, T>
+where
+    T: BorshSerialize,
+{
+    vec: &'a PackedVector<T>,
+    range: Range<u32>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.range.len();
+        (remaining, Some(remaining))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth(n)?;
+        Some(self.vec.get(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize {}
+impl<'a, T> FusedIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize {}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let index = self.range.nth_back(n)?;
+        Some(self.vec.get(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::PackedVector;
+    use borsh::BorshSerialize;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn matches_vec() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut vec = PackedVector::with_chunk_size(b"p", 3);
+        let mut baseline = Vec::new();
+        for _ in 0..500 {
+            match rng.gen_range(0..5) {
+                0 | 1 => {
+                    let value = rng.gen::<u16>();
+                    vec.push(value);
+                    baseline.push(value);
+                }
+                2 => assert_eq!(vec.pop(), baseline.pop()),
+                3 if !baseline.is_empty() => {
+                    let index = rng.gen_range(0..baseline.len());
+                    assert_eq!(vec.swap_remove(index as u32), baseline.swap_remove(index));
+                }
+                _ if !baseline.is_empty() => {
+                    let index = rng.gen_range(0..baseline.len());
+                    let value = rng.gen::<u16>();
+                    assert_eq!(vec.replace(index as u32, value), baseline[index]);
+                    baseline[index] = value;
+                }
+                _ => {}
+            }
+            assert_eq!(vec.len() as usize, baseline.len());
+            assert_eq!(vec.chunk_count() as usize, (baseline.len() + 2) / 3);
+            if rng.gen_bool(0.1) {
+                vec.flush();
+            }
+        }
+        assert!(vec.iter().eq(baseline.iter()));
+        assert!(vec.iter().rev().eq(baseline.iter().rev()));
+    }
+
+    #[test]
+    fn storage_entries() {
+        let mut vec = PackedVector::with_chunk_size(b"p", 8);
+        vec.extend(0u8..20);
+        drop(vec);
+
+        let storage = crate::mock::with_mocked_blockchain(|b| b.take_storage());
+        assert_eq!(storage.len(), 3);
+        let chunk = storage.get(&[b"p".as_slice(), &1u32.to_le_bytes()].concat()).unwrap();
+        assert_eq!(chunk, &(8u8..16).collect::<Vec<_>>().try_to_vec().unwrap());
+    }
+}
@@ -0,0 +1,189 @@
+use std::ops::Bound;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::key::ToKey;
+use super::{TreeMap, UnorderedMap, UnorderedSet, Vector};
+
+/// Items of a collection starting at an index, returned by [`Paginate::page`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+#[serde(crate = "crate::serde")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Index of the item after the last one of the page, to request the next page from, or
+    /// `None` if this is the last page.
+    pub next_cursor: Option<u32>,
+}
+
+impl<T> Page<T> {
+    fn new<I>(items: I, from_index: u32, limit: u32, len: u32) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let items: Vec<T> = items.into_iter().take(limit as usize).collect();
+        let next = from_index.saturating_add(items.len() as u32);
+        Self { items, next_cursor: if next < len { Some(next) } else { None } }
+    }
+}
+
+/// Collections that can be read a page at a time, in the order of their iterators.
+///
+/// Indices are positions in iteration order, so the `next_cursor` of a page is the `from_index`
+/// of the next one. Changes to the collection in-between pages can shift the positions of the
+/// items, like for any pagination by index.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::{Page, Paginate, UnorderedMap};
+///
+/// let mut map: UnorderedMap<String, u32> = UnorderedMap::new(b"m");
+/// map.extend((0..25).map(|i| (format!("account-{}", i), i)));
+///
+/// let page = map.page(0, 10);
+/// assert_eq!(page.items.len(), 10);
+/// assert_eq!(page.next_cursor, Some(10));
+///
+/// let last = map.page(20, 10);
+/// assert_eq!(last.items.len(), 5);
+/// assert_eq!(last.next_cursor, None);
+/// ```
+///
+/// A [`Page`] serializes with `serde`, so a view method can return one instead of a whole
+/// collection, which keeps the number of values loaded per call bounded:
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::near_bindgen;
+/// use near_sdk::store::{Page, Paginate, UnorderedMap};
+///
+/// #[near_bindgen]
+/// #[derive(BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {
+///     balances: UnorderedMap<String, u32>,
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn get_balances(&self, from_index: u32, limit: u32) -> Page<(&String, &u32)> {
+///         self.balances.page(from_index, limit.min(100))
+///     }
+/// }
+///
+/// let mut balances = UnorderedMap::new(b"b");
+/// balances.extend([("alice".to_string(), 5), ("bob".to_string(), 7)]);
+/// let contract = Contract { balances };
+/// assert_eq!(
+///     near_sdk::serde_json::to_string(&contract.get_balances(1, 10)).unwrap(),
+///     r#"{"items":[["bob",7]],"next_cursor":null}"#
+/// );
+/// ```
+pub trait Paginate<'a> {
+    /// Type of the items of a page.
+    type Item;
+
+    /// Returns up to `limit` items, starting from the item at `from_index`.
+    fn page(&'a self, from_index: u32, limit: u32) -> Page<Self::Item>;
+}
+
+impl<'a, T> Paginate<'a> for Vector<T>
+where
+    T: BorshSerialize + BorshDeserialize + 'a,
+{
+    type Item = &'a T;
+
+    fn page(&'a self, from_index: u32, limit: u32) -> Page<&'a T> {
+        Page::new(self.iter().skip(from_index as usize), from_index, limit, self.len())
+    }
+}
+
+impl<'a, T, H> Paginate<'a> for UnorderedSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + 'a,
+    H: ToKey + 'a,
+{
+    type Item = &'a T;
+
+    fn page(&'a self, from_index: u32, limit: u32) -> Page<&'a T> {
+        Page::new(self.iter().skip(from_index as usize), from_index, limit, self.len())
+    }
+}
+
+impl<'a, K, V, H> Paginate<'a> for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone + 'a,
+    V: BorshSerialize + BorshDeserialize + 'a,
+    H: ToKey + 'a,
+{
+    type Item = (&'a K, &'a V);
+
+    fn page(&'a self, from_index: u32, limit: u32) -> Page<(&'a K, &'a V)> {
+        Page::new(self.iter().skip(from_index as usize), from_index, limit, self.len())
+    }
+}
+
+/// Finds the first item of the page with [`TreeMap::select`], so that the items before it are not
+/// loaded.
+impl<'a, K, V, H> Paginate<'a> for TreeMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone + 'a,
+    V: BorshSerialize + BorshDeserialize + 'a,
+    H: ToKey + 'a,
+{
+    type Item = (&'a K, &'a V);
+
+    fn page(&'a self, from_index: u32, limit: u32) -> Page<(&'a K, &'a V)> {
+        match self.select(from_index) {
+            Some((first, _)) => Page::new(
+                self.range((Bound::Included(first), Bound::Unbounded)),
+                from_index,
+                limit,
+                self.len(),
+            ),
+            None => Page { items: Vec::new(), next_cursor: None },
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pages<'a, C: Paginate<'a>>(collection: &'a C, limit: u32) -> Vec<Vec<C::Item>> {
+        let mut pages = Vec::new();
+        let mut cursor = Some(0);
+        while let Some(from_index) = cursor {
+            let page = collection.page(from_index, limit);
+            cursor = page.next_cursor;
+            pages.push(page.items);
+        }
+        pages
+    }
+
+    #[test]
+    fn pages_cover_iteration_order() {
+        let mut vec = Vector::new(b"v");
+        vec.extend(0..10u32);
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(0..10u32);
+        set.remove(&4);
+        let mut map = UnorderedMap::new(b"m");
+        map.extend((0..10u32).map(|i| (i, i)));
+        map.remove(&4);
+        let mut tree = TreeMap::new(b"t");
+        tree.extend((0..10u32).rev().map(|i| (i, i)));
+
+        let vec_pages = pages(&vec, 4);
+        assert_eq!(vec_pages.iter().map(Vec::len).collect::<Vec<_>>(), [4, 4, 2]);
+        assert!(vec_pages.concat().into_iter().eq(vec.iter()));
+
+        assert!(pages(&set, 4).concat().into_iter().eq(set.iter()));
+        assert!(pages(&map, 4).concat().into_iter().eq(map.iter()));
+        assert!(pages(&tree, 3).concat().into_iter().eq(tree.iter()));
+
+        assert_eq!(tree.page(10, 3), Page { items: Vec::new(), next_cursor: None });
+        assert_eq!(vec.page(8, 2).next_cursor, None);
+    }
+}
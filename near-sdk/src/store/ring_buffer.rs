@@ -0,0 +1,441 @@
+use std::fmt;
+use std::iter::{FusedIterator, Rev};
+use std::ops::Range;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{IndexMap, ERR_INCONSISTENT_STATE};
+use crate::{env, IntoStorageKey};
+
+const ERR_ZERO_CAPACITY: &str = "RingBuffer capacity must be greater than zero";
+const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+
+fn expect_consistent_state<T>(val: Option<T>) -> T {
+    val.unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE))
+}
+
+/// A buffer of the most recent elements pushed to it, up to a fixed capacity, stored on the trie.
+/// Once the buffer is full, each push overwrites the oldest element.
+///
+/// Elements are stored in `capacity` storage slots that are reused in turn, so the storage used
+/// by the buffer never grows past the capacity, however many elements are pushed. This fits
+/// bounded histories like recent prices for a time-weighted average, or a feed of recent events.
+///
+/// Like [`Vector`](crate::store::Vector), changes are cached and only written to storage when
+/// the buffer is dropped, or when [`RingBuffer::flush`] is called.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::RingBuffer;
+///
+/// let mut prices: RingBuffer<u128> = RingBuffer::new(b"p", 3);
+/// for price in [10, 12, 11, 15] {
+///     prices.push(price);
+/// }
+///
+/// // The oldest price was overwritten.
+/// assert!(Iterator::eq(prices.iter(), [12, 11, 15].iter()));
+///
+/// let recent: Vec<_> = prices.iter_recent(2).copied().collect();
+/// assert_eq!(recent, [15, 11]);
+/// assert_eq!(prices.iter().sum::<u128>() / u128::from(prices.len()), 12);
+/// ```
+pub struct RingBuffer<T>
+where
+    T: BorshSerialize,
+{
+    capacity: u32,
+    /// Storage index of the oldest element.
+    head: u32,
+    len: u32,
+    values: IndexMap<T>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T> BorshSerialize for RingBuffer<T>
+where
+    T: BorshSerialize,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.capacity, writer)?;
+        BorshSerialize::serialize(&self.head, writer)?;
+        BorshSerialize::serialize(&self.len, writer)?;
+        BorshSerialize::serialize(&self.values, writer)?;
+        Ok(())
+    }
+}
+
+impl<T> BorshDeserialize for RingBuffer<T>
+where
+    T: BorshSerialize,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self {
+            capacity: BorshDeserialize::deserialize(buf)?,
+            head: BorshDeserialize::deserialize(buf)?,
+            len: BorshDeserialize::deserialize(buf)?,
+            values: BorshDeserialize::deserialize(buf)?,
+        })
+    }
+}
+
+impl<T> Drop for RingBuffer<T>
+where
+    T: BorshSerialize,
+{
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+impl<T> RingBuffer<T>
+where
+    T: BorshSerialize,
+{
+    /// Create a new buffer that keeps up to `capacity` elements. Prefixes storage access with the
+    /// prefix provided.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::RingBuffer;
+    ///
+    /// let mut events: RingBuffer<String> = RingBuffer::new(b"e", 100);
+    /// ```
+    pub fn new<S>(prefix: S, capacity: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if capacity == 0 {
+            env::panic_str(ERR_ZERO_CAPACITY);
+        }
+        Self { capacity, head: 0, len: 0, values: IndexMap::new(prefix) }
+    }
+
+    /// Returns the maximum number of elements the buffer keeps.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Returns the number of elements in the buffer.
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if the buffer contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the buffer holds as many elements as its capacity, so that the next push
+    /// overwrites the oldest element.
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    /// Storage index of the element at `index` from the oldest.
+    fn storage_index(&self, index: u32) -> u32 {
+        // Widened so that the sum can't overflow for capacities close to `u32::MAX`.
+        ((u64::from(self.head) + u64::from(index)) % u64::from(self.capacity)) as u32
+    }
+
+    /// Removes all elements from the buffer. This will remove all storage values for the
+    /// length of the [`RingBuffer`].
+    pub fn clear(&mut self) {
+        for i in 0..self.len {
+            self.values.set(self.storage_index(i), None);
+        }
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Flushes the cache and writes all modified values to storage.
+    ///
+    /// This operation is performed on [`Drop`], but this method can be called to persist
+    /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
+    pub fn flush(&mut self) {
+        self.values.flush();
+    }
+}
+
+impl<T> RingBuffer<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    /// Appends an element to the buffer, and returns the oldest element if the buffer was full
+    /// and the element overwrote it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::new(b"b", 2);
+    /// assert_eq!(buffer.push(1), None);
+    /// assert_eq!(buffer.push(2), None);
+    /// assert_eq!(buffer.push(3), Some(1));
+    /// assert_eq!(buffer.oldest(), Some(&2));
+    /// ```
+    pub fn push(&mut self, element: T) -> Option<T> {
+        if self.is_full() {
+            let prev = self.values.insert(self.head, element);
+            self.head = self.storage_index(1);
+            Some(expect_consistent_state(prev))
+        } else {
+            self.values.set(self.storage_index(self.len), Some(element));
+            self.len += 1;
+            None
+        }
+    }
+
+    /// Returns the element at `index` from the oldest, or `None` if it is out of bounds.
+    pub fn get(&self, index: u32) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get(self.storage_index(index))
+    }
+
+    /// Returns a mutable reference to the element at `index` from the oldest, or `None` if it is
+    /// out of bounds.
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        self.values.get_mut(self.storage_index(index))
+    }
+
+    /// Returns the oldest element, or `None` if the buffer is empty.
+    pub fn oldest(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns the most recently pushed element, or `None` if the buffer is empty.
+    pub fn latest(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Returns an iterator over the buffer, from the oldest element to the most recent one. This
+    /// iterator will lazily load any values iterated over from storage.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { buffer: self, range: 0..self.len }
+    }
+
+    /// Returns an iterator over the `n` most recent elements of the buffer, or all of them if
+    /// there are fewer, from the most recent element to the oldest of them. Only those elements
+    /// are loaded from storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::RingBuffer;
+    ///
+    /// let mut buffer = RingBuffer::new(b"b", 10);
+    /// buffer.extend(1..=5);
+    ///
+    /// assert!(Iterator::eq(buffer.iter_recent(3), [5, 4, 3].iter()));
+    /// assert_eq!(buffer.iter_recent(20).count(), 5);
+    /// ```
+    pub fn iter_recent(&self, n: u32) -> Rev<Iter<'_, T>> {
+        Iter { buffer: self, range: self.len.saturating_sub(n)..self.len }.rev()
+    }
+}
+
+impl<T> fmt::Debug for RingBuffer<T>
+where
+    T: BorshSerialize + BorshDeserialize + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "expensive-debug") {
+            fmt::Debug::fmt(&self.iter().collect::<Vec<_>>(), f)
+        } else {
+            f.debug_struct("RingBuffer")
+                .field("capacity", &self.capacity)
+                .field("head", &self.head)
+                .field("len", &self.len)
+                .field("prefix", &self.values.prefix)
+                .finish()
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+/// An iterator over references to the elements of a [`RingBuffer`], from the oldest element to
+/// the most recent one.
+#[derive(Debug)]
+pub struct Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    buffer: &'a RingBuffer<T>,
+    /// Range of indices from the oldest element to iterate.
+    range: Range<u32>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.range.len();
+        (remaining, Some(remaining))
+    }
+
+    fn count(self) -> usize {
+        self.range.len()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let idx = self.range.nth(n)?;
+        Some(self.buffer.get(idx).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize {}
+impl<'a, T> FusedIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize {}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let idx = self.range.nth_back(n)?;
+        Some(self.buffer.get(idx).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS)))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::VecDeque;
+
+    use super::RingBuffer;
+    use crate::test_utils::test_env::setup_free;
+
+    #[test]
+    fn storage_is_bounded() {
+        let mut buffer = RingBuffer::new(b"b", 4);
+        buffer.extend(0..100u32);
+        buffer.flush();
+        assert_eq!(crate::mock::with_mocked_blockchain(|m| m.take_storage()).len(), 4);
+
+        assert_eq!((buffer.oldest(), buffer.latest()), (Some(&96), Some(&99)));
+        buffer.clear();
+        assert!(buffer.is_empty() && buffer.iter().next().is_none());
+        buffer.flush();
+        assert!(crate::mock::with_mocked_blockchain(|m| m.take_storage()).is_empty());
+    }
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Push(u8),
+        Set(u8, u8),
+        Recent(u8),
+        Clear,
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for capacity in (1..=16).cycle().take(256) {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut sb = RingBuffer::new(b"b", capacity);
+            let mut mb = VecDeque::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Push(v) => {
+                            let evicted =
+                                if mb.len() == capacity as usize { mb.pop_front() } else { None };
+                            mb.push_back(v);
+                            assert_eq!(sb.push(v), evicted);
+                        }
+                        Op::Set(i, v) => {
+                            if let Some(value) = sb.get_mut(i.into()) {
+                                *value = v;
+                                mb[i as usize] = v;
+                            }
+                        }
+                        Op::Recent(n) => {
+                            assert!(Iterator::eq(
+                                sb.iter_recent(n.into()),
+                                mb.iter().rev().take(n.into())
+                            ));
+                        }
+                        Op::Clear => {
+                            sb.clear();
+                            mb.clear();
+                        }
+                        Op::Flush => sb.flush(),
+                        Op::Reset => {
+                            let serialized = sb.try_to_vec().unwrap();
+                            sb.flush();
+                            sb = RingBuffer::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(sb.len() as usize, mb.len());
+                    assert_eq!(sb.latest(), mb.back());
+                }
+            }
+            assert!(Iterator::eq(sb.iter(), mb.iter()));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::iter::FusedIterator;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::{ShardedMap, ToKey, UnorderedMap};
+use crate::store::unordered_map;
+
+impl<'a, K, V, H> IntoIterator for &'a ShardedMap<K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over elements of a [`ShardedMap`], one shard after the other.
+///
+/// This `struct` is created by the `iter` method on [`ShardedMap`].
+pub struct Iter<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Shards which have not been visited yet.
+    shards: std::slice::Iter<'a, UnorderedMap<K, V, H>>,
+    /// Iterator over the shard that is currently visited.
+    current: Option<unordered_map::Iter<'a, K, V, H>>,
+    /// Iterator over the shard that is currently visited from the back.
+    back: Option<unordered_map::Iter<'a, K, V, H>>,
+    /// Amount of elements which have not been visited yet.
+    remaining: usize,
+}
+
+impl<'a, K, V, H> Iter<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    pub(super) fn new(map: &'a ShardedMap<K, V, H>) -> Self {
+        Self { shards: map.shards.iter(), current: None, back: None, remaining: map.len() as usize }
+    }
+}
+
+impl<'a, K, V, H> Iterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            if let Some(current) = &mut self.current {
+                // Skip whole shards without loading any of their values.
+                let len = current.len();
+                if n < len {
+                    self.remaining -= n + 1;
+                    return current.nth(n);
+                }
+                n -= len;
+                self.remaining -= len;
+            }
+            self.current = match self.shards.next() {
+                Some(shard) => Some(shard.iter()),
+                // The last shard may already be visited from the back.
+                None => Some(self.back.take()?),
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+
+    fn count(self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, K, V, H> DoubleEndedIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            if let Some(back) = &mut self.back {
+                let len = back.len();
+                if n < len {
+                    self.remaining -= n + 1;
+                    return back.nth_back(n);
+                }
+                n -= len;
+                self.remaining -= len;
+            }
+            self.back = match self.shards.next_back() {
+                Some(shard) => Some(shard.iter()),
+                None => Some(self.current.take()?),
+            };
+        }
+    }
+}
+
+impl<'a, K, V, H> ExactSizeIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+}
+impl<'a, K, V, H> FusedIterator for Iter<'a, K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + Clone,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+}
+
+/// An iterator over the keys of a [`ShardedMap`], one shard after the other.
+///
+/// This `struct` is created by the `keys` method on [`ShardedMap`].
+pub struct Keys<'a, K: 'a>
+where
+    K: BorshSerialize + BorshDeserialize,
+{
+    shards: std::vec::IntoIter<unordered_map::Keys<'a, K>>,
+    current: Option<unordered_map::Keys<'a, K>>,
+    back: Option<unordered_map::Keys<'a, K>>,
+    remaining: usize,
+}
+
+impl<'a, K> Keys<'a, K>
+where
+    K: BorshSerialize + BorshDeserialize,
+{
+    pub(super) fn new<V, H>(map: &'a ShardedMap<K, V, H>) -> Self
+    where
+        K: Ord,
+        V: BorshSerialize,
+        H: ToKey,
+    {
+        let shards: Vec<_> = map.shards.iter().map(UnorderedMap::keys).collect();
+        Self {
+            shards: shards.into_iter(),
+            current: None,
+            back: None,
+            remaining: map.len() as usize,
+        }
+    }
+}
+
+impl<'a, K> Iterator for Keys<'a, K>
+where
+    K: BorshSerialize + BorshDeserialize,
+{
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        <Self as Iterator>::nth(self, 0)
+    }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            if let Some(current) = &mut self.current {
+                let len = current.len();
+                if n < len {
+                    self.remaining -= n + 1;
+                    return current.nth(n);
+                }
+                n -= len;
+                self.remaining -= len;
+            }
+            self.current = match self.shards.next() {
+                Some(shard) => Some(shard),
+                None => Some(self.back.take()?),
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+
+    fn count(self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, K> DoubleEndedIterator for Keys<'a, K>
+where
+    K: BorshSerialize + Ord + BorshDeserialize,
+{
+    fn next_back(&mut self) -> Option<&'a K> {
+        <Self as DoubleEndedIterator>::nth_back(self, 0)
+    }
+
+    fn nth_back(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            if let Some(back) = &mut self.back {
+                let len = back.len();
+                if n < len {
+                    self.remaining -= n + 1;
+                    return back.nth_back(n);
+                }
+                n -= len;
+                self.remaining -= len;
+            }
+            self.back = match self.shards.next_back() {
+                Some(shard) => Some(shard),
+                None => Some(self.current.take()?),
+            };
+        }
+    }
+}
+
+impl<'a, K> ExactSizeIterator for Keys<'a, K> where K: BorshSerialize + BorshDeserialize {}
+impl<'a, K> FusedIterator for Keys<'a, K> where K: BorshSerialize + BorshDeserialize {}
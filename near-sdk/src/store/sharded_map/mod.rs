@@ -0,0 +1,445 @@
+mod iter;
+
+use std::borrow::Borrow;
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::store::key::{Sha256, ToKey};
+use crate::{env, IntoStorageKey};
+
+pub use self::iter::{Iter, Keys};
+use super::UnorderedMap;
+
+/// An iterable map split across a fixed number of [`UnorderedMap`] shards.
+///
+/// Each key is assigned to a shard by the [`Sha256`] hash of its [`BorshSerialize`]
+/// representation, so entries are spread uniformly regardless of the key distribution. Every
+/// shard stores its entries under its own prefix, which keeps the metadata of each shard small
+/// and allows indexers and migrations to process the map one shard at a time.
+///
+/// The number of shards is fixed when the map is created, since changing it would move most keys
+/// to a different shard.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::ShardedMap;
+///
+/// let mut map: ShardedMap<String, u8> = ShardedMap::new(b"s", 4);
+///
+/// map.insert("test".to_string(), 7u8);
+/// assert_eq!(map.get("test"), Some(&7u8));
+/// assert_eq!(map.len(), 1);
+///
+/// // Entries of one shard can be paginated independently of the others.
+/// let shard = map.shard_of("test");
+/// let page: Vec<_> = map.shard(shard).iter().skip(0).take(10).collect();
+/// assert_eq!(page, [(&"test".to_string(), &7u8)]);
+/// ```
+pub struct ShardedMap<K, V, H = Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    shards: Vec<UnorderedMap<K, V, H>>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<K, V, H> BorshSerialize for ShardedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.shards, writer)
+    }
+}
+
+impl<K, V, H> BorshDeserialize for ShardedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self { shards: BorshDeserialize::deserialize(buf)? })
+    }
+}
+
+impl<K, V, H> fmt::Debug for ShardedMap<K, V, H>
+where
+    K: BorshSerialize + Ord + BorshDeserialize + fmt::Debug,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedMap").field("shards", &self.shards).finish()
+    }
+}
+
+impl<K, V> ShardedMap<K, V, Sha256>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+{
+    /// Create a new map with `shard_count` shards. Use `prefix` as a unique prefix for keys.
+    ///
+    /// Panics if `shard_count` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::ShardedMap;
+    ///
+    /// let mut map: ShardedMap<String, u8> = ShardedMap::new(b"s", 16);
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S, shard_count: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix, shard_count)
+    }
+}
+
+impl<K, V, H> ShardedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    /// Initialize a [`ShardedMap`] whose shards use a custom hash function. Keys are always
+    /// assigned to shards with [`Sha256`].
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{ShardedMap, key::Keccak256};
+    ///
+    /// let map = ShardedMap::<String, String, Keccak256>::with_hasher(b"s", 8);
+    /// ```
+    pub fn with_hasher<S>(prefix: S, shard_count: u32) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        if shard_count == 0 {
+            env::panic_str("ShardedMap requires at least one shard");
+        }
+        let prefix = prefix.into_storage_key();
+        let shards = (0..shard_count)
+            .map(|index| UnorderedMap::with_hasher([&prefix[..], &index.to_le_bytes()].concat()))
+            .collect();
+        Self { shards }
+    }
+
+    /// Return the number of shards of the map.
+    pub fn shard_count(&self) -> u32 {
+        self.shards.len() as u32
+    }
+
+    /// Return the index of the shard that `k` is stored in.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::ShardedMap;
+    ///
+    /// let mut map: ShardedMap<String, u8> = ShardedMap::new(b"s", 4);
+    /// map.insert("a".to_string(), 1);
+    /// assert!(map.shard(map.shard_of("a")).contains_key("a"));
+    /// ```
+    pub fn shard_of<Q: ?Sized>(&self, k: &Q) -> u32
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize,
+    {
+        let hash = Sha256::to_key(&[], k, &mut Vec::new());
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&hash[..4]);
+        u32::from_le_bytes(bytes) % self.shard_count()
+    }
+
+    /// Return the shard with the given index, which can be used to iterate over or paginate the
+    /// entries of a single shard.
+    ///
+    /// Panics if `index` is not less than [`shard_count`](Self::shard_count).
+    pub fn shard(&self, index: u32) -> &UnorderedMap<K, V, H> {
+        self.shards.get(index as usize).unwrap_or_else(|| env::panic_str("Shard does not exist"))
+    }
+
+    /// Return a mutable reference to the shard with the given index, for example to migrate the
+    /// entries of one shard at a time.
+    ///
+    /// Inserting a key into a shard other than the one returned by [`shard_of`](Self::shard_of)
+    /// makes it unreachable through the map.
+    ///
+    /// Panics if `index` is not less than [`shard_count`](Self::shard_count).
+    pub fn shard_mut(&mut self, index: u32) -> &mut UnorderedMap<K, V, H> {
+        self.shards
+            .get_mut(index as usize)
+            .unwrap_or_else(|| env::panic_str("Shard does not exist"))
+    }
+
+    /// Return the amount of elements inside of the map across all shards.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::ShardedMap;
+    ///
+    /// let mut map: ShardedMap<String, u8> = ShardedMap::new(b"s", 4);
+    /// map.insert("a".to_string(), 1);
+    /// map.insert("b".to_string(), 2);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> u32 {
+        self.shards.iter().map(UnorderedMap::len).sum()
+    }
+
+    /// Returns true if there are no elements inside of the map.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(UnorderedMap::is_empty)
+    }
+
+    /// Clears all shards, removing all key-value pairs.
+    pub fn clear(&mut self)
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        for shard in &mut self.shards {
+            shard.clear();
+        }
+    }
+
+    /// An iterator visiting all key-value pairs one shard after the other, in the order of the
+    /// shard indices.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::ShardedMap;
+    ///
+    /// let mut map: ShardedMap<String, u8> = ShardedMap::new(b"s", 4);
+    /// map.insert("a".to_string(), 1);
+    /// map.insert("b".to_string(), 2);
+    ///
+    /// let mut entries: Vec<_> = map.iter().collect();
+    /// entries.sort();
+    /// assert_eq!(entries, [(&"a".to_string(), &1), (&"b".to_string(), &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<K, V, H>
+    where
+        K: BorshDeserialize,
+    {
+        Iter::new(self)
+    }
+
+    /// An iterator visiting all keys one shard after the other, in the order of the shard indices.
+    pub fn keys(&self) -> Keys<K>
+    where
+        K: BorshDeserialize,
+    {
+        Keys::new(self)
+    }
+
+    /// Flushes the intermediate values of all shards before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values in
+    /// memory.
+    pub fn flush(&mut self) {
+        for shard in &mut self.shards {
+            shard.flush();
+        }
+    }
+
+    fn shard_for<Q: ?Sized>(&self, k: &Q) -> &UnorderedMap<K, V, H>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize,
+    {
+        &self.shards[self.shard_of(k) as usize]
+    }
+
+    fn shard_for_mut<Q: ?Sized>(&mut self, k: &Q) -> &mut UnorderedMap<K, V, H>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize,
+    {
+        let index = self.shard_of(k) as usize;
+        &mut self.shards[index]
+    }
+}
+
+impl<K, V, H> ShardedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but
+    /// [`BorshSerialize`] and [`ToOwned<Owned = K>`](ToOwned) on the borrowed form *must* match
+    /// those for the key type.
+    pub fn get<Q: ?Sized>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.shard_for(k).get(k)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q: ?Sized>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.shard_for_mut(k).get_mut(k)
+    }
+
+    /// Inserts a key-value pair into the shard of the key.
+    ///
+    /// If the map did not have this key present, [`None`] is returned. Otherwise the value is
+    /// updated and the old value is returned.
+    pub fn insert(&mut self, k: K, value: V) -> Option<V>
+    where
+        K: Clone + BorshDeserialize,
+    {
+        self.shard_for_mut(&k).insert(k, value)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key<Q: ?Sized>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = K> + Ord,
+    {
+        self.shard_for(k).contains_key(k)
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in
+    /// the map.
+    pub fn remove<Q: ?Sized>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: BorshSerialize + ToOwned<Owned = K>,
+    {
+        self.shard_for_mut(k).remove(k)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::ShardedMap;
+    use crate::store::UnorderedMap;
+    use crate::test_utils::test_env::setup_free;
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use std::collections::HashMap;
+
+    #[test]
+    fn basic_functionality() {
+        let mut map = ShardedMap::new(b"s", 4);
+        assert!(map.is_empty());
+        assert!(map.insert("test".to_string(), 5u8).is_none());
+        assert_eq!(map.get("test"), Some(&5));
+        assert!(map.contains_key("test"));
+        assert_eq!(map.len(), 1);
+
+        *map.get_mut("test").unwrap() = 6;
+        assert_eq!(map.insert("test".to_string(), 7), Some(6));
+
+        assert_eq!(map.remove("test"), Some(7));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn entries_spread_across_shards() {
+        let mut map = ShardedMap::new(b"s", 8);
+        let mut expected = HashMap::new();
+        for i in 0..200u32 {
+            map.insert(i, i * 2);
+            expected.insert(i, i * 2);
+        }
+        assert_eq!(map.len(), 200);
+
+        for index in 0..map.shard_count() {
+            let shard = map.shard(index);
+            // Uniform enough that no shard is empty or holds most entries.
+            assert!(shard.len() > 5 && shard.len() < 50);
+            for (key, _) in shard.iter() {
+                assert_eq!(map.shard_of(key), index);
+            }
+        }
+
+        let collected: HashMap<u32, u32> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(collected, expected);
+        assert_eq!(map.keys().count(), 200);
+        assert_eq!(map.iter().size_hint(), (200, Some(200)));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().next(), None);
+    }
+
+    #[test]
+    fn shards_use_separate_prefixes() {
+        let mut map = ShardedMap::new(b"s", 2);
+        for i in 0..20u8 {
+            map.insert(i, i);
+        }
+        map.flush();
+
+        let index = map.shard_of(&3);
+        let shard: UnorderedMap<u8, u8> =
+            UnorderedMap::new([&b"s"[..], &index.to_le_bytes()].concat());
+        assert_eq!(shard.get(&3), Some(&3));
+    }
+
+    #[test]
+    fn persists_through_serialization() {
+        setup_free();
+        let mut map = ShardedMap::new(b"s", 3);
+        map.insert(1u64, "one".to_string());
+        map.insert(2u64, "two".to_string());
+        map.flush();
+
+        let bytes = map.try_to_vec().unwrap();
+        let map = ShardedMap::<u64, String>::try_from_slice(&bytes).unwrap();
+        assert_eq!(map.shard_count(), 3);
+        assert_eq!(map.get(&2), Some(&"two".to_string()));
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn iterate_from_both_ends() {
+        let mut map = ShardedMap::new(b"s", 4);
+        for i in 0u32..30 {
+            map.insert(i, i);
+        }
+        let forward: Vec<_> = map.iter().map(|(k, _)| *k).collect();
+
+        let mut reversed: Vec<_> = map.iter().rev().map(|(k, _)| *k).collect();
+        reversed.reverse();
+        assert_eq!(reversed, forward);
+        assert!(map.keys().rev().eq(forward.iter().rev()));
+
+        // Alternating between both ends, skipping every other entry from the back.
+        let mut iter = map.iter();
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        while let Some((k, _)) = iter.next() {
+            front.push(*k);
+            assert_eq!(iter.len(), forward.len() - front.len() - 2 * back.len());
+            if let Some((k, _)) = iter.nth_back(1) {
+                back.push(*k);
+            }
+        }
+        assert_eq!(front, forward[..10]);
+        assert!(back.iter().eq(forward[10..].iter().rev().skip(1).step_by(2)));
+        assert!(iter.next_back().is_none());
+        assert_eq!(map.iter().nth_back(29).map(|(k, _)| *k), Some(forward[0]));
+    }
+}
@@ -0,0 +1,277 @@
+use std::ops::{Deref, DerefMut};
+
+/// State of a collection that is kept in the collection itself rather than in storage, like its
+/// length, saved by [`Transactional::snapshot`] to be restored by [`Transactional::rollback`].
+#[must_use]
+#[derive(Debug)]
+pub struct Snapshot<T>(pub(crate) T);
+
+/// Collection whose buffered changes can be discarded, which allows it to take part in a
+/// [`transaction`] or a [`StateTransaction`].
+///
+/// Changes are discarded from the cache of the collection, so changes that were already written
+/// to storage since the snapshot, by a `flush`, with
+/// [`WritePolicy::WriteThrough`](super::WritePolicy::WriteThrough) or to bound the cache with
+/// `set_cache_capacity`, are kept. Collections stored as
+/// values of other collections, like the sets of a [`MultiMap`](super::MultiMap), write their
+/// changes when they are discarded, so the collections that contain them are not transactional.
+///
+/// Structs of several collections, like the contract state, can implement this trait by
+/// combining the snapshots of their fields:
+///
+/// ```
+/// use near_sdk::store::{LookupMap, Transactional, Vector};
+///
+/// struct Contract {
+///     balances: LookupMap<String, u64>,
+///     history: Vector<String>,
+/// }
+///
+/// impl Transactional for Contract {
+///     type Snapshot = (
+///         <LookupMap<String, u64> as Transactional>::Snapshot,
+///         <Vector<String> as Transactional>::Snapshot,
+///     );
+///
+///     fn snapshot(&mut self) -> Self::Snapshot {
+///         (self.balances.snapshot(), self.history.snapshot())
+///     }
+///
+///     fn rollback(&mut self, (balances, history): Self::Snapshot) {
+///         self.balances.rollback(balances);
+///         self.history.rollback(history);
+///     }
+/// }
+/// ```
+pub trait Transactional {
+    /// State of the collection that is restored by [`rollback`](Self::rollback).
+    type Snapshot;
+
+    /// Writes all pending changes to storage, making the current state the point that
+    /// [`rollback`](Self::rollback) returns to with the returned snapshot.
+    fn snapshot(&mut self) -> Self::Snapshot;
+
+    /// Discards all changes made since `snapshot` was taken. Later snapshots of the collection
+    /// can no longer be rolled back to.
+    fn rollback(&mut self, snapshot: Self::Snapshot);
+}
+
+impl<T: Transactional + ?Sized> Transactional for &mut T {
+    type Snapshot = T::Snapshot;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        (**self).snapshot()
+    }
+
+    fn rollback(&mut self, snapshot: Self::Snapshot) {
+        (**self).rollback(snapshot)
+    }
+}
+
+macro_rules! impl_transactional_for_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Transactional),+> Transactional for ($($name,)+) {
+            type Snapshot = ($($name::Snapshot,)+);
+
+            fn snapshot(&mut self) -> Self::Snapshot {
+                ($(self.$index.snapshot(),)+)
+            }
+
+            fn rollback(&mut self, snapshot: Self::Snapshot) {
+                $(self.$index.rollback(snapshot.$index);)+
+            }
+        }
+    };
+}
+
+impl_transactional_for_tuple!(A 0);
+impl_transactional_for_tuple!(A 0, B 1);
+impl_transactional_for_tuple!(A 0, B 1, C 2);
+impl_transactional_for_tuple!(A 0, B 1, C 2, D 3);
+
+/// Runs `f` over the given collections and discards every change it made to them if it returns
+/// an error, so that updates spanning several collections are applied either completely or not
+/// at all.
+///
+/// Changes made before the transaction are written to storage when it begins, which means that
+/// transactions do not nest: rolling back an inner transaction keeps the changes of the outer one,
+/// and rolling back the outer one after that only discards the changes made after the inner
+/// transaction began.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::store::{transaction, LookupMap};
+///
+/// let mut balances: LookupMap<String, u64> = LookupMap::new(b"b");
+/// let mut history: LookupMap<u64, String> = LookupMap::new(b"h");
+/// balances.insert("alice".to_string(), 10);
+///
+/// let result = transaction(&mut (&mut balances, &mut history), |(balances, history)| {
+///     history.insert(0, "alice withdraws 20".to_string());
+///     let balance = balances.get_mut("alice").unwrap();
+///     *balance = balance.checked_sub(20).ok_or("not enough balance")?;
+///     Ok(())
+/// });
+///
+/// assert_eq!(result, Err("not enough balance"));
+/// assert_eq!(balances["alice"], 10);
+/// assert_eq!(history.get(&0), None);
+/// ```
+pub fn transaction<T, F, R, E>(collections: &mut T, f: F) -> Result<R, E>
+where
+    T: Transactional + ?Sized,
+    F: FnOnce(&mut T) -> Result<R, E>,
+{
+    let snapshot = collections.snapshot();
+    let result = f(collections);
+    if result.is_err() {
+        collections.rollback(snapshot);
+    }
+    result
+}
+
+/// Guard which discards every change made to the state it wraps, unless it is
+/// [`commit`](Self::commit)ted.
+///
+/// The state is usually the whole contract, implementing [`Transactional`] for its fields, so that
+/// a method can bail out without a panic, like to return an error or log a failure, while keeping
+/// none of the changes made before it decided to.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::store::{StateTransaction, Vector};
+///
+/// let mut queue: Vector<u32> = Vector::new(b"q");
+/// queue.extend([1, 2, 3]);
+///
+/// let mut tx = StateTransaction::new(&mut queue);
+/// tx.pop();
+/// tx.push(4);
+/// // Dropping the transaction without committing it rolls back the changes.
+/// drop(tx);
+/// assert_eq!(queue.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+///
+/// let mut tx = StateTransaction::new(&mut queue);
+/// tx.push(4);
+/// tx.commit();
+/// assert_eq!(queue.len(), 4);
+/// ```
+#[must_use]
+pub struct StateTransaction<'a, T>
+where
+    T: Transactional + ?Sized,
+{
+    state: &'a mut T,
+    snapshot: Option<T::Snapshot>,
+}
+
+impl<'a, T> StateTransaction<'a, T>
+where
+    T: Transactional + ?Sized,
+{
+    /// Takes a snapshot of `state` to roll back to if the transaction is not committed.
+    pub fn new(state: &'a mut T) -> Self {
+        let snapshot = state.snapshot();
+        Self { state, snapshot: Some(snapshot) }
+    }
+
+    /// Keeps the changes made during the transaction.
+    pub fn commit(mut self) {
+        self.snapshot = None;
+    }
+
+    /// Discards the changes made during the transaction. This is also done when the transaction
+    /// is dropped without being committed.
+    pub fn rollback(self) {}
+}
+
+impl<'a, T> Deref for StateTransaction<'a, T>
+where
+    T: Transactional + ?Sized,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.state
+    }
+}
+
+impl<'a, T> DerefMut for StateTransaction<'a, T>
+where
+    T: Transactional + ?Sized,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.state
+    }
+}
+
+impl<'a, T> Drop for StateTransaction<'a, T>
+where
+    T: Transactional + ?Sized,
+{
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            self.state.rollback(snapshot);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::{StateTransaction, Transactional};
+    use crate::store::{LazyOption, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector};
+
+    #[test]
+    fn state_transaction_rollback() {
+        let mut vec = Vector::new(b"v");
+        let mut map = UnorderedMap::new(b"m");
+        let mut set = UnorderedSet::new(b"s");
+        let mut tree = TreeMap::new(b"t");
+        let mut lookup = LookupSet::new(b"l");
+        let mut lazy = LazyOption::new(b"o", Some(1u8));
+        vec.extend([1u8, 2, 3]);
+        map.extend([(1u8, 1u8), (2, 2)]);
+        set.extend([1u8, 2]);
+        tree.extend([(1u8, 1u8), (2, 2)]);
+        lookup.insert(1u8);
+
+        let mut state = (&mut vec, &mut map, &mut set, &mut tree);
+        let mut tx = StateTransaction::new(&mut state);
+        tx.0.pop();
+        tx.0.push(4);
+        tx.0.push(5);
+        tx.1.remove(&1);
+        tx.1.insert(3, 3);
+        tx.2.remove(&2);
+        tx.2.insert(3);
+        tx.3.remove(&1);
+        tx.3.insert(3, 3);
+        tx.3.insert(4, 4);
+        drop(tx);
+
+        let snapshot = (&mut lookup, &mut lazy).snapshot();
+        lookup.remove(&1);
+        lookup.insert(2);
+        lazy.set(None);
+        (&mut lookup, &mut lazy).rollback(snapshot);
+
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(), [(1, 1), (2, 2)]);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(tree.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(), [(1, 1), (2, 2)]);
+        assert!(lookup.contains(&1) && !lookup.contains(&2));
+        assert_eq!(lazy.get(), &Some(1));
+
+        // Committed changes are kept, and later rollbacks return to the new state.
+        let mut tx = StateTransaction::new(&mut vec);
+        tx.push(4);
+        tx.commit();
+        let mut tx = StateTransaction::new(&mut vec);
+        tx.clear();
+        tx.rollback();
+        assert_eq!(vec.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+}
@@ -125,7 +125,7 @@ where
     V: BorshSerialize + BorshDeserialize,
     H: ToKey,
 {
-    let entry = expect(map.get_mut(key));
+    let entry = expect(map.get_mut_without_eviction(key).value_mut().as_mut());
     //* SAFETY: The lifetime can be swapped here because we can assert that the iterator
     //*         will only give out one mutable reference for every individual key in the bucket
     //*         during the iteration, and there is no overlap. This operates under the
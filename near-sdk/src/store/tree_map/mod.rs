@@ -5,14 +5,16 @@ mod iter;
 use super::lookup_map as lm;
 use crate::store::free_list::{FreeList, FreeListIndex};
 use crate::store::key::{Sha256, ToKey};
-use crate::store::LookupMap;
+use crate::store::{ClearProgress, LookupMap, Snapshot, Transactional};
 use crate::{env, IntoStorageKey};
 use borsh::{BorshDeserialize, BorshSerialize};
 pub use entry::Entry;
+pub(crate) use iter::KeysRange;
 pub use iter::{Iter, IterMut, Keys, Range, RangeMut, Values, ValuesMut};
 use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt;
-use std::ops::RangeBounds;
+use std::ops::{Bound, RangeBounds};
 
 type NodeAndIndex<'a, K> = (FreeListIndex, &'a Node<K>);
 
@@ -28,6 +30,7 @@ fn expect<T>(val: Option<T>) -> T {
 /// - `min`/`max`:              O(log(N))
 /// - `above`/`below`:          O(log(N))
 /// - `range` of K elements:    O(Klog(N))
+/// - `rank`/`select`:          O(log(N))
 pub struct TreeMap<K, V, H = Sha256>
 where
     K: BorshSerialize + Ord,
@@ -122,6 +125,7 @@ struct Node<K> {
     lft: Option<FreeListIndex>, // left link of a node
     rgt: Option<FreeListIndex>, // right link of a node
     ht: u32,                    // height of a subtree at a node
+    sz: u32,                    // number of nodes in a subtree at a node
 }
 
 impl<K> Node<K>
@@ -129,7 +133,7 @@ where
     K: BorshSerialize + BorshDeserialize,
 {
     fn of(key: K) -> Self {
-        Self { key, lft: None, rgt: None, ht: 1 }
+        Self { key, lft: None, rgt: None, ht: 1, sz: 1 }
     }
 
     fn left<'a>(&self, list: &'a FreeList<Node<K>>) -> Option<(FreeListIndex, &'a Node<K>)> {
@@ -204,6 +208,42 @@ where
         }
     }
 
+    /// Removes up to `max_entries` key-value pairs, smallest keys first, to clear the map over
+    /// multiple calls when removing all pairs at once would exceed the gas limit.
+    ///
+    /// Each pair is removed as with [`remove`](Self::remove), so the map stays balanced and can
+    /// be used as usual in-between the calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{ClearProgress, TreeMap};
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.extend([(1, "a".to_string()), (2, "b".to_string()), (3, "c".to_string())]);
+    ///
+    /// assert_eq!(map.clear_chunk(2), ClearProgress::Remaining(1));
+    /// assert_eq!(map.first_key_value(), Some((&3, &"c".to_string())));
+    /// assert_eq!(map.clear_chunk(2), ClearProgress::Done);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear_chunk(&mut self, max_entries: u32) -> ClearProgress
+    where
+        K: BorshDeserialize,
+    {
+        for _ in 0..max_entries {
+            let key = match self.tree.min() {
+                Some(key) => key.clone(),
+                None => break,
+            };
+            let (new_root, _) = self.tree.do_remove(&key);
+            self.tree.root = new_root;
+            // Set instead of remove to avoid loading the value from storage.
+            self.values.set(key, None);
+        }
+        ClearProgress::remaining(self.len())
+    }
+
     /// Returns `true` if the map contains a value for the specified key.
     ///
     /// The key may be any borrowed form of the map's key type, but
@@ -253,6 +293,106 @@ where
         self.values.get(k).map(|v| (expect(self.tree.equal_key(k)), v))
     }
 
+    /// Returns the key-value pair with the smallest key in the map, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// assert_eq!(map.first_key_value(), None);
+    /// map.insert(2, "b".to_string());
+    /// map.insert(1, "a".to_string());
+    /// assert_eq!(map.first_key_value(), Some((&1, &"a".to_string())));
+    /// ```
+    pub fn first_key_value(&self) -> Option<(&K, &V)>
+    where
+        K: BorshDeserialize,
+    {
+        let key = self.tree.min()?;
+        Some((key, expect(self.values.get(key))))
+    }
+
+    /// Returns the key-value pair with the largest key in the map, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// assert_eq!(map.last_key_value(), None);
+    /// map.insert(2, "b".to_string());
+    /// map.insert(1, "a".to_string());
+    /// assert_eq!(map.last_key_value(), Some((&2, &"b".to_string())));
+    /// ```
+    pub fn last_key_value(&self) -> Option<(&K, &V)>
+    where
+        K: BorshDeserialize,
+    {
+        let key = self.tree.max()?;
+        Some((key, expect(self.values.get(key))))
+    }
+
+    /// Returns the number of keys in the map that are strictly less than `key`, which is the
+    /// position of `key` in ascending order if it is in the map.
+    ///
+    /// The supplied key may be any borrowed form of the map's key type, but the ordering
+    /// on the borrowed form *must* match the ordering on the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.extend([(10, "a"), (20, "b"), (30, "c")].map(|(k, v)| (k, v.to_string())));
+    /// assert_eq!(map.rank(&10), 0);
+    /// assert_eq!(map.rank(&30), 2);
+    /// assert_eq!(map.rank(&25), 2);
+    /// assert_eq!(map.rank(&40), 3);
+    /// ```
+    pub fn rank<Q: ?Sized>(&self, key: &Q) -> u32
+    where
+        K: Borrow<Q> + BorshDeserialize,
+        Q: Ord,
+    {
+        self.tree.rank(key)
+    }
+
+    /// Returns the key-value pair at position `index` in ascending order of keys, or `None` if
+    /// `index` is not less than the length of the map.
+    ///
+    /// Together with [`range`](Self::range), this allows paginating the map by position without
+    /// iterating over the entries before the page.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    /// use std::ops::Bound;
+    ///
+    /// let mut scores = TreeMap::new(b"t");
+    /// scores.extend((0..200u32).map(|score| (score, format!("player-{}", score))));
+    ///
+    /// // Median
+    /// assert_eq!(scores.select(scores.len() / 2), Some((&100, &"player-100".to_string())));
+    ///
+    /// // Entries 100..120
+    /// let (first, _) = scores.select(100).unwrap();
+    /// let page: Vec<_> =
+    ///     scores.range((Bound::Included(first), Bound::Unbounded)).take(20).map(|(k, _)| *k).collect();
+    /// assert_eq!(page, (100..120).collect::<Vec<_>>());
+    /// ```
+    pub fn select(&self, index: u32) -> Option<(&K, &V)>
+    where
+        K: BorshDeserialize,
+    {
+        let key = self.tree.select(index)?;
+        Some((key, expect(self.values.get(key))))
+    }
+
     /// Returns a mutable reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type, but
@@ -380,6 +520,47 @@ where
         }
     }
 
+    /// Number of nodes in the subtree at `at`.
+    fn size(&self, at: Option<FreeListIndex>) -> u32 {
+        at.and_then(|id| self.node(id)).map(|n| n.sz).unwrap_or_default()
+    }
+
+    /// Returns the number of keys that are strictly less than `key`
+    fn rank<Q>(&self, key: &Q) -> u32
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        let mut rank = 0;
+        let mut at = self.root;
+        while let Some(node) = at.and_then(|id| self.node(id)) {
+            if key.le(node.key.borrow()) {
+                at = node.lft;
+            } else {
+                rank += self.size(node.lft) + 1;
+                at = node.rgt;
+            }
+        }
+        rank
+    }
+
+    /// Returns the key at position `index` in ascending order
+    fn select(&self, mut index: u32) -> Option<&K> {
+        let mut at = self.root;
+        while let Some(node) = at.and_then(|id| self.node(id)) {
+            let lft = self.size(node.lft);
+            match index.cmp(&lft) {
+                Ordering::Less => at = node.lft,
+                Ordering::Equal => return Some(&node.key),
+                Ordering::Greater => {
+                    index -= lft + 1;
+                    at = node.rgt;
+                }
+            }
+        }
+        None
+    }
+
     /// Returns (node, parent node) of left-most lower (min) node starting from given node `at`.
     fn min_at(&self, mut at: FreeListIndex) -> Option<(NodeAndIndex<K>, Option<NodeAndIndex<K>>)> {
         let mut parent: Option<NodeAndIndex<K>> = None;
@@ -566,13 +747,15 @@ where
         }
     }
 
-    // Calculate and save the height of a subtree at node `at`:
+    // Calculate and save the height and size of a subtree at node `at`:
     // height[at] = 1 + max(height[at.L], height[at.R])
+    // size[at] = 1 + size[at.L] + size[at.R]
     fn update_height(&mut self, node: &mut Node<K>, id: FreeListIndex) {
         let lft = node.lft.and_then(|id| self.node(id).map(|n| n.ht)).unwrap_or_default();
         let rgt = node.rgt.and_then(|id| self.node(id).map(|n| n.ht)).unwrap_or_default();
 
         node.ht = 1 + std::cmp::max(lft, rgt);
+        node.sz = 1 + self.size(node.lft) + self.size(node.rgt);
         // This side effect isn't great, but a lot of logic depends on values in storage/cache to be
         // up to date. Until changes and the tree are kept all in a single data structure, this
         // will be necessary.
@@ -900,6 +1083,34 @@ where
         Range::new(self, (range.start_bound(), range.end_bound()))
     }
 
+    /// Constructs a double-ended iterator over the elements of the map starting from the given
+    /// key, inclusive, in ascending order. Equivalent to `range(key..)` for keys that can be
+    /// used in a range, like borrowed forms of the key type.
+    ///
+    /// Iterating the result in reverse yields the elements from the largest key down to `key`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut map = TreeMap::new(b"t");
+    /// map.insert(3, "a".to_string());
+    /// map.insert(5, "b".to_string());
+    /// map.insert(8, "c".to_string());
+    /// let keys: Vec<_> = map.iter_from(&4).map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [5, 8]);
+    /// let keys: Vec<_> = map.iter_from(&5).rev().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [8, 5]);
+    /// ```
+    pub fn iter_from<'a, Q>(&'a self, key: &'a Q) -> Range<'a, K, V, H>
+    where
+        K: BorshDeserialize + Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        Range::new(self, (Bound::Included(key), Bound::Unbounded))
+    }
+
     /// Constructs a mutable double-ended iterator over a sub-range of elements in the map.
     /// The simplest way is to use the range syntax `min..max`, thus `range(min..max)` will
     /// yield elements from min (inclusive) to max (exclusive).
@@ -938,6 +1149,17 @@ where
     {
         RangeMut::new(self, (range.start_bound(), range.end_bound()))
     }
+
+    /// Constructs a double-ended iterator over the keys in a sub-range of the map, without
+    /// loading their values.
+    pub(crate) fn keys_range<R, Q>(&self, range: R) -> KeysRange<'_, K>
+    where
+        K: BorshDeserialize + Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        KeysRange::new(&self.tree, (range.start_bound(), range.end_bound()))
+    }
 }
 
 impl<K, V, H> TreeMap<K, V, H>
@@ -975,6 +1197,32 @@ where
         })
     }
 
+    /// Removes up to `limit` key-value pairs with the smallest keys and returns them in order, to
+    /// process the pairs of the map a chunk at a time over multiple calls, like a queue of work
+    /// items ordered by key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeMap;
+    ///
+    /// let mut due: TreeMap<u64, String> = TreeMap::new(b"d");
+    /// due.insert(30, "c".to_string());
+    /// due.insert(10, "a".to_string());
+    /// due.insert(20, "b".to_string());
+    ///
+    /// assert_eq!(due.drain_limit(2), [(10, "a".to_string()), (20, "b".to_string())]);
+    /// assert_eq!(due.len(), 1);
+    /// ```
+    pub fn drain_limit(&mut self, limit: u32) -> Vec<(K, V)>
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        let keys: Vec<K> = self.keys().take(limit as usize).cloned().collect();
+        keys.iter().map(|k| expect(self.remove_entry(k))).collect()
+    }
+
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
     /// ```
     /// use near_sdk::store::TreeMap;
@@ -1014,6 +1262,27 @@ where
     }
 }
 
+impl<K, V, H> Transactional for TreeMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    type Snapshot =
+        Snapshot<(Snapshot<()>, Option<FreeListIndex>, <FreeList<K> as Transactional>::Snapshot)>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        Snapshot((self.values.snapshot(), self.tree.root, self.tree.nodes.snapshot()))
+    }
+
+    fn rollback(&mut self, snapshot: Self::Snapshot) {
+        let (values, root, nodes) = snapshot.0;
+        self.values.rollback(values);
+        self.tree.root = root;
+        self.tree.nodes.rollback(nodes);
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -1237,6 +1506,31 @@ mod tests {
         map.clear();
     }
 
+    #[test]
+    fn test_first_last_iter_from() {
+        let mut map: TreeMap<u32, u32> = TreeMap::new(next_trie_id());
+        assert_eq!(map.first_key_value(), None);
+        assert_eq!(map.iter_from(&0).next(), None);
+
+        for x in [30, 10, 50, 20, 40] {
+            map.insert(x, x * 2);
+        }
+        map.flush();
+        assert_eq!(map.first_key_value(), Some((&10, &20)));
+        assert_eq!(map.last_key_value(), Some((&50, &100)));
+
+        map.remove(&10);
+        map.remove(&50);
+        assert_eq!(map.first_key_value(), Some((&20, &40)));
+        assert_eq!(map.last_key_value(), Some((&40, &80)));
+
+        let keys: Vec<u32> = map.iter_from(&25).map(|(k, _)| *k).collect();
+        assert_eq!(keys, [30, 40]);
+        let keys: Vec<u32> = map.iter_from(&20).rev().map(|(k, _)| *k).collect();
+        assert_eq!(keys, [40, 30, 20]);
+        assert_eq!(map.iter_from(&41).next(), None);
+    }
+
     #[test]
     fn test_lower() {
         let mut map: TreeMap<u32, u32> = TreeMap::new(next_trie_id());
@@ -2064,6 +2358,40 @@ mod tests {
         assert_eq!(map.keys().collect::<Vec<_>>(), [&0, &2, &3]);
     }
 
+    #[test]
+    fn test_rank_select_random() {
+        let mut map: TreeMap<u32, u32> = TreeMap::new(next_trie_id());
+        let mut set = BTreeMap::new();
+        for k in random(500) {
+            map.insert(k, k);
+            set.insert(k, k);
+        }
+        for k in random(250) {
+            assert_eq!(map.remove(&k), set.remove(&k));
+        }
+
+        for (i, (k, v)) in set.iter().enumerate() {
+            assert_eq!(map.rank(k), i as u32);
+            assert_eq!(map.select(i as u32), Some((k, v)));
+        }
+        assert_eq!(map.select(map.len()), None);
+        assert_eq!(map.rank(&u32::MAX), map.len());
+    }
+
+    #[test]
+    fn test_clear_chunk() {
+        let mut map: TreeMap<u32, u32> = TreeMap::new(next_trie_id());
+        map.extend((0..200).map(|k| (k, k)));
+
+        assert_eq!(map.clear_chunk(150), ClearProgress::Remaining(50));
+        assert!(height(&map) <= max_tree_height(50));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), (150..200).collect::<Vec<_>>());
+
+        assert_eq!(map.clear_chunk(150), ClearProgress::Done);
+        assert!(map.is_empty());
+        assert_eq!(map.get(&199), None);
+    }
+
     #[derive(Arbitrary, Debug)]
     enum Op {
         Insert(u8, u8),
@@ -2071,6 +2399,8 @@ mod tests {
         Flush,
         Restore,
         Get(u8),
+        Rank(u8),
+        Select(u8),
     }
 
     #[test]
@@ -2112,6 +2442,16 @@ mod tests {
                             let r2 = hm.get(&k);
                             assert_eq!(r1, r2)
                         }
+                        Op::Rank(k) => {
+                            let r1 = um.rank(&k);
+                            let r2 = hm.range(..k).count() as u32;
+                            assert_eq!(r1, r2)
+                        }
+                        Op::Select(i) => {
+                            let r1 = um.select(u32::from(i));
+                            let r2 = hm.iter().nth(usize::from(i));
+                            assert_eq!(r1, r2)
+                        }
                     }
                 }
             }
@@ -0,0 +1,503 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::store::key::{Sha256, ToKey};
+use crate::store::tree_map::{Keys, KeysRange};
+use crate::store::{ClearProgress, Transactional, TreeMap};
+use crate::IntoStorageKey;
+
+/// An ordered set of values based on an AVL-tree, the set counterpart of [`TreeMap`].
+///
+/// Values are kept in ascending order, so the set can be iterated over in order, from any value
+/// with [`iter_from`](Self::iter_from) or over a sub-range with [`range`](Self::range), and the
+/// smallest and largest values are found in `O(log(N))`. Checking whether a value is in the set
+/// is `O(1)`, like [`TreeMap::contains_key`], and iterating over the set only loads the nodes of
+/// the tree.
+///
+/// # Examples
+/// ```
+/// use near_sdk::store::TreeSet;
+///
+/// // Identifiers of items ordered by the timestamp when they expire.
+/// let mut expiring: TreeSet<(u64, u32)> = TreeSet::new(b"e");
+/// expiring.insert((300, 1));
+/// expiring.insert((100, 2));
+/// expiring.insert((200, 3));
+///
+/// assert_eq!(expiring.first(), Some(&(100, 2)));
+/// let expired: Vec<_> = expiring.range(..(250, 0)).collect();
+/// assert_eq!(expired, [&(100, 2), &(200, 3)]);
+///
+/// assert_eq!(expiring.pop_first(), Some((100, 2)));
+/// assert_eq!(expiring.len(), 2);
+/// ```
+pub struct TreeSet<T, H = Sha256>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    map: TreeMap<T, (), H>,
+}
+
+//? Manual implementations needed only because borsh derive is leaking field types
+// https://github.com/near/borsh-rs/issues/41
+impl<T, H> BorshSerialize for TreeSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), borsh::maybestd::io::Error> {
+        BorshSerialize::serialize(&self.map, writer)
+    }
+}
+
+impl<T, H> BorshDeserialize for TreeSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, borsh::maybestd::io::Error> {
+        Ok(Self { map: BorshDeserialize::deserialize(buf)? })
+    }
+}
+
+impl<T, H> fmt::Debug for TreeSet<T, H>
+where
+    T: Ord + Clone + fmt::Debug + BorshSerialize + BorshDeserialize,
+    H: ToKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TreeSet").field("map", &self.map).finish()
+    }
+}
+
+impl<T> TreeSet<T, Sha256>
+where
+    T: BorshSerialize + Ord,
+{
+    /// Create a new set. Use `prefix` as a unique prefix for keys.
+    ///
+    /// This prefix can be anything that implements [`IntoStorageKey`]. The prefix is used when
+    /// storing and looking up values in storage to ensure no collisions with other collections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeSet;
+    ///
+    /// let mut set: TreeSet<u64> = TreeSet::new(b"s");
+    /// ```
+    #[inline]
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self::with_hasher(prefix)
+    }
+}
+
+impl<T, H> TreeSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    /// Initialize a [`TreeSet`] with a custom hash function.
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::{TreeSet, key::Keccak256};
+    ///
+    /// let set = TreeSet::<String, Keccak256>::with_hasher(b"s");
+    /// ```
+    pub fn with_hasher<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self { map: TreeMap::with_hasher(prefix) }
+    }
+
+    /// Returns the number of elements in the set.
+    pub fn len(&self) -> u32 {
+        self.map.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// An iterator visiting all elements in ascending order.
+    /// The iterator element type is `&'a T`.
+    pub fn iter(&self) -> Iter<'_, T>
+    where
+        T: BorshDeserialize,
+    {
+        Iter { inner: self.map.keys() }
+    }
+
+    /// Constructs a double-ended iterator over a sub-range of elements in the set, in ascending
+    /// order. The range may be entered with the range syntax, like `min..max`, or as a
+    /// `(Bound<T>, Bound<T>)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if range `start > end`.
+    /// Panics if range `start == end` and both bounds are `Excluded`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeSet;
+    ///
+    /// let mut set = TreeSet::new(b"s");
+    /// set.extend([3u32, 5, 8, 13]);
+    ///
+    /// assert_eq!(set.range(4..=8).collect::<Vec<_>>(), [&5, &8]);
+    /// assert_eq!(set.range(..8).rev().collect::<Vec<_>>(), [&5, &3]);
+    /// ```
+    pub fn range<R, Q>(&self, range: R) -> Range<'_, T>
+    where
+        T: BorshDeserialize + Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        Range { inner: self.map.keys_range(range) }
+    }
+
+    /// Constructs a double-ended iterator over the elements of the set starting from the given
+    /// value, inclusive, in ascending order. Equivalent to `range(value..)` for values that can be
+    /// used in a range, like borrowed forms of the element type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeSet;
+    ///
+    /// let mut set = TreeSet::new(b"s");
+    /// set.extend(["alice".to_string(), "bob".to_string(), "carol".to_string()]);
+    ///
+    /// let from_b: Vec<_> = set.iter_from("b").map(String::as_str).collect();
+    /// assert_eq!(from_b, ["bob", "carol"]);
+    /// ```
+    pub fn iter_from<Q>(&self, value: &Q) -> Range<'_, T>
+    where
+        T: BorshDeserialize + Borrow<Q>,
+        Q: ?Sized + Ord,
+    {
+        Range { inner: self.map.keys_range((Bound::Included(value), Bound::Unbounded)) }
+    }
+
+    /// Returns the smallest element of the set, or `None` if it is empty.
+    pub fn first(&self) -> Option<&T>
+    where
+        T: BorshDeserialize,
+    {
+        self.map.keys().next()
+    }
+
+    /// Returns the largest element of the set, or `None` if it is empty.
+    pub fn last(&self) -> Option<&T>
+    where
+        T: BorshDeserialize,
+    {
+        self.map.keys().next_back()
+    }
+
+    /// Flushes the intermediate values of the set before this is called when the structure is
+    /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
+    /// in memory.
+    pub fn flush(&mut self) {
+        self.map.flush()
+    }
+}
+
+impl<T, H> TreeSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+    H: ToKey,
+{
+    /// Returns `true` if the set contains the specified value.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`], [`ToOwned<Owned = T>`](ToOwned) and [`Ord`] on the borrowed form *must*
+    /// match those for the value type.
+    pub fn contains<Q: ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = T> + Ord,
+    {
+        self.map.contains_key(value)
+    }
+
+    /// Adds a value to the set.
+    ///
+    /// If the set did not have this value present, `true` is returned.
+    ///
+    /// If the set did have this value present, `false` is returned.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    /// Removes a value from the set. Returns whether the value was present in the set.
+    ///
+    /// The value may be any borrowed form of the set's value type, but
+    /// [`BorshSerialize`], [`ToOwned<Owned = T>`](ToOwned) and [`Ord`] on the borrowed form *must*
+    /// match those for the value type.
+    pub fn remove<Q: ?Sized>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: BorshSerialize + ToOwned<Owned = T> + Ord,
+    {
+        self.map.remove(value).is_some()
+    }
+
+    /// Removes and returns the smallest element of the set, or `None` if it is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::TreeSet;
+    ///
+    /// let mut set = TreeSet::new(b"s");
+    /// set.extend([2u8, 1]);
+    ///
+    /// assert_eq!(set.pop_first(), Some(1));
+    /// assert_eq!(set.pop_first(), Some(2));
+    /// assert_eq!(set.pop_first(), None);
+    /// ```
+    pub fn pop_first(&mut self) -> Option<T> {
+        let first = self.first()?.clone();
+        self.map.remove(&first);
+        Some(first)
+    }
+
+    /// Removes and returns the largest element of the set, or `None` if it is empty.
+    pub fn pop_last(&mut self) -> Option<T> {
+        let last = self.last()?.clone();
+        self.map.remove(&last);
+        Some(last)
+    }
+
+    /// Clears the set, removing all values.
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// Removes up to `max_elements` values, smallest first, to clear the set over multiple calls
+    /// when removing all values at once would exceed the gas limit.
+    pub fn clear_chunk(&mut self, max_elements: u32) -> ClearProgress {
+        self.map.clear_chunk(max_elements)
+    }
+}
+
+impl<T, H> Extend<T> for TreeSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Clone,
+    H: ToKey,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T, H> IntoIterator for &'a TreeSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+    H: ToKey,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, H> Transactional for TreeSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    type Snapshot = <TreeMap<T, (), H> as Transactional>::Snapshot;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.map.snapshot()
+    }
+
+    fn rollback(&mut self, snapshot: Self::Snapshot) {
+        self.map.rollback(snapshot)
+    }
+}
+
+/// An iterator over the elements of a [`TreeSet`], in ascending order.
+///
+/// This `struct` is created by the `iter` method on [`TreeSet`].
+pub struct Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    inner: Keys<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    fn count(self) -> usize {
+        self.inner.count()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize + Ord {}
+impl<'a, T> FusedIterator for Iter<'a, T> where T: BorshSerialize + BorshDeserialize + Ord {}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+/// An iterator over a sub-range of the elements of a [`TreeSet`], in ascending order.
+///
+/// This `struct` is created by the `range` and `iter_from` methods on [`TreeSet`].
+pub struct Range<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    inner: KeysRange<'a, T>,
+}
+
+impl<'a, T> Iterator for Range<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T> FusedIterator for Range<'a, T> where T: BorshSerialize + BorshDeserialize + Ord {}
+
+impl<'a, T> DoubleEndedIterator for Range<'a, T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use rand::{RngCore, SeedableRng};
+    use std::collections::BTreeSet;
+
+    use super::TreeSet;
+    use crate::test_utils::test_env::setup_free;
+
+    #[derive(Arbitrary, Debug)]
+    enum Op {
+        Insert(u8),
+        Remove(u8),
+        PopFirst,
+        PopLast,
+        Contains(u8),
+        Range(u8, u8),
+        IterFrom(u8),
+        Flush,
+        Reset,
+    }
+
+    #[test]
+    fn arbitrary() {
+        setup_free();
+
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut buf = vec![0; 4096];
+        for _ in 0..512 {
+            // Clear storage in-between runs
+            crate::mock::with_mocked_blockchain(|b| b.take_storage());
+            rng.fill_bytes(&mut buf);
+
+            let mut ss = TreeSet::new(b"s");
+            let mut ms = BTreeSet::new();
+            let u = Unstructured::new(&buf);
+            if let Ok(ops) = Vec::<Op>::arbitrary_take_rest(u) {
+                for op in ops {
+                    match op {
+                        Op::Insert(v) => assert_eq!(ss.insert(v), ms.insert(v)),
+                        Op::Remove(v) => assert_eq!(ss.remove(&v), ms.remove(&v)),
+                        Op::PopFirst => {
+                            let first = ms.iter().next().cloned();
+                            if let Some(x) = &first {
+                                ms.remove(x);
+                            }
+                            assert_eq!(ss.pop_first(), first);
+                        }
+                        Op::PopLast => {
+                            let last = ms.iter().next_back().cloned();
+                            if let Some(x) = &last {
+                                ms.remove(x);
+                            }
+                            assert_eq!(ss.pop_last(), last);
+                        }
+                        Op::Contains(v) => assert_eq!(ss.contains(&v), ms.contains(&v)),
+                        Op::Range(a, b) => {
+                            let (start, end) = (a.min(b), a.max(b));
+                            assert!(Iterator::eq(ss.range(start..end), ms.range(start..end)));
+                            assert!(Iterator::eq(
+                                ss.range(start..=end).rev(),
+                                ms.range(start..=end).rev()
+                            ));
+                        }
+                        Op::IterFrom(v) => assert!(Iterator::eq(ss.iter_from(&v), ms.range(v..))),
+                        Op::Flush => ss.flush(),
+                        Op::Reset => {
+                            ss.flush();
+                            let serialized = ss.try_to_vec().unwrap();
+                            ss = TreeSet::deserialize(&mut serialized.as_slice()).unwrap();
+                        }
+                    }
+                    assert_eq!(ss.first(), ms.first());
+                    assert_eq!(ss.last(), ms.last());
+                }
+            }
+            assert!(Iterator::eq(ss.iter(), ms.iter()));
+            assert_eq!(ss.len() as usize, ms.len());
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::borrow::Borrow;
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Serialize, Serializer};
 
 use super::{ToKey, UnorderedMap, ERR_NOT_EXIST};
 use crate::env;
@@ -11,6 +12,9 @@ where
     V: BorshSerialize + BorshDeserialize,
     H: ToKey,
 {
+    /// Inserts all key-value pairs of the iterator, replacing the values of existing keys. Each
+    /// key is read once to check if it is already in the map, and the entries, key indices and
+    /// length are only written to storage when the map is flushed.
     fn extend<I>(&mut self, iter: I)
     where
         I: IntoIterator<Item = (K, V)>,
@@ -40,3 +44,41 @@ where
         self.get(index).unwrap_or_else(|| env::panic_str(ERR_NOT_EXIST))
     }
 }
+
+/// Serializes all entries as a sequence of `(key, value)` pairs, in the order of
+/// [`UnorderedMap::iter`], loading each of them from storage. Pairs are used rather than a map so
+/// that keys do not need to serialize to strings for JSON. For large maps, return a
+/// [`Page`](crate::store::Page) of the entries instead.
+impl<K, V, H> Serialize for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + BorshDeserialize + Ord + Clone + Serialize,
+    V: BorshSerialize + BorshDeserialize + Serialize,
+    H: ToKey,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "abi")]
+impl<K, V, H> schemars::JsonSchema for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord + schemars::JsonSchema,
+    V: BorshSerialize + schemars::JsonSchema,
+    H: ToKey,
+{
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        <Vec<(K, V)>>::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <Vec<(K, V)>>::json_schema(gen)
+    }
+}
@@ -149,8 +149,12 @@ where
         K: Clone,
         V: BorshDeserialize,
     {
-        let entry =
-            self.values.get_mut(key).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+        let entry = self
+            .values
+            .get_mut_without_eviction(key)
+            .value_mut()
+            .as_mut()
+            .unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
         //* SAFETY: The lifetime can be swapped here because we can assert that the iterator
         //*         will only give out one mutable reference for every individual key in the bucket
         //*         during the iteration, and there is no overlap. This operates under the
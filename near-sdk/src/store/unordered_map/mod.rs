@@ -14,7 +14,10 @@ pub use entry::{Entry, OccupiedEntry, VacantEntry};
 
 pub use self::iter::{Drain, Iter, IterMut, Keys, Values, ValuesMut};
 use super::free_list::FreeListIndex;
-use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE, ERR_NOT_EXIST};
+use super::{
+    ClearProgress, FreeList, LookupMap, Snapshot, Transactional, WritePolicy,
+    ERR_INCONSISTENT_STATE, ERR_NOT_EXIST,
+};
 
 /// A lazily loaded storage map that stores its content directly on the storage trie.
 /// This structure is similar to [`near_sdk::store::LookupMap`](crate::store::LookupMap), except
@@ -255,6 +258,78 @@ where
         }
     }
 
+    /// Removes up to `max_entries` key-value pairs, to clear the map over multiple calls when
+    /// removing all pairs at once would exceed the gas limit.
+    ///
+    /// Storage slots of pairs that were removed before are cleaned up as well, and count towards
+    /// `max_entries`. The map can be used as usual in-between the calls, but pairs inserted in the
+    /// meantime will be removed by later calls as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{ClearProgress, UnorderedMap};
+    ///
+    /// let mut map: UnorderedMap<String, u8> = UnorderedMap::new(b"b");
+    /// map.insert("a".to_string(), 1);
+    /// map.insert("b".to_string(), 2);
+    ///
+    /// assert_eq!(map.clear_chunk(1), ClearProgress::Remaining(1));
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.clear_chunk(1), ClearProgress::Done);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn clear_chunk(&mut self, max_entries: u32) -> ClearProgress
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        for k in self.keys.clear_chunk(max_entries) {
+            // Set instead of remove to avoid loading the value from storage.
+            self.values.set(k, None);
+        }
+        ClearProgress::remaining(self.keys.slots())
+    }
+
+    /// Moves up to `max_entries` keys into the storage slots of removed pairs, to compact the map
+    /// over multiple calls. Returns `true` once the map is compact.
+    ///
+    /// Removed pairs keep their storage slot until an insertion reuses it, and iterating over the
+    /// map loads the slots of removed pairs too, so a map that had many pairs removed costs more
+    /// storage and gas to iterate than its length suggests. Compacting moves the last keys into
+    /// those slots and removes the slots that are left at the end, after which iterating loads
+    /// only as many slots as the map has pairs. Removing slots at the end counts towards
+    /// `max_entries` as well. The map can be used as usual in-between the calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut map: UnorderedMap<u32, u32> = UnorderedMap::new(b"m");
+    /// map.extend((0..100).map(|i| (i, i)));
+    /// for i in 0..90 {
+    ///     map.remove(&i);
+    /// }
+    ///
+    /// while !map.compact(20) {}
+    /// assert_eq!(map.len(), 10);
+    /// assert_eq!(map[&95], 95);
+    /// ```
+    pub fn compact(&mut self, max_entries: u32) -> bool
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        let values = &mut self.values;
+        let done = self.keys.compact(max_entries, |k, index| {
+            let entry = values.get_mut(k).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            entry.key_index = index;
+        });
+        self.values.write_through();
+        done
+    }
+
     /// An iterator visiting all key-value pairs in arbitrary order.
     /// The iterator element type is `(&'a K, &'a V)`.
     ///
@@ -442,6 +517,29 @@ where
         self.values.get(k).map(|v| &v.value)
     }
 
+    /// Returns references to the values of `keys`, in the same order, reading every key that is
+    /// not cached yet from storage in one pass before any value is used. See
+    /// [`LookupMap::get_many`].
+    ///
+    /// # Example
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut balances: UnorderedMap<String, u128> = UnorderedMap::new(b"b");
+    /// balances.insert("alice".to_string(), 10);
+    ///
+    /// let total: u128 = balances.get_many(["alice", "bob"]).into_iter().flatten().sum();
+    /// assert_eq!(total, 10);
+    /// ```
+    pub fn get_many<'k, Q, I>(&self, keys: I) -> Vec<Option<&V>>
+    where
+        I: IntoIterator<Item = &'k Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + BorshSerialize + ToOwned<Owned = K> + 'k,
+    {
+        keys.into_iter().map(|k| self.get(k)).collect()
+    }
+
     /// Returns a mutable reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map's key type, but
@@ -494,14 +592,16 @@ where
     {
         // Check if value is in map to replace first
         let entry = self.values.get_mut_inner(&k);
-        if let Some(existing) = entry.value_mut() {
-            return Some(mem::replace(&mut existing.value, value));
-        }
-
-        // At this point, we know that the key-value doesn't exist in the map, add key to bucket.
-        let key_index = self.keys.insert(k);
-        entry.replace(Some(ValueAndIndex { value, key_index }));
-        None
+        let prev = if let Some(existing) = entry.value_mut() {
+            Some(mem::replace(&mut existing.value, value))
+        } else {
+            // At this point, we know that the key-value doesn't exist in the map, add key to bucket.
+            let key_index = self.keys.insert(k);
+            entry.replace(Some(ValueAndIndex { value, key_index }));
+            None
+        };
+        self.values.write_through();
+        prev
     }
 
     /// Returns `true` if the map contains a value for the specified key.
@@ -528,6 +628,17 @@ where
         self.values.contains_key(k)
     }
 
+    /// Returns whether the map contains each of `keys`, in the same order. See
+    /// [`LookupMap::contains_many`].
+    pub fn contains_many<'k, Q, I>(&self, keys: I) -> Vec<bool>
+    where
+        I: IntoIterator<Item = &'k Q>,
+        K: Borrow<Q>,
+        Q: ?Sized + BorshSerialize + ToOwned<Owned = K> + Ord + 'k,
+    {
+        keys.into_iter().map(|k| self.contains_key(k)).collect()
+    }
+
     /// Removes a key from the map, returning the value at the key if the key
     /// was previously in the map.
     ///
@@ -591,6 +702,36 @@ where
         Some((key, old_value.value))
     }
 
+    /// Removes up to `limit` key-value pairs and returns them, to process the pairs of the map a
+    /// chunk at a time over multiple calls, like a set of pending work items.
+    ///
+    /// The pairs are removed in arbitrary order, starting with the pairs stored last. Like other
+    /// changes, the removals are written to storage together when the map is flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut pending: UnorderedMap<u32, String> = UnorderedMap::new(b"p");
+    /// pending.extend((0..5).map(|i| (i, format!("job {}", i))));
+    ///
+    /// let batch = pending.drain_limit(3);
+    /// assert_eq!(batch.len(), 3);
+    /// assert_eq!(pending.len(), 2);
+    /// assert!(batch.iter().all(|(k, _)| !pending.contains_key(k)));
+    /// ```
+    pub fn drain_limit(&mut self, limit: u32) -> Vec<(K, V)>
+    where
+        K: BorshDeserialize + Clone,
+        V: BorshDeserialize,
+    {
+        let keys: Vec<K> = self.keys.iter().rev().take(limit as usize).cloned().collect();
+        keys.iter()
+            .map(|k| self.remove_entry(k).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE)))
+            .collect()
+    }
+
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
     /// ```
     /// use near_sdk::store::UnorderedMap;
@@ -623,17 +764,102 @@ where
 {
     /// Flushes the intermediate values of the map before this is called when the structure is
     /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
-    /// in memory.
+    /// in memory, unless there are more of them than the
+    /// [cache capacity](Self::set_cache_capacity).
     pub fn flush(&mut self) {
         self.keys.flush();
         self.values.flush();
     }
+
+    /// Returns when the map writes its changes to storage.
+    pub fn write_policy(&self) -> WritePolicy {
+        self.values.write_policy()
+    }
+
+    /// Sets when the map writes its changes to storage. Changes that are still cached are
+    /// written when switching to [`WritePolicy::WriteThrough`].
+    ///
+    /// The policy is not persisted with the map, see [`WritePolicy`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{UnorderedMap, WritePolicy};
+    ///
+    /// let map: UnorderedMap<u32, u32> =
+    ///     UnorderedMap::new(b"m").with_write_policy(WritePolicy::WriteThrough);
+    /// assert_eq!(map.write_policy(), WritePolicy::WriteThrough);
+    /// ```
+    pub fn set_write_policy(&mut self, policy: WritePolicy) {
+        self.keys.set_write_policy(policy);
+        self.values.set_write_policy(policy);
+    }
+
+    /// Returns the map with the given write policy, to choose it when the map is created.
+    pub fn with_write_policy(mut self, policy: WritePolicy) -> Self {
+        self.set_write_policy(policy);
+        self
+    }
+
+    /// Bounds the number of entries the map keeps in memory when they are accessed through
+    /// methods that take `&mut self`.
+    ///
+    /// The entries of the map and their positions are cached separately, and each cache is
+    /// emptied before loading a value once it holds `capacity` values, as with
+    /// [`LookupMap::set_cache_capacity`]. Values read through `&self`, including by iterators,
+    /// stay in memory until the next change, so a method that reads many entries should call
+    /// [`flush`](Self::flush) between chunks of reads.
+    ///
+    /// The capacity is not persisted with the map, so maps loaded from the contract state
+    /// start with an unbounded cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedMap;
+    ///
+    /// let mut map: UnorderedMap<u32, u64> = UnorderedMap::new(b"m");
+    /// map.extend((0..100).map(|i| (i, u64::from(i))));
+    /// map.flush();
+    /// map.set_cache_capacity(Some(10));
+    ///
+    /// let mut total = 0;
+    /// for start in (0..map.len()).step_by(10) {
+    ///     total += map.values().skip(start as usize).take(10).sum::<u64>();
+    ///     map.flush();
+    /// }
+    /// assert_eq!(total, 4950);
+    /// ```
+    pub fn set_cache_capacity(&mut self, capacity: Option<u32>) {
+        self.keys.set_cache_capacity(capacity);
+        self.values.set_cache_capacity(capacity);
+    }
+}
+
+impl<K, V, H> Transactional for UnorderedMap<K, V, H>
+where
+    K: BorshSerialize + Ord,
+    V: BorshSerialize,
+    H: ToKey,
+{
+    type Snapshot = Snapshot<(<FreeList<K> as Transactional>::Snapshot, Snapshot<()>)>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        Snapshot((self.keys.snapshot(), self.values.snapshot()))
+    }
+
+    fn rollback(&mut self, snapshot: Self::Snapshot) {
+        let (keys, values) = snapshot.0;
+        self.keys.rollback(keys);
+        self.values.rollback(values);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
     use super::UnorderedMap;
+    use crate::store::{ClearProgress, WritePolicy};
     use crate::test_utils::test_env::setup_free;
     use arbitrary::{Arbitrary, Unstructured};
     use borsh::{BorshDeserialize, BorshSerialize};
@@ -672,6 +898,108 @@ mod tests {
         assert_eq!(*value, 12);
     }
 
+    #[test]
+    fn extend_writes_on_flush() {
+        let mut map = UnorderedMap::new(b"b");
+        map.extend((0..100u32).map(|i| (i, i)));
+        map.extend((50..150u32).map(|i| (i, i + 1)));
+        assert_eq!(map.len(), 150);
+        assert_eq!(map[&60], 61);
+
+        // Nothing is written until the map is flushed, then one value and one key per entry.
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+        map.flush();
+        crate::mock::with_mocked_blockchain(|m| assert_eq!(m.take_storage().len(), 300));
+    }
+
+    #[test]
+    fn clear_chunk_across_calls() {
+        let mut map = UnorderedMap::new(b"b");
+        map.extend((0..100u32).map(|i| (i, i)));
+        for i in (0..100u32).step_by(3) {
+            map.remove(&i);
+        }
+        map.flush();
+
+        assert_eq!(map.clear_chunk(40), ClearProgress::Remaining(60));
+        // Pairs can still be used and inserted in-between calls.
+        map.insert(200, 200);
+        assert_eq!(map[&1], 1);
+        assert_eq!(map.get(&99), None);
+        assert_eq!(map.len(), 41);
+
+        while !map.clear_chunk(40).is_done() {}
+        assert!(map.is_empty());
+        map.flush();
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+    }
+
+    #[test]
+    fn compact_across_calls() {
+        let mut map = UnorderedMap::new(b"b");
+        map.extend((0..100u32).map(|i| (i, i)));
+        for i in (0..100u32).filter(|i| i % 4 != 0) {
+            map.remove(&i);
+        }
+        map.flush();
+
+        assert!(!map.compact(10));
+        // Pairs can still be used and inserted in-between calls.
+        map.insert(200, 200);
+        map.remove(&96);
+        assert_eq!(map[&92], 92);
+
+        while !map.compact(10) {}
+        assert_eq!(map.len(), 25);
+        assert_eq!(map.keys.slots(), 25);
+        let expected: HashMap<_, _> = (0..96).step_by(4).chain([200]).map(|i| (i, i)).collect();
+        assert_eq!(map.iter().map(|(k, v)| (*k, *v)).collect::<HashMap<_, _>>(), expected);
+        for (k, v) in &expected {
+            assert_eq!(map.get(k), Some(v));
+        }
+        assert_eq!(map.remove(&200), Some(200));
+        assert_eq!(map.remove(&0), Some(0));
+        assert_eq!(map.len(), 23);
+    }
+
+    #[test]
+    fn drain_limit_in_chunks() {
+        let mut map = UnorderedMap::new(b"m");
+        map.extend((0..10u32).map(|i| (i, i * 2)));
+
+        let mut drained = Vec::new();
+        while !map.is_empty() {
+            let chunk = map.drain_limit(4);
+            assert!(chunk.len() <= 4);
+            drained.extend(chunk);
+        }
+        map.flush();
+
+        drained.sort_unstable();
+        assert_eq!(drained, (0..10).map(|i| (i, i * 2)).collect::<Vec<_>>());
+        assert!(map.drain_limit(4).is_empty());
+        let reloaded =
+            UnorderedMap::<u32, u32>::try_from_slice(&map.try_to_vec().unwrap()).unwrap();
+        assert!(reloaded.is_empty() && reloaded.iter().next().is_none());
+    }
+
+    #[test]
+    fn write_through() {
+        let reload = |map: &UnorderedMap<u8, u8>| {
+            UnorderedMap::<u8, u8>::try_from_slice(&map.try_to_vec().unwrap())
+        };
+        let mut map = UnorderedMap::new(b"m").with_write_policy(WritePolicy::WriteThrough);
+        map.insert(1u8, 1u8);
+        map.insert(2, 2);
+        assert_eq!(reload(&map).unwrap().get(&1), Some(&1));
+
+        map.insert(1, 10);
+        assert_eq!(reload(&map).unwrap().get(&1), Some(&10));
+        map.remove(&1);
+        let reloaded = reload(&map).unwrap();
+        assert_eq!((reloaded.len(), reloaded.get(&1), reloaded.get(&2)), (1, None, Some(&2)));
+    }
+
     #[test]
     fn map_iterator() {
         let mut map = UnorderedMap::new(b"b");
@@ -756,4 +1084,12 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn serde_serialize() {
+        let mut map = UnorderedMap::new(b"m");
+        map.extend([(1u8, "a".to_string()), (2, "b".to_string()), (3, "c".to_string())]);
+        map.remove(&2);
+        assert_eq!(serde_json::to_string(&map).unwrap(), r#"[[1,"a"],[3,"c"]]"#);
+    }
 }
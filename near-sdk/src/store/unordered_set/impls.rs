@@ -1,6 +1,7 @@
 use super::UnorderedSet;
 use crate::store::key::ToKey;
 use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Serialize, Serializer};
 
 impl<T, H> Extend<T> for UnorderedSet<T, H>
 where
@@ -16,3 +17,38 @@ where
         }
     }
 }
+
+/// Serializes all elements as a sequence, in the order of [`UnorderedSet::iter`], loading each of
+/// them from storage. For large sets, return a [`Page`](crate::store::Page) of the elements
+/// instead.
+impl<T, H> Serialize for UnorderedSet<T, H>
+where
+    T: BorshSerialize + BorshDeserialize + Ord + Serialize,
+    H: ToKey,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "abi")]
+impl<T, H> schemars::JsonSchema for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord + schemars::JsonSchema,
+    H: ToKey,
+{
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        <Vec<T>>::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <Vec<T>>::json_schema(gen)
+    }
+}
@@ -158,6 +158,8 @@ where
     H: ToKey,
 {
     pub(super) fn new(set: &'a UnorderedSet<T, H>, other: &'a UnorderedSet<T, H>) -> Self {
+        // Iterate over the smaller set to load fewer elements and look them up in the larger one.
+        let (set, other) = if set.len() <= other.len() { (set, other) } else { (other, set) };
         Self { elements: set.elements.iter(), other }
     }
 }
@@ -192,10 +194,10 @@ where
 
 /// A lazy iterator producing elements in the symmetrical difference of `UnorderedSet`s.
 ///
-/// This `struct` is created by the [`symmetrical_difference`] method on [`UnorderedSet`].
+/// This `struct` is created by the [`symmetric_difference`] method on [`UnorderedSet`].
 /// See its documentation for more.
 ///
-/// [`symmetrical_difference`]: UnorderedSet::symmetrical_difference
+/// [`symmetric_difference`]: UnorderedSet::symmetric_difference
 pub struct SymmetricDifference<'a, T, H>
 where
     T: BorshSerialize + Ord + BorshDeserialize,
@@ -1,17 +1,21 @@
 mod impls;
 mod iter;
 
-use super::{FreeList, LookupMap, ERR_INCONSISTENT_STATE};
+use super::{
+    ClearProgress, FreeList, LookupMap, Snapshot, Transactional, WritePolicy,
+    ERR_INCONSISTENT_STATE,
+};
 use crate::store::free_list::FreeListIndex;
 use crate::store::key::{Sha256, ToKey};
-use crate::store::unordered_set::iter::{
-    Difference, Drain, Intersection, Iter, SymmetricDifference, Union,
-};
 use crate::{env, IntoStorageKey};
 use borsh::{BorshDeserialize, BorshSerialize};
+use rand_core::RngCore;
 use std::borrow::Borrow;
+use std::collections::BTreeSet;
 use std::fmt;
 
+pub use self::iter::{Difference, Drain, Intersection, Iter, SymmetricDifference, Union};
+
 /// A lazily loaded storage set that stores its content directly on the storage trie.
 /// This structure is similar to [`near_sdk::store::LookupSet`](crate::store::LookupSet), except
 /// that it keeps track of the elements so that [`UnorderedSet`] can be iterable among other things.
@@ -182,6 +186,59 @@ where
         }
     }
 
+    /// Removes up to `max_entries` values, to clear the set over multiple calls when removing
+    /// all values at once would exceed the gas limit.
+    ///
+    /// Storage slots of values that were removed before are cleaned up as well, and count
+    /// towards `max_entries`. The set can be used as usual in-between the calls, but values
+    /// inserted in the meantime will be removed by later calls as well.
+    pub fn clear_chunk(&mut self, max_entries: u32) -> ClearProgress
+    where
+        T: BorshDeserialize + Clone,
+    {
+        for e in self.elements.clear_chunk(max_entries) {
+            self.index.set(e, None);
+        }
+        ClearProgress::remaining(self.elements.slots())
+    }
+
+    /// Moves up to `max_entries` values into the storage slots of removed values, to compact the
+    /// set over multiple calls. Returns `true` once the set is compact.
+    ///
+    /// Removed values keep their storage slot until an insertion reuses it, and iterating over
+    /// the set loads the slots of removed values too. Compacting moves the last values into those
+    /// slots and removes the slots that are left at the end, after which iterating loads only as
+    /// many slots as the set has values. Removing slots at the end counts towards `max_entries`
+    /// as well. The set can be used as usual in-between the calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let mut set: UnorderedSet<u32> = UnorderedSet::new(b"s");
+    /// set.extend(0..100);
+    /// for v in 0..100 {
+    ///     if v % 10 != 0 {
+    ///         set.remove(&v);
+    ///     }
+    /// }
+    ///
+    /// while !set.compact(20) {}
+    /// assert_eq!(set.len(), 10);
+    /// assert!(set.contains(&90));
+    /// ```
+    pub fn compact(&mut self, max_entries: u32) -> bool
+    where
+        T: BorshDeserialize + Clone,
+    {
+        let index = &mut self.index;
+        self.elements.compact(max_entries, |value, element_index| {
+            // Set instead of modifying to avoid loading the previous index from storage.
+            index.set(value.clone(), Some(element_index));
+        })
+    }
+
     /// Visits the values representing the difference, i.e., the values that are in `self` but not
     /// in `other`.
     ///
@@ -248,6 +305,9 @@ where
     /// Visits the values representing the intersection, i.e., the values that are both in `self`
     /// and `other`.
     ///
+    /// The values are loaded from the smaller of the two sets and looked up in the other one, so
+    /// the order of the arguments does not change the cost.
+    ///
     /// # Examples
     ///
     /// ```
@@ -421,6 +481,67 @@ where
         Iter::new(self)
     }
 
+    /// Returns a uniformly random element of the set, or `None` if the set is empty.
+    ///
+    /// Elements are picked by their position in storage, so only the picked elements are loaded,
+    /// along with the positions left empty by removed elements that were picked first. Use
+    /// [`ContractRng`](crate::rand::ContractRng) to sample with the random seed of the block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::{rand::ContractRng, store::UnorderedSet};
+    ///
+    /// let mut tickets = UnorderedSet::new(b"t");
+    /// tickets.extend(["alice.near", "bob.near", "carol.near"].map(String::from));
+    ///
+    /// let winner = tickets.sample(&mut ContractRng::with_nonce(b"raffle")).unwrap();
+    /// assert!(tickets.contains(winner));
+    /// ```
+    pub fn sample<R>(&self, rng: &mut R) -> Option<&T>
+    where
+        T: BorshDeserialize,
+        R: RngCore,
+    {
+        self.elements.sample(rng).map(|(_, value)| value)
+    }
+
+    /// Returns `n` distinct elements of the set picked uniformly at random, in the order they
+    /// were picked, or all elements in random order if the set has fewer than `n` elements.
+    ///
+    /// Like [`sample`](Self::sample), this only loads the picked elements, but picks again when
+    /// an element was already picked, so it is cheapest when `n` is small compared to the length
+    /// of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::{rand::ContractRng, store::UnorderedSet};
+    ///
+    /// let mut validators: UnorderedSet<u32> = UnorderedSet::new(b"v");
+    /// validators.extend(0..100);
+    ///
+    /// let selected = validators.sample_n(&mut ContractRng::new(), 5);
+    /// assert_eq!(selected.len(), 5);
+    /// ```
+    pub fn sample_n<R>(&self, rng: &mut R, n: u32) -> Vec<&T>
+    where
+        T: BorshDeserialize,
+        R: RngCore,
+    {
+        let n = n.min(self.len());
+        let mut picked = BTreeSet::new();
+        let mut samples = Vec::with_capacity(n as usize);
+        while samples.len() < n as usize {
+            let (index, value) =
+                self.elements.sample(rng).unwrap_or_else(|| env::panic_str(ERR_INCONSISTENT_STATE));
+            if picked.insert(index.0) {
+                samples.push(value);
+            }
+        }
+        samples
+    }
+
     /// Clears the set, returning all elements in an iterator.
     ///
     /// # Examples
@@ -468,13 +589,15 @@ where
         T: Clone + BorshDeserialize,
     {
         let entry = self.index.get_mut_inner(&value);
-        if entry.value_mut().is_some() {
+        let inserted = if entry.value_mut().is_some() {
             false
         } else {
             let element_index = self.elements.insert(value);
             entry.replace(Some(element_index));
             true
-        }
+        };
+        self.index.write_through();
+        inserted
     }
 
     /// Removes a value from the set. Returns whether the value was present in the set.
@@ -500,11 +623,94 @@ where
 
     /// Flushes the intermediate values of the map before this is called when the structure is
     /// [`Drop`]ed. This will write all modified values to storage but keep all cached values
-    /// in memory.
+    /// in memory, unless there are more of them than the
+    /// [cache capacity](Self::set_cache_capacity).
     pub fn flush(&mut self) {
         self.elements.flush();
         self.index.flush();
     }
+
+    /// Returns when the set writes its changes to storage.
+    pub fn write_policy(&self) -> WritePolicy {
+        self.index.write_policy()
+    }
+
+    /// Sets when the set writes its changes to storage. Changes that are still cached are
+    /// written when switching to [`WritePolicy::WriteThrough`].
+    ///
+    /// The policy is not persisted with the set, see [`WritePolicy`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{UnorderedSet, WritePolicy};
+    ///
+    /// let set: UnorderedSet<u32> =
+    ///     UnorderedSet::new(b"s").with_write_policy(WritePolicy::WriteThrough);
+    /// assert_eq!(set.write_policy(), WritePolicy::WriteThrough);
+    /// ```
+    pub fn set_write_policy(&mut self, policy: WritePolicy) {
+        self.elements.set_write_policy(policy);
+        self.index.set_write_policy(policy);
+    }
+
+    /// Returns the set with the given write policy, to choose it when the set is created.
+    pub fn with_write_policy(mut self, policy: WritePolicy) -> Self {
+        self.set_write_policy(policy);
+        self
+    }
+
+    /// Bounds the number of elements the set keeps in memory when they are accessed through
+    /// methods that take `&mut self`.
+    ///
+    /// The elements of the set and their positions are cached separately, and each cache is
+    /// emptied before loading a value once it holds `capacity` values, as with
+    /// [`LookupMap::set_cache_capacity`]. Values read through `&self`, including by iterators,
+    /// stay in memory until the next change, so a method that reads many elements should call
+    /// [`flush`](Self::flush) between chunks of reads.
+    ///
+    /// The capacity is not persisted with the set, so sets loaded from the contract state
+    /// start with an unbounded cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::UnorderedSet;
+    ///
+    /// let mut set: UnorderedSet<u64> = UnorderedSet::new(b"s");
+    /// set.extend(0..100);
+    /// set.flush();
+    /// set.set_cache_capacity(Some(10));
+    ///
+    /// let mut total = 0;
+    /// for start in (0..set.len()).step_by(10) {
+    ///     total += set.iter().skip(start as usize).take(10).sum::<u64>();
+    ///     set.flush();
+    /// }
+    /// assert_eq!(total, 4950);
+    /// ```
+    pub fn set_cache_capacity(&mut self, capacity: Option<u32>) {
+        self.elements.set_cache_capacity(capacity);
+        self.index.set_cache_capacity(capacity);
+    }
+}
+
+impl<T, H> Transactional for UnorderedSet<T, H>
+where
+    T: BorshSerialize + Ord,
+    H: ToKey,
+{
+    type Snapshot = Snapshot<(<FreeList<T> as Transactional>::Snapshot, Snapshot<()>)>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        Snapshot((self.elements.snapshot(), self.index.snapshot()))
+    }
+
+    fn rollback(&mut self, snapshot: Self::Snapshot) {
+        let (elements, index) = snapshot.0;
+        self.elements.rollback(elements);
+        self.index.rollback(index);
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -530,6 +736,53 @@ mod tests {
         assert_eq!(set.len(), 0);
     }
 
+    #[test]
+    fn compact_across_calls() {
+        let mut set = UnorderedSet::new(b"b");
+        set.extend(0..100u32);
+        for i in (0..100u32).rev().filter(|i| i % 3 != 0) {
+            set.remove(&i);
+        }
+
+        assert!(!set.compact(10));
+        set.insert(1000);
+        while !set.compact(10) {}
+        assert_eq!(set.elements.slots(), set.len());
+
+        let expected: HashSet<_> = (0..100).step_by(3).chain([1000]).collect();
+        assert_eq!(set.iter().copied().collect::<HashSet<_>>(), expected);
+        for v in &expected {
+            assert!(set.remove(v));
+        }
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn sample() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(0);
+        let mut set = UnorderedSet::new(b"s");
+        assert_eq!(set.sample(&mut rng), None);
+        assert!(set.sample_n(&mut rng, 3).is_empty());
+
+        set.extend(0..20u8);
+        for i in 0..15 {
+            set.remove(&i);
+        }
+        let mut counts = [0u32; 20];
+        for _ in 0..1000 {
+            counts[*set.sample(&mut rng).unwrap() as usize] += 1;
+        }
+        assert!(counts[..15].iter().all(|&c| c == 0));
+        assert!(counts[15..].iter().all(|&c| c > 150));
+
+        let picked: HashSet<_> = set.sample_n(&mut rng, 3).into_iter().collect();
+        assert_eq!(picked.len(), 3);
+        assert!(picked.iter().all(|v| set.contains(*v)));
+        let mut all = set.sample_n(&mut rng, 10);
+        all.sort();
+        assert_eq!(all, [&15, &16, &17, &18, &19]);
+    }
+
     #[test]
     fn set_iterator() {
         let mut set = UnorderedSet::new(b"b");
@@ -736,6 +989,51 @@ mod tests {
         assert_eq!(set1.union(&set2).collect::<HashSet<_>>(), HashSet::new());
     }
 
+    #[test]
+    fn set_operations_match_std() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(3);
+        let mut set1 = UnorderedSet::new(b"m");
+        let mut set2 = UnorderedSet::new(b"n");
+        let mut baseline1 = HashSet::new();
+        let mut baseline2 = HashSet::new();
+        for _ in 0..200 {
+            let value = (rng.next_u32() % 100) as u8;
+            if rng.next_u32() % 3 == 0 {
+                set1.insert(value);
+                baseline1.insert(value);
+            } else {
+                set2.insert(value);
+                baseline2.insert(value);
+            }
+            if rng.next_u32() % 5 == 0 {
+                let value = (rng.next_u32() % 100) as u8;
+                set2.remove(&value);
+                baseline2.remove(&value);
+            }
+        }
+        set1.flush();
+        set2.flush();
+
+        for (a, b, base_a, base_b) in
+            [(&set1, &set2, &baseline1, &baseline2), (&set2, &set1, &baseline2, &baseline1)]
+        {
+            assert_eq!(a.union(b).collect::<HashSet<_>>(), base_a.union(base_b).collect());
+            assert_eq!(
+                a.intersection(b).collect::<HashSet<_>>(),
+                base_a.intersection(base_b).collect()
+            );
+            assert_eq!(
+                a.difference(b).collect::<HashSet<_>>(),
+                base_a.difference(base_b).collect()
+            );
+            assert_eq!(
+                a.symmetric_difference(b).collect::<HashSet<_>>(),
+                base_a.symmetric_difference(base_b).collect()
+            );
+            assert_eq!(a.union(b).count(), base_a.union(base_b).count());
+        }
+    }
+
     #[test]
     fn test_subset_and_superset() {
         let mut a = UnorderedSet::new(b"m");
@@ -844,4 +1142,11 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn serde_serialize() {
+        let mut set = UnorderedSet::new(b"s");
+        set.extend(["a".to_string(), "b".to_string()]);
+        assert_eq!(serde_json::to_string(&set).unwrap(), r#"["a","b"]"#);
+    }
 }
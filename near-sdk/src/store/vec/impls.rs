@@ -1,4 +1,5 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Serialize, Serializer};
 
 use super::iter::{Iter, IterMut};
 use super::{Vector, ERR_INDEX_OUT_OF_BOUNDS};
@@ -41,6 +42,9 @@ impl<T> Extend<T> for Vector<T>
 where
     T: BorshSerialize + BorshDeserialize,
 {
+    /// Appends all elements of the iterator. Like [`Vector::push`], the elements are only
+    /// written to storage when the vector is flushed, so extending with many elements results in
+    /// a single write per element and no reads.
     fn extend<I>(&mut self, iter: I)
     where
         I: IntoIterator<Item = T>,
@@ -70,3 +74,56 @@ where
         self.get_mut(index).unwrap_or_else(|| env::panic_str(ERR_INDEX_OUT_OF_BOUNDS))
     }
 }
+
+/// Serializes all elements as a sequence, loading each of them from storage. For large vectors,
+/// return a [`Page`](crate::store::Page) of the elements instead.
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::near_bindgen;
+/// use near_sdk::store::Vector;
+///
+/// #[near_bindgen]
+/// #[derive(BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {
+///     messages: Vector<String>,
+/// }
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn get_messages(&self) -> &Vector<String> {
+///         &self.messages
+///     }
+/// }
+/// ```
+impl<T> Serialize for Vector<T>
+where
+    T: BorshSerialize + BorshDeserialize + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "abi")]
+impl<T> schemars::JsonSchema for Vector<T>
+where
+    T: BorshSerialize + schemars::JsonSchema,
+{
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        <Vec<T>>::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <Vec<T>>::json_schema(gen)
+    }
+}
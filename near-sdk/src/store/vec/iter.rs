@@ -105,7 +105,7 @@ where
     T: BorshSerialize + BorshDeserialize,
 {
     fn get_mut<'b>(&'b mut self, at: u32) -> Option<&'a mut T> {
-        self.vec.get_mut(at).map(|value| {
+        self.vec.values.get_mut_without_eviction(at).value_mut().as_mut().map(|value| {
             //* SAFETY: The lifetime can be swapped here because we can assert that the iterator
             //*         will only give out one mutable reference for every individual item
             //*         during the iteration, and there is no overlap. This must be checked
@@ -199,11 +199,8 @@ where
     T: BorshSerialize + BorshDeserialize,
 {
     fn drop(&mut self) {
-        let delete_indices = (self.delete_range.start..self.range.start)
-            .chain(self.range.end..self.delete_range.end);
-
         // Delete any non-deleted elements from iterator (not loading from storage)
-        for i in delete_indices {
+        for i in self.range.clone() {
             self.vec.values.set(i, None);
         }
 
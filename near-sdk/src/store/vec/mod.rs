@@ -56,6 +56,7 @@ mod impls;
 mod iter;
 
 use std::{
+    cmp::Ordering,
     fmt,
     ops::{Bound, Range, RangeBounds},
 };
@@ -66,7 +67,7 @@ pub use self::iter::{Drain, Iter, IterMut};
 use super::ERR_INCONSISTENT_STATE;
 use crate::{env, IntoStorageKey};
 
-use super::IndexMap;
+use super::{IndexMap, WritePolicy};
 
 const ERR_INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
 
@@ -235,9 +236,37 @@ where
             self.values.set(i, None);
         }
         self.len = 0;
+        self.write_through();
     }
 
-    /// Flushes the cache and writes all modified values to storage.
+    /// Removes up to `max_entries` elements from the back of the collection, to clear it over
+    /// multiple calls when removing all elements at once would exceed the gas limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{ClearProgress, Vector};
+    ///
+    /// let mut vec = Vector::new(b"a");
+    /// vec.extend([1, 2, 3]);
+    ///
+    /// assert_eq!(vec.clear_chunk(2), ClearProgress::Remaining(1));
+    /// assert_eq!(vec.clear_chunk(2), ClearProgress::Done);
+    /// assert!(vec.is_empty());
+    /// ```
+    #[cfg(feature = "unstable")]
+    pub fn clear_chunk(&mut self, max_entries: u32) -> super::ClearProgress {
+        let new_len = self.len.saturating_sub(max_entries);
+        for i in new_len..self.len {
+            self.values.set(i, None);
+        }
+        self.len = new_len;
+        self.write_through();
+        super::ClearProgress::remaining(new_len)
+    }
+
+    /// Flushes the cache and writes all modified values to storage. Cached values are kept in
+    /// memory, unless there are more of them than the [cache capacity](Self::set_cache_capacity).
     ///
     /// This operation is performed on [`Drop`], but this method can be called to persist
     /// intermediate writes in cases where [`Drop`] is not called or to identify storage changes.
@@ -245,6 +274,95 @@ where
         self.values.flush();
     }
 
+    /// Returns when the vector writes its changes to storage.
+    pub fn write_policy(&self) -> WritePolicy {
+        self.values.write_policy
+    }
+
+    /// Sets when the vector writes its changes to storage. Changes that are still cached are
+    /// written when switching to [`WritePolicy::WriteThrough`].
+    ///
+    /// The policy is not persisted with the vector, see [`WritePolicy`]. The length of the vector
+    /// is part of the contract state either way, so it is written with the state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::{env, store::{Vector, WritePolicy}};
+    ///
+    /// let mut vec: Vector<u8> = Vector::new(b"v");
+    /// vec.set_write_policy(WritePolicy::WriteThrough);
+    ///
+    /// vec.push(1);
+    /// assert!(env::storage_has_key(&[b"v".as_slice(), &0u32.to_le_bytes()].concat()));
+    /// ```
+    pub fn set_write_policy(&mut self, policy: WritePolicy) {
+        self.values.set_write_policy(policy);
+    }
+
+    /// Returns the vector with the given write policy, to choose it when the vector is created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::{Vector, WritePolicy};
+    ///
+    /// let vec: Vector<u8> = Vector::new(b"v").with_write_policy(WritePolicy::WriteThrough);
+    /// assert_eq!(vec.write_policy(), WritePolicy::WriteThrough);
+    /// ```
+    pub fn with_write_policy(mut self, policy: WritePolicy) -> Self {
+        self.set_write_policy(policy);
+        self
+    }
+
+    /// Returns the number of elements the vector keeps in memory before emptying its cache, or
+    /// `None` if the cache is not bounded.
+    pub fn cache_capacity(&self) -> Option<u32> {
+        self.values.cache_capacity
+    }
+
+    /// Bounds the number of elements the vector keeps in memory when they are accessed through
+    /// methods that take `&mut self`.
+    ///
+    /// Before such a method loads an element into a full cache, the changes are written to
+    /// storage and the cache is emptied, and elements accessed later are read from storage again.
+    /// [`get`](Self::get), [`iter`](Self::iter) and [`iter_mut`](Self::iter_mut) return
+    /// references into the cache, so the elements they load stay in memory until the next
+    /// [`flush`](Self::flush) or change. A method that iterates over many elements should call
+    /// `flush` between chunks of the iteration. Changes written this way are kept if a
+    /// [`transaction`](crate::store::transaction) fails, like changes written under
+    /// [`WritePolicy::WriteThrough`].
+    ///
+    /// The capacity is not persisted with the vector, so vectors loaded from the contract state
+    /// start with an unbounded cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec: Vector<u64> = Vector::new(b"v");
+    /// vec.extend(0..100);
+    /// vec.set_cache_capacity(Some(10));
+    ///
+    /// let mut total = 0;
+    /// for start in (0..vec.len()).step_by(10) {
+    ///     total += vec.iter().skip(start as usize).take(10).sum::<u64>();
+    ///     vec.flush();
+    /// }
+    /// assert_eq!(total, 4950);
+    /// ```
+    pub fn set_cache_capacity(&mut self, capacity: Option<u32>) {
+        self.values.cache_capacity = capacity;
+        self.write_through();
+    }
+
+    /// Writes the changes if the vector writes through, or all of them if its cache is over
+    /// capacity.
+    fn write_through(&mut self) {
+        self.values.write_through();
+    }
+
     /// Sets a value at a given index to the value provided. This does not shift values after the
     /// index to the right.
     ///
@@ -274,6 +392,7 @@ where
         }
 
         self.values.set(index, Some(value));
+        self.write_through();
     }
 
     /// Appends an element to the back of the collection.
@@ -405,9 +524,40 @@ where
         let new_idx = self.len.checked_sub(1)?;
         let prev = self.values.get_mut_inner(new_idx).replace(None);
         self.len = new_idx;
+        self.write_through();
         prev
     }
 
+    /// Removes up to `n` elements from the end of the vector and returns them in the order they
+    /// were removed, starting with the last element.
+    ///
+    /// This is the same as calling [`Vector::pop`] up to `n` times, except that with
+    /// [`WritePolicy::WriteThrough`] the removals are written to storage once, together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec = Vector::new(b"v");
+    /// vec.extend([1, 2, 3, 4, 5]);
+    ///
+    /// assert_eq!(vec.pop_n(2), [5, 4]);
+    /// assert_eq!(vec.pop_n(10), [3, 2, 1]);
+    /// assert!(vec.pop_n(1).is_empty());
+    /// ```
+    pub fn pop_n(&mut self, n: u32) -> Vec<T> {
+        let n = n.min(self.len);
+        let mut popped = Vec::with_capacity(n as usize);
+        for _ in 0..n {
+            self.len -= 1;
+            let prev = self.values.get_mut_inner(self.len).replace(None);
+            popped.push(expect_consistent_state(prev));
+        }
+        self.write_through();
+        popped
+    }
+
     /// Inserts a element at `index`, returns an evicted element.
     ///
     /// # Panics
@@ -432,7 +582,9 @@ where
         if index >= self.len {
             env::panic_str(ERR_INDEX_OUT_OF_BOUNDS);
         }
-        self.values.insert(index, element).unwrap()
+        let prev = self.values.insert(index, element).unwrap();
+        self.write_through();
+        prev
     }
 
     /// Returns an iterator over the vector. This iterator will lazily load any values iterated
@@ -526,6 +678,185 @@ where
         // This will also cap the max length at the length of the vector.
         Drain::new(self, Range { start, end: core::cmp::min(end, self.len()) })
     }
+
+    /// Retains only the elements for which `f` returns `true`, preserving their order.
+    ///
+    /// Every element is loaded once. The retained elements are shifted over the removed ones in
+    /// the cache, so each storage slot that changed is written or removed only once, when the
+    /// vector is flushed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec: Vector<u32> = Vector::new(b"v");
+    /// vec.extend([1, 2, 3, 4, 5]);
+    ///
+    /// vec.retain(|x| x % 2 == 1);
+    /// assert_eq!(vec.iter().copied().collect::<Vec<_>>(), &[1, 3, 5]);
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut kept = 0;
+        for i in 0..self.len {
+            if f(expect_consistent_state(self.values.get(i))) {
+                // Move the element into the first removed slot, which leaves the slot empty.
+                self.values.swap(kept, i);
+                kept += 1;
+            } else {
+                self.values.set(i, None);
+            }
+        }
+        self.len = kept;
+        self.write_through();
+    }
+
+    /// Binary searches this sorted vector with a comparator function, loading only the
+    /// `O(log n)` elements that are compared.
+    ///
+    /// The comparator should return whether the element is [`Less`](Ordering::Less),
+    /// [`Equal`](Ordering::Equal) or [`Greater`](Ordering::Greater) than the target. If the
+    /// vector is not sorted in a way consistent with the comparator, the result is unspecified.
+    ///
+    /// If a matching element is found, [`Ok`] is returned with its index, and if there are
+    /// several matches, any one of them may be returned. Otherwise, [`Err`] is returned with the
+    /// index where a matching element could be inserted while keeping the vector sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec: Vector<u32> = Vector::new(b"v");
+    /// vec.extend([1, 3, 5, 7]);
+    ///
+    /// assert_eq!(vec.binary_search_by(|x| x.cmp(&5)), Ok(2));
+    /// assert_eq!(vec.binary_search_by(|x| x.cmp(&4)), Err(2));
+    /// ```
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<u32, u32>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match f(expect_consistent_state(self.values.get(mid))) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(low)
+    }
+
+    /// Binary searches this sorted vector for the element with the given key, as extracted by
+    /// `f`. See [`binary_search_by`](Self::binary_search_by) for the returned value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// // Price levels sorted by price.
+    /// let mut levels: Vector<(u128, u32)> = Vector::new(b"v");
+    /// levels.extend([(100, 5), (120, 1), (150, 8)]);
+    ///
+    /// assert_eq!(levels.binary_search_by_key(&120, |&(price, _)| price), Ok(1));
+    /// assert_eq!(levels.binary_search_by_key(&200, |&(price, _)| price), Err(3));
+    /// ```
+    pub fn binary_search_by_key<B, F>(&self, key: &B, mut f: F) -> Result<u32, u32>
+    where
+        F: FnMut(&T) -> B,
+        B: Ord,
+    {
+        self.binary_search_by(|element| f(element).cmp(key))
+    }
+
+    /// Returns the index of the first element for which `pred` returns `false`, assuming that
+    /// the vector is partitioned so that `pred` returns `true` for all elements before it.
+    ///
+    /// Only the `O(log n)` elements passed to `pred` are loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// // Timestamps of entries, in the order they were added.
+    /// let mut timestamps: Vector<u64> = Vector::new(b"v");
+    /// timestamps.extend([10, 20, 20, 30, 40]);
+    ///
+    /// // Number of entries before timestamp 25.
+    /// assert_eq!(timestamps.partition_point(|&t| t < 25), 3);
+    /// ```
+    pub fn partition_point<P>(&self, mut pred: P) -> u32
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.binary_search_by(
+            |element| if pred(element) { Ordering::Less } else { Ordering::Greater },
+        )
+        .unwrap_or_else(|i| i)
+    }
+}
+
+impl<T> Vector<T>
+where
+    T: BorshSerialize + BorshDeserialize + Ord,
+{
+    /// Binary searches this sorted vector for `element`. See
+    /// [`binary_search_by`](Self::binary_search_by) for the returned value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec: Vector<u32> = Vector::new(b"v");
+    /// vec.extend([1, 3, 5, 7]);
+    ///
+    /// assert_eq!(vec.binary_search(&7), Ok(3));
+    /// assert_eq!(vec.binary_search(&0), Err(0));
+    /// ```
+    pub fn binary_search(&self, element: &T) -> Result<u32, u32> {
+        self.binary_search_by(|probe| probe.cmp(element))
+    }
+
+    /// Inserts `element` into this sorted vector, keeping it sorted, and returns its index.
+    /// Equal elements are kept in the order they were inserted.
+    ///
+    /// The position is found with a binary search, but every element after it is moved one
+    /// index up, so inserting near the end of the vector is cheapest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new length of the vector overflows `u32`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use near_sdk::store::Vector;
+    ///
+    /// let mut vec: Vector<u32> = Vector::new(b"v");
+    /// for x in [5, 1, 4, 2] {
+    ///     vec.insert_sorted(x);
+    /// }
+    ///
+    /// assert_eq!(vec.insert_sorted(3), 2);
+    /// assert_eq!(vec.iter().copied().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+    /// ```
+    pub fn insert_sorted(&mut self, element: T) -> u32 {
+        let index = self.partition_point(|probe| probe <= &element);
+        self.push(element);
+        for i in (index..self.len - 1).rev() {
+            self.values.swap(i, i + 1);
+        }
+        self.write_through();
+        index
+    }
 }
 
 impl<T> fmt::Debug for Vector<T>
@@ -544,6 +875,29 @@ where
     }
 }
 
+#[cfg(feature = "unstable")]
+impl<T> crate::store::Transactional for Vector<T>
+where
+    T: BorshSerialize,
+{
+    type Snapshot = crate::store::Snapshot<u32>;
+
+    fn snapshot(&mut self) -> Self::Snapshot {
+        self.flush();
+        crate::store::Snapshot(self.len)
+    }
+
+    fn rollback(&mut self, snapshot: Self::Snapshot) {
+        // Elements past the restored length are either in the cache as modified entries, or were
+        // flushed since the snapshot and are overwritten or removed by later changes anyway.
+        self.values
+            .cache
+            .inner()
+            .retain(|_, v| !matches!(v.get(), Some(entry) if entry.is_modified()));
+        self.len = snapshot.0;
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod tests {
@@ -620,6 +974,59 @@ mod tests {
         assert_eq!(actual, baseline);
     }
 
+    #[test]
+    pub fn test_retain() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(4);
+        let mut vec = Vector::new(b"v".to_vec());
+        let mut baseline = vec![];
+        for _ in 0..500 {
+            let value = rng.gen::<u8>();
+            vec.push(value);
+            baseline.push(value);
+        }
+        vec.flush();
+
+        vec.retain(|v| v % 3 != 0);
+        baseline.retain(|v| v % 3 != 0);
+        let actual: Vec<_> = vec.iter().cloned().collect();
+        assert_eq!(actual, baseline);
+
+        // Removed slots past the new length are cleared from storage.
+        vec.retain(|_| false);
+        vec.flush();
+        assert!(vec.is_empty());
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
+    }
+
+    #[test]
+    pub fn test_sorted() {
+        let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(5);
+        let mut vec = Vector::new(b"v".to_vec());
+        let mut baseline: Vec<u8> = vec![];
+        for _ in 0..300 {
+            let value = rng.gen::<u8>();
+            let index = vec.insert_sorted(value);
+            let expected = baseline.partition_point(|&x| x <= value);
+            baseline.insert(expected, value);
+            assert_eq!(index as usize, expected);
+
+            let probe = rng.gen::<u8>();
+            match (vec.binary_search(&probe), baseline.binary_search(&probe)) {
+                (Ok(i), Ok(_)) => assert_eq!(baseline[i as usize], probe),
+                (actual, expected) => {
+                    assert_eq!(actual, expected.map(|i| i as u32).map_err(|i| i as u32))
+                }
+            }
+            assert_eq!(
+                vec.partition_point(|&x| x < probe) as usize,
+                baseline.partition_point(|&x| x < probe)
+            );
+        }
+        vec.flush();
+        let actual: Vec<_> = vec.iter().cloned().collect();
+        assert_eq!(actual, baseline);
+    }
+
     #[test]
     pub fn test_clear() {
         let mut rng = rand_xorshift::XorShiftRng::seed_from_u64(3);
@@ -760,6 +1167,8 @@ mod tests {
 
         // Drain rest
         assert!(Iterator::eq(vec.drain(..), baseline.drain(..)));
+        vec.flush();
+        crate::mock::with_mocked_blockchain(|m| assert!(m.take_storage().is_empty()));
 
         // Test double ended iterator functions
         let mut vec = Vector::new(b"v");
@@ -871,6 +1280,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bounded_cache() {
+        let mut vec = Vector::new(b"v");
+        vec.set_cache_capacity(Some(10));
+        vec.extend(0..100u32);
+        assert!(vec.values.cache.inner().len() <= 10);
+        for i in 0..100 {
+            vec[i] += 1;
+            assert!(vec.values.cache.inner().len() <= 10);
+        }
+        // Mutable iterators give out references into the cache, so they are only evicted by the
+        // next flush.
+        vec.iter_mut().for_each(|x| *x -= 1);
+        vec.flush();
+        assert!(vec.values.cache.inner().is_empty());
+
+        assert_eq!(vec.iter().sum::<u32>(), 4950);
+        vec.flush();
+        assert!(vec.values.cache.inner().is_empty());
+
+        vec[5] = 50;
+        vec.swap_remove(0);
+        vec.flush();
+        assert_eq!(vec.values.cache.inner().len(), 3);
+        assert_eq!(vec.cache_capacity(), Some(10));
+        let reloaded = Vector::<u32>::try_from_slice(&vec.try_to_vec().unwrap()).unwrap();
+        assert_eq!(reloaded.cache_capacity(), None);
+        assert_eq!(reloaded.iter().sum::<u32>(), 4950 + 45);
+        assert_eq!((reloaded[0], reloaded[5]), (99, 50));
+    }
+
+    #[test]
+    fn pop_n_writes_through_once() {
+        let mut vec = Vector::new(b"v");
+        vec.extend(0..10u8);
+        vec.flush();
+        vec.set_write_policy(crate::store::WritePolicy::WriteThrough);
+
+        assert_eq!(vec.pop_n(4), [9, 8, 7, 6]);
+        let reloaded = Vector::<u8>::try_from_slice(&vec.try_to_vec().unwrap()).unwrap();
+        assert_eq!(reloaded.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+        assert!(crate::env::storage_read(&[b'v', 6, 0, 0, 0]).is_none());
+
+        assert_eq!(vec.pop_n(u32::MAX), [5, 4, 3, 2, 1, 0]);
+        assert!(vec.is_empty());
+        assert!(vec.pop_n(1).is_empty());
+    }
+
     #[test]
     fn serialized_bytes() {
         use borsh::{BorshDeserialize, BorshSerialize};
@@ -889,4 +1346,12 @@ mod tests {
         let vec = Vector::<String>::deserialize(&mut serialized.as_slice()).unwrap();
         assert_eq!(vec[0], "Some data");
     }
+
+    #[test]
+    fn serde_serialize() {
+        let mut vec = Vector::new(b"v");
+        vec.extend(["a".to_string(), "b".to_string()]);
+        assert_eq!(serde_json::to_string(&vec).unwrap(), r#"["a","b"]"#);
+        assert_eq!(serde_json::to_string(&Vector::<u8>::new(b"e")).unwrap(), "[]");
+    }
 }
@@ -0,0 +1,352 @@
+//! Values that are upgraded from the versions they were stored with. See [`Versioned`].
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use borsh::maybestd::io;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Type of a value that can be upgraded from the previous version of the type, to be stored as a
+/// [`Versioned`] value.
+///
+/// Each version of a type is a separate type, which points to the version before it with
+/// [`FromVersion::Previous`]. The first version points to [`NoPrevious`] instead, which has no
+/// values. Versions are numbered from 1 for the first version, so that values of a version are
+/// stored tagged with [`FromVersion::VERSION`], and a new version can be added by renaming the
+/// latest type and adding a type that upgrades from it.
+///
+/// # Examples
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::store::{FromVersion, NoPrevious};
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct AccountV1 {
+///     balance: u64,
+/// }
+///
+/// impl FromVersion for AccountV1 {
+///     type Previous = NoPrevious;
+///
+///     fn from_previous(previous: NoPrevious) -> Self {
+///         match previous {}
+///     }
+/// }
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct Account {
+///     balance: u128,
+///     nonce: u64,
+/// }
+///
+/// impl FromVersion for Account {
+///     type Previous = AccountV1;
+///
+///     fn from_previous(previous: AccountV1) -> Self {
+///         Self { balance: previous.balance.into(), nonce: 0 }
+///     }
+/// }
+///
+/// assert_eq!(Account::VERSION, 2);
+/// ```
+pub trait FromVersion: BorshSerialize + BorshDeserialize {
+    /// Previous version of the type, or [`NoPrevious`] for the first version.
+    type Previous: FromVersion;
+
+    /// Version that values of the type are stored with, which follows the version of
+    /// [`FromVersion::Previous`].
+    const VERSION: u8 = <Self::Previous as FromVersion>::VERSION + 1;
+
+    /// Upgrades a value of the previous version.
+    fn from_previous(previous: Self::Previous) -> Self;
+}
+
+/// Previous version of the first version of a [`FromVersion`] type. It has no values, so
+/// [`FromVersion::from_previous`] of the first version can be implemented with `match previous {}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoPrevious {}
+
+impl BorshSerialize for NoPrevious {
+    fn serialize<W: io::Write>(&self, _writer: &mut W) -> Result<(), io::Error> {
+        match *self {}
+    }
+}
+
+impl BorshDeserialize for NoPrevious {
+    fn deserialize(_buf: &mut &[u8]) -> Result<Self, io::Error> {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown version of versioned value"))
+    }
+}
+
+impl FromVersion for NoPrevious {
+    type Previous = NoPrevious;
+
+    const VERSION: u8 = 0;
+
+    fn from_previous(previous: NoPrevious) -> Self {
+        previous
+    }
+}
+
+/// Deserializes a value stored with `version`, and upgrades it to `T` through the versions in
+/// between.
+fn deserialize_version<T: FromVersion>(version: u8, buf: &mut &[u8]) -> Result<T, io::Error> {
+    if version == T::VERSION {
+        T::deserialize(buf)
+    } else if version < T::VERSION {
+        deserialize_version::<T::Previous>(version, buf).map(T::from_previous)
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown version of versioned value"))
+    }
+}
+
+/// Value that is stored along with the version of its type, and upgraded to the latest version
+/// of the type when it is read. See [`FromVersion`] for how to define the versions of a type.
+///
+/// This lets a collection keep values of older versions, and upgrade each value when it is
+/// accessed, instead of migrating every value of the collection when the type changes. A value
+/// read from an older version is only stored in the latest version once it is written, for
+/// example through `get_mut` of a map, so contracts that want values to be upgraded in storage
+/// as they are read can check [`Versioned::upgraded_from`].
+///
+/// Values must be stored as `Versioned` from the first version of their type, since values
+/// stored without a version can't be told apart from versioned ones.
+///
+/// # Examples
+/// ```
+/// # use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::store::{FromVersion, LookupMap, NoPrevious, Versioned};
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct ProfileV1 {
+///     name: String,
+/// }
+/// # impl FromVersion for ProfileV1 {
+/// #     type Previous = NoPrevious;
+/// #     fn from_previous(previous: NoPrevious) -> Self {
+/// #         match previous {}
+/// #     }
+/// # }
+///
+/// #[derive(BorshSerialize, BorshDeserialize)]
+/// struct Profile {
+///     name: String,
+///     bio: Option<String>,
+/// }
+///
+/// impl FromVersion for Profile {
+///     type Previous = ProfileV1;
+///
+///     fn from_previous(previous: ProfileV1) -> Self {
+///         Self { name: previous.name, bio: None }
+///     }
+/// }
+///
+/// // Stored by an earlier version of the contract.
+/// let mut profiles: LookupMap<u32, Versioned<ProfileV1>> = LookupMap::new(b"p");
+/// profiles.insert(1, Versioned::new(ProfileV1 { name: "alice".to_string() }));
+/// profiles.flush();
+///
+/// let mut profiles: LookupMap<u32, Versioned<Profile>> = LookupMap::new(b"p");
+/// let profile = profiles.get_mut(&1).unwrap();
+/// assert_eq!(profile.upgraded_from(), Some(1));
+/// profile.bio = Some("...".to_string());
+/// ```
+pub struct Versioned<T> {
+    value: T,
+    upgraded_from: Option<u8>,
+}
+
+impl<T> Versioned<T>
+where
+    T: FromVersion,
+{
+    /// Wraps `value`, to be stored with the version of `T`.
+    pub fn new(value: T) -> Self {
+        Self { value, upgraded_from: None }
+    }
+
+    /// Returns the version that the value was read with, if it was upgraded from an older
+    /// version when it was read.
+    pub fn upgraded_from(&self) -> Option<u8> {
+        self.upgraded_from
+    }
+
+    /// Returns the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for Versioned<T>
+where
+    T: FromVersion,
+{
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Deref for Versioned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Versioned<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T> Clone for Versioned<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self { value: self.value.clone(), upgraded_from: self.upgraded_from }
+    }
+}
+
+impl<T> PartialEq for Versioned<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Versioned<T> where T: Eq {}
+
+impl<T> fmt::Debug for Versioned<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Versioned")
+            .field("value", &self.value)
+            .field("upgraded_from", &self.upgraded_from)
+            .finish()
+    }
+}
+
+impl<T> BorshSerialize for Versioned<T>
+where
+    T: FromVersion,
+{
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        BorshSerialize::serialize(&T::VERSION, writer)?;
+        BorshSerialize::serialize(&self.value, writer)
+    }
+}
+
+impl<T> BorshDeserialize for Versioned<T>
+where
+    T: FromVersion,
+{
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, io::Error> {
+        let version: u8 = BorshDeserialize::deserialize(buf)?;
+        let value = deserialize_version(version, buf)?;
+        let upgraded_from = if version == T::VERSION { None } else { Some(version) };
+        Ok(Self { value, upgraded_from })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::LookupMap;
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct V1 {
+        a: u8,
+    }
+
+    impl FromVersion for V1 {
+        type Previous = NoPrevious;
+
+        fn from_previous(previous: NoPrevious) -> Self {
+            match previous {}
+        }
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct V2 {
+        a: u16,
+    }
+
+    impl FromVersion for V2 {
+        type Previous = V1;
+
+        fn from_previous(previous: V1) -> Self {
+            Self { a: previous.a.into() }
+        }
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct V3 {
+        a: u16,
+        b: String,
+    }
+
+    impl FromVersion for V3 {
+        type Previous = V2;
+
+        fn from_previous(previous: V2) -> Self {
+            Self { a: previous.a * 10, b: "v2".to_string() }
+        }
+    }
+
+    #[test]
+    fn upgrades_through_versions() {
+        assert_eq!((V1::VERSION, V2::VERSION, V3::VERSION), (1, 2, 3));
+
+        let v1 = Versioned::new(V1 { a: 7 }).try_to_vec().unwrap();
+        assert_eq!(v1, [1, 7]);
+        let v3 = Versioned::<V3>::try_from_slice(&v1).unwrap();
+        assert_eq!(v3.upgraded_from(), Some(1));
+        assert_eq!(*v3, V3 { a: 70, b: "v2".to_string() });
+
+        // Upgraded values are written with the latest version.
+        let written = v3.try_to_vec().unwrap();
+        assert_eq!(written[0], 3);
+        let read = Versioned::<V3>::try_from_slice(&written).unwrap();
+        assert_eq!(read.upgraded_from(), None);
+        assert_eq!(read, v3);
+
+        let v2 = Versioned::new(V2 { a: 300 }).try_to_vec().unwrap();
+        assert_eq!(Versioned::<V3>::try_from_slice(&v2).unwrap().a, 3000);
+    }
+
+    #[test]
+    fn unknown_versions() {
+        // Values of newer versions, and of version 0, can't be read.
+        let v3 = Versioned::new(V3 { a: 1, b: String::new() }).try_to_vec().unwrap();
+        assert!(Versioned::<V2>::try_from_slice(&v3).is_err());
+        assert!(Versioned::<V3>::try_from_slice(&[0, 1]).is_err());
+        assert!(Versioned::<V1>::try_from_slice(&[]).is_err());
+    }
+
+    #[test]
+    fn upgrades_entries_when_written() {
+        let mut map: LookupMap<u8, Versioned<V1>> = LookupMap::new(b"m");
+        map.extend((0..4).map(|i| (i, V1 { a: i }.into())));
+        map.flush();
+        drop(map);
+
+        let mut map: LookupMap<u8, Versioned<V2>> = LookupMap::new(b"m");
+        assert_eq!(map[&0].upgraded_from(), Some(1));
+        map.get_mut(&1).unwrap().a += 100;
+        map.insert(2, V2 { a: 2 }.into());
+        map.flush();
+
+        let stored_version = |k: u8| crate::env::storage_read(&[b'm', k]).unwrap()[0];
+        assert_eq!((0..4).map(stored_version).collect::<Vec<_>>(), [1, 2, 2, 1]);
+        let map: LookupMap<u8, Versioned<V2>> = LookupMap::new(b"m");
+        assert_eq!((0..4).map(|k| map[&k].a).collect::<Vec<_>>(), [0, 101, 2, 3]);
+    }
+}
@@ -0,0 +1,50 @@
+/// When a collection writes its changes to storage, chosen with `with_write_policy` when the
+/// collection is created or changed with `set_write_policy`, on [`Vector`](super::Vector),
+/// [`LookupMap`](super::LookupMap), [`UnorderedMap`](super::UnorderedMap) and
+/// [`UnorderedSet`](super::UnorderedSet).
+///
+/// The policy only decides when a contract call writes its changes, not whether they are kept:
+/// every storage write of a call, and every promise it creates, is discarded if the call panics.
+///
+/// The policy is not part of the serialized state of the collection, so collections loaded from
+/// the contract state always start with [`WriteBack`](WritePolicy::WriteBack), and a contract that
+/// needs write-through for a field of its state has to set it again in each method. Metadata
+/// kept in the collection itself, like the length of a [`Vector`](super::Vector), is part of the
+/// contract state and written with it under either policy.
+///
+/// # Examples
+/// ```
+/// use near_sdk::{env, store::{Vector, WritePolicy}};
+///
+/// let mut vec: Vector<u32> = Vector::new(b"v");
+/// vec.set_write_policy(WritePolicy::WriteThrough);
+///
+/// vec.push(1);
+/// assert!(env::storage_has_key(&[b"v".as_slice(), &0u32.to_le_bytes()].concat()));
+///
+/// // Changes through references are written by the next change.
+/// vec[0] = 2;
+/// vec.push(3);
+/// vec.set_write_policy(WritePolicy::WriteBack);
+/// vec.push(4);
+/// assert!(!env::storage_has_key(&[b"v".as_slice(), &2u32.to_le_bytes()].concat()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WritePolicy {
+    /// Changes are kept in the cache of the collection, and written to storage when the
+    /// collection is flushed or dropped. Each storage slot is written at most once however many
+    /// times it is changed.
+    WriteBack,
+    /// Changes are written to storage by the method that makes them, which only writes the
+    /// entries it changed. Changes made through references, like those returned by `get_mut`,
+    /// are written by the next change, flush or drop of the collection.
+    WriteThrough,
+}
+
+// `#[default]` on enum variants needs Rust 1.62, which is above the minimum supported Rust version.
+#[allow(clippy::derivable_impls)]
+impl Default for WritePolicy {
+    fn default() -> Self {
+        WritePolicy::WriteBack
+    }
+}
@@ -170,6 +170,11 @@ impl VMContextBuilder {
         self
     }
 
+    pub fn input(&mut self, input: Vec<u8>) -> &mut Self {
+        self.context.input = input;
+        self
+    }
+
     pub fn is_view(&mut self, is_view: bool) -> &mut Self {
         self.context.view_config =
             if is_view { Some(ViewConfig { max_gas_burnt: 200000000000000 }) } else { None };
@@ -68,7 +68,7 @@ macro_rules! testing_env {
             $config,
             $fee_config,
             $promise_results,
-            $crate::mock::with_mocked_blockchain(|b| b.take_storage()),
+            $crate::mock::take_storage().unwrap_or_default(),
             $validators,
             None,
         ))
@@ -0,0 +1,334 @@
+//! Typed durations and calendar dates for the nanosecond timestamps of the blockchain.
+//!
+//! Timestamps, like [`env::block_timestamp`], are plain `u64` counts of nanoseconds, which makes
+//! it easy to mix them up with milliseconds or seconds. [`Duration`] keeps track of the unit, so
+//! lock-up periods and deadlines can be written as `Duration::from_days(30)` instead of a
+//! nanosecond constant.
+//!
+//! ```
+//! use near_sdk::env;
+//! use near_sdk::time::Duration;
+//!
+//! let unlock_at = Duration::from_days(30).after(env::block_timestamp());
+//! assert_eq!(Duration::between(env::block_timestamp(), unlock_at).as_secs(), 30 * 24 * 60 * 60);
+//! ```
+//!
+//! [`env::block_timestamp`]: crate::env::block_timestamp
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use core::{fmt, ops};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Timestamp;
+
+const NANOS_PER_MILLI: u64 = 1_000_000;
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A span of time, stored in nanoseconds like the timestamps of the blockchain.
+///
+/// It is serialized to JSON as a string of nanoseconds, the same as
+/// [`U64`](crate::json_types::U64), and to Borsh as a `u64`.
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    BorshSchema,
+)]
+#[repr(transparent)]
+pub struct Duration(u64);
+
+impl Duration {
+    /// No time.
+    pub const ZERO: Duration = Duration(0);
+
+    /// Duration of `nanos` nanoseconds.
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+    /// Duration of `millis` milliseconds. Panics on overflow.
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(millis * NANOS_PER_MILLI)
+    }
+
+    /// Duration of `secs` seconds. Panics on overflow.
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(secs * NANOS_PER_SEC)
+    }
+
+    /// Duration of `mins` minutes. Panics on overflow.
+    pub const fn from_mins(mins: u64) -> Self {
+        Self::from_secs(mins * 60)
+    }
+
+    /// Duration of `hours` hours. Panics on overflow.
+    pub const fn from_hours(hours: u64) -> Self {
+        Self::from_secs(hours * 60 * 60)
+    }
+
+    /// Duration of `days` days of 24 hours. Panics on overflow.
+    pub const fn from_days(days: u64) -> Self {
+        Self::from_secs(days * SECS_PER_DAY)
+    }
+
+    /// Time between the timestamps, or zero if `later` is before `earlier`.
+    pub const fn between(earlier: Timestamp, later: Timestamp) -> Self {
+        Self(later.saturating_sub(earlier))
+    }
+
+    /// The duration in nanoseconds.
+    pub const fn as_nanos(&self) -> u64 {
+        self.0
+    }
+
+    /// The duration in whole milliseconds, rounded down.
+    pub const fn as_millis(&self) -> u64 {
+        self.0 / NANOS_PER_MILLI
+    }
+
+    /// The duration in whole seconds, rounded down.
+    pub const fn as_secs(&self) -> u64 {
+        self.0 / NANOS_PER_SEC
+    }
+
+    /// The timestamp this duration after `timestamp`. Panics on overflow.
+    pub const fn after(&self, timestamp: Timestamp) -> Timestamp {
+        timestamp + self.0
+    }
+
+    /// The timestamp this duration before `timestamp`, or zero if it would be negative.
+    pub const fn before(&self, timestamp: Timestamp) -> Timestamp {
+        timestamp.saturating_sub(self.0)
+    }
+
+    /// Adds the durations, returning `None` on overflow.
+    pub const fn checked_add(self, other: Self) -> Option<Self> {
+        match self.0.checked_add(other.0) {
+            Some(nanos) => Some(Self(nanos)),
+            None => None,
+        }
+    }
+
+    /// Subtracts the durations, returning `None` if `other` is longer.
+    pub const fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.0.checked_sub(other.0) {
+            Some(nanos) => Some(Self(nanos)),
+            None => None,
+        }
+    }
+
+    /// Subtracts the durations, saturating at zero.
+    pub const fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse::<u64>().map(Self).map_err(|err| de::Error::custom(err.to_string()))
+    }
+}
+
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for Duration {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    /// Panics if the duration does not fit in `u64` nanoseconds, which is about 584 years.
+    fn from(duration: std::time::Duration) -> Self {
+        match u64::try_from(duration.as_nanos()) {
+            Ok(nanos) => Self(nanos),
+            Err(_) => crate::env::panic_str("Duration is too long"),
+        }
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(duration: Duration) -> Self {
+        std::time::Duration::from_nanos(duration.0)
+    }
+}
+
+impl ops::Add for Duration {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl ops::AddAssign for Duration {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+    }
+}
+
+impl ops::Sub for Duration {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl ops::SubAssign for Duration {
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0;
+    }
+}
+
+impl ops::Mul<u64> for Duration {
+    type Output = Self;
+
+    fn mul(self, other: u64) -> Self {
+        Self(self.0 * other)
+    }
+}
+
+impl ops::Div<u64> for Duration {
+    type Output = Self;
+
+    fn div(self, other: u64) -> Self {
+        Self(self.0 / other)
+    }
+}
+
+/// A date and time in UTC, in the proleptic Gregorian calendar, like [`env::block_datetime`].
+///
+/// Dates are ordered chronologically, and displayed in the ISO 8601 format of JavaScript's
+/// `Date.toISOString`, e.g. `2022-03-01T12:30:00.000Z`.
+///
+/// [`env::block_datetime`]: crate::env::block_datetime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    /// The year, e.g. `2022`.
+    pub year: u32,
+    /// The month, from 1 to 12.
+    pub month: u8,
+    /// The day of the month, from 1 to 31.
+    pub day: u8,
+    /// The hour, from 0 to 23.
+    pub hour: u8,
+    /// The minute, from 0 to 59.
+    pub minute: u8,
+    /// The second, from 0 to 59. There are no leap seconds in timestamps.
+    pub second: u8,
+    /// The fraction of the second in nanoseconds, from 0 to 999_999_999.
+    pub nanosecond: u32,
+}
+
+impl DateTime {
+    /// The date and time of the timestamp, in nanoseconds since January 1, 1970 0:00:00 UTC.
+    pub const fn from_timestamp(timestamp: Timestamp) -> Self {
+        let secs = timestamp / NANOS_PER_SEC;
+        let secs_of_day = secs % SECS_PER_DAY;
+
+        // Converts the days since the epoch to a civil date, with years starting in March so that
+        // leap days are at the end of the year. From http://howardhinnant.github.io/date_algorithms.html
+        let days = secs / SECS_PER_DAY + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+        let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year: year as u32,
+            month: month as u8,
+            day: day as u8,
+            hour: (secs_of_day / 3600) as u8,
+            minute: (secs_of_day / 60 % 60) as u8,
+            second: (secs_of_day % 60) as u8,
+            nanosecond: (timestamp % NANOS_PER_SEC) as u32,
+        }
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            self.nanosecond / NANOS_PER_MILLI as u32
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_units() {
+        assert_eq!(Duration::from_millis(1500).as_nanos(), 1_500_000_000);
+        assert_eq!(Duration::from_millis(1500).as_secs(), 1);
+        assert_eq!(Duration::from_days(1), Duration::from_hours(24));
+        assert_eq!(Duration::from_mins(2).as_millis(), 120_000);
+        assert_eq!(Duration::between(10, 4), Duration::ZERO);
+        assert_eq!(Duration::from_nanos(5).after(10), 15);
+        assert_eq!(Duration::from_nanos(15).before(10), 0);
+        assert_eq!(Duration::from(std::time::Duration::from_secs(3)), Duration::from_secs(3));
+
+        let ser = serde_json::to_string(&Duration::from_secs(1)).unwrap();
+        assert_eq!(ser, "\"1000000000\"");
+        assert_eq!(serde_json::from_str::<Duration>(&ser).unwrap(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn datetime() {
+        assert_eq!(DateTime::from_timestamp(0).to_string(), "1970-01-01T00:00:00.000Z");
+        // 2020-02-29T23:59:59.123456789Z, a leap day.
+        let leap_day = DateTime::from_timestamp(1_583_020_799_123_456_789);
+        assert_eq!(leap_day.to_string(), "2020-02-29T23:59:59.123Z");
+        assert_eq!(leap_day.nanosecond, 123_456_789);
+        assert_eq!(
+            DateTime::from_timestamp(1_583_020_800_000_000_000).to_string(),
+            "2020-03-01T00:00:00.000Z"
+        );
+        assert_eq!(DateTime::from_timestamp(u64::MAX).to_string(), "2554-07-21T23:34:33.709Z");
+        assert!(DateTime::from_timestamp(1) > DateTime::from_timestamp(0));
+    }
+}
@@ -0,0 +1,50 @@
+//! Points of the alt_bn128 (BN254) elliptic curve used by the `env::alt_bn128_*` functions.
+//!
+//! All field elements are 256-bit integers encoded in little-endian byte order.
+
+/// Element of the quadratic extension field `Fq2`, which the coordinates of [`G2Point`]s
+/// belong to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fq2 {
+    pub re: [u8; 32],
+    pub im: [u8; 32],
+}
+
+/// Point `(x, y)` on the alt_bn128 curve `y^2 = x^3 + 3` over `Fq`. The point at infinity is
+/// encoded as `(0, 0)`, which is also the [`Default`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct G1Point {
+    pub x: [u8; 32],
+    pub y: [u8; 32],
+}
+
+/// Point `(x, y)` of the `Fr`-ordered subgroup of the alt_bn128 twist `y^2 = x^3 + 3/(i+9)` over
+/// `Fq2`. The point at infinity is encoded as `(0, 0)`, which is also the [`Default`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct G2Point {
+    pub x: Fq2,
+    pub y: Fq2,
+}
+
+impl G1Point {
+    pub(crate) fn from_bytes(bytes: [u8; 64]) -> Self {
+        let mut point = Self::default();
+        point.x.copy_from_slice(&bytes[..32]);
+        point.y.copy_from_slice(&bytes[32..]);
+        point
+    }
+
+    pub(crate) fn write_to(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.x);
+        buf.extend_from_slice(&self.y);
+    }
+}
+
+impl G2Point {
+    pub(crate) fn write_to(&self, buf: &mut Vec<u8>) {
+        for coordinate in [&self.x, &self.y] {
+            buf.extend_from_slice(&coordinate.re);
+            buf.extend_from_slice(&coordinate.im);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Points and field elements of the BLS12-381 elliptic curve used by the `env::bls12381_*`
+//! functions.
+//!
+//! Field elements are 381-bit integers encoded in big-endian byte order, and points are
+//! serialized in the ZCash format: the three most significant bits of the first byte are the
+//! compression, infinity and sign flags. Scalars are 256-bit integers in little-endian byte order.
+
+/// Element of the base field `Fp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fp(pub [u8; 48]);
+
+/// Element `c0 + c1 * u` of the quadratic extension field `Fp2`, which the coordinates of
+/// [`G2Point`]s belong to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fp2 {
+    pub c0: Fp,
+    pub c1: Fp,
+}
+
+/// Uncompressed point `(x, y)` on the curve `y^2 = x^3 + 4` over `Fp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct G1Point(pub [u8; 96]);
+
+/// Uncompressed point `(x, y)` on the twist `y^2 = x^3 + 4(u + 1)` over `Fp2`. Each coordinate
+/// is encoded as `c1` followed by `c0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct G2Point(pub [u8; 192]);
+
+/// Compressed form of a [`G1Point`], which only stores `x` and the sign of `y`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressedG1Point(pub [u8; 48]);
+
+/// Compressed form of a [`G2Point`], which only stores `x` and the sign of `y`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressedG2Point(pub [u8; 96]);
+
+const fn infinity<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    bytes[0] = 0x40;
+    bytes
+}
+
+impl G1Point {
+    /// The point at infinity, which is the identity of the group.
+    pub const INFINITY: Self = Self(infinity());
+
+    pub(crate) fn from_slice(bytes: &[u8]) -> Self {
+        Self(bytes.try_into().unwrap())
+    }
+}
+
+impl G2Point {
+    /// The point at infinity, which is the identity of the group.
+    pub const INFINITY: Self = Self(infinity());
+
+    pub(crate) fn from_slice(bytes: &[u8]) -> Self {
+        Self(bytes.try_into().unwrap())
+    }
+}
+
+impl Fp2 {
+    pub(crate) fn write_to(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.c1.0);
+        buf.extend_from_slice(&self.c0.0);
+    }
+}
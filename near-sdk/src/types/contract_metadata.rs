@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Source metadata of a contract, as specified by
+/// [NEP-330](https://github.com/near/NEPs/blob/master/neps/nep-0330.md), which is returned by its
+/// `contract_source_metadata` view method.
+///
+/// `#[near_bindgen(contract_metadata(..))]` on the contract struct generates the method. The
+/// version and the link default to the `version` and the `repository` of the package of the
+/// contract, and the standards always include NEP-330 itself.
+///
+/// ```
+/// use near_sdk::{ContractSourceMetadata, Standard};
+///
+/// let metadata = ContractSourceMetadata {
+///     version: Some("1.2.0".to_string()),
+///     link: Some("https://github.com/near/near-sdk-rs".to_string()),
+///     standards: vec![Standard { standard: "nep330".to_string(), version: "1.1.0".to_string() }],
+/// };
+/// assert_eq!(
+///     near_sdk::serde_json::to_string(&metadata).unwrap(),
+///     r#"{"version":"1.2.0","link":"https://github.com/near/near-sdk-rs","standards":[{"standard":"nep330","version":"1.1.0"}]}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub struct ContractSourceMetadata {
+    /// Version of the source code, such as a release or a commit hash.
+    pub version: Option<String>,
+    /// Link to the source code, such as its repository.
+    pub link: Option<String>,
+    /// Standards implemented by the contract.
+    pub standards: Vec<Standard>,
+}
+
+/// Standard implemented by a contract, such as `nep141` in version `1.0.0`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub struct Standard {
+    /// Name of the standard, such as `nep141`.
+    pub standard: String,
+    /// Version of the standard, such as `1.0.0`.
+    pub version: String,
+}
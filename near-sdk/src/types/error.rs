@@ -22,6 +22,17 @@
 /// ```
 pub trait FunctionError {
     fn panic(&self) -> !;
+
+    /// Errors listed in the ABI of `#[handle_result]` methods returning this type, which are
+    /// given by `#[derive(ContractError)]`.
+    #[cfg(feature = "abi")]
+    #[doc(hidden)]
+    fn __abi_errors(_gen: &mut schemars::gen::SchemaGenerator) -> crate::__private::AbiErrors
+    where
+        Self: Sized,
+    {
+        Default::default()
+    }
 }
 
 impl<T> FunctionError for T
@@ -33,6 +44,98 @@ where
     }
 }
 
+/// An error with a numeric code, which terminates the execution with [`env::panic_err`], so that
+/// indexers and frontends can classify failures by code instead of matching on messages.
+///
+/// `#[derive(ContractError)]` implements this trait from `#[error_code(..)]` attributes on the
+/// struct or on each variant of the enum, and implements [`FunctionError`] to panic with the code,
+/// so that the error can be returned from `#[handle_result]` methods. The message of the error is
+/// its `Display` implementation, and its name is the name of the struct or of the variant. With
+/// `#[error_data]` on the type, which must then implement `serde::Serialize`, the error itself is
+/// serialized to JSON as its data.
+///
+/// The execution is terminated with the code followed by the name, the message and the data of the
+/// error in JSON, like `E2: {"name":"InsufficientBalance","message":"missing 5 yoctoNEAR"}`, which
+/// clients can parse. With the `abi` feature, the codes and names of the errors are listed in the
+/// ABI of `#[handle_result]` methods, along with the schema of their data, for which the type must
+/// also implement `schemars::JsonSchema`.
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::{near_bindgen, ContractError};
+/// use std::fmt;
+///
+/// #[derive(ContractError)]
+/// enum Error {
+///     #[error_code(1)]
+///     NotFound,
+///     #[error_code(2)]
+///     InsufficientBalance { missing: u128 },
+/// }
+///
+/// impl fmt::Display for Error {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         match self {
+///             Error::NotFound => write!(f, "not found"),
+///             Error::InsufficientBalance { missing } => write!(f, "missing {} yoctoNEAR", missing),
+///         }
+///     }
+/// }
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Contract;
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     // Panics with `E1: {"name":"NotFound","message":"not found"}`.
+///     #[handle_result]
+///     pub fn get(&self) -> Result<String, Error> {
+///         Err(Error::NotFound)
+///     }
+/// }
+/// ```
+///
+/// [`env::panic_err`]: crate::env::panic_err
+pub trait ContractError: std::fmt::Display {
+    /// Code of the error, which should not change between versions of the contract.
+    fn error_code(&self) -> u32;
+
+    /// Name of the error, which defaults to the name of the type.
+    fn error_name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Data of the error serialized to JSON, if it has any.
+    fn error_data(&self) -> Option<String> {
+        None
+    }
+
+    /// Terminates the execution with the code of the error, followed by its name, message and
+    /// data in JSON.
+    fn panic_err(&self) -> ! {
+        crate::env::panic_err(self.error_code(), &error_json(self))
+    }
+}
+
+/// Name, message and data of the error in JSON.
+fn error_json<E: ContractError + ?Sized>(error: &E) -> String {
+    // Strings always serialize to JSON.
+    let json_str = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let mut json = format!(
+        "{{\"name\":{},\"message\":{}",
+        json_str(error.error_name()),
+        json_str(&error.to_string())
+    );
+    if let Some(data) = error.error_data() {
+        json.push_str(&format!(",\"data\":{}", data));
+    }
+    json.push('}');
+    json
+}
+
 /// A simple type used in conjunction with [FunctionError] representing that the function should
 /// abort without a custom message.
 ///
@@ -64,3 +167,98 @@ impl FunctionError for Abort {
         crate::env::abort()
     }
 }
+
+/// Reason the input to the contract call could not be deserialized by [`env::input_json`] or
+/// [`env::input_borsh`]. Its message matches the panic message of the code generated for
+/// `#[near_bindgen]` methods.
+///
+/// [`env::input_json`]: crate::env::input_json
+/// [`env::input_borsh`]: crate::env::input_borsh
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseInputError {
+    /// The contract was called without input.
+    Missing,
+    /// The input is not valid JSON for the type, with the debug output of the JSON error.
+    Json(String),
+    /// The input is not valid Borsh for the type, with the debug output of the Borsh error.
+    Borsh(String),
+}
+
+impl std::fmt::Display for ParseInputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing => write!(f, "Expected input since method has arguments."),
+            Self::Json(err) => write!(f, "Failed to deserialize input from JSON.: {}", err),
+            Self::Borsh(err) => write!(f, "Failed to deserialize input from Borsh.: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParseInputError {}
+
+/// Error returned by the `try_` variants of [`env`](crate::env) functions, which panic or abort
+/// with the same errors otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EnvError {
+    /// There is no promise result with the given index.
+    PromiseIndexOutOfBounds { index: u64, count: u64 },
+    /// A value could not be serialized, with the message of the serialization error.
+    Serialization(String),
+    /// A value could not be deserialized, with the message of the deserialization error.
+    Deserialization(String),
+}
+
+impl std::fmt::Display for EnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PromiseIndexOutOfBounds { index, count } => {
+                write!(f, "promise result index {} is out of bounds of {} results", index, count)
+            }
+            Self::Serialization(err) => write!(f, "cannot serialize the value: {}", err),
+            Self::Deserialization(err) => write!(f, "cannot deserialize the value: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::ContractError;
+    use std::fmt;
+
+    struct InsufficientBalance {
+        missing: u128,
+    }
+
+    impl fmt::Display for InsufficientBalance {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "missing \"{}\" yoctoNEAR", self.missing)
+        }
+    }
+
+    impl ContractError for InsufficientBalance {
+        fn error_code(&self) -> u32 {
+            2
+        }
+
+        fn error_data(&self) -> Option<String> {
+            Some(format!("{{\"missing\":\"{}\"}}", self.missing))
+        }
+    }
+
+    #[test]
+    fn default_error_name() {
+        assert_eq!(InsufficientBalance { missing: 5 }.error_name(), "InsufficientBalance");
+    }
+
+    #[test]
+    fn error_json() {
+        assert_eq!(
+            super::error_json(&InsufficientBalance { missing: 5 }),
+            r#"{"name":"InsufficientBalance","message":"missing \"5\" yoctoNEAR","data":{"missing":"5"}}"#
+        );
+    }
+}
@@ -0,0 +1,58 @@
+/// An event logged in the [NEP-297](https://github.com/near/NEPs/blob/master/neps/nep-0297.md)
+/// format, `EVENT_JSON:{"standard":..,"version":..,"event":..,"data":[..]}`, which indexers pick
+/// up from the logs of the contract.
+///
+/// `#[derive(NearEvent)]` implements this trait for an enum with one variant per event. The
+/// standard and the version are given with `#[event(standard = "..", version = "..")]` on the
+/// enum, and the name of each event is the name of its variant in snake case, unless given with
+/// `#[event(name = "..")]` on the variant. The data of a variant with named fields is an object of
+/// its fields serialized to JSON, leaving out the fields serialized to `null` such as `None`, and
+/// the data of a variant with a single unnamed field is that field serialized to JSON. Unit
+/// variants have no data.
+///
+/// ```
+/// use near_sdk::json_types::U128;
+/// use near_sdk::{test_utils, AccountId, NearEvent};
+///
+/// #[derive(NearEvent)]
+/// #[event(standard = "nep141", version = "1.0.0")]
+/// enum FtEvent {
+///     FtMint { owner_id: AccountId, amount: U128, memo: Option<String> },
+///     #[event(name = "ft_burn")]
+///     Burn { owner_id: AccountId, amount: U128, memo: Option<String> },
+/// }
+///
+/// let owner_id: AccountId = "alice.near".parse().unwrap();
+/// FtEvent::FtMint { owner_id, amount: U128(100), memo: None }.emit();
+/// assert_eq!(
+///     test_utils::get_logs(),
+///     [r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_mint","data":[{"owner_id":"alice.near","amount":"100"}]}"#]
+/// );
+/// ```
+pub trait NearEvent: Sized {
+    /// Log of several events of the same kind, with the data of each of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` is empty or holds events of different kinds.
+    fn to_event_log_many(events: &[Self]) -> String;
+
+    /// Log of the event.
+    fn to_event_log(&self) -> String {
+        Self::to_event_log_many(core::slice::from_ref(self))
+    }
+
+    /// Logs the event.
+    fn emit(&self) {
+        crate::env::log_str(&self.to_event_log())
+    }
+
+    /// Logs several events of the same kind as a single event, with the data of each of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` is empty or holds events of different kinds.
+    fn emit_many(events: &[Self]) {
+        crate::env::log_str(&Self::to_event_log_many(events))
+    }
+}
@@ -0,0 +1,90 @@
+use crate::json_types::Base64VecU8;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+/// Call of a method of the contract within a batch, given to the `execute_batch` method generated
+/// by `#[near_bindgen(multicall)]`, with its arguments serialized as the method expects them.
+///
+/// ```
+/// use near_sdk::json_types::Base64VecU8;
+/// use near_sdk::MethodCall;
+///
+/// let call = MethodCall {
+///     method_name: "set_fee".to_string(),
+///     args: Base64VecU8(br#"{"fee":5}"#.to_vec()),
+/// };
+/// assert_eq!(
+///     near_sdk::serde_json::to_string(&call).unwrap(),
+///     r#"{"method_name":"set_fee","args":"eyJmZWUiOjV9"}"#
+/// );
+/// ```
+///
+/// A failing call aborts the whole batch, including a `#[handle_result]` method returning an error:
+///
+/// ```should_panic
+/// use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+/// use near_sdk::json_types::Base64VecU8;
+/// use near_sdk::{near_bindgen, MethodCall};
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Counter {
+///     value: u32,
+/// }
+///
+/// #[near_bindgen(multicall)]
+/// impl Counter {
+///     pub fn increment(&mut self) {
+///         self.value += 1;
+///     }
+///
+///     #[handle_result]
+///     pub fn check(&mut self) -> Result<(), String> {
+///         if self.value > 1 {
+///             return Err("Counter is too large".to_string());
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let call = |name: &str| MethodCall { method_name: name.to_string(), args: Base64VecU8(vec![]) };
+/// Counter::default().execute_batch(vec![call("increment"), call("increment"), call("check")]);
+/// ```
+///
+/// The deposit attached to the batch is checked by each of its calls, so that it aborts the batch
+/// if one of the methods does not accept a deposit:
+///
+/// ```should_panic
+/// use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+/// use near_sdk::json_types::Base64VecU8;
+/// use near_sdk::test_utils::VMContextBuilder;
+/// use near_sdk::{near_bindgen, testing_env, MethodCall};
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Counter {
+///     value: u32,
+/// }
+///
+/// #[near_bindgen(multicall)]
+/// impl Counter {
+///     #[payable]
+///     pub fn donate(&mut self) {}
+///
+///     pub fn increment(&mut self) {
+///         self.value += 1;
+///     }
+/// }
+///
+/// testing_env!(VMContextBuilder::new().attached_deposit(1).build());
+/// let call = |name: &str| MethodCall { method_name: name.to_string(), args: Base64VecU8(vec![]) };
+/// Counter::default().execute_batch(vec![call("donate"), call("increment")]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+pub struct MethodCall {
+    /// Name of the method.
+    pub method_name: String,
+    /// Arguments of the method, in JSON or in Borsh depending on its serializer.
+    pub args: Base64VecU8,
+}
@@ -10,12 +10,33 @@ pub use self::primitives::*;
 mod account_id;
 pub use self::account_id::{AccountId, ParseAccountIdError};
 
+mod signed_payload;
+pub use self::signed_payload::SignedPayload;
+
 mod gas;
 pub use self::gas::Gas;
 
+mod near_token;
+pub use self::near_token::NearToken;
+
+pub mod alt_bn128;
+pub mod bls12_381;
+
 mod error;
 pub use self::error::Abort;
+pub use self::error::ContractError;
+pub use self::error::EnvError;
 pub use self::error::FunctionError;
+pub use self::error::ParseInputError;
+
+mod event;
+pub use self::event::NearEvent;
+
+mod contract_metadata;
+pub use self::contract_metadata::{ContractSourceMetadata, Standard};
+
+mod method_call;
+pub use self::method_call::MethodCall;
 
 /// Raw type for duration in nanoseconds
 pub type Duration = u64;
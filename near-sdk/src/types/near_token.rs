@@ -0,0 +1,222 @@
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use core::{fmt, ops};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::Balance;
+
+/// An amount of NEAR tokens, stored in yoctoNEAR (10^-24 NEAR).
+///
+/// Unlike [`Balance`], which is a plain `u128`, amounts of tokens cannot be confused with other
+/// integers, like amounts of gas or of fungible tokens. It is serialized to JSON as a string of
+/// yoctoNEAR, the same as [`U128`](crate::json_types::U128), and to Borsh as a `u128`.
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    BorshSchema,
+)]
+#[repr(transparent)]
+pub struct NearToken(pub Balance);
+
+impl NearToken {
+    /// No tokens.
+    pub const ZERO: NearToken = NearToken(0);
+    /// One yoctoNEAR, the smallest amount of tokens.
+    pub const ONE_YOCTO: NearToken = NearToken(1);
+    /// One NEAR, which is 10^24 yoctoNEAR.
+    pub const ONE_NEAR: NearToken = NearToken(10u128.pow(24));
+
+    /// Amount of `yoctonear` yoctoNEAR.
+    pub const fn from_yoctonear(yoctonear: Balance) -> Self {
+        Self(yoctonear)
+    }
+
+    /// Amount of `near` NEAR. Panics on overflow.
+    pub const fn from_near(near: u128) -> Self {
+        Self(near * Self::ONE_NEAR.0)
+    }
+
+    /// The amount in yoctoNEAR.
+    pub const fn as_yoctonear(&self) -> Balance {
+        self.0
+    }
+
+    /// The amount in whole NEAR, rounded down.
+    pub const fn as_near(&self) -> u128 {
+        self.0 / Self::ONE_NEAR.0
+    }
+
+    /// Returns `true` if the amount is zero.
+    pub const fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Adds the amounts, returning `None` on overflow.
+    pub const fn checked_add(self, other: Self) -> Option<Self> {
+        match self.0.checked_add(other.0) {
+            Some(amount) => Some(Self(amount)),
+            None => None,
+        }
+    }
+
+    /// Subtracts the amounts, returning `None` if `other` is larger.
+    pub const fn checked_sub(self, other: Self) -> Option<Self> {
+        match self.0.checked_sub(other.0) {
+            Some(amount) => Some(Self(amount)),
+            None => None,
+        }
+    }
+
+    /// Adds the amounts, saturating at the maximum amount.
+    pub const fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    /// Subtracts the amounts, saturating at zero.
+    pub const fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+/// Formats the amount in NEAR, with as many decimals as needed, e.g. `1.5 NEAR`.
+impl fmt::Display for NearToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fraction = self.0 % Self::ONE_NEAR.0;
+        if fraction == 0 {
+            write!(f, "{} NEAR", self.as_near())
+        } else {
+            let fraction = format!("{:024}", fraction);
+            write!(f, "{}.{} NEAR", self.as_near(), fraction.trim_end_matches('0'))
+        }
+    }
+}
+
+impl Serialize for NearToken {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for NearToken {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse::<Balance>().map(Self).map_err(|err| de::Error::custom(err.to_string()))
+    }
+}
+
+#[cfg(feature = "abi")]
+impl schemars::JsonSchema for NearToken {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        String::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+impl From<Balance> for NearToken {
+    fn from(yoctonear: Balance) -> Self {
+        Self(yoctonear)
+    }
+}
+
+impl From<NearToken> for Balance {
+    fn from(amount: NearToken) -> Self {
+        amount.0
+    }
+}
+
+impl ops::Add for NearToken {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl ops::AddAssign for NearToken {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+    }
+}
+
+impl ops::SubAssign for NearToken {
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0;
+    }
+}
+
+impl ops::Sub for NearToken {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl ops::Mul<u128> for NearToken {
+    type Output = Self;
+
+    fn mul(self, other: u128) -> Self {
+        Self(self.0 * other)
+    }
+}
+
+impl ops::Div<u128> for NearToken {
+    type Output = Self;
+
+    fn div(self, other: u128) -> Self {
+        Self(self.0 / other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_ser() {
+        for val in [u128::MAX, 8, 0] {
+            let ser = serde_json::to_string(&NearToken(val)).unwrap();
+            assert_eq!(ser, format!("\"{}\"", val));
+            let de: NearToken = serde_json::from_str(&ser).unwrap();
+            assert_eq!(de.0, val);
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(NearToken::ZERO.to_string(), "0 NEAR");
+        assert_eq!(NearToken::from_near(3).to_string(), "3 NEAR");
+        assert_eq!((NearToken::ONE_NEAR * 3 / 2).to_string(), "1.5 NEAR");
+        assert_eq!(NearToken::ONE_YOCTO.to_string(), "0.000000000000000000000001 NEAR");
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(NearToken::ONE_NEAR.as_yoctonear(), 10u128.pow(24));
+        assert_eq!((NearToken::from_near(2) + NearToken::ONE_YOCTO).as_near(), 2);
+        assert_eq!(NearToken::ONE_YOCTO.checked_sub(NearToken::ONE_NEAR), None);
+        assert_eq!(NearToken::ONE_YOCTO.saturating_sub(NearToken::ONE_NEAR), NearToken::ZERO);
+        assert_eq!(NearToken(u128::MAX).checked_add(NearToken::ONE_YOCTO), None);
+    }
+}
@@ -0,0 +1,152 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::json_types::Base64VecU8;
+use crate::{env, CurveType, PublicKey};
+
+/// A payload together with an ed25519 signature of its Borsh serialization.
+///
+/// Contracts that accept data signed off-chain, for example votes or rollup transactions, can take
+/// `SignedPayload<T>` as an argument and check it with [`verify`](Self::verify), or check many of
+/// them at once with [`verify_batch`](Self::verify_batch).
+///
+/// # Examples
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshSerialize};
+/// use near_sdk::SignedPayload;
+///
+/// #[derive(BorshSerialize)]
+/// struct Vote {
+///     proposal_id: u64,
+///     approve: bool,
+/// }
+///
+/// fn tally(votes: &[SignedPayload<Vote>]) -> usize {
+///     if let Err(index) = SignedPayload::verify_batch(votes) {
+///         near_sdk::env::panic_str(&format!("Invalid signature of vote {}", index));
+///     }
+///     votes.iter().filter(|vote| vote.payload.approve).count()
+/// }
+///
+/// assert_eq!(tally(&[]), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[cfg_attr(feature = "abi", derive(schemars::JsonSchema))]
+#[serde(crate = "crate::serde")]
+pub struct SignedPayload<T> {
+    pub payload: T,
+    /// The ed25519 public key that signed the payload.
+    pub public_key: PublicKey,
+    /// The 64 byte ed25519 signature of the Borsh serialization of the payload.
+    pub signature: Base64VecU8,
+}
+
+impl<T: BorshSerialize> SignedPayload<T> {
+    /// The message that is signed, which is the Borsh serialization of the payload.
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = Vec::new();
+        self.write_message(&mut message);
+        message
+    }
+
+    /// Returns `true` if the signature of the payload is valid. Signatures by keys that are not
+    /// ed25519 keys or that are not 64 bytes long are invalid.
+    pub fn verify(&self) -> bool {
+        self.verify_with(&mut Vec::new())
+    }
+
+    /// Verifies the signatures of all payloads, returning the index of the first invalid one.
+    ///
+    /// The buffer for the messages is shared by all payloads, so that it is only allocated once.
+    pub fn verify_batch(payloads: &[Self]) -> Result<(), usize> {
+        let mut message = Vec::new();
+        match payloads.iter().position(|payload| !payload.verify_with(&mut message)) {
+            Some(index) => Err(index),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the payload if its signature is valid.
+    pub fn into_verified(self) -> Option<T> {
+        if self.verify() {
+            Some(self.payload)
+        } else {
+            None
+        }
+    }
+
+    fn write_message(&self, buffer: &mut Vec<u8>) {
+        buffer.clear();
+        self.payload
+            .serialize(buffer)
+            .unwrap_or_else(|_| env::panic_str("Failed to serialize the signed payload"));
+    }
+
+    fn verify_with(&self, buffer: &mut Vec<u8>) -> bool {
+        let (signature, public_key) = match (
+            <&[u8; 64]>::try_from(self.signature.0.as_slice()),
+            <&[u8; 32]>::try_from(&self.public_key.as_bytes()[1..]),
+        ) {
+            (Ok(signature), Ok(public_key)) => (signature, public_key),
+            _ => return false,
+        };
+        if self.public_key.curve_type() != CurveType::ED25519 {
+            return false;
+        }
+        self.write_message(buffer);
+        env::ed25519_verify(signature, buffer, public_key)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use near_crypto::{KeyType, SecretKey, Signature};
+
+    fn sign<T: BorshSerialize>(seed: &str, payload: T) -> SignedPayload<T> {
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, seed);
+        let signature = match secret_key.sign(&payload.try_to_vec().unwrap()) {
+            Signature::ED25519(signature) => signature.to_bytes().to_vec(),
+            _ => unreachable!(),
+        };
+        let public_key = secret_key.public_key().to_string().parse().unwrap();
+        SignedPayload { payload, public_key, signature: signature.into() }
+    }
+
+    #[test]
+    fn verify_signed_payloads() {
+        let signed = sign("alice", (7u64, "vote".to_string()));
+        assert!(signed.verify());
+        assert_eq!(signed.message(), (7u64, "vote".to_string()).try_to_vec().unwrap());
+
+        let mut tampered = signed.clone();
+        tampered.payload.0 = 8;
+        assert!(!tampered.verify());
+
+        let mut truncated = signed.clone();
+        truncated.signature.0.pop();
+        assert!(!truncated.verify());
+
+        let mut secp = signed.clone();
+        secp.public_key =
+            format!("secp256k1:{}", bs58::encode([1; 64]).into_string()).parse().unwrap();
+        assert!(!secp.verify());
+
+        assert_eq!(signed.into_verified(), Some((7u64, "vote".to_string())));
+        assert_eq!(tampered.into_verified(), None);
+    }
+
+    #[test]
+    fn verify_batch_reports_first_invalid() {
+        let mut payloads: Vec<_> = (0..5u32).map(|i| sign(&i.to_string(), i)).collect();
+        assert_eq!(SignedPayload::verify_batch(&payloads), Ok(()));
+
+        payloads[3].payload = 30;
+        payloads[4].payload = 40;
+        assert_eq!(SignedPayload::verify_batch(&payloads), Err(3));
+
+        let json = serde_json::to_string(&payloads[0]).unwrap();
+        assert_eq!(serde_json::from_str::<SignedPayload<u32>>(&json).unwrap(), payloads[0]);
+    }
+}
@@ -26,6 +26,13 @@ impl<T> CacheEntry<T> {
         &mut self.value
     }
 
+    /// Returns a mutable reference to the value without marking the entry as modified, for
+    /// changes that do not affect the serialized value.
+    #[allow(dead_code)]
+    pub fn value_mut_unmodified(&mut self) -> &mut Option<T> {
+        &mut self.value
+    }
+
     #[allow(dead_code)]
     pub fn into_value(self) -> Option<T> {
         self.value
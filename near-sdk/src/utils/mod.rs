@@ -2,12 +2,14 @@
 
 pub(crate) mod storage_key_impl;
 
+pub(crate) mod namespace;
+
 mod stable_map;
 pub(crate) use self::stable_map::StableMap;
 mod cache_entry;
 pub(crate) use cache_entry::{CacheEntry, EntryState};
 
-use crate::{env, PromiseResult};
+use crate::{env, AccountId, Promise, PromiseResult};
 
 /// Helper macro to log a message through [`env::log_str`].
 /// This macro can be used similar to the [`std::format`] macro in most cases.
@@ -42,6 +44,113 @@ macro_rules! log {
     };
 }
 
+/// Logs a message with a level through [`env::log_str`], prefixed with the level in upper case,
+/// e.g. `DEBUG: message`. The message is formatted like with the [`std::format`] macro.
+///
+/// The levels are `error`, `warn`, `info`, `debug` and `trace`. `debug` and `trace` messages are
+/// only logged when the `debug-logs` feature of `near-sdk` is enabled. Otherwise they are compiled
+/// out entirely, so that they cost neither code size nor gas, and the arguments are not evaluated.
+///
+/// # Example use
+///
+/// ```no_run
+/// use near_sdk::{near_debug, near_log};
+///
+/// # fn main() {
+/// let balance = 10;
+/// near_log!(info, "Deposited {}", balance);
+/// near_log!(trace, "balance = {}", balance);
+/// near_debug!("Only logged with the `debug-logs` feature: {}", balance);
+/// # }
+/// ```
+///
+/// [`env::log_str`]: crate::env::log_str
+#[macro_export]
+macro_rules! near_log {
+    (error, $($arg:tt)+) => {
+        $crate::__near_log!("ERROR", $($arg)+)
+    };
+    (warn, $($arg:tt)+) => {
+        $crate::__near_log!("WARN", $($arg)+)
+    };
+    (info, $($arg:tt)+) => {
+        $crate::__near_log!("INFO", $($arg)+)
+    };
+    (debug, $($arg:tt)+) => {
+        $crate::__near_debug_log!("DEBUG", $($arg)+)
+    };
+    (trace, $($arg:tt)+) => {
+        $crate::__near_debug_log!("TRACE", $($arg)+)
+    };
+}
+
+/// Logs a debug message, which is compiled out unless the `debug-logs` feature is enabled.
+/// Shorthand for `near_log!(debug, ..)`, see [`near_log!`].
+#[macro_export]
+macro_rules! near_debug {
+    ($($arg:tt)+) => {
+        $crate::near_log!(debug, $($arg)+)
+    };
+}
+
+/// Evaluates the expression and logs the gas it used through [`env::log_str`], as
+/// `label: 123 gas`. Returns the value of the expression.
+///
+/// Nothing is logged if the expression returns from the function or panics.
+///
+/// # Example use
+///
+/// ```no_run
+/// use near_sdk::{env, profile_gas};
+///
+/// # fn main() {
+/// let hash = profile_gas!("hash", {
+///     env::sha256(b"data")
+/// });
+/// # }
+/// ```
+///
+/// [`env::log_str`]: crate::env::log_str
+#[macro_export]
+macro_rules! profile_gas {
+    ($label:expr, $body:expr) => {{
+        let checkpoint = $crate::env::gas_checkpoint();
+        let value = $body;
+        $crate::env::log_str(&format!("{}: {} gas", $label, $crate::env::gas_since(checkpoint).0));
+        value
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __near_log {
+    ($level:literal, $($arg:tt)+) => {
+        $crate::env::log_str(&format!("{}: {}", $level, format_args!($($arg)+)))
+    };
+}
+
+#[cfg(feature = "debug-logs")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __near_debug_log {
+    ($level:literal, $($arg:tt)+) => {
+        $crate::__near_log!($level, $($arg)+)
+    };
+}
+
+#[cfg(not(feature = "debug-logs"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __near_debug_log {
+    // The arguments are still type checked, so that variables only used in debug logs are not
+    // reported as unused, but the branch is removed at compile time.
+    ($level:literal, $($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
 /// Helper macro to create assertions that will panic through the runtime host functions.
 ///
 /// This macro can be used similarly to [`assert!`] but will reduce code size by not including
@@ -105,6 +214,101 @@ pub fn promise_result_as_success() -> Option<Vec<u8>> {
     }
 }
 
+/// Name of the method generated by [`impl_account_exists_resolver!`] which resolves
+/// [`account_exists`].
+pub const ACCOUNT_EXISTS_RESOLVER: &str = "resolve_account_exists";
+
+/// Gas attached to the callback resolving [`account_exists`].
+pub const ACCOUNT_EXISTS_RESOLVER_GAS: crate::Gas = crate::Gas(3_000_000_000_000);
+
+/// Checks whether `account_id` exists, since there is no host function to do it directly. The
+/// returned promise resolves to a JSON `bool`, so it can be returned from a method or read in a
+/// callback with `#[callback_unwrap] exists: bool`.
+///
+/// The check is a transfer of 0 yoctoNEAR, which fails only if the account does not exist and
+/// needs no prepaid gas. Its outcome is turned into the `bool` by the [`ACCOUNT_EXISTS_RESOLVER`]
+/// method of the current contract, with [`ACCOUNT_EXISTS_RESOLVER_GAS`] attached, so the contract
+/// has to define it with [`impl_account_exists_resolver!`]. A callback of the returned promise
+/// needs its own gas on top of that.
+///
+/// Panics if `account_id` is an implicit account, because a transfer creates such an account
+/// instead of failing.
+///
+/// # Example use
+///
+/// ```no_run
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::{near_bindgen, AccountId, Promise};
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {}
+///
+/// #[near_bindgen]
+/// impl Contract {
+///     pub fn check_account(&self, account_id: AccountId) -> Promise {
+///         near_sdk::account_exists(account_id)
+///     }
+/// }
+///
+/// near_sdk::impl_account_exists_resolver!(Contract);
+/// ```
+pub fn account_exists(account_id: AccountId) -> Promise {
+    let is_implicit = account_id.as_str().len() == 64
+        && account_id.as_bytes().iter().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+    require!(!is_implicit, "Cannot probe the existence of an implicit account");
+    Promise::new(account_id).transfer(0).then(
+        Promise::new(env::current_account_id()).function_call(
+            ACCOUNT_EXISTS_RESOLVER.to_string(),
+            Vec::new(),
+            0,
+            ACCOUNT_EXISTS_RESOLVER_GAS,
+        ),
+    )
+}
+
+/// Adds the `#[private]` method `resolve_account_exists` to the contract, which turns the outcome
+/// of [`account_exists`] into a `bool`. Like other methods of the contract, it is part of its ABI.
+///
+/// # Example use
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::test_utils::{accounts, VMContextBuilder};
+/// use near_sdk::{near_bindgen, testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+///
+/// #[near_bindgen]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Contract {}
+///
+/// near_sdk::impl_account_exists_resolver!(Contract);
+///
+/// let context = VMContextBuilder::new()
+///     .current_account_id(accounts(0))
+///     .predecessor_account_id(accounts(0))
+///     .build();
+/// testing_env!(
+///     context,
+///     VMConfig::test(),
+///     RuntimeFeesConfig::test(),
+///     Default::default(),
+///     vec![PromiseResult::Failed],
+/// );
+/// assert!(!Contract::resolve_account_exists());
+/// ```
+#[macro_export]
+macro_rules! impl_account_exists_resolver {
+    ($contract: ident) => {
+        #[$crate::near_bindgen]
+        impl $contract {
+            #[private]
+            pub fn resolve_account_exists() -> bool {
+                $crate::is_promise_success()
+            }
+        }
+    };
+}
+
 /// Deprecated helper function which used to generate code to initialize the [`GlobalAllocator`].
 /// This is now initialized by default. Disable `wee_alloc` feature to configure manually.
 ///
@@ -122,7 +326,9 @@ macro_rules! setup_alloc {
 
 #[cfg(test)]
 mod tests {
-    use crate::test_utils::get_logs;
+    use crate::mock::VmAction;
+    use crate::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use crate::testing_env;
 
     #[test]
     fn test_log_simple() {
@@ -137,4 +343,40 @@ mod tests {
 
         assert_eq!(get_logs(), vec!["hello user_name (25)".to_string()]);
     }
+
+    #[test]
+    fn test_near_log_levels() {
+        let value = 3;
+        near_log!(info, "value = {}", value);
+        near_log!(error, "value = {}", value);
+        near_debug!("debug value = {}", value);
+        near_log!(trace, "trace value = {}", value);
+
+        let mut expected = vec!["INFO: value = 3".to_string(), "ERROR: value = 3".to_string()];
+        if cfg!(feature = "debug-logs") {
+            expected.push("DEBUG: debug value = 3".to_string());
+            expected.push("TRACE: trace value = 3".to_string());
+        }
+        assert_eq!(get_logs(), expected);
+    }
+
+    #[test]
+    fn test_account_exists() {
+        testing_env!(VMContextBuilder::new().current_account_id(accounts(0)).build());
+        super::account_exists(accounts(1));
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, accounts(1));
+        assert_eq!(receipts[0].actions, vec![VmAction::Transfer { deposit: 0 }]);
+        assert_eq!(receipts[1].receiver_id, accounts(0));
+        assert_eq!(
+            receipts[1].actions,
+            vec![VmAction::FunctionCall {
+                function_name: super::ACCOUNT_EXISTS_RESOLVER.to_string(),
+                args: vec![],
+                gas: super::ACCOUNT_EXISTS_RESOLVER_GAS,
+                deposit: 0,
+            }]
+        );
+    }
 }
@@ -0,0 +1,312 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{env, IntoStorageKey};
+
+/// Raw key-value storage of the contract.
+///
+/// Components that only need a few values, like a pause flag or a list of approved accounts, can
+/// claim a prefix with [`Storage::namespace`] and read and write keys under it directly, without
+/// defining a collection or a field of the contract state.
+pub struct Storage;
+
+impl Storage {
+    /// Returns the [`Namespace`] of the keys of storage under `prefix`, to read and write them.
+    ///
+    /// The prefix must not overlap with the prefixes of collections or of other namespaces, as
+    /// their keys would otherwise collide.
+    ///
+    /// # Examples
+    /// ```
+    /// use near_sdk::{env, Storage};
+    ///
+    /// let paused = Storage::namespace(b"paused:".to_vec());
+    /// assert!(!paused.contains(b"ft_transfer"));
+    ///
+    /// paused.write(b"ft_transfer", &[]);
+    /// assert!(paused.contains(b"ft_transfer"));
+    /// assert!(env::storage_has_key(b"paused:ft_transfer"));
+    /// ```
+    pub fn namespace<S>(prefix: S) -> Namespace
+    where
+        S: IntoStorageKey,
+    {
+        Namespace::new(prefix)
+    }
+}
+
+/// A storage prefix that collections, or structs of collections, are created under.
+///
+/// Nested collections need a unique prefix for every instance, like the set of tokens of each
+/// account in a `LookupMap<AccountId, UnorderedSet<TokenId>>`. [`Namespace::nested`] derives
+/// such prefixes from the prefix of the outer collection and the key of the entry, and
+/// [`Namespace::child`] from a name, so they don't have to be built by hand.
+///
+/// Keys are appended in their Borsh serialization, which can't be a prefix of the serialization
+/// of another key of the same type, so the namespaces of different keys never overlap.
+///
+/// # Examples
+/// ```
+/// use near_sdk::borsh::{self, BorshSerialize};
+/// use near_sdk::collections::{LookupMap, UnorderedSet};
+/// use near_sdk::{AccountId, BorshStorageKey, Namespace};
+///
+/// #[derive(BorshSerialize, BorshStorageKey)]
+/// enum StorageKey {
+///     Tokens,
+///     TokensPerOwner,
+/// }
+///
+/// let mut tokens: LookupMap<AccountId, UnorderedSet<String>> = LookupMap::new(StorageKey::Tokens);
+///
+/// let owner: AccountId = "alice.near".parse().unwrap();
+/// let mut owned = tokens.get(&owner).unwrap_or_else(|| {
+///     Namespace::new(StorageKey::TokensPerOwner).nested(&owner).collection()
+/// });
+/// owned.insert(&"token-1".to_string());
+/// tokens.insert(&owner, &owned);
+/// ```
+///
+/// The keys of storage under a namespace can also be read and written directly, see
+/// [`Storage::namespace`]. Unlike collections, nothing is cached: each method reads or writes
+/// storage when it is called.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, BorshSerialize, BorshDeserialize)]
+pub struct Namespace(Vec<u8>);
+
+impl Namespace {
+    /// Namespace with the given prefix.
+    pub fn new<S>(prefix: S) -> Self
+    where
+        S: IntoStorageKey,
+    {
+        Self(prefix.into_storage_key())
+    }
+
+    /// Namespace of the entry of `key`, in a collection that uses this namespace as its prefix.
+    pub fn nested<K>(&self, key: &K) -> Self
+    where
+        K: BorshSerialize + ?Sized,
+    {
+        let mut prefix = self.0.clone();
+        key.serialize(&mut prefix).unwrap_or_else(|_| env::panic_str("Cannot serialize key"));
+        Self(prefix)
+    }
+
+    /// Namespace with `name` appended to this prefix.
+    ///
+    /// Names of the children of a namespace should not be prefixes of each other, since the
+    /// storage keys of one child could then overlap with the keys of another.
+    pub fn child(&self, name: &[u8]) -> Self {
+        Self([&self.0[..], name].concat())
+    }
+
+    /// Creates a collection, or a struct deriving [`StorageNamespace`], in this namespace.
+    pub fn collection<T>(&self) -> T
+    where
+        T: StorageNamespace,
+    {
+        T::new_in(self)
+    }
+
+    /// The prefix of the namespace.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Reads the value stored under `key` in this namespace.
+    pub fn read(&self, key: &[u8]) -> Option<Vec<u8>> {
+        env::storage_read(&self.key(key))
+    }
+
+    /// Writes `value` under `key` in this namespace. Returns `true` if a value was replaced.
+    pub fn write(&self, key: &[u8], value: &[u8]) -> bool {
+        env::storage_write(&self.key(key), value)
+    }
+
+    /// Removes the value stored under `key` in this namespace. Returns `true` if a value was
+    /// removed.
+    pub fn remove(&self, key: &[u8]) -> bool {
+        env::storage_remove(&self.key(key))
+    }
+
+    /// Returns `true` if a value is stored under `key` in this namespace.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        env::storage_has_key(&self.key(key))
+    }
+
+    fn key(&self, key: &[u8]) -> Vec<u8> {
+        [&self.0[..], key].concat()
+    }
+}
+
+impl IntoStorageKey for Namespace {
+    #[inline]
+    fn into_storage_key(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl IntoStorageKey for &Namespace {
+    #[inline]
+    fn into_storage_key(self) -> Vec<u8> {
+        self.0.clone()
+    }
+}
+
+/// Types that can be created under a [`Namespace`], like collections and structs of collections.
+///
+/// The trait can be derived for structs with named fields that all implement it. Each field is
+/// created in the [child](Namespace::child) namespace of its name, which can be changed with
+/// `#[namespace(name = "..")]`, and `#[namespace(skip)]` fields are created with [`Default`].
+/// Names that are prefixes of one another are rejected at compile time.
+///
+/// ```
+/// use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+/// use near_sdk::collections::{LookupMap, Vector};
+/// use near_sdk::{Namespace, StorageNamespace};
+///
+/// #[derive(BorshSerialize, BorshDeserialize, StorageNamespace)]
+/// struct Account {
+///     #[namespace(name = "b")]
+///     balances: LookupMap<String, u128>,
+///     #[namespace(name = "h")]
+///     history: Vector<u64>,
+///     #[namespace(skip)]
+///     nonce: u64,
+/// }
+///
+/// let account: Account = Namespace::new(b"a").nested("alice.near").collection();
+/// assert_eq!(account.history.len(), 0);
+/// ```
+pub trait StorageNamespace {
+    /// Creates an empty instance that stores its values under `namespace`.
+    fn new_in(namespace: &Namespace) -> Self;
+}
+
+macro_rules! impl_storage_namespace {
+    ($ty:ident<$($param:ident),*> $(where $($bound:tt)*)?) => {
+        impl<$($param),*> StorageNamespace for crate::collections::$ty<$($param),*>
+        $(where $($bound)*)?
+        {
+            fn new_in(namespace: &Namespace) -> Self {
+                Self::new(namespace)
+            }
+        }
+    };
+}
+
+impl_storage_namespace!(Vector<T>);
+impl_storage_namespace!(LookupMap<K, V>);
+impl_storage_namespace!(LookupSet<T>);
+impl_storage_namespace!(UnorderedMap<K, V>);
+impl_storage_namespace!(UnorderedSet<T>);
+impl_storage_namespace!(TreeMap<K, V> where
+    K: Ord + Clone + BorshSerialize + borsh::BorshDeserialize,
+    V: BorshSerialize + borsh::BorshDeserialize);
+
+#[cfg(feature = "unstable")]
+mod store_impls {
+    use borsh::BorshSerialize;
+
+    use super::{Namespace, StorageNamespace};
+    use crate::store::key::ToKey;
+    use crate::store::{LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector};
+
+    impl<T> StorageNamespace for Vector<T>
+    where
+        T: BorshSerialize,
+    {
+        fn new_in(namespace: &Namespace) -> Self {
+            Self::new(namespace)
+        }
+    }
+
+    impl<K, V, H> StorageNamespace for LookupMap<K, V, H>
+    where
+        K: BorshSerialize + Ord,
+        V: BorshSerialize,
+        H: ToKey,
+    {
+        fn new_in(namespace: &Namespace) -> Self {
+            Self::with_hasher(namespace)
+        }
+    }
+
+    impl<T, H> StorageNamespace for LookupSet<T, H>
+    where
+        T: BorshSerialize + Ord,
+        H: ToKey,
+    {
+        fn new_in(namespace: &Namespace) -> Self {
+            Self::with_hasher(namespace)
+        }
+    }
+
+    impl<K, V, H> StorageNamespace for UnorderedMap<K, V, H>
+    where
+        K: BorshSerialize + Ord,
+        V: BorshSerialize,
+        H: ToKey,
+    {
+        fn new_in(namespace: &Namespace) -> Self {
+            Self::with_hasher(namespace)
+        }
+    }
+
+    impl<T, H> StorageNamespace for UnorderedSet<T, H>
+    where
+        T: BorshSerialize + Ord,
+        H: ToKey,
+    {
+        fn new_in(namespace: &Namespace) -> Self {
+            Self::with_hasher(namespace)
+        }
+    }
+
+    impl<K, V, H> StorageNamespace for TreeMap<K, V, H>
+    where
+        K: BorshSerialize + Ord,
+        V: BorshSerialize,
+        H: ToKey,
+    {
+        fn new_in(namespace: &Namespace) -> Self {
+            Self::with_hasher(namespace)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_prefixes() {
+        let root = Namespace::new(b"t");
+        assert_eq!(root.nested("ab").as_bytes(), b"t\x02\0\0\0ab");
+        assert_eq!(root.nested(&7u8).child(b"x").as_bytes(), b"t\x07x");
+        assert_ne!(root.nested("a").child(b"b"), root.nested("ab"));
+
+        let vector: crate::collections::Vector<u8> = root.child(b"v").collection();
+        assert_eq!(vector.len(), 0);
+    }
+
+    #[test]
+    fn raw_storage() {
+        let ns = Storage::namespace(b"ns".to_vec());
+        assert_eq!(ns.read(b"a"), None);
+        assert!(!ns.write(b"a", b"1"));
+        assert!(ns.write(b"a", b"2"));
+        assert!(ns.contains(b"a"));
+        assert_eq!(env::storage_read(b"nsa"), Some(b"2".to_vec()));
+
+        // Children share the keys of their parent, under their name.
+        let child = ns.child(b"c");
+        child.write(b"d", b"3");
+        assert_eq!(ns.read(b"cd"), Some(b"3".to_vec()));
+        assert!(!child.contains(b"a"));
+
+        assert!(ns.remove(b"a"));
+        assert!(!ns.remove(b"a"));
+        assert!(!ns.contains(b"a"));
+        assert_eq!(child.read(b"d"), Some(b"3".to_vec()));
+    }
+}
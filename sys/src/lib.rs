@@ -44,6 +44,14 @@ extern "C" {
         malleability_flag: u64,
         register_id: u64,
     ) -> u64;
+    pub fn ed25519_verify(
+        sig_len: u64,
+        sig_ptr: u64,
+        msg_len: u64,
+        msg_ptr: u64,
+        pub_key_len: u64,
+        pub_key_ptr: u64,
+    ) -> u64;
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -172,6 +180,18 @@ extern "C" {
     pub fn alt_bn128_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
     pub fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64);
     pub fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    // #############
+    // # BLS12-381 #
+    // #############
+    pub fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    pub fn bls12381_p1_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+    pub fn bls12381_p2_decompress(value_len: u64, value_ptr: u64, register_id: u64) -> u64;
 }
 
 /// Alias for [`block_index`] function. Returns the height of the current block.